@@ -0,0 +1,264 @@
+//! Python module `nexus_parser` exposing the parsers of the `nexus_parser` crate via PyO3.
+//!
+//! - `parse_nexus_file(path)`/`parse_newick(text)`: Parse trees into a list of `Tree`s and their `LeafLabelMap`
+//! - `robinson_foulds(tree, other)`: Robinson-Foulds distance of two trees on the same taxa
+//! - `Tree`: Parsed tree with Newick export, traversals and vertex accessors
+//! - `LeafLabelMap`: Taxa shared by the trees of a file
+//!
+//! Build e.g. with `maturin develop` in `bindings/python`.
+//!
+//! # Example (Python)
+//! ```python
+//! import nexus_parser
+//!
+//! trees, taxa = nexus_parser.parse_nexus_file("sample.trees")
+//! print(len(trees), "trees on", len(taxa), "taxa")
+//! for v in trees[0].postorder():
+//!     print(v, trees[0].taxon(v), trees[0].branch_length(v))
+//! print(trees[0].newick(), trees[0].rf_distance(trees[1]))
+//! ```
+
+use nexus_parser::algorithms::robinson_foulds as rf;
+use nexus_parser::io::parser::newick;
+use nexus_parser::io::writer::newick::to_newick;
+use nexus_parser::model::leaf_label_map::LeafLabelMap;
+use nexus_parser::model::tree::{Tree, TreeIndex};
+use nexus_parser::parse_nexus_file as parse_nexus;
+use pyo3::exceptions::{PyIndexError, PyValueError};
+use pyo3::prelude::*;
+use std::sync::Arc;
+
+// =#========================================================================#=
+// MODULE
+// =#========================================================================#=
+/// Fast parser for NEXUS and Newick tree files.
+#[pymodule]
+#[pyo3(name = "nexus_parser")]
+fn python_module(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_function(wrap_pyfunction!(parse_nexus_file, module)?)?;
+    module.add_function(wrap_pyfunction!(parse_newick, module)?)?;
+    module.add_function(wrap_pyfunction!(robinson_foulds, module)?)?;
+    module.add_class::<PyTree>()?;
+    module.add_class::<PyLeafLabelMap>()?;
+    Ok(())
+}
+
+/// Parses all trees of a NEXUS file (possibly compressed), see [parse_nexus_file](nexus_parser::parse_nexus_file).
+///
+/// # Errors
+/// Raises `ValueError` if the file cannot be read or parsed.
+#[pyfunction]
+pub fn parse_nexus_file(path: &str) -> PyResult<(Vec<PyTree>, PyLeafLabelMap)> {
+    let (trees, labels) = parse_nexus(path).map_err(|e| PyValueError::new_err(e.to_string()))?;
+    Ok(to_python(trees, labels))
+}
+
+/// Parses all Newick trees of a string (one tree per `;`), see [newick::parse_str].
+///
+/// # Errors
+/// Raises `ValueError` if a tree is invalid.
+#[pyfunction]
+pub fn parse_newick(text: &str) -> PyResult<(Vec<PyTree>, PyLeafLabelMap)> {
+    let (trees, labels) = newick::parse_str(text).map_err(|e| PyValueError::new_err(e.to_string()))?;
+    Ok(to_python(trees, labels))
+}
+
+/// Returns the Robinson-Foulds distance of two trees, see [PyTree::rf_distance].
+///
+/// # Errors
+/// Raises `ValueError` if the trees do not have the same taxa.
+#[pyfunction]
+pub fn robinson_foulds(tree: &PyTree, other: &PyTree) -> PyResult<usize> {
+    tree.rf_distance(other)
+}
+
+// =#========================================================================#=
+// TREE
+// =#========================================================================#=
+/// Parsed tree (`Tree` in Python) sharing its taxa with the other trees of its file.
+///
+/// Vertices are referred to by their index, from `0` to `num_vertices - 1`.
+#[pyclass(name = "Tree", frozen)]
+pub struct PyTree {
+    tree: Tree,
+    labels: Arc<LeafLabelMap>,
+}
+
+#[pymethods]
+impl PyTree {
+    /// Returns the name of the tree (as in its NEXUS TREE command), or `None` if unnamed.
+    #[getter]
+    pub fn name(&self) -> Option<String> {
+        self.tree.name().cloned()
+    }
+
+    /// Returns whether the root of the tree is meaningful.
+    #[getter]
+    pub fn is_rooted(&self) -> bool {
+        self.tree.is_rooted()
+    }
+
+    /// Returns the index of the root vertex.
+    #[getter]
+    pub fn root(&self) -> TreeIndex {
+        self.tree.root().index()
+    }
+
+    /// Returns the number of vertices.
+    #[getter]
+    pub fn num_vertices(&self) -> usize {
+        self.tree.num_vertices()
+    }
+
+    /// Returns the number of leaves.
+    #[getter]
+    pub fn num_leaves(&self) -> usize {
+        self.tree.num_leaves()
+    }
+
+    /// Returns the height of the tree (length of the longest path from the root to a leaf).
+    #[getter]
+    pub fn height(&self) -> f64 {
+        self.tree.height()
+    }
+
+    /// Returns the Newick string of the tree with taxon labels.
+    pub fn newick(&self) -> String {
+        to_newick(&self.tree, &self.labels)
+    }
+
+    /// Returns the vertices in pre-order (parents before children).
+    pub fn preorder(&self) -> Vec<TreeIndex> {
+        self.tree.pre_order_iter().map(|vertex| vertex.index()).collect()
+    }
+
+    /// Returns the vertices in post-order (children before parents).
+    pub fn postorder(&self) -> Vec<TreeIndex> {
+        self.tree.post_order_iter().map(|vertex| vertex.index()).collect()
+    }
+
+    /// Returns the parent of a vertex, or `None` for the root.
+    ///
+    /// # Errors
+    /// Raises `IndexError` if there is no such vertex.
+    pub fn parent(&self, index: TreeIndex) -> PyResult<Option<TreeIndex>> {
+        self.check_index(index)?;
+        Ok(self.tree[index].parent_index())
+    }
+
+    /// Returns the children of a vertex (empty for leaves).
+    ///
+    /// # Errors
+    /// Raises `IndexError` if there is no such vertex.
+    pub fn children(&self, index: TreeIndex) -> PyResult<Vec<TreeIndex>> {
+        self.check_index(index)?;
+        Ok(self.tree[index].children().unwrap_or(&[]).to_vec())
+    }
+
+    /// Returns the length of the branch above a vertex, or `None` if it has none.
+    ///
+    /// # Errors
+    /// Raises `IndexError` if there is no such vertex.
+    pub fn branch_length(&self, index: TreeIndex) -> PyResult<Option<f64>> {
+        self.check_index(index)?;
+        Ok(self.tree[index].branch_length().map(|length| *length))
+    }
+
+    /// Returns the taxon label of a leaf, or `None` for internal vertices.
+    ///
+    /// # Errors
+    /// Raises `IndexError` if there is no such vertex.
+    pub fn taxon(&self, index: TreeIndex) -> PyResult<Option<String>> {
+        self.check_index(index)?;
+        Ok(self.tree[index].label_index()
+            .and_then(|taxon| self.labels.get_label(taxon))
+            .map(str::to_string))
+    }
+
+    /// Returns the taxon labels of the leaves, in pre-order.
+    pub fn leaves(&self) -> Vec<String> {
+        self.tree.pre_order_iter()
+            .filter_map(|vertex| vertex.label_index())
+            .filter_map(|taxon| self.labels.get_label(taxon))
+            .map(str::to_string)
+            .collect()
+    }
+
+    /// Returns the Robinson-Foulds distance to another tree, i.e. the number of splits in only one of them.
+    ///
+    /// # Errors
+    /// Raises `ValueError` if the trees do not have the same taxa.
+    pub fn rf_distance(&self, other: &PyTree) -> PyResult<usize> {
+        if !Arc::ptr_eq(&self.labels, &other.labels) && self.labels.labels() != other.labels.labels() {
+            return Err(PyValueError::new_err("Trees do not have the same taxa (in the same order)"));
+        }
+        Ok(rf::robinson_foulds(&self.tree, &other.tree))
+    }
+
+    fn __repr__(&self) -> String {
+        match self.tree.name() {
+            Some(name) => format!("Tree('{}', {} leaves)", name, self.tree.num_leaves()),
+            None => format!("Tree({} leaves)", self.tree.num_leaves()),
+        }
+    }
+}
+
+impl PyTree {
+    /// Returns the wrapped tree.
+    pub fn tree(&self) -> &Tree {
+        &self.tree
+    }
+
+    fn check_index(&self, index: TreeIndex) -> PyResult<()> {
+        if index < self.tree.num_vertices() {
+            Ok(())
+        } else {
+            Err(PyIndexError::new_err(format!("Vertex {} not in tree of {} vertices", index, self.tree.num_vertices())))
+        }
+    }
+}
+
+// =#========================================================================#=
+// LEAF LABEL MAP
+// =#========================================================================#=
+/// Taxa shared by the trees of a file (`LeafLabelMap` in Python).
+#[pyclass(name = "LeafLabelMap", frozen)]
+pub struct PyLeafLabelMap {
+    labels: Arc<LeafLabelMap>,
+}
+
+#[pymethods]
+impl PyLeafLabelMap {
+    /// Returns the labels in order of their indices.
+    pub fn labels(&self) -> Vec<String> {
+        self.labels.labels().clone()
+    }
+
+    /// Returns the index of a label, or `None` if not present.
+    pub fn index(&self, label: &str) -> Option<usize> {
+        self.labels.get_index(label)
+    }
+
+    /// Returns the label of an index, or `None` if out of range.
+    pub fn label(&self, index: usize) -> Option<String> {
+        self.labels.get_label(index).map(str::to_string)
+    }
+
+    fn __len__(&self) -> usize {
+        self.labels.num_labels()
+    }
+
+    fn __contains__(&self, label: &str) -> bool {
+        self.labels.contains_label(label)
+    }
+}
+
+// ============================================================================
+// Little Helpers
+// ============================================================================
+/// Wraps parsed trees and their labels, sharing the labels among the trees.
+fn to_python(trees: Vec<Tree>, labels: LeafLabelMap) -> (Vec<PyTree>, PyLeafLabelMap) {
+    let labels = Arc::new(labels);
+    let trees = trees.into_iter().map(|tree| PyTree { tree, labels: Arc::clone(&labels) }).collect();
+    (trees, PyLeafLabelMap { labels })
+}
@@ -0,0 +1,38 @@
+use nexus_parser_py::{parse_newick, parse_nexus_file, robinson_foulds};
+use std::path::Path;
+
+#[test]
+fn test_parse_nexus_file_to_trees_and_labels() {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("../../tests/fixtures").join("nexus_t2_n4_translate.trees");
+    let (trees, labels) = parse_nexus_file(path.to_str().unwrap()).unwrap();
+    assert_eq!(trees.len(), 2);
+    assert_eq!(labels.labels().len(), 4);
+
+    let tree = &trees[0];
+    assert_eq!(tree.num_leaves(), 4);
+    assert_eq!(tree.parent(tree.root()).unwrap(), None);
+    assert_eq!(tree.preorder()[0], tree.root());
+    assert_eq!(*tree.postorder().last().unwrap(), tree.root());
+    assert!(tree.newick().ends_with(';'));
+    assert_eq!(robinson_foulds(tree, tree).unwrap(), 0);
+}
+
+#[test]
+fn test_tree_accessors() {
+    let (trees, labels) = parse_newick("((A:1,B:2):0.5,C:3);((A:1,C:2):0.5,B:3);").unwrap();
+    let tree = &trees[0];
+
+    let a = (0..tree.num_vertices()).find(|&v| tree.taxon(v).unwrap().as_deref() == Some("A")).unwrap();
+    let parent = tree.parent(a).unwrap().unwrap();
+    assert_eq!(tree.branch_length(a).unwrap(), Some(1.0));
+    assert_eq!(tree.branch_length(parent).unwrap(), Some(0.5));
+    assert_eq!(tree.taxon(parent).unwrap(), None);
+    assert_eq!(tree.children(parent).unwrap().len(), 2);
+    assert!(tree.children(a).unwrap().is_empty());
+    assert_eq!(tree.leaves().len(), 3);
+
+    assert_eq!(labels.index("C"), Some(labels.labels().iter().position(|l| l == "C").unwrap()));
+    assert_eq!(labels.label(labels.index("B").unwrap()).as_deref(), Some("B"));
+    assert_eq!(labels.index("D"), None);
+    assert_eq!(tree.rf_distance(&trees[1]).unwrap(), robinson_foulds(&trees[1], tree).unwrap());
+}
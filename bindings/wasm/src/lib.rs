@@ -0,0 +1,172 @@
+//! WebAssembly bindings exposing the parsers of `nexus_parser` to JavaScript via `wasm-bindgen`.
+//!
+//! - `parseNexus(bytes)`/`parseNewick(text)`: Parse trees into a `TreeSample`
+//! - `TreeSample`: Parsed trees with their shared taxa, also as Newick strings
+//! - `Tree`: Vertex arrays of a tree (parents, branch lengths, taxa), ready for drawing
+//!
+//! Build e.g. with `wasm-pack build --target web bindings/wasm`.
+//!
+//! # Example (JavaScript)
+//! ```js
+//! import init, { parseNexus } from "./pkg/nexus_parser_wasm.js";
+//!
+//! await init();
+//! const sample = parseNexus(new Uint8Array(await file.arrayBuffer()));
+//! const tree = sample.tree(0);
+//! for (let v = 0; v < tree.numVertices; v++) {
+//!     const taxon = tree.taxa[v] >= 0 ? sample.taxa[tree.taxa[v]] : null;
+//!     console.log(v, tree.parents[v], tree.branchLengths[v], taxon);
+//! }
+//! ```
+
+use nexus_parser::io::parser::newick;
+use nexus_parser::io::writer::newick::to_newick;
+use nexus_parser::model::leaf_label_map::LeafLabelMap;
+use nexus_parser::model::tree::Tree;
+use nexus_parser::parse_nexus_bytes;
+use wasm_bindgen::prelude::*;
+
+/// Entry of vertex arrays for "none", e.g. the parent of the root or the taxon of an internal vertex
+const NONE: i32 = -1;
+
+// =#========================================================================#=
+// PARSING
+// =#========================================================================#=
+/// Parses all trees of NEXUS content, see [parse_nexus_bytes].
+///
+/// # Errors
+/// Throws an error with the message of the [ParsingError](nexus_parser::io::parser::parsing_error::ParsingError)
+/// if the content cannot be parsed.
+#[wasm_bindgen(js_name = parseNexus)]
+pub fn parse_nexus(bytes: &[u8]) -> Result<JsTreeSample, JsError> {
+    let (trees, labels) = parse_nexus_bytes(bytes)?;
+    Ok(JsTreeSample { trees, labels })
+}
+
+/// Parses all Newick trees of a string (one tree per `;`), see [newick::parse_str].
+///
+/// # Errors
+/// Throws an error with the message of the [ParsingError](nexus_parser::io::parser::parsing_error::ParsingError)
+/// if a tree is invalid.
+#[wasm_bindgen(js_name = parseNewick)]
+pub fn parse_newick(text: &str) -> Result<JsTreeSample, JsError> {
+    let (trees, labels) = newick::parse_str(text)?;
+    Ok(JsTreeSample { trees, labels })
+}
+
+// =#========================================================================#=
+// TREE SAMPLE
+// =#========================================================================#=
+/// Parsed trees and their shared taxa (`TreeSample` in JavaScript).
+#[wasm_bindgen(js_name = TreeSample)]
+pub struct JsTreeSample {
+    trees: Vec<Tree>,
+    labels: LeafLabelMap,
+}
+
+#[wasm_bindgen(js_class = TreeSample)]
+impl JsTreeSample {
+    /// Returns the number of trees.
+    #[wasm_bindgen(getter, js_name = numTrees)]
+    pub fn num_trees(&self) -> usize {
+        self.trees.len()
+    }
+
+    /// Returns the labels of the taxa, indexed by the entries of [JsTree::taxa].
+    #[wasm_bindgen(getter)]
+    pub fn taxa(&self) -> Vec<String> {
+        self.labels.labels().clone()
+    }
+
+    /// Returns the `index`-th tree, or `undefined` if out of range.
+    pub fn tree(&self, index: usize) -> Option<JsTree> {
+        self.trees.get(index).map(JsTree::new)
+    }
+
+    /// Returns the `index`-th tree as Newick string with taxon labels, or `undefined` if out of range.
+    pub fn newick(&self, index: usize) -> Option<String> {
+        self.trees.get(index).map(|tree| to_newick(tree, &self.labels))
+    }
+}
+
+// =#========================================================================#=
+// TREE
+// =#========================================================================#=
+/// Tree as arrays over its vertices (`Tree` in JavaScript), indexed like the vertices of a [Tree].
+#[wasm_bindgen(js_name = Tree)]
+pub struct JsTree {
+    name: Option<String>,
+    rooted: bool,
+    root: usize,
+    parents: Vec<i32>,
+    branch_lengths: Vec<f64>,
+    taxa: Vec<i32>,
+    node_labels: Vec<String>,
+}
+
+impl JsTree {
+    /// Converts a tree into its vertex arrays.
+    fn new(tree: &Tree) -> Self {
+        let vertices = (0..tree.num_vertices()).map(|index| &tree[index]);
+        Self {
+            name: tree.name().cloned(),
+            rooted: tree.is_rooted(),
+            root: tree.root().index(),
+            parents: vertices.clone().map(|v| v.parent_index().map_or(NONE, |parent| parent as i32)).collect(),
+            branch_lengths: vertices.clone().map(|v| v.branch_length().map_or(f64::NAN, |length| *length)).collect(),
+            taxa: vertices.clone().map(|v| v.label_index().map_or(NONE, |taxon| taxon as i32)).collect(),
+            node_labels: vertices.map(|v| v.node_label().unwrap_or_default().to_string()).collect(),
+        }
+    }
+}
+
+#[wasm_bindgen(js_class = Tree)]
+impl JsTree {
+    /// Returns the name of the tree (as in its NEXUS TREE command), or `undefined` if unnamed.
+    #[wasm_bindgen(getter)]
+    pub fn name(&self) -> Option<String> {
+        self.name.clone()
+    }
+
+    /// Returns whether the root of the tree is meaningful.
+    #[wasm_bindgen(getter)]
+    pub fn rooted(&self) -> bool {
+        self.rooted
+    }
+
+    /// Returns the index of the root vertex.
+    #[wasm_bindgen(getter)]
+    pub fn root(&self) -> usize {
+        self.root
+    }
+
+    /// Returns the number of vertices, i.e. the length of all vertex arrays.
+    #[wasm_bindgen(getter, js_name = numVertices)]
+    pub fn num_vertices(&self) -> usize {
+        self.parents.len()
+    }
+
+    /// Returns the parent of each vertex (`-1` for the root).
+    #[wasm_bindgen(getter)]
+    pub fn parents(&self) -> Vec<i32> {
+        self.parents.clone()
+    }
+
+    /// Returns the length of the branch above each vertex (`NaN` if none).
+    #[wasm_bindgen(getter, js_name = branchLengths)]
+    pub fn branch_lengths(&self) -> Vec<f64> {
+        self.branch_lengths.clone()
+    }
+
+    /// Returns the taxon of each vertex as index into [JsTreeSample::taxa] (`-1` for internal vertices).
+    #[wasm_bindgen(getter)]
+    pub fn taxa(&self) -> Vec<i32> {
+        self.taxa.clone()
+    }
+
+    /// Returns the node label of each vertex, e.g. a support value (empty if none).
+    #[wasm_bindgen(getter, js_name = nodeLabels)]
+    pub fn node_labels(&self) -> Vec<String> {
+        self.node_labels.clone()
+    }
+}
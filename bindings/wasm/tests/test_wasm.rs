@@ -0,0 +1,33 @@
+use nexus_parser_wasm::{parse_newick, parse_nexus};
+use std::path::Path;
+
+#[test]
+fn test_parse_nexus_to_vertex_arrays() {
+    let bytes = std::fs::read(Path::new(env!("CARGO_MANIFEST_DIR")).join("../../tests/fixtures").join("nexus_t2_n4_translate.trees")).unwrap();
+    let sample = parse_nexus(&bytes).unwrap();
+    assert_eq!(sample.num_trees(), 2);
+    assert_eq!(sample.taxa().len(), 4);
+    assert!(sample.tree(2).is_none());
+
+    let tree = sample.tree(0).unwrap();
+    assert_eq!(tree.num_vertices(), 7);
+    assert_eq!(tree.parents()[tree.root()], -1);
+    assert_eq!(tree.taxa().iter().filter(|&&taxon| taxon >= 0).count(), 4);
+    assert_eq!(tree.branch_lengths().len(), tree.num_vertices());
+    assert!(sample.newick(0).unwrap().ends_with(';'));
+}
+
+#[test]
+fn test_parse_newick_to_vertex_arrays() {
+    let sample = parse_newick("((A:1,B:2)90:0.5,C:3);").unwrap();
+    let tree = sample.tree(0).unwrap();
+    let taxa = sample.taxa();
+
+    let a = tree.taxa().iter().position(|&taxon| taxon >= 0 && taxa[taxon as usize] == "A").unwrap();
+    let parent = tree.parents()[a] as usize;
+    assert_eq!(tree.branch_lengths()[a], 1.0);
+    assert_eq!(tree.branch_lengths()[parent], 0.5);
+    assert_eq!(tree.node_labels()[parent], "90");
+    assert!(tree.branch_lengths()[tree.root()].is_nan());
+    assert_eq!(sample.newick(0).unwrap(), "((A:1,B:2)90:0.5,C:3);");
+}
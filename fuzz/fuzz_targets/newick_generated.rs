@@ -0,0 +1,23 @@
+//! Fuzzes the Newick parser with valid Newick strings generated from the fuzzer's input as seed:
+//! they must parse and survive writing and parsing again.
+//!
+//! Run with `cargo fuzz run newick_generated` (from the crate root).
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use nexus_parser::io::parser::newick;
+use nexus_parser::testing::{check_round_trip, NewickGenerator};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+fuzz_target!(|seed: [u8; 32]| {
+    let generator = NewickGenerator::new().with_max_leaves(50).with_max_children(5).with_comments(0.2);
+    let newick = generator.generate(&mut StdRng::from_seed(seed));
+    if let Err(e) = newick::parse_str(&newick) {
+        panic!("Generated Newick cannot be parsed: {}\n{}", e, newick);
+    }
+    if let Err(e) = check_round_trip(&newick) {
+        panic!("{}", e);
+    }
+});
@@ -0,0 +1,17 @@
+//! Fuzzes the Newick parser with arbitrary input: parsing must not panic,
+//! and parsed trees must survive writing and parsing again.
+//!
+//! Run with `cargo fuzz run newick_round_trip` (from the crate root).
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use nexus_parser::testing::check_round_trip;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(newicks) = std::str::from_utf8(data) {
+        if let Err(e) = check_round_trip(newicks) {
+            panic!("{}", e);
+        }
+    }
+});
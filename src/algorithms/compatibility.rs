@@ -0,0 +1,98 @@
+//! Compatibility module for filtering tree samples by a backbone (constraint) tree.
+//!
+//! - `splits_compatible`: Whether two splits can be present in the same tree
+//! - `Tree::is_compatible_with`: Whether all splits of a tree are compatible with those of a backbone
+//! - `filter_compatible`: Trees of a sample compatible with a backbone and the posterior mass retained
+
+use crate::model::taxon_set::TaxonSet;
+use crate::model::tree::Tree;
+use std::collections::HashSet;
+
+// ============================================================================
+// Split Compatibility (pub)
+// ============================================================================
+/// Returns whether two splits, as returned by [Tree::bipartitions], are compatible,
+/// i.e. whether some tree contains both.
+///
+/// Since both splits are represented by the side not containing taxon `0`,
+/// they are compatible if and only if they are disjoint or one contains the other.
+pub fn splits_compatible(split: &TaxonSet, other: &TaxonSet) -> bool {
+    split.is_disjoint(other) || split.is_subset_of(other) || other.is_subset_of(split)
+}
+
+// ============================================================================
+// Tree Compatibility (pub)
+// ============================================================================
+impl Tree {
+    /// Checks whether this tree is compatible with the backbone,
+    /// i.e. whether each split of the backbone is compatible with every split of this tree.
+    ///
+    /// A backbone is typically a partially resolved (multifurcating) tree with the splits of interest.
+    /// For a binary tree, compatibility means that it contains all splits of the backbone.
+    /// Rooting is ignored.
+    ///
+    /// # Panics
+    /// Panics if the trees do not have the same number of leaves (over the same [LeafLabelMap](crate::model::leaf_label_map::LeafLabelMap)).
+    pub fn is_compatible_with(&self, backbone: &Tree) -> bool {
+        assert_eq!(self.num_leaves_init(), backbone.num_leaves_init(), "Trees must have the same number of leaves");
+        compatible_with_splits(&self.bipartitions(), &backbone.bipartitions())
+    }
+}
+
+/// Returns the trees of the sample compatible with the backbone (see [Tree::is_compatible_with]),
+/// together with the posterior mass they retain, i.e. their fraction of the sample.
+///
+/// Useful for constrained re-analysis (e.g. keep only trees with a certain clade)
+/// or to estimate the posterior probability of a hypothesis given by the backbone.
+///
+/// # Arguments
+/// * `trees` - Tree sample (e.g. after burnin), all trees equally weighted
+/// * `backbone` - Backbone tree over the same leaf label map, typically multifurcating
+///
+/// # Returns
+/// The compatible trees in order of the sample and their fraction (`0.0` for an empty sample).
+///
+/// # Panics
+/// Panics if a tree does not have the same number of leaves as the backbone.
+///
+/// # Example
+/// ```
+/// use nexus_parser::algorithms::compatibility::filter_compatible;
+/// use nexus_parser::io::parser::byte_parser::ByteParser;
+/// use nexus_parser::io::parser::newick::NewickParser;
+///
+/// let mut newick_parser = NewickParser::new().with_num_leaves(5);
+/// let mut parse = |newick: &str| newick_parser.parse(&mut ByteParser::from_str(newick)).unwrap();
+/// let trees = vec![parse("(((A,B),C),(D,E));"), parse("(((A,C),B),(D,E));")];
+/// let backbone = parse("((A,B),C,D,E);");
+///
+/// let (compatible, posterior_mass) = filter_compatible(&trees, &backbone);
+/// assert_eq!(compatible.len(), 1);
+/// assert_eq!(posterior_mass, 0.5);
+/// ```
+pub fn filter_compatible<'a>(trees: &'a [Tree], backbone: &Tree) -> (Vec<&'a Tree>, f64) {
+    let backbone_splits = backbone.bipartitions();
+
+    let compatible: Vec<&Tree> = trees.iter()
+        .filter(|tree| {
+            assert_eq!(tree.num_leaves_init(), backbone.num_leaves_init(), "Trees must have the same number of leaves");
+            compatible_with_splits(&tree.bipartitions(), &backbone_splits)
+        })
+        .collect();
+
+    let posterior_mass = if trees.is_empty() {
+        0.0
+    } else {
+        compatible.len() as f64 / trees.len() as f64
+    };
+
+    (compatible, posterior_mass)
+}
+
+pub(crate) fn compatible_with_splits(splits: &HashSet<TaxonSet>, backbone_splits: &HashSet<TaxonSet>) -> bool {
+    backbone_splits.iter().all(|backbone_split| {
+        // Fast path: contained splits are compatible
+        splits.contains(backbone_split)
+            || splits.iter().all(|split| splits_compatible(split, backbone_split))
+    })
+}
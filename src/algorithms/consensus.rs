@@ -0,0 +1,141 @@
+//! Consensus module for summarizing a tree sample by a single tree.
+//!
+//! - `clade_frequencies`: Counts the (non-trivial) clades across a sample
+//! - `majority_rule`: Consensus tree of all clades with frequency above a threshold
+//! - `strict`: Consensus tree of all clades present in every tree
+//!
+//! Consensus trees are rooted like the sample (clades are the bipartitions of the rooted trees)
+//! and multifurcating where the sample does not resolve a clade. The frequency of each clade
+//! is stored as node label of its vertex, see [Vertex::support](crate::model::vertex::Vertex::support).
+
+use crate::model::taxon_set::TaxonSet;
+use crate::model::tree::{Tree, TreeIndex};
+use std::collections::{HashMap, HashSet};
+
+// =#========================================================================#=
+// CLADE FREQUENCIES
+// =#========================================================================#=
+/// Returns for each non-trivial clade (at least two but not all taxa) the number of trees containing it.
+///
+/// # Panics
+/// Panics if the trees do not all have the same number of leaves.
+pub fn clade_frequencies(trees: &[Tree]) -> HashMap<TaxonSet, usize> {
+    let mut counts = HashMap::new();
+    let Some(first) = trees.first() else {
+        return counts;
+    };
+    let num_taxa = first.num_leaves_init();
+
+    for tree in trees {
+        assert_eq!(tree.num_leaves_init(), num_taxa, "Trees must have the same number of leaves");
+        let mut clades = tree.clades(num_taxa);
+        // A clade appears only once per tree, except through vertices with a single child
+        clades.sort_unstable();
+        clades.dedup();
+        for clade in clades {
+            if clade.len() > 1 && clade.len() < num_taxa {
+                *counts.entry(clade).or_insert(0) += 1;
+            }
+        }
+    }
+
+    counts
+}
+
+// =#========================================================================#=
+// CONSENSUS TREES
+// =#========================================================================#=
+/// Returns the majority-rule consensus tree of the sample,
+/// containing all clades present in more than `threshold` of the trees.
+///
+/// With the default threshold `0.5`, this is the classic majority-rule consensus tree;
+/// higher thresholds give less resolved trees. Every internal vertex has its clade frequency
+/// (in `(threshold, 1]`) as node label; the root has none. Vertices have no branch lengths.
+///
+/// # Arguments
+/// * `trees` - Tree sample over the same leaf label map (e.g. after burnin)
+/// * `threshold` - Minimum frequency (exclusive) of clades to include, in `[0.5, 1)`
+///
+/// # Panics
+/// Panics if `trees` is empty, the trees do not have the same number of leaves,
+/// or `threshold` is not in `[0.5, 1)` (lower thresholds could select incompatible clades).
+///
+/// # Example
+/// ```
+/// use nexus_parser::algorithms::consensus::majority_rule;
+/// use nexus_parser::io::parser::byte_parser::ByteParser;
+/// use nexus_parser::io::parser::newick::NewickParser;
+/// use nexus_parser::io::writer::newick::NewickStyle;
+///
+/// let mut newick_parser = NewickParser::new().with_num_leaves(4);
+/// let mut parse = |newick: &str| newick_parser.parse(&mut ByteParser::from_str(newick)).unwrap();
+/// let trees = vec![parse("(((A,B),C),D);"), parse("(((A,B),D),C);"), parse("((A,(B,C)),D);")];
+///
+/// let consensus = majority_rule(&trees, 0.5);
+/// assert_eq!(consensus.to_newick(&NewickStyle::ZeroIndexed, None), "(((0,1)0.6666666666666666,2)0.6666666666666666,3);");
+/// ```
+pub fn majority_rule(trees: &[Tree], threshold: f64) -> Tree {
+    assert!((0.5..1.0).contains(&threshold), "Threshold {} not within [0.5, 1)", threshold);
+    consensus(trees, |count, num_trees| count as f64 > threshold * num_trees as f64)
+}
+
+/// Returns the strict consensus tree of the sample, containing all clades present in every tree.
+///
+/// Every internal vertex has frequency `1` as node label; the root has none.
+///
+/// # Panics
+/// Panics if `trees` is empty or the trees do not have the same number of leaves.
+pub fn strict(trees: &[Tree]) -> Tree {
+    consensus(trees, |count, num_trees| count == num_trees)
+}
+
+/// Builds the consensus tree of all clades whose count is accepted by `include`.
+///
+/// The included clades must be pairwise compatible (nested or disjoint).
+fn consensus(trees: &[Tree], include: impl Fn(usize, usize) -> bool) -> Tree {
+    assert!(!trees.is_empty(), "Consensus requires at least one tree");
+    consensus_from_counts(clade_frequencies(trees), trees[0].num_leaves_init(), trees.len(), include)
+}
+
+/// Builds the consensus tree of all clades (or splits, as clades of a tree rooted at taxon `0`)
+/// with counts among `num_trees` trees accepted by `include`.
+pub(crate) fn consensus_from_counts(counts: HashMap<TaxonSet, usize>, num_taxa: usize, num_trees: usize,
+                                    include: impl Fn(usize, usize) -> bool) -> Tree {
+    let mut clades: Vec<(TaxonSet, usize)> = counts.into_iter()
+        .filter(|&(_, count)| include(count, num_trees))
+        .collect();
+    // Build bottom-up: smaller clades first (deterministic order for equal sizes)
+    clades.sort_unstable_by(|(a, _), (b, _)| a.len().cmp(&b.len()).then_with(|| a.cmp(b)));
+
+    let mut tree = Tree::new(num_taxa);
+    // Topmost vertex built so far containing each taxon
+    let mut top: Vec<TreeIndex> = (0..num_taxa)
+        .map(|label_index| tree.add_leaf(None, label_index))
+        .collect();
+
+    for (clade, count) in clades {
+        let children = distinct_tops(&top, clade.iter());
+        let index = tree.add_internal_vertex(children, None);
+        tree[index].set_node_label(Some((count as f64 / num_trees as f64).to_string()));
+        for label_index in clade.iter() {
+            top[label_index] = index;
+        }
+    }
+
+    let children = distinct_tops(&top, 0..num_taxa);
+    tree.add_root(children);
+
+    tree
+}
+
+/// Returns the distinct topmost vertices of the given taxa, in order of first appearance.
+fn distinct_tops(top: &[TreeIndex], taxa: impl Iterator<Item = usize>) -> Vec<TreeIndex> {
+    let mut children: Vec<TreeIndex> = Vec::new();
+    let mut seen = HashSet::new();
+    for label_index in taxa {
+        if seen.insert(top[label_index]) {
+            children.push(top[label_index]);
+        }
+    }
+    children
+}
@@ -0,0 +1,136 @@
+//! Distance matrix module for all pairwise distances of a tree sample.
+//!
+//! - `distance_matrix`: Symmetric matrix of a distance (e.g. Robinson-Foulds) between all pairs of trees
+//! - `distance_matrix_checkpointed`: Same, saving progress to a [Checkpoint] and resuming from it
+
+#[cfg(feature = "fs")]
+use crate::io::checkpoint::Checkpoint;
+use crate::model::tree::Tree;
+#[cfg(feature = "fs")]
+use std::fmt::Write;
+#[cfg(feature = "fs")]
+use std::io;
+
+/// First word of the header line of distance matrix checkpoints
+#[cfg(feature = "fs")]
+const CHECKPOINT_HEADER: &str = "distance_matrix";
+
+// =#========================================================================#=
+// DISTANCE MATRIX
+// =#========================================================================#=
+/// Returns the symmetric matrix of distances between all pairs of trees, with zero diagonal.
+///
+/// # Arguments
+/// * `trees` - Tree sample
+/// * `distance` - Symmetric distance of two trees, evaluated once per pair
+///
+/// # Example
+/// ```
+/// use nexus_parser::algorithms::distance_matrix::distance_matrix;
+/// use nexus_parser::algorithms::robinson_foulds::robinson_foulds;
+/// use nexus_parser::io::parser::byte_parser::ByteParser;
+/// use nexus_parser::io::parser::newick::NewickParser;
+///
+/// let mut newick_parser = NewickParser::new().with_num_leaves(5);
+/// let mut parse = |newick: &str| newick_parser.parse(&mut ByteParser::from_str(newick)).unwrap();
+/// let trees = vec![parse("(((A,B),C),(D,E));"), parse("(((A,C),B),(D,E));")];
+///
+/// let matrix = distance_matrix(&trees, |a, b| robinson_foulds(a, b) as f64);
+/// assert_eq!(matrix, vec![vec![0.0, 2.0], vec![2.0, 0.0]]);
+/// ```
+pub fn distance_matrix(trees: &[Tree], distance: impl Fn(&Tree, &Tree) -> f64) -> Vec<Vec<f64>> {
+    let rows: Vec<Vec<f64>> = (0..trees.len())
+        .map(|i| upper_row(trees, i, &distance))
+        .collect();
+    to_symmetric(rows)
+}
+
+/// Returns the symmetric matrix of distances between all pairs of trees (see [distance_matrix]),
+/// saving the computed rows to the checkpoint whenever it is due.
+///
+/// If the checkpoint file exists (e.g. from a crashed run), the rows saved there are reused
+/// and only the remaining ones computed. Once complete, the checkpoint file is removed.
+///
+/// # Errors
+/// Returns an error if the checkpoint cannot be read or written,
+/// or it belongs to a different tree sample (see [Checkpoint::fingerprint]) or is corrupt.
+#[cfg(feature = "fs")]
+pub fn distance_matrix_checkpointed(trees: &[Tree], distance: impl Fn(&Tree, &Tree) -> f64,
+                                    checkpoint: &mut Checkpoint) -> io::Result<Vec<Vec<f64>>> {
+    let header = format!("{} {} {:016x}", CHECKPOINT_HEADER, trees.len(), Checkpoint::fingerprint(trees));
+
+    let mut rows = match checkpoint.load()? {
+        Some(content) => parse_checkpoint(&content, &header, trees.len())?,
+        None => Vec::new(),
+    };
+
+    for i in rows.len()..trees.len() {
+        rows.push(upper_row(trees, i, &distance));
+        checkpoint.save_if_due(|| format_checkpoint(&header, &rows))?;
+    }
+
+    checkpoint.remove()?;
+    Ok(to_symmetric(rows))
+}
+
+// ============================================================================
+// Little Helpers
+// ============================================================================
+/// Distances of tree `i` to all trees `j > i`.
+fn upper_row(trees: &[Tree], i: usize, distance: &impl Fn(&Tree, &Tree) -> f64) -> Vec<f64> {
+    trees[i + 1..].iter()
+        .map(|other| distance(&trees[i], other))
+        .collect()
+}
+
+/// Turns the upper rows into the full symmetric matrix.
+fn to_symmetric(rows: Vec<Vec<f64>>) -> Vec<Vec<f64>> {
+    let n = rows.len();
+    let mut matrix = vec![vec![0.0; n]; n];
+    for (i, row) in rows.into_iter().enumerate() {
+        for (offset, value) in row.into_iter().enumerate() {
+            let j = i + 1 + offset;
+            matrix[i][j] = value;
+            matrix[j][i] = value;
+        }
+    }
+    matrix
+}
+
+/// Header line, then one line of tab-separated distances per completed row.
+#[cfg(feature = "fs")]
+fn format_checkpoint(header: &str, rows: &[Vec<f64>]) -> String {
+    let mut content = format!("{}\n", header);
+    for row in rows {
+        let values: Vec<String> = row.iter().map(f64::to_string).collect();
+        let _ = writeln!(content, "{}", values.join("\t"));
+    }
+    content
+}
+
+#[cfg(feature = "fs")]
+fn parse_checkpoint(content: &str, header: &str, num_trees: usize) -> io::Result<Vec<Vec<f64>>> {
+    let invalid = |msg: String| io::Error::new(io::ErrorKind::InvalidData, msg);
+
+    let mut lines = content.lines();
+    if lines.next() != Some(header) {
+        return Err(invalid("Checkpoint belongs to a different tree sample or analysis".to_string()));
+    }
+
+    let mut rows = Vec::new();
+    for (i, line) in lines.enumerate() {
+        let row = if line.is_empty() {
+            Vec::new()
+        } else {
+            line.split('\t')
+                .map(|value| value.parse::<f64>())
+                .collect::<Result<Vec<f64>, _>>()
+                .map_err(|e| invalid(format!("Invalid distance in row {} of checkpoint - {}", i, e)))?
+        };
+        if i >= num_trees || row.len() != num_trees - 1 - i {
+            return Err(invalid(format!("Row {} of checkpoint has unexpected length", i)));
+        }
+        rows.push(row);
+    }
+    Ok(rows)
+}
@@ -0,0 +1,364 @@
+//! Embedding module for turning trees into fixed-length feature vectors,
+//! e.g. to cluster tree samples or reduce their dimension with ML libraries.
+//!
+//! - `FeatureScheme`: Kendall-Colijn vectors, split indicators over a dictionary, or pairwise leaf distances
+//! - `SplitDictionary`: Fixed, ordered set of splits used for split indicator vectors
+//! - `Tree::to_feature_vector`: Feature vector of a single tree
+//! - `feature_matrix`, `write_feature_tsv` (and `feature_array` with feature `ndarray`,
+//!   `write_feature_parquet` with feature `parquet`): Batch export
+//!
+//! All vectors of a scheme have the same length for trees over the same [LeafLabelMap],
+//! with entries in the order given by `FeatureScheme::feature_names`.
+
+use crate::model::leaf_label_map::LeafLabelMap;
+use crate::model::taxon_set::TaxonSet;
+use crate::model::tree::{Tree, TreeIndex};
+use std::collections::HashMap;
+use std::io::{self, Write};
+
+// =#========================================================================#=
+// FEATURE SCHEME
+// =#========================================================================#=
+/// How a tree is turned into a feature vector.
+///
+/// Pairs of taxa `(i, j)` with `i < j` (by label index) are ordered row by row:
+/// `(0,1), (0,2), ..., (0,n-1), (1,2), ...`.
+#[derive(Debug, Clone, Copy)]
+pub enum FeatureScheme<'a> {
+    /// Kendall-Colijn vector `(1 - lambda) m + lambda M` of a rooted tree:
+    /// for each pair of taxa, the depth of their most recent common ancestor
+    /// in edges (`m`) and in branch length (`M`), followed for each taxon
+    /// by `1` (`m`) and its pendant branch length (`M`).
+    /// `lambda` in `[0, 1]` weighs topology (`0`) against branch lengths (`1`).
+    KendallColijn { lambda: f64 },
+    /// For each split of the dictionary, `1.0` if the tree contains it and `0.0` otherwise.
+    SplitIndicators(&'a SplitDictionary),
+    /// For each pair of taxa, the length of the path between their leaves,
+    /// summing branch lengths or, if `topological`, counting edges.
+    PairwiseDistances { topological: bool },
+}
+
+impl FeatureScheme<'_> {
+    /// Returns the length of the feature vectors for trees with `num_taxa` leaves.
+    pub fn num_features(&self, num_taxa: usize) -> usize {
+        match self {
+            FeatureScheme::KendallColijn { .. } => num_pairs(num_taxa) + num_taxa,
+            FeatureScheme::SplitIndicators(dictionary) => dictionary.len(),
+            FeatureScheme::PairwiseDistances { .. } => num_pairs(num_taxa),
+        }
+    }
+
+    /// Returns a name for each feature, e.g. as column headers.
+    ///
+    /// Pairs are named `A|B`, taxa by their label, and splits by their taxa joined with `+`.
+    pub fn feature_names(&self, label_map: &LeafLabelMap) -> Vec<String> {
+        let num_taxa = label_map.num_labels();
+        let label = |index: usize| label_map.get_label(index).unwrap_or("?").to_string();
+
+        let pair_names = || (0..num_taxa)
+            .flat_map(move |i| (i + 1..num_taxa).map(move |j| (i, j)))
+            .map(|(i, j)| format!("{}|{}", label(i), label(j)));
+
+        match self {
+            FeatureScheme::KendallColijn { .. } => pair_names()
+                .chain((0..num_taxa).map(label))
+                .collect(),
+            FeatureScheme::SplitIndicators(dictionary) => dictionary.splits().iter()
+                .map(|split| split.iter().map(label).collect::<Vec<_>>().join("+"))
+                .collect(),
+            FeatureScheme::PairwiseDistances { .. } => pair_names().collect(),
+        }
+    }
+}
+
+// =#========================================================================#=
+// SPLIT DICTIONARY
+// =#========================================================================#=
+/// Ordered set of splits (as returned by [Tree::bipartitions]) defining the entries
+/// of split indicator vectors, so that vectors of different trees are comparable.
+#[derive(Debug, Clone, Default)]
+pub struct SplitDictionary {
+    splits: Vec<TaxonSet>,
+    indices: HashMap<TaxonSet, usize>,
+}
+
+impl SplitDictionary {
+    /// Creates a dictionary of the given splits in the given order (duplicates are dropped).
+    pub fn new(splits: impl IntoIterator<Item = TaxonSet>) -> Self {
+        let mut dictionary = Self::default();
+        for split in splits {
+            if !dictionary.indices.contains_key(&split) {
+                dictionary.indices.insert(split.clone(), dictionary.splits.len());
+                dictionary.splits.push(split);
+            }
+        }
+        dictionary
+    }
+
+    /// Creates a dictionary of all splits occurring in the trees,
+    /// ordered by decreasing frequency (ties in a fixed, deterministic order).
+    pub fn from_trees(trees: &[Tree]) -> Self {
+        let mut counts: HashMap<TaxonSet, usize> = HashMap::new();
+        for tree in trees {
+            for split in tree.bipartitions() {
+                *counts.entry(split).or_insert(0) += 1;
+            }
+        }
+
+        let mut splits: Vec<(TaxonSet, usize)> = counts.into_iter().collect();
+        splits.sort_unstable_by(|(a, count_a), (b, count_b)| count_b.cmp(count_a).then_with(|| a.cmp(b)));
+        Self::new(splits.into_iter().map(|(split, _)| split))
+    }
+
+    /// Returns the splits in order.
+    pub fn splits(&self) -> &[TaxonSet] {
+        &self.splits
+    }
+
+    /// Returns the position of the split, or `None` if it is not in the dictionary.
+    pub fn index_of(&self, split: &TaxonSet) -> Option<usize> {
+        self.indices.get(split).copied()
+    }
+
+    /// Returns the number of splits.
+    pub fn len(&self) -> usize {
+        self.splits.len()
+    }
+
+    /// Returns whether the dictionary has no splits.
+    pub fn is_empty(&self) -> bool {
+        self.splits.is_empty()
+    }
+}
+
+// ============================================================================
+// Feature Vectors (pub)
+// ============================================================================
+impl Tree {
+    /// Returns the feature vector of this tree under the given scheme,
+    /// of length [FeatureScheme::num_features] for the [number of leaves](Tree::num_leaves_init).
+    ///
+    /// Missing branch lengths count as `0`. Entries involving taxa not in the tree are `NaN`.
+    ///
+    /// # Panics
+    /// Panics if `lambda` of a Kendall-Colijn scheme is not in `[0, 1]`.
+    ///
+    /// # Example
+    /// ```
+    /// use nexus_parser::algorithms::embedding::FeatureScheme;
+    /// use nexus_parser::io::parser::byte_parser::ByteParser;
+    /// use nexus_parser::io::parser::newick::NewickParser;
+    ///
+    /// let mut newick_parser = NewickParser::new().with_num_leaves(3);
+    /// let tree = newick_parser.parse(&mut ByteParser::from_str("((A:1,B:1):2,C:3);")).unwrap();
+    ///
+    /// // Pairs AB, AC, BC
+    /// let distances = tree.to_feature_vector(&FeatureScheme::PairwiseDistances { topological: false });
+    /// assert_eq!(distances, vec![2.0, 6.0, 6.0]);
+    ///
+    /// // MRCA depths of AB, AC, BC, then pendant edges of A, B, C
+    /// let kc = tree.to_feature_vector(&FeatureScheme::KendallColijn { lambda: 0.0 });
+    /// assert_eq!(kc, vec![1.0, 0.0, 0.0, 1.0, 1.0, 1.0]);
+    /// ```
+    pub fn to_feature_vector(&self, scheme: &FeatureScheme) -> Vec<f64> {
+        let num_taxa = self.num_leaves_init();
+        match *scheme {
+            FeatureScheme::KendallColijn { lambda } => {
+                assert!((0.0..=1.0).contains(&lambda), "Lambda {} not within [0, 1]", lambda);
+                let (depths, distances) = self.root_depths();
+                let mut vector: Vec<f64> = self.pair_mrcas().into_iter()
+                    .map(|mrca| match mrca {
+                        Some(mrca) => (1.0 - lambda) * depths[mrca] as f64 + lambda * distances[mrca],
+                        None => f64::NAN,
+                    })
+                    .collect();
+
+                let mut pendants = vec![f64::NAN; num_taxa];
+                for (index, leaf) in self.leaves() {
+                    let length = self[index].branch_length().map_or(0.0, |bl| *bl);
+                    pendants[leaf] = (1.0 - lambda) + lambda * length;
+                }
+                vector.extend(pendants);
+                vector
+            }
+            FeatureScheme::SplitIndicators(dictionary) => {
+                let mut vector = vec![0.0; dictionary.len()];
+                for split in self.bipartitions() {
+                    if let Some(index) = dictionary.index_of(&split) {
+                        vector[index] = 1.0;
+                    }
+                }
+                vector
+            }
+            FeatureScheme::PairwiseDistances { topological } => {
+                let (depths, distances) = self.root_depths();
+                let leaf_vertices = self.leaf_vertices();
+                let depth = |index: TreeIndex| if topological { depths[index] as f64 } else { distances[index] };
+
+                let mrcas = self.pair_mrcas();
+                let mut vector = Vec::with_capacity(mrcas.len());
+                for i in 0..num_taxa {
+                    for j in i + 1..num_taxa {
+                        let mrca = mrcas[pair_index(num_taxa, i, j)];
+                        vector.push(match (leaf_vertices[i], leaf_vertices[j], mrca) {
+                            (Some(a), Some(b), Some(mrca)) => depth(a) + depth(b) - 2.0 * depth(mrca),
+                            _ => f64::NAN,
+                        });
+                    }
+                }
+                vector
+            }
+        }
+    }
+
+    /// Returns the tree index and label index of each leaf.
+    fn leaves(&self) -> impl Iterator<Item = (TreeIndex, usize)> + '_ {
+        self.pre_order_iter()
+            .filter_map(|vertex| vertex.label_index().map(|leaf| (vertex.index(), leaf)))
+    }
+
+    /// Returns for each label index the tree index of its leaf, if present.
+    fn leaf_vertices(&self) -> Vec<Option<TreeIndex>> {
+        let mut leaf_vertices = vec![None; self.num_leaves_init()];
+        for (index, leaf) in self.leaves() {
+            leaf_vertices[leaf] = Some(index);
+        }
+        leaf_vertices
+    }
+
+    /// Returns for each vertex its depth below the root in edges and in branch length.
+    fn root_depths(&self) -> (Vec<usize>, Vec<f64>) {
+        let mut depths = vec![0; self.num_vertices()];
+        let mut distances = vec![0.0; self.num_vertices()];
+        for vertex in self.pre_order_iter() {
+            // Parents are visited before their children (pre-order)
+            if let Some(parent) = vertex.parent_index() {
+                depths[vertex.index()] = depths[parent] + 1;
+                distances[vertex.index()] = distances[parent] + vertex.branch_length().map_or(0.0, |bl| *bl);
+            }
+        }
+        (depths, distances)
+    }
+
+    /// Returns for each pair of taxa (see [FeatureScheme]) the tree index of their most recent common ancestor.
+    fn pair_mrcas(&self) -> Vec<Option<TreeIndex>> {
+        let num_taxa = self.num_leaves_init();
+        let mut mrcas = vec![None; num_pairs(num_taxa)];
+        // Taxa below each vertex, moved up to the parent once it is visited
+        let mut below: Vec<Vec<usize>> = vec![Vec::new(); self.num_vertices()];
+
+        for vertex in self.post_order_iter() {
+            let index = vertex.index();
+            if let Some(leaf) = vertex.label_index() {
+                below[index].push(leaf);
+            } else if let Some(children) = vertex.children() {
+                let mut taxa: Vec<usize> = Vec::new();
+                for &child in children {
+                    let child_taxa = std::mem::take(&mut below[child]);
+                    // Taxa of different children meet first at this vertex
+                    for &a in &taxa {
+                        for &b in &child_taxa {
+                            mrcas[pair_index(num_taxa, a.min(b), a.max(b))] = Some(index);
+                        }
+                    }
+                    taxa.extend(child_taxa);
+                }
+                below[index] = taxa;
+            }
+        }
+
+        mrcas
+    }
+}
+
+// ============================================================================
+// Batch Export (pub)
+// ============================================================================
+/// Returns the feature vectors of the trees as rows of a matrix.
+pub fn feature_matrix(trees: &[Tree], scheme: &FeatureScheme) -> Vec<Vec<f64>> {
+    trees.iter()
+        .map(|tree| tree.to_feature_vector(scheme))
+        .collect()
+}
+
+/// Returns the feature vectors of the trees as rows of an [ndarray::Array2],
+/// e.g. to pass on to clustering or PCA.
+///
+/// # Panics
+/// Panics if the trees do not have the same number of leaves.
+#[cfg(feature = "ndarray")]
+pub fn feature_array(trees: &[Tree], scheme: &FeatureScheme) -> ndarray::Array2<f64> {
+    let num_features = trees.first().map_or(0, |tree| scheme.num_features(tree.num_leaves_init()));
+    let mut array = ndarray::Array2::zeros((trees.len(), num_features));
+    for (mut row, tree) in array.rows_mut().into_iter().zip(trees) {
+        assert_eq!(scheme.num_features(tree.num_leaves_init()), num_features, "Trees must have the same number of leaves");
+        row.assign(&ndarray::ArrayView1::from(&tree.to_feature_vector(scheme)));
+    }
+    array
+}
+
+/// Writes the feature vectors of the trees as tab-separated table,
+/// with a header row of feature names (see [FeatureScheme::feature_names])
+/// and one row per tree, starting with its name (or its position in `trees`).
+///
+/// # Errors
+/// Returns an error if writing fails.
+pub fn write_feature_tsv<W: Write>(trees: &[Tree], scheme: &FeatureScheme, label_map: &LeafLabelMap, mut writer: W) -> io::Result<()> {
+    writeln!(writer, "tree\t{}", scheme.feature_names(label_map).join("\t"))?;
+    for (position, tree) in trees.iter().enumerate() {
+        let name = tree.name().cloned().unwrap_or_else(|| position.to_string());
+        let values: Vec<String> = tree.to_feature_vector(scheme).iter().map(f64::to_string).collect();
+        writeln!(writer, "{}\t{}", name, values.join("\t"))?;
+    }
+    writer.flush()
+}
+
+/// Writes the feature vectors of the trees as Parquet file (e.g. for pandas or polars), with the same columns
+/// as [write_feature_tsv]: a string column `tree` with the tree names (or positions in `trees`)
+/// and a `double` column per feature (see [FeatureScheme::feature_names]).
+///
+/// # Errors
+/// Returns an error if writing fails.
+///
+/// # Panics
+/// Panics if the trees do not have the same number of leaves.
+#[cfg(feature = "parquet")]
+pub fn write_feature_parquet<W: Write + Send>(trees: &[Tree], scheme: &FeatureScheme, label_map: &LeafLabelMap,
+                                             writer: W) -> parquet::errors::Result<()> {
+    use arrow_array::{ArrayRef, Float64Array, RecordBatch, StringArray};
+    use arrow_schema::{DataType, Field, Schema};
+    use std::sync::Arc;
+
+    let feature_names = scheme.feature_names(label_map);
+    let vectors: Vec<Vec<f64>> = trees.iter()
+        .map(|tree| {
+            assert_eq!(scheme.num_features(tree.num_leaves_init()), feature_names.len(), "Trees must have the same number of leaves");
+            tree.to_feature_vector(scheme)
+        })
+        .collect();
+
+    let mut fields = vec![Field::new("tree", DataType::Utf8, false)];
+    fields.extend(feature_names.iter().map(|name| Field::new(name, DataType::Float64, false)));
+    let names = trees.iter().enumerate().map(|(position, tree)| tree.name().cloned().unwrap_or_else(|| position.to_string()));
+    let mut columns: Vec<ArrayRef> = vec![Arc::new(StringArray::from_iter_values(names))];
+    columns.extend((0..feature_names.len())
+        .map(|feature| Arc::new(Float64Array::from_iter_values(vectors.iter().map(|vector| vector[feature]))) as ArrayRef));
+
+    let batch = RecordBatch::try_new(Arc::new(Schema::new(fields)), columns)?;
+    let mut writer = parquet::arrow::ArrowWriter::try_new(writer, batch.schema(), None)?;
+    writer.write(&batch)?;
+    writer.close()?;
+    Ok(())
+}
+
+// ============================================================================
+// Little Helpers
+// ============================================================================
+fn num_pairs(num_taxa: usize) -> usize {
+    num_taxa * num_taxa.saturating_sub(1) / 2
+}
+
+/// Position of pair `(i, j)` with `i < j` in row-by-row order.
+fn pair_index(num_taxa: usize, i: usize, j: usize) -> usize {
+    i * num_taxa - i * (i + 1) / 2 + (j - i - 1)
+}
@@ -0,0 +1,282 @@
+//! Likelihood module for the probability of a nucleotide alignment on a tree with branch lengths.
+//!
+//! - `SubstitutionModel`: Time-reversible nucleotide substitution model (JC69 or GTR) with its transition probabilities
+//! - `log_likelihood`: Log-likelihood of an alignment on a tree by Felsenstein's pruning algorithm
+//!
+//! Identical sites are collapsed into weighted patterns first. Partial likelihoods of all vertices
+//! are stored in one contiguous buffer indexed by vertex (pattern by pattern, state by state) and filled
+//! in post-order over the vertex arena; they are rescaled per pattern at every internal vertex to avoid underflow.
+
+use crate::model::alignment::{nucleotide_bases, Alignment};
+use crate::model::tree::Tree;
+use std::collections::HashMap;
+
+/// Number of nucleotide states (A, C, G, T)
+const NUM_STATES: usize = 4;
+
+/// Maximal number of Jacobi sweeps of the eigendecomposition (converging after a few for 4x4 matrices)
+const MAX_JACOBI_SWEEPS: usize = 50;
+
+// =#========================================================================#=
+// SUBSTITUTION MODEL
+// =#========================================================================#=
+/// Time-reversible nucleotide substitution model, normalized to one expected substitution per site
+/// and unit of branch length, with states ordered `A, C, G, T`.
+///
+/// # Example
+/// ```
+/// use nexus_parser::algorithms::likelihood::SubstitutionModel;
+///
+/// let jc69 = SubstitutionModel::jc69();
+/// let p = jc69.transition_matrix(0.3);
+/// assert!((p[0][0] - (0.25 + 0.75 * (-0.4f64).exp())).abs() < 1e-12);
+///
+/// // Transitions (A <-> G, C <-> T) four times as frequent as transversions
+/// let gtr = SubstitutionModel::gtr([1.0, 4.0, 1.0, 1.0, 4.0, 1.0], [0.3, 0.2, 0.2, 0.3]);
+/// let p = gtr.transition_matrix(0.1);
+/// assert!(p[0][2] > p[0][1]);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct SubstitutionModel {
+    /// Stationary frequencies of the states
+    frequencies: [f64; NUM_STATES],
+    /// Eigenvalues of the rate matrix
+    eigenvalues: [f64; NUM_STATES],
+    /// Eigenvectors (columns) of the symmetrized rate matrix `D^1/2 Q D^-1/2` with `D = diag(frequencies)`
+    eigenvectors: [[f64; NUM_STATES]; NUM_STATES],
+}
+
+impl SubstitutionModel {
+    /// Creates the Jukes-Cantor model (JC69): equal rates and equal frequencies.
+    pub fn jc69() -> Self {
+        Self::gtr([1.0; 6], [0.25; NUM_STATES])
+    }
+
+    /// Creates the general time-reversible model (GTR).
+    ///
+    /// # Arguments
+    /// * `rates` - Relative exchange rates `A-C, A-G, A-T, C-G, C-T, G-T` (only their ratios matter)
+    /// * `frequencies` - Stationary frequencies of `A, C, G, T`, summing to 1
+    ///
+    /// # Panics
+    /// Panics if a rate is not positive and finite, or the frequencies are not positive or do not sum to 1.
+    pub fn gtr(rates: [f64; 6], frequencies: [f64; NUM_STATES]) -> Self {
+        assert!(rates.iter().all(|&rate| rate > 0.0 && rate.is_finite()), "Rates must be positive and finite, got {:?}", rates);
+        assert!(frequencies.iter().all(|&frequency| frequency > 0.0) && (frequencies.iter().sum::<f64>() - 1.0).abs() < 1e-6,
+                "Frequencies must be positive and sum to 1, got {:?}", frequencies);
+
+        // Symmetrized rate matrix: S_ij = r_ij sqrt(pi_i pi_j), S_ii = Q_ii
+        let pairs = [(0, 1), (0, 2), (0, 3), (1, 2), (1, 3), (2, 3)];
+        let mut symmetric = [[0.0; NUM_STATES]; NUM_STATES];
+        for (&(i, j), &rate) in pairs.iter().zip(&rates) {
+            symmetric[i][j] = rate * (frequencies[i] * frequencies[j]).sqrt();
+            symmetric[j][i] = symmetric[i][j];
+            symmetric[i][i] -= rate * frequencies[j];
+            symmetric[j][j] -= rate * frequencies[i];
+        }
+
+        // Normalize to one expected substitution per unit of time
+        let mean_rate: f64 = -(0..NUM_STATES).map(|i| frequencies[i] * symmetric[i][i]).sum::<f64>();
+        for row in symmetric.iter_mut() {
+            for entry in row.iter_mut() {
+                *entry /= mean_rate;
+            }
+        }
+
+        let (eigenvalues, eigenvectors) = jacobi_eigen(symmetric);
+        Self { frequencies, eigenvalues, eigenvectors }
+    }
+
+    /// Returns the stationary frequencies of `A, C, G, T`.
+    pub fn frequencies(&self) -> [f64; NUM_STATES] {
+        self.frequencies
+    }
+
+    /// Returns the matrix of probabilities `P[i][j]` of state `i` becoming state `j` along a branch of the given length.
+    ///
+    /// # Panics
+    /// Panics if the length is negative or not finite.
+    pub fn transition_matrix(&self, length: f64) -> [[f64; NUM_STATES]; NUM_STATES] {
+        assert!(length >= 0.0 && length.is_finite(), "Branch length must be non-negative and finite, got {}", length);
+        let exps = self.eigenvalues.map(|eigenvalue| (eigenvalue * length).exp());
+        let mut matrix = [[0.0; NUM_STATES]; NUM_STATES];
+        for (i, row) in matrix.iter_mut().enumerate() {
+            for (j, entry) in row.iter_mut().enumerate() {
+                let symmetric: f64 = (0..NUM_STATES).map(|k| self.eigenvectors[i][k] * exps[k] * self.eigenvectors[j][k]).sum();
+                // Rounding may give tiny negative probabilities for short branches
+                *entry = (symmetric * (self.frequencies[j] / self.frequencies[i]).sqrt()).max(0.0);
+            }
+        }
+        matrix
+    }
+}
+
+// =#========================================================================#=
+// LOG-LIKELIHOOD
+// =#========================================================================#=
+/// Returns the log-likelihood of the nucleotide alignment on the tree under the substitution model,
+/// computed by Felsenstein's pruning algorithm with the root at the stationary distribution.
+///
+/// Leaves are matched to sequences by their label index (see [Alignment::sequence]);
+/// gaps, missing data and leaves without sequence are treated as unknown states, ambiguity codes
+/// (e.g. IUPAC `R`) as any of their states. Since the models are time-reversible, the result does
+/// not depend on the position of the root (e.g. of unrooted trees with a trifurcation at the root).
+///
+/// # Panics
+/// Panics if the alignment is not of nucleotides (DNA or RNA), or a non-root vertex has no branch length.
+///
+/// # Example
+/// ```
+/// use nexus_parser::algorithms::likelihood::{log_likelihood, SubstitutionModel};
+/// use nexus_parser::io::parser::fasta;
+/// use nexus_parser::model::alignment::DataType;
+/// use nexus_parser::model::vertex::StoredLength;
+/// use nexus_parser::parse_newick_str;
+///
+/// let (trees, mut labels) = parse_newick_str("(A:0.1,B:0.2);").unwrap();
+/// let alignment = fasta::parse_str(">A\nA\n>B\nA\n", DataType::Dna, &mut labels).unwrap();
+///
+/// // Both leaves are 0.3 apart: 1/4 * P_AA(0.3)
+/// let expected = (0.25 * (0.25 + 0.75 * (-0.4f64).exp())).ln();
+/// // Within the precision branch lengths are stored at (`f32` with the `f32-lengths` feature)
+/// let tolerance = 1e-12_f64.max(StoredLength::EPSILON as f64);
+/// assert!((log_likelihood(&trees[0], &alignment, &SubstitutionModel::jc69()) - expected).abs() < tolerance);
+/// ```
+pub fn log_likelihood(tree: &Tree, alignment: &Alignment, model: &SubstitutionModel) -> f64 {
+    assert!(alignment.data_type().is_nucleotide(), "Likelihood requires nucleotides, got {}", alignment.data_type());
+    let (patterns, weights) = site_patterns(alignment);
+    let num_patterns = weights.len();
+    let width = num_patterns * NUM_STATES;
+
+    // Partial likelihoods of each vertex, and log scale factors of each pattern
+    let mut partials = vec![1.0; tree.num_vertices() * width];
+    let mut log_scales = vec![0.0; num_patterns];
+    let mut partial = vec![0.0; width];
+    for vertex in tree.post_order_iter() {
+        let index = vertex.index();
+        match vertex.children() {
+            None | Some([]) => {
+                let pattern_states = vertex.label_index().and_then(|label_index| patterns.get(&label_index));
+                leaf_partial(pattern_states, &mut partial);
+            }
+            Some(children) => {
+                partial.fill(1.0);
+                for &child in children {
+                    let length = *tree[child].branch_length()
+                        .unwrap_or_else(|| panic!("Vertex {} has no branch length", child));
+                    let matrix = model.transition_matrix(length);
+                    let child_partial = &partials[child * width..(child + 1) * width];
+                    for (pattern, child_pattern) in partial.chunks_exact_mut(NUM_STATES).zip(child_partial.chunks_exact(NUM_STATES)) {
+                        for (state, probability) in pattern.iter_mut().enumerate() {
+                            *probability *= (0..NUM_STATES).map(|j| matrix[state][j] * child_pattern[j]).sum::<f64>();
+                        }
+                    }
+                }
+                rescale(&mut partial, &mut log_scales);
+            }
+        }
+        partials[index * width..(index + 1) * width].copy_from_slice(&partial);
+    }
+
+    let frequencies = model.frequencies();
+    let root = tree.root().index();
+    partials[root * width..(root + 1) * width].chunks_exact(NUM_STATES)
+        .zip(&weights)
+        .zip(&log_scales)
+        .map(|((pattern, &weight), &log_scale)| {
+            let likelihood: f64 = pattern.iter().zip(&frequencies).map(|(p, f)| p * f).sum();
+            weight as f64 * (likelihood.ln() + log_scale)
+        })
+        .sum()
+}
+
+// ============================================================================
+// Little Helpers
+// ============================================================================
+/// Collapses identical sites of the alignment into patterns and returns, per label index,
+/// its states of each pattern together with the number of sites of each pattern.
+fn site_patterns(alignment: &Alignment) -> (HashMap<usize, Vec<u8>>, Vec<usize>) {
+    let sequences: Vec<(usize, &[u8])> = alignment.iter().collect();
+    let mut pattern_index: HashMap<Vec<u8>, usize> = HashMap::new();
+    let mut weights: Vec<usize> = Vec::new();
+    let mut patterns: HashMap<usize, Vec<u8>> = sequences.iter().map(|&(label_index, _)| (label_index, Vec::new())).collect();
+    for site in 0..alignment.num_sites() {
+        let column: Vec<u8> = sequences.iter().map(|(_, sequence)| sequence[site]).collect();
+        if let Some(&pattern) = pattern_index.get(&column) {
+            weights[pattern] += 1;
+            continue;
+        }
+        pattern_index.insert(column, weights.len());
+        weights.push(1);
+        for &(label_index, sequence) in &sequences {
+            patterns.get_mut(&label_index).expect("Every sequence has patterns").push(sequence[site]);
+        }
+    }
+    (patterns, weights)
+}
+
+/// Sets the partial likelihoods of a leaf with the given states per pattern (unknown without states).
+fn leaf_partial(pattern_states: Option<&Vec<u8>>, partial: &mut [f64]) {
+    let Some(states) = pattern_states else {
+        partial.fill(1.0);
+        return;
+    };
+    for (pattern, &symbol) in partial.chunks_exact_mut(NUM_STATES).zip(states) {
+        let bases = nucleotide_bases(symbol).unwrap_or(0b1111);
+        for (state, probability) in pattern.iter_mut().enumerate() {
+            *probability = if bases & (1 << state) != 0 { 1.0 } else { 0.0 };
+        }
+    }
+}
+
+/// Divides the partials of each pattern by their maximum and adds its logarithm to the log scale of the pattern.
+fn rescale(partial: &mut [f64], log_scales: &mut [f64]) {
+    for (pattern, log_scale) in partial.chunks_exact_mut(NUM_STATES).zip(log_scales) {
+        let max = pattern.iter().copied().fold(0.0, f64::max);
+        if max > 0.0 && max != 1.0 {
+            pattern.iter_mut().for_each(|probability| *probability /= max);
+            *log_scale += max.ln();
+        }
+    }
+}
+
+/// Returns the eigenvalues and eigenvectors (columns) of the symmetric matrix by the cyclic Jacobi method.
+fn jacobi_eigen(mut matrix: [[f64; NUM_STATES]; NUM_STATES]) -> ([f64; NUM_STATES], [[f64; NUM_STATES]; NUM_STATES]) {
+    let mut vectors = [[0.0; NUM_STATES]; NUM_STATES];
+    for (i, row) in vectors.iter_mut().enumerate() {
+        row[i] = 1.0;
+    }
+
+    for _ in 0..MAX_JACOBI_SWEEPS {
+        let off_diagonal: f64 = (0..NUM_STATES)
+            .flat_map(|i| (0..NUM_STATES).filter(move |&j| j != i).map(move |j| (i, j)))
+            .map(|(i, j)| matrix[i][j] * matrix[i][j])
+            .sum();
+        if off_diagonal < 1e-30 {
+            break;
+        }
+
+        for p in 0..NUM_STATES {
+            for q in p + 1..NUM_STATES {
+                if matrix[p][q].abs() < 1e-300 {
+                    continue;
+                }
+                // Rotation zeroing matrix[p][q]
+                let theta = (matrix[q][q] - matrix[p][p]) / (2.0 * matrix[p][q]);
+                let t = theta.signum() / (theta.abs() + (theta * theta + 1.0).sqrt());
+                let t = if theta == 0.0 { 1.0 } else { t };
+                let (c, s) = (1.0 / (t * t + 1.0).sqrt(), t / (t * t + 1.0).sqrt());
+                for row in matrix.iter_mut().chain(vectors.iter_mut()) {
+                    let (kp, kq) = (row[p], row[q]);
+                    row[p] = c * kp - s * kq;
+                    row[q] = s * kp + c * kq;
+                }
+                let (row_p, row_q) = (matrix[p], matrix[q]);
+                matrix[p] = std::array::from_fn(|k| c * row_p[k] - s * row_q[k]);
+                matrix[q] = std::array::from_fn(|k| s * row_p[k] + c * row_q[k]);
+            }
+        }
+    }
+
+    (std::array::from_fn(|i| matrix[i][i]), vectors)
+}
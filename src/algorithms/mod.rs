@@ -0,0 +1,26 @@
+/// Bipartitions (splits) of trees and tree distances (Robinson-Foulds, branch score, path difference)
+pub mod robinson_foulds;
+/// Split compatibility of trees with a backbone tree
+pub mod compatibility;
+/// Majority-rule and strict consensus trees of tree samples
+pub mod consensus;
+/// Feature vectors of trees (Kendall-Colijn, split indicators, pairwise distances)
+pub mod embedding;
+/// Equality of trees up to the order of children, with or without branch lengths
+pub mod tree_equality;
+/// Hashes of tree topologies and deduplication of samples
+pub mod topology_hash;
+/// Explicit rooted or unrooted interpretation of trees for distances and consensus
+pub mod tree_view;
+/// All-pairs distance matrices of tree samples, with checkpointing
+pub mod distance_matrix;
+/// Leaf orders of tree pairs with few crossings for tanglegrams
+pub mod tanglegram;
+pub use tanglegram::untangle;
+/// Patristic distances between the leaves of a tree, with PHYLIP export
+pub mod patristic;
+/// Parsimony length of alignments on trees (Fitch algorithm)
+pub mod parsimony;
+pub use parsimony::fitch_score;
+/// Log-likelihood of nucleotide alignments on trees (JC69 and GTR, Felsenstein pruning)
+pub mod likelihood;
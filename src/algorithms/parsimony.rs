@@ -0,0 +1,201 @@
+//! Parsimony module for the parsimony length of an alignment on a tree.
+//!
+//! - `fitch_score`: Minimal number of state changes (Fitch algorithm, Hartigan's generalization
+//!   for polytomies) needed to explain the alignment on the tree
+//!
+//! State sets are bit-parallel: each vertex stores, per state, a bitset over the sites
+//! (64 sites per word), so that binary vertices are computed with a few word operations for 64 sites at once.
+
+use crate::model::alignment::{nucleotide_bases, Alignment, DataType};
+use crate::model::tree::Tree;
+
+/// Amino acids in their usual order
+const AMINO_ACIDS: &[u8; 20] = b"ARNDCQEGHILKMFPSTWYV";
+
+/// Sites per word of the bitsets
+const WORD_BITS: usize = 64;
+
+// =#========================================================================#=
+// FITCH SCORE
+// =#========================================================================#=
+/// Returns the parsimony length of the alignment on the tree: the minimal number of state changes
+/// along its branches, summed over all sites (unweighted, unordered characters).
+///
+/// Leaves are matched to sequences by their label index (see [Alignment::sequence]).
+/// Gaps, missing data and leaves without sequence can take any state, and ambiguity codes
+/// (e.g. IUPAC `R` for `A` or `G`, or `B` for asparagine or aspartic acid) any of their states.
+/// The states of standard characters are the symbols occurring in the alignment.
+/// The score does not depend on the root, and polytomies count the minimal changes to resolve them.
+///
+/// # Panics
+/// Panics if the standard characters of the alignment have more than 64 distinct states.
+///
+/// # Example
+/// ```
+/// use nexus_parser::algorithms::parsimony::fitch_score;
+/// use nexus_parser::io::parser::fasta;
+/// use nexus_parser::model::alignment::DataType;
+/// use nexus_parser::parse_newick_str;
+///
+/// let (trees, mut labels) = parse_newick_str("((A,B),(C,D));").unwrap();
+/// let alignment = fasta::parse_str(">A\nAC\n>B\nAC\n>C\nGT\n>D\nGA\n", DataType::Dna, &mut labels).unwrap();
+///
+/// // First site: one change A <-> G; second site: C, T and A need two changes
+/// assert_eq!(fitch_score(&trees[0], &alignment), 3);
+/// ```
+pub fn fitch_score(tree: &Tree, alignment: &Alignment) -> usize {
+    let encoding = StateEncoding::new(alignment);
+    let num_words = alignment.num_sites().div_ceil(WORD_BITS);
+    let width = encoding.num_states * num_words;
+    if width == 0 {
+        return 0;
+    }
+
+    // Mask of the sites of each word (the last one may be partial)
+    let site_masks: Vec<u64> = (0..num_words)
+        .map(|w| match alignment.num_sites() - w * WORD_BITS {
+            sites if sites >= WORD_BITS => u64::MAX,
+            sites => (1 << sites) - 1,
+        })
+        .collect();
+
+    // State sets of each vertex: per state, a bitset over the sites
+    let mut sets = vec![0u64; tree.num_vertices() * width];
+    let mut set = vec![0u64; width];
+    let mut score = 0;
+    for vertex in tree.post_order_iter() {
+        let children = vertex.children().unwrap_or(&[]);
+        let child_set = |child: usize| &sets[child * width..(child + 1) * width];
+        match children {
+            [] => {
+                let sequence = vertex.label_index().and_then(|label_index| alignment.sequence(label_index));
+                encoding.leaf_set(sequence, num_words, &site_masks, &mut set);
+            }
+            [child] => set.copy_from_slice(child_set(*child)),
+            [first, second] => score += fitch_step(child_set(*first), child_set(*second), &site_masks, &mut set),
+            _ => {
+                let children_sets: Vec<&[u64]> = children.iter().map(|&child| child_set(child)).collect();
+                score += hartigan_step(&children_sets, alignment.num_sites(), num_words, &mut set);
+            }
+        }
+        let index = vertex.index();
+        sets[index * width..(index + 1) * width].copy_from_slice(&set);
+    }
+
+    score
+}
+
+// ============================================================================
+// Little Helpers
+// ============================================================================
+/// Sets of states of the symbols of an alignment.
+struct StateEncoding {
+    num_states: usize,
+    /// Set of states (bit per state) of each symbol
+    symbol_states: [u64; 256],
+}
+
+impl StateEncoding {
+    /// Creates the encoding of the data type of the alignment; unknown symbols (e.g. gap and missing) get all states.
+    fn new(alignment: &Alignment) -> Self {
+        let mut symbol_states = [0u64; 256];
+        let num_states = match alignment.data_type() {
+            DataType::Dna | DataType::Rna => {
+                for symbol in 0..=u8::MAX {
+                    symbol_states[symbol as usize] = nucleotide_bases(symbol).map_or(0, u64::from);
+                }
+                4
+            }
+            DataType::Protein => {
+                for (state, &symbol) in AMINO_ACIDS.iter().enumerate() {
+                    symbol_states[symbol as usize] = 1 << state;
+                }
+                let state = |symbol: u8| symbol_states[symbol as usize];
+                let ambiguous = [(b'B', state(b'N') | state(b'D')), (b'Z', state(b'Q') | state(b'E')), (b'J', state(b'I') | state(b'L'))];
+                for (symbol, states) in ambiguous {
+                    symbol_states[symbol as usize] = states;
+                }
+                AMINO_ACIDS.len()
+            }
+            DataType::Standard => {
+                let mut symbols: Vec<u8> = alignment.iter()
+                    .flat_map(|(_, sequence)| sequence.iter().copied())
+                    .filter(|&symbol| symbol != alignment.gap() && symbol != alignment.missing() && symbol != b'?' && symbol != b'-')
+                    .collect();
+                symbols.sort_unstable();
+                symbols.dedup();
+                assert!(symbols.len() <= 64, "Standard characters have at most 64 states, found {}", symbols.len());
+                for (state, &symbol) in symbols.iter().enumerate() {
+                    symbol_states[symbol as usize] = 1 << state;
+                }
+                symbols.len()
+            }
+        };
+
+        let all_states = if num_states == 64 { u64::MAX } else { (1 << num_states) - 1 };
+        for states in symbol_states.iter_mut().filter(|states| **states == 0) {
+            *states = all_states;
+        }
+        Self { num_states, symbol_states }
+    }
+
+    /// Sets the state sets of a leaf with the given sequence (all states without sequence).
+    fn leaf_set(&self, sequence: Option<&[u8]>, num_words: usize, site_masks: &[u64], set: &mut [u64]) {
+        let Some(sequence) = sequence else {
+            for (i, word) in set.iter_mut().enumerate() {
+                *word = site_masks[i % num_words];
+            }
+            return;
+        };
+
+        set.fill(0);
+        for (site, &symbol) in sequence.iter().enumerate() {
+            let states = self.symbol_states[symbol as usize];
+            for state in 0..self.num_states {
+                if states & (1 << state) != 0 {
+                    set[state * num_words + site / WORD_BITS] |= 1 << (site % WORD_BITS);
+                }
+            }
+        }
+    }
+}
+
+/// Combines the state sets of two children (Fitch): their intersection where not empty, their union otherwise.
+/// Returns the number of sites with empty intersection (one change each).
+fn fitch_step(first: &[u64], second: &[u64], site_masks: &[u64], set: &mut [u64]) -> usize {
+    let num_words = site_masks.len();
+    let mut changes = 0;
+    for (w, &site_mask) in site_masks.iter().enumerate() {
+        let states = (w..set.len()).step_by(num_words);
+        let shared = states.clone().fold(0, |shared, i| shared | (first[i] & second[i]));
+        let disjoint = !shared & site_mask;
+        changes += disjoint.count_ones() as usize;
+        for i in states {
+            set[i] = (first[i] & second[i]) | (disjoint & (first[i] | second[i]));
+        }
+    }
+    changes
+}
+
+/// Combines the state sets of any number of children (Hartigan): the states contained in most children.
+/// Returns the number of changes, i.e. per site the number of children without such a state.
+fn hartigan_step(children: &[&[u64]], num_sites: usize, num_words: usize, set: &mut [u64]) -> usize {
+    let num_states = set.len() / num_words;
+    let mut counts = vec![0; num_states];
+    let mut changes = 0;
+    set.fill(0);
+    for site in 0..num_sites {
+        let (word, bit) = (site / WORD_BITS, 1 << (site % WORD_BITS));
+        for (state, count) in counts.iter_mut().enumerate() {
+            *count = children.iter().filter(|child| child[state * num_words + word] & bit != 0).count();
+        }
+        let max = counts.iter().copied().max().unwrap_or(0);
+        changes += children.len() - max;
+        for (state, &count) in counts.iter().enumerate() {
+            if count == max {
+                set[state * num_words + word] |= bit;
+            }
+        }
+    }
+    changes
+}
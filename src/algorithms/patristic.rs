@@ -0,0 +1,137 @@
+//! Patristic distance module for distances between the leaves of a tree.
+//!
+//! - `DistanceMatrix`: Symmetric matrix of distances between labeled taxa, exportable in PHYLIP format
+//! - `Tree::patristic_distances`: Lengths of the paths between all pairs of leaves of a tree
+
+use crate::algorithms::robinson_foulds::{leaf_pair_index, leaf_path_lengths};
+use crate::model::leaf_label_map::LeafLabelMap;
+use crate::model::tree::Tree;
+use std::io;
+use std::io::Write;
+
+// =#========================================================================#=
+// DISTANCE MATRIX
+// =#========================================================================#=
+/// Symmetric matrix of distances between taxa (with zero diagonal), e.g. for clustering.
+///
+/// Stores only the distances of pairs `(i, j)` with `i < j`, row by row.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DistanceMatrix {
+    labels: Vec<String>,
+    distances: Vec<f64>,
+}
+
+impl DistanceMatrix {
+    /// Creates a distance matrix of the given taxa from the distances of all pairs `(i, j)` with `i < j`,
+    /// ordered row by row, i.e. `(0, 1), (0, 2), ..., (1, 2), ...`.
+    ///
+    /// # Panics
+    /// Panics if the number of distances does not match the number of pairs of taxa.
+    pub fn new(labels: Vec<String>, distances: Vec<f64>) -> Self {
+        let num_taxa = labels.len();
+        assert_eq!(distances.len(), num_taxa * num_taxa.saturating_sub(1) / 2,
+                   "Expected one distance per pair of {} taxa", num_taxa);
+        Self { labels, distances }
+    }
+
+    /// Returns the number of taxa.
+    pub fn len(&self) -> usize {
+        self.labels.len()
+    }
+
+    /// Returns whether the matrix has no taxa.
+    pub fn is_empty(&self) -> bool {
+        self.labels.is_empty()
+    }
+
+    /// Returns the labels of the taxa, in order of rows and columns.
+    pub fn labels(&self) -> &[String] {
+        &self.labels
+    }
+
+    /// Returns the distance between the taxa in rows `i` and `j`.
+    ///
+    /// # Panics
+    /// Panics if `i` or `j` is out of bounds.
+    pub fn get(&self, i: usize, j: usize) -> f64 {
+        assert!(i < self.len() && j < self.len(), "Index ({}, {}) out of bounds for {} taxa", i, j, self.len());
+        if i == j {
+            0.0
+        } else {
+            self.distances[leaf_pair_index(self.len(), i, j)]
+        }
+    }
+
+    /// Returns the distance between the taxa with the given labels, if both are present.
+    pub fn get_by_label(&self, label: &str, other: &str) -> Option<f64> {
+        let i = self.labels.iter().position(|l| l == label)?;
+        let j = self.labels.iter().position(|l| l == other)?;
+        Some(self.get(i, j))
+    }
+
+    /// Returns the full matrix as rows.
+    pub fn to_rows(&self) -> Vec<Vec<f64>> {
+        (0..self.len())
+            .map(|i| (0..self.len()).map(|j| self.get(i, j)).collect())
+            .collect()
+    }
+
+    /// Writes the matrix in (relaxed) PHYLIP distance format: the number of taxa,
+    /// then one row per taxon with its label (padded to 10 characters, spaces replaced by underscores)
+    /// followed by its distances to all taxa.
+    ///
+    /// # Errors
+    /// Returns an error if writing fails.
+    ///
+    /// # Example
+    /// ```
+    /// use nexus_parser::algorithms::patristic::DistanceMatrix;
+    ///
+    /// let matrix = DistanceMatrix::new(vec!["A".to_string(), "Homo sapiens".to_string()], vec![0.5]);
+    /// let mut phylip = Vec::new();
+    /// matrix.write_phylip(&mut phylip).unwrap();
+    /// assert_eq!(String::from_utf8(phylip).unwrap(), "2\nA          0 0.5\nHomo_sapiens 0.5 0\n");
+    /// ```
+    pub fn write_phylip<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        writeln!(writer, "{}", self.len())?;
+        for i in 0..self.len() {
+            let values: Vec<String> = (0..self.len()).map(|j| self.get(i, j).to_string()).collect();
+            writeln!(writer, "{:<10} {}", self.labels[i].replace(' ', "_"), values.join(" "))?;
+        }
+        writer.flush()
+    }
+}
+
+// =#========================================================================#=
+// PATRISTIC DISTANCES
+// =#========================================================================#=
+impl Tree {
+    /// Returns the patristic distances of this tree, i.e. the lengths of the paths between all pairs of leaves,
+    /// with rows in order of label indices and labeled by the given [LeafLabelMap].
+    ///
+    /// Computed in a single post-order traversal in `O(n^2)` time for `n` leaves, combining the distances
+    /// to the leaves below the children of each vertex. Branches without length count as length `0.0`.
+    ///
+    /// # Panics
+    /// Panics if the tree does not have each of its [leaves](Tree::num_leaves_init) exactly once.
+    ///
+    /// # Example
+    /// ```
+    /// use nexus_parser::io::parser::byte_parser::ByteParser;
+    /// use nexus_parser::io::parser::newick::NewickParser;
+    ///
+    /// let mut newick_parser = NewickParser::new().with_num_leaves(3);
+    /// let tree = newick_parser.parse(&mut ByteParser::from_str("((A:1,B:2):0.5,C:3);")).unwrap();
+    ///
+    /// let distances = tree.patristic_distances(newick_parser.leaf_label_map());
+    /// assert_eq!(distances.get(0, 1), 3.0);
+    /// assert_eq!(distances.get_by_label("A", "C"), Some(4.5));
+    /// ```
+    pub fn patristic_distances(&self, leaf_label_map: &LeafLabelMap) -> DistanceMatrix {
+        let distances = leaf_path_lengths(self, |index| self[index].branch_length().map_or(0.0, |length| *length));
+        let labels = (0..self.num_leaves_init())
+            .map(|index| leaf_label_map.get_label(index).unwrap_or_default().to_string())
+            .collect();
+        DistanceMatrix::new(labels, distances)
+    }
+}
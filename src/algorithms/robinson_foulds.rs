@@ -0,0 +1,265 @@
+//! Robinson-Foulds module for comparing trees by their splits, and further tree distances.
+//!
+//! - `Tree::bipartitions`: Non-trivial bipartitions (splits) of a tree as hashable [TaxonSet]s
+//! - `robinson_foulds`: Number of splits in exactly one of two trees
+//! - `normalized_robinson_foulds`: Same, divided by the maximum possible distance
+//! - `Tree::split_lengths`: Length of the branch of each split, including trivial ones
+//! - `branch_score`: Kuhner-Felsenstein branch score distance, based on branch lengths per split
+//! - `path_distance`: Path-difference distance, based on numbers of branches between pairs of leaves
+//!
+//! All distances compare leaves by their label index, i.e. trees need to share a
+//! [LeafLabelMap](crate::model::leaf_label_map::LeafLabelMap); splits and pairs of leaves
+//! are ordered by label indices, so per-tree data can be computed once and compared directly.
+
+use crate::model::taxon_set::TaxonSet;
+use crate::model::tree::{Tree, TreeIndex};
+use std::collections::{HashMap, HashSet};
+
+// ============================================================================
+// Bipartitions (pub)
+// ============================================================================
+impl Tree {
+    /// Returns the non-trivial bipartitions (splits) of this tree, ignoring its root.
+    ///
+    /// Each edge splits the taxa into two parts; a split is represented by the part
+    /// *not* containing taxon (label index) `0`, so equal splits are equal [TaxonSet]s
+    /// ranging over the [number of leaves](Tree::num_leaves_init) of this tree.
+    /// Trivial splits (a single taxon versus the rest) are left out,
+    /// as are duplicates (e.g. both edges below a binary root induce the same split).
+    ///
+    /// # Example
+    /// ```
+    /// use nexus_parser::model::taxon_set::TaxonSet;
+    /// use nexus_parser::model::tree::Tree;
+    ///
+    /// // ((A,B),(C,D)) has the single non-trivial split AB|CD
+    /// let mut tree = Tree::new(4);
+    /// let a = tree.add_leaf(None, 0);
+    /// let b = tree.add_leaf(None, 1);
+    /// let c = tree.add_leaf(None, 2);
+    /// let d = tree.add_leaf(None, 3);
+    /// let ab = tree.add_internal_vertex(vec![a, b], None);
+    /// let cd = tree.add_internal_vertex(vec![c, d], None);
+    /// tree.add_root(vec![ab, cd]);
+    ///
+    /// let splits = tree.bipartitions();
+    /// assert_eq!(splits.len(), 1);
+    /// assert!(splits.contains(&TaxonSet::from_indices(4, [2, 3])));
+    /// ```
+    pub fn bipartitions(&self) -> HashSet<TaxonSet> {
+        let num_taxa = self.num_leaves_init();
+
+        let mut splits = HashSet::with_capacity(self.num_internal());
+        for (index, clade) in self.clades(num_taxa).iter().enumerate() {
+            if self[index].is_root() {
+                continue;
+            }
+            let split = if clade.contains(0) { clade.complement() } else { clade.clone() };
+            if split.len() > 1 && split.len() + 1 < num_taxa {
+                splits.insert(split);
+            }
+        }
+
+        splits
+    }
+
+    /// Returns the length of the branch of each split of this tree, ignoring its root,
+    /// including trivial splits (of single leaves); splits are represented as in [Tree::bipartitions].
+    ///
+    /// The two branches below a binary root induce the same split, their lengths are summed.
+    /// Branches without length count as length `0.0`.
+    pub fn split_lengths(&self) -> HashMap<TaxonSet, f64> {
+        let mut lengths = HashMap::with_capacity(self.num_vertices());
+        for (index, clade) in self.clades(self.num_leaves_init()).iter().enumerate() {
+            if self[index].is_root() {
+                continue;
+            }
+            let split = if clade.contains(0) { clade.complement() } else { clade.clone() };
+            let length = self[index].branch_length().map_or(0.0, |length| *length);
+            *lengths.entry(split).or_insert(0.0) += length;
+        }
+
+        lengths
+    }
+}
+
+// ============================================================================
+// Robinson-Foulds Distance (pub)
+// ============================================================================
+/// Returns the (unrooted) Robinson-Foulds distance of two trees,
+/// i.e. the number of non-trivial splits contained in exactly one of them.
+///
+/// # Panics
+/// Panics if the trees do not have the same number of leaves (over the same [LeafLabelMap](crate::model::leaf_label_map::LeafLabelMap)).
+///
+/// # Example
+/// ```
+/// use nexus_parser::algorithms::robinson_foulds::robinson_foulds;
+/// use nexus_parser::io::parser::byte_parser::ByteParser;
+/// use nexus_parser::io::parser::newick::NewickParser;
+///
+/// let mut newick_parser = NewickParser::new().with_num_leaves(5);
+/// let tree_1 = newick_parser.parse(&mut ByteParser::from_str("(((A,B),C),(D,E));")).unwrap();
+/// let tree_2 = newick_parser.parse(&mut ByteParser::from_str("(((A,C),B),(D,E));")).unwrap();
+///
+/// // AB|CDE vs. AC|BDE
+/// assert_eq!(robinson_foulds(&tree_1, &tree_2), 2);
+/// ```
+pub fn robinson_foulds(tree: &Tree, other: &Tree) -> usize {
+    assert_eq!(tree.num_leaves_init(), other.num_leaves_init(), "Trees must have the same number of leaves");
+    let splits = tree.bipartitions();
+    let other_splits = other.bipartitions();
+    splits.symmetric_difference(&other_splits).count()
+}
+
+/// Returns the Robinson-Foulds distance divided by its maximum `2 (n - 3)` for binary trees on `n` taxa,
+/// or `0.0` for trees with fewer than four taxa (which have no non-trivial splits).
+///
+/// # Panics
+/// Panics if the trees do not have the same number of leaves.
+pub fn normalized_robinson_foulds(tree: &Tree, other: &Tree) -> f64 {
+    let distance = robinson_foulds(tree, other);
+    let num_taxa = tree.num_leaves_init();
+    if num_taxa < 4 {
+        return 0.0;
+    }
+    distance as f64 / (2 * (num_taxa - 3)) as f64
+}
+
+// ============================================================================
+// Branch Score Distance (pub)
+// ============================================================================
+/// Returns the (unrooted) branch score distance of Kuhner and Felsenstein (1994) of two trees,
+/// i.e. the Euclidean distance of their vectors of branch lengths per split (see [Tree::split_lengths]),
+/// where splits missing in a tree have length `0.0`.
+///
+/// # Panics
+/// Panics if the trees do not have the same number of leaves.
+///
+/// # Example
+/// ```
+/// use nexus_parser::algorithms::robinson_foulds::branch_score;
+/// use nexus_parser::io::parser::byte_parser::ByteParser;
+/// use nexus_parser::io::parser::newick::NewickParser;
+///
+/// let mut newick_parser = NewickParser::new().with_num_leaves(4);
+/// let tree_1 = newick_parser.parse(&mut ByteParser::from_str("((A:1,B:1):1,(C:1,D:1):1);")).unwrap();
+/// let tree_2 = newick_parser.parse(&mut ByteParser::from_str("((A:1,C:1):2,(B:1,D:1):1);")).unwrap();
+///
+/// // AB|CD of length 2 vs. AC|BD of length 3
+/// assert_eq!(branch_score(&tree_1, &tree_2), 13.0_f64.sqrt());
+/// ```
+pub fn branch_score(tree: &Tree, other: &Tree) -> f64 {
+    assert_eq!(tree.num_leaves_init(), other.num_leaves_init(), "Trees must have the same number of leaves");
+    let lengths = tree.split_lengths();
+    let other_lengths = other.split_lengths();
+
+    let mut sum = 0.0;
+    for (split, length) in &lengths {
+        let other_length = other_lengths.get(split).copied().unwrap_or(0.0);
+        sum += (length - other_length).powi(2);
+    }
+    for (split, other_length) in &other_lengths {
+        if !lengths.contains_key(split) {
+            sum += other_length.powi(2);
+        }
+    }
+
+    sum.sqrt()
+}
+
+// ============================================================================
+// Path-Difference Distance (pub)
+// ============================================================================
+/// Returns the path-difference distance of Steel and Penny (1993) of two trees, i.e. the Euclidean distance
+/// of their vectors of path lengths (numbers of branches) between all pairs of leaves.
+///
+/// Like the Robinson-Foulds distance, it ignores branch lengths and the root (a binary root is suppressed),
+/// but also tells apart how different the placements of taxa are.
+///
+/// # Panics
+/// Panics if the trees do not have the same number of leaves, or not the same taxa.
+///
+/// # Example
+/// ```
+/// use nexus_parser::algorithms::robinson_foulds::path_distance;
+/// use nexus_parser::io::parser::byte_parser::ByteParser;
+/// use nexus_parser::io::parser::newick::NewickParser;
+///
+/// let mut newick_parser = NewickParser::new().with_num_leaves(4);
+/// let tree_1 = newick_parser.parse(&mut ByteParser::from_str("((A,B),(C,D));")).unwrap();
+/// let tree_2 = newick_parser.parse(&mut ByteParser::from_str("((A,C),(B,D));")).unwrap();
+///
+/// // Path lengths of AB, CD, AC, BD differ by 1 each (3 and 2 branches, swapped)
+/// assert_eq!(path_distance(&tree_1, &tree_2), 2.0);
+/// ```
+pub fn path_distance(tree: &Tree, other: &Tree) -> f64 {
+    assert_eq!(tree.num_leaves_init(), other.num_leaves_init(), "Trees must have the same number of leaves");
+    let path_lengths = leaf_path_lengths(tree, unrooted_branch_count(tree));
+    let other_path_lengths = leaf_path_lengths(other, unrooted_branch_count(other));
+
+    path_lengths.iter().zip(&other_path_lengths)
+        .map(|(length, other_length)| (length - other_length).powi(2))
+        .sum::<f64>()
+        .sqrt()
+}
+
+// ============================================================================
+// Little Helpers
+// ============================================================================
+/// Returns the lengths of the paths between all pairs of leaves, with branches weighted by `weight`
+/// (of the vertex below the branch). Pairs `(i, j)` of label indices `i < j` are ordered
+/// row by row, i.e. `(0, 1), (0, 2), ..., (1, 2), ...` (see [leaf_pair_index]).
+///
+/// A binary root is not suppressed, so the path through it counts both of its branches.
+///
+/// # Panics
+/// Panics if a label index of the `n` [leaves](Tree::num_leaves_init) is missing or appears twice.
+pub(crate) fn leaf_path_lengths(tree: &Tree, weight: impl Fn(TreeIndex) -> f64) -> Vec<f64> {
+    let num_taxa = tree.num_leaves_init();
+    let mut path_lengths = vec![f64::NAN; num_taxa * num_taxa.saturating_sub(1) / 2];
+
+    // Per vertex, the leaves below it (by label index) with their distance to it
+    let mut below: Vec<Vec<(usize, f64)>> = vec![Vec::new(); tree.num_vertices()];
+    for vertex in tree.post_order_iter() {
+        let index = vertex.index();
+        let Some(children) = vertex.children() else {
+            below[index].push((vertex.label_index().unwrap(), 0.0));
+            continue;
+        };
+
+        let mut leaves: Vec<(usize, f64)> = Vec::new();
+        for &child in children {
+            let mut child_leaves = std::mem::take(&mut below[child]);
+            let child_weight = weight(child);
+            for (_, distance) in &mut child_leaves {
+                *distance += child_weight;
+            }
+            for &(leaf, distance) in &leaves {
+                for &(child_leaf, child_distance) in &child_leaves {
+                    path_lengths[leaf_pair_index(num_taxa, leaf, child_leaf)] = distance + child_distance;
+                }
+            }
+            leaves.append(&mut child_leaves);
+        }
+        below[index] = leaves;
+    }
+
+    assert!(path_lengths.iter().all(|length| !length.is_nan()), "Tree must have each of its {} taxa exactly once", num_taxa);
+    path_lengths
+}
+
+/// Returns the weight of branches to count them along paths of the unrooted tree:
+/// `1.0`, but `0.5` for the two branches below a binary root, which form a single branch.
+fn unrooted_branch_count(tree: &Tree) -> impl Fn(TreeIndex) -> f64 + '_ {
+    let binary_root = tree.root().num_children() == 2;
+    move |index| if binary_root && tree[index].parent_index() == Some(tree.root().index()) { 0.5 } else { 1.0 }
+}
+
+/// Returns the position of the pair of distinct label indices `i` and `j` in the row by row
+/// ordering of all pairs of `num_taxa` taxa, see [leaf_path_lengths].
+pub(crate) fn leaf_pair_index(num_taxa: usize, i: usize, j: usize) -> usize {
+    let (i, j) = if i < j { (i, j) } else { (j, i) };
+    assert!(i != j && j < num_taxa, "Invalid pair of taxa ({}, {}) of {} taxa", i, j, num_taxa);
+    i * num_taxa - i * (i + 1) / 2 + (j - i - 1)
+}
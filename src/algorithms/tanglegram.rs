@@ -0,0 +1,156 @@
+//! Tanglegram module for drawing two trees over the same taxa face to face (e.g. cophylogenies).
+//!
+//! - `untangle`: Leaf orders of two trees with few crossings of the lines connecting their shared taxa
+//! - `Untangled`: The two leaf orders and their number of crossings
+//! - `count_crossings`: Number of crossings between two leaf orders
+//!
+//! The leaf orders are realized by reordering children with [Tree::order_leaves],
+//! after which [Tree::to_svg] and [Tree::to_ascii] draw the leaves in these orders.
+//! Minimizing the crossings is NP-hard; [untangle] uses the barycenter heuristic,
+//! alternately ordering the children of one tree by the positions of the taxa in the other.
+
+use crate::model::tree::{LabelIndex, Tree};
+
+/// Maximal number of rounds (reordering both trees) of the heuristic
+const MAX_ROUNDS: usize = 10;
+
+// =#========================================================================#=
+// UNTANGLE
+// =#========================================================================#=
+/// Leaf orders of two trees, top to bottom, found by [untangle].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Untangled {
+    /// Label indices of the leaves of the first tree in order
+    left: Vec<LabelIndex>,
+    /// Label indices of the leaves of the second tree in order
+    right: Vec<LabelIndex>,
+    /// Number of crossings of the lines connecting the same taxa
+    crossings: usize,
+}
+
+impl Untangled {
+    /// Returns the leaf order of the first tree (label indices, top to bottom).
+    pub fn left(&self) -> &[LabelIndex] {
+        &self.left
+    }
+
+    /// Returns the leaf order of the second tree (label indices, top to bottom).
+    pub fn right(&self) -> &[LabelIndex] {
+        &self.right
+    }
+
+    /// Returns the number of crossings of the lines connecting the same taxa in both orders.
+    pub fn crossings(&self) -> usize {
+        self.crossings
+    }
+}
+
+/// Returns leaf orders of the two trees, realizable by reordering children, with few crossings of the lines
+/// connecting the same taxa (by [LabelIndex], so the trees need to share a [LeafLabelMap](crate::model::leaf_label_map::LeafLabelMap)).
+///
+/// Starting from the current orders, the children of the second and then the first tree are ordered
+/// by the positions of their taxa in the other tree (see [Tree::order_leaves]), for a few rounds while
+/// the number of crossings decreases. The result is never worse than the current orders but need not be optimal.
+/// Taxa in only one of the trees are ignored.
+///
+/// # Example
+/// ```
+/// use nexus_parser::algorithms::tanglegram::{count_crossings, untangle};
+/// use nexus_parser::parse_newick_str;
+///
+/// let (mut trees, labels) = parse_newick_str("(((A,B),C),(D,E));\n((E,D),(C,(B,A)));\n").unwrap();
+/// assert_eq!(count_crossings(&trees[0].leaf_order(), &trees[1].leaf_order()), 10);
+///
+/// let untangled = untangle(&trees[0], &trees[1]);
+/// assert_eq!(untangled.crossings(), 0);
+///
+/// // Reorder the children for drawing
+/// trees[0].order_leaves(untangled.left());
+/// trees[1].order_leaves(untangled.right());
+/// assert_eq!(trees[0].leaf_order(), trees[1].leaf_order());
+/// ```
+pub fn untangle(tree: &Tree, other: &Tree) -> Untangled {
+    let mut left = tree.clone();
+    let mut right = other.clone();
+
+    let mut best = Untangled { left: left.leaf_order(), right: right.leaf_order(), crossings: 0 };
+    best.crossings = count_crossings(&best.left, &best.right);
+    for _ in 0..MAX_ROUNDS {
+        if best.crossings == 0 {
+            break;
+        }
+
+        let crossings = best.crossings;
+        right.order_leaves(&left.leaf_order());
+        keep_if_better(&mut best, &left, &right);
+        left.order_leaves(&right.leaf_order());
+        keep_if_better(&mut best, &left, &right);
+        if best.crossings == crossings {
+            break;
+        }
+    }
+    best
+}
+
+/// Returns the number of pairs of taxa in both leaf orders that are in different order,
+/// i.e. of crossings of the lines connecting the same taxa when the orders are drawn face to face.
+/// Taxa in only one of the orders are ignored.
+///
+/// # Example
+/// ```
+/// use nexus_parser::algorithms::tanglegram::count_crossings;
+///
+/// assert_eq!(count_crossings(&[0, 1, 2, 3], &[1, 0, 2, 3]), 1);
+/// assert_eq!(count_crossings(&[0, 1, 2, 3], &[3, 2, 1, 0]), 6);
+/// assert_eq!(count_crossings(&[0, 1, 2], &[2, 1, 4]), 1);
+/// ```
+pub fn count_crossings(order: &[LabelIndex], other: &[LabelIndex]) -> usize {
+    let num_labels = order.iter().chain(other).max().map_or(0, |&max| max + 1);
+    let mut positions = vec![None; num_labels];
+    for (position, &label_index) in other.iter().enumerate() {
+        positions[label_index] = Some(position);
+    }
+
+    // Positions in the other order, in this order; crossings are its inversions
+    let mut sequence: Vec<usize> = order.iter().filter_map(|&label_index| positions[label_index]).collect();
+    count_inversions(&mut sequence)
+}
+
+// ============================================================================
+// Little Helpers
+// ============================================================================
+/// Replaces the best orders by those of the trees if they have fewer crossings.
+fn keep_if_better(best: &mut Untangled, left: &Tree, right: &Tree) {
+    let (left, right) = (left.leaf_order(), right.leaf_order());
+    let crossings = count_crossings(&left, &right);
+    if crossings < best.crossings {
+        *best = Untangled { left, right, crossings };
+    }
+}
+
+/// Returns the number of pairs `i < j` with `values[i] > values[j]`, sorting the values (merge sort).
+fn count_inversions(values: &mut [usize]) -> usize {
+    if values.len() < 2 {
+        return 0;
+    }
+
+    let middle = values.len() / 2;
+    let mut inversions = count_inversions(&mut values[..middle]) + count_inversions(&mut values[middle..]);
+    let mut merged = Vec::with_capacity(values.len());
+    let (mut i, mut j) = (0, middle);
+    while i < middle && j < values.len() {
+        if values[j] < values[i] {
+            // All remaining values of the left half are larger
+            inversions += middle - i;
+            merged.push(values[j]);
+            j += 1;
+        } else {
+            merged.push(values[i]);
+            i += 1;
+        }
+    }
+    merged.extend_from_slice(&values[i..middle]);
+    merged.extend_from_slice(&values[j..]);
+    values.copy_from_slice(&merged);
+    inversions
+}
@@ -0,0 +1,95 @@
+//! Topology hash module for identifying and deduplicating tree topologies.
+//!
+//! - `Tree::topology_hash`: Hash of the topology, independent of the order of children and branch lengths
+//! - `dedup_topologies`: Distinct topologies of a sample with their number of occurrences
+//!
+//! Topologies are identified by their non-trivial clades (rooted trees) or splits (unrooted trees),
+//! like in [Tree::topology_eq], so trees need to share a [LeafLabelMap](crate::model::leaf_label_map::LeafLabelMap).
+
+use crate::algorithms::tree_view::TreeView;
+use crate::model::tree::Tree;
+use std::collections::HashMap;
+use std::hash::{DefaultHasher, Hash, Hasher};
+
+// ============================================================================
+// Topology Hash (pub)
+// ============================================================================
+impl Tree {
+    /// Returns a hash of the topology of this tree, canonical over its clades (if [rooted](Tree::is_rooted))
+    /// or splits (if unrooted), thus independent of the order of children, branch lengths and labels of vertices.
+    ///
+    /// Of trees equally marked as rooted, those with equal topology (see [Tree::topology_eq]) have the same hash,
+    /// and (barring hash collisions) only those. The hash is stable across runs.
+    ///
+    /// # Example
+    /// ```
+    /// use nexus_parser::io::parser::byte_parser::ByteParser;
+    /// use nexus_parser::io::parser::newick::NewickParser;
+    ///
+    /// let mut newick_parser = NewickParser::new().with_num_leaves(4);
+    /// let tree_1 = newick_parser.parse(&mut ByteParser::from_str("((A:1,B:2):1,(C:1,D:1):1);")).unwrap();
+    /// let tree_2 = newick_parser.parse(&mut ByteParser::from_str("((D,C),(B,A));")).unwrap();
+    /// let tree_3 = newick_parser.parse(&mut ByteParser::from_str("((A,C),(B,D));")).unwrap();
+    ///
+    /// assert_eq!(tree_1.topology_hash(), tree_2.topology_hash());
+    /// assert_ne!(tree_1.topology_hash(), tree_3.topology_hash());
+    /// ```
+    pub fn topology_hash(&self) -> u64 {
+        let mut split_hashes: Vec<u64> = TreeView::of(self).splits().iter()
+            .map(|split| {
+                let mut hasher = DefaultHasher::new();
+                split.hash(&mut hasher);
+                hasher.finish()
+            })
+            .collect();
+        split_hashes.sort_unstable();
+
+        let mut hasher = DefaultHasher::new();
+        self.is_rooted().hash(&mut hasher);
+        self.num_leaves_init().hash(&mut hasher);
+        self.leaf_taxa().hash(&mut hasher);
+        split_hashes.hash(&mut hasher);
+        hasher.finish()
+    }
+}
+
+// ============================================================================
+// Deduplication (pub)
+// ============================================================================
+/// Returns the distinct topologies of the given trees with their number of occurrences,
+/// ordered by decreasing count (ties in order of first appearance).
+///
+/// Each topology is represented by its first tree in the sample. Trees are grouped by
+/// [Tree::topology_hash], with equal hashes confirmed by [Tree::topology_eq].
+///
+/// # Example
+/// ```
+/// use nexus_parser::algorithms::topology_hash::dedup_topologies;
+/// use nexus_parser::parse_newick_str;
+///
+/// let (trees, _) = parse_newick_str("((A,B),(C,D));\n((A,C),(B,D));\n((D,C),(A,B));").unwrap();
+/// let topologies = dedup_topologies(&trees);
+///
+/// assert_eq!(topologies.len(), 2);
+/// assert_eq!(topologies[0].1, 2);
+/// assert!(std::ptr::eq(topologies[0].0, &trees[0]));
+/// ```
+pub fn dedup_topologies(trees: &[Tree]) -> Vec<(&Tree, usize)> {
+    let mut topologies: Vec<(&Tree, usize)> = Vec::new();
+    let mut by_hash: HashMap<u64, Vec<usize>> = HashMap::new();
+
+    for tree in trees {
+        let candidates = by_hash.entry(tree.topology_hash()).or_default();
+        match candidates.iter().find(|&&i| topologies[i].0.topology_eq(tree)) {
+            Some(&i) => topologies[i].1 += 1,
+            None => {
+                candidates.push(topologies.len());
+                topologies.push((tree, 1));
+            }
+        }
+    }
+
+    // Stable sort keeps order of first appearance for ties
+    topologies.sort_by_key(|&(_, count)| std::cmp::Reverse(count));
+    topologies
+}
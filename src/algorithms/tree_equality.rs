@@ -0,0 +1,102 @@
+//! Tree equality module for comparing trees up to the order of children.
+//!
+//! - `Tree::topology_eq`: Same clades (rooted) or splits (unrooted), ignoring branch lengths
+//! - `Tree::exact_eq`: Same rooted tree with branch lengths equal within a tolerance
+//!
+//! Both compare leaves by their label index, so the trees need to share a
+//! [LeafLabelMap](crate::model::leaf_label_map::LeafLabelMap) (or [LabelArena](crate::model::label_arena::LabelArena)).
+//! Tree names, node labels and annotations are ignored.
+
+use crate::algorithms::tree_view::TreeView;
+use crate::model::taxon_set::TaxonSet;
+use crate::model::tree::Tree;
+use crate::model::vertex::BranchLength;
+use std::collections::HashMap;
+
+// ============================================================================
+// Equality (pub)
+// ============================================================================
+impl Tree {
+    /// Returns whether both trees have the same topology, ignoring branch lengths and the order of children.
+    ///
+    /// Trees are compared by their clades if both are [rooted](Tree::is_rooted),
+    /// otherwise by their splits (so unrooted trees rooted differently are equal).
+    /// In any case, both trees need to have the same taxa.
+    ///
+    /// # Example
+    /// ```
+    /// use nexus_parser::io::parser::byte_parser::ByteParser;
+    /// use nexus_parser::io::parser::newick::NewickParser;
+    ///
+    /// let mut newick_parser = NewickParser::new().with_num_leaves(4);
+    /// let tree_1 = newick_parser.parse(&mut ByteParser::from_str("((A:1,B:2):1,(C:1,D:1):1);")).unwrap();
+    /// let tree_2 = newick_parser.parse(&mut ByteParser::from_str("((D,C),(B,A));")).unwrap();
+    /// let tree_3 = newick_parser.parse(&mut ByteParser::from_str("(A,(B,(C,D)));")).unwrap();
+    ///
+    /// assert!(tree_1.topology_eq(&tree_2));
+    /// assert!(!tree_1.topology_eq(&tree_3));
+    /// // As unrooted trees, the rootings do not matter
+    /// assert!(tree_1.clone().with_rooted(false).topology_eq(&tree_3));
+    /// ```
+    pub fn topology_eq(&self, other: &Tree) -> bool {
+        if !self.has_same_taxa(other) {
+            return false;
+        }
+
+        if self.is_rooted() && other.is_rooted() {
+            TreeView::Rooted(self).splits() == TreeView::Rooted(other).splits()
+        } else {
+            self.bipartitions() == other.bipartitions()
+        }
+    }
+
+    /// Returns whether both trees are the same rooted tree, ignoring the order of children,
+    /// with the lengths of corresponding branches differing by at most `tolerance`.
+    ///
+    /// Branches correspond if they lead to the same clade; a branch without length
+    /// only corresponds to a branch without length. Whether the trees are marked as rooted is ignored.
+    ///
+    /// # Example
+    /// ```
+    /// use nexus_parser::io::parser::byte_parser::ByteParser;
+    /// use nexus_parser::io::parser::newick::NewickParser;
+    ///
+    /// let mut newick_parser = NewickParser::new().with_num_leaves(3);
+    /// let tree_1 = newick_parser.parse(&mut ByteParser::from_str("((A:1,B:2):0.5,C:3);")).unwrap();
+    /// let tree_2 = newick_parser.parse(&mut ByteParser::from_str("(C:3,(B:2.0000001,A:1):0.5);")).unwrap();
+    ///
+    /// assert!(tree_1.exact_eq(&tree_2, 1e-6));
+    /// # // Both lengths round to the same f32 when stored with the `f32-lengths` feature
+    /// # #[cfg(not(feature = "f32-lengths"))]
+    /// assert!(!tree_1.exact_eq(&tree_2, 0.0));
+    /// ```
+    pub fn exact_eq(&self, other: &Tree, tolerance: f64) -> bool {
+        if !self.has_same_taxa(other) || self.num_vertices() != other.num_vertices() {
+            return false;
+        }
+
+        let clades = self.clades(self.num_leaves_init());
+        let branch_lengths: HashMap<&TaxonSet, Option<BranchLength>> = clades.iter()
+            .enumerate()
+            .map(|(index, clade)| (clade, self[index].branch_length()))
+            .collect();
+
+        other.clades(other.num_leaves_init()).iter().enumerate().all(|(index, clade)| {
+            match (branch_lengths.get(clade), other[index].branch_length()) {
+                (Some(Some(length)), Some(other_length)) => (**length - *other_length).abs() <= tolerance,
+                (Some(None), None) => true,
+                _ => false,
+            }
+        })
+    }
+
+    // ============================================================================
+    // Little Helpers
+    // ============================================================================
+    /// Returns whether both trees range over the same number of taxa and have the same taxa at their leaves.
+    fn has_same_taxa(&self, other: &Tree) -> bool {
+        self.num_leaves_init() == other.num_leaves_init()
+            && self.num_leaves() == other.num_leaves()
+            && self.leaf_taxa() == other.leaf_taxa()
+    }
+}
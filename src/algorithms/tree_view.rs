@@ -0,0 +1,263 @@
+//! Tree view module for explicitly interpreting trees as rooted or unrooted.
+//!
+//! The same [Tree] describes different things depending on whether its root is meaningful:
+//! a rooted tree is characterized by its clades, an unrooted tree by its splits.
+//! The plain functions of the other modules fix one interpretation
+//! ([robinson_foulds](crate::algorithms::robinson_foulds::robinson_foulds) and
+//! [compatibility](crate::algorithms::compatibility) unrooted, [consensus](crate::algorithms::consensus) rooted).
+//! Wrapping trees in a [TreeView] instead makes the interpretation explicit:
+//! - `TreeView::Rooted` / `TreeView::Unrooted`: Tree with its interpretation
+//! - `TreeView::robinson_foulds`, `TreeView::is_compatible_with`: Distance and compatibility of two views
+//! - `TreeView::majority_rule`, `TreeView::strict`, `TreeView::filter_compatible`: Functions on samples of views
+//! - `TreeViewError`: Mixed interpretations or otherwise incomparable trees
+
+use crate::algorithms::compatibility::compatible_with_splits;
+use crate::algorithms::consensus::consensus_from_counts;
+use crate::model::taxon_set::TaxonSet;
+use crate::model::tree::Tree;
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+use std::fmt;
+
+// =#========================================================================#=
+// TREE VIEW ERROR
+// =#========================================================================#=
+/// Error for views that cannot be compared or summarized together.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TreeViewError {
+    /// Rooted and unrooted views were mixed, e.g. a rooted tree compared with an unrooted one
+    MixedRooting,
+    /// Trees do not have the same number of leaves
+    DifferentNumLeaves { expected: usize, found: usize },
+    /// A sample without any tree was given where at least one is required
+    EmptySample,
+}
+
+impl fmt::Display for TreeViewError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TreeViewError::MixedRooting => write!(f, "Cannot mix rooted and unrooted trees"),
+            TreeViewError::DifferentNumLeaves { expected, found } => {
+                write!(f, "Trees must have the same number of leaves, expected {} but found {}", expected, found)
+            }
+            TreeViewError::EmptySample => write!(f, "At least one tree is required"),
+        }
+    }
+}
+
+impl Error for TreeViewError {}
+
+// =#========================================================================#=
+// TREE VIEW
+// =#========================================================================#=
+/// A tree together with whether its root is meaningful.
+///
+/// - `Rooted`: Compared by its non-trivial clades (at least two but not all taxa), see [Tree::clades]
+/// - `Unrooted`: Compared by its non-trivial splits, see [Tree::bipartitions]
+///
+/// # Example
+/// ```
+/// use nexus_parser::algorithms::tree_view::{TreeView, TreeViewError};
+/// use nexus_parser::io::parser::byte_parser::ByteParser;
+/// use nexus_parser::io::parser::newick::NewickParser;
+///
+/// let mut newick_parser = NewickParser::new().with_num_leaves(4);
+/// let tree_1 = newick_parser.parse(&mut ByteParser::from_str("((A,B),(C,D));")).unwrap();
+/// let tree_2 = newick_parser.parse(&mut ByteParser::from_str("(A,(B,(C,D)));")).unwrap();
+///
+/// // Same unrooted tree, but different rootings
+/// assert_eq!(TreeView::Unrooted(&tree_1).robinson_foulds(&TreeView::Unrooted(&tree_2)), Ok(0));
+/// assert_eq!(TreeView::Rooted(&tree_1).robinson_foulds(&TreeView::Rooted(&tree_2)), Ok(2));
+/// assert_eq!(TreeView::Rooted(&tree_1).robinson_foulds(&TreeView::Unrooted(&tree_2)), Err(TreeViewError::MixedRooting));
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub enum TreeView<'a> {
+    /// Tree whose root is meaningful, e.g. a time tree
+    Rooted(&'a Tree),
+    /// Tree whose root is arbitrary, e.g. from an unrooted analysis
+    Unrooted(&'a Tree),
+}
+
+impl<'a> TreeView<'a> {
+    /// Returns the viewed tree.
+    pub fn tree(&self) -> &'a Tree {
+        match self {
+            TreeView::Rooted(tree) | TreeView::Unrooted(tree) => tree,
+        }
+    }
+
+    /// Returns whether the tree is viewed as rooted.
+    pub fn is_rooted(&self) -> bool {
+        matches!(self, TreeView::Rooted(_))
+    }
+
+    /// Returns the view of the tree as marked, see [Tree::is_rooted].
+    pub fn of(tree: &'a Tree) -> TreeView<'a> {
+        if tree.is_rooted() {
+            TreeView::Rooted(tree)
+        } else {
+            TreeView::Unrooted(tree)
+        }
+    }
+
+    /// Returns views of all trees as marked, see [TreeView::of].
+    pub fn of_all(trees: &'a [Tree]) -> Vec<TreeView<'a>> {
+        trees.iter().map(TreeView::of).collect()
+    }
+
+    /// Returns views of all trees as rooted.
+    pub fn rooted_all(trees: &'a [Tree]) -> Vec<TreeView<'a>> {
+        trees.iter().map(TreeView::Rooted).collect()
+    }
+
+    /// Returns views of all trees as unrooted.
+    pub fn unrooted_all(trees: &'a [Tree]) -> Vec<TreeView<'a>> {
+        trees.iter().map(TreeView::Unrooted).collect()
+    }
+
+    /// Returns the non-trivial clades (rooted) or splits (unrooted) of the tree.
+    pub fn splits(&self) -> HashSet<TaxonSet> {
+        match self {
+            TreeView::Rooted(tree) => {
+                let num_taxa = tree.num_leaves_init();
+                tree.clades(num_taxa).into_iter()
+                    .filter(|clade| clade.len() > 1 && clade.len() < num_taxa)
+                    .collect()
+            }
+            TreeView::Unrooted(tree) => tree.bipartitions(),
+        }
+    }
+
+    /// Returns the maximum number of non-trivial clades (rooted) or splits (unrooted)
+    /// of a tree with `num_taxa` leaves, i.e. those of a binary tree.
+    fn max_num_splits(&self, num_taxa: usize) -> usize {
+        match self {
+            TreeView::Rooted(_) => num_taxa.saturating_sub(2),
+            TreeView::Unrooted(_) => num_taxa.saturating_sub(3),
+        }
+    }
+
+    // ============================================================================
+    // Distances and Compatibility (pub)
+    // ============================================================================
+    /// Returns the Robinson-Foulds distance of the two views,
+    /// i.e. the number of clades (rooted) or splits (unrooted) in exactly one of them.
+    ///
+    /// # Errors
+    /// Returns an error if one view is rooted and the other unrooted,
+    /// or the trees do not have the same number of leaves.
+    pub fn robinson_foulds(&self, other: &TreeView) -> Result<usize, TreeViewError> {
+        check_comparable(self, [other])?;
+        Ok(self.splits().symmetric_difference(&other.splits()).count())
+    }
+
+    /// Returns the Robinson-Foulds distance divided by its maximum for binary trees,
+    /// `2 (n - 2)` for rooted and `2 (n - 3)` for unrooted trees on `n` taxa,
+    /// or `0.0` if there are no non-trivial clades or splits.
+    ///
+    /// # Errors
+    /// Returns an error if the views are not comparable, see [TreeView::robinson_foulds].
+    pub fn normalized_robinson_foulds(&self, other: &TreeView) -> Result<f64, TreeViewError> {
+        let distance = self.robinson_foulds(other)?;
+        let max_num_splits = self.max_num_splits(self.tree().num_leaves_init());
+        if max_num_splits == 0 {
+            return Ok(0.0);
+        }
+        Ok(distance as f64 / (2 * max_num_splits) as f64)
+    }
+
+    /// Checks whether this view is compatible with the backbone,
+    /// i.e. whether each clade (rooted) or split (unrooted) of the backbone
+    /// is compatible with every one of this tree.
+    ///
+    /// # Errors
+    /// Returns an error if the views are not comparable, see [TreeView::robinson_foulds].
+    pub fn is_compatible_with(&self, backbone: &TreeView) -> Result<bool, TreeViewError> {
+        check_comparable(backbone, [self])?;
+        Ok(compatible_with_splits(&self.splits(), &backbone.splits()))
+    }
+
+    // ============================================================================
+    // Samples (pub)
+    // ============================================================================
+    /// Returns the trees of the sample compatible with the backbone
+    /// and the posterior mass they retain, see [filter_compatible](crate::algorithms::compatibility::filter_compatible).
+    ///
+    /// # Errors
+    /// Returns an error if a view of the sample is not comparable with the backbone.
+    pub fn filter_compatible(views: &[TreeView<'a>], backbone: &TreeView) -> Result<(Vec<&'a Tree>, f64), TreeViewError> {
+        check_comparable(backbone, views)?;
+        let backbone_splits = backbone.splits();
+
+        let compatible: Vec<&Tree> = views.iter()
+            .filter(|view| compatible_with_splits(&view.splits(), &backbone_splits))
+            .map(|view| view.tree())
+            .collect();
+
+        let posterior_mass = if views.is_empty() {
+            0.0
+        } else {
+            compatible.len() as f64 / views.len() as f64
+        };
+
+        Ok((compatible, posterior_mass))
+    }
+
+    /// Returns the majority-rule consensus tree of the sample, see [majority_rule](crate::algorithms::consensus::majority_rule).
+    ///
+    /// Unrooted views are summarized by their splits; the resulting tree is then
+    /// rooted at the leaf of taxon `0`, i.e. this leaf is a child of the root.
+    ///
+    /// # Errors
+    /// Returns an error if the sample is empty or mixes rooted and unrooted views
+    /// or trees with different numbers of leaves.
+    ///
+    /// # Panics
+    /// Panics if `threshold` is not in `[0.5, 1)`.
+    pub fn majority_rule(views: &[TreeView], threshold: f64) -> Result<Tree, TreeViewError> {
+        assert!((0.5..1.0).contains(&threshold), "Threshold {} not within [0.5, 1)", threshold);
+        view_consensus(views, |count, num_trees| count as f64 > threshold * num_trees as f64)
+    }
+
+    /// Returns the strict consensus tree of the sample, see [strict](crate::algorithms::consensus::strict)
+    /// and [TreeView::majority_rule] for unrooted views.
+    ///
+    /// # Errors
+    /// Returns an error if the sample is empty or mixes rooted and unrooted views
+    /// or trees with different numbers of leaves.
+    pub fn strict(views: &[TreeView]) -> Result<Tree, TreeViewError> {
+        view_consensus(views, |count, num_trees| count == num_trees)
+    }
+}
+
+// ============================================================================
+// Little Helpers
+// ============================================================================
+/// Checks that all other views have the rooting and number of leaves of the reference view.
+fn check_comparable<'v, 'a: 'v>(reference: &TreeView, others: impl IntoIterator<Item = &'v TreeView<'a>>) -> Result<(), TreeViewError> {
+    let expected = reference.tree().num_leaves_init();
+    for other in others {
+        if other.is_rooted() != reference.is_rooted() {
+            return Err(TreeViewError::MixedRooting);
+        }
+        let found = other.tree().num_leaves_init();
+        if found != expected {
+            return Err(TreeViewError::DifferentNumLeaves { expected, found });
+        }
+    }
+    Ok(())
+}
+
+fn view_consensus(views: &[TreeView], include: impl Fn(usize, usize) -> bool) -> Result<Tree, TreeViewError> {
+    let first = views.first().ok_or(TreeViewError::EmptySample)?;
+    check_comparable(first, views)?;
+
+    let mut counts: HashMap<TaxonSet, usize> = HashMap::new();
+    for view in views {
+        for split in view.splits() {
+            *counts.entry(split).or_insert(0) += 1;
+        }
+    }
+
+    Ok(consensus_from_counts(counts, first.tree().num_leaves_init(), views.len(), include))
+}
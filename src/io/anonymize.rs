@@ -0,0 +1,204 @@
+//! Anonymization module replacing taxon labels with pseudonyms.
+//!
+//! - `Anonymizer`: Stable mapping from labels to pseudonyms (e.g. `taxon_7`),
+//!   applied to [LeafLabelMap]s and node labels of trees, and written/read as TSV,
+//!   so files can be shared without disclosing (unpublished) taxon names.
+
+use crate::model::leaf_label_map::LeafLabelMap;
+use crate::model::tree::Tree;
+use std::collections::HashMap;
+use std::io;
+use std::io::{BufRead, Write};
+
+/// Default prefix of pseudonyms
+const DEFAULT_PREFIX: &str = "taxon_";
+
+// =#========================================================================#=
+// ANONYMIZER
+// =#========================================================================#=
+/// Replaces labels with pseudonyms `<prefix><number>`, numbered from 1 in order of assignment.
+///
+/// Pseudonyms are stable: the same label always gets the same pseudonym,
+/// also across files when continuing from a previously written mapping (see `from_mapping_tsv`).
+/// Labels of a [LeafLabelMap] get their pseudonyms in alphabetical order, so numbering
+/// does not depend on the order of taxa in a file.
+///
+/// Since trees reference leaf labels by [LabelIndex](crate::model::tree::LabelIndex),
+/// anonymizing the shared [LeafLabelMap] anonymizes all leaves; writing trees with the anonymized map
+/// (e.g. with [NexusWriter](crate::io::writer::nexus::NexusWriter)) thus anonymizes TAXA block,
+/// TRANSLATE command and Newick strings. Node labels of internal vertices are anonymized separately,
+/// except for numeric ones (e.g. support values).
+///
+/// # Example
+/// ```
+/// use nexus_parser::io::anonymize::Anonymizer;
+/// use nexus_parser::model::leaf_label_map::LeafLabelMap;
+///
+/// let mut labels = LeafLabelMap::new(2);
+/// labels.get_or_insert("Nestor notabilis");
+/// labels.get_or_insert("Apteryx owenii");
+///
+/// let mut anonymizer = Anonymizer::new();
+/// let anonymized = anonymizer.anonymize_label_map(&labels);
+///
+/// // Alphabetical assignment, same indices
+/// assert_eq!(anonymized.get_label(0), Some("taxon_2"));
+/// assert_eq!(anonymized.get_label(1), Some("taxon_1"));
+/// assert_eq!(anonymizer.original("taxon_2"), Some("Nestor notabilis"));
+/// ```
+#[derive(Debug, Clone)]
+pub struct Anonymizer {
+    prefix: String,
+    /// Map from original label to pseudonym
+    pseudonyms: HashMap<String, String>,
+    /// Map from pseudonym to original label
+    originals: HashMap<String, String>,
+    /// Original labels in order of assignment
+    order: Vec<String>,
+}
+
+impl Default for Anonymizer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Anonymizer {
+    /// Creates a new [Anonymizer] without any assigned pseudonyms and prefix `taxon_`.
+    pub fn new() -> Self {
+        Self {
+            prefix: DEFAULT_PREFIX.to_string(),
+            pseudonyms: HashMap::new(),
+            originals: HashMap::new(),
+            order: Vec::new(),
+        }
+    }
+
+    /// Sets the prefix of pseudonyms assigned from now on.
+    pub fn with_prefix(mut self, prefix: &str) -> Self {
+        self.prefix = prefix.to_string();
+        self
+    }
+
+    /// Reads a mapping previously written with `write_mapping_tsv`,
+    /// so that further labels get new pseudonyms while known ones keep theirs.
+    ///
+    /// # Errors
+    /// Returns an error if reading fails, a line does not have two columns,
+    /// or a label or pseudonym appears twice.
+    pub fn from_mapping_tsv<R: BufRead>(reader: R) -> io::Result<Self> {
+        let mut anonymizer = Anonymizer::new();
+
+        for (line_number, line) in reader.lines().enumerate() {
+            let line = line?;
+            let line = line.trim_end_matches('\r');
+            if line.trim().is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let Some((original, pseudonym)) = line.split_once('\t') else {
+                return Err(invalid_data(format!("Line {} does not have two tab-separated columns", line_number + 1)));
+            };
+            if anonymizer.pseudonyms.contains_key(original) || anonymizer.originals.contains_key(pseudonym) {
+                return Err(invalid_data(format!("Label or pseudonym assigned twice in line {}", line_number + 1)));
+            }
+            anonymizer.assign(original, pseudonym.to_string());
+        }
+
+        Ok(anonymizer)
+    }
+
+    /// Returns the pseudonym of the label, assigning the next one if the label is new.
+    pub fn pseudonym(&mut self, label: &str) -> &str {
+        if !self.pseudonyms.contains_key(label) {
+            // Skip pseudonyms already taken (e.g. by a mapping read before)
+            let mut number = self.order.len() + 1;
+            let mut pseudonym = format!("{}{}", self.prefix, number);
+            while self.originals.contains_key(&pseudonym) {
+                number += 1;
+                pseudonym = format!("{}{}", self.prefix, number);
+            }
+            self.assign(label, pseudonym);
+        }
+        &self.pseudonyms[label]
+    }
+
+    /// Returns the original label of a pseudonym, if assigned.
+    pub fn original(&self, pseudonym: &str) -> Option<&str> {
+        self.originals.get(pseudonym).map(String::as_str)
+    }
+
+    /// Returns the number of assigned pseudonyms.
+    pub fn num_pseudonyms(&self) -> usize {
+        self.order.len()
+    }
+
+    /// Returns a copy of the [LeafLabelMap] with all labels replaced by their pseudonyms,
+    /// keeping the [LabelIndex](crate::model::tree::LabelIndex) of every label.
+    ///
+    /// New labels are assigned pseudonyms in alphabetical order.
+    pub fn anonymize_label_map(&mut self, leaf_label_map: &LeafLabelMap) -> LeafLabelMap {
+        let mut sorted: Vec<&String> = leaf_label_map.labels().iter().collect();
+        sorted.sort_unstable();
+        for label in sorted {
+            self.pseudonym(label);
+        }
+
+        let mut anonymized = LeafLabelMap::new(leaf_label_map.num_labels());
+        for label in leaf_label_map.labels() {
+            anonymized.get_or_insert(&self.pseudonyms[label]);
+        }
+        anonymized
+    }
+
+    /// Replaces non-numeric node labels (e.g. clade names) of internal vertices and root by pseudonyms.
+    ///
+    /// Numeric node labels (e.g. support values) are kept. Leaves are not touched,
+    /// since their labels are anonymized via the [LeafLabelMap] (see `anonymize_label_map`).
+    pub fn anonymize_node_labels(&mut self, tree: &mut Tree) {
+        for index in 0..tree.num_vertices() {
+            if tree[index].support().is_some() {
+                continue;
+            }
+            let Some(label) = tree[index].node_label().map(str::to_string) else {
+                continue;
+            };
+            let pseudonym = self.pseudonym(&label).to_string();
+            tree[index].set_node_label(Some(pseudonym));
+        }
+    }
+
+    /// Anonymizes the node labels of all trees and returns the anonymized [LeafLabelMap] of the trees.
+    pub fn anonymize(&mut self, trees: &mut [Tree], leaf_label_map: &LeafLabelMap) -> LeafLabelMap {
+        let anonymized = self.anonymize_label_map(leaf_label_map);
+        for tree in trees.iter_mut() {
+            self.anonymize_node_labels(tree);
+        }
+        anonymized
+    }
+
+    /// Writes the mapping as TSV, one `label<TAB>pseudonym` line per label in order of assignment.
+    ///
+    /// Keep this file private; it can be read again with `from_mapping_tsv`.
+    pub fn write_mapping_tsv<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        for original in &self.order {
+            writeln!(writer, "{}\t{}", original, self.pseudonyms[original])?;
+        }
+        writer.flush()
+    }
+}
+
+// ============================================================================
+// Little Helpers
+// ============================================================================
+impl Anonymizer {
+    fn assign(&mut self, label: &str, pseudonym: String) {
+        self.originals.insert(pseudonym.clone(), label.to_string());
+        self.pseudonyms.insert(label.to_string(), pseudonym);
+        self.order.push(label.to_string());
+    }
+}
+
+fn invalid_data(msg: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
@@ -0,0 +1,510 @@
+//! Cache module for reloading parsed tree samples much faster than re-parsing them.
+//!
+//! - `write_cache`, `write_cache_to`: Write trees and their [LeafLabelMap] in a compact binary layout
+//! - `read_cache`, `read_cache_from`: Read them back, verifying version and checksum
+//! - `LengthPrecision`: Branch lengths as `f64` (exact) or `f32` (half the size)
+//! - `CacheError`: Errors when reading a cache
+//!
+//! # Layout (version 1)
+//! All integers are unsigned LEB128 varints, strings are their length followed by UTF-8 bytes,
+//! and options are a presence byte (`0` or `1`) followed by the value.
+//! - Header: magic bytes `PHYC`, version byte, precision byte (`0` for `f64`, `1` for `f32`)
+//! - Labels: number of labels, then labels in order of their indices
+//! - Trees: number of trees, then per tree its number of leaves, rooted flag, optional name and metadata,
+//!   root index, vertices in arena order, vertex and branch annotations (sorted by vertex index)
+//! - Vertex: tag (`0` root, `1` internal, `2` leaf); parent (non-root); label index (leaf) or children;
+//!   optional branch length (non-root) as little-endian float; optional node label (non-leaf)
+//! - Annotation: raw content and whether it is parsed (entries are parsed again when reading)
+//! - Trailer: FNV-1a checksum over all preceding bytes, as little-endian `u64`
+
+use crate::model::annotation::Annotation;
+use crate::model::leaf_label_map::LeafLabelMap;
+use crate::model::tree::{Tree, TreeIndex};
+use crate::model::vertex::{BranchLength, Vertex};
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+#[cfg(feature = "fs")]
+use std::fs::File;
+use std::io;
+use std::io::{Read, Write};
+#[cfg(feature = "fs")]
+use std::io::{BufReader, BufWriter};
+#[cfg(feature = "fs")]
+use std::path::Path;
+
+/// Magic bytes at the start of every cache file
+const MAGIC: &[u8; 4] = b"PHYC";
+
+/// Version of the layout written by this crate
+const VERSION: u8 = 1;
+
+/// Tags of the vertex kinds
+const TAG_ROOT: u8 = 0;
+const TAG_INTERNAL: u8 = 1;
+const TAG_LEAF: u8 = 2;
+
+/// Offset basis and prime of the 64-bit FNV-1a checksum
+const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0100_0000_01b3;
+
+// =#========================================================================#=
+// OPTIONS AND ERRORS
+// =#========================================================================#=
+/// Precision of branch lengths in a cache.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LengthPrecision {
+    /// Exact 8-byte lengths (default)
+    #[default]
+    F64,
+    /// 4-byte lengths, rounded to about 7 significant digits
+    F32,
+}
+
+/// Error when reading a cache.
+#[derive(Debug)]
+pub enum CacheError {
+    /// Reading the cache failed (including a truncated file)
+    Io(io::Error),
+    /// The file does not start with the magic bytes of a cache
+    NotACache,
+    /// The cache was written with another version of the layout
+    UnsupportedVersion(u8),
+    /// The checksum does not match the content, e.g. after a partial write or disk corruption
+    ChecksumMismatch,
+    /// The content is inconsistent, e.g. a vertex refers to a vertex out of bounds
+    Corrupt(String),
+}
+
+impl fmt::Display for CacheError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CacheError::Io(e) => write!(f, "Failed to read cache: {}", e),
+            CacheError::NotACache => write!(f, "Not a tree sample cache (missing magic bytes)"),
+            CacheError::UnsupportedVersion(version) => {
+                write!(f, "Unsupported cache version {}, expected {}", version, VERSION)
+            }
+            CacheError::ChecksumMismatch => write!(f, "Cache checksum mismatch, file is corrupt"),
+            CacheError::Corrupt(msg) => write!(f, "Corrupt cache: {}", msg),
+        }
+    }
+}
+
+impl Error for CacheError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            CacheError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for CacheError {
+    fn from(e: io::Error) -> Self {
+        CacheError::Io(e)
+    }
+}
+
+// =#========================================================================#=
+// WRITING
+// =#========================================================================#=
+/// Writes the trees and their labels as cache file.
+///
+/// # Arguments
+/// * `path` - Path of the cache file, created or truncated
+/// * `trees` - Trees whose leaves refer to `leaf_label_map`
+/// * `leaf_label_map` - Labels of the trees
+/// * `precision` - Precision of the stored branch lengths ([LengthPrecision::F64] keeps them exact)
+///
+/// # Errors
+/// Returns an error if the file cannot be created or written.
+///
+/// # Example
+/// ```no_run
+/// use nexus_parser::io::cache::{read_cache, write_cache, LengthPrecision};
+/// use nexus_parser::parse_nexus_file;
+///
+/// let (trees, labels) = parse_nexus_file("posterior.trees").unwrap();
+/// write_cache("posterior.cache", &trees, &labels, LengthPrecision::F64).unwrap();
+///
+/// // Later runs skip parsing
+/// let (trees, labels) = read_cache("posterior.cache").unwrap();
+/// ```
+#[cfg(feature = "fs")]
+pub fn write_cache(path: impl AsRef<Path>, trees: &[Tree], leaf_label_map: &LeafLabelMap,
+                   precision: LengthPrecision) -> io::Result<()> {
+    write_cache_to(BufWriter::new(File::create(path)?), trees, leaf_label_map, precision)
+}
+
+/// Writes the trees and their labels as cache to the given writer, see the [module](self) for the layout.
+///
+/// # Arguments
+/// * `writer` - Destination of the cache (buffer it, e.g. with [BufWriter], for files)
+/// * `trees` - Trees whose leaves refer to `leaf_label_map`
+/// * `leaf_label_map` - Labels of the trees
+/// * `precision` - Precision of the stored branch lengths
+///
+/// # Errors
+/// Returns an error if writing fails.
+pub fn write_cache_to<W: Write>(writer: W, trees: &[Tree], leaf_label_map: &LeafLabelMap,
+                                precision: LengthPrecision) -> io::Result<()> {
+    let mut writer = ChecksumWriter { inner: writer, checksum: FNV_OFFSET_BASIS };
+    writer.write_all(MAGIC)?;
+    writer.write_all(&[VERSION, precision as u8])?;
+
+    write_varint(&mut writer, leaf_label_map.num_labels() as u64)?;
+    for label in leaf_label_map.labels() {
+        write_string(&mut writer, label)?;
+    }
+
+    write_varint(&mut writer, trees.len() as u64)?;
+    for tree in trees {
+        write_tree(&mut writer, tree, precision)?;
+    }
+
+    let checksum = writer.checksum;
+    writer.inner.write_all(&checksum.to_le_bytes())?;
+    writer.inner.flush()
+}
+
+/// Writes a single tree.
+fn write_tree<W: Write>(writer: &mut W, tree: &Tree, precision: LengthPrecision) -> io::Result<()> {
+    write_varint(writer, tree.num_leaves_init() as u64)?;
+    writer.write_all(&[tree.is_rooted() as u8])?;
+    write_option(writer, tree.name(), |writer, name| write_string(writer, name))?;
+    write_option(writer, tree.metadata(), write_annotation)?;
+    write_varint(writer, tree.root().index() as u64)?;
+
+    write_varint(writer, tree.num_vertices() as u64)?;
+    for index in 0..tree.num_vertices() {
+        let vertex = &tree[index];
+        match vertex {
+            Vertex::Root { children, .. } => {
+                writer.write_all(&[TAG_ROOT])?;
+                write_indices(writer, children)?;
+            }
+            Vertex::Internal { parent, children, .. } => {
+                writer.write_all(&[TAG_INTERNAL])?;
+                write_varint(writer, *parent as u64)?;
+                write_indices(writer, children)?;
+            }
+            Vertex::Leaf { parent, label_index, .. } => {
+                writer.write_all(&[TAG_LEAF])?;
+                write_varint(writer, *parent as u64)?;
+                write_varint(writer, *label_index as u64)?;
+            }
+        }
+        if !vertex.is_root() {
+            write_option(writer, vertex.branch_length(), |writer, length| match precision {
+                LengthPrecision::F64 => writer.write_all(&length.to_le_bytes()),
+                LengthPrecision::F32 => writer.write_all(&(*length as f32).to_le_bytes()),
+            })?;
+        }
+        if !vertex.is_leaf() {
+            write_option(writer, vertex.node_label(), |writer, label| write_string(writer, label))?;
+        }
+    }
+
+    for annotation_of in [Tree::annotation, Tree::branch_annotation] {
+        let annotated: Vec<(TreeIndex, &Annotation)> = (0..tree.num_vertices())
+            .filter_map(|index| annotation_of(tree, index).map(|annotation| (index, annotation)))
+            .collect();
+        write_varint(writer, annotated.len() as u64)?;
+        for (index, annotation) in annotated {
+            write_varint(writer, index as u64)?;
+            write_annotation(writer, annotation)?;
+        }
+    }
+    Ok(())
+}
+
+// =#========================================================================#=
+// READING
+// =#========================================================================#=
+/// Reads trees and their labels from a cache file written by [write_cache] (or [write_cache_to]).
+///
+/// # Errors
+/// Returns a [CacheError] if the file cannot be read, is not a cache of this version,
+/// or its checksum or content is invalid.
+#[cfg(feature = "fs")]
+pub fn read_cache(path: impl AsRef<Path>) -> Result<(Vec<Tree>, LeafLabelMap), CacheError> {
+    read_cache_from(BufReader::new(File::open(path)?))
+}
+
+/// Reads trees and their labels from a cache, see [read_cache].
+///
+/// # Errors
+/// Returns a [CacheError] if reading fails, the content is not a cache of this version,
+/// or its checksum or content is invalid.
+pub fn read_cache_from<R: Read>(reader: R) -> Result<(Vec<Tree>, LeafLabelMap), CacheError> {
+    let mut reader = ChecksumReader { inner: reader, checksum: FNV_OFFSET_BASIS };
+
+    let mut magic = [0u8; 4];
+    reader.read_exact(&mut magic)?;
+    if &magic != MAGIC {
+        return Err(CacheError::NotACache);
+    }
+    let version = read_byte(&mut reader)?;
+    if version != VERSION {
+        return Err(CacheError::UnsupportedVersion(version));
+    }
+    let precision = match read_byte(&mut reader)? {
+        0 => LengthPrecision::F64,
+        1 => LengthPrecision::F32,
+        other => return Err(CacheError::Corrupt(format!("unknown length precision {}", other))),
+    };
+
+    let num_labels = read_varint(&mut reader)? as usize;
+    let mut leaf_label_map = LeafLabelMap::new(num_labels.min(1 << 20));
+    for _ in 0..num_labels {
+        leaf_label_map.insert(read_string(&mut reader)?);
+    }
+
+    let num_trees = read_varint(&mut reader)? as usize;
+    let mut trees = Vec::with_capacity(num_trees.min(1 << 16));
+    for _ in 0..num_trees {
+        trees.push(read_tree(&mut reader, precision, num_labels)?);
+    }
+
+    let checksum = reader.checksum;
+    let mut stored = [0u8; 8];
+    reader.inner.read_exact(&mut stored)?;
+    if u64::from_le_bytes(stored) != checksum {
+        return Err(CacheError::ChecksumMismatch);
+    }
+
+    Ok((trees, leaf_label_map))
+}
+
+/// Reads a single tree, checking that all indices are in bounds.
+fn read_tree<R: Read>(reader: &mut R, precision: LengthPrecision, num_labels: usize) -> Result<Tree, CacheError> {
+    let num_leaves = read_varint(reader)? as usize;
+    if num_leaves == 0 {
+        return Err(CacheError::Corrupt("tree without leaves".to_string()));
+    }
+    let rooted = read_byte(reader)? != 0;
+    let name = read_option(reader, read_string)?;
+    let metadata = read_option(reader, read_annotation)?;
+    let root_index = read_varint(reader)? as usize;
+
+    let num_vertices = read_varint(reader)? as usize;
+    let check_index = |index: u64| -> Result<TreeIndex, CacheError> {
+        if (index as usize) < num_vertices {
+            Ok(index as usize)
+        } else {
+            Err(CacheError::Corrupt(format!("vertex index {} out of bounds for {} vertices", index, num_vertices)))
+        }
+    };
+    check_index(root_index as u64)?;
+
+    let mut vertices = Vec::with_capacity(num_vertices.min(1 << 20));
+    for index in 0..num_vertices {
+        let tag = read_byte(reader)?;
+        let parent = match tag {
+            TAG_ROOT => None,
+            _ => Some(check_index(read_varint(reader)?)?),
+        };
+        let mut vertex = match tag {
+            TAG_ROOT | TAG_INTERNAL => {
+                let num_children = read_varint(reader)? as usize;
+                let children = (0..num_children)
+                    .map(|_| check_index(read_varint(reader)?))
+                    .collect::<Result<Vec<_>, _>>()?;
+                if tag == TAG_ROOT {
+                    Vertex::new_root(index, children)
+                } else {
+                    Vertex::new_internal(index, children, None)
+                }
+            }
+            TAG_LEAF => {
+                let label_index = read_varint(reader)? as usize;
+                if label_index >= num_labels {
+                    return Err(CacheError::Corrupt(format!("label index {} out of bounds for {} labels", label_index, num_labels)));
+                }
+                Vertex::new_leaf(index, None, label_index)
+            }
+            other => return Err(CacheError::Corrupt(format!("unknown vertex tag {}", other))),
+        };
+        if let Some(parent) = parent {
+            vertex.set_parent(parent);
+            let length = read_option(reader, |reader| read_length(reader, precision))?;
+            vertex.set_branch_length(length);
+        }
+        if !vertex.is_leaf() {
+            vertex.set_node_label(read_option(reader, read_string)?);
+        }
+        vertices.push(vertex);
+    }
+    if !vertices[root_index].is_root() {
+        return Err(CacheError::Corrupt(format!("root index {} is not a root", root_index)));
+    }
+
+    let mut annotation_maps: [HashMap<TreeIndex, Annotation>; 2] = Default::default();
+    for annotations in &mut annotation_maps {
+        let num_annotated = read_varint(reader)? as usize;
+        for _ in 0..num_annotated {
+            let index = check_index(read_varint(reader)?)?;
+            annotations.insert(index, read_annotation(reader)?);
+        }
+    }
+    let [annotations, branch_annotations] = annotation_maps;
+
+    let mut tree = Tree::new(num_leaves).with_rooted(rooted);
+    if let Some(name) = name {
+        tree = tree.with_name(name);
+    }
+    tree.set_metadata(metadata);
+    tree.replace_vertices(vertices, root_index, annotations, branch_annotations);
+    Ok(tree)
+}
+
+/// Reads a branch length of the given precision, rejecting invalid lengths.
+fn read_length<R: Read>(reader: &mut R, precision: LengthPrecision) -> Result<BranchLength, CacheError> {
+    let length = match precision {
+        LengthPrecision::F64 => {
+            let mut bytes = [0u8; 8];
+            reader.read_exact(&mut bytes)?;
+            f64::from_le_bytes(bytes)
+        }
+        LengthPrecision::F32 => {
+            let mut bytes = [0u8; 4];
+            reader.read_exact(&mut bytes)?;
+            f32::from_le_bytes(bytes) as f64
+        }
+    };
+    BranchLength::try_new(length).map_err(|error| CacheError::Corrupt(format!("invalid branch length: {}", error)))
+}
+
+// =#========================================================================#=
+// LITTLE HELPERS
+// =#========================================================================#=
+/// Writer computing the checksum of all bytes written through it.
+struct ChecksumWriter<W> {
+    inner: W,
+    checksum: u64,
+}
+
+impl<W: Write> Write for ChecksumWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        self.checksum = fnv1a(self.checksum, &buf[..written]);
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Reader computing the checksum of all bytes read through it.
+struct ChecksumReader<R> {
+    inner: R,
+    checksum: u64,
+}
+
+impl<R: Read> Read for ChecksumReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        self.checksum = fnv1a(self.checksum, &buf[..read]);
+        Ok(read)
+    }
+}
+
+/// Continues the FNV-1a hash with the given bytes.
+fn fnv1a(mut hash: u64, bytes: &[u8]) -> u64 {
+    for &byte in bytes {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(FNV_PRIME);
+    }
+    hash
+}
+
+fn write_varint<W: Write>(writer: &mut W, mut value: u64) -> io::Result<()> {
+    let mut bytes = [0u8; 10];
+    let mut len = 0;
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if value == 0 {
+            bytes[len] = byte;
+            len += 1;
+            break;
+        }
+        bytes[len] = byte | 0x80;
+        len += 1;
+    }
+    writer.write_all(&bytes[..len])
+}
+
+fn read_varint<R: Read>(reader: &mut R) -> Result<u64, CacheError> {
+    let mut value = 0u64;
+    for shift in (0..64).step_by(7) {
+        let byte = read_byte(reader)?;
+        value |= ((byte & 0x7f) as u64) << shift;
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+    Err(CacheError::Corrupt("varint too long".to_string()))
+}
+
+fn read_byte<R: Read>(reader: &mut R) -> io::Result<u8> {
+    let mut byte = [0u8; 1];
+    reader.read_exact(&mut byte)?;
+    Ok(byte[0])
+}
+
+fn write_indices<W: Write>(writer: &mut W, indices: &[TreeIndex]) -> io::Result<()> {
+    write_varint(writer, indices.len() as u64)?;
+    for &index in indices {
+        write_varint(writer, index as u64)?;
+    }
+    Ok(())
+}
+
+fn write_string<W: Write>(writer: &mut W, s: &str) -> io::Result<()> {
+    write_varint(writer, s.len() as u64)?;
+    writer.write_all(s.as_bytes())
+}
+
+fn read_string<R: Read>(reader: &mut R) -> Result<String, CacheError> {
+    let len = read_varint(reader)? as usize;
+    let mut bytes = Vec::with_capacity(len.min(1 << 16));
+    reader.take(len as u64).read_to_end(&mut bytes)?;
+    if bytes.len() != len {
+        return Err(CacheError::Io(io::ErrorKind::UnexpectedEof.into()));
+    }
+    String::from_utf8(bytes).map_err(|_| CacheError::Corrupt("string is not valid UTF-8".to_string()))
+}
+
+fn write_annotation<W: Write>(writer: &mut W, annotation: &Annotation) -> io::Result<()> {
+    write_string(writer, annotation.raw())?;
+    writer.write_all(&[annotation.is_parsed() as u8])
+}
+
+fn read_annotation<R: Read>(reader: &mut R) -> Result<Annotation, CacheError> {
+    let raw = read_string(reader)?;
+    Ok(match read_byte(reader)? {
+        0 => Annotation::from_raw(raw),
+        _ => Annotation::parse(&raw),
+    })
+}
+
+fn write_option<W: Write, T>(writer: &mut W, value: Option<T>,
+                             write: impl FnOnce(&mut W, T) -> io::Result<()>) -> io::Result<()> {
+    match value {
+        Some(value) => {
+            writer.write_all(&[1])?;
+            write(writer, value)
+        }
+        None => writer.write_all(&[0]),
+    }
+}
+
+fn read_option<R: Read, T>(reader: &mut R,
+                           read: impl FnOnce(&mut R) -> Result<T, CacheError>) -> Result<Option<T>, CacheError> {
+    match read_byte(reader)? {
+        0 => Ok(None),
+        _ => read(reader).map(Some),
+    }
+}
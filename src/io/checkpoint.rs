@@ -0,0 +1,139 @@
+//! Checkpoint module for resuming long-running analyses after a crash or abort.
+//!
+//! - `Checkpoint`: File to which an analysis periodically saves its progress (atomically)
+//!   and from which it resumes when rerun
+//! - `Checkpoint::fingerprint`: Identifies a tree sample, so progress is not resumed for another sample
+//!
+//! Analyses define the content of their checkpoints, see e.g.
+//! [distance_matrix_checkpointed](crate::algorithms::distance_matrix::distance_matrix_checkpointed).
+
+use crate::io::writer::newick::NewickStyle;
+use crate::model::tree::Tree;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+/// Default minimum time between two saves of a checkpoint
+const DEFAULT_INTERVAL: Duration = Duration::from_secs(60);
+
+// =#========================================================================#=
+// CHECKPOINT
+// =#========================================================================#=
+/// File to which an analysis saves its progress at most once per interval.
+///
+/// Saving writes a temporary file next to the checkpoint and renames it,
+/// so a crash while saving leaves the previous checkpoint intact.
+///
+/// # Example
+/// ```no_run
+/// use nexus_parser::algorithms::distance_matrix::distance_matrix_checkpointed;
+/// use nexus_parser::algorithms::robinson_foulds::robinson_foulds;
+/// use nexus_parser::io::checkpoint::Checkpoint;
+/// use nexus_parser::parse_nexus_file;
+/// use std::time::Duration;
+///
+/// let (trees, _) = parse_nexus_file("phylo.trees").unwrap();
+/// let mut checkpoint = Checkpoint::new("rf_matrix.ckpt").with_interval(Duration::from_secs(600));
+///
+/// // Rerunning after a crash continues from the last saved row
+/// let matrix = distance_matrix_checkpointed(&trees, |a, b| robinson_foulds(a, b) as f64, &mut checkpoint).unwrap();
+/// ```
+#[derive(Debug)]
+pub struct Checkpoint {
+    path: PathBuf,
+    interval: Duration,
+    last_saved: Instant,
+}
+
+impl Checkpoint {
+    /// Creates a checkpoint at the given path, saved at most once a minute.
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            interval: DEFAULT_INTERVAL,
+            last_saved: Instant::now(),
+        }
+    }
+
+    /// Sets the minimum time between two saves (`Duration::ZERO` saves whenever asked to).
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Returns the path of the checkpoint file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns whether the interval has passed since the last save (or creation).
+    pub fn is_due(&self) -> bool {
+        self.last_saved.elapsed() >= self.interval
+    }
+
+    /// Returns the content of the checkpoint file, or `None` if there is none.
+    ///
+    /// # Errors
+    /// Returns an error if the file exists but cannot be read.
+    pub fn load(&self) -> io::Result<Option<String>> {
+        match fs::read_to_string(&self.path) {
+            Ok(content) => Ok(Some(content)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Saves the content atomically, replacing any previous checkpoint.
+    ///
+    /// # Errors
+    /// Returns an error if the file cannot be written.
+    pub fn save(&mut self, content: &str) -> io::Result<()> {
+        let mut temp_path = self.path.clone().into_os_string();
+        temp_path.push(".tmp");
+        fs::write(&temp_path, content)?;
+        fs::rename(&temp_path, &self.path)?;
+        self.last_saved = Instant::now();
+        Ok(())
+    }
+
+    /// Saves the content created by `content` if the checkpoint [is due](Checkpoint::is_due).
+    ///
+    /// # Returns
+    /// Whether the checkpoint was saved.
+    ///
+    /// # Errors
+    /// Returns an error if the file cannot be written.
+    pub fn save_if_due(&mut self, content: impl FnOnce() -> String) -> io::Result<bool> {
+        if !self.is_due() {
+            return Ok(false);
+        }
+        self.save(&content())?;
+        Ok(true)
+    }
+
+    /// Removes the checkpoint file (if any), e.g. once the analysis completed.
+    ///
+    /// # Errors
+    /// Returns an error if the file exists but cannot be removed.
+    pub fn remove(&self) -> io::Result<()> {
+        match fs::remove_file(&self.path) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        }
+    }
+
+    /// Returns a fingerprint of the trees (their topologies and branch lengths, in order),
+    /// stable across runs and platforms, to store in and compare with a checkpoint.
+    pub fn fingerprint(trees: &[Tree]) -> u64 {
+        // FNV-1a, since the hash of std is not guaranteed to be stable across releases
+        let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+        for tree in trees {
+            for byte in tree.to_newick(&NewickStyle::ZeroIndexed, None).bytes() {
+                hash ^= byte as u64;
+                hash = hash.wrapping_mul(0x0100_0000_01b3);
+            }
+        }
+        hash
+    }
+}
@@ -0,0 +1,122 @@
+//! Compression module for reading compressed tree files transparently.
+//!
+//! - `Compression`: Compression format, detected from the magic bytes at the start of the input
+//! - `decompressed_reader`: Wraps a reader to decompress on the fly (if compressed)
+//! - `open_decompressed`: Opens a file for reading, decompressing on the fly (if compressed)
+//!
+//! Decoders are behind the features `gzip` (including multi-member files like bgzip), `xz` and `zstd`;
+//! reading a compressed input without the respective feature returns an error naming it.
+
+#[cfg(feature = "fs")]
+use std::fs::File;
+use std::io;
+use std::io::BufRead;
+#[cfg(feature = "fs")]
+use std::path::Path;
+
+/// Magic bytes at the start of gzip input
+const GZIP_MAGIC: &[u8] = &[0x1f, 0x8b];
+
+/// Magic bytes at the start of xz input
+const XZ_MAGIC: &[u8] = &[0xfd, b'7', b'z', b'X', b'Z', 0x00];
+
+/// Magic bytes at the start of zstd input
+const ZSTD_MAGIC: &[u8] = &[0x28, 0xb5, 0x2f, 0xfd];
+
+// =#========================================================================#=
+// COMPRESSION
+// =#========================================================================#=
+/// Compression format of an input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compression {
+    /// Plain, uncompressed input
+    None,
+    /// gzip (`.gz`), possibly of several members (e.g. bgzip)
+    Gzip,
+    /// xz (`.xz`)
+    Xz,
+    /// Zstandard (`.zst`)
+    Zstd,
+}
+
+impl Compression {
+    /// Detects the compression format from the first bytes of an input (at least 6 bytes to detect all).
+    ///
+    /// # Example
+    /// ```
+    /// use nexus_parser::io::compression::Compression;
+    ///
+    /// assert_eq!(Compression::detect(&[0x1f, 0x8b, 0x08, 0x00]), Compression::Gzip);
+    /// assert_eq!(Compression::detect(b"#NEXUS"), Compression::None);
+    /// ```
+    pub fn detect(bytes: &[u8]) -> Self {
+        if bytes.starts_with(GZIP_MAGIC) {
+            Compression::Gzip
+        } else if bytes.starts_with(XZ_MAGIC) {
+            Compression::Xz
+        } else if bytes.starts_with(ZSTD_MAGIC) {
+            Compression::Zstd
+        } else {
+            Compression::None
+        }
+    }
+
+    /// Returns the name of the feature required to decompress this format (`None` if plain).
+    pub fn feature(&self) -> Option<&'static str> {
+        match self {
+            Compression::None => None,
+            Compression::Gzip => Some("gzip"),
+            Compression::Xz => Some("xz"),
+            Compression::Zstd => Some("zstd"),
+        }
+    }
+}
+
+// =#========================================================================#=
+// DECOMPRESSION
+// =#========================================================================#=
+/// Returns a reader over the decompressed content of the given reader,
+/// or over the content itself if it is not compressed.
+///
+/// # Errors
+/// Returns an error if the start of the input cannot be read, or it is compressed
+/// in a format whose feature is not enabled (of kind [io::ErrorKind::Unsupported]).
+///
+/// # Example
+/// ```
+/// use nexus_parser::io::compression::decompressed_reader;
+/// use std::io::Read;
+///
+/// let mut content = String::new();
+/// decompressed_reader(&b"#NEXUS\n"[..]).unwrap().read_to_string(&mut content).unwrap();
+/// assert_eq!(content, "#NEXUS\n");
+/// ```
+pub fn decompressed_reader<'a, R: BufRead + Send + 'a>(mut reader: R) -> io::Result<Box<dyn BufRead + Send + 'a>> {
+    // Peek without consuming; the first fill of a buffered reader holds more than the magic bytes
+    let compression = Compression::detect(reader.fill_buf()?);
+    match compression {
+        Compression::None => Ok(Box::new(reader)),
+        #[cfg(feature = "gzip")]
+        Compression::Gzip => Ok(Box::new(io::BufReader::new(flate2::bufread::MultiGzDecoder::new(reader)))),
+        #[cfg(feature = "xz")]
+        Compression::Xz => Ok(Box::new(io::BufReader::new(xz2::bufread::XzDecoder::new_multi_decoder(reader)))),
+        #[cfg(feature = "zstd")]
+        Compression::Zstd => Ok(Box::new(io::BufReader::new(zstd::stream::read::Decoder::with_buffer(reader)?))),
+        #[allow(unreachable_patterns)]
+        _ => Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            format!("Input is {:?} compressed, enable feature `{}` to read it", compression, compression.feature().unwrap()),
+        )),
+    }
+}
+
+/// Opens the file at the given path for buffered reading, decompressing it on the fly if compressed
+/// (detected from its content, not its extension), see [decompressed_reader].
+///
+/// # Errors
+/// Returns an error if the file cannot be opened or read, or it is compressed
+/// in a format whose feature is not enabled.
+#[cfg(feature = "fs")]
+pub fn open_decompressed(path: impl AsRef<Path>) -> io::Result<Box<dyn BufRead + Send>> {
+    decompressed_reader(io::BufReader::new(File::open(path)?))
+}
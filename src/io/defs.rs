@@ -1,12 +1,19 @@
 /// Nexus label parsing delimiters: parentheses, comma, colon, semicolon, whitespace
 pub(crate) const NEXUS_LABEL_DELIMITERS: &[u8] = b" ,;\t\n\r";
 
+/// Translate key and label delimiters: as NEXUS labels, plus the start of a comment
+pub(crate) const TRANSLATE_DELIMITERS: &[u8] = b" ,;\t\n\r[";
+
 pub(crate) const NEXUS_HEADER: &[u8] = b"#NEXUS";
 
 pub(crate) const BLOCK_BEGIN: &[u8] = b"Begin";
 
 pub(crate) const BLOCK_END: &[u8] = b"End;";
 
+pub(crate) const END: &[u8] = b"End";
+
+pub(crate) const ENDBLOCK: &[u8] = b"Endblock";
+
 // Taxa block
 pub(crate) const TAXA: &[u8] = b"taxa;";
 
@@ -23,7 +30,7 @@ pub(crate) const TRANSLATE: &[u8] = b"Translate";
 
 pub(crate) const TREE: &[u8] = b"tree";
 
-/// TODO
+/// Block types of NEXUS files (only TAXA and TREES blocks are parsed, others are skipped)
 #[derive(Debug, PartialEq, Clone)]
 pub enum NexusBlock {
     Taxa,
@@ -0,0 +1,53 @@
+//! Format module for recognizing tree file formats by their content.
+//!
+//! - `TreeFileFormat`: Formats of tree files and their detection from the first non-whitespace bytes
+
+use crate::io::defs::NEXUS_HEADER;
+
+/// UTF-8 byte order mark, written by some (Windows) editors at the start of text files
+pub const UTF8_BOM: &[u8] = b"\xEF\xBB\xBF";
+
+// =#========================================================================#=
+// TREE FILE FORMAT
+// =#========================================================================#=
+/// Format of a file containing trees.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TreeFileFormat {
+    /// NEXUS file starting with `#NEXUS`
+    Nexus,
+    /// Plain Newick trees, starting with `(` (or a comment `[...]`)
+    Newick,
+    /// XML based format like NeXML or phyloXML, starting with `<`
+    Xml,
+}
+
+impl TreeFileFormat {
+    /// Detects the format from the first non-whitespace bytes of the content
+    /// (after a UTF-8 byte order mark, if any).
+    ///
+    /// # Returns
+    /// The detected format, or `None` if the content does not start like any known format
+    ///
+    /// # Example
+    /// ```
+    /// use nexus_parser::io::format::TreeFileFormat;
+    ///
+    /// assert_eq!(TreeFileFormat::detect(b"#NEXUS\nBegin trees;"), Some(TreeFileFormat::Nexus));
+    /// assert_eq!(TreeFileFormat::detect(b"\n  ((A,B),C);"), Some(TreeFileFormat::Newick));
+    /// assert_eq!(TreeFileFormat::detect(b"<?xml version=\"1.0\"?>"), Some(TreeFileFormat::Xml));
+    /// assert_eq!(TreeFileFormat::detect(b"A,B,C"), None);
+    /// ```
+    pub fn detect(content: &[u8]) -> Option<Self> {
+        let content = content.strip_prefix(UTF8_BOM).unwrap_or(content);
+        let start = content.iter().position(|b| !b.is_ascii_whitespace())?;
+        let content = &content[start..];
+
+        match content[0] {
+            b'#' if content.len() >= NEXUS_HEADER.len()
+                && content[..NEXUS_HEADER.len()].eq_ignore_ascii_case(NEXUS_HEADER) => Some(TreeFileFormat::Nexus),
+            b'(' | b'[' => Some(TreeFileFormat::Newick),
+            b'<' => Some(TreeFileFormat::Xml),
+            _ => None,
+        }
+    }
+}
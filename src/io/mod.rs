@@ -0,0 +1,17 @@
+/// NEXUS and Newick format parsers
+pub mod parser;
+/// NEXUS and Newick format writers
+pub mod writer;
+/// Detection of tree file formats
+pub mod format;
+/// Transparent decompression of gzip, xz and zstd input
+pub mod compression;
+/// Anonymization of taxon labels for sharing files
+pub mod anonymize;
+/// Checkpoints for resuming long-running analyses
+#[cfg(feature = "fs")]
+pub mod checkpoint;
+/// Compact binary cache of parsed tree samples for fast reloading
+pub mod cache;
+/// NEXUS keywords and block definitions shared by parser and writer
+pub(crate) mod defs;
@@ -1,27 +1,32 @@
-use crate::parser::byte_parser::ConsumeMode::Inclusive;
-use crate::parser::byte_source::{ByteSource, InMemoryByteSource};
-use crate::parser::parsing_error::ParsingError;
+use crate::io::parser::byte_parser::ConsumeMode::Inclusive;
+use crate::io::parser::byte_source::{ByteSource, InMemoryByteSource};
+use crate::io::parser::parsing_error::ParsingError;
+use memchr::{memchr, memchr2, memchr3};
+use std::borrow::Cow;
 
 /// A byte-by-byte parser for ASCII text with support for peeking, consuming, and pattern matching.
 ///
 /// [ByteParser] provides parsing operations for text-based formats, specifically targeting Newick and NEXUS.
-/// It operates on byte sources and assumes ASCII encoding, offering both peek, consume,
-/// and skip operations with case-insensitive matching.
+/// It operates on byte sources and assumes ASCII encoding for syntax (labels are decoded as UTF-8),
+/// offering both peek, consume, and skip operations with case-insensitive matching.
 ///
 /// # Features
 /// - Works with any ByteSource (in-memory or buffered)
 /// - Case-insensitive matching for ASCII characters
 /// - Whitespace and comment skipping
-/// - Quote-aware label parsing (single quotes with escaping)
+/// - Quote-aware label parsing (single quotes with escaping, UTF-8)
+/// - Optional conversion of underscores to spaces in unquoted labels (NEXUS convention)
 /// - Context extraction for error reporting
+/// - Scanning over whole slices (with `memchr`) for sources holding their bytes in memory
+///   (see [ByteSource::remaining]), falling back to byte-by-byte loops for other sources
 ///
 /// # TODOs
 /// - Make consume_until methods comment-sensitive
 ///
 /// # Example
 /// ```ignore
-/// use nexus_parser::parser::byte_parser::ByteParser;
-/// use nexus_parser::parser::byte_source::InMemoryByteSource;
+/// use nexus_parser::io::parser::byte_parser::ByteParser;
+/// use nexus_parser::io::parser::byte_source::InMemoryByteSource;
 ///
 /// let input = "BEGIN TREES;\n  TREE t1 = (A:1.0,B:1.0):0.0;";
 /// let source = InMemoryByteSource::new(input.as_bytes());
@@ -35,6 +40,8 @@ use crate::parser::parsing_error::ParsingError;
 /// ```
 pub struct ByteParser<S: ByteSource> {
     source: S,
+    /// Whether underscores in unquoted labels are converted to spaces
+    underscores_to_spaces: bool,
 }
 
 impl ByteParser<InMemoryByteSource> {
@@ -50,6 +57,7 @@ impl ByteParser<InMemoryByteSource> {
     ///
     /// # Arguments
     /// * `input` - The string to parse
+    #[allow(clippy::should_implement_trait)]
     pub fn from_str(input: &str) -> Self {
         Self::new(InMemoryByteSource::from_vec(input.as_bytes().to_vec()))
     }
@@ -61,7 +69,24 @@ impl<S: ByteSource> ByteParser<S> {
     /// # Arguments
     /// * `source` - The byte source to parse
     pub fn new(source: S) -> Self {
-        Self { source }
+        Self {
+            source,
+            underscores_to_spaces: false,
+        }
+    }
+
+    /// Sets whether underscores in unquoted labels are converted to spaces (default `false`).
+    ///
+    /// Following the NEXUS standard, an unquoted `Homo_sapiens` then becomes `Homo sapiens`,
+    /// while underscores in quoted labels (e.g. `'Homo_sapiens'`) are kept.
+    pub fn with_underscores_to_spaces(mut self, underscores_to_spaces: bool) -> Self {
+        self.underscores_to_spaces = underscores_to_spaces;
+        self
+    }
+
+    /// Returns whether underscores in unquoted labels are converted to spaces.
+    pub fn underscores_to_spaces(&self) -> bool {
+        self.underscores_to_spaces
     }
 
     /// Peeks at the current byte without consuming it.
@@ -80,6 +105,7 @@ impl<S: ByteSource> ByteParser<S> {
     /// * `Some(u8)` - The current byte if available
     /// * `None` - If at end of data (EOF)
     #[inline(always)]
+    #[allow(clippy::should_implement_trait)]
     pub fn next(&mut self) -> Option<u8> {
         self.source.next()
     }
@@ -88,8 +114,14 @@ impl<S: ByteSource> ByteParser<S> {
     ///
     /// Whitespace includes: space (' '), tab ('\t'), newline ('\n'), and carriage return ('\r').
     pub fn skip_whitespace(&mut self) {
+        if let Some(rest) = self.source.remaining() {
+            let length = rest.iter().position(|&b| !is_whitespace(b)).unwrap_or(rest.len());
+            self.advance(length);
+            return;
+        }
+
         while let Some(b) = self.peek() {
-            if b == b' ' || b == b'\t' || b == b'\n' || b == b'\r' {
+            if is_whitespace(b) {
                 self.next();
             } else {
                 break;
@@ -119,6 +151,42 @@ impl<S: ByteSource> ByteParser<S> {
         Ok(false)
     }
 
+    /// Parses (consumes) a NEXUS-style comment if present and returns its content.
+    ///
+    /// # Returns
+    /// * `Ok(Some(content))` - The content between `[` and `]` of the consumed comment
+    /// * `Ok(None)` - No comment at current position
+    /// * `Err(ParsingError)` - Comment was opened but never closed
+    ///
+    /// # Errors
+    /// Returns an error if a comment starts with `[` but doesn't have a closing `]`.
+    pub fn parse_comment(&mut self) -> Result<Option<String>, ParsingError> {
+        if !self.consume_if(b'[') {
+            return Ok(None);
+        }
+
+        let mut content = Vec::new();
+        if let Some(rest) = self.source.remaining() {
+            let Some(length) = memchr(b']', rest) else {
+                self.advance(rest.len());
+                return Err(ParsingError::unclosed_comment(self));
+            };
+            content.extend_from_slice(&rest[..length]);
+            self.advance(length + 1);
+            return Ok(Some(bytes_to_label(content)));
+        }
+
+        loop {
+            match self.next() {
+                Some(b']') => break,
+                Some(b) => content.push(b),
+                None => return Err(ParsingError::unclosed_comment(self)),
+            }
+        }
+
+        Ok(Some(bytes_to_label(content)))
+    }
+
     /// Skips (consumes) all consecutive whitespace and NEXUS comments.
     ///
     /// This method repeatedly skips whitespace and comments until no more are found.
@@ -208,6 +276,47 @@ impl<S: ByteSource> ByteParser<S> {
         }
     }
 
+    /// Checks if the following bytes match the given keyword (case-insensitive) as a whole word,
+    /// i.e. not followed by an alphanumeric byte (or `_`).
+    ///
+    /// This is a peek operation - the parser position is not changed.
+    ///
+    /// # Arguments
+    /// * `keyword` - The keyword to match against, e.g. `b"tree"` (but not matching `"trees"`)
+    ///
+    /// # Returns
+    /// `true` if the next bytes are the keyword (case-insensitive), `false` otherwise
+    pub fn peek_is_keyword(&self, keyword: &[u8]) -> bool {
+        if !self.peek_is_sequence(keyword) {
+            return false;
+        }
+
+        match self.source.peek_slice(keyword.len() + 1).get(keyword.len()) {
+            Some(b) => !b.is_ascii_alphanumeric() && *b != b'_',
+            None => true,
+        }
+    }
+
+    /// Consumes the next bytes if they are the given keyword (case-insensitive) as a whole word,
+    /// see [ByteParser::peek_is_keyword].
+    ///
+    /// # Arguments
+    /// * `keyword` - The keyword to match and consume
+    ///
+    /// # Returns
+    /// `true` if the keyword was matched and consumed, `false` otherwise
+    pub fn consume_if_keyword(&mut self, keyword: &[u8]) -> bool {
+        if !self.peek_is_keyword(keyword) {
+            return false;
+        }
+
+        for _ in 0..keyword.len() {
+            self.next();
+        }
+
+        true
+    }
+
     /// Consumes the next bytes if they match the given word/token (case-insensitive).
     ///
     /// # Arguments
@@ -250,6 +359,19 @@ impl<S: ByteSource> ByteParser<S> {
     /// # Returns
     /// `true` if the target was found, `false` if EOF was reached first
     pub fn consume_until(&mut self, target: u8, mode: ConsumeMode) -> bool {
+        if let Some(rest) = self.source.remaining() {
+            return match memchr(target, rest) {
+                Some(length) => {
+                    self.advance(length + usize::from(mode == ConsumeMode::Inclusive));
+                    true
+                }
+                None => {
+                    self.advance(rest.len());
+                    false
+                }
+            };
+        }
+
         while let Some(b) = self.peek() {
             if b == target {
                 if mode == ConsumeMode::Inclusive {
@@ -271,6 +393,20 @@ impl<S: ByteSource> ByteParser<S> {
     /// # Returns
     /// `Some(u8)` with the found byte, or `None` if EOF was reached first
     pub fn consume_until_any(&mut self, targets: &[u8], mode: ConsumeMode) -> Option<u8> {
+        if let Some(rest) = self.source.remaining() {
+            return match find_any(targets, rest) {
+                Some(length) => {
+                    let b = rest[length];
+                    self.advance(length + usize::from(mode == ConsumeMode::Inclusive));
+                    Some(b)
+                }
+                None => {
+                    self.advance(rest.len());
+                    None
+                }
+            };
+        }
+
         while let Some(b) = self.peek() {
             if targets.contains(&b) {
                 if mode == ConsumeMode::Inclusive {
@@ -326,6 +462,40 @@ impl<S: ByteSource> ByteParser<S> {
         }
     }
 
+    /// Consumes bytes up to and including the `;` terminating the current NEXUS command.
+    ///
+    /// Semicolons within comments `[...]` are ignored, and if `quote_aware`,
+    /// also those within quoted labels `'...'`. Use `quote_aware = false` for free text
+    /// (e.g. in unknown blocks) where single quotes may be apostrophes.
+    ///
+    /// # Arguments
+    /// * `quote_aware` - Whether single quotes start and end quoted labels
+    ///
+    /// # Returns
+    /// `true` if the terminating `;` was found, `false` if EOF was reached first
+    pub fn consume_command(&mut self, quote_aware: bool) -> bool {
+        if let Some(rest) = self.source.remaining() {
+            return match CommandScanner::default().scan(rest, quote_aware) {
+                Some(length) => {
+                    self.advance(length);
+                    true
+                }
+                None => {
+                    self.advance(rest.len());
+                    false
+                }
+            };
+        }
+
+        let mut scanner = CommandScanner::default();
+        while let Some(b) = self.next() {
+            if scanner.scan(&[b], quote_aware).is_some() {
+                return true;
+            }
+        }
+        false // reached EOF without finding end of command
+    }
+
     /// Returns whether the end of data (EOF) has been reached.
     ///
     /// # Returns
@@ -344,6 +514,12 @@ impl<S: ByteSource> ByteParser<S> {
         self.source.position()
     }
 
+    /// Returns the line and column (both starting at 1) of the current position, if the source can tell,
+    /// see [ByteSource::line_and_column].
+    pub fn line_and_column(&self) -> Option<(usize, usize)> {
+        self.source.line_and_column()
+    }
+
     /// Sets the position in the byte stream.
     ///
     /// # Arguments
@@ -352,6 +528,13 @@ impl<S: ByteSource> ByteParser<S> {
         self.source.set_position(pos);
     }
 
+    /// Advances the position by `length` bytes, e.g. over bytes scanned in [ByteSource::remaining].
+    #[inline(always)]
+    fn advance(&mut self, length: usize) {
+        let pos = self.position();
+        self.set_position(pos + length);
+    }
+
     /// Returns a slice of the input from a start position to the current position.
     ///
     /// # Arguments
@@ -388,6 +571,30 @@ impl<S: ByteSource> ByteParser<S> {
         String::from_utf8_lossy(context_bytes).chars().collect()
     }
 
+    /// Parses an unsigned integer from consecutive ASCII digits.
+    ///
+    /// Consumes digits directly without allocating an intermediate string,
+    /// e.g. for integer keys of a Nexus `TRANSLATE` command.
+    ///
+    /// # Returns
+    /// * `Some(usize)` - The parsed integer
+    /// * `None` - If there is no digit at the current position or the value overflows
+    pub fn parse_unsigned_integer(&mut self) -> Option<usize> {
+        let mut value: usize = 0;
+        let mut found_digit = false;
+
+        while let Some(b) = self.peek() {
+            if !b.is_ascii_digit() {
+                break;
+            }
+            value = value.checked_mul(10)?.checked_add((b - b'0') as usize)?;
+            found_digit = true;
+            self.next();
+        }
+
+        if found_digit { Some(value) } else { None }
+    }
+
     /// Parses a label (quoted or unquoted) with the given delimiter set.
     ///
     /// This method automatically detects whether the label is quoted (single quotes)
@@ -415,6 +622,7 @@ impl<S: ByteSource> ByteParser<S> {
     ///
     /// Assumes the opening quote has not been consumed yet. Single quotes within
     /// the label are escaped by doubling them (e.g., `'Wilson''s'` becomes `Wilson's`).
+    /// Any other character, including delimiters like spaces, parentheses and underscores, is kept.
     ///
     /// # Returns
     /// The parsed label string without the enclosing quotes
@@ -424,23 +632,42 @@ impl<S: ByteSource> ByteParser<S> {
     pub fn parse_quoted_label(&mut self) -> Result<String, ParsingError> {
         self.next(); // consume opening '
 
-        let mut label = String::new();
-        while let Some(b) = self.next() {
-            if b == b'\'' {
-                // Check for escaped quote (two single quotes in a row)
-                if self.peek() == Some(b'\'') {
-                    label.push('\'');
-                    self.next(); // consume second quote
-                } else {
-                    // End of quoted label
-                    break;
+        let mut label = Vec::new();
+        while let Some(rest) = self.source.remaining() {
+            let Some(length) = memchr(b'\'', rest) else {
+                self.advance(rest.len());
+                return Err(ParsingError::unclosed_quote(self));
+            };
+            label.extend_from_slice(&rest[..length]);
+            // Escaped quote (two single quotes in a row) or end of quoted label
+            let escaped = rest.get(length + 1) == Some(&b'\'');
+            if escaped {
+                label.push(b'\'');
+            }
+            self.advance(length + 1 + usize::from(escaped));
+            if !escaped {
+                return Ok(bytes_to_label(label));
+            }
+        }
+
+        loop {
+            match self.next() {
+                Some(b'\'') => {
+                    // Check for escaped quote (two single quotes in a row)
+                    if self.peek() == Some(b'\'') {
+                        label.push(b'\'');
+                        self.next(); // consume second quote
+                    } else {
+                        // End of quoted label
+                        break;
+                    }
                 }
-            } else {
-                label.push(b as char);
+                Some(b) => label.push(b),
+                None => return Err(ParsingError::unclosed_quote(self)),
             }
         }
 
-        Ok(label)
+        Ok(bytes_to_label(label))
     }
 
     /// Parses an unquoted label until any of the given delimiters is encountered.
@@ -449,26 +676,216 @@ impl<S: ByteSource> ByteParser<S> {
     /// * `delimiters` - Byte array of characters that terminate the label
     ///
     /// # Returns
-    /// The parsed label string
+    /// The parsed label string, with underscores converted to spaces if configured
     ///
     /// # Errors
     /// Currently does not return errors, but returns `Result` for API consistency
     pub fn parse_unquoted_label(&mut self, delimiters: &[u8]) -> Result<String, ParsingError> {
-        let mut label = String::new();
+        let mut label = Vec::new();
+        if let Some(rest) = self.source.remaining() {
+            let length = find_any(delimiters, rest).unwrap_or(rest.len());
+            label.extend_from_slice(&rest[..length]);
+            if self.underscores_to_spaces {
+                label.iter_mut().filter(|b| **b == b'_').for_each(|b| *b = b' ');
+            }
+            self.advance(length);
+            return Ok(bytes_to_label(label));
+        }
 
         while let Some(b) = self.peek() {
             // Stop at any delimiter
             if delimiters.contains(&b) {
                 break;
             }
-            label.push(b as char);
+            label.push(if b == b'_' && self.underscores_to_spaces { b' ' } else { b });
             self.next();
         }
 
-        Ok(label)
+        Ok(bytes_to_label(label))
+    }
+}
+
+impl<'a> ByteParser<InMemoryByteSource<&'a [u8]>> {
+    /// Creates a new `ByteParser` over borrowed bytes, without copying them.
+    ///
+    /// # Arguments
+    /// * `input` - The bytes to parse
+    pub fn from_slice(input: &'a [u8]) -> Self {
+        Self::new(InMemoryByteSource::new(input))
+    }
+
+    /// Parses a label like [parse_label](ByteParser::parse_label), but borrows it from the input
+    /// instead of copying it, unless it differs from its bytes in the input:
+    /// quoted labels with escaped quotes, labels with underscores to convert to spaces,
+    /// and labels that are not valid UTF-8 are copied.
+    ///
+    /// # Arguments
+    /// * `delimiters` - Byte array of characters that terminate an unquoted label
+    ///
+    /// # Returns
+    /// The parsed label, borrowed from the input if possible
+    ///
+    /// # Errors
+    /// Returns an error if a quoted label is not properly closed
+    ///
+    /// # Example
+    /// ```
+    /// use nexus_parser::io::parser::byte_parser::ByteParser;
+    /// use std::borrow::Cow;
+    ///
+    /// let mut parser = ByteParser::from_slice(b"Homo_sapiens 'Wilson''s'");
+    /// assert!(matches!(parser.parse_label_borrowed(b" "), Ok(Cow::Borrowed("Homo_sapiens"))));
+    /// assert!(matches!(parser.parse_label_borrowed(b" "), Ok(Cow::Owned(label)) if label == "Wilson's"));
+    /// ```
+    pub fn parse_label_borrowed(&mut self, delimiters: &[u8]) -> Result<Cow<'a, str>, ParsingError> {
+        self.skip_comment_and_whitespace()?;
+        let input = self.source.input();
+        let start = self.position();
+
+        let (content, end) = if self.peek() == Some(b'\'') {
+            let length = memchr(b'\'', &input[start + 1..]);
+            match length {
+                // Closing quote not followed by another quote (escaped quote)
+                Some(length) if input.get(start + length + 2) != Some(&b'\'') => {
+                    (&input[start + 1..start + 1 + length], start + length + 2)
+                }
+                _ => return self.parse_label(delimiters).map(Cow::Owned),
+            }
+        } else {
+            let length = find_any(delimiters, &input[start..]).unwrap_or(input.len() - start);
+            let content = &input[start..start + length];
+            if self.underscores_to_spaces && content.contains(&b'_') {
+                return self.parse_label(delimiters).map(Cow::Owned);
+            }
+            (content, start + length)
+        };
+
+        match std::str::from_utf8(content) {
+            Ok(label) => {
+                self.set_position(end);
+                Ok(Cow::Borrowed(label))
+            }
+            Err(_) => self.parse_label(delimiters).map(Cow::Owned),
+        }
+    }
+}
+
+/// Label types a [ByteParser] can parse: owned `String`s from any source,
+/// and `Cow<str>`s borrowed from in-memory input (see [ByteParser::parse_label_borrowed]).
+///
+/// Lets the Newick and NEXUS parsers fill a [LeafLabelMap](crate::model::leaf_label_map::LeafLabelMap)
+/// of either label type.
+pub trait ParseLabel<S: ByteSource>: Sized {
+    /// Parses a label (quoted or unquoted) with the given delimiter set, see [ByteParser::parse_label].
+    ///
+    /// # Errors
+    /// Returns an error if a quoted label is not properly closed
+    fn parse_label(parser: &mut ByteParser<S>, delimiters: &[u8]) -> Result<Self, ParsingError>;
+}
+
+impl<S: ByteSource> ParseLabel<S> for String {
+    fn parse_label(parser: &mut ByteParser<S>, delimiters: &[u8]) -> Result<Self, ParsingError> {
+        parser.parse_label(delimiters)
+    }
+}
+
+impl<'a> ParseLabel<InMemoryByteSource<&'a [u8]>> for Cow<'a, str> {
+    fn parse_label(parser: &mut ByteParser<InMemoryByteSource<&'a [u8]>>, delimiters: &[u8]) -> Result<Self, ParsingError> {
+        parser.parse_label_borrowed(delimiters)
+    }
+}
+
+/// Returns whether the byte is whitespace: space, tab, newline or carriage return.
+#[inline(always)]
+fn is_whitespace(b: u8) -> bool {
+    b == b' ' || b == b'\t' || b == b'\n' || b == b'\r'
+}
+
+/// Returns the offset of the first of the target bytes in `haystack`, if any,
+/// searching with `memchr` for up to three targets.
+#[inline]
+fn find_any(targets: &[u8], haystack: &[u8]) -> Option<usize> {
+    match *targets {
+        [] => None,
+        [a] => memchr(a, haystack),
+        [a, b] => memchr2(a, b, haystack),
+        [a, b, c] => memchr3(a, b, c, haystack),
+        _ => haystack.iter().position(|b| targets.contains(b)),
     }
 }
 
+/// Scanning state of a NEXUS command read in chunks, tracking whether a comment or quote is open.
+#[derive(Debug, Default)]
+pub(crate) struct CommandScanner {
+    in_comment: bool,
+    in_quote: bool,
+}
+
+impl CommandScanner {
+    /// Scans the next chunk of a command (quotes only delimit labels if `quote_aware`),
+    /// jumping with `memchr` to the next byte that can change the state.
+    ///
+    /// # Returns
+    /// The length of the chunk up to and including the terminating `;`, or `None` if it does not end the command
+    pub(crate) fn scan(&mut self, chunk: &[u8], quote_aware: bool) -> Option<usize> {
+        let mut i = 0;
+        while i < chunk.len() {
+            let rest = &chunk[i..];
+            let found = if self.in_comment {
+                memchr(b']', rest)
+            } else if self.in_quote {
+                memchr(b'\'', rest)
+            } else if quote_aware {
+                memchr3(b'[', b';', b'\'', rest)
+            } else {
+                memchr2(b'[', b';', rest)
+            };
+
+            i += found?;
+            match chunk[i] {
+                b'[' => self.in_comment = true,
+                b']' => self.in_comment = false,
+                b'\'' => self.in_quote = !self.in_quote,
+                _ => {
+                    *self = Self::default();
+                    return Some(i + 1);
+                }
+            }
+            i += 1;
+        }
+        None
+    }
+
+    /// Scans the next chunk of a command of a TREES block (quotes always delimit labels)
+    /// up to any of the `delimiters` outside comments and quotes, e.g. the end of the next step
+    /// of a Newick string (see [NEWICK_STEP_DELIMITERS](crate::io::parser::newick::NEWICK_STEP_DELIMITERS)).
+    ///
+    /// # Returns
+    /// The length of the chunk up to and including the delimiter, or `None` if it contains none
+    pub(crate) fn scan_until(&mut self, chunk: &[u8], delimiters: &[u8]) -> Option<usize> {
+        for (i, &b) in chunk.iter().enumerate() {
+            if self.in_comment {
+                self.in_comment = b != b']';
+            } else if self.in_quote {
+                self.in_quote = b != b'\'';
+            } else if b == b'[' {
+                self.in_comment = true;
+            } else if b == b'\'' {
+                self.in_quote = true;
+            } else if delimiters.contains(&b) {
+                *self = Self::default();
+                return Some(i + 1);
+            }
+        }
+        None
+    }
+}
+
+/// Turns the bytes of a label into a String, decoding UTF-8 (invalid sequences are replaced).
+fn bytes_to_label(bytes: Vec<u8>) -> String {
+    String::from_utf8(bytes).unwrap_or_else(|e| String::from_utf8_lossy(e.as_bytes()).into_owned())
+}
+
 /// Specifies whether to consume or leave the target when using `consume_until` methods.
 ///
 /// This enum controls the behavior of various `consume_until` methods in `ByteParser`,
@@ -477,7 +894,7 @@ impl<S: ByteSource> ByteParser<S> {
 ///
 /// # Examples
 /// ```
-/// use nexus_parser::parser::byte_parser::{ByteParser, ConsumeMode};
+/// use nexus_parser::io::parser::byte_parser::{ByteParser, ConsumeMode};
 ///
 /// let mut parser = ByteParser::from_str("TREE t1=((A:0.5,B:0.5):0.3,C:0.8):0.0");
 ///
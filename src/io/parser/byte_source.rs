@@ -4,7 +4,7 @@
 /// Trait defining the interface for different byte sources used by ByteParser.
 ///
 /// This trait abstracts over different ways of accessing byte data:
-/// - In-memory byte slices (`&[u8]`)
+/// - In-memory bytes (anything `&[u8]`-like, e.g. `Vec<u8>`, `&[u8]` or a memory-mapped file)
 /// - Buffered reading from files (`BufReader<File>`)
 ///
 /// By using this trait, the same parsing logic can work with both small files
@@ -68,14 +68,31 @@ pub trait ByteSource {
     /// # Returns
     /// `true` if at or beyond the end of data, `false` otherwise
     fn is_eof(&self) -> bool;
+
+    /// Returns all remaining bytes from the current position, if the source holds them in one slice.
+    ///
+    /// Lets [ByteParser](crate::io::parser::byte_parser::ByteParser) scan for delimiters over whole slices
+    /// (e.g. with `memchr`) instead of byte by byte; sources without slice access return `None` (default).
+    fn remaining(&self) -> Option<&[u8]> {
+        None
+    }
+
+    /// Returns the line and column (both starting at 1, columns counted in bytes) of the current position,
+    /// e.g. for error messages, or `None` if the source cannot tell.
+    ///
+    /// Computed on demand rather than tracked while parsing, so it may be linear in the position.
+    fn line_and_column(&self) -> Option<(usize, usize)> {
+        None
+    }
 }
 
-/// An in-memory byte source that owns its data.
+/// An in-memory byte source over any `&[u8]`-like data (by default an owned `Vec<u8>`).
 ///
-/// This is the most efficient byte source for files that can fit entirely in memory.
-pub struct InMemoryByteSource {
-    /// The owned byte data being parsed
-    input: Vec<u8>,
+/// This is the most efficient byte source for files that can fit entirely in memory,
+/// or that are memory-mapped (e.g. `memmap2::Mmap`), so the operating system pages them in as needed.
+pub struct InMemoryByteSource<B: AsRef<[u8]> = Vec<u8>> {
+    /// The byte data being parsed
+    input: B,
     /// Current position in the byte slice
     pos: usize,
 }
@@ -89,17 +106,39 @@ impl InMemoryByteSource {
     /// # Arguments
     /// * `bytes` - The byte vector to parse
     pub fn from_vec(bytes: Vec<u8>) -> Self {
+        Self::new(bytes)
+    }
+}
+
+impl<B: AsRef<[u8]>> InMemoryByteSource<B> {
+    /// Creates a new in-memory byte source over the given bytes, without copying them.
+    ///
+    /// # Arguments
+    /// * `bytes` - The bytes to parse, e.g. a `&[u8]` or a memory-mapped file
+    pub fn new(bytes: B) -> Self {
         Self {
             input: bytes,
             pos: 0,
         }
     }
+
+    #[inline(always)]
+    fn bytes(&self) -> &[u8] {
+        self.input.as_ref()
+    }
+}
+
+impl<'a> InMemoryByteSource<&'a [u8]> {
+    /// Returns the borrowed input as a whole, e.g. to borrow labels from it.
+    pub(crate) fn input(&self) -> &'a [u8] {
+        self.input
+    }
 }
 
-impl ByteSource for InMemoryByteSource {
+impl<B: AsRef<[u8]>> ByteSource for InMemoryByteSource<B> {
     #[inline(always)]
     fn peek(&self) -> Option<u8> {
-        self.input.get(self.pos).copied()
+        self.bytes().get(self.pos).copied()
     }
 
     #[inline(always)]
@@ -120,25 +159,47 @@ impl ByteSource for InMemoryByteSource {
     }
 
     fn slice_from(&self, start: usize) -> Option<&[u8]> {
-        if start <= self.pos && self.pos <= self.input.len() {
-            Some(&self.input[start..self.pos])
+        if start <= self.pos && self.pos <= self.bytes().len() {
+            Some(&self.bytes()[start..self.pos])
         } else {
             None
         }
     }
 
     fn get_context(&self, k: usize) -> Vec<u8> {
-        let end = (self.pos + k).min(self.input.len());
-        self.input[self.pos..end].to_vec()
+        let end = (self.pos + k).min(self.bytes().len());
+        self.bytes()[self.pos..end].to_vec()
     }
 
     #[inline(always)]
     fn peek_slice(&self, k: usize) -> &[u8] {
-        let end = (self.pos + k).min(self.input.len());
-        &self.input[self.pos..end]
+        let end = (self.pos + k).min(self.bytes().len());
+        &self.bytes()[self.pos..end]
     }
 
     fn is_eof(&self) -> bool {
-        self.pos >= self.input.len()
+        self.pos >= self.bytes().len()
+    }
+
+    #[inline(always)]
+    fn remaining(&self) -> Option<&[u8]> {
+        let bytes = self.bytes();
+        Some(&bytes[self.pos.min(bytes.len())..])
+    }
+
+    fn line_and_column(&self) -> Option<(usize, usize)> {
+        let before = &self.bytes()[..self.pos.min(self.bytes().len())];
+        Some(advance_line_and_column((1, 1), before))
+    }
+}
+
+/// Returns the line and column after `bytes`, starting at the given line and column.
+pub(crate) fn advance_line_and_column((line, column): (usize, usize), bytes: &[u8]) -> (usize, usize) {
+    match bytes.iter().rposition(|&b| b == b'\n') {
+        Some(last_newline) => {
+            let num_newlines = bytes.iter().filter(|&&b| b == b'\n').count();
+            (line + num_newlines, bytes.len() - last_newline)
+        }
+        None => (line, column + bytes.len()),
     }
 }
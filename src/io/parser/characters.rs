@@ -0,0 +1,304 @@
+//! Characters module for the DATA and CHARACTERS blocks of NEXUS files.
+//!
+//! - `CharactersBlock`: Parses the commands of a DATA or CHARACTERS block (`DIMENSIONS NCHAR`,
+//!   `FORMAT` and `MATRIX`), skipping others
+//! - `CharacterMatrix`: Named sequences of a MATRIX command, resolved into an [Alignment]
+//!   against the [LeafLabelMap] of the trees
+//!
+//! Matrices may be interleaved (`FORMAT INTERLEAVE`), where every line holds a name and the next chunk
+//! of its sequence, or not, where each sequence may span several lines. Whitespace and comments
+//! within sequences are skipped, the match character (e.g. `.`) is replaced by the state of the first
+//! sequence, and sets of states (`{AG}` or `(AG)`) are stored as one symbol (see [Alignment]).
+
+use crate::io::defs::DIMENSIONS;
+use crate::io::parser::byte_parser::{ByteParser, ConsumeMode};
+use crate::io::parser::byte_source::ByteSource;
+use crate::io::parser::parsing_error::ParsingError;
+use crate::model::alignment::{nucleotide_bases, Alignment, DataType, IUPAC_CODES};
+use crate::model::leaf_label_map::LeafLabelMap;
+use std::collections::HashMap;
+
+/// Delimiters of sequence names in MATRIX commands: semicolon, comment start, whitespace
+const MATRIX_NAME_DELIMITERS: &[u8] = b";[ \t\n\r";
+
+/// Delimiters of keys and unquoted values in DIMENSIONS and FORMAT commands
+const FORMAT_DELIMITERS: &[u8] = b"=;[ \t\n\r";
+
+const NCHAR: &[u8] = b"nchar";
+
+const FORMAT: &[u8] = b"format";
+
+const MATRIX: &[u8] = b"matrix";
+
+// =#========================================================================#=
+// CHARACTER MATRIX
+// =#========================================================================#=
+/// Sequences of a MATRIX command by name, in order of appearance, with the format of their block.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct CharacterMatrix {
+    format: Format,
+    num_sites: usize,
+    rows: Vec<(String, Vec<u8>)>,
+}
+
+impl CharacterMatrix {
+    /// Converts the matrix into an [Alignment] over the given label map,
+    /// adding the names of sequences not yet in it as labels.
+    pub(crate) fn into_alignment(self, labels: &mut LeafLabelMap) -> Alignment {
+        let alignment = if self.rows.is_empty() {
+            Alignment::new(self.format.data_type, self.num_sites)
+        } else {
+            Alignment::from_named_sequences(self.format.data_type, self.rows, labels)
+                .expect("Sequences have distinct names and the same length")
+        };
+        alignment.with_gap(self.format.gap).with_missing(self.format.missing)
+    }
+}
+
+/// Settings of a FORMAT command.
+#[derive(Debug, Clone, PartialEq)]
+struct Format {
+    data_type: DataType,
+    gap: u8,
+    missing: u8,
+    match_char: Option<u8>,
+    interleave: bool,
+}
+
+impl Default for Format {
+    fn default() -> Self {
+        Self { data_type: DataType::Standard, gap: b'-', missing: b'?', match_char: None, interleave: false }
+    }
+}
+
+// =#========================================================================#=
+// CHARACTERS BLOCK
+// =#========================================================================#=
+/// State of parsing a DATA or CHARACTERS block, command by command.
+#[derive(Debug, Default)]
+pub(crate) struct CharactersBlock {
+    /// Number of characters (sites) declared by `DIMENSIONS NCHAR`
+    nchar: Option<usize>,
+    format: Format,
+    matrix: Option<CharacterMatrix>,
+}
+
+impl CharactersBlock {
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the matrix of the block, if it had a MATRIX command.
+    pub(crate) fn into_matrix(self) -> Option<CharacterMatrix> {
+        self.matrix
+    }
+
+    /// Parses the next command of the block (with the parser positioned at its start):
+    /// `DIMENSIONS [NTAX=<n>] NCHAR=<m>;`, `FORMAT <key>[=<value>] ...;` and `MATRIX <name> <sequence> ... ;`,
+    /// skipping other commands.
+    ///
+    /// # Errors
+    /// Returns a [ParsingError] if a command is malformed, the data type is not supported,
+    /// or the sequences of the matrix do not match the declared number of characters.
+    pub(crate) fn parse_command<S: ByteSource>(&mut self, parser: &mut ByteParser<S>) -> Result<(), ParsingError> {
+        if parser.consume_if_keyword(DIMENSIONS) {
+            for (key, value) in parse_key_values(parser)? {
+                if key.as_bytes().eq_ignore_ascii_case(NCHAR) {
+                    let nchar = value.and_then(|value| value.parse::<usize>().ok()).ok_or_else(||
+                        ParsingError::invalid_characters_block(parser, "Expected 'nchar=<number>' in DIMENSIONS command".to_string()))?;
+                    self.nchar = Some(nchar);
+                }
+            }
+        } else if parser.consume_if_keyword(FORMAT) {
+            self.parse_format(parser)?;
+        } else if parser.consume_if_keyword(MATRIX) {
+            self.parse_matrix(parser)?;
+        } else {
+            parser.consume_command(true);
+        }
+        Ok(())
+    }
+
+    /// Parses the settings of a FORMAT command (after the `FORMAT` keyword), ignoring unknown ones.
+    fn parse_format<S: ByteSource>(&mut self, parser: &mut ByteParser<S>) -> Result<(), ParsingError> {
+        for (key, value) in parse_key_values(parser)? {
+            let symbol = || value.as_deref()
+                .and_then(|value| value.bytes().next())
+                .ok_or_else(|| ParsingError::invalid_characters_block(parser, format!("Expected symbol for '{}' in FORMAT command", key)));
+            match key.to_ascii_lowercase().as_str() {
+                "datatype" => {
+                    let name = value.as_deref().unwrap_or_default();
+                    self.format.data_type = DataType::from_name(name).ok_or_else(||
+                        ParsingError::invalid_characters_block(parser, format!("Unsupported datatype '{}'", name)))?;
+                }
+                "gap" => self.format.gap = symbol()?,
+                "missing" => self.format.missing = symbol()?,
+                "matchchar" => self.format.match_char = Some(symbol()?),
+                "interleave" => self.format.interleave = value.is_none_or(|value| !value.eq_ignore_ascii_case("no")),
+                _ => {}
+            }
+        }
+
+        // Symbols are stored like the states of the sequences
+        let data_type = self.format.data_type;
+        self.format.gap = data_type.normalize(self.format.gap);
+        self.format.missing = data_type.normalize(self.format.missing);
+        self.format.match_char = self.format.match_char.map(|symbol| data_type.normalize(symbol));
+        Ok(())
+    }
+
+    /// Parses the sequences of a MATRIX command up to and including its `;` (after the `MATRIX` keyword).
+    fn parse_matrix<S: ByteSource>(&mut self, parser: &mut ByteParser<S>) -> Result<(), ParsingError> {
+        let Some(nchar) = self.nchar else {
+            return Err(ParsingError::invalid_characters_block(parser, "Expected 'nchar' in DIMENSIONS command before MATRIX".to_string()));
+        };
+
+        let mut rows: Vec<(String, Vec<u8>)> = Vec::new();
+        let mut row_of_name: HashMap<String, usize> = HashMap::new();
+        loop {
+            parser.skip_comment_and_whitespace()?;
+            match parser.peek() {
+                None => return Err(ParsingError::unexpected_eof(parser)),
+                Some(b';') => {
+                    parser.next();
+                    break;
+                }
+                Some(_) => {}
+            }
+
+            let name = parser.parse_label(MATRIX_NAME_DELIMITERS)?;
+            let row = match row_of_name.get(&name) {
+                // Next chunk of an interleaved sequence
+                Some(&row) if self.format.interleave => row,
+                Some(_) => return Err(ParsingError::invalid_characters_block(parser, format!("Duplicate sequence '{}' in MATRIX", name))),
+                None => {
+                    row_of_name.insert(name.clone(), rows.len());
+                    rows.push((name, Vec::with_capacity(nchar)));
+                    rows.len() - 1
+                }
+            };
+
+            let (name, sequence) = &mut rows[row];
+            let limit = (!self.format.interleave).then_some(nchar);
+            parse_states(parser, sequence, limit, &self.format)?;
+            if sequence.len() > nchar {
+                let msg = format!("Sequence '{}' has more than {} characters (nchar)", name, nchar);
+                return Err(ParsingError::invalid_characters_block(parser, msg));
+            }
+        }
+
+        if let Some((name, sequence)) = rows.iter().find(|(_, sequence)| sequence.len() != nchar) {
+            let msg = format!("Sequence '{}' has {} characters, expected {} (nchar)", name, sequence.len(), nchar);
+            return Err(ParsingError::invalid_characters_block(parser, msg));
+        }
+        if let Some(match_char) = self.format.match_char && let Some((_, first)) = rows.first().cloned() {
+            for (_, sequence) in rows.iter_mut().skip(1) {
+                for (state, &first_state) in sequence.iter_mut().zip(&first) {
+                    if *state == match_char {
+                        *state = first_state;
+                    }
+                }
+            }
+        }
+
+        self.matrix = Some(CharacterMatrix { format: self.format.clone(), num_sites: nchar, rows });
+        Ok(())
+    }
+}
+
+// ============================================================================
+// Little Helpers
+// ============================================================================
+/// Parses the `<key>[=<value>]` pairs of a command up to and including its `;`.
+/// Values may be quoted with single or double quotes (e.g. `SYMBOLS="0 1 2"`).
+fn parse_key_values<S: ByteSource>(parser: &mut ByteParser<S>) -> Result<Vec<(String, Option<String>)>, ParsingError> {
+    let mut pairs = Vec::new();
+    loop {
+        parser.skip_comment_and_whitespace()?;
+        if parser.consume_if(b';') {
+            return Ok(pairs);
+        }
+        if parser.is_eof() {
+            return Err(ParsingError::unexpected_eof(parser));
+        }
+
+        let key = parser.parse_unquoted_label(FORMAT_DELIMITERS)?;
+        if key.is_empty() {
+            return Err(ParsingError::invalid_characters_block(parser, "Expected '<key>=<value>'".to_string()));
+        }
+        parser.skip_comment_and_whitespace()?;
+        let value = if parser.consume_if(b'=') {
+            parser.skip_comment_and_whitespace()?;
+            Some(match parser.peek() {
+                Some(b'\'') => parser.parse_quoted_label()?,
+                Some(b'"') => {
+                    parser.next();
+                    let start = parser.position();
+                    if !parser.consume_until(b'"', ConsumeMode::Exclusive) {
+                        return Err(ParsingError::unclosed_quote(parser));
+                    }
+                    let value = String::from_utf8_lossy(parser.slice_from(start)).into_owned();
+                    parser.next();
+                    value
+                }
+                _ => parser.parse_unquoted_label(FORMAT_DELIMITERS)?,
+            })
+        } else {
+            None
+        };
+        pairs.push((key, value));
+    }
+}
+
+/// Parses states of a sequence into `sequence`, skipping whitespace and comments,
+/// up to `limit` states or (without limit, for interleaved matrices) the end of the line,
+/// or the `;` ending the matrix (not consumed).
+fn parse_states<S: ByteSource>(parser: &mut ByteParser<S>, sequence: &mut Vec<u8>, limit: Option<usize>, format: &Format)
+                               -> Result<(), ParsingError> {
+    while limit.is_none_or(|limit| sequence.len() < limit) {
+        match parser.peek() {
+            None => return Err(ParsingError::unexpected_eof(parser)),
+            Some(b';') => break,
+            Some(b'\n' | b'\r') if limit.is_none() => break,
+            Some(b) if b.is_ascii_whitespace() => {
+                parser.next();
+            }
+            Some(b'[') => {
+                parser.skip_comment()?;
+            }
+            Some(open @ (b'{' | b'(')) => {
+                parser.next();
+                let close = if open == b'{' { b'}' } else { b')' };
+                sequence.push(parse_state_set(parser, close, format)?);
+            }
+            Some(b) => {
+                parser.next();
+                sequence.push(format.data_type.normalize(b));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Parses a set of states up to and including its closing bracket and returns its IUPAC code
+/// (for nucleotides) or the missing character of the format (otherwise).
+fn parse_state_set<S: ByteSource>(parser: &mut ByteParser<S>, close: u8, format: &Format) -> Result<u8, ParsingError> {
+    let mut bases = 0;
+    let mut known = format.data_type.is_nucleotide();
+    loop {
+        match parser.next() {
+            None | Some(b';') => {
+                return Err(ParsingError::invalid_characters_block(parser, "Unclosed set of states".to_string()));
+            }
+            Some(b) if b == close => break,
+            Some(b',' | b' ' | b'\t') => {}
+            Some(b) => match nucleotide_bases(b) {
+                Some(b) => bases |= b,
+                None => known = false,
+            },
+        }
+    }
+
+    let code = if known { IUPAC_CODES[bases as usize] } else { format.missing };
+    Ok(if format.data_type == DataType::Rna && code == b'T' { b'U' } else { code })
+}
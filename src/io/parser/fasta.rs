@@ -0,0 +1,132 @@
+//! FASTA alignment file parser.
+//!
+//! - `parse_file`, `parse_str`, `parse_bytes`: Parse an aligned FASTA file into an [Alignment]
+//!
+//! Each sequence starts with a header line `>name [description]`, followed by any number of lines
+//! of the sequence. The name is the first word of the header; it is resolved through a [LeafLabelMap]
+//! (e.g. of trees parsed before), so that the alignment can be joined with trees by [LabelIndex](crate::model::tree::LabelIndex).
+//! Whitespace within sequences and comment lines starting with `;` are skipped.
+
+use crate::io::parser::byte_parser::{ByteParser, ConsumeMode};
+use crate::io::parser::byte_source::{ByteSource, InMemoryByteSource};
+use crate::io::parser::parsing_error::ParsingError;
+use crate::model::alignment::{Alignment, DataType};
+use crate::model::leaf_label_map::LeafLabelMap;
+use std::collections::HashSet;
+#[cfg(feature = "fs")]
+use std::fs::File;
+#[cfg(feature = "fs")]
+use std::io::Read;
+
+// =#========================================================================#=
+// FASTA FILE PARSING
+// =#========================================================================#=
+/// Parses an aligned FASTA file into an [Alignment] over the given label map.
+///
+/// # Arguments
+/// * `file` - FASTA file with sequences of equal length
+/// * `data_type` - Data type of the sequences (nucleotides and amino acids are stored upper case)
+/// * `labels` - Label map resolving the sequence names; names not yet in it are added
+///
+/// # Errors
+/// Returns a [ParsingError] if the file cannot be read, has no sequence, content before the first header,
+/// a header without name, a repeated name, or sequences of different lengths
+#[cfg(feature = "fs")]
+pub fn parse_file(mut file: File, data_type: DataType, labels: &mut LeafLabelMap) -> Result<Alignment, ParsingError> {
+    let mut bytes = Vec::new();
+    file.read_to_end(&mut bytes).map_err(|e| ParsingError::io_error(&e, 0))?;
+    parse_all(&mut ByteParser::new(InMemoryByteSource::from_vec(bytes)), data_type, labels)
+}
+
+/// Parses an aligned FASTA string, see [parse_file].
+///
+/// # Errors
+/// Returns a [ParsingError] if the sequences are invalid, see [parse_file]
+///
+/// # Example
+/// ```
+/// use nexus_parser::io::parser::fasta;
+/// use nexus_parser::model::alignment::DataType;
+/// use nexus_parser::parse_newick_str;
+///
+/// let (trees, mut labels) = parse_newick_str("((A,B),C);").unwrap();
+/// let alignment = fasta::parse_str(">C sample 3\nAC-T\n>A\nAC\nGT\n>B\nacgt\n", DataType::Dna, &mut labels).unwrap();
+///
+/// assert_eq!(labels.num_labels(), 3);
+/// assert_eq!(alignment.sequence(labels.get_index("A").unwrap()), Some(&b"ACGT"[..]));
+/// assert_eq!(alignment.sequence(labels.get_index("B").unwrap()), Some(&b"ACGT"[..]));
+/// ```
+pub fn parse_str(fasta: &str, data_type: DataType, labels: &mut LeafLabelMap) -> Result<Alignment, ParsingError> {
+    parse_bytes(fasta.as_bytes(), data_type, labels)
+}
+
+/// Parses aligned FASTA bytes, see [parse_file].
+///
+/// # Errors
+/// Returns a [ParsingError] if the sequences are invalid, see [parse_file]
+pub fn parse_bytes<B: AsRef<[u8]>>(bytes: B, data_type: DataType, labels: &mut LeafLabelMap) -> Result<Alignment, ParsingError> {
+    parse_all(&mut ByteParser::new(InMemoryByteSource::new(bytes)), data_type, labels)
+}
+
+/// Parses all sequences and resolves their names once all are checked.
+fn parse_all<S: ByteSource>(parser: &mut ByteParser<S>, data_type: DataType, labels: &mut LeafLabelMap)
+                            -> Result<Alignment, ParsingError> {
+    let mut rows: Vec<(String, Vec<u8>)> = Vec::new();
+    let mut names: HashSet<String> = HashSet::new();
+    loop {
+        match parser.peek() {
+            None | Some(b'>') => {
+                check_length(parser, &rows)?;
+                if !parser.consume_if(b'>') {
+                    break;
+                }
+                let name = parse_header(parser);
+                if name.is_empty() {
+                    return Err(ParsingError::invalid_fasta(parser, "Missing sequence name after '>'".to_string()));
+                }
+                if !names.insert(name.clone()) {
+                    return Err(ParsingError::invalid_fasta(parser, format!("Duplicate sequence '{}'", name)));
+                }
+                rows.push((name, Vec::new()));
+            }
+            Some(b';') => {
+                parser.consume_until(b'\n', ConsumeMode::Inclusive);
+            }
+            Some(b) if b.is_ascii_whitespace() => {
+                parser.next();
+            }
+            Some(b) => {
+                let Some((_, sequence)) = rows.last_mut() else {
+                    return Err(ParsingError::invalid_fasta(parser, "Expected '>' before first sequence".to_string()));
+                };
+                sequence.push(data_type.normalize(b));
+                parser.next();
+            }
+        }
+    }
+
+    if rows.is_empty() {
+        return Err(ParsingError::invalid_fasta(parser, "No sequences".to_string()));
+    }
+    Ok(Alignment::from_named_sequences(data_type, rows, labels).expect("Sequences have distinct names and the same length"))
+}
+
+// ============================================================================
+// Little Helpers
+// ============================================================================
+/// Parses the rest of a header line and returns its first word (the name).
+fn parse_header<S: ByteSource>(parser: &mut ByteParser<S>) -> String {
+    let start = parser.position();
+    parser.consume_until(b'\n', ConsumeMode::Exclusive);
+    let header = String::from_utf8_lossy(parser.slice_from(start)).into_owned();
+    header.split_whitespace().next().unwrap_or_default().to_string()
+}
+
+/// Checks that the last sequence has the length of the first one.
+fn check_length<S: ByteSource>(parser: &ByteParser<S>, rows: &[(String, Vec<u8>)]) -> Result<(), ParsingError> {
+    if let (Some((_, first)), Some((name, last))) = (rows.first(), rows.last()) && last.len() != first.len() {
+        let msg = format!("Sequence '{}' has {} characters, expected {} as the first sequence", name, last.len(), first.len());
+        return Err(ParsingError::invalid_fasta(parser, msg));
+    }
+    Ok(())
+}
@@ -2,6 +2,22 @@
 pub mod newick;
 /// NEXUS format file parser
 pub mod nexus;
+/// NEXUS TRANSLATE table and its tokenizer
+pub mod translation;
+/// NEXUS DATA and CHARACTERS blocks (character matrices)
+pub(crate) mod characters;
+/// FASTA alignment file parser
+pub mod fasta;
+/// PHYLIP alignment file parser
+pub mod phylip;
+/// Parameter trace (BEAST .log, MrBayes .p) parser
+pub mod trace;
+/// phyloXML format file parser
+pub mod phyloxml;
+/// NeXML format file parser
+pub mod nexml;
+/// Minimal XML reader for XML based formats
+pub(crate) mod xml;
 /// Low-level byte parsing utilities
 pub mod byte_parser;
 /// Byte source abstractions for parsing (trait and implementations)
@@ -1,23 +1,129 @@
-use crate::model::leaf_label_map::LeafLabelMap;
-use crate::model::tree::{LabelIndex, Tree, TreeIndex};
-use crate::model::vertex::BranchLength;
-use crate::parser::byte_parser::ByteParser;
-use crate::parser::byte_source::ByteSource;
-use crate::parser::parsing_error::ParsingError;
+use crate::model::annotation::Annotation;
+use crate::model::leaf_label_map::{DuplicateTaxonPolicy, LeafLabel, LeafLabelMap};
+use crate::model::network::{split_reticulation_tag, Network, TaggedVertex};
+use crate::model::tree::{LabelIndex, StoredIndex, Tree, TreeIndex};
+use crate::model::tree_builder::TreeBuilder;
+use crate::model::vertex::{from_stored, BranchLength, BranchLengthError};
+#[cfg(feature = "fs")]
+use crate::io::compression::decompressed_reader;
+use crate::io::parser::byte_parser::{ByteParser, ParseLabel};
+use crate::io::parser::byte_source::{ByteSource, InMemoryByteSource};
+use crate::io::parser::parsing_error::ParsingError;
+use std::borrow::Cow;
 use std::collections::HashMap;
 use std::fmt;
+#[cfg(feature = "fs")]
+use std::fs::File;
+#[cfg(feature = "fs")]
+use std::io::{BufReader, Read};
 
 /// Newick label delimiters: parentheses, comma, colon, semicolon, whitespace
 const NEWICK_LABEL_DELIMITERS: &[u8] = b"([,:; \n\t\r)]";
 
+/// Bytes ending a step of parsing a tree with [NewickParser::parse_step] (outside comments and quotes)
+pub(crate) const NEWICK_STEP_DELIMITERS: &[u8] = b"(),;";
+
 /// Default guess for number of leaves, when unknown
 const DEFAULT_NUM_LEAVES_GUESS: usize = 10;
 
+/// Label index of placeholder leaves standing for additional parents of reticulations while parsing a network
+/// (largest label index a vertex can store)
+const RETICULATION_PLACEHOLDER: LabelIndex = from_stored(StoredIndex::MAX);
+
+/// Largest magnitude of a negative branch length considered numerical noise (e.g. `-1e-17`),
+/// handled by the [NegativeBranchLengthPolicy]; more negative lengths are always an error
+pub const NEGATIVE_BRANCH_LENGTH_TOLERANCE: f64 = 1e-6;
+
+// =#========================================================================#=
+// NEWICK FILE PARSING
+// =#========================================================================#=
+/// Parses all Newick trees of a file, e.g. one tree per line, each terminated by `;`.
+///
+/// Trees share a single [LeafLabelMap] built from their verbatim labels;
+/// comments and whitespace between trees are skipped. Compressed files are decompressed
+/// (see [decompressed_reader]).
+///
+/// # Returns
+/// A tuple of (trees, label_map) with all trees in order of appearance
+///
+/// # Errors
+/// Returns a [ParsingError] if the file cannot be read or a tree is invalid
+#[cfg(feature = "fs")]
+pub fn parse_file(file: File) -> Result<(Vec<Tree>, LeafLabelMap), ParsingError> {
+    let mut bytes = Vec::new();
+    decompressed_reader(BufReader::new(file))
+        .and_then(|mut reader| reader.read_to_end(&mut bytes))
+        .map_err(|e| ParsingError::io_error(&e, 0))?;
+    parse_all(&mut ByteParser::new(InMemoryByteSource::from_vec(bytes)))
+}
+
+/// Parses all Newick trees of a string, see [parse_file].
+///
+/// # Errors
+/// Returns a [ParsingError] if a tree is invalid
+pub fn parse_str(newicks: &str) -> Result<(Vec<Tree>, LeafLabelMap), ParsingError> {
+    parse_bytes(newicks.as_bytes())
+}
+
+/// Parses all Newick trees of the given bytes, see [parse_file].
+///
+/// # Errors
+/// Returns a [ParsingError] if a tree is invalid
+pub fn parse_bytes<B: AsRef<[u8]>>(bytes: B) -> Result<(Vec<Tree>, LeafLabelMap), ParsingError> {
+    parse_all(&mut ByteParser::new(InMemoryByteSource::new(bytes)))
+}
+
+/// Parses all Newick trees of a string like [parse_str], but with labels borrowed from the string
+/// where possible instead of copying them, see [LeafLabelMap::new_borrowed].
+///
+/// # Errors
+/// Returns a [ParsingError] if a tree is invalid
+///
+/// # Example
+/// ```
+/// use nexus_parser::io::parser::newick;
+///
+/// let newicks = String::from("((A,B),C);\n((A,C),B);");
+/// let (trees, labels) = newick::parse_str_borrowed(&newicks).unwrap();
+/// assert_eq!(labels.num_borrowed(), 3);
+///
+/// // Keep labels after input is gone
+/// let labels = labels.into_owned();
+/// drop(newicks);
+/// assert_eq!(labels.get_label(0), Some("A"));
+/// assert_eq!(trees[1].num_leaves(), 3);
+/// ```
+pub fn parse_str_borrowed(newicks: &str) -> Result<(Vec<Tree>, LeafLabelMap<Cow<'_, str>>), ParsingError> {
+    let (trees, newick_parser) = parse_trees(&mut ByteParser::from_slice(newicks.as_bytes()), NewickParser::new_borrowed())?;
+    Ok((trees, newick_parser.into_leaf_label_map()))
+}
+
+/// Parses trees until the end of input, skipping comments and whitespace between them.
+fn parse_all<S: ByteSource>(parser: &mut ByteParser<S>) -> Result<(Vec<Tree>, LeafLabelMap), ParsingError> {
+    let (trees, newick_parser) = parse_trees(parser, NewickParser::new())?;
+    Ok((trees, newick_parser.into_leaf_label_map()))
+}
+
+/// Parses trees with the given parser until the end of input and returns them and the parser.
+fn parse_trees<S: ByteSource, R: ResolveLeafLabel<S>>(parser: &mut ByteParser<S>, mut newick_parser: NewickParser<R>)
+    -> Result<(Vec<Tree>, NewickParser<R>), ParsingError> {
+    let mut trees = Vec::new();
+    loop {
+        parser.skip_comment_and_whitespace()?;
+        if parser.is_eof() {
+            break;
+        }
+        trees.push(newick_parser.parse(parser)?);
+    }
+
+    Ok((trees, newick_parser))
+}
+
 // =#========================================================================#=
 // NEWICK PARSER
 // =#========================================================================#=
 
-/// Parser (configuration) for Newick format (binary) phylogenetic [Tree]s.
+/// Parser (configuration) for Newick format phylogenetic [Tree]s (binary or multifurcating).
 ///
 /// Supports parsing single or multiple Newick trees. Uses a [LabelResolver]
 /// mechanism to turn ids or labels in Newick strings into a shared [LeafLabelMap].
@@ -25,41 +131,56 @@ const DEFAULT_NUM_LEAVES_GUESS: usize = 10;
 ///
 /// # Configuration
 /// * `with_num_leaves(num_leaves)` - Can be configured with number of leaves in trees to parse,
-///    otherwise it is inferred from the first parsed tree and then stored.
+///   otherwise it is inferred from the first parsed tree and then stored.
 /// * `with_resolver(resolver)` - Requires a [LabelResolver] if labels are not stored directly in newick strings.
-/// * Plan to include in the future `with_annotations()`, so that it can be configured
-///   to parse vertex annotation instead of considering them comments,
-///   (e.g. extract `pop_size` and value from "A[&pop_size=0.123]").
-///   For now, annotation remains unsupported.
+/// * `with_topology_only()` - Fast path for Newick strings with (1-based) integer keys as labels,
+///   e.g. from a Nexus `TRANSLATE` command, when labels and branch lengths are not needed;
+///   key `k` directly becomes [LabelIndex] `k - 1` without touching any [LeafLabelMap].
+/// * `with_annotations(mode)` - Whether to skip metadata comments like `[&pop_size=0.123]` (default),
+///   or to collect them (raw or parsed into key/value entries) as [Annotation]s of the tree,
+///   see [AnnotationMode].
+/// * `with_duplicate_taxa(policy)` - Whether a taxon appearing more than once in the same tree
+///   is an error (default), allowed, or renamed, see [DuplicateTaxonPolicy].
+/// * `with_negative_branch_lengths(policy)` - Whether tiny negative branch lengths from numerical noise
+///   are clamped to zero (default), an error, or kept, see [NegativeBranchLengthPolicy].
+///
+/// `new_borrowed()` creates a parser for borrowed in-memory input
+/// that keeps labels as slices of the input in a `LeafLabelMap<Cow<str>>` (see [LeafLabelMap::new_borrowed]).
 ///
 /// # Format
 /// The Newick format has the following simple structure:
 /// * tree ::= vertex ';'
 /// * vertex ::= leaf | internal_vertex
-/// * internal_vertex ::= '(' vertex ',' vertex ')' \[branch_length\]
+/// * internal_vertex ::= '(' vertex { ',' vertex } ')' \[label\] \[branch_length\]
 /// * leaf ::= label \[branch_length\]
 /// * branch_length ::= ':' number
 ///
 /// Furthermore:
+/// * Labels of internal vertices (and the root) are optional,
+///   e.g. clade names `(A,B)Kiwi:0.1` or support values `(A,B)95:0.1`
 /// * Whitespace can occur between elements,
 ///   just not within unquoted label or in branch_length
 /// * Even newlines can occur anywhere except in labels (quoted and unquoted)
 /// * Comments are square brackets and can occur anywhere where newlines are allowed
 ///
-/// In the extended Newick format, there can be comment-like annotation:
-/// * `[@pop_size=0.543,color=blue]`
+/// In the extended Newick format (e.g. of BEAST and MrBayes), there can be comment-like annotation:
+/// * `[&pop_size=0.543,color=blue]`
+///
 /// For a leaf:
 /// * label \[annotation\] \[branch_length\]
-///   - Example: A\[@pop_size=0.543\]:2.1
+///   - Example: A\[&pop_size=0.543\]:2.1
+///
 /// For an internal vertex and the root:
-/// * (children) \[annotation\] \[branch_length\]
-///   - Example: (A,B\[@pop_seize=0.345\]:6.7
-/// These are considered comments for now and skipped.
+/// * (children) \[label\] \[annotation\] \[branch_length\]
+///   - Example: (A,B)\[&pop_size=0.345\]:6.7
+///
+/// Annotations directly after the colon are annotations of the branch, e.g. `A:[&rate=0.1]2.1`.
+/// Unless configured otherwise, they are considered comments and skipped.
 ///
 /// # Example
 /// ```
-/// use nexus_parser::parser::newick::NewickParser;
-/// use nexus_parser::parser::byte_parser::ByteParser;
+/// use nexus_parser::io::parser::newick::NewickParser;
+/// use nexus_parser::io::parser::byte_parser::ByteParser;
 /// use nexus_parser::model::tree::Tree;
 /// use nexus_parser::model::leaf_label_map::LeafLabelMap;
 ///
@@ -70,11 +191,115 @@ const DEFAULT_NUM_LEAVES_GUESS: usize = 10;
 /// let tree = newick_parser.parse(&mut byte_parser).unwrap(); // let it parse to get tree
 /// let labels = newick_parser.into_leaf_label_map(); // consume into LeafLabelMap
 /// ```
-pub struct NewickParser {
+pub struct NewickParser<R = LabelResolver> {
     know_num_leaves: bool,
     num_leaves: usize,
-    resolver: LabelResolver,
-    // parse_annotation: bool,
+    resolver: R,
+    topology_only: bool,
+    annotations: AnnotationMode,
+    duplicate_taxa: DuplicateTaxonPolicy,
+    negative_branch_lengths: NegativeBranchLengthPolicy,
+    /// Taxa already seen in the tree currently parsed, indexed by [LabelIndex]
+    seen_taxa: Vec<bool>,
+    /// Source of vertex arenas and children lists, reusing those of recycled trees
+    builder: TreeBuilder,
+    /// Vertices with reticulation tags (e.g. `#H1`) of the network currently parsed; `None` unless parsing a network
+    tagged: Option<Vec<TaggedVertex>>,
+}
+
+/// Handling of metadata comments `[&...]` in Newick strings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AnnotationMode {
+    /// Skip them like any other comment (default, fastest)
+    #[default]
+    Skip,
+    /// Collect their raw content as [Annotation]s of the tree
+    Raw,
+    /// Collect them as [Annotation]s of the tree, parsed into key/value entries
+    Parsed,
+}
+
+/// Handling of tiny negative branch lengths (at most [NEGATIVE_BRANCH_LENGTH_TOLERANCE] below zero,
+/// e.g. `-1e-17`) that some tools write due to numerical noise.
+///
+/// More negative lengths, and lengths that are not finite (e.g. `1e999`), are always a parsing error.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NegativeBranchLengthPolicy {
+    /// Set them to zero (default)
+    #[default]
+    ClampToZero,
+    /// Fail with an invalid Newick string parsing error
+    Error,
+    /// Keep them as they are, e.g. to inspect them (reported by [Tree::validate])
+    Keep,
+}
+
+/// Tree parsed step by step by a [NewickParser], see [NewickParser::parse_step],
+/// e.g. to continue parsing it once the next chunk of input arrives.
+pub(crate) struct PartialTree {
+    tree: Tree,
+    /// Children parsed so far of each vertex whose `(` but not yet `)` was consumed (most vertices are binary)
+    open: Vec<Vec<TreeIndex>>,
+    next: NextStep,
+}
+
+/// Next step of parsing a [PartialTree]
+enum NextStep {
+    /// Opening `(` of the root
+    Start,
+    /// Next child of the innermost open vertex, either opening an internal vertex or a leaf
+    Child,
+    /// Rest of an internal vertex after its children
+    Internal(Vec<TreeIndex>),
+    /// Rest of the root after its children
+    Root(Vec<TreeIndex>),
+    /// None, tree is complete
+    Done,
+}
+
+impl PartialTree {
+    /// Returns the tree parsed so far.
+    pub(crate) fn tree(&self) -> &Tree {
+        &self.tree
+    }
+
+    /// Returns the tree parsed so far, e.g. to set whether it is rooted.
+    pub(crate) fn tree_mut(&mut self) -> &mut Tree {
+        &mut self.tree
+    }
+
+    /// Returns whether the root was parsed.
+    pub(crate) fn is_complete(&self) -> bool {
+        matches!(self.next, NextStep::Done)
+    }
+
+    /// Adds a parsed child to the innermost open vertex and consumes the `,` or `)` after it;
+    /// on `)`, the rest of the closed vertex (or of the root) is the next step.
+    fn add_child<S: ByteSource>(&mut self, parser: &mut ByteParser<S>, child: TreeIndex) -> Result<(), ParsingError> {
+        self.open.last_mut().expect("Some vertex is open while parsing children").push(child);
+
+        parser.skip_comment_and_whitespace()?;
+        if parser.consume_if(b',') {
+            self.next = NextStep::Child;
+            return Ok(());
+        }
+        if !parser.consume_if(b')') {
+            return Err(ParsingError::invalid_newick_string(
+                parser,
+                format!("Expected ',' or ')' after child but found {:?}", parser.peek().map(|b| b as char)),
+            ));
+        }
+
+        let children = self.open.pop().expect("Some vertex is open while parsing children");
+        self.next = if self.open.is_empty() { NextStep::Root(children) } else { NextStep::Internal(children) };
+        Ok(())
+    }
+}
+
+impl Default for NewickParser {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl NewickParser {
@@ -86,36 +311,107 @@ impl NewickParser {
             know_num_leaves: false,
             num_leaves: DEFAULT_NUM_LEAVES_GUESS,
             resolver: LabelResolver::VerbatimLabels(LeafLabelMap::new(DEFAULT_NUM_LEAVES_GUESS)),
+            topology_only: false,
+            annotations: AnnotationMode::Skip,
+            duplicate_taxa: DuplicateTaxonPolicy::Error,
+            negative_branch_lengths: NegativeBranchLengthPolicy::ClampToZero,
+            seen_taxa: Vec::new(),
+            builder: TreeBuilder::new(),
+            tagged: None,
         }
     }
+}
 
+impl<L: LeafLabel> NewickParser<LabelResolver<L>> {
     /// Creates a new [NewickParser] based on the given [LabelResolver].
     ///
     /// The number of leaves is derived from [LabelResolver]'s [LeafLabelMap] if non-zero,
     /// otherwise default value used and actual number counted parsing first tree.
-    pub fn new_with_resolver(resolver: LabelResolver) -> Self {
+    pub fn new_with_resolver(resolver: LabelResolver<L>) -> Self {
         if resolver.leaf_label_map().num_labels() > 0 {
             Self {
                 know_num_leaves: true,
                 num_leaves: resolver.leaf_label_map().num_labels(),
                 resolver,
+                topology_only: false,
+                annotations: AnnotationMode::Skip,
+                duplicate_taxa: DuplicateTaxonPolicy::Error,
+                negative_branch_lengths: NegativeBranchLengthPolicy::ClampToZero,
+                seen_taxa: Vec::new(),
+                builder: TreeBuilder::new(),
+                tagged: None,
             }
         } else {
             Self {
                 know_num_leaves: false,
                 num_leaves: DEFAULT_NUM_LEAVES_GUESS,
                 resolver,
+                topology_only: false,
+                annotations: AnnotationMode::Skip,
+                duplicate_taxa: DuplicateTaxonPolicy::Error,
+                negative_branch_lengths: NegativeBranchLengthPolicy::ClampToZero,
+                seen_taxa: Vec::new(),
+                builder: TreeBuilder::new(),
+                tagged: None,
             }
         }
     }
 
     /// Sets a [LabelResolver] to resolve short/id keys or labels in Newick string
     /// to indices in [LeafLabelMap].
-    pub fn with_resolver(mut self, resolver: LabelResolver) -> Self {
+    pub fn with_resolver(mut self, resolver: LabelResolver<L>) -> Self {
         self.resolver = resolver;
         self
     }
 
+    /// Consumes the parser and returns the underlying [LeafLabelMap].
+    ///
+    /// This should be called after all trees have been parsed to retrieve
+    /// the mapping of leaf labels to indices. This could either be a
+    /// constructed [LeafLabelMap] or the originally provided via a [LabelResolver].
+    pub fn into_leaf_label_map(self) -> LeafLabelMap<L> {
+        self.resolver.into_leaf_label_map()
+    }
+
+    /// Get ref to [LeafLabelMap] of all taxa
+    pub fn leaf_label_map(&self) -> &LeafLabelMap<L> {
+        self.resolver.leaf_label_map()
+    }
+}
+
+impl<'a> NewickParser<LabelResolver<Cow<'a, str>>> {
+    /// Creates a new [NewickParser] for borrowed in-memory input (see [ByteParser::from_slice])
+    /// that stores leaf labels as slices of the input where possible, see [LeafLabelMap::new_borrowed].
+    ///
+    /// # Example
+    /// ```
+    /// use nexus_parser::io::parser::byte_parser::ByteParser;
+    /// use nexus_parser::io::parser::newick::NewickParser;
+    ///
+    /// let input = b"((A:1,B:1):1,'C':2);";
+    /// let mut newick_parser = NewickParser::new_borrowed();
+    /// let tree = newick_parser.parse(&mut ByteParser::from_slice(input)).unwrap();
+    /// let labels = newick_parser.into_leaf_label_map();
+    /// assert_eq!(labels.num_borrowed(), 3);
+    /// assert_eq!(tree.num_leaves(), 3);
+    /// ```
+    pub fn new_borrowed() -> Self {
+        Self {
+            know_num_leaves: false,
+            num_leaves: DEFAULT_NUM_LEAVES_GUESS,
+            resolver: LabelResolver::VerbatimLabels(LeafLabelMap::new_borrowed(DEFAULT_NUM_LEAVES_GUESS)),
+            topology_only: false,
+            annotations: AnnotationMode::Skip,
+            duplicate_taxa: DuplicateTaxonPolicy::Error,
+            negative_branch_lengths: NegativeBranchLengthPolicy::ClampToZero,
+            seen_taxa: Vec::new(),
+            builder: TreeBuilder::new(),
+            tagged: None,
+        }
+    }
+}
+
+impl<R> NewickParser<R> {
     /// Sets the expected number of leaves in the tree.
     ///
     /// This allows pre-allocation of data structures for better performance.
@@ -126,18 +422,56 @@ impl NewickParser {
         self
     }
 
-    /// Consumes the parser and returns the underlying [LeafLabelMap].
+    /// Configures the parser to only parse the topology of trees with (1-based) integer keys as labels.
     ///
-    /// This should be called after all trees have been parsed to retrieve
-    /// the mapping of leaf labels to indices. This could either be a
-    /// constructed [LeafLabelMap] or the originally provided via a [LabelResolver].
-    pub fn into_leaf_label_map(self) -> LeafLabelMap {
-        self.resolver.into_leaf_label_map()
+    /// Meant for workloads that never need label strings or branch lengths
+    /// (e.g. RF matrices over translated Nexus files):
+    /// - Leaf with key `k` gets [LabelIndex] `k - 1`, without any hash lookups
+    /// - Branch lengths are skipped instead of parsed, so vertices have no [BranchLength]
+    /// - The [LabelResolver] is bypassed and its [LeafLabelMap] stays untouched
+    ///
+    /// Parsing fails with an unresolved label error if a leaf is not keyed by a positive integer,
+    /// or by one larger than the number of leaves (if known).
+    pub fn with_topology_only(mut self) -> Self {
+        self.topology_only = true;
+        self
     }
 
-    /// Get ref to [LeafLabelMap] of all taxa
-    pub fn leaf_label_map(&self) -> &LeafLabelMap {
-        &self.resolver.leaf_label_map()
+    /// Sets how metadata comments `[&...]` are handled (default [AnnotationMode::Skip]).
+    ///
+    /// Collected annotations are available via [Tree::annotation] and [Tree::branch_annotation];
+    /// several comments at the same position are joined by `,`.
+    /// In topology-only mode, annotations are always skipped.
+    pub fn with_annotations(mut self, mode: AnnotationMode) -> Self {
+        self.annotations = mode;
+        self
+    }
+
+    /// Sets how a taxon appearing more than once in the same tree is handled (default [DuplicateTaxonPolicy::Error]).
+    ///
+    /// With [DuplicateTaxonPolicy::AutoRename], renamed taxa must not exceed the number of leaves once known
+    /// (e.g. `A_3` in a later tree whose first tree had only `A` and `A_2`, or any new taxon with a TAXA block),
+    /// otherwise it fails like [DuplicateTaxonPolicy::Error].
+    /// In topology-only mode, the [LeafLabelMap] stays untouched, so [DuplicateTaxonPolicy::AutoRename]
+    /// fails like [DuplicateTaxonPolicy::Error].
+    pub fn with_duplicate_taxa(mut self, policy: DuplicateTaxonPolicy) -> Self {
+        self.duplicate_taxa = policy;
+        self
+    }
+
+    /// Sets how tiny negative branch lengths from numerical noise are handled
+    /// (default [NegativeBranchLengthPolicy::ClampToZero]).
+    pub fn with_negative_branch_lengths(mut self, policy: NegativeBranchLengthPolicy) -> Self {
+        self.negative_branch_lengths = policy;
+        self
+    }
+
+
+    /// Takes back a parsed tree that is no longer needed, so the next trees reuse its vertex arena
+    /// and children lists instead of allocating new ones (see [TreeBuilder]),
+    /// e.g. when streaming a sample and processing one tree at a time.
+    pub fn recycle(&mut self, tree: Tree) {
+        self.builder.recycle(tree);
     }
 
     /// Parses a single Newick tree from the given [ByteParser].
@@ -153,40 +487,153 @@ impl NewickParser {
     /// * `Ok(Tree)` - The parsed phylogenetic tree
     /// * `Err(ParsingError)` - If the Newick format is invalid
     ///
-    pub fn parse<S: ByteSource>(&mut self, parser: &mut ByteParser<S>) -> Result<Tree, ParsingError> {
-        let mut tree = Tree::new(self.num_leaves);
+    pub fn parse<S: ByteSource>(&mut self, parser: &mut ByteParser<S>) -> Result<Tree, ParsingError>
+        where R: ResolveLeafLabel<S> {
+        let mut partial = self.begin_tree();
+        while !partial.is_complete() {
+            if let Err(e) = self.parse_step(parser, &mut partial) {
+                self.abandon_tree(partial);
+                return Err(e);
+            }
+        }
+
+        Ok(self.end_tree(partial))
+    }
+
+    /// Starts parsing a tree step by step, see [NewickParser::parse_step].
+    pub(crate) fn begin_tree(&mut self) -> PartialTree {
+        let tree = self.builder.tree(self.num_leaves.max(1));
+        self.seen_taxa.clear();
 
         // Reset number of leaves to 0, so we can now track it and determine the actual count
         if !self.know_num_leaves {
             self.num_leaves = 0;
         }
 
-        self.parse_root(parser, &mut tree)?;
+        PartialTree { tree, open: Vec::new(), next: NextStep::Start }
+    }
+
+    /// Parses the next step of a tree and adds the vertex it completes (if any) to the tree.
+    ///
+    /// Each step ends with the first `(`, `,`, `)` or `;` outside comments and quotes (see [NEWICK_STEP_DELIMITERS]),
+    /// so input can be fed step by step as it arrives:
+    /// - `(` opening the root or an internal vertex (skipping comments/whitespace before it)
+    /// - `label[:branch_length]` of a leaf and the `,` or `)` after it
+    /// - `[label][:branch_length]` of an internal vertex after its closing `)`, and the `,` or `)` after it
+    /// - `[label][:branch_length]` of the root after its closing `)`, and the terminating `;`
+    ///
+    /// Parses nested internal vertices with an explicit stack instead of recursion,
+    /// so arbitrarily deep trees (e.g. caterpillars on 100k+ taxa) cannot overflow the call stack.
+    ///
+    /// # Returns
+    /// - [TreeIndex] of the vertex completed by the step, if any (the root completes the tree)
+    /// - [ParsingError] if something went wrong
+    pub(crate) fn parse_step<S: ByteSource>(&mut self, parser: &mut ByteParser<S>, partial: &mut PartialTree)
+        -> Result<Option<TreeIndex>, ParsingError>
+        where R: ResolveLeafLabel<S> {
+        match std::mem::replace(&mut partial.next, NextStep::Done) {
+            NextStep::Start => {
+                parser.skip_comment_and_whitespace()?;
+                if !parser.consume_if(b'(') {
+                    return Err(ParsingError::invalid_newick_string(
+                        parser,
+                        format!("Expected '(' before children but found {:?}", parser.peek().map(|b| b as char)),
+                    ));
+                }
+                partial.open.push(self.builder.children());
+                partial.next = NextStep::Child;
+                Ok(None)
+            }
+            NextStep::Child => {
+                // Next child either opens an internal vertex or is a leaf
+                parser.skip_comment_and_whitespace()?;
+                if parser.consume_if(b'(') {
+                    partial.open.push(self.builder.children());
+                    partial.next = NextStep::Child;
+                    return Ok(None);
+                }
+                let child = self.parse_leaf(parser, &mut partial.tree)?;
+                partial.add_child(parser, child)?;
+                Ok(Some(child))
+            }
+            NextStep::Internal(children) => {
+                let child = self.parse_internal_vertex(parser, &mut partial.tree, children)?;
+                partial.add_child(parser, child)?;
+                Ok(Some(child))
+            }
+            NextStep::Root(children) => self.parse_root(parser, &mut partial.tree, children).map(Some),
+            NextStep::Done => Ok(None),
+        }
+    }
+
+    /// Finishes a tree parsed step by step (see [NewickParser::parse_step]) once it is complete.
+    pub(crate) fn end_tree(&mut self, partial: PartialTree) -> Tree {
+        debug_assert!(partial.is_complete(), "Tree is complete");
+        let mut tree = partial.tree;
 
         // Having parsed a full tree, we now know the number of leaves in a tree
+        if !self.know_num_leaves {
+            tree.set_num_leaves_init(self.num_leaves);
+        }
         self.know_num_leaves = true;
 
-        Ok(tree)
+        tree
+    }
+
+    /// Abandons a tree parsed step by step (e.g. after an error), reusing its vertex arena for the next tree.
+    pub(crate) fn abandon_tree(&mut self, partial: PartialTree) {
+        self.builder.recycle(partial.tree);
     }
 
-    /// Parses root of tree and adds it to tree:
-    /// - `(left, right)[:branch_length]`
-    /// - Skips leading comments and whitespace
-    /// - Calls `parser_children` to parse the children pair
+    /// Parses a single rooted phylogenetic network in extended Newick from the given [ByteParser],
+    /// e.g. `((A,(B)#H1),(#H1,C));` (see [Network]).
     ///
-    /// Equivalent to `parse_internal_vertex` but taking care of root specialities
-    fn parse_root<S: ByteSource>(&mut self, parser: &mut ByteParser<S>, tree: &mut Tree) -> Result<(), ParsingError> {
-        parser.skip_comment_and_whitespace()?;
+    /// Labels ending in a reticulation tag (`#H<n>`, `#LGT<n>` or `#R<n>`) mark the reticulation vertex
+    /// (with its subtree or taxon, e.g. `(B)#H1` or `B#H1`) and, as leaves with just the tag (`#H1`),
+    /// each of its additional parents; the tags are removed from the labels. Networks without tags are trees.
+    /// In topology-only mode, tags are not recognized.
+    ///
+    /// # Returns
+    /// * `Ok(Network)` - The parsed network
+    /// * `Err(ParsingError)` - If the Newick format is invalid, or a tag has no vertex (or several),
+    ///   tags the root or the reticulations form a cycle
+    ///
+    /// # Example
+    /// ```
+    /// use nexus_parser::io::parser::byte_parser::ByteParser;
+    /// use nexus_parser::io::parser::newick::NewickParser;
+    ///
+    /// let mut newick_parser = NewickParser::new();
+    /// let network = newick_parser.parse_network(&mut ByteParser::from_str("((A,B#LGT1),(#LGT1:0.5,C));")).unwrap();
+    ///
+    /// let transfer = &network.reticulations()[0];
+    /// assert_eq!(transfer.tag(), "#LGT1");
+    /// assert_eq!(network.tree()[transfer.child].label_index(), newick_parser.leaf_label_map().get_index("B"));
+    /// ```
+    pub fn parse_network<S: ByteSource>(&mut self, parser: &mut ByteParser<S>) -> Result<Network, ParsingError>
+        where R: ResolveLeafLabel<S> {
+        self.tagged = Some(Vec::new());
+        let tree = self.parse(parser);
+        let tagged = self.tagged.take().unwrap_or_default();
+        Network::from_tagged_tree(tree?, tagged).map_err(|msg| ParsingError::invalid_newick_string(parser, msg))
+    }
 
-        let (left_index, right_index) = self.parser_children(parser, tree)?;
+    /// Parses the rest of the root after its children and adds it to tree:
+    /// - `[label][:branch_length];` after the closing `)` of `(child, ..., child)`
+    /// - Consumes the terminating semicolon
+    ///
+    /// Equivalent to `parse_internal_vertex` but taking care of root specialities
+    fn parse_root<S: ByteSource>(&mut self, parser: &mut ByteParser<S>, tree: &mut Tree, children: Vec<TreeIndex>) -> Result<TreeIndex, ParsingError>
+        where R: ResolveLeafLabel<S> {
+        let mut node_annotations = Vec::new();
+        let mut branch_annotations = Vec::new();
+        let node_label = self.parse_node_label(parser, &mut node_annotations)?;
 
         // Root may have an optional branch length (which we ignore for now)
-        if parser.peek() == Some(b':') {
-            let _ = self.parse_branch_length(parser)?;
-        }
+        let _ = self.parse_branch_length(parser, &mut node_annotations, &mut branch_annotations)?;
 
         // Consume the terminating semicolon
-        parser.skip_comment_and_whitespace()?;
+        self.skip_collecting_annotations(parser, &mut node_annotations)?;
         if !parser.consume_if(b';') {
             return Err(ParsingError::invalid_newick_string(
                 parser,
@@ -194,96 +641,177 @@ impl NewickParser {
             ));
         }
 
-        tree.add_root((left_index, right_index));
+        let index = tree.add_root(children);
+        let node_label = self.take_reticulation_tag(parser, index, node_label)?;
+        tree[index].set_node_label(node_label);
+        self.store_annotations(tree, index, node_annotations, branch_annotations);
 
-        Ok(())
+        Ok(index)
     }
 
-    /// Parses a vertex (either internal vertex or leaf) and returns its vertex:
-    /// - Skips leading comments and whitespace
-    /// - Dispatches to `parse_internal_vertex` if starts with `(`, otherwise `parse_leaf`
+    /// Parses the rest of an internal vertex after its children, adds it to tree, and returns its index:
+    /// - `[label][:branch_length]` after the closing `)` of `(child, ..., child)`
     ///
     /// # Returns
     /// - [TreeIndex] of parsed internal vertex
     /// - [ParsingError] if something went wrong
-    fn parse_vertex<S: ByteSource>(&mut self, parser: &mut ByteParser<S>, tree: &mut Tree) -> Result<TreeIndex, ParsingError> {
-        parser.skip_comment_and_whitespace()?;
-        if parser.peek_is(b'(') {
-            self.parse_internal_vertex(parser, tree)
-        } else {
-            self.parse_leaf(parser, tree)
-        }
+    fn parse_internal_vertex<S: ByteSource>(&mut self, parser: &mut ByteParser<S>, tree: &mut Tree, children: Vec<TreeIndex>) -> Result<TreeIndex, ParsingError>
+        where R: ResolveLeafLabel<S> {
+        let mut node_annotations = Vec::new();
+        let mut branch_annotations = Vec::new();
+        let node_label = self.parse_node_label(parser, &mut node_annotations)?;
+        let branch_length = self.parse_branch_length(parser, &mut node_annotations, &mut branch_annotations)?;
+
+        let index = tree.add_internal_vertex(children, branch_length);
+        let node_label = self.take_reticulation_tag(parser, index, node_label)?;
+        tree[index].set_node_label(node_label);
+        self.store_annotations(tree, index, node_annotations, branch_annotations);
+
+        Ok(index)
     }
 
-    /// Parses internal vertex, adds it to tree, and returns its index:
-    /// - `(left, right)[:branch_length]`
-    /// - Calls `parser_children` to parse the children pair
+    /// Parses optional label of internal vertex or root after its closing `)`:
+    /// - Quoted or unquoted, e.g. `NodeX` or support value `95` in `(A,B)95:0.1`
+    /// - Skips comments/whitespace before label, collecting annotations into `node_annotations` (if configured)
+    /// - In topology-only mode, label is skipped
     ///
     /// # Returns
-    /// - [TreeIndex] of parsed internal vertex
+    /// - Label if found
+    /// - `None` if found no label (or in topology-only mode)
     /// - [ParsingError] if something went wrong
-    fn parse_internal_vertex<S: ByteSource>(&mut self, parser: &mut ByteParser<S>, tree: &mut Tree) -> Result<TreeIndex, ParsingError> {
-        let (left_index, right_index) = self.parser_children(parser, tree)?;
-        // Annotation parsing will be added here.
-        let branch_length = self.parse_branch_length(parser)?;
-
-        let index = tree.add_internal_vertex((left_index, right_index), branch_length);
-
-        Ok(index)
+    fn parse_node_label<S: ByteSource>(&mut self, parser: &mut ByteParser<S>, node_annotations: &mut Vec<String>) -> Result<Option<String>, ParsingError> {
+        self.skip_collecting_annotations(parser, node_annotations)?;
+        match parser.peek() {
+            Some(b) if b == b'\'' || !NEWICK_LABEL_DELIMITERS.contains(&b) => {
+                let label = parser.parse_label(NEWICK_LABEL_DELIMITERS)?;
+                Ok(if self.topology_only { None } else { Some(label) })
+            }
+            _ => Ok(None),
+        }
     }
 
-    /// Parses children pair `(left, right)` and returns their indices:
-    /// - Expects parser at opening `(`
+    /// Parses leaf vertex and adds it to tree:
+    /// - `label[:branch_length]`
+    /// - Expects parser at start of label
     ///   (caller should skip leading comments/whitespace)
+    /// - Resolves label via the configured resolver
     ///
     /// # Returns
-    /// - [TreeIndex]s of left and right child vertices
+    /// - [TreeIndex] of parsed leaf
     /// - [ParsingError] if something went wrong
-    fn parser_children<S: ByteSource>(&mut self, parser: &mut ByteParser<S>, tree: &mut Tree) -> Result<(TreeIndex, TreeIndex), ParsingError> {
-        // Calling methods should have skipped comments and whitespace
-        if !parser.consume_if(b'(') {
-            return Err(ParsingError::invalid_newick_string(
-                parser,
-                format!("Expected '(' before children but found {:?}", parser.peek().map(|b| b as char)),
-            ));
+    fn parse_leaf<S: ByteSource>(&mut self, parser: &mut ByteParser<S>, tree: &mut Tree) -> Result<TreeIndex, ParsingError>
+        where R: ResolveLeafLabel<S> {
+        if self.topology_only {
+            return self.parse_leaf_by_key(parser, tree);
         }
-        let left_index = self.parse_vertex(parser, tree)?;
 
-        parser.skip_comment_and_whitespace()?;
-        if !parser.consume_if(b',') {
-            return Err(ParsingError::invalid_newick_string(
-                parser,
-                format!("Expected ',' between children but found {:?}", parser.peek().map(|b| b as char)),
-            ));
+        if self.tagged.is_some() {
+            return self.parse_network_leaf(parser, tree);
         }
-        let right_index = self.parse_vertex(parser, tree)?;
 
-        parser.skip_comment_and_whitespace()?;
-        if !parser.consume_if(b')') {
-            return Err(ParsingError::invalid_newick_string(
-                parser,
-                format!("Expected ')' after children but found {:?}", parser.peek().map(|b| b as char)),
-            ));
+        let label_index = self.resolver.parse_leaf_label(parser)?;
+        if let Some(label) = self.resolver.label(label_index)
+            && label.contains('#')
+            && split_reticulation_tag(label).is_some() {
+            return Err(ParsingError::reticulate_network(parser, label.to_string()));
         }
+        let label_index = self.check_duplicate_taxon(label_index, parser)?;
+        let mut node_annotations = Vec::new();
+        let mut branch_annotations = Vec::new();
+        let branch_length = self.parse_branch_length(parser, &mut node_annotations, &mut branch_annotations)?;
 
-        Ok((left_index, right_index))
+        let index = tree.add_leaf(branch_length, label_index);
+        self.store_annotations(tree, index, node_annotations, branch_annotations);
+        if !self.know_num_leaves {
+            self.num_leaves += 1;
+        }
+
+        Ok(index)
     }
 
-    /// Parses leaf vertex and adds it to tree:
-    /// - `label[:branch_length]`
-    /// - Expects parser at start of label
-    ///   (caller should skip leading comments/whitespace)
-    /// - Resolves label via the configured resolver
+    /// Parses leaf vertex of a network and adds it to tree, like `parse_leaf`:
+    /// - `label[#tag][:branch_length]` for a taxon (if tagged, a reticulation vertex)
+    /// - `#tag[:branch_length]` for an additional parent of a reticulation vertex,
+    ///   added as placeholder leaf that is not a taxon
     ///
     /// # Returns
     /// - [TreeIndex] of parsed leaf
     /// - [ParsingError] if something went wrong
-    fn parse_leaf<S: ByteSource>(&mut self, parser: &mut ByteParser<S>, tree: &mut Tree) -> Result<TreeIndex, ParsingError> {
+    fn parse_network_leaf<S: ByteSource>(&mut self, parser: &mut ByteParser<S>, tree: &mut Tree) -> Result<TreeIndex, ParsingError>
+        where R: ResolveLeafLabel<S> {
         let label = parser.parse_label(NEWICK_LABEL_DELIMITERS)?;
-        // Annotation parsing will be added here.
-        let label_index = self.resolver.resolve_label(&*label, parser)?;
-        let branch_length = self.parse_branch_length(parser)?;
+        let (label_index, tag) = match split_reticulation_tag(&label) {
+            Some(("", kind, number)) => (RETICULATION_PLACEHOLDER, Some((kind, number))),
+            Some((name, kind, number)) => (self.resolver.resolve_leaf_label(name, parser)?, Some((kind, number))),
+            None => (self.resolver.resolve_leaf_label(&label, parser)?, None),
+        };
+        let is_reference = label_index == RETICULATION_PLACEHOLDER;
+        let label_index = if is_reference { label_index } else { self.check_duplicate_taxon(label_index, parser)? };
+        let mut node_annotations = Vec::new();
+        let mut branch_annotations = Vec::new();
+        let branch_length = self.parse_branch_length(parser, &mut node_annotations, &mut branch_annotations)?;
 
+        let index = tree.add_leaf(branch_length, label_index);
+        self.store_annotations(tree, index, node_annotations, branch_annotations);
+        if let (Some((kind, number)), Some(tagged)) = (tag, self.tagged.as_mut()) {
+            tagged.push(TaggedVertex { index, kind, number, is_reference });
+        }
+        if !self.know_num_leaves && !is_reference {
+            self.num_leaves += 1;
+        }
+
+        Ok(index)
+    }
+
+    /// Handles a reticulation tag (e.g. `#H1`) at the end of the label of an internal vertex or the root:
+    /// records the vertex and returns the label without tag (`None` if empty) when parsing a network,
+    /// otherwise fails with a [ReticulateNetwork](crate::io::parser::parsing_error::ParsingErrorType::ReticulateNetwork) error.
+    fn take_reticulation_tag<S: ByteSource>(&mut self, parser: &ByteParser<S>, index: TreeIndex, node_label: Option<String>)
+        -> Result<Option<String>, ParsingError> {
+        let Some(label) = node_label.as_deref().filter(|label| label.contains('#')) else {
+            return Ok(node_label);
+        };
+        let Some((name, kind, number)) = split_reticulation_tag(label) else {
+            return Ok(node_label);
+        };
+        let Some(tagged) = self.tagged.as_mut() else {
+            return Err(ParsingError::reticulate_network(parser, label.to_string()));
+        };
+
+        tagged.push(TaggedVertex { index, kind, number, is_reference: false });
+        Ok((!name.is_empty()).then(|| name.to_string()))
+    }
+
+    /// Parses leaf vertex in topology-only mode and adds it to tree:
+    /// - `key[:branch_length]` with `key` a positive integer
+    /// - Expects parser at start of key
+    ///   (caller should skip leading comments/whitespace)
+    /// - Uses `key - 1` as label index and skips branch length
+    ///
+    /// # Returns
+    /// - [TreeIndex] of parsed leaf
+    /// - [ParsingError] if key is not a positive integer or out of bounds
+    fn parse_leaf_by_key<S: ByteSource>(&mut self, parser: &mut ByteParser<S>, tree: &mut Tree) -> Result<TreeIndex, ParsingError>
+        where R: ResolveLeafLabel<S> {
+        let key = match parser.parse_unsigned_integer() {
+            Some(key) if key > 0 => key,
+            _ => return Err(ParsingError::unresolved_label(
+                parser,
+                "Topology-only parsing requires positive integer keys as labels".to_string(),
+            )),
+        };
+
+        if self.know_num_leaves && key > self.num_leaves {
+            return Err(ParsingError::unresolved_label(
+                parser,
+                format!("Key {} out of bounds (1-based indexing, valid range: 1-{})", key, self.num_leaves),
+            ));
+        }
+
+        // Annotations are skipped in topology-only mode
+        let branch_length = self.parse_branch_length(parser, &mut Vec::new(), &mut Vec::new())?;
+
+        let label_index = self.check_duplicate_taxon(key - 1, parser)?;
         let index = tree.add_leaf(branch_length, label_index);
         if !self.know_num_leaves {
             self.num_leaves += 1;
@@ -292,26 +820,81 @@ impl NewickParser {
         Ok(index)
     }
 
+    /// Checks whether the taxon of a leaf was already seen in the current tree
+    /// and handles it according to the [DuplicateTaxonPolicy].
+    ///
+    /// # Returns
+    /// - [LabelIndex] of the leaf (of the renamed taxon if renamed)
+    /// - [ParsingError] if the taxon is a duplicate and duplicates are not allowed
+    fn check_duplicate_taxon<S: ByteSource>(&mut self, label_index: LabelIndex, parser: &ByteParser<S>) -> Result<LabelIndex, ParsingError>
+        where R: ResolveLeafLabel<S> {
+        if self.duplicate_taxa == DuplicateTaxonPolicy::AllowDuplicates {
+            return Ok(label_index);
+        }
+        if label_index >= self.seen_taxa.len() {
+            self.seen_taxa.resize(label_index + 1, false);
+        }
+        if !self.seen_taxa[label_index] {
+            self.seen_taxa[label_index] = true;
+            return Ok(label_index);
+        }
+
+        // Report and rename by full label, not by key (which is `label_index + 1` in topology-only mode)
+        let label = self.resolver.label(label_index)
+            .filter(|_| !self.topology_only)
+            .map_or_else(|| (label_index + 1).to_string(), str::to_string);
+        if self.duplicate_taxa == DuplicateTaxonPolicy::Error || self.topology_only {
+            return Err(ParsingError::duplicate_taxon(parser, label));
+        }
+
+        // Renamed taxon must not exceed known number of leaves (all trees are on the same number of taxa)
+        let seen_taxa = &self.seen_taxa;
+        let num_leaves = self.know_num_leaves.then_some(self.num_leaves);
+        let Some(renamed) = self.resolver
+            .get_or_insert_renamed(&label, |index| seen_taxa.get(index).copied().unwrap_or(false), num_leaves) else {
+            return Err(ParsingError::duplicate_taxon(parser, label));
+        };
+        if renamed >= self.seen_taxa.len() {
+            self.seen_taxa.resize(renamed + 1, false);
+        }
+        self.seen_taxa[renamed] = true;
+        Ok(renamed)
+    }
+
     /// Parses optional branch length `[:number]`:
-    /// - Skips comments/whitespace before and after `:`
+    /// - Skips comments/whitespace before and after `:`, collecting annotations
+    ///   before `:` into `node_annotations` and after into `branch_annotations` (if configured)
     /// - Supports scientific notation (e.g., `1.5e-10`)
+    /// - Handles tiny negative values according to the [NegativeBranchLengthPolicy]
+    /// - In topology-only mode, skips the number without parsing it
     ///
     /// # Returns
     /// - [BranchLength] if found branch length and was able to parse it
-    /// - `None` if found no branch length
-    /// - [ParsingError] if it couldn't parse branch length value
-    fn parse_branch_length<S: ByteSource>(&mut self, parser: &mut ByteParser<S>) -> Result<Option<BranchLength>, ParsingError> {
+    /// - `None` if found no branch length (or in topology-only mode)
+    /// - [ParsingError] if it couldn't parse branch length value, or it is not finite or negative (beyond policy)
+    fn parse_branch_length<S: ByteSource>(
+        &mut self,
+        parser: &mut ByteParser<S>,
+        node_annotations: &mut Vec<String>,
+        branch_annotations: &mut Vec<String>,
+    ) -> Result<Option<BranchLength>, ParsingError> {
         // Whitespace/Comments : Whitespace/Comments
-        parser.skip_comment_and_whitespace()?;
+        self.skip_collecting_annotations(parser, node_annotations)?;
         if !parser.consume_if(b':') {
             return Ok(None);
         }
-        parser.skip_comment_and_whitespace()?;
+        self.skip_collecting_annotations(parser, branch_annotations)?;
+
+        if self.topology_only {
+            while parser.peek().is_some_and(is_branch_length_byte) {
+                parser.next();
+            }
+            return Ok(None);
+        }
 
         let mut branch_length_str = String::new();
         while let Some(b) = parser.peek() {
-            // Valid characters for a float: digits, '.', '-', '+', 'e', 'E'
-            if b.is_ascii_digit() || b == b'.' || b == b'-' || b == b'+' || b == b'e' || b == b'E' {
+            if is_branch_length_byte(b) {
                 branch_length_str.push(b as char);
                 parser.next(); // consume it
             } else {
@@ -321,10 +904,132 @@ impl NewickParser {
 
         let value: f64 = branch_length_str.parse()
             .map_err(|_| ParsingError::invalid_newick_string(parser, format!("Invalid branch length: {}", branch_length_str)))?;
-        Ok(Some(BranchLength::new(value)))
+        let negative = || ParsingError::invalid_newick_string(parser, format!("Negative branch length: {}", branch_length_str));
+        match BranchLength::try_new(value) {
+            Ok(length) => Ok(Some(length)),
+            Err(BranchLengthError::NotFinite(_)) =>
+                Err(ParsingError::invalid_newick_string(parser, format!("Branch length is not finite: {}", branch_length_str))),
+            Err(BranchLengthError::Negative(_)) => match self.negative_branch_lengths {
+                _ if value < -NEGATIVE_BRANCH_LENGTH_TOLERANCE => Err(negative()),
+                NegativeBranchLengthPolicy::ClampToZero => Ok(Some(BranchLength::new(0.0))),
+                NegativeBranchLengthPolicy::Error => Err(negative()),
+                NegativeBranchLengthPolicy::Keep => Ok(Some(BranchLength::new_unchecked(value))),
+            },
+        }
     }
 }
 
+// ============================================================================
+// Annotations
+// ============================================================================
+impl<R> NewickParser<R> {
+    /// Skips comments and whitespace, collecting the content of annotation comments `[&...]`
+    /// (without `&`) if annotations are configured to be collected.
+    fn skip_collecting_annotations<S: ByteSource>(&self, parser: &mut ByteParser<S>, annotations: &mut Vec<String>) -> Result<(), ParsingError> {
+        if self.annotations == AnnotationMode::Skip || self.topology_only {
+            return parser.skip_comment_and_whitespace();
+        }
+
+        parser.skip_whitespace();
+        while let Some(comment) = parser.parse_comment()? {
+            if let Some(annotation) = comment.strip_prefix('&') {
+                annotations.push(annotation.to_string());
+            }
+            parser.skip_whitespace();
+        }
+
+        Ok(())
+    }
+
+    /// Stores collected annotations of vertex and its branch in the tree.
+    fn store_annotations(&self, tree: &mut Tree, index: TreeIndex, node_annotations: Vec<String>, branch_annotations: Vec<String>) {
+        if !node_annotations.is_empty() {
+            tree.set_annotation(index, Some(self.to_annotation(node_annotations)));
+        }
+        if !branch_annotations.is_empty() {
+            tree.set_branch_annotation(index, Some(self.to_annotation(branch_annotations)));
+        }
+    }
+
+    /// Joins collected annotations into one [Annotation], parsed into entries if configured.
+    pub(crate) fn to_annotation(&self, annotations: Vec<String>) -> Annotation {
+        let raw = annotations.join(",");
+        match self.annotations {
+            AnnotationMode::Parsed => Annotation::parse(&raw),
+            _ => Annotation::from_raw(raw),
+        }
+    }
+}
+
+/// Returns whether byte can be part of a branch length: digits, '.', '-', '+', 'e', 'E'
+#[inline(always)]
+fn is_branch_length_byte(b: u8) -> bool {
+    b.is_ascii_digit() || b == b'.' || b == b'-' || b == b'+' || b == b'e' || b == b'E'
+}
+
+
+// =#========================================================================#=
+// RESOLVE LEAF LABEL
+// =#========================================================================#=
+/// Parses labels of leaves and resolves them to indices for a [NewickParser].
+///
+/// Implemented by [LabelResolver]s of any [LeafLabel] type the input can be parsed into (see [ParseLabel]):
+/// owned labels for any input, and labels stored as slices of borrowed in-memory input.
+pub trait ResolveLeafLabel<S: ByteSource> {
+    /// Parses the label of a leaf at the current position and returns its [LabelIndex].
+    ///
+    /// # Errors
+    /// Returns a [ParsingError] if the label is invalid or cannot be resolved.
+    fn parse_leaf_label(&mut self, parser: &mut ByteParser<S>) -> Result<LabelIndex, ParsingError>;
+
+    /// Resolves an already parsed label of a leaf to its [LabelIndex], e.g. after removing a reticulation tag.
+    ///
+    /// # Errors
+    /// Returns a [ParsingError] if the label cannot be resolved.
+    fn resolve_leaf_label(&mut self, label: &str, parser: &ByteParser<S>) -> Result<LabelIndex, ParsingError>;
+
+    /// Returns the label with the given index, if known.
+    fn label(&self, index: LabelIndex) -> Option<&str>;
+
+    /// Gets the index of the first label `<label>_2`, `<label>_3`, ... not taken yet,
+    /// see [LeafLabelMap::get_or_insert_renamed].
+    ///
+    /// # Returns
+    /// The index of the renamed label, or `None` (without inserting it)
+    /// if it is not below `num_leaves`, if given.
+    fn get_or_insert_renamed(&mut self, label: &str, is_taken: impl Fn(LabelIndex) -> bool, num_leaves: Option<usize>)
+        -> Option<LabelIndex>;
+}
+
+impl<S: ByteSource, L: LeafLabel + ParseLabel<S>> ResolveLeafLabel<S> for LabelResolver<L> {
+    fn parse_leaf_label(&mut self, parser: &mut ByteParser<S>) -> Result<LabelIndex, ParsingError> {
+        let label = L::parse_label(parser, NEWICK_LABEL_DELIMITERS)?;
+        match self {
+            // Store new verbatim labels without copying them
+            LabelResolver::VerbatimLabels(leaf_label_map) => Ok(leaf_label_map.get_or_insert_label(label)),
+            _ => self.resolve_label(label.borrow(), parser),
+        }
+    }
+
+    fn resolve_leaf_label(&mut self, label: &str, parser: &ByteParser<S>) -> Result<LabelIndex, ParsingError> {
+        self.resolve_label(label, parser)
+    }
+
+    fn label(&self, index: LabelIndex) -> Option<&str> {
+        self.leaf_label_map().get_label(index)
+    }
+
+    fn get_or_insert_renamed(&mut self, label: &str, is_taken: impl Fn(LabelIndex) -> bool, num_leaves: Option<usize>)
+        -> Option<LabelIndex> {
+        let leaf_label_map = self.leaf_label_map_mut();
+        let renamed = leaf_label_map.renamed_label(label, is_taken);
+        let index = leaf_label_map.get_index(&renamed).unwrap_or(leaf_label_map.num_labels());
+        if num_leaves.is_some_and(|num_leaves| index >= num_leaves) {
+            return None;
+        }
+        Some(leaf_label_map.get_or_insert_label(L::from(renamed)))
+    }
+}
 
 // =#========================================================================#=
 // LABEL RESOLVER
@@ -341,7 +1046,7 @@ impl NewickParser {
 /// Since in practice often actually uses TRANSLATE block with indices,
 /// could add a faster array based resolver.
 #[derive(Debug)]
-pub enum LabelResolver {
+pub enum LabelResolver<L = String> {
     /// Resolves direct verbatim label -> index mapping for raw Newick strings.
     ///
     /// # Warning
@@ -349,7 +1054,7 @@ pub enum LabelResolver {
     /// based on the order in which the labels were defined in the TAXA block.
     ///
     /// Example: "White-fronted tern" → index 11
-    VerbatimLabels(LeafLabelMap),
+    VerbatimLabels(LeafLabelMap<L>),
 
     /// Resolves all allowed types of keys and labels in Newick strings of Nexus TREES command,
     /// using mapping from TRANSLATE block, if provided:
@@ -363,9 +1068,9 @@ pub enum LabelResolver {
     /// - "White-fronted tern" -> index 11
     NexusLabels {
         /// Pre-computed mapping from keys to leaf indices
-        index_map: HashMap<String, LabelIndex>,
+        index_map: HashMap<L, LabelIndex>,
         /// The complete mapping of labels to indices
-        leaf_label_map: LeafLabelMap,
+        leaf_label_map: LeafLabelMap<L>,
     },
 
     /// Resolves only integer keys in Newick strings of Nexus TREES command,
@@ -377,11 +1082,11 @@ pub enum LabelResolver {
         /// Array mapping translate indices to leaf label indices
         index_array: Vec<LabelIndex>,
         /// The shared leaf label map
-        leaf_label_map: LeafLabelMap,
+        leaf_label_map: LeafLabelMap<L>,
     },
 }
 
-impl LabelResolver {
+impl<L: LeafLabel> LabelResolver<L> {
     /// Creates a `VerbatimLabels` resolver for verbatim label parsing.
     ///
     /// Use this when parsing:
@@ -390,7 +1095,7 @@ impl LabelResolver {
     ///
     /// # Arguments
     /// * `leaf_map` - An existing or new `LeafLabelMap` to populate
-    pub(crate) fn new_verbatim_labels_resolver(leaf_map: LeafLabelMap) -> Self {
+    pub(crate) fn new_verbatim_labels_resolver(leaf_map: LeafLabelMap<L>) -> Self {
         LabelResolver::VerbatimLabels(leaf_map)
     }
 
@@ -402,14 +1107,15 @@ impl LabelResolver {
     ///
     /// # Panics
     /// Panics if a label provided by `translation` does not appear in the provided [LeafLabelMap].
-    pub(crate) fn new_nexus_labels_resolver(translation: HashMap<String, String>, leaf_label_map: LeafLabelMap) -> Self {
+    pub(crate) fn new_nexus_labels_resolver(translation: HashMap<L, L>, leaf_label_map: LeafLabelMap<L>) -> Self {
         // Instead of going from key -> label and then from label -> index,
         // we create a direct mapping
         let mut index_map = HashMap::with_capacity(translation.len());
-        for (key, actual_label) in &translation {
+        for (key, actual_label) in translation {
+            let actual_label: &str = actual_label.borrow();
             let label_index = leaf_label_map.get_index(actual_label)
-                .expect(format!("Label {} provided by translation should have been present in provided LeafLabelMap.", actual_label).as_str());
-            index_map.insert(key.clone(), label_index);
+                .unwrap_or_else(|| panic!("Label {} provided by translation should have been present in provided LeafLabelMap.", actual_label));
+            index_map.insert(key, label_index);
         }
 
         LabelResolver::NexusLabels { index_map, leaf_label_map }
@@ -426,9 +1132,9 @@ impl LabelResolver {
     /// - A key is not a valid positive integer
     /// - A key is out of bounds (0 or > num_labels)
     /// - A label provided by `translation` does not appear in the provided [LeafLabelMap];
-    ///     you can check consistent with `leaf_label_map.check_consistency(translation)` beforehand
+    ///   you can check consistent with `leaf_label_map.check_consistency(translation)` beforehand
     /// - Keys are not consecutive integers starting from 1
-    pub(crate) fn new_nexus_integer_labels_resolver(translation: HashMap<String, String>, leaf_label_map: LeafLabelMap) -> Self {
+    pub(crate) fn new_nexus_integer_labels_resolver(translation: HashMap<L, L>, leaf_label_map: LeafLabelMap<L>) -> Self {
         let num_labels = leaf_label_map.num_labels();
 
         // Validate all keys are valid integers and build index array;
@@ -436,9 +1142,10 @@ impl LabelResolver {
         let mut index_array = vec![0; num_labels];
 
         for (key, actual_label) in &translation {
+            let (key, actual_label): (&str, &str) = (key.borrow(), actual_label.borrow());
             // Parse key as integer
             let nexus_index = key.parse::<usize>()
-                .expect(&format!("TRANSLATE key '{}' is not a valid integer", key));
+                .unwrap_or_else(|_| panic!("TRANSLATE key '{}' is not a valid integer", key));
 
             // Validate bounds (1-based NEXUS indexing)
             if nexus_index == 0 || nexus_index > num_labels {
@@ -448,7 +1155,7 @@ impl LabelResolver {
 
             // Look up the label in the leaf_label_map
             let label_index = leaf_label_map.get_index(actual_label)
-                .expect(&format!("Label '{}' provided by translation not found in LeafLabelMap", actual_label));
+                .unwrap_or_else(|| panic!("Label '{}' provided by translation not found in LeafLabelMap", actual_label));
 
             // Store in array (converting from 1-based to 0-based indexing)
             index_array[nexus_index - 1] = label_index;
@@ -498,6 +1205,9 @@ impl LabelResolver {
                 }
 
 
+                if split_reticulation_tag(parsed_label).is_some() {
+                    return Err(ParsingError::reticulate_network(parser, parsed_label.to_string()));
+                }
                 Err(ParsingError::unresolved_label(parser, format!("NexusResolver could not resolve {parsed_label}")))
             }
 
@@ -516,6 +1226,9 @@ impl LabelResolver {
                     return Ok(index_array[nexus_index - 1]);
                 }
 
+                if split_reticulation_tag(parsed_label).is_some() {
+                    return Err(ParsingError::reticulate_network(parser, parsed_label.to_string()));
+                }
                 Err(ParsingError::unresolved_label(
                     parser,
                     format!("NexusIntegerLabels resolver requires integer labels, got '{}'", parsed_label),
@@ -532,7 +1245,7 @@ impl LabelResolver {
     /// # Returns
     /// The [LeafLabelMap] initially supplied or a new one containing all labels encountered during parsing.
     /// Returns an empty map if the resolver was never initialized (`None` variant).
-    pub(crate) fn into_leaf_label_map(self) -> LeafLabelMap {
+    pub(crate) fn into_leaf_label_map(self) -> LeafLabelMap<L> {
         match self {
             LabelResolver::VerbatimLabels(leaf_label_map) => leaf_label_map,
             LabelResolver::NexusLabels { leaf_label_map, .. } => leaf_label_map,
@@ -540,16 +1253,25 @@ impl LabelResolver {
         }
     }
 
-    pub(crate) fn leaf_label_map(&self) -> &LeafLabelMap {
+    pub(crate) fn leaf_label_map(&self) -> &LeafLabelMap<L> {
         match self {
-            LabelResolver::VerbatimLabels(leaf_label_map) => &leaf_label_map,
-            LabelResolver::NexusLabels { leaf_label_map, .. } => &leaf_label_map,
-            LabelResolver::NexusIntegerLabels { leaf_label_map, .. } => &leaf_label_map,
+            LabelResolver::VerbatimLabels(leaf_label_map) => leaf_label_map,
+            LabelResolver::NexusLabels { leaf_label_map, .. } => leaf_label_map,
+            LabelResolver::NexusIntegerLabels { leaf_label_map, .. } => leaf_label_map,
+        }
+    }
+
+    /// Mutable access to the [LeafLabelMap], e.g. to add renamed duplicate taxa.
+    pub(crate) fn leaf_label_map_mut(&mut self) -> &mut LeafLabelMap<L> {
+        match self {
+            LabelResolver::VerbatimLabels(leaf_label_map) => leaf_label_map,
+            LabelResolver::NexusLabels { leaf_label_map, .. } => leaf_label_map,
+            LabelResolver::NexusIntegerLabels { leaf_label_map, .. } => leaf_label_map,
         }
     }
 }
 
-impl fmt::Display for LabelResolver {
+impl<L: LeafLabel> fmt::Display for LabelResolver<L> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
             LabelResolver::VerbatimLabels(_) => {
@@ -558,7 +1280,7 @@ impl fmt::Display for LabelResolver {
             LabelResolver::NexusLabels { index_map, .. } => {
                 writeln!(f, "LabelResolver::NexusLabels with internal mapping:")?;
                 for (key, value) in index_map {
-                    writeln!(f, "  {} -> {}", key, value)?;
+                    writeln!(f, "  {} -> {}", key.borrow(), value)?;
                 }
                 Ok(())
             }
@@ -0,0 +1,353 @@
+//! NeXML tree file parser.
+//!
+//! - `parse_file`, `parse_str`, `parse_bytes`: Parse all trees of a NeXML document
+//!
+//! NeXML (<http://www.nexml.org>) declares taxa as `<otu>` elements of `<otus>` blocks,
+//! and trees as flat lists of `<node>` and `<edge>` elements of `<trees>` blocks.
+//! All otus are mapped onto a shared [LeafLabelMap] (by their label, or id if unlabelled)
+//! in order of appearance, like the TAXA block of a NEXUS file. Leaf nodes refer to their otu;
+//! internal nodes keep their label as node label, edges their length as branch length.
+//! Character matrices, networks and metadata are skipped.
+
+use crate::io::parser::byte_parser::ByteParser;
+use crate::io::parser::byte_source::{ByteSource, InMemoryByteSource};
+use crate::io::parser::parsing_error::ParsingError;
+use crate::io::parser::xml::{XmlEvent, XmlReader};
+use crate::model::leaf_label_map::LeafLabelMap;
+use crate::model::tree::{Tree, TreeIndex};
+use crate::model::vertex::BranchLength;
+use std::collections::HashMap;
+#[cfg(feature = "fs")]
+use std::fs::File;
+#[cfg(feature = "fs")]
+use std::io::Read;
+
+/// Root element of a NeXML document
+pub(crate) const NEXML: &str = "nexml";
+/// Block of taxa
+const OTUS: &str = "otus";
+/// Single taxon
+const OTU: &str = "otu";
+/// Block of trees (and networks)
+const TREES: &str = "trees";
+/// Single tree
+const TREE: &str = "tree";
+/// Vertex of a tree
+const NODE: &str = "node";
+/// Branch of a tree from `source` to `target` vertex
+const EDGE: &str = "edge";
+/// Attribute with the (unique) id of an element
+const ID: &str = "id";
+/// Attribute with the human-readable label of an element
+const LABEL: &str = "label";
+/// Attribute of leaf nodes with the id of their otu
+const OTU_REF: &str = "otu";
+/// Attribute of a node marking it as root
+const ROOT: &str = "root";
+/// Attribute of edges with their parent vertex
+const SOURCE: &str = "source";
+/// Attribute of edges with their child vertex
+const TARGET: &str = "target";
+/// Attribute of edges with their length
+const LENGTH: &str = "length";
+
+/// Default guess for number of leaves, when unknown
+const DEFAULT_NUM_LEAVES_GUESS: usize = 10;
+
+// =#========================================================================#=
+// NEXML FILE PARSING
+// =#========================================================================#=
+/// Parses all trees of a NeXML file.
+///
+/// Trees share a single [LeafLabelMap] built from all otus of the document.
+/// Tree names are the labels (or ids) of the trees; trees with a node marked `root="true"`
+/// are rooted there, others are unrooted and hang from the node without incoming edge.
+///
+/// # Returns
+/// A tuple of (trees, label_map) with all trees in order of appearance
+///
+/// # Errors
+/// Returns a [ParsingError] if the file cannot be read, is not well-formed XML,
+/// or a tree is invalid (e.g. an edge refers to an unknown node, or the edges do not form a tree)
+#[cfg(feature = "fs")]
+pub fn parse_file(mut file: File) -> Result<(Vec<Tree>, LeafLabelMap), ParsingError> {
+    let mut bytes = Vec::new();
+    file.read_to_end(&mut bytes).map_err(|e| ParsingError::io_error(&e, 0))?;
+    parse_all(&mut ByteParser::new(InMemoryByteSource::from_vec(bytes)))
+}
+
+/// Parses all trees of a NeXML string, see [parse_file].
+///
+/// # Errors
+/// Returns a [ParsingError] if the document or a tree is invalid
+///
+/// # Example
+/// ```
+/// use nexus_parser::io::parser::nexml;
+///
+/// let xml = r#"<nex:nexml xmlns:nex="http://www.nexml.org/2009" version="0.9">
+///   <otus id="taxa"><otu id="t1" label="A"/><otu id="t2" label="B"/><otu id="t3" label="C"/></otus>
+///   <trees id="trees" otus="taxa">
+///     <tree id="tree1" label="example">
+///       <node id="n1" root="true"/><node id="n2"/>
+///       <node id="n3" otu="t1"/><node id="n4" otu="t2"/><node id="n5" otu="t3"/>
+///       <edge id="e1" source="n1" target="n2" length="1"/>
+///       <edge id="e2" source="n2" target="n3" length="1"/>
+///       <edge id="e3" source="n2" target="n4" length="1"/>
+///       <edge id="e4" source="n1" target="n5" length="2"/>
+///     </tree>
+///   </trees>
+/// </nex:nexml>"#;
+///
+/// let (trees, labels) = nexml::parse_str(xml).unwrap();
+/// assert_eq!(trees[0].name().unwrap(), "example");
+/// assert_eq!(labels.num_labels(), 3);
+/// assert!(trees[0].is_ultrametric());
+/// ```
+pub fn parse_str(xml: &str) -> Result<(Vec<Tree>, LeafLabelMap), ParsingError> {
+    parse_bytes(xml.as_bytes())
+}
+
+/// Parses all trees of the given NeXML bytes, see [parse_file].
+///
+/// # Errors
+/// Returns a [ParsingError] if the document or a tree is invalid
+pub fn parse_bytes<B: AsRef<[u8]>>(bytes: B) -> Result<(Vec<Tree>, LeafLabelMap), ParsingError> {
+    parse_all(&mut ByteParser::new(InMemoryByteSource::new(bytes)))
+}
+
+/// Parses the `<nexml>` root element with its otus and trees blocks.
+fn parse_all<S: ByteSource>(parser: &mut ByteParser<S>) -> Result<(Vec<Tree>, LeafLabelMap), ParsingError> {
+    let mut reader = XmlReader::new(parser);
+    let mut leaf_label_map = LeafLabelMap::new(DEFAULT_NUM_LEAVES_GUESS);
+    // Label index of each otu by its id
+    let mut otus = HashMap::new();
+    let mut trees = Vec::new();
+
+    match reader.next_event()? {
+        Some(XmlEvent::Start { name, .. }) if name == NEXML => {}
+        _ => return Err(ParsingError::invalid_nexml(reader.parser(), "expected <nexml> root element".to_string())),
+    }
+
+    while let Some(event) = reader.next_event()? {
+        match event {
+            XmlEvent::Start { name, .. } if name == OTUS => parse_otus(&mut reader, &mut leaf_label_map, &mut otus)?,
+            XmlEvent::Start { name, .. } if name == TREES => {
+                while let Some(event) = reader.next_event()? {
+                    match event {
+                        XmlEvent::Start { name, attributes } if name == TREE => {
+                            let tree_name = attribute(&attributes, LABEL).or(attribute(&attributes, ID));
+                            let tree = parse_tree(&mut reader, &leaf_label_map, &otus)?;
+                            trees.push(match tree_name {
+                                Some(tree_name) => tree.with_name(tree_name.to_string()),
+                                None => tree,
+                            });
+                        }
+                        XmlEvent::Start { .. } => reader.skip_element()?,
+                        XmlEvent::End { .. } => break,
+                        XmlEvent::Text(_) => {}
+                    }
+                }
+            }
+            XmlEvent::Start { .. } => reader.skip_element()?,
+            _ => {}
+        }
+    }
+
+    Ok((trees, leaf_label_map))
+}
+
+/// Parses the content of an `<otus>` element (after its start tag), adding its otus to the label map.
+fn parse_otus<S: ByteSource>(reader: &mut XmlReader<S>, leaf_label_map: &mut LeafLabelMap,
+                             otus: &mut HashMap<String, usize>) -> Result<(), ParsingError> {
+    while let Some(event) = reader.next_event()? {
+        match event {
+            XmlEvent::Start { name, attributes } => {
+                if name == OTU {
+                    let id = required_attribute(reader, &attributes, ID, OTU)?;
+                    let label = attribute(&attributes, LABEL).unwrap_or(id);
+                    if leaf_label_map.contains_label(label) {
+                        return Err(ParsingError::invalid_nexml(reader.parser(), format!("otu label '{}' is not unique", label)));
+                    }
+                    otus.insert(id.to_string(), leaf_label_map.get_or_insert(label));
+                }
+                reader.skip_element()?;
+            }
+            XmlEvent::End { .. } => break,
+            XmlEvent::Text(_) => {}
+        }
+    }
+    Ok(())
+}
+
+// =#========================================================================#=
+// TREE PARSING
+// =#========================================================================#=
+/// Node of a NeXML tree as declared, before edges are resolved.
+struct Node {
+    /// Label index of the otu of a leaf
+    label_index: Option<usize>,
+    label: Option<String>,
+    is_root: bool,
+    children: Vec<usize>,
+    parent: Option<usize>,
+    branch_length: Option<BranchLength>,
+}
+
+/// Parses the content of a `<tree>` element (after its start tag) into a tree.
+///
+/// Nodes and edges may appear in any order; the tree is assembled once all of them are read,
+/// with an explicit stack instead of recursion, so arbitrarily deep trees can be parsed.
+fn parse_tree<S: ByteSource>(reader: &mut XmlReader<S>, leaf_label_map: &LeafLabelMap,
+                             otus: &HashMap<String, usize>) -> Result<Tree, ParsingError> {
+    let mut nodes: Vec<Node> = Vec::new();
+    let mut node_indices: HashMap<String, usize> = HashMap::new();
+    let mut edges: Vec<(String, String, Option<BranchLength>)> = Vec::new();
+
+    while let Some(event) = reader.next_event()? {
+        match event {
+            XmlEvent::Start { name, attributes } => {
+                if name == NODE {
+                    let id = required_attribute(reader, &attributes, ID, NODE)?;
+                    let label_index = match attribute(&attributes, OTU_REF) {
+                        Some(otu) => Some(*otus.get(otu).ok_or_else(|| {
+                            ParsingError::unresolved_label(reader.parser(), format!("otu '{}' of node '{}' is not declared", otu, id))
+                        })?),
+                        None => None,
+                    };
+                    if node_indices.insert(id.to_string(), nodes.len()).is_some() {
+                        return Err(ParsingError::invalid_nexml(reader.parser(), format!("node id '{}' is not unique", id)));
+                    }
+                    nodes.push(Node {
+                        label_index,
+                        label: attribute(&attributes, LABEL).map(str::to_string),
+                        is_root: attribute(&attributes, ROOT).is_some_and(|root| root.trim() == "true"),
+                        children: Vec::new(),
+                        parent: None,
+                        branch_length: None,
+                    });
+                } else if name == EDGE {
+                    let source = required_attribute(reader, &attributes, SOURCE, EDGE)?;
+                    let target = required_attribute(reader, &attributes, TARGET, EDGE)?;
+                    let length = match attribute(&attributes, LENGTH) {
+                        Some(length) => Some(parse_branch_length(reader, length)?),
+                        None => None,
+                    };
+                    edges.push((source.to_string(), target.to_string(), length));
+                }
+                reader.skip_element()?;
+            }
+            XmlEvent::End { .. } => break,
+            XmlEvent::Text(_) => {}
+        }
+    }
+
+    // Resolve edges
+    for (source, target, length) in edges {
+        let resolve = |id: &str| node_indices.get(id).copied().ok_or_else(|| {
+            ParsingError::invalid_nexml(reader.parser(), format!("edge refers to unknown node '{}'", id))
+        });
+        let (source, target) = (resolve(&source)?, resolve(&target)?);
+        if nodes[target].parent.is_some() {
+            return Err(ParsingError::invalid_nexml(reader.parser(), "node has more than one incoming edge".to_string()));
+        }
+        nodes[target].parent = Some(source);
+        nodes[target].branch_length = length;
+        nodes[source].children.push(target);
+    }
+
+    let root = find_root(reader, &nodes)?;
+    build_tree(reader, nodes, root, leaf_label_map)
+}
+
+/// Returns the node marked as root, or else the only node without incoming edge.
+fn find_root<S: ByteSource>(reader: &XmlReader<S>, nodes: &[Node]) -> Result<usize, ParsingError> {
+    let mut roots = nodes.iter().enumerate().filter(|(_, node)| node.parent.is_none());
+    let root = match (roots.next(), roots.next()) {
+        (Some((root, _)), None) => root,
+        (None, _) => return Err(ParsingError::invalid_nexml(reader.parser(), "tree has no root".to_string())),
+        (Some(_), Some(_)) => return Err(ParsingError::invalid_nexml(reader.parser(), "tree is not connected".to_string())),
+    };
+
+    match nodes.iter().position(|node| node.is_root) {
+        Some(marked) if marked != root => Err(ParsingError::invalid_nexml(reader.parser(), "root node has an incoming edge".to_string())),
+        _ => Ok(root),
+    }
+}
+
+/// Adds the nodes below `root` to a new tree, children before parents.
+fn build_tree<S: ByteSource>(reader: &XmlReader<S>, nodes: Vec<Node>, root: usize,
+                             leaf_label_map: &LeafLabelMap) -> Result<Tree, ParsingError> {
+    if nodes[root].children.is_empty() {
+        return Err(ParsingError::invalid_nexml(reader.parser(), "root node has no children".to_string()));
+    }
+
+    let num_taxa = leaf_label_map.num_labels();
+    let mut tree = Tree::new(num_taxa.max(1));
+    let mut seen_taxa = vec![false; num_taxa];
+    let mut tree_indices: Vec<Option<TreeIndex>> = vec![None; nodes.len()];
+    let mut num_visited = 0;
+
+    // Post-order: a node is added once all its children have been added
+    let mut stack = vec![(root, false)];
+    while let Some((node_index, children_added)) = stack.pop() {
+        let node = &nodes[node_index];
+        if !children_added && !node.children.is_empty() {
+            stack.push((node_index, true));
+            stack.extend(node.children.iter().rev().map(|&child| (child, false)));
+            continue;
+        }
+        num_visited += 1;
+
+        let branch_length = node.branch_length;
+        let index = if node.children.is_empty() {
+            let label_index = node.label_index.ok_or_else(|| {
+                ParsingError::invalid_nexml(reader.parser(), "leaf node has no otu".to_string())
+            })?;
+            if seen_taxa[label_index] {
+                let label = leaf_label_map.get_label(label_index).unwrap_or_default().to_string();
+                return Err(ParsingError::duplicate_taxon(reader.parser(), label));
+            }
+            seen_taxa[label_index] = true;
+            tree.add_leaf(branch_length, label_index)
+        } else {
+            let children = node.children.iter().map(|&child| tree_indices[child].expect("Child was added")).collect();
+            let index = if node_index == root { tree.add_root(children) } else { tree.add_internal_vertex(children, branch_length) };
+            tree[index].set_node_label(node.label.clone());
+            index
+        };
+        tree_indices[node_index] = Some(index);
+    }
+
+    if num_visited != nodes.len() {
+        return Err(ParsingError::invalid_nexml(reader.parser(), "edges of tree contain a cycle".to_string()));
+    }
+
+    Ok(tree.with_rooted(nodes[root].is_root))
+}
+
+// ============================================================================
+// Little Helpers
+// ============================================================================
+/// Returns the value of the attribute with the given name, if present.
+fn attribute<'a>(attributes: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    attributes.iter()
+        .find(|(key, _)| key == name)
+        .map(|(_, value)| value.as_str())
+}
+
+/// Returns the value of an attribute an element must have.
+fn required_attribute<'a, S: ByteSource>(reader: &XmlReader<S>, attributes: &'a [(String, String)],
+                                         name: &str, element: &str) -> Result<&'a str, ParsingError> {
+    attribute(attributes, name).ok_or_else(|| {
+        ParsingError::invalid_nexml(reader.parser(), format!("<{}> without attribute '{}'", element, name))
+    })
+}
+
+/// Parses a branch length, which must be a non-negative, finite number.
+fn parse_branch_length<S: ByteSource>(reader: &XmlReader<S>, text: &str) -> Result<BranchLength, ParsingError> {
+    let length = text.trim().parse::<f64>()
+        .map_err(|_| ParsingError::invalid_nexml(reader.parser(), format!("invalid branch length '{}'", text)))?;
+    BranchLength::try_new(length)
+        .map_err(|error| ParsingError::invalid_nexml(reader.parser(), format!("invalid branch length '{}': {}", text, error)))
+}
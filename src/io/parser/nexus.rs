@@ -0,0 +1,2056 @@
+//! NEXUS parser module for phylogenetic tree files.
+//!
+//! - `NexusParserBuilder`/`NexusParser`: Parses an in-memory NEXUS file eagerly or lazily,
+//!   with support for burnin and skipping the first tree
+//! - `TreeReader`: Streams trees one by one from any [BufRead] source,
+//!   for posterior samples too large to fit into memory
+//! - `NexusEventParser`/`TreeEvent`: Push parser fed with chunks of input (e.g. from a socket),
+//!   reporting trees as events to a callback
+//! - `Burnin`: Number or fraction of trees to discard at the start of a sample
+//! - `LoadOptions`: Which trees of a sample to load (skipping, burnin and thinning)
+//! - `ParseOptions`: Options shared by both parsers, e.g. strict validation of taxa
+//! - `SkippedTree`: Malformed tree skipped by a lenient [NexusParser], with its error
+//! - `RawBlock`/`NexusDocument`: Other blocks retained verbatim (e.g. FIGTREE), with the trees, taxa
+//!   and the [Alignment] of a DATA or CHARACTERS block
+//! - `parse_str_borrowed`: Parses an in-memory NEXUS string with labels borrowed from it where possible
+
+#[cfg(feature = "fs")]
+use crate::io::compression::decompressed_reader;
+use crate::io::defs::{NexusBlock, BLOCK_BEGIN, DIMENSIONS, END, ENDBLOCK, NEXUS_HEADER, NEXUS_LABEL_DELIMITERS, NTAX, TAXLABELS, TRANSLATE, TREE};
+use crate::io::parser::byte_parser::{ByteParser, CommandScanner, ConsumeMode, ParseLabel};
+use crate::io::parser::byte_source::{advance_line_and_column, ByteSource, InMemoryByteSource};
+use crate::io::parser::characters::{CharacterMatrix, CharactersBlock};
+use crate::io::parser::newick::{AnnotationMode, LabelResolver, NegativeBranchLengthPolicy, NewickParser, PartialTree, ResolveLeafLabel, NEWICK_STEP_DELIMITERS};
+use crate::io::parser::parsing_error::ParsingError;
+use crate::io::parser::translation::TranslationTable;
+use crate::model::alignment::Alignment;
+use crate::model::leaf_label_map::{DuplicateTaxonPolicy, LeafLabel, LeafLabelMap};
+use crate::model::tree::{Tree, TreeIndex};
+use std::borrow::Cow;
+use std::collections::HashMap;
+#[cfg(feature = "fs")]
+use std::fs::File;
+use std::io;
+use std::io::BufRead;
+#[cfg(feature = "fs")]
+use std::io::{BufReader, Read};
+use std::ops::Range;
+use std::sync::Arc;
+
+/// Delimiters of tree names in TREE commands: `=`, semicolon, comment start, whitespace
+const TREE_NAME_DELIMITERS: &[u8] = b"=;[ \t\n\r";
+
+/// Minimum number of bytes read between two calls of a [ProgressHook]
+const PROGRESS_INTERVAL: usize = 1 << 20;
+
+/// Delimiters of block names in BEGIN commands: semicolon, comment start, whitespace
+const BLOCK_NAME_DELIMITERS: &[u8] = b";[ \t\n\r";
+
+// =#========================================================================#=
+// BURNIN
+// =#========================================================================#=
+/// Trees to discard at the start of a posterior sample (after skipping the first tree, if configured).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Burnin {
+    /// Discard the given number of trees
+    Count(usize),
+    /// Discard the given fraction (in `[0, 1]`) of trees, rounded down
+    Percentage(f64),
+}
+
+impl Burnin {
+    /// Returns the number of trees to discard out of `num_trees`.
+    pub(crate) fn num_discarded(&self, num_trees: usize) -> usize {
+        match *self {
+            Burnin::Count(count) => count.min(num_trees),
+            Burnin::Percentage(fraction) => ((num_trees as f64 * fraction).floor() as usize).min(num_trees),
+        }
+    }
+}
+
+// =#========================================================================#=
+// LOAD OPTIONS
+// =#========================================================================#=
+/// Subset of the trees of an MCMC sample to load; other trees are skipped without being parsed.
+///
+/// Trees are selected in this order:
+/// 1. The first `skip_first` trees are skipped (e.g. the starting tree)
+/// 2. The fraction `skip_fraction` of the remaining trees is discarded as burnin (rounded down)
+/// 3. Of the rest, only every `thin_every`-th tree is kept (the first, the `thin_every + 1`-th, ...)
+///
+/// # Example
+/// ```
+/// use nexus_parser::io::parser::nexus::{LoadOptions, NexusParserBuilder};
+///
+/// let mut nexus = String::from("#NEXUS\nBEGIN TREES;\n");
+/// for i in 0..=10 {
+///     nexus.push_str(&format!("TREE STATE_{} = ((A,B),C);\n", i * 100));
+/// }
+/// nexus.push_str("END;\n");
+///
+/// // Skip starting tree, discard 20% burnin, then keep every 4th tree
+/// let options = LoadOptions { skip_first: 1, skip_fraction: 0.2, thin_every: 4 };
+/// let (trees, _) = NexusParserBuilder::for_bytes(nexus.into_bytes())
+///     .with_load_options(options)
+///     .build().unwrap()
+///     .into_results().unwrap();
+///
+/// let names: Vec<&str> = trees.iter().map(|tree| tree.name().unwrap().as_str()).collect();
+/// assert_eq!(names, vec!["STATE_300", "STATE_700"]);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LoadOptions {
+    /// Number of trees to skip first
+    pub skip_first: usize,
+    /// Fraction (in `[0, 1]`) of the remaining trees to discard as burnin
+    pub skip_fraction: f64,
+    /// Interval of trees to keep after burnin, `1` keeps all
+    pub thin_every: usize,
+}
+
+impl Default for LoadOptions {
+    /// Loads all trees.
+    fn default() -> Self {
+        Self {
+            skip_first: 0,
+            skip_fraction: 0.0,
+            thin_every: 1,
+        }
+    }
+}
+
+impl LoadOptions {
+    /// Selects the trees of an already parsed sample (e.g. of a Newick file) as a parser would load them.
+    ///
+    /// # Panics
+    /// Panics if `skip_fraction` is not within `[0, 1]` or `thin_every` is `0`.
+    ///
+    /// # Example
+    /// ```
+    /// use nexus_parser::io::parser::nexus::LoadOptions;
+    ///
+    /// let options = LoadOptions { skip_first: 1, skip_fraction: 0.2, thin_every: 4 };
+    /// let states: Vec<usize> = (0..=10).map(|i| i * 100).collect();
+    /// assert_eq!(options.select(states), vec![300, 700]);
+    /// ```
+    pub fn select<T>(&self, trees: Vec<T>) -> Vec<T> {
+        assert!((0.0..=1.0).contains(&self.skip_fraction), "Burnin percentage {} not within [0, 1]", self.skip_fraction);
+        assert!(self.thin_every > 0, "Thinning interval must be positive");
+        let start = first_loaded(self.skip_first, Burnin::Percentage(self.skip_fraction), trees.len());
+        trees.into_iter().skip(start).step_by(self.thin_every).collect()
+    }
+}
+
+/// Returns the index of the first tree to load out of `num_trees`, after skipping the first `num_skip_first` trees
+/// and discarding the burnin of the remaining ones.
+fn first_loaded(num_skip_first: usize, burnin: Burnin, num_trees: usize) -> usize {
+    let num_skipped = num_skip_first.min(num_trees);
+    num_skipped + burnin.num_discarded(num_trees - num_skipped)
+}
+
+// =#========================================================================#=
+// PARSE OPTIONS
+// =#========================================================================#=
+/// Options for parsing NEXUS files, shared by [NexusParserBuilder] and [TreeReader].
+///
+/// # Example
+/// ```
+/// use nexus_parser::io::parser::nexus::{NexusParserBuilder, ParseOptions};
+///
+/// let nexus = b"#NEXUS\nBEGIN TAXA;\nDIMENSIONS NTAX=3;\nTAXLABELS A B C;\nEND;\n\
+///               BEGIN TREES;\nTREE t = ((A,B),D);\nEND;\n".to_vec();
+/// let options = ParseOptions { strict: true, ..ParseOptions::default() };
+///
+/// // Leaf D is not declared in the TAXA block
+/// assert!(NexusParserBuilder::for_bytes(nexus).with_options(options).build().is_err());
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ParseOptions {
+    /// Checks that trees contain exactly the taxa declared in the TAXA block (each once),
+    /// and that the TRANSLATE command matches them, failing with
+    /// [UndeclaredTaxon](crate::io::parser::parsing_error::ParsingErrorType::UndeclaredTaxon) or
+    /// [TaxonCountMismatch](crate::io::parser::parsing_error::ParsingErrorType::TaxonCountMismatch) otherwise;
+    /// files without TAXA block thus fail on their first label
+    pub strict: bool,
+    /// Converts underscores in unquoted labels to spaces (NEXUS convention)
+    pub underscores_to_spaces: bool,
+    /// Handling of taxa appearing more than once in the same tree (default: error)
+    pub duplicate_taxa: DuplicateTaxonPolicy,
+    /// Handling of tiny negative branch lengths from numerical noise (default: clamp to zero)
+    pub negative_branch_lengths: NegativeBranchLengthPolicy,
+}
+
+// =#========================================================================#=
+// PROGRESS HOOK
+// =#========================================================================#=
+/// Callback reporting the progress of a parse with the number of bytes read and trees parsed,
+/// see [NexusParserBuilder::with_progress]
+type ProgressHook = Arc<dyn Fn(usize, usize) + Send + Sync>;
+
+/// Counts parsed trees and invokes a [ProgressHook] (if any) at most every [PROGRESS_INTERVAL] bytes.
+#[derive(Default)]
+struct ProgressReporter {
+    hook: Option<ProgressHook>,
+    num_trees: usize,
+    /// Bytes read at the last invocation
+    last_reported: usize,
+    finished: bool,
+}
+
+impl ProgressReporter {
+    fn new(hook: Option<ProgressHook>) -> Self {
+        Self { hook, ..Self::default() }
+    }
+
+    /// Records a parsed tree ending after `bytes_read` bytes, invoking the hook if due.
+    fn tree_parsed(&mut self, bytes_read: usize) {
+        self.num_trees += 1;
+        if let Some(hook) = &self.hook
+            && bytes_read >= self.last_reported + PROGRESS_INTERVAL {
+            self.last_reported = bytes_read;
+            hook(bytes_read, self.num_trees);
+        }
+    }
+
+    /// Invokes the hook (once) after the last tree.
+    fn finish(&mut self, bytes_read: usize) {
+        if let Some(hook) = &self.hook
+            && !self.finished {
+            self.finished = true;
+            self.last_reported = bytes_read;
+            hook(bytes_read, self.num_trees);
+        }
+    }
+}
+
+// =#========================================================================#=
+// SKIPPED TREE
+// =#========================================================================#=
+/// Malformed tree skipped by a lenient [NexusParser] (see [NexusParserBuilder::with_lenient]).
+#[derive(Debug)]
+pub struct SkippedTree {
+    /// Index of the tree among all trees of the file (counting from `0`)
+    pub index: usize,
+    /// Name of the tree, if its TREE command got that far
+    pub name: Option<String>,
+    /// Error that occurred parsing the tree
+    pub error: ParsingError,
+}
+
+/// Trees, skipped malformed trees and shared label mapping of a lenient parse
+pub type LenientResults = (Vec<Tree>, Vec<SkippedTree>, LeafLabelMap);
+
+// =#========================================================================#=
+// RAW BLOCK
+// =#========================================================================#=
+/// Block of a NEXUS file other than TAXA and TREES (e.g. DATA, ASSUMPTIONS or FIGTREE), retained verbatim
+/// by a [NexusParser] built [with raw blocks](NexusParserBuilder::with_raw_blocks).
+#[derive(Debug, Clone, PartialEq)]
+pub struct RawBlock {
+    /// Name of the block as written in its BEGIN command, e.g. `figtree`
+    pub name: String,
+    /// Byte range of the block in the (decompressed) file, from `BEGIN` to the `;` of its `END` command
+    pub range: Range<usize>,
+    /// Content of the block, from `BEGIN` to the `;` of its `END` command
+    pub content: Vec<u8>,
+}
+
+impl RawBlock {
+    /// Returns whether the block has the given name (case-insensitive).
+    pub fn is_named(&self, name: &str) -> bool {
+        self.name.eq_ignore_ascii_case(name)
+    }
+
+    /// Returns the content of the block as text, replacing invalid UTF-8 sequences.
+    pub fn text(&self) -> Cow<'_, str> {
+        String::from_utf8_lossy(&self.content)
+    }
+}
+
+// =#========================================================================#=
+// NEXUS DOCUMENT
+// =#========================================================================#=
+/// Trees, taxa and other blocks of a NEXUS file, see [NexusParser::into_document].
+///
+/// # Example
+/// ```
+/// use nexus_parser::io::parser::nexus::NexusParserBuilder;
+///
+/// let nexus = b"#NEXUS\nBEGIN TREES;\nTREE t = ((A,B),C);\nEND;\n\
+///               BEGIN FIGTREE;\nset appearance.branchLineWidth=2.0;\nEND;\n".to_vec();
+/// let document = NexusParserBuilder::for_bytes(nexus).with_raw_blocks().build().unwrap()
+///     .into_document().unwrap();
+///
+/// assert_eq!(document.trees.len(), 1);
+/// let figtree = document.block("FigTree").unwrap();
+/// assert!(figtree.text().starts_with("BEGIN FIGTREE;"));
+/// assert!(figtree.text().ends_with("END;"));
+/// ```
+#[derive(Debug, Clone)]
+pub struct NexusDocument {
+    /// Blocks other than TAXA and TREES in order of the file (only if retained, otherwise empty)
+    pub blocks: Vec<RawBlock>,
+    /// Trees of the file (after skipping, burnin and thinning)
+    pub trees: Vec<Tree>,
+    /// Shared label mapping of the trees (and the sequences of the alignment)
+    pub taxa: LeafLabelMap,
+    /// Alignment of the first DATA or CHARACTERS block before the trees (only if parsed, otherwise `None`)
+    pub alignment: Option<Alignment>,
+}
+
+impl NexusDocument {
+    /// Returns the first block with the given name (case-insensitive), if any.
+    pub fn block(&self, name: &str) -> Option<&RawBlock> {
+        self.blocks.iter().find(|block| block.is_named(name))
+    }
+}
+
+// =#========================================================================#=
+// NEXUS PARSER BUILDER
+// =#========================================================================#=
+/// Builder (configuration) for a [NexusParser] on an in-memory NEXUS file.
+///
+/// # Configuration
+/// * `with_skip_first()` - Skips the first tree (e.g. the starting tree of an MCMC run)
+/// * `with_burnin(burnin)` - Discards a number or fraction of the (remaining) trees as burnin
+/// * `with_thinning(thin_every)` - Only keeps every `thin_every`-th tree after burnin
+/// * `with_load_options(options)` - Sets skipping, burnin and thinning at once (see [LoadOptions])
+/// * `with_underscores_to_spaces()` - Converts underscores in unquoted labels to spaces (NEXUS convention)
+/// * `with_annotations(mode)` - Collects metadata comments `[&...]` of vertices and branches (see [AnnotationMode])
+/// * `with_duplicate_taxa(policy)` - Handling of taxa appearing more than once in a tree (see [DuplicateTaxonPolicy])
+/// * `with_negative_branch_lengths(policy)` - Handling of tiny negative branch lengths (see [NegativeBranchLengthPolicy])
+/// * `with_strict()` - Checks trees against the taxa declared in the TAXA block (see [ParseOptions::strict])
+/// * `with_options(options)` - Sets all [ParseOptions] at once
+/// * `with_progress(callback)` - Reports the progress of parsing trees to a callback, e.g. for a progress bar
+/// * `with_lenient()` - Skips malformed trees instead of failing (see [SkippedTree])
+/// * `with_raw_blocks()` - Retains blocks other than TAXA and TREES verbatim (see [RawBlock])
+/// * `with_alignment()` - Parses the MATRIX of a DATA or CHARACTERS block into an [Alignment] (see [NexusDocument])
+/// * `eager()` - Parses all trees when building (default)
+/// * `lazy()` - Only locates trees when building and parses them on demand
+///
+/// # Example
+/// ```no_run
+/// use nexus_parser::io::parser::nexus::{Burnin, NexusParserBuilder};
+///
+/// let bytes = std::fs::read("phylo.trees").unwrap();
+/// let mut parser = NexusParserBuilder::for_bytes(bytes)
+///     .with_burnin(Burnin::Percentage(0.1))
+///     .lazy()
+///     .build()
+///     .unwrap();
+///
+/// while let Some(tree) = parser.next_tree().unwrap() {
+///     println!("{}", tree.num_leaves());
+/// }
+/// ```
+pub struct NexusParserBuilder<B: AsRef<[u8]> = Vec<u8>> {
+    parser: ByteParser<InMemoryByteSource<B>>,
+    num_skip_first: usize,
+    burnin: Burnin,
+    thin_every: usize,
+    mode: Mode,
+    annotations: AnnotationMode,
+    duplicate_taxa: DuplicateTaxonPolicy,
+    negative_branch_lengths: NegativeBranchLengthPolicy,
+    strict: bool,
+    lenient: bool,
+    raw_blocks: bool,
+    alignment: bool,
+    progress: Option<ProgressHook>,
+}
+
+/// Whether trees are parsed when building the [NexusParser] or on demand
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Mode {
+    Eager,
+    Lazy,
+}
+
+impl NexusParserBuilder {
+    /// Creates a new [NexusParserBuilder] reading the whole file into memory,
+    /// decompressing it if compressed (see [decompressed_reader]).
+    ///
+    /// # Errors
+    /// Returns an error if the file cannot be read, or is compressed in a format whose feature is not enabled.
+    #[cfg(feature = "fs")]
+    pub fn for_file(file: File) -> io::Result<Self> {
+        let mut bytes = Vec::new();
+        decompressed_reader(BufReader::new(file))?.read_to_end(&mut bytes)?;
+        Ok(Self::for_bytes(bytes))
+    }
+
+    /// Creates a new [NexusParserBuilder] for the given NEXUS content.
+    pub fn for_bytes(bytes: Vec<u8>) -> Self {
+        Self::for_buffer(bytes)
+    }
+}
+
+#[cfg(feature = "mmap")]
+impl NexusParserBuilder<memmap2::Mmap> {
+    /// Creates a new [NexusParserBuilder] over the memory-mapped file,
+    /// so the file is not read into (and held twice in) memory up front.
+    ///
+    /// # Errors
+    /// Returns an error if the file cannot be mapped.
+    ///
+    /// Note that the file must not be modified (e.g. by a still running MCMC) while the parser exists,
+    /// since changes to a memory-mapped file are undefined behavior.
+    pub fn for_mmap(file: &File) -> io::Result<Self> {
+        // SAFETY: Mapped read-only; concurrent modification is excluded by the documented contract
+        let mmap = unsafe { memmap2::Mmap::map(file)? };
+        Ok(Self::for_buffer(mmap))
+    }
+}
+
+impl<B: AsRef<[u8]>> NexusParserBuilder<B> {
+    /// Creates a new [NexusParserBuilder] for NEXUS content in any `&[u8]`-like buffer, without copying it.
+    pub fn for_buffer(buffer: B) -> Self {
+        Self {
+            parser: ByteParser::new(InMemoryByteSource::new(buffer)),
+            num_skip_first: 0,
+            burnin: Burnin::Count(0),
+            thin_every: 1,
+            mode: Mode::Eager,
+            annotations: AnnotationMode::Skip,
+            duplicate_taxa: DuplicateTaxonPolicy::Error,
+            negative_branch_lengths: NegativeBranchLengthPolicy::ClampToZero,
+            strict: false,
+            lenient: false,
+            raw_blocks: false,
+            alignment: false,
+            progress: None,
+        }
+    }
+
+    /// Skips the first tree of the file.
+    pub fn with_skip_first(mut self) -> Self {
+        self.num_skip_first = 1;
+        self
+    }
+
+    /// Sets the burnin, discarded after skipping the first tree (if configured).
+    ///
+    /// # Panics
+    /// Panics if a [Burnin::Percentage] is not within `[0, 1]`.
+    pub fn with_burnin(mut self, burnin: Burnin) -> Self {
+        if let Burnin::Percentage(fraction) = burnin {
+            assert!((0.0..=1.0).contains(&fraction), "Burnin percentage {} not within [0, 1]", fraction);
+        }
+        self.burnin = burnin;
+        self
+    }
+
+    /// Only keeps every `thin_every`-th tree after burnin (the first, the `thin_every + 1`-th, ...);
+    /// other trees are not parsed.
+    ///
+    /// # Panics
+    /// Panics if `thin_every` is `0`.
+    pub fn with_thinning(mut self, thin_every: usize) -> Self {
+        assert!(thin_every > 0, "Thinning interval must be positive");
+        self.thin_every = thin_every;
+        self
+    }
+
+    /// Sets which trees to load (see [LoadOptions]), overriding skipping, burnin and thinning configured before.
+    ///
+    /// # Panics
+    /// Panics if `skip_fraction` is not within `[0, 1]` or `thin_every` is `0`.
+    pub fn with_load_options(mut self, options: LoadOptions) -> Self {
+        self.num_skip_first = options.skip_first;
+        self.with_burnin(Burnin::Percentage(options.skip_fraction))
+            .with_thinning(options.thin_every)
+    }
+
+    /// Converts underscores in unquoted labels (TAXLABELS, TRANSLATE, Newick strings, tree names) to spaces,
+    /// as the NEXUS standard prescribes; underscores in quoted labels are kept.
+    pub fn with_underscores_to_spaces(mut self) -> Self {
+        self.parser = self.parser.with_underscores_to_spaces(true);
+        self
+    }
+
+    /// Sets how metadata comments `[&...]` in trees are handled (default [AnnotationMode::Skip]).
+    pub fn with_annotations(mut self, mode: AnnotationMode) -> Self {
+        self.annotations = mode;
+        self
+    }
+
+    /// Sets how a taxon appearing more than once in the same tree is handled (default [DuplicateTaxonPolicy::Error]).
+    pub fn with_duplicate_taxa(mut self, policy: DuplicateTaxonPolicy) -> Self {
+        self.duplicate_taxa = policy;
+        self
+    }
+
+    /// Sets how tiny negative branch lengths from numerical noise are handled
+    /// (default [NegativeBranchLengthPolicy::ClampToZero]).
+    pub fn with_negative_branch_lengths(mut self, policy: NegativeBranchLengthPolicy) -> Self {
+        self.negative_branch_lengths = policy;
+        self
+    }
+
+    /// Checks that trees contain exactly the taxa declared in the TAXA block, see [ParseOptions::strict].
+    pub fn with_strict(mut self) -> Self {
+        self.strict = true;
+        self
+    }
+
+    /// Sets all [ParseOptions], overriding previous configuration of the same options.
+    pub fn with_options(mut self, options: ParseOptions) -> Self {
+        self.parser = self.parser.with_underscores_to_spaces(options.underscores_to_spaces);
+        self.duplicate_taxa = options.duplicate_taxa;
+        self.negative_branch_lengths = options.negative_branch_lengths;
+        self.strict = options.strict;
+        self
+    }
+
+    /// Sets a callback invoked periodically while parsing trees with the number of bytes read
+    /// and the number of trees parsed so far, e.g. to show a progress bar.
+    ///
+    /// The callback is invoked after a tree once at least 1 MiB has been read since its last invocation,
+    /// and once more after the last tree. Bytes count the input up to the end of the last tree.
+    ///
+    /// # Example
+    /// ```
+    /// use nexus_parser::io::parser::nexus::NexusParserBuilder;
+    /// use std::sync::atomic::{AtomicUsize, Ordering};
+    /// use std::sync::Arc;
+    ///
+    /// let nexus = b"#NEXUS\nBEGIN TREES;\nTREE t1 = ((A,B),C);\nTREE t2 = ((A,C),B);\nEND;\n".to_vec();
+    /// let num_trees = Arc::new(AtomicUsize::new(0));
+    /// let counter = Arc::clone(&num_trees);
+    ///
+    /// NexusParserBuilder::for_bytes(nexus)
+    ///     .with_progress(move |_bytes_read, trees_parsed| counter.store(trees_parsed, Ordering::Relaxed))
+    ///     .build()
+    ///     .unwrap();
+    /// assert_eq!(num_trees.load(Ordering::Relaxed), 2);
+    /// ```
+    pub fn with_progress(mut self, callback: impl Fn(usize, usize) + Send + Sync + 'static) -> Self {
+        self.progress = Some(Arc::new(callback));
+        self
+    }
+
+    /// Skips malformed trees (e.g. truncated by a killed run) instead of failing, collecting their errors
+    /// (see [NexusParser::skipped_trees]). A TREE command missing its `;` ends at the next line starting
+    /// with `tree` (or `end`), so the following tree is still parsed.
+    pub fn with_lenient(mut self) -> Self {
+        self.lenient = true;
+        self
+    }
+
+    /// Retains blocks other than TAXA and TREES (before and after the trees) verbatim as [RawBlock]s,
+    /// see [NexusParser::raw_blocks], e.g. to extract FigTree settings or write them back.
+    pub fn with_raw_blocks(mut self) -> Self {
+        self.raw_blocks = true;
+        self
+    }
+
+    /// Parses the MATRIX of the first DATA or CHARACTERS block before the trees into an [Alignment]
+    /// of [NexusDocument::alignment], linking its sequences to the [LeafLabelMap] of the trees by name.
+    ///
+    /// Interleaved and non-interleaved matrices are supported, with the `DATATYPE` (DNA, RNA, protein
+    /// or standard), `MISSING`, `GAP` and `MATCHCHAR` of their FORMAT command.
+    /// Sequences of taxa not in the trees are added to the label map after all trees are parsed.
+    pub fn with_alignment(mut self) -> Self {
+        self.alignment = true;
+        self
+    }
+
+    /// Parses all trees when building (default).
+    pub fn eager(mut self) -> Self {
+        self.mode = Mode::Eager;
+        self
+    }
+
+    /// Parses trees on demand, only locating them when building.
+    pub fn lazy(mut self) -> Self {
+        self.mode = Mode::Lazy;
+        self
+    }
+
+    /// Builds the [NexusParser], parsing the TAXA block and TRANSLATE command,
+    /// locating all trees and (in eager mode) parsing them.
+    ///
+    /// # Errors
+    /// Returns a [ParsingError] if the file is not a valid NEXUS file,
+    /// (in eager mode, unless lenient) a tree cannot be parsed, or (in strict mode) does not match the TAXA block.
+    pub fn build(self) -> Result<NexusParser<B>, ParsingError> {
+        let mut parser = self.parser;
+
+        // Parse everything up to first tree
+        let mut preamble = if self.raw_blocks { Preamble::new().with_raw_blocks() } else { Preamble::new() };
+        preamble.parse_characters = self.alignment;
+        while preamble.parse_command(&mut parser)? == Step::Continue {}
+        let declared_taxa = self.strict.then(|| preamble.num_declared_taxa());
+        let translation = preamble.translation.clone();
+        let mut raw_blocks = preamble.raw_blocks.take().unwrap_or_default();
+        let matrix = preamble.matrix.take();
+        let newick_parser = preamble.into_newick_parser(&parser, self.strict)?
+            .with_annotations(self.annotations)
+            .with_duplicate_taxa(self.duplicate_taxa)
+            .with_negative_branch_lengths(self.negative_branch_lengths);
+
+        // Locate trees
+        let tree_positions = locate_trees(&mut parser, self.lenient);
+        if self.raw_blocks {
+            raw_blocks.extend(scan_trailing_blocks(&mut parser));
+        }
+        let start = first_loaded(self.num_skip_first, self.burnin, tree_positions.len());
+
+        let mut nexus_parser = NexusParser {
+            parser,
+            newick_parser,
+            translation,
+            raw_blocks,
+            matrix,
+            tree_positions,
+            start,
+            thin_every: self.thin_every,
+            current: start,
+            trees: None,
+            declared_taxa,
+            lenient: self.lenient,
+            skipped: Vec::new(),
+            progress: ProgressReporter::new(self.progress),
+        };
+
+        if self.mode == Mode::Eager {
+            let mut trees = Vec::with_capacity(nexus_parser.num_trees());
+            for i in (start..nexus_parser.tree_positions.len()).step_by(self.thin_every) {
+                trees.push(nexus_parser.parse_or_skip_tree_at(i)?);
+            }
+            nexus_parser.progress.finish(nexus_parser.parser.position());
+            nexus_parser.trees = Some(trees);
+        }
+
+        Ok(nexus_parser)
+    }
+}
+
+// =#========================================================================#=
+// NEXUS PARSER
+// =#========================================================================#=
+/// Parser for trees of an in-memory NEXUS file, created with a [NexusParserBuilder].
+///
+/// Trees share a single [LeafLabelMap], based on the TAXA block and TRANSLATE command.
+/// Parsed trees are named as in their TREE command.
+/// If lenient, malformed trees are skipped and collected as [SkippedTree]s.
+pub struct NexusParser<B: AsRef<[u8]> = Vec<u8>> {
+    parser: ByteParser<InMemoryByteSource<B>>,
+    newick_parser: NewickParser,
+    /// TRANSLATE command of the TREES block, if any
+    translation: Option<TranslationTable>,
+    /// Blocks other than TAXA and TREES, if retained
+    raw_blocks: Vec<RawBlock>,
+    /// Matrix of a DATA or CHARACTERS block, if parsed (resolved against the labels once all trees are parsed)
+    matrix: Option<CharacterMatrix>,
+    /// Start positions of all TREE commands
+    tree_positions: Vec<usize>,
+    /// Index of first tree after skipping first and burnin
+    start: usize,
+    /// Interval of trees to return from `start` on
+    thin_every: usize,
+    /// Index of next tree to return
+    current: usize,
+    /// Trees to return (from `start` on, thinned; `None` if skipped), if parsed eagerly
+    trees: Option<Vec<Option<Tree>>>,
+    /// Number of taxa declared in TAXA block, if trees are checked against it (strict mode)
+    declared_taxa: Option<usize>,
+    /// Whether malformed trees are skipped instead of failing
+    lenient: bool,
+    /// Malformed trees skipped so far (lenient mode)
+    skipped: Vec<SkippedTree>,
+    progress: ProgressReporter,
+}
+
+impl<B: AsRef<[u8]>> NexusParser<B> {
+    /// Returns the number of trees after skipping the first tree(s), burnin and thinning
+    /// (including malformed trees skipped in lenient mode).
+    pub fn num_trees(&self) -> usize {
+        (self.tree_positions.len() - self.start).div_ceil(self.thin_every)
+    }
+
+    /// Returns the total number of trees in the file.
+    pub fn num_total_trees(&self) -> usize {
+        self.tree_positions.len()
+    }
+
+    /// Returns the next tree, or `None` if all trees have been returned.
+    /// In lenient mode, malformed trees are skipped.
+    ///
+    /// # Errors
+    /// Returns a [ParsingError] if (in lazy mode, unless lenient) the tree cannot be parsed.
+    pub fn next_tree(&mut self) -> Result<Option<Tree>, ParsingError> {
+        while self.current < self.tree_positions.len() {
+            let tree = match &self.trees {
+                Some(trees) => trees[(self.current - self.start) / self.thin_every].clone(),
+                None => self.parse_or_skip_tree_at(self.current)?,
+            };
+            self.current += self.thin_every;
+            if tree.is_some() {
+                return Ok(tree);
+            }
+        }
+        self.progress.finish(self.parser.position());
+        Ok(None)
+    }
+
+    /// Resets the parser to the first tree (after skipping first tree(s) and burnin).
+    pub fn reset(&mut self) {
+        self.current = self.start;
+    }
+
+    /// Returns the malformed trees skipped so far in lenient mode (in eager mode all of them,
+    /// in lazy mode those reached), in order of the file (possibly repeated after a [reset](NexusParser::reset)).
+    pub fn skipped_trees(&self) -> &[SkippedTree] {
+        &self.skipped
+    }
+
+    /// Returns the shared [LeafLabelMap] of the trees.
+    pub fn leaf_label_map(&self) -> &LeafLabelMap {
+        self.newick_parser.leaf_label_map()
+    }
+
+    /// Returns the [TranslationTable] of the TRANSLATE command, if the file has one.
+    pub fn translation_table(&self) -> Option<&TranslationTable> {
+        self.translation.as_ref()
+    }
+
+    /// Returns the blocks other than TAXA and TREES in order of the file,
+    /// if retained (see [NexusParserBuilder::with_raw_blocks]), otherwise none.
+    pub fn raw_blocks(&self) -> &[RawBlock] {
+        &self.raw_blocks
+    }
+
+    /// Consumes the parser and returns all trees not yet returned together with their [LeafLabelMap].
+    ///
+    /// # Errors
+    /// Returns a [ParsingError] if (in lazy mode) a tree cannot be parsed.
+    pub fn into_results(mut self) -> Result<(Vec<Tree>, LeafLabelMap), ParsingError> {
+        let trees = self.remaining_trees()?;
+        Ok((trees, self.newick_parser.into_leaf_label_map()))
+    }
+
+    /// Consumes the parser and returns all trees not yet returned, the malformed trees skipped
+    /// (see [skipped_trees](NexusParser::skipped_trees)) and the [LeafLabelMap].
+    ///
+    /// # Errors
+    /// Returns a [ParsingError] if (in lazy mode, unless lenient) a tree cannot be parsed.
+    pub fn into_results_with_skipped(mut self) -> Result<LenientResults, ParsingError> {
+        let trees = self.remaining_trees()?;
+        Ok((trees, self.skipped, self.newick_parser.into_leaf_label_map()))
+    }
+
+    /// Consumes the parser and returns a [NexusDocument] with all trees not yet returned,
+    /// their [LeafLabelMap], the retained [RawBlock]s (see [NexusParserBuilder::with_raw_blocks])
+    /// and the parsed [Alignment] (see [NexusParserBuilder::with_alignment]).
+    ///
+    /// # Errors
+    /// Returns a [ParsingError] if (in lazy mode, unless lenient) a tree cannot be parsed.
+    pub fn into_document(mut self) -> Result<NexusDocument, ParsingError> {
+        let trees = self.remaining_trees()?;
+        let mut taxa = self.newick_parser.into_leaf_label_map();
+        let alignment = self.matrix.map(|matrix| matrix.into_alignment(&mut taxa));
+        Ok(NexusDocument { blocks: self.raw_blocks, trees, taxa, alignment })
+    }
+
+    /// Returns all trees not yet returned (parsing them in lazy mode).
+    fn remaining_trees(&mut self) -> Result<Vec<Tree>, ParsingError> {
+        let trees = match self.trees.take() {
+            Some(mut trees) => trees.split_off((self.current - self.start) / self.thin_every)
+                .into_iter().flatten().collect(),
+            None => {
+                let mut trees = Vec::with_capacity(self.num_trees());
+                while let Some(tree) = self.next_tree()? {
+                    trees.push(tree);
+                }
+                trees
+            }
+        };
+        self.current = self.tree_positions.len();
+        Ok(trees)
+    }
+
+    /// Parses the `i`-th tree; if lenient, records a malformed tree as skipped and returns `None` instead of failing.
+    fn parse_or_skip_tree_at(&mut self, i: usize) -> Result<Option<Tree>, ParsingError> {
+        self.parser.set_position(self.tree_positions[i]);
+        match parse_tree_command(&mut self.parser, &mut self.newick_parser, self.declared_taxa) {
+            Ok(tree) => {
+                self.progress.tree_parsed(self.parser.position());
+                Ok(Some(tree))
+            }
+            Err(error) if self.lenient => {
+                let name = parse_tree_name_at(&mut self.parser, self.tree_positions[i]);
+                self.skipped.push(SkippedTree { index: i, name, error });
+                Ok(None)
+            }
+            Err(error) => Err(error),
+        }
+    }
+}
+
+// =#========================================================================#=
+// TREE READER
+// =#========================================================================#=
+/// Streaming reader for trees of a NEXUS file from any [BufRead] source.
+///
+/// Parses the TAXA block and TRANSLATE command up front and then yields trees lazily,
+/// one TREE command at a time, so memory usage is independent of the number of trees.
+/// After an error, iteration continues with the next TREE command (if any).
+///
+/// # Example
+/// ```no_run
+/// use nexus_parser::io::parser::nexus::TreeReader;
+/// use std::fs::File;
+/// use std::io::BufReader;
+///
+/// let reader = TreeReader::new(BufReader::new(File::open("huge.trees").unwrap())).unwrap();
+/// let mut num_trees = 0;
+/// for tree in reader {
+///     let tree = tree.unwrap();
+///     num_trees += 1;
+/// }
+/// ```
+pub struct TreeReader<R: BufRead> {
+    reader: R,
+    newick_parser: NewickParser,
+    /// TRANSLATE command of the TREES block, if any
+    translation: Option<TranslationTable>,
+    /// First TREE command, already read while parsing the preamble
+    pending: Option<ByteParser<InMemoryByteSource>>,
+    /// Number of bytes read so far (for error positions)
+    position: usize,
+    /// Offset of the command currently parsed (for error positions)
+    command_offset: usize,
+    /// Line and column after the bytes read so far (for error locations)
+    line_and_column: (usize, usize),
+    /// Line and column of the command currently parsed (for error locations)
+    command_line_and_column: (usize, usize),
+    /// Whether underscores in unquoted labels are converted to spaces
+    underscores_to_spaces: bool,
+    /// Number of taxa declared in TAXA block, if trees are checked against it (strict mode)
+    declared_taxa: Option<usize>,
+    /// Number of trees still to skip (without parsing them)
+    num_to_skip: usize,
+    /// Interval of trees to return after skipping
+    thin_every: usize,
+    /// Number of trees seen after skipping (for thinning)
+    num_seen: usize,
+    progress: ProgressReporter,
+    done: bool,
+}
+
+#[cfg(feature = "fs")]
+impl TreeReader<Box<dyn BufRead + Send>> {
+    /// Creates a new [TreeReader] on the given file, parsing everything up to the first tree.
+    /// Compressed files are decompressed on the fly (see [decompressed_reader]).
+    ///
+    /// # Errors
+    /// Returns a [ParsingError] if the file is not a valid NEXUS file or cannot be read,
+    /// or is compressed in a format whose feature is not enabled.
+    pub fn for_file(file: File) -> Result<Self, ParsingError> {
+        let reader = decompressed_reader(BufReader::new(file)).map_err(|e| ParsingError::io_error(&e, 0))?;
+        Self::new(reader)
+    }
+}
+
+impl<R: BufRead> TreeReader<R> {
+    /// Creates a new [TreeReader] on the given source, parsing everything up to the first tree.
+    ///
+    /// # Errors
+    /// Returns a [ParsingError] if the input is not a valid NEXUS file or cannot be read.
+    pub fn new(reader: R) -> Result<Self, ParsingError> {
+        Self::new_with_underscores_to_spaces(reader, false)
+    }
+
+    /// Creates a new [TreeReader] on the given source like [TreeReader::new],
+    /// converting underscores in unquoted labels to spaces if `underscores_to_spaces` is set
+    /// (see [ByteParser::with_underscores_to_spaces]).
+    ///
+    /// # Errors
+    /// Returns a [ParsingError] if the input is not a valid NEXUS file or cannot be read.
+    pub fn new_with_underscores_to_spaces(reader: R, underscores_to_spaces: bool) -> Result<Self, ParsingError> {
+        Self::new_with_options(reader, ParseOptions { underscores_to_spaces, ..ParseOptions::default() })
+    }
+
+    /// Creates a new [TreeReader] on the given source like [TreeReader::new], configured by [ParseOptions].
+    ///
+    /// # Errors
+    /// Returns a [ParsingError] if the input is not a valid NEXUS file or cannot be read,
+    /// or (in strict mode) the TRANSLATE command does not match the TAXA block.
+    pub fn new_with_options(mut reader: R, options: ParseOptions) -> Result<Self, ParsingError> {
+        let ParseOptions { strict, underscores_to_spaces, duplicate_taxa, negative_branch_lengths } = options;
+        let mut preamble = Preamble::new();
+        let mut position = 0;
+        let mut line_and_column = (1, 1);
+        let mut pending = None;
+
+        loop {
+            let mut command = Vec::new();
+            if !read_command(&mut reader, preamble.in_known_block(), &mut command)
+                .map_err(|e| ParsingError::io_error(&e, position))? {
+                break;
+            }
+
+            let offset = position;
+            let location = line_and_column;
+            position += command.len();
+            line_and_column = advance_line_and_column(line_and_column, &command);
+            let mut parser = ByteParser::new(InMemoryByteSource::from_vec(command))
+                .with_underscores_to_spaces(underscores_to_spaces);
+            // Command may contain several steps, e.g. header and BEGIN or words of unknown block
+            let mut step = Step::Continue;
+            while step == Step::Continue {
+                step = preamble.parse_command(&mut parser)
+                    .map_err(|e| e.offset_by(offset).located_in_chunk_at(location))?;
+            }
+            if step == Step::FirstTree {
+                pending = Some((parser, offset, location));
+                break;
+            }
+        }
+
+        let (pending, command_offset, command_line_and_column) = match pending {
+            Some((parser, offset, location)) => (Some(parser), offset, location),
+            None => (None, position, line_and_column),
+        };
+        let done = pending.is_none();
+        let declared_taxa = strict.then(|| preamble.num_declared_taxa());
+        let translation = preamble.translation.clone();
+        let newick_parser = match &pending {
+            Some(parser) => preamble.into_newick_parser(parser, strict),
+            None => preamble.into_newick_parser(&ByteParser::from_bytes(b""), strict),
+        }.map_err(|e| e.offset_by(command_offset).located_in_chunk_at(command_line_and_column))?
+            .with_duplicate_taxa(duplicate_taxa)
+            .with_negative_branch_lengths(negative_branch_lengths);
+
+        Ok(Self {
+            reader,
+            newick_parser,
+            translation,
+            pending,
+            position,
+            command_offset,
+            line_and_column,
+            command_line_and_column,
+            underscores_to_spaces,
+            declared_taxa,
+            num_to_skip: 0,
+            thin_every: 1,
+            num_seen: 0,
+            progress: ProgressReporter::default(),
+            done,
+        })
+    }
+
+    /// Sets how metadata comments `[&...]` in trees are handled (default [AnnotationMode::Skip]).
+    pub fn with_annotations(mut self, mode: AnnotationMode) -> Self {
+        self.newick_parser = self.newick_parser.with_annotations(mode);
+        self
+    }
+
+    /// Sets a callback invoked periodically while reading trees with the number of bytes read
+    /// (decompressed) and the number of trees parsed so far, see [NexusParserBuilder::with_progress].
+    pub fn with_progress(mut self, callback: impl Fn(usize, usize) + Send + Sync + 'static) -> Self {
+        self.progress = ProgressReporter::new(Some(Arc::new(callback)));
+        self
+    }
+
+    /// Sets which trees to read (see [LoadOptions]); skipped trees are not parsed.
+    ///
+    /// # Panics
+    /// Panics if `skip_fraction` is not `0`, since the number of trees of a stream is unknown in advance
+    /// (use a [NexusParserBuilder] instead), or if `thin_every` is `0`.
+    pub fn with_load_options(mut self, options: LoadOptions) -> Self {
+        assert!(options.skip_fraction == 0.0,
+                "Cannot discard a fraction of trees when streaming, the number of trees is unknown in advance");
+        assert!(options.thin_every > 0, "Thinning interval must be positive");
+        self.num_to_skip = options.skip_first;
+        self.thin_every = options.thin_every;
+        self
+    }
+
+    /// Returns the shared [LeafLabelMap] of the trees (growing while reading if labels are verbatim).
+    pub fn leaf_label_map(&self) -> &LeafLabelMap {
+        self.newick_parser.leaf_label_map()
+    }
+
+    /// Returns the [TranslationTable] of the TRANSLATE command, if the file has one.
+    pub fn translation_table(&self) -> Option<&TranslationTable> {
+        self.translation.as_ref()
+    }
+
+    /// Consumes the reader and returns the shared [LeafLabelMap] of the trees read.
+    pub fn into_leaf_label_map(self) -> LeafLabelMap {
+        self.newick_parser.into_leaf_label_map()
+    }
+
+    /// Takes back a tree read before that is no longer needed, so the next trees reuse its buffers
+    /// (see [NewickParser::recycle]).
+    ///
+    /// # Example
+    /// ```
+    /// use nexus_parser::io::parser::nexus::TreeReader;
+    ///
+    /// let input = "#NEXUS\nBEGIN TREES;\n  TREE t1 = ((A:1,B:1):1,C:2);\n  TREE t2 = ((A:1,C:1):1,B:2);\nEND;";
+    /// let mut reader = TreeReader::new(input.as_bytes()).unwrap();
+    /// let mut total_length = 0.0;
+    /// while let Some(tree) = reader.next() {
+    ///     let tree = tree.unwrap();
+    ///     total_length += tree.total_branch_length();
+    ///     reader.recycle(tree);
+    /// }
+    /// assert_eq!(total_length, 10.0);
+    /// ```
+    pub fn recycle(&mut self, tree: Tree) {
+        self.newick_parser.recycle(tree);
+    }
+
+    /// Reads the next command of the TREES block, or `None` at EOF.
+    fn next_command(&mut self) -> Result<Option<ByteParser<InMemoryByteSource>>, ParsingError> {
+        if let Some(parser) = self.pending.take() {
+            return Ok(Some(parser));
+        }
+
+        let mut command = Vec::new();
+        if !read_command(&mut self.reader, true, &mut command)
+            .map_err(|e| ParsingError::io_error(&e, self.position))? {
+            return Ok(None);
+        }
+        self.command_offset = self.position;
+        self.position += command.len();
+        self.command_line_and_column = self.line_and_column;
+        self.line_and_column = advance_line_and_column(self.line_and_column, &command);
+
+        Ok(Some(ByteParser::new(InMemoryByteSource::from_vec(command))
+            .with_underscores_to_spaces(self.underscores_to_spaces)))
+    }
+
+    /// Shifts the location of an error in the current command to the whole input.
+    fn locate_error(&self, error: ParsingError) -> ParsingError {
+        error.offset_by(self.command_offset).located_in_chunk_at(self.command_line_and_column)
+    }
+}
+
+impl<R: BufRead> Iterator for TreeReader<R> {
+    type Item = Result<Tree, ParsingError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.done {
+            let mut parser = match self.next_command() {
+                Ok(Some(parser)) => parser,
+                Ok(None) => break,
+                Err(e) => {
+                    self.done = true;
+                    return Some(Err(e));
+                }
+            };
+
+            if let Err(e) = parser.skip_comment_and_whitespace() {
+                return Some(Err(self.locate_error(e)));
+            }
+            if parser.peek_is_keyword(TREE) {
+                if self.num_to_skip > 0 {
+                    self.num_to_skip -= 1;
+                    continue;
+                }
+                self.num_seen += 1;
+                if !(self.num_seen - 1).is_multiple_of(self.thin_every) {
+                    continue;
+                }
+                let result = parse_tree_command(&mut parser, &mut self.newick_parser, self.declared_taxa);
+                if result.is_ok() {
+                    self.progress.tree_parsed(self.position);
+                }
+                return Some(result.map_err(|e| self.locate_error(e)));
+            }
+            if parser.peek_is_keyword(END) || parser.peek_is_keyword(ENDBLOCK) {
+                break;
+            }
+            // Other commands (and trailing whitespace/comments) are ignored
+        }
+
+        self.done = true;
+        self.progress.finish(self.position);
+        None
+    }
+}
+
+// =#========================================================================#=
+// EVENT PARSER
+// =#========================================================================#=
+/// Event of a tree reported by a [NexusEventParser].
+///
+/// Each tree is reported as `BeginTree`, then its vertices in post-order as soon as they are parsed
+/// (children before their parent, the root last) and finally `EndTree`.
+/// A malformed tree ends without `EndTree`, see [NexusEventParser::feed].
+#[derive(Debug, Clone, Copy)]
+pub enum TreeEvent<'a> {
+    /// Start of a tree, with its name and whether it is rooted
+    BeginTree { name: &'a str, rooted: bool },
+    /// Leaf with its [TreeIndex], label and [LabelIndex](crate::model::tree::LabelIndex)
+    Leaf { index: usize, label: &'a str, label_index: usize, branch_length: Option<f64> },
+    /// Internal vertex or root with its [TreeIndex], children and node label (if any)
+    Internal { index: usize, children: &'a [usize], label: Option<&'a str>, branch_length: Option<f64> },
+    /// End of a tree, with the whole tree for consumers that need more than the events
+    EndTree(&'a Tree),
+}
+
+/// Push parser for NEXUS trees arriving in chunks, e.g. from a socket or a chunked HTTP body.
+///
+/// Instead of pulling bytes from a source like a [TreeReader], it is fed chunks of any size
+/// and reports each vertex of a tree as [TreeEvent] to a callback as soon as it is parsed,
+/// driving the same [NewickParser] as files one step (vertex) at a time across chunks.
+/// Only the current incomplete step is buffered, e.g. a label split between chunks or a command other than TREE,
+/// never the whole payload or a whole TREE command. Trees are parsed with the same preamble handling
+/// (TAXA block, TRANSLATE command) as files, and their vertices are collected into the [Tree] of
+/// [TreeEvent::EndTree] (recycled for the next tree).
+///
+/// # Example
+/// ```
+/// use nexus_parser::io::parser::nexus::{NexusEventParser, TreeEvent};
+///
+/// let mut leaves = Vec::new();
+/// let mut parser = NexusEventParser::new(|event| {
+///     if let TreeEvent::Leaf { label, .. } = event {
+///         leaves.push(label.to_string());
+///     }
+/// });
+///
+/// // Chunks split anywhere, e.g. within labels
+/// for chunk in ["#NEXUS\nBEGIN TREES;\n  TREE t1 = ((Ho", "mo,Pan),Gorilla);\n", "END;\n"] {
+///     parser.feed(chunk.as_bytes()).unwrap();
+/// }
+/// let labels = parser.finish().unwrap();
+///
+/// assert_eq!(labels.num_labels(), 3);
+/// assert_eq!(leaves, vec!["Homo", "Pan", "Gorilla"]);
+/// ```
+pub struct NexusEventParser<F: FnMut(TreeEvent<'_>)> {
+    callback: F,
+    state: EventState,
+    command: Command,
+    options: ParseOptions,
+    annotations: AnnotationMode,
+    /// Bytes of the current, incomplete command (or step of its tree) from `start` on
+    buffer: Vec<u8>,
+    /// Start of the bytes of the buffer not parsed yet (drained once per feed)
+    start: usize,
+    /// Number of bytes after `start` already scanned
+    scanned: usize,
+    scanner: CommandScanner,
+    /// Number of bytes parsed (for error positions)
+    position: usize,
+    /// Line and column after the bytes parsed (for error locations)
+    line_and_column: (usize, usize),
+    num_trees: usize,
+}
+
+/// Parsing state of a [NexusEventParser]
+enum EventState {
+    /// Before the first TREE command
+    Preamble(Preamble),
+    /// Within the TREES block, with the parser of its trees
+    Trees { newick_parser: NewickParser, declared_taxa: Option<usize> },
+    /// After the TREES block or a fatal error, with the labels of the trees (if any)
+    Done(Option<LeafLabelMap>),
+}
+
+/// Current command of a [NexusEventParser], as far as known
+enum Command {
+    /// Not known yet: within the TREES block a TREE command once its Newick string starts at `(`,
+    /// otherwise complete at `;`
+    Unknown,
+    /// Command of the TREES block other than TREE containing `(`, complete at `;`
+    Other,
+    /// Rest of a malformed TREE command, skipped up to `;`
+    Malformed,
+    /// TREE command with its tree parsed so far
+    Tree(Box<PartialTree>, TreeHead),
+}
+
+impl<F: FnMut(TreeEvent<'_>)> NexusEventParser<F> {
+    /// Creates a push parser reporting the events of all trees to `callback`.
+    pub fn new(callback: F) -> Self {
+        Self {
+            callback,
+            state: EventState::Preamble(Preamble::new()),
+            command: Command::Unknown,
+            options: ParseOptions::default(),
+            annotations: AnnotationMode::default(),
+            buffer: Vec::new(),
+            start: 0,
+            scanned: 0,
+            scanner: CommandScanner::default(),
+            position: 0,
+            line_and_column: (1, 1),
+            num_trees: 0,
+        }
+    }
+
+    /// Configures the parser by [ParseOptions] (before feeding any input).
+    pub fn with_options(mut self, options: ParseOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Sets how metadata comments `[&...]` in trees are handled (default [AnnotationMode::Skip]),
+    /// available through the tree of [TreeEvent::EndTree].
+    pub fn with_annotations(mut self, mode: AnnotationMode) -> Self {
+        self.annotations = mode;
+        self
+    }
+
+    /// Feeds the next chunk of input, parsing all commands and vertices of trees completed by it
+    /// and reporting their events.
+    ///
+    /// # Errors
+    /// Returns a [ParsingError] at the first malformed command, keeping the remaining input buffered:
+    /// the rest of a malformed tree is skipped (without [TreeEvent::EndTree]), and the commands after it
+    /// are parsed by the next call (e.g. `feed(&[])` to collect all errors of a chunk) or by [NexusEventParser::finish].
+    /// An error before the first tree (e.g. a missing `#NEXUS` header) is fatal, and further input is ignored.
+    ///
+    /// # Example
+    /// ```
+    /// use nexus_parser::io::parser::nexus::NexusEventParser;
+    ///
+    /// let mut parser = NexusEventParser::new(|_| {});
+    /// let mut errors = Vec::new();
+    /// let mut chunk: &[u8] = b"#NEXUS\nBEGIN TREES;\nTREE a = (A,;\nTREE b = (A,B);\nTREE c = (A;\nEND;\n";
+    /// while let Err(error) = parser.feed(chunk) {
+    ///     errors.push(error);
+    ///     chunk = &[];
+    /// }
+    /// assert_eq!(errors.len(), 2);
+    /// assert_eq!(parser.num_trees(), 1);
+    /// ```
+    pub fn feed(&mut self, chunk: &[u8]) -> Result<(), ParsingError> {
+        if matches!(self.state, EventState::Done(_)) {
+            return Ok(());
+        }
+        self.buffer.extend_from_slice(chunk);
+
+        // Parse slices of the buffer, draining parsed bytes only once per chunk
+        let buffer = std::mem::take(&mut self.buffer);
+        let result = self.parse_buffered(&buffer);
+        self.buffer = buffer;
+        self.buffer.drain(..self.start);
+        self.start = 0;
+        result
+    }
+
+    /// Ends the input, parsing the commands still buffered and a last command without terminating `;` (if any).
+    ///
+    /// # Returns
+    /// The shared [LeafLabelMap] of the trees (empty without trees).
+    ///
+    /// # Errors
+    /// Returns the first [ParsingError] of the buffered commands, or if the input ends within a command.
+    pub fn finish(mut self) -> Result<LeafLabelMap, ParsingError> {
+        self.feed(&[])?;
+        let rest = std::mem::take(&mut self.buffer);
+        if !matches!(self.state, EventState::Done(_)) {
+            match self.command {
+                // Rest has no delimiter, so the next step fails at the end of input
+                Command::Tree(..) => self.parse_tree_step(&rest)?,
+                Command::Unknown | Command::Other if !rest.is_empty() => self.parse_command(&rest)?,
+                _ => {}
+            }
+        }
+
+        Ok(match self.state {
+            EventState::Trees { newick_parser, .. } => newick_parser.into_leaf_label_map(),
+            EventState::Done(labels) => labels.unwrap_or_else(|| LeafLabelMap::new(0)),
+            EventState::Preamble(_) => LeafLabelMap::new(0),
+        })
+    }
+
+    /// Returns the number of trees reported so far.
+    pub fn num_trees(&self) -> usize {
+        self.num_trees
+    }
+
+    /// Returns the shared [LeafLabelMap] of the trees, once the first tree was reached.
+    pub fn leaf_label_map(&self) -> Option<&LeafLabelMap> {
+        match &self.state {
+            EventState::Trees { newick_parser, .. } => Some(newick_parser.leaf_label_map()),
+            EventState::Done(labels) => labels.as_ref(),
+            EventState::Preamble(_) => None,
+        }
+    }
+
+    /// Parses all complete commands and steps of trees of the buffer after `start`.
+    fn parse_buffered(&mut self, buffer: &[u8]) -> Result<(), ParsingError> {
+        while !matches!(self.state, EventState::Done(_)) {
+            let unscanned = &buffer[self.start + self.scanned..];
+            let in_trees_block = match &self.state {
+                EventState::Preamble(preamble) => preamble.in_trees_block(),
+                _ => true,
+            };
+            let found = match &self.command {
+                Command::Tree(..) => self.scanner.scan_until(unscanned, NEWICK_STEP_DELIMITERS),
+                Command::Unknown if in_trees_block => self.scanner.scan_until(unscanned, b"(;"),
+                _ => {
+                    let quote_aware = match &self.state {
+                        EventState::Preamble(preamble) => preamble.in_known_block(),
+                        _ => true,
+                    };
+                    self.scanner.scan(unscanned, quote_aware)
+                }
+            };
+            let Some(len) = found else {
+                self.scanned = buffer.len() - self.start;
+                break;
+            };
+
+            let bytes = &buffer[self.start..self.start + self.scanned + len];
+            self.scanned = 0;
+            match self.command {
+                Command::Tree(..) => self.parse_tree_step(bytes)?,
+                Command::Malformed => {
+                    self.advance(bytes);
+                    self.command = Command::Unknown;
+                }
+                Command::Unknown if bytes.ends_with(b"(") => {
+                    let head = &bytes[..bytes.len() - 1];
+                    if is_tree_command(head) {
+                        self.begin_tree(head)?;
+                    } else {
+                        self.command = Command::Other;
+                        self.scanned = bytes.len();
+                    }
+                }
+                _ => {
+                    self.command = Command::Unknown;
+                    self.parse_command(bytes)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Marks the given bytes at `start` of the buffer as parsed.
+    fn advance(&mut self, bytes: &[u8]) {
+        self.start += bytes.len();
+        self.position += bytes.len();
+        self.line_and_column = advance_line_and_column(self.line_and_column, bytes);
+    }
+
+    /// Parses a complete command of the preamble or TREES block.
+    fn parse_command(&mut self, command: &[u8]) -> Result<(), ParsingError> {
+        let offset = self.position;
+        let location = self.line_and_column;
+        self.advance(command);
+        let locate = |e: ParsingError| e.offset_by(offset).located_in_chunk_at(location);
+        let mut parser = ByteParser::from_slice(command).with_underscores_to_spaces(self.options.underscores_to_spaces);
+
+        if let EventState::Preamble(preamble) = &mut self.state {
+            // Command may contain several steps, e.g. header and BEGIN
+            let mut step = Ok(Step::Continue);
+            while matches!(step, Ok(Step::Continue)) {
+                step = preamble.parse_command(&mut parser);
+            }
+            match step {
+                Ok(Step::FirstTree) => {}
+                Ok(_) => return Ok(()),
+                Err(e) => {
+                    self.state = EventState::Done(None);
+                    return Err(locate(e));
+                }
+            }
+            self.enter_trees(&parser).map_err(locate)?;
+        }
+
+        let EventState::Trees { newick_parser, declared_taxa } = &mut self.state else {
+            return Ok(());
+        };
+        parser.skip_comment_and_whitespace().map_err(locate)?;
+        if parser.peek_is_keyword(END) || parser.peek_is_keyword(ENDBLOCK) {
+            let EventState::Trees { newick_parser, .. } = std::mem::replace(&mut self.state, EventState::Done(None)) else {
+                unreachable!("State is trees");
+            };
+            self.state = EventState::Done(Some(newick_parser.into_leaf_label_map()));
+            return Ok(());
+        }
+        if !parser.peek_is_keyword(TREE) {
+            // Other commands (and trailing whitespace/comments) are ignored
+            return Ok(());
+        }
+
+        // TREE command without Newick string, which is otherwise parsed step by step
+        let tree = parse_tree_command(&mut parser, newick_parser, *declared_taxa).map_err(locate)?;
+        self.num_trees += 1;
+        report_tree_events(&tree, newick_parser.leaf_label_map(), &mut self.callback);
+        newick_parser.recycle(tree);
+        Ok(())
+    }
+
+    /// Leaves the preamble at the first TREE command, creating the [NewickParser] of the trees.
+    ///
+    /// # Errors
+    /// Returns a [ParsingError] if TAXA block and TRANSLATE command do not match in strict mode (fatal).
+    fn enter_trees<S: ByteSource>(&mut self, parser: &ByteParser<S>) -> Result<(), ParsingError> {
+        if !matches!(self.state, EventState::Preamble(_)) {
+            return Ok(());
+        }
+        let EventState::Preamble(preamble) = std::mem::replace(&mut self.state, EventState::Done(None)) else {
+            unreachable!("State is preamble");
+        };
+        let declared_taxa = self.options.strict.then(|| preamble.num_declared_taxa());
+        let newick_parser = preamble.into_newick_parser(parser, self.options.strict)?
+            .with_annotations(self.annotations)
+            .with_duplicate_taxa(self.options.duplicate_taxa)
+            .with_negative_branch_lengths(self.options.negative_branch_lengths);
+        self.state = EventState::Trees { newick_parser, declared_taxa };
+        Ok(())
+    }
+
+    /// Parses the head of a TREE command up to its Newick string, reports the start of its tree
+    /// and continues with parsing the tree step by step.
+    fn begin_tree(&mut self, head: &[u8]) -> Result<(), ParsingError> {
+        let (offset, location) = (self.position, self.line_and_column);
+        let locate = |e: ParsingError| e.offset_by(offset).located_in_chunk_at(location);
+        let mut parser = ByteParser::from_slice(head).with_underscores_to_spaces(self.options.underscores_to_spaces);
+        self.enter_trees(&parser).map_err(locate)?;
+        let tree_head = match parse_tree_head(&mut parser) {
+            Ok(tree_head) => tree_head,
+            Err(e) => {
+                self.command = Command::Malformed;
+                return Err(locate(e));
+            }
+        };
+        self.advance(head);
+
+        let EventState::Trees { newick_parser, .. } = &mut self.state else {
+            unreachable!("State is trees");
+        };
+        let mut partial = newick_parser.begin_tree();
+        if let Some(rooted) = tree_head.rooted {
+            partial.tree_mut().set_rooted(rooted);
+        }
+        (self.callback)(TreeEvent::BeginTree { name: &tree_head.name, rooted: partial.tree().is_rooted() });
+        self.command = Command::Tree(Box::new(partial), tree_head);
+        Ok(())
+    }
+
+    /// Parses the next step of the current tree (see [NewickParser::parse_step]) and reports the vertex it completes,
+    /// and the end of the tree once its root is parsed.
+    fn parse_tree_step(&mut self, step: &[u8]) -> Result<(), ParsingError> {
+        let (offset, location) = (self.position, self.line_and_column);
+        let locate = |e: ParsingError| e.offset_by(offset).located_in_chunk_at(location);
+        let (EventState::Trees { newick_parser, declared_taxa }, Command::Tree(partial, _)) = (&mut self.state, &mut self.command) else {
+            unreachable!("Trees are parsed within TREES block");
+        };
+        let mut parser = ByteParser::from_slice(step).with_underscores_to_spaces(self.options.underscores_to_spaces);
+
+        match newick_parser.parse_step(&mut parser, partial) {
+            Ok(Some(index)) => report_vertex(partial.tree(), index, newick_parser.leaf_label_map(), &mut self.callback),
+            Ok(None) => {}
+            Err(e) => {
+                let Command::Tree(partial, tree_head) = std::mem::replace(&mut self.command, Command::Malformed) else {
+                    unreachable!("Command is TREE");
+                };
+                newick_parser.abandon_tree(*partial);
+                return Err(locate(e.in_tree(&tree_head.name)));
+            }
+        }
+        let result = if partial.is_complete() {
+            let Command::Tree(partial, tree_head) = std::mem::replace(&mut self.command, Command::Unknown) else {
+                unreachable!("Command is TREE");
+            };
+            let tree = newick_parser.end_tree(*partial);
+            finish_tree(tree, tree_head, newick_parser, *declared_taxa, &parser).map(|tree| {
+                self.num_trees += 1;
+                (self.callback)(TreeEvent::EndTree(&tree));
+                newick_parser.recycle(tree);
+            })
+        } else {
+            Ok(())
+        };
+        self.advance(&step[..parser.position()]);
+        result.map_err(locate)
+    }
+}
+
+/// Returns whether a command of the TREES block is a TREE command.
+fn is_tree_command(command: &[u8]) -> bool {
+    let mut parser = ByteParser::from_slice(command);
+    parser.skip_comment_and_whitespace().is_ok() && parser.peek_is_keyword(TREE)
+}
+
+/// Reports a parsed vertex of a tree as [TreeEvent::Leaf] or [TreeEvent::Internal].
+fn report_vertex(tree: &Tree, index: TreeIndex, labels: &LeafLabelMap, callback: &mut impl FnMut(TreeEvent<'_>)) {
+    let vertex = &tree[index];
+    let branch_length = vertex.branch_length().map(|length| *length);
+    match vertex.label_index() {
+        Some(label_index) => callback(TreeEvent::Leaf {
+            index,
+            label: labels.get_label(label_index).unwrap_or_default(),
+            label_index,
+            branch_length,
+        }),
+        None => callback(TreeEvent::Internal {
+            index,
+            children: vertex.children().unwrap_or(&[]),
+            label: vertex.node_label(),
+            branch_length,
+        }),
+    }
+}
+
+/// Reports the events of a whole parsed tree (see [TreeEvent]).
+fn report_tree_events(tree: &Tree, labels: &LeafLabelMap, callback: &mut impl FnMut(TreeEvent<'_>)) {
+    callback(TreeEvent::BeginTree { name: tree.name().map_or("", String::as_str), rooted: tree.is_rooted() });
+    for vertex in tree.post_order_iter() {
+        report_vertex(tree, vertex.index(), labels, callback);
+    }
+    callback(TreeEvent::EndTree(tree));
+}
+
+// =#========================================================================#=
+// BORROWED PARSING
+// =#========================================================================#=
+/// Parses all trees of an in-memory NEXUS string like [NexusParser::into_results] (with default options),
+/// but with labels of the TAXA block and TRANSLATE command (or verbatim labels of the trees)
+/// borrowed from the string where possible instead of copying them, see [LeafLabelMap::new_borrowed].
+///
+/// # Errors
+/// Returns a [ParsingError] if the string is not a valid NEXUS file or a tree cannot be parsed
+///
+/// # Example
+/// ```
+/// use nexus_parser::io::parser::nexus;
+///
+/// let nexus = String::from("#NEXUS\nBEGIN TAXA;\nTAXLABELS A B 'C c';\nEND;\n\
+///                           BEGIN TREES;\nTRANSLATE 1 A, 2 B, 3 'C c';\nTREE t = ((1,2),3);\nEND;\n");
+/// let (trees, labels) = nexus::parse_str_borrowed(&nexus).unwrap();
+/// assert_eq!(labels.num_borrowed(), 3);
+///
+/// // Keep labels after input is gone
+/// let labels = labels.into_owned();
+/// drop(nexus);
+/// assert_eq!(labels.get_label(2), Some("C c"));
+/// assert_eq!(trees[0].num_leaves(), 3);
+/// ```
+pub fn parse_str_borrowed(nexus: &str) -> Result<(Vec<Tree>, LeafLabelMap<Cow<'_, str>>), ParsingError> {
+    let mut parser = ByteParser::from_slice(nexus.as_bytes());
+    let mut preamble = Preamble::new();
+    while preamble.parse_command(&mut parser)? == Step::Continue {}
+    let mut newick_parser = preamble.into_newick_parser(&parser, false)?;
+
+    let tree_positions = locate_trees(&mut parser, false);
+    let mut trees = Vec::with_capacity(tree_positions.len());
+    for position in tree_positions {
+        parser.set_position(position);
+        trees.push(parse_tree_command(&mut parser, &mut newick_parser, None)?);
+    }
+
+    Ok((trees, newick_parser.into_leaf_label_map()))
+}
+
+// =#========================================================================#=
+// PREAMBLE (everything before first tree)
+// =#========================================================================#=
+/// Parsing state of a NEXUS file up to its first TREE command, fed one command at a time.
+///
+/// Labels of the TAXA block and TRANSLATE command are owned by default,
+/// or borrowed from in-memory input where possible (see [parse_str_borrowed]).
+struct Preamble<L = String> {
+    header_seen: bool,
+    block: Option<NexusBlock>,
+    /// Number of taxa declared in TAXA block
+    ntax: Option<usize>,
+    /// Labels of TAXA block
+    taxa: Option<LeafLabelMap<L>>,
+    /// Key-label pairs of TRANSLATE command in order of appearance
+    translation: Option<TranslationTable<L>>,
+    /// Blocks other than TAXA and TREES, if they are retained
+    raw_blocks: Option<Vec<RawBlock>>,
+    /// Name and start position of the current block
+    block_start: (String, usize),
+    /// Whether the first DATA or CHARACTERS block is parsed
+    parse_characters: bool,
+    /// Current DATA or CHARACTERS block, if parsed
+    characters: Option<CharactersBlock>,
+    /// Matrix of the first DATA or CHARACTERS block with a MATRIX command
+    matrix: Option<CharacterMatrix>,
+}
+
+/// Result of parsing a command of the preamble
+#[derive(Debug, PartialEq)]
+enum Step {
+    /// Command parsed, continue with next command
+    Continue,
+    /// Found first TREE command; parser is positioned at its start
+    FirstTree,
+    /// Reached end of input (of whole file, or of current command when streaming)
+    Eof,
+}
+
+impl<L: LeafLabel> Preamble<L> {
+    fn new() -> Self {
+        Self {
+            header_seen: false,
+            block: None,
+            ntax: None,
+            taxa: None,
+            translation: None,
+            raw_blocks: None,
+            block_start: (String::new(), 0),
+            parse_characters: false,
+            characters: None,
+            matrix: None,
+        }
+    }
+
+    /// Retains blocks other than TAXA and TREES as [RawBlock]s.
+    fn with_raw_blocks(mut self) -> Self {
+        self.raw_blocks = Some(Vec::new());
+        self
+    }
+
+    /// Returns whether the current block is parsed (and quotes thus delimit labels).
+    fn in_known_block(&self) -> bool {
+        matches!(self.block, None | Some(NexusBlock::Taxa) | Some(NexusBlock::Trees))
+    }
+
+    /// Returns whether the current block is the TREES block.
+    fn in_trees_block(&self) -> bool {
+        matches!(self.block, Some(NexusBlock::Trees))
+    }
+
+    /// Returns whether the current block is the TAXA or TREES block.
+    fn in_parsed_block(&self) -> bool {
+        matches!(self.block, Some(NexusBlock::Taxa) | Some(NexusBlock::Trees))
+    }
+
+    /// Parses the next command (including `#NEXUS` header if not yet seen).
+    fn parse_command<S: ByteSource>(&mut self, parser: &mut ByteParser<S>) -> Result<Step, ParsingError>
+    where
+        L: ParseLabel<S>,
+    {
+        parser.skip_comment_and_whitespace()?;
+        if !self.header_seen {
+            if !parser.consume_if_keyword(NEXUS_HEADER) {
+                return Err(ParsingError::missing_nexus_header(parser));
+            }
+            self.header_seen = true;
+            parser.skip_comment_and_whitespace()?;
+        }
+
+        if parser.is_eof() {
+            return Ok(Step::Eof);
+        }
+
+        // Block end is the same in all blocks
+        if self.block.is_some() && (parser.peek_is_keyword(END) || parser.peek_is_keyword(ENDBLOCK)) {
+            parser.consume_command(true);
+            if !self.in_parsed_block() && let Some(raw_blocks) = &mut self.raw_blocks {
+                let (name, start) = std::mem::take(&mut self.block_start);
+                raw_blocks.push(RawBlock { name, range: start..parser.position(), content: parser.slice_from(start).to_vec() });
+            }
+            if let Some(characters) = self.characters.take() {
+                self.matrix = characters.into_matrix();
+            }
+            self.block = None;
+            return Ok(Step::Continue);
+        }
+
+        match &self.block {
+            None => self.parse_block_begin(parser)?,
+            Some(NexusBlock::Taxa) => self.parse_taxa_command(parser)?,
+            Some(NexusBlock::Trees) => {
+                if parser.peek_is_keyword(TREE) {
+                    return Ok(Step::FirstTree);
+                }
+                self.parse_trees_command(parser)?;
+            }
+            Some(NexusBlock::Data | NexusBlock::Characters) if let Some(characters) = &mut self.characters => {
+                characters.parse_command(parser)?;
+            }
+            Some(_) => {
+                // Unknown or unsupported block, may contain free text (e.g. apostrophes),
+                // so skip word by word to not miss its end (but not `end` within comments)
+                parser.consume_until_any(b" \t\n\r;[", ConsumeMode::Exclusive);
+                parser.consume_if(b';');
+            }
+        }
+
+        Ok(Step::Continue)
+    }
+
+    /// Parses `BEGIN <name>;`
+    fn parse_block_begin<S: ByteSource>(&mut self, parser: &mut ByteParser<S>) -> Result<(), ParsingError> {
+        let start = parser.position();
+        if !parser.consume_if_keyword(BLOCK_BEGIN) {
+            return Err(ParsingError::invalid_formatting(parser));
+        }
+        let name = parser.parse_label(BLOCK_NAME_DELIMITERS)?;
+        parser.skip_comment_and_whitespace()?;
+        if name.is_empty() || !parser.consume_if(b';') {
+            return Err(ParsingError::invalid_block_name(parser));
+        }
+
+        self.block = Some(NexusBlock::from_name(&name));
+        self.block_start = (name, start);
+        if self.parse_characters && self.matrix.is_none()
+            && matches!(self.block, Some(NexusBlock::Data | NexusBlock::Characters)) {
+            self.characters = Some(CharactersBlock::new());
+        }
+        Ok(())
+    }
+
+    /// Parses `DIMENSIONS NTAX=<n>;` and `TAXLABELS <label> ... ;`, skips other commands
+    fn parse_taxa_command<S: ByteSource>(&mut self, parser: &mut ByteParser<S>) -> Result<(), ParsingError>
+    where
+        L: ParseLabel<S>,
+    {
+        if parser.consume_if_keyword(DIMENSIONS) {
+            parser.skip_comment_and_whitespace()?;
+            if !parser.consume_if_keyword(NTAX) {
+                return Err(ParsingError::invalid_taxa_block(parser, "Expected 'ntax' in DIMENSIONS command".to_string()));
+            }
+            parser.skip_comment_and_whitespace()?;
+            let ntax = if parser.consume_if(b'=') {
+                parser.skip_comment_and_whitespace()?;
+                parser.parse_unsigned_integer()
+            } else {
+                None
+            };
+            let Some(ntax) = ntax else {
+                return Err(ParsingError::invalid_taxa_block(parser, "Expected 'ntax=<number>' in DIMENSIONS command".to_string()));
+            };
+            self.ntax = Some(ntax);
+            parser.consume_command(true);
+        } else if parser.consume_if_keyword(TAXLABELS) {
+            let mut taxa = LeafLabelMap::with_capacity(self.ntax.unwrap_or(0));
+            loop {
+                parser.skip_comment_and_whitespace()?;
+                if parser.consume_if(b';') {
+                    break;
+                }
+                if parser.is_eof() {
+                    return Err(ParsingError::unexpected_eof(parser));
+                }
+                let label = L::parse_label(parser, NEXUS_LABEL_DELIMITERS)?;
+                if taxa.contains_label(label.borrow()) {
+                    let msg = format!("Duplicate label '{}' in TAXLABELS", label.borrow());
+                    return Err(ParsingError::invalid_taxa_block(parser, msg));
+                }
+                taxa.get_or_insert_label(label);
+            }
+
+            if let Some(ntax) = self.ntax && ntax != taxa.num_labels() {
+                return Err(ParsingError::invalid_taxa_block(
+                    parser,
+                    format!("Expected {} labels (ntax) but found {}", ntax, taxa.num_labels()),
+                ));
+            }
+            self.taxa = Some(taxa);
+        } else {
+            parser.consume_command(true);
+        }
+
+        Ok(())
+    }
+
+    /// Parses `TRANSLATE <key> <label>, ... ;` (see [TranslationTable]), skips other commands (before first tree)
+    ///
+    /// Keys and labels may be quoted (`'Wilson''s storm-petrel'`) and be surrounded
+    /// by or contain comments (`1 'Homo sapiens' [isolate 7]`), which are ignored.
+    fn parse_trees_command<S: ByteSource>(&mut self, parser: &mut ByteParser<S>) -> Result<(), ParsingError>
+    where
+        L: ParseLabel<S>,
+    {
+        if !parser.consume_if_keyword(TRANSLATE) {
+            parser.consume_command(true);
+            return Ok(());
+        }
+
+        self.translation = Some(TranslationTable::parse(parser)?);
+        Ok(())
+    }
+
+    /// Returns the number of taxa declared in the TAXA block (0 without TAXA block).
+    fn num_declared_taxa(&self) -> usize {
+        self.taxa.as_ref().map_or(0, |taxa| taxa.num_labels())
+    }
+
+    /// Creates a [NewickParser] with a [LabelResolver] matching TAXA block and TRANSLATE command:
+    /// - With TRANSLATE command using keys `1..=n`: [LabelResolver::NexusIntegerLabels]
+    /// - With other TRANSLATE command: [LabelResolver::NexusLabels]
+    /// - With only TAXA block: [LabelResolver::NexusLabels] resolving verbatim labels first,
+    ///   then 1-based indices
+    /// - Otherwise: [LabelResolver::VerbatimLabels]
+    ///
+    /// In strict mode, all labels of the TRANSLATE command must be declared in the TAXA block
+    /// and their number must match.
+    fn into_newick_parser<S: ByteSource>(self, parser: &ByteParser<S>, strict: bool)
+        -> Result<NewickParser<LabelResolver<L>>, ParsingError> {
+        if strict && let Some(translation) = &self.translation {
+            let num_declared = self.num_declared_taxa();
+            if let Some((_, label)) = translation.iter()
+                .find(|(_, label)| !self.taxa.as_ref().is_some_and(|taxa| taxa.contains_label(label))) {
+                return Err(ParsingError::undeclared_taxon(parser, label.to_string()));
+            }
+            if translation.len() != num_declared {
+                return Err(ParsingError::taxon_count_mismatch(parser, num_declared, translation.len()));
+            }
+        }
+
+        let resolver = match (self.taxa, self.translation) {
+            (taxa, Some(translation)) => {
+                let taxa = match taxa {
+                    Some(taxa) => taxa,
+                    None => {
+                        // Labels defined by TRANSLATE command only
+                        let mut taxa = LeafLabelMap::with_capacity(translation.len());
+                        for (_, label) in translation.entries() {
+                            taxa.get_or_insert_label(label.clone());
+                        }
+                        taxa
+                    }
+                };
+
+                let is_integer_keyed = translation.is_integer_keyed_up_to(taxa.num_labels());
+                let translation: HashMap<L, L> = translation.into_entries().into_iter().collect();
+                if !taxa.check_consistency_with_translation(&translation) {
+                    return Err(ParsingError::invalid_translate_command(parser));
+                }
+
+                if is_integer_keyed {
+                    LabelResolver::new_nexus_integer_labels_resolver(translation, taxa)
+                } else {
+                    LabelResolver::new_nexus_labels_resolver(translation, taxa)
+                }
+            }
+            (Some(taxa), None) => {
+                let identity = taxa.labels().iter()
+                    .map(|label| (label.clone(), label.clone()))
+                    .collect();
+                LabelResolver::new_nexus_labels_resolver(identity, taxa)
+            }
+            (None, None) => LabelResolver::new_verbatim_labels_resolver(LeafLabelMap::with_capacity(0)),
+        };
+
+        Ok(NewickParser::new_with_resolver(resolver))
+    }
+}
+
+// =#========================================================================#=
+// HELPERS
+// =#========================================================================#=
+/// Locates the start positions of all TREE commands of the TREES block,
+/// starting at the first one, and stops at the end of the block.
+///
+/// If `lenient`, a command also ends before a line starting with `tree`, `end` or `endblock`,
+/// so a TREE command missing its `;` (or with an unclosed quote or comment) does not swallow the next ones.
+fn locate_trees<S: ByteSource>(parser: &mut ByteParser<S>, lenient: bool) -> Vec<usize> {
+    let mut positions = Vec::new();
+    loop {
+        if parser.skip_comment_and_whitespace().is_err() || parser.is_eof() {
+            break;
+        }
+        if parser.peek_is_keyword(END) || parser.peek_is_keyword(ENDBLOCK) {
+            break;
+        }
+        let start = parser.position();
+        if parser.peek_is_keyword(TREE) {
+            positions.push(start);
+        }
+        let terminated = parser.consume_command(true);
+        if lenient && let Some(next) = find_command_at_line_start(parser, start) {
+            parser.set_position(next);
+            continue;
+        }
+        if !terminated {
+            break;
+        }
+    }
+    positions
+}
+
+/// Returns the position of the first line after `start` (up to the current position)
+/// that starts with a TREE, END or ENDBLOCK command, if any; keeps the current position otherwise.
+fn find_command_at_line_start<S: ByteSource>(parser: &mut ByteParser<S>, start: usize) -> Option<usize> {
+    let end = parser.position();
+    let line_starts: Vec<usize> = parser.slice_from(start).iter()
+        .enumerate()
+        .filter(|&(_, &b)| b == b'\n')
+        .map(|(i, _)| start + i + 1)
+        .collect();
+
+    for line_start in line_starts {
+        parser.set_position(line_start);
+        parser.skip_whitespace();
+        if parser.position() < end
+            && (parser.peek_is_keyword(TREE) || parser.peek_is_keyword(END) || parser.peek_is_keyword(ENDBLOCK)) {
+            return Some(parser.position());
+        }
+    }
+    parser.set_position(end);
+    None
+}
+
+/// Scans the blocks after the TREES block (with the parser positioned at its end) and returns them
+/// as [RawBlock]s, except further TAXA and TREES blocks. Stops at content that cannot be parsed.
+fn scan_trailing_blocks<S: ByteSource>(parser: &mut ByteParser<S>) -> Vec<RawBlock> {
+    let mut preamble: Preamble = Preamble::new().with_raw_blocks();
+    preamble.header_seen = true;
+    preamble.block = Some(NexusBlock::Trees);
+    loop {
+        match preamble.parse_command(parser) {
+            Ok(Step::Continue) => {}
+            // TREE command of a further TREES block
+            Ok(Step::FirstTree) => {
+                parser.consume_command(true);
+            }
+            Ok(Step::Eof) | Err(_) => break,
+        }
+    }
+    preamble.raw_blocks.unwrap_or_default()
+}
+
+/// Returns the name of the TREE command at the given position, if it can be parsed.
+fn parse_tree_name_at<S: ByteSource>(parser: &mut ByteParser<S>, position: usize) -> Option<String> {
+    parser.set_position(position);
+    parser.consume_if_keyword(TREE);
+    parser.skip_comment_and_whitespace().ok()?;
+    parser.consume_if(b'*');
+    parser.skip_comment_and_whitespace().ok()?;
+    parser.parse_label(TREE_NAME_DELIMITERS).ok().filter(|name| !name.is_empty())
+}
+
+/// Head of a TREE command up to its Newick string, see [parse_tree_head]
+struct TreeHead {
+    name: String,
+    /// Whether the tree is rooted, if set by a rooting comment
+    rooted: Option<bool>,
+    /// Other comments before the Newick string
+    metadata: Vec<String>,
+}
+
+/// Parses `TREE [*] <name> = <newick>;` and returns the tree named accordingly.
+///
+/// Comments after the name and before the Newick string (e.g. `[&lnP=-1234.5]` of BEAST or MrBayes)
+/// are kept as [metadata](Tree::metadata) of the tree (without leading `&`, parsed if annotations are parsed),
+/// except rooting comments `[&R]` and `[&U]`, which set whether the tree is rooted.
+///
+/// If `declared_taxa` is given (strict mode), checks that the tree has exactly these taxa, see [check_declared_taxa].
+fn parse_tree_command<S: ByteSource, L: LeafLabel>(parser: &mut ByteParser<S>, newick_parser: &mut NewickParser<LabelResolver<L>>,
+                                                   declared_taxa: Option<usize>) -> Result<Tree, ParsingError>
+where
+    LabelResolver<L>: ResolveLeafLabel<S>,
+{
+    let head = parse_tree_head(parser)?;
+    let tree = newick_parser.parse(parser).map_err(|e| e.in_tree(&head.name))?;
+    finish_tree(tree, head, newick_parser, declared_taxa, parser)
+}
+
+/// Parses `TREE [*] <name> = ` up to the Newick string of a TREE command, see [parse_tree_command].
+fn parse_tree_head<S: ByteSource>(parser: &mut ByteParser<S>) -> Result<TreeHead, ParsingError> {
+    parser.skip_comment_and_whitespace()?;
+    if !parser.consume_if_keyword(TREE) {
+        return Err(ParsingError::invalid_trees_block(parser, "Expected TREE command".to_string()));
+    }
+    parser.skip_comment_and_whitespace()?;
+    // Optional default tree marker (e.g. PAUP*)
+    if parser.consume_if(b'*') {
+        parser.skip_comment_and_whitespace()?;
+    }
+
+    let name = parser.parse_label(TREE_NAME_DELIMITERS)?;
+    let mut head = TreeHead { name, rooted: None, metadata: Vec::new() };
+    collect_tree_comments(parser, &mut head.rooted, &mut head.metadata).map_err(|e| e.in_tree(&head.name))?;
+    if !parser.consume_if(b'=') {
+        let message = format!("Expected '=' after tree name '{}'", head.name);
+        return Err(ParsingError::invalid_trees_block(parser, message).in_tree(&head.name));
+    }
+    collect_tree_comments(parser, &mut head.rooted, &mut head.metadata).map_err(|e| e.in_tree(&head.name))?;
+    Ok(head)
+}
+
+/// Finishes a tree parsed after the head of its TREE command: checks its taxa if `declared_taxa` is given
+/// (see [check_declared_taxa]), then sets name, rooting and metadata of the head.
+fn finish_tree<S: ByteSource, L: LeafLabel>(mut tree: Tree, head: TreeHead, newick_parser: &NewickParser<LabelResolver<L>>,
+                                            declared_taxa: Option<usize>, parser: &ByteParser<S>) -> Result<Tree, ParsingError> {
+    if let Some(num_taxa) = declared_taxa {
+        check_declared_taxa(&tree, newick_parser.leaf_label_map(), num_taxa, parser).map_err(|e| e.in_tree(&head.name))?;
+    }
+    if let Some(rooted) = head.rooted {
+        tree = tree.with_rooted(rooted);
+    }
+    if !head.metadata.is_empty() {
+        tree = tree.with_metadata(newick_parser.to_annotation(head.metadata));
+    }
+    Ok(tree.with_name(head.name))
+}
+
+/// Skips whitespace and comments of a TREE command before its Newick string,
+/// recording rooting comments `[&R]`/`[&U]` in `rooted` and collecting others (without leading `&`) in `metadata`.
+fn collect_tree_comments<S: ByteSource>(parser: &mut ByteParser<S>, rooted: &mut Option<bool>,
+                                        metadata: &mut Vec<String>) -> Result<(), ParsingError> {
+    parser.skip_whitespace();
+    while let Some(comment) = parser.parse_comment()? {
+        match comment.trim().to_ascii_uppercase().as_str() {
+            "&R" => *rooted = Some(true),
+            "&U" => *rooted = Some(false),
+            "" => {}
+            _ => {
+                let comment = comment.trim();
+                metadata.push(comment.strip_prefix('&').unwrap_or(comment).to_string());
+            }
+        }
+        parser.skip_whitespace();
+    }
+    Ok(())
+}
+
+/// Checks that the leaves of the tree are exactly the `num_taxa` taxa declared in the TAXA block
+/// (the first `num_taxa` labels of `leaf_label_map`), each appearing once.
+fn check_declared_taxa<S: ByteSource, L: LeafLabel>(tree: &Tree, leaf_label_map: &LeafLabelMap<L>, num_taxa: usize,
+                                      parser: &ByteParser<S>) -> Result<(), ParsingError> {
+    let mut seen = vec![false; num_taxa];
+    for label_index in tree.post_order_iter().filter_map(|vertex| vertex.label_index()) {
+        let label = leaf_label_map.get_label(label_index).unwrap_or_default();
+        if label_index >= num_taxa {
+            return Err(ParsingError::undeclared_taxon(parser, label.to_string()));
+        }
+        if seen[label_index] {
+            return Err(ParsingError::duplicate_taxon(parser, label.to_string()));
+        }
+        seen[label_index] = true;
+    }
+
+    let found = seen.iter().filter(|&&seen| seen).count();
+    if found != num_taxa {
+        return Err(ParsingError::taxon_count_mismatch(parser, num_taxa, found));
+    }
+    Ok(())
+}
+
+/// Reads the next command, up to and including its terminating `;`, from a [BufRead] source
+/// (see [ByteParser::consume_command] for handling of comments and quotes).
+///
+/// # Returns
+/// `false` if EOF was reached without reading anything, otherwise `true`
+/// (the command may lack the terminating `;` at EOF)
+fn read_command<R: BufRead>(reader: &mut R, quote_aware: bool, command: &mut Vec<u8>) -> io::Result<bool> {
+    let mut scanner = CommandScanner::default();
+
+    loop {
+        let buffer = reader.fill_buf()?;
+        if buffer.is_empty() {
+            return Ok(!command.is_empty());
+        }
+
+        let end = scanner.scan(buffer, quote_aware);
+        let len = end.unwrap_or(buffer.len());
+        command.extend_from_slice(&buffer[..len]);
+        reader.consume(len);
+        if end.is_some() {
+            return Ok(true);
+        }
+    }
+}
+
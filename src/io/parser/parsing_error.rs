@@ -1,7 +1,8 @@
-use crate::parser::byte_parser::ByteParser;
-use crate::parser::byte_source::ByteSource;
+use crate::io::parser::byte_parser::ByteParser;
+use crate::io::parser::byte_source::ByteSource;
 use std::error::Error;
 use std::fmt;
+use std::io;
 
 /// Default length of context provided by error from parser
 const DEFAULT_CONTEXT_LENGTH: usize = 50;
@@ -18,22 +19,40 @@ pub enum ParsingErrorType {
     InvalidBlockName,
     InvalidTaxaBlock(String),
     InvalidTreesBlock(String),
+    InvalidCharactersBlock(String),
     InvalidTranslateCommand,
+    InvalidTranslateEntry { key: String, msg: String },
     UnclosedComment,
+    UnclosedQuote,
     InvalidNewickString(String),
     InvalidFormatting,
     UnresolvedLabel(String),
+    DuplicateTaxon(String),
+    UndeclaredTaxon(String),
+    ReticulateNetwork(String),
+    TaxonCountMismatch { expected: usize, found: usize },
+    InvalidXml(String),
+    InvalidPhyloXml(String),
+    InvalidNeXml(String),
+    InvalidFasta(String),
+    InvalidPhylip(String),
+    InvalidTrace(String),
+    IoError(String),
 }
 
 
 // =#========================================================================#=
 // PARSING ERROR
 // =#========================================================================#=
-/// Parsing error with contextual information (position and surrounding bytes)
+/// Parsing error with contextual information (position, line and column, tree and surrounding bytes)
 #[derive(Debug)]
 pub struct ParsingError {
     kind: ParsingErrorType,
     position: usize,
+    /// Line and column (both starting at 1) of the position, if known
+    line_and_column: Option<(usize, usize)>,
+    /// Name of the tree whose TREE command failed, if any
+    tree_name: Option<Box<str>>,
     context: String,
 }
 
@@ -43,6 +62,8 @@ impl ParsingError {
         Self {
             kind,
             position: parser.position(),
+            line_and_column: parser.line_and_column(),
+            tree_name: None,
             context: parser.get_context_as_string(DEFAULT_CONTEXT_LENGTH),
         }
     }
@@ -72,16 +93,31 @@ impl ParsingError {
         Self::from_parser(ParsingErrorType::InvalidTreesBlock(msg), parser)
     }
 
+    /// Convenience constructor for InvalidCharactersBlock
+    pub fn invalid_characters_block<S: ByteSource>(parser: &ByteParser<S>, msg: String) -> Self {
+        Self::from_parser(ParsingErrorType::InvalidCharactersBlock(msg), parser)
+    }
+
     /// Convenience constructor for InvalidTranslateCommand
     pub fn invalid_translate_command<S: ByteSource>(parser: &ByteParser<S>) -> Self {
         Self::from_parser(ParsingErrorType::InvalidTranslateCommand, parser)
     }
 
+    /// Convenience constructor for InvalidTranslateEntry
+    pub fn invalid_translate_entry<S: ByteSource>(parser: &ByteParser<S>, key: String, msg: String) -> Self {
+        Self::from_parser(ParsingErrorType::InvalidTranslateEntry { key, msg }, parser)
+    }
+
     /// Convenience constructor for UnclosedComment
     pub fn unclosed_comment<S: ByteSource>(parser: &ByteParser<S>) -> Self {
         Self::from_parser(ParsingErrorType::UnclosedComment, parser)
     }
 
+    /// Convenience constructor for UnclosedQuote
+    pub fn unclosed_quote<S: ByteSource>(parser: &ByteParser<S>) -> Self {
+        Self::from_parser(ParsingErrorType::UnclosedQuote, parser)
+    }
+
     /// Convenience constructor for InvalidNewickString
     pub fn invalid_newick_string<S: ByteSource>(parser: &ByteParser<S>, msg: String) -> Self {
         Self::from_parser(ParsingErrorType::InvalidNewickString(msg), parser)
@@ -97,6 +133,90 @@ impl ParsingError {
         Self::from_parser(ParsingErrorType::UnresolvedLabel(msg), parser)
     }
 
+    /// Convenience constructor for DuplicateTaxon
+    pub fn duplicate_taxon<S: ByteSource>(parser: &ByteParser<S>, label: String) -> Self {
+        Self::from_parser(ParsingErrorType::DuplicateTaxon(label), parser)
+    }
+
+    /// Convenience constructor for UndeclaredTaxon
+    pub fn undeclared_taxon<S: ByteSource>(parser: &ByteParser<S>, label: String) -> Self {
+        Self::from_parser(ParsingErrorType::UndeclaredTaxon(label), parser)
+    }
+
+    /// Convenience constructor for ReticulateNetwork, a reticulation tag (e.g. `#H1`) found while parsing a tree
+    pub fn reticulate_network<S: ByteSource>(parser: &ByteParser<S>, label: String) -> Self {
+        Self::from_parser(ParsingErrorType::ReticulateNetwork(label), parser)
+    }
+
+    /// Convenience constructor for TaxonCountMismatch
+    pub fn taxon_count_mismatch<S: ByteSource>(parser: &ByteParser<S>, expected: usize, found: usize) -> Self {
+        Self::from_parser(ParsingErrorType::TaxonCountMismatch { expected, found }, parser)
+    }
+
+    /// Convenience constructor for InvalidXml
+    pub fn invalid_xml<S: ByteSource>(parser: &ByteParser<S>, msg: String) -> Self {
+        Self::from_parser(ParsingErrorType::InvalidXml(msg), parser)
+    }
+
+    /// Convenience constructor for InvalidPhyloXml
+    pub fn invalid_phyloxml<S: ByteSource>(parser: &ByteParser<S>, msg: String) -> Self {
+        Self::from_parser(ParsingErrorType::InvalidPhyloXml(msg), parser)
+    }
+
+    /// Convenience constructor for InvalidNeXml
+    pub fn invalid_nexml<S: ByteSource>(parser: &ByteParser<S>, msg: String) -> Self {
+        Self::from_parser(ParsingErrorType::InvalidNeXml(msg), parser)
+    }
+
+    /// Convenience constructor for InvalidFasta
+    pub fn invalid_fasta<S: ByteSource>(parser: &ByteParser<S>, msg: String) -> Self {
+        Self::from_parser(ParsingErrorType::InvalidFasta(msg), parser)
+    }
+
+    /// Convenience constructor for InvalidPhylip
+    pub fn invalid_phylip<S: ByteSource>(parser: &ByteParser<S>, msg: String) -> Self {
+        Self::from_parser(ParsingErrorType::InvalidPhylip(msg), parser)
+    }
+
+    /// Convenience constructor for InvalidTrace
+    pub fn invalid_trace<S: ByteSource>(parser: &ByteParser<S>, msg: String) -> Self {
+        Self::from_parser(ParsingErrorType::InvalidTrace(msg), parser)
+    }
+
+    /// Create a ParsingError from an I/O error while reading (streamed) input at the given position
+    pub fn io_error(error: &io::Error, position: usize) -> Self {
+        Self {
+            kind: ParsingErrorType::IoError(error.to_string()),
+            position,
+            line_and_column: None,
+            tree_name: None,
+            context: String::new(),
+        }
+    }
+
+    /// Shifts the position of this error by `offset`,
+    /// e.g. when a chunk of streamed input was parsed on its own
+    pub(crate) fn offset_by(mut self, offset: usize) -> Self {
+        self.position += offset;
+        self
+    }
+
+    /// Shifts the line and column of this error, relative to a chunk of streamed input,
+    /// to the whole input, given the line and column at which the chunk starts
+    pub(crate) fn located_in_chunk_at(mut self, (line, column): (usize, usize)) -> Self {
+        self.line_and_column = self.line_and_column.map(|(chunk_line, chunk_column)| match chunk_line {
+            1 => (line, column + chunk_column - 1),
+            _ => (line + chunk_line - 1, chunk_column),
+        });
+        self
+    }
+
+    /// Sets the name of the tree in whose TREE command this error occurred
+    pub(crate) fn in_tree(mut self, name: &str) -> Self {
+        self.tree_name = Some(name.into());
+        self
+    }
+
     /// Get the error kind
     pub fn kind(&self) -> &ParsingErrorType {
         &self.kind
@@ -106,6 +226,21 @@ impl ParsingError {
     pub fn position(&self) -> usize {
         self.position
     }
+
+    /// Get the line (starting at 1) where the error occurred, if known
+    pub fn line(&self) -> Option<usize> {
+        self.line_and_column.map(|(line, _)| line)
+    }
+
+    /// Get the column (starting at 1, counted in bytes) where the error occurred, if known
+    pub fn column(&self) -> Option<usize> {
+        self.line_and_column.map(|(_, column)| column)
+    }
+
+    /// Get the name of the tree in which the error occurred, if any
+    pub fn tree_name(&self) -> Option<&str> {
+        self.tree_name.as_deref()
+    }
 }
 
 impl fmt::Display for ParsingError {
@@ -115,17 +250,37 @@ impl fmt::Display for ParsingError {
             ParsingErrorType::MissingNexusHeader => write!(f, "File does not start with #NEXUS header")?,
             ParsingErrorType::InvalidTaxaBlock(msg) => write!(f, "Invalid TAXA block format - {msg}")?,
             ParsingErrorType::InvalidTreesBlock(msg) => write!(f, "Invalid TREES block format - {msg}")?,
+            ParsingErrorType::InvalidCharactersBlock(msg) => write!(f, "Invalid DATA/CHARACTERS block format - {msg}")?,
             ParsingErrorType::InvalidTranslateCommand => write!(f, "Invalid TRANSLATE command - likely inconsistent with TAXA block")?,
+            ParsingErrorType::InvalidTranslateEntry { key, msg } => write!(f, "Invalid TRANSLATE entry with key '{key}' - {msg}")?,
             ParsingErrorType::UnclosedComment => write!(f, "Unclosed comment")?,
+            ParsingErrorType::UnclosedQuote => write!(f, "Unclosed quoted label")?,
             ParsingErrorType::InvalidBlockName => write!(f, "Invalid block name")?,
             ParsingErrorType::InvalidNewickString(msg) => write!(f, "Invalid newick string: {}", msg)?,
             ParsingErrorType::UnexpectedEOF => write!(f, "Unexpected end of file")?,
             ParsingErrorType::InvalidFormatting => write!(f, "Invalid formatting")?,
             ParsingErrorType::UnresolvedLabel(msg) => write!(f, "Could not resolve label - {msg}")?,
+            ParsingErrorType::DuplicateTaxon(label) => write!(f, "Taxon '{label}' appears more than once in tree")?,
+            ParsingErrorType::UndeclaredTaxon(label) => write!(f, "Taxon '{label}' is not declared in TAXA block")?,
+            ParsingErrorType::ReticulateNetwork(label) => write!(f, "Label '{label}' has a reticulation tag of a phylogenetic network (extended Newick), parse it with NewickParser::parse_network")?,
+            ParsingErrorType::TaxonCountMismatch { expected, found } => write!(f, "Expected {expected} taxa as declared in TAXA block but found {found}")?,
+            ParsingErrorType::InvalidXml(msg) => write!(f, "Invalid XML - {msg}")?,
+            ParsingErrorType::InvalidPhyloXml(msg) => write!(f, "Invalid phyloXML - {msg}")?,
+            ParsingErrorType::InvalidNeXml(msg) => write!(f, "Invalid NeXML - {msg}")?,
+            ParsingErrorType::InvalidFasta(msg) => write!(f, "Invalid FASTA - {msg}")?,
+            ParsingErrorType::InvalidPhylip(msg) => write!(f, "Invalid PHYLIP - {msg}")?,
+            ParsingErrorType::InvalidTrace(msg) => write!(f, "Invalid parameter trace - {msg}")?,
+            ParsingErrorType::IoError(msg) => write!(f, "Could not read input - {msg}")?,
         }
 
         // Additional position information
-        write!(f, " at position {}", self.position)?;
+        match self.line_and_column {
+            Some((line, column)) => write!(f, " at line {}, column {} (position {})", line, column, self.position)?,
+            None => write!(f, " at position {}", self.position)?,
+        }
+        if let Some(name) = &self.tree_name {
+            write!(f, " in tree '{}'", name)?;
+        }
 
         // Additional context if available
         if !self.context.is_empty() {
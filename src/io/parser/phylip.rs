@@ -0,0 +1,206 @@
+//! PHYLIP alignment file parser.
+//!
+//! - `PhylipOptions`: Strict or relaxed names, interleaved or sequential layout
+//! - `parse_file`, `parse_str`, `parse_bytes`: Parse a PHYLIP file into an [Alignment]
+//!
+//! A PHYLIP file starts with the number of taxa and of characters (`4 12`), followed by the sequences.
+//! Names are either the first 10 characters of a line, padded with spaces (strict, as by PHYLIP itself;
+//! not starting with a space), or the first word (relaxed, as by RAxML and IQ-TREE). They are resolved through a [LeafLabelMap]
+//! (e.g. of trees parsed before), so that the alignment can be joined with trees by [LabelIndex](crate::model::tree::LabelIndex).
+//!
+//! In interleaved files, the first block holds every name with the first chunk of its sequence
+//! and further blocks (possibly separated by blank lines) continue the sequences in the same order without names.
+//! In sequential files, each name is followed by its whole sequence, which may span several lines.
+//! Files with one line per sequence can be read either way. Whitespace within sequences is skipped.
+
+use crate::io::parser::byte_parser::ByteParser;
+use crate::io::parser::byte_source::{ByteSource, InMemoryByteSource};
+use crate::io::parser::parsing_error::ParsingError;
+use crate::model::alignment::{Alignment, DataType};
+use crate::model::leaf_label_map::LeafLabelMap;
+use std::collections::HashSet;
+#[cfg(feature = "fs")]
+use std::fs::File;
+#[cfg(feature = "fs")]
+use std::io::Read;
+
+/// Length of (padded) names in strict PHYLIP files
+const STRICT_NAME_LENGTH: usize = 10;
+
+// =#========================================================================#=
+// PHYLIP OPTIONS
+// =#========================================================================#=
+/// Variant of the PHYLIP format to parse (default: relaxed names, interleaved).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PhylipOptions {
+    /// Names are exactly the first 10 characters of a line (trailing spaces removed),
+    /// and may thus contain spaces or be directly followed by the sequence
+    pub strict_names: bool,
+    /// Each sequence follows its name completely (possibly over several lines) instead of interleaved blocks
+    pub sequential: bool,
+}
+
+// =#========================================================================#=
+// PHYLIP FILE PARSING
+// =#========================================================================#=
+/// Parses a PHYLIP file into an [Alignment] over the given label map.
+///
+/// # Arguments
+/// * `file` - PHYLIP file
+/// * `data_type` - Data type of the sequences (nucleotides and amino acids are stored upper case)
+/// * `labels` - Label map resolving the sequence names; names not yet in it are added
+/// * `options` - Variant of the format (see [PhylipOptions])
+///
+/// # Errors
+/// Returns a [ParsingError] if the file cannot be read, its header is missing or malformed,
+/// a name is missing or repeated, or a sequence does not have the declared number of characters
+#[cfg(feature = "fs")]
+pub fn parse_file(mut file: File, data_type: DataType, labels: &mut LeafLabelMap, options: PhylipOptions)
+                  -> Result<Alignment, ParsingError> {
+    let mut bytes = Vec::new();
+    file.read_to_end(&mut bytes).map_err(|e| ParsingError::io_error(&e, 0))?;
+    parse_all(&mut ByteParser::new(InMemoryByteSource::from_vec(bytes)), data_type, labels, options)
+}
+
+/// Parses a PHYLIP string, see [parse_file].
+///
+/// # Errors
+/// Returns a [ParsingError] if the content is invalid, see [parse_file]
+///
+/// # Example
+/// ```
+/// use nexus_parser::io::parser::phylip::{self, PhylipOptions};
+/// use nexus_parser::model::alignment::DataType;
+/// use nexus_parser::model::leaf_label_map::LeafLabelMap;
+///
+/// let phylip = " 3 8\nHomo_sapiens ACGT\nPan        ACGA\nGorilla    AC-A\n\nTTGA\nTTGA\nTTCA\n";
+/// let mut labels = LeafLabelMap::new(3);
+/// let alignment = phylip::parse_str(phylip, DataType::Dna, &mut labels, PhylipOptions::default()).unwrap();
+///
+/// assert_eq!(alignment.num_sites(), 8);
+/// assert_eq!(alignment.sequence(labels.get_index("Gorilla").unwrap()), Some(&b"AC-ATTCA"[..]));
+///
+/// let strict = PhylipOptions { strict_names: true, sequential: true };
+/// let alignment = phylip::parse_str("2 4\nHomo sapi ACGT\nPan       AC\nGA\n", DataType::Dna, &mut labels, strict).unwrap();
+/// assert_eq!(alignment.sequence(labels.get_index("Homo sapi").unwrap()), Some(&b"ACGT"[..]));
+/// ```
+pub fn parse_str(phylip: &str, data_type: DataType, labels: &mut LeafLabelMap, options: PhylipOptions)
+                 -> Result<Alignment, ParsingError> {
+    parse_bytes(phylip.as_bytes(), data_type, labels, options)
+}
+
+/// Parses PHYLIP bytes, see [parse_file].
+///
+/// # Errors
+/// Returns a [ParsingError] if the content is invalid, see [parse_file]
+pub fn parse_bytes<B: AsRef<[u8]>>(bytes: B, data_type: DataType, labels: &mut LeafLabelMap, options: PhylipOptions)
+                                   -> Result<Alignment, ParsingError> {
+    parse_all(&mut ByteParser::new(InMemoryByteSource::new(bytes)), data_type, labels, options)
+}
+
+/// Parses the header and all sequences, and resolves their names once all are checked.
+fn parse_all<S: ByteSource>(parser: &mut ByteParser<S>, data_type: DataType, labels: &mut LeafLabelMap, options: PhylipOptions)
+                            -> Result<Alignment, ParsingError> {
+    let (ntax, nchar) = parse_header(parser)?;
+
+    let mut rows: Vec<(String, Vec<u8>)> = Vec::with_capacity(ntax);
+    let mut names: HashSet<String> = HashSet::with_capacity(ntax);
+    for _ in 0..ntax {
+        parser.skip_whitespace();
+        let name = parse_name(parser, options.strict_names)?;
+        if !names.insert(name.clone()) {
+            return Err(ParsingError::invalid_phylip(parser, format!("Duplicate sequence '{}'", name)));
+        }
+
+        let mut sequence = Vec::with_capacity(nchar);
+        let limit = options.sequential.then_some(nchar);
+        parse_states(parser, &mut sequence, limit, data_type);
+        check_length(parser, &name, &sequence, nchar, options.sequential)?;
+        rows.push((name, sequence));
+    }
+
+    // Further blocks of interleaved files
+    while !options.sequential && rows.iter().any(|(_, sequence)| sequence.len() < nchar) {
+        for (name, sequence) in &mut rows {
+            parser.skip_whitespace();
+            parse_states(parser, sequence, None, data_type);
+            check_length(parser, name, sequence, nchar, parser.is_eof())?;
+        }
+    }
+
+    Ok(Alignment::from_named_sequences(data_type, rows, labels).expect("Sequences have distinct names and the same length"))
+}
+
+// ============================================================================
+// Little Helpers
+// ============================================================================
+/// Parses the number of taxa and characters of the first line, ignoring the rest of the line (e.g. options).
+fn parse_header<S: ByteSource>(parser: &mut ByteParser<S>) -> Result<(usize, usize), ParsingError> {
+    parser.skip_whitespace();
+    let ntax = parser.parse_unsigned_integer();
+    skip_spaces(parser);
+    let nchar = parser.parse_unsigned_integer();
+    let (Some(ntax), Some(nchar)) = (ntax, nchar) else {
+        return Err(ParsingError::invalid_phylip(parser, "Expected number of taxa and characters in first line".to_string()));
+    };
+    while parser.peek().is_some_and(|b| b != b'\n') {
+        parser.next();
+    }
+    Ok((ntax, nchar))
+}
+
+/// Parses a name (at the start of a line, or after the previous sequence in sequential files):
+/// the next 10 characters of the line (strict) or the next word (relaxed).
+fn parse_name<S: ByteSource>(parser: &mut ByteParser<S>, strict: bool) -> Result<String, ParsingError> {
+    let start = parser.position();
+    if strict {
+        for _ in 0..STRICT_NAME_LENGTH {
+            if parser.peek().is_none_or(|b| b == b'\n' || b == b'\r') {
+                break;
+            }
+            parser.next();
+        }
+    } else {
+        while parser.peek().is_some_and(|b| !b.is_ascii_whitespace()) {
+            parser.next();
+        }
+    }
+
+    let name = String::from_utf8_lossy(parser.slice_from(start)).trim().to_string();
+    if name.is_empty() {
+        let msg = if parser.is_eof() { "Expected more sequences" } else { "Missing sequence name" };
+        return Err(ParsingError::invalid_phylip(parser, msg.to_string()));
+    }
+    Ok(name)
+}
+
+/// Parses states into `sequence`, skipping whitespace, up to `limit` states
+/// or (without limit) the end of the line.
+fn parse_states<S: ByteSource>(parser: &mut ByteParser<S>, sequence: &mut Vec<u8>, limit: Option<usize>, data_type: DataType) {
+    while limit.is_none_or(|limit| sequence.len() < limit) {
+        match parser.peek() {
+            None => break,
+            Some(b'\n') if limit.is_none() => break,
+            Some(b) if b.is_ascii_whitespace() => {}
+            Some(b) => sequence.push(data_type.normalize(b)),
+        }
+        parser.next();
+    }
+}
+
+/// Checks that the sequence has at most (if `complete`, exactly) the declared number of characters.
+fn check_length<S: ByteSource>(parser: &ByteParser<S>, name: &str, sequence: &[u8], nchar: usize, complete: bool)
+                               -> Result<(), ParsingError> {
+    if sequence.len() > nchar || (complete && sequence.len() < nchar) {
+        let msg = format!("Sequence '{}' has {} characters, expected {}", name, sequence.len(), nchar);
+        return Err(ParsingError::invalid_phylip(parser, msg));
+    }
+    Ok(())
+}
+
+/// Skips spaces and tabs (but not line breaks).
+fn skip_spaces<S: ByteSource>(parser: &mut ByteParser<S>) {
+    while parser.peek().is_some_and(|b| b == b' ' || b == b'\t') {
+        parser.next();
+    }
+}
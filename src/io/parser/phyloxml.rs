@@ -0,0 +1,232 @@
+//! phyloXML tree file parser.
+//!
+//! - `parse_file`, `parse_str`, `parse_bytes`: Parse all phylogenies of a phyloXML document
+//!
+//! phyloXML (<http://www.phyloxml.org>) nests `<clade>` elements to describe a tree.
+//! Of each clade, the `<name>`, `<branch_length>` (element or attribute) and the first `<confidence>`
+//! are read; other elements (e.g. taxonomy, sequences, events) are skipped.
+//! Leaf clades are mapped onto a shared [LeafLabelMap] by their names.
+//! Internal clades get their confidence (e.g. bootstrap support) as node label,
+//! or their name if they have no confidence.
+
+use crate::io::parser::byte_parser::ByteParser;
+use crate::io::parser::byte_source::{ByteSource, InMemoryByteSource};
+use crate::io::parser::parsing_error::ParsingError;
+use crate::io::parser::xml::{XmlEvent, XmlReader};
+use crate::model::leaf_label_map::LeafLabelMap;
+use crate::model::tree::{Tree, TreeIndex};
+use crate::model::vertex::BranchLength;
+#[cfg(feature = "fs")]
+use std::fs::File;
+#[cfg(feature = "fs")]
+use std::io::Read;
+
+/// Root element of a phyloXML document
+pub(crate) const PHYLOXML: &str = "phyloxml";
+/// Element of a single tree
+const PHYLOGENY: &str = "phylogeny";
+/// Element of a vertex (and the subtree below it)
+const CLADE: &str = "clade";
+/// Element (and attribute of clades) with the length of the branch above a clade
+const BRANCH_LENGTH: &str = "branch_length";
+/// Element with a support value of a clade
+const CONFIDENCE: &str = "confidence";
+/// Element with the name of a phylogeny or clade
+const NAME: &str = "name";
+/// Attribute of phylogenies whether they are rooted
+const ROOTED: &str = "rooted";
+
+/// Default guess for number of leaves, when unknown
+const DEFAULT_NUM_LEAVES_GUESS: usize = 10;
+
+// =#========================================================================#=
+// PHYLOXML FILE PARSING
+// =#========================================================================#=
+/// Parses all phylogenies of a phyloXML file into trees.
+///
+/// Trees share a single [LeafLabelMap] built from the names of their leaf clades;
+/// tree names and whether they are rooted are taken from the phylogenies.
+///
+/// # Returns
+/// A tuple of (trees, label_map) with all trees in order of appearance
+///
+/// # Errors
+/// Returns a [ParsingError] if the file cannot be read, is not well-formed XML,
+/// or a phylogeny is invalid (e.g. a leaf clade without name)
+#[cfg(feature = "fs")]
+pub fn parse_file(mut file: File) -> Result<(Vec<Tree>, LeafLabelMap), ParsingError> {
+    let mut bytes = Vec::new();
+    file.read_to_end(&mut bytes).map_err(|e| ParsingError::io_error(&e, 0))?;
+    parse_all(&mut ByteParser::new(InMemoryByteSource::from_vec(bytes)))
+}
+
+/// Parses all phylogenies of a phyloXML string, see [parse_file].
+///
+/// # Errors
+/// Returns a [ParsingError] if the document or a phylogeny is invalid
+///
+/// # Example
+/// ```
+/// use nexus_parser::io::parser::phyloxml;
+///
+/// let xml = r#"<phyloxml><phylogeny rooted="true"><name>example</name>
+///   <clade>
+///     <clade><confidence type="bootstrap">89</confidence>
+///       <clade branch_length="0.1"><name>A</name></clade>
+///       <clade><name>B</name><branch_length>0.2</branch_length></clade>
+///     </clade>
+///     <clade><name>C</name></clade>
+///   </clade>
+/// </phylogeny></phyloxml>"#;
+///
+/// let (trees, labels) = phyloxml::parse_str(xml).unwrap();
+/// assert_eq!(trees[0].name().unwrap(), "example");
+/// assert_eq!(labels.num_labels(), 3);
+/// ```
+pub fn parse_str(xml: &str) -> Result<(Vec<Tree>, LeafLabelMap), ParsingError> {
+    parse_bytes(xml.as_bytes())
+}
+
+/// Parses all phylogenies of the given phyloXML bytes, see [parse_file].
+///
+/// # Errors
+/// Returns a [ParsingError] if the document or a phylogeny is invalid
+pub fn parse_bytes<B: AsRef<[u8]>>(bytes: B) -> Result<(Vec<Tree>, LeafLabelMap), ParsingError> {
+    parse_all(&mut ByteParser::new(InMemoryByteSource::new(bytes)))
+}
+
+/// Parses the `<phyloxml>` root element and all its phylogenies.
+fn parse_all<S: ByteSource>(parser: &mut ByteParser<S>) -> Result<(Vec<Tree>, LeafLabelMap), ParsingError> {
+    let mut reader = XmlReader::new(parser);
+    let mut leaf_label_map = LeafLabelMap::new(DEFAULT_NUM_LEAVES_GUESS);
+    let mut trees = Vec::new();
+
+    match reader.next_event()? {
+        Some(XmlEvent::Start { name, .. }) if name == PHYLOXML => {}
+        _ => return Err(ParsingError::invalid_phyloxml(reader.parser(), "expected <phyloxml> root element".to_string())),
+    }
+
+    while let Some(event) = reader.next_event()? {
+        if let XmlEvent::Start { name, attributes } = event {
+            if name == PHYLOGENY {
+                let rooted = attributes.iter().find(|(key, _)| key == ROOTED)
+                    .is_none_or(|(_, value)| value.trim() != "false");
+                let tree = parse_phylogeny(&mut reader, &mut leaf_label_map)?;
+                trees.push(tree.with_rooted(rooted));
+            } else {
+                reader.skip_element()?;
+            }
+        }
+    }
+
+    Ok((trees, leaf_label_map))
+}
+
+// =#========================================================================#=
+// PHYLOGENY PARSING
+// =#========================================================================#=
+/// Clade whose start tag has been read, but not its end tag yet.
+#[derive(Default)]
+struct OpenClade {
+    children: Vec<TreeIndex>,
+    name: Option<String>,
+    branch_length: Option<BranchLength>,
+    confidence: Option<String>,
+}
+
+/// Parses the content of a `<phylogeny>` element (after its start tag) into a tree.
+///
+/// Clades are processed with an explicit stack instead of recursion, so arbitrarily deep trees can be parsed.
+fn parse_phylogeny<S: ByteSource>(reader: &mut XmlReader<S>, leaf_label_map: &mut LeafLabelMap) -> Result<Tree, ParsingError> {
+    let mut tree = Tree::new(leaf_label_map.num_labels().max(DEFAULT_NUM_LEAVES_GUESS));
+    let mut tree_name = None;
+    let mut seen_taxa = vec![false; leaf_label_map.num_labels()];
+    let mut num_leaves = 0;
+    let mut open_clades: Vec<OpenClade> = Vec::new();
+    let mut root_seen = false;
+
+    loop {
+        let event = reader.next_event()?
+            .ok_or_else(|| ParsingError::unexpected_eof(reader.parser()))?;
+
+        match event {
+            XmlEvent::Start { name, attributes } if name == CLADE => {
+                if open_clades.is_empty() && root_seen {
+                    return Err(ParsingError::invalid_phyloxml(reader.parser(), "phylogeny has more than one root clade".to_string()));
+                }
+                let branch_length = match attributes.iter().find(|(key, _)| key == BRANCH_LENGTH) {
+                    Some((_, value)) => Some(parse_branch_length(reader, value)?),
+                    None => None,
+                };
+                open_clades.push(OpenClade { branch_length, ..OpenClade::default() });
+            }
+            XmlEvent::Start { name, .. } => match open_clades.last_mut() {
+                Some(clade) if name == NAME => clade.name = Some(reader.read_text()?),
+                Some(clade) if name == BRANCH_LENGTH => {
+                    let text = reader.read_text()?;
+                    clade.branch_length = Some(parse_branch_length(reader, &text)?);
+                }
+                Some(clade) if name == CONFIDENCE && clade.confidence.is_none() => clade.confidence = Some(reader.read_text()?),
+                None if name == NAME => tree_name = Some(reader.read_text()?),
+                _ => reader.skip_element()?,
+            },
+            XmlEvent::End { name } if name == CLADE => {
+                let clade = open_clades.pop().expect("Clade is open");
+                let branch_length = clade.branch_length;
+
+                let index = if open_clades.is_empty() {
+                    root_seen = true;
+                    if clade.children.is_empty() {
+                        return Err(ParsingError::invalid_phyloxml(reader.parser(), "root clade has no children".to_string()));
+                    }
+                    // Root may have a branch length, which we ignore
+                    let index = tree.add_root(clade.children);
+                    tree[index].set_node_label(clade.confidence.or(clade.name));
+                    index
+                } else if clade.children.is_empty() {
+                    let label = clade.name.ok_or_else(|| {
+                        ParsingError::invalid_phyloxml(reader.parser(), "leaf clade has no name".to_string())
+                    })?;
+                    let label_index = leaf_label_map.get_or_insert(&label);
+                    if label_index >= seen_taxa.len() {
+                        seen_taxa.resize(label_index + 1, false);
+                    }
+                    if seen_taxa[label_index] {
+                        return Err(ParsingError::duplicate_taxon(reader.parser(), label));
+                    }
+                    seen_taxa[label_index] = true;
+                    num_leaves += 1;
+                    tree.add_leaf(branch_length, label_index)
+                } else {
+                    let index = tree.add_internal_vertex(clade.children, branch_length);
+                    tree[index].set_node_label(clade.confidence.or(clade.name));
+                    index
+                };
+
+                if let Some(parent) = open_clades.last_mut() {
+                    parent.children.push(index);
+                }
+            }
+            XmlEvent::End { .. } => break,
+            XmlEvent::Text(_) => {}
+        }
+    }
+
+    if !root_seen {
+        return Err(ParsingError::invalid_phyloxml(reader.parser(), "phylogeny has no clade".to_string()));
+    }
+    tree.set_num_leaves_init(num_leaves);
+
+    Ok(match tree_name {
+        Some(name) => tree.with_name(name),
+        None => tree,
+    })
+}
+
+/// Parses a branch length, which must be a non-negative, finite number.
+fn parse_branch_length<S: ByteSource>(reader: &XmlReader<S>, text: &str) -> Result<BranchLength, ParsingError> {
+    let length = text.trim().parse::<f64>()
+        .map_err(|_| ParsingError::invalid_phyloxml(reader.parser(), format!("invalid branch length '{}'", text)))?;
+    BranchLength::try_new(length)
+        .map_err(|error| ParsingError::invalid_phyloxml(reader.parser(), format!("invalid branch length '{}': {}", text, error)))
+}
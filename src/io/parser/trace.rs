@@ -0,0 +1,205 @@
+//! Parameter trace parser for the `.log` files of BEAST and the `.p` files of MrBayes.
+//!
+//! - `parse_file`, `parse_str`, `parse_bytes`: Parse a tab-separated parameter trace into a [Trace]
+//! - `Trace`: Sampled values of each parameter by column name, with burnin handling
+//!   and per-parameter summaries (mean, 95% HPD interval and ESS, see [TraceSummary])
+//!
+//! Posterior tree files come with such a trace of the same run: a header line with the column names,
+//! followed by one line of tab-separated values per sampled state. The first column is usually the state
+//! (`state` in BEAST, `Gen` in MrBayes). Empty lines and comment lines starting with `#` (BEAST)
+//! or `[` (MrBayes, e.g. `[ID: 1234]`) are skipped.
+
+use crate::io::parser::byte_parser::{ByteParser, ConsumeMode};
+use crate::io::parser::byte_source::{ByteSource, InMemoryByteSource};
+use crate::io::parser::nexus::Burnin;
+use crate::io::parser::parsing_error::ParsingError;
+use crate::stats::summary::TraceSummary;
+#[cfg(feature = "fs")]
+use std::fs::File;
+#[cfg(feature = "fs")]
+use std::io::Read;
+
+/// Names of the column holding the state (generation) of each sample, compared case-insensitively
+const STATE_COLUMN_NAMES: [&str; 4] = ["state", "gen", "generation", "sample"];
+
+// =#========================================================================#=
+// TRACE
+// =#========================================================================#=
+/// Parameter trace of an MCMC run, i.e. the sampled values of each parameter in sample order.
+///
+/// # Example
+/// ```
+/// use nexus_parser::io::parser::nexus::Burnin;
+/// use nexus_parser::io::parser::trace;
+///
+/// let log = "# BEAST v2.7\nstate\tposterior\tclockRate\n0\t-120.5\t0.01\n1000\t-110.0\t0.02\n2000\t-108.5\t0.03\n3000\t-109.0\t0.02\n";
+/// let mut trace = trace::parse_str(log).unwrap();
+/// assert_eq!(trace.column_names(), ["state", "posterior", "clockRate"]);
+/// assert_eq!(trace.num_samples(), 4);
+///
+/// trace.discard_burnin(Burnin::Percentage(0.25));
+/// assert_eq!(trace.states(), Some(&[1000.0, 2000.0, 3000.0][..]));
+///
+/// let summary = trace.summary("clockRate").unwrap();
+/// assert!((summary.mean() - 0.07 / 3.0).abs() < 1e-12);
+/// assert_eq!(summary.hpd(), Some((0.02, 0.03)));
+/// assert_eq!(trace.summaries().len(), 2);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Trace {
+    /// Names of the columns, in order of the file
+    names: Vec<String>,
+    /// Values of each column, in sample order
+    columns: Vec<Vec<f64>>,
+}
+
+impl Trace {
+    /// Returns the names of the columns, in order of the file.
+    pub fn column_names(&self) -> &[String] {
+        &self.names
+    }
+
+    /// Returns the number of columns (including the state column).
+    pub fn num_columns(&self) -> usize {
+        self.names.len()
+    }
+
+    /// Returns the number of samples, i.e. of values per column.
+    pub fn num_samples(&self) -> usize {
+        self.columns.first().map_or(0, Vec::len)
+    }
+
+    /// Returns the values of the first column with the given name, if any.
+    pub fn column(&self, name: &str) -> Option<&[f64]> {
+        let index = self.names.iter().position(|column_name| column_name == name)?;
+        self.column_at(index)
+    }
+
+    /// Returns the values of the column at the given position, if any.
+    pub fn column_at(&self, index: usize) -> Option<&[f64]> {
+        self.columns.get(index).map(Vec::as_slice)
+    }
+
+    /// Returns the states (generations) of the samples, if the first column holds them
+    /// (named `state`, `Gen`, `Generation` or `Sample`).
+    pub fn states(&self) -> Option<&[f64]> {
+        self.state_column().and_then(|index| self.column_at(index))
+    }
+
+    /// Discards the burnin at the start of the trace, i.e. a number or fraction of the samples.
+    ///
+    /// # Returns
+    /// The number of samples discarded
+    pub fn discard_burnin(&mut self, burnin: Burnin) -> usize {
+        let num_discarded = burnin.num_discarded(self.num_samples());
+        for column in &mut self.columns {
+            column.drain(..num_discarded);
+        }
+        num_discarded
+    }
+
+    /// Returns the mean, 95% HPD interval and ESS of the first column with the given name, if any.
+    pub fn summary(&self, name: &str) -> Option<TraceSummary> {
+        self.column(name).map(|values| TraceSummary::new(name, values))
+    }
+
+    /// Returns the mean, 95% HPD interval and ESS of every parameter (all columns but the state column),
+    /// in order of the file.
+    pub fn summaries(&self) -> Vec<TraceSummary> {
+        let state_column = self.state_column();
+        self.names.iter().zip(&self.columns)
+            .enumerate()
+            .filter(|&(index, _)| Some(index) != state_column)
+            .map(|(_, (name, values))| TraceSummary::new(name, values))
+            .collect()
+    }
+
+    /// Returns the position of the state column (always the first column), if any.
+    fn state_column(&self) -> Option<usize> {
+        let first = self.names.first()?;
+        STATE_COLUMN_NAMES.iter().any(|name| first.eq_ignore_ascii_case(name)).then_some(0)
+    }
+}
+
+// =#========================================================================#=
+// TRACE FILE PARSING
+// =#========================================================================#=
+/// Parses a parameter trace file (BEAST `.log`, MrBayes `.p`) into a [Trace].
+///
+/// # Errors
+/// Returns a [ParsingError] if the file cannot be read, has no header line, a repeated column name,
+/// a line with another number of values than columns, or a value that is not a number
+#[cfg(feature = "fs")]
+pub fn parse_file(mut file: File) -> Result<Trace, ParsingError> {
+    let mut bytes = Vec::new();
+    file.read_to_end(&mut bytes).map_err(|e| ParsingError::io_error(&e, 0))?;
+    parse_all(&mut ByteParser::new(InMemoryByteSource::from_vec(bytes)))
+}
+
+/// Parses a parameter trace string, see [parse_file].
+///
+/// # Errors
+/// Returns a [ParsingError] if the trace is invalid, see [parse_file]
+pub fn parse_str(trace: &str) -> Result<Trace, ParsingError> {
+    parse_bytes(trace.as_bytes())
+}
+
+/// Parses parameter trace bytes, see [parse_file].
+///
+/// # Errors
+/// Returns a [ParsingError] if the trace is invalid, see [parse_file]
+pub fn parse_bytes<B: AsRef<[u8]>>(bytes: B) -> Result<Trace, ParsingError> {
+    parse_all(&mut ByteParser::new(InMemoryByteSource::new(bytes)))
+}
+
+/// Parses the header line and all lines of values.
+fn parse_all<S: ByteSource>(parser: &mut ByteParser<S>) -> Result<Trace, ParsingError> {
+    let Some(header) = next_line(parser) else {
+        return Err(ParsingError::invalid_trace(parser, "Missing header line with column names".to_string()));
+    };
+    let names: Vec<String> = header.split('\t').map(|name| name.trim().to_string()).collect();
+    if let Some(index) = (1..names.len()).find(|&i| names[..i].contains(&names[i])) {
+        return Err(ParsingError::invalid_trace(parser, format!("Duplicate column '{}'", names[index])));
+    }
+
+    let mut columns: Vec<Vec<f64>> = vec![Vec::new(); names.len()];
+    while let Some(line) = next_line(parser) {
+        let fields: Vec<&str> = line.split('\t').collect();
+        if fields.len() != names.len() {
+            let msg = format!("Line has {} values, expected {} as columns", fields.len(), names.len());
+            return Err(ParsingError::invalid_trace(parser, msg));
+        }
+        for ((field, name), column) in fields.iter().zip(&names).zip(&mut columns) {
+            let Ok(value) = field.trim().parse::<f64>() else {
+                let msg = format!("Invalid value '{}' in column '{}'", field.trim(), name);
+                return Err(ParsingError::invalid_trace(parser, msg));
+            };
+            column.push(value);
+        }
+    }
+
+    Ok(Trace { names, columns })
+}
+
+// ============================================================================
+// Little Helpers
+// ============================================================================
+/// Returns the next line that is neither empty nor a comment, without line break and
+/// without one trailing tab (BEAST 2 writes a tab after every field, header included),
+/// leaving the parser at its end (before the line break).
+fn next_line<S: ByteSource>(parser: &mut ByteParser<S>) -> Option<String> {
+    loop {
+        parser.consume_if(b'\n');
+        if parser.is_eof() {
+            return None;
+        }
+        let start = parser.position();
+        parser.consume_until(b'\n', ConsumeMode::Exclusive);
+        let line = String::from_utf8_lossy(parser.slice_from(start));
+        let line = line.trim_end_matches('\r');
+        let line = line.strip_suffix('\t').unwrap_or(line);
+        if !line.trim().is_empty() && !line.starts_with('#') && !line.starts_with('[') {
+            return Some(line.to_string());
+        }
+    }
+}
@@ -0,0 +1,241 @@
+//! Translation module for the TRANSLATE command of a NEXUS TREES block.
+//!
+//! - `TranslationTable`: Key-label pairs of a TRANSLATE command in order of appearance,
+//!   e.g. `1 'Homo sapiens', 2 Pan_troglodytes;`, available from the NEXUS parsers
+//! - `TranslateToken`: Tokens of a TRANSLATE command (words, commas and the final semicolon)
+//!
+//! Real-world TRANSLATE commands are parsed leniently where this is unambiguous:
+//! keys may be numeric or any (quoted) word, labels may be quoted with commas or comments inside,
+//! a trailing comma before `;` is allowed, and entries on separate lines may omit the comma.
+
+use crate::io::defs::TRANSLATE_DELIMITERS;
+use crate::io::parser::byte_parser::{ByteParser, ParseLabel};
+use crate::io::parser::byte_source::ByteSource;
+use crate::io::parser::parsing_error::ParsingError;
+use crate::model::leaf_label_map::LeafLabel;
+
+// =#========================================================================#=
+// TRANSLATION TABLE
+// =#========================================================================#=
+/// Key-label pairs of a NEXUS TRANSLATE command in order of appearance.
+///
+/// Keys are unique; most files use the keys `1..=n` for their `n` taxa (see [TranslationTable::is_integer_keyed]),
+/// but any word is allowed. Keys and labels are owned strings by default, or, like the labels of a
+/// [LeafLabelMap](crate::model::leaf_label_map::LeafLabelMap), slices of the parsed input where possible.
+///
+/// # Example
+/// ```
+/// use nexus_parser::io::parser::nexus::NexusParserBuilder;
+///
+/// let nexus = b"#NEXUS\nBEGIN TREES;\nTRANSLATE\n\t1 'Homo sapiens',\n\t2 Pan\n\t3 Gorilla;\n\
+///               TREE t = ((1,2),3);\nEND;\n".to_vec();
+/// let parser = NexusParserBuilder::for_bytes(nexus).build().unwrap();
+///
+/// let table = parser.translation_table().unwrap();
+/// assert_eq!(table.len(), 3);
+/// assert_eq!(table.get("1"), Some("Homo sapiens"));
+/// assert_eq!(table.key_of("Gorilla"), Some("3"));
+/// assert!(table.is_integer_keyed());
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct TranslationTable<L = String> {
+    entries: Vec<(L, L)>,
+}
+
+impl<L> Default for TranslationTable<L> {
+    fn default() -> Self {
+        Self { entries: Vec::new() }
+    }
+}
+
+impl<L: LeafLabel> TranslationTable<L> {
+    /// Returns the number of entries.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns whether the table has no entries.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns the label of the given key, if any.
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.entries.iter().find(|(k, _)| k.borrow() == key).map(|(_, label)| label.borrow())
+    }
+
+    /// Returns the (first) key of the given label, if any.
+    pub fn key_of(&self, label: &str) -> Option<&str> {
+        self.entries.iter().find(|(_, l)| l.borrow() == label).map(|(key, _)| key.borrow())
+    }
+
+    /// Returns an iterator over the key-label pairs in order of appearance.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.entries.iter().map(|(key, label)| (key.borrow(), label.borrow()))
+    }
+
+    /// Returns whether every key is an integer in `1..=len()`, as written by BEAST and MrBayes.
+    pub fn is_integer_keyed(&self) -> bool {
+        self.is_integer_keyed_up_to(self.len())
+    }
+
+    /// Returns whether every key is an integer in `1..=max_key`.
+    pub(crate) fn is_integer_keyed_up_to(&self, max_key: usize) -> bool {
+        self.entries.iter().all(|(key, _)| key.borrow().parse::<usize>().is_ok_and(|k| k >= 1 && k <= max_key))
+    }
+
+    /// Returns the key-label pairs in order of appearance.
+    pub(crate) fn entries(&self) -> &[(L, L)] {
+        &self.entries
+    }
+
+    /// Consumes the table and returns its key-label pairs in order of appearance.
+    pub fn into_entries(self) -> Vec<(L, L)> {
+        self.entries
+    }
+
+    /// Parses the entries of a TRANSLATE command up to and including its `;`
+    /// (with the parser positioned after the `TRANSLATE` keyword).
+    ///
+    /// Entries are separated by commas, or by line breaks (a missing comma within a line is an error).
+    ///
+    /// # Errors
+    /// Returns a [ParsingError] if an entry misses its key or label, a key is repeated,
+    /// entries are not separated, or the input ends before `;`.
+    pub(crate) fn parse<S: ByteSource>(parser: &mut ByteParser<S>) -> Result<Self, ParsingError>
+    where
+        L: ParseLabel<S>,
+    {
+        let mut entries: Vec<(L, L)> = Vec::new();
+        // Key of the next entry, if already read after a previous entry without comma
+        let mut next_key: Option<L> = None;
+        loop {
+            let key = match next_key.take() {
+                Some(key) => key,
+                None => match TranslateToken::<L>::next(parser)? {
+                    Some((TranslateToken::Semicolon, _)) => break,
+                    Some((TranslateToken::Word(key), _)) if !key.borrow().is_empty() => key,
+                    Some((token, _)) => {
+                        let key = if let TranslateToken::Word(key) = token { key.into() } else { String::new() };
+                        return Err(ParsingError::invalid_translate_entry(parser, key, "Missing key".to_string()));
+                    }
+                    None => return Err(ParsingError::unexpected_eof(parser)),
+                },
+            };
+
+            let label = match TranslateToken::<L>::next(parser)? {
+                Some((TranslateToken::Word(label), _)) if !label.borrow().is_empty() => label,
+                Some(_) => return Err(ParsingError::invalid_translate_entry(parser, key.into(), "Missing label".to_string())),
+                None => {
+                    let msg = "Unexpected end of file after key".to_string();
+                    return Err(ParsingError::invalid_translate_entry(parser, key.into(), msg));
+                }
+            };
+            if entries.iter().any(|(k, _)| *k == key) {
+                return Err(ParsingError::invalid_translate_entry(parser, key.into(), "Duplicate key".to_string()));
+            }
+
+            match TranslateToken::<L>::next(parser)? {
+                Some((TranslateToken::Comma, _)) => {}
+                Some((TranslateToken::Semicolon, _)) => {
+                    entries.push((key, label));
+                    break;
+                }
+                Some((TranslateToken::Word(word), true)) => next_key = Some(word),
+                Some((TranslateToken::Word(_), false)) => {
+                    let msg = format!("Expected ',' or ';' after label '{}'", label.borrow());
+                    return Err(ParsingError::invalid_translate_entry(parser, key.into(), msg));
+                }
+                None => {
+                    let msg = "Unexpected end of file after label".to_string();
+                    return Err(ParsingError::invalid_translate_entry(parser, key.into(), msg));
+                }
+            }
+            entries.push((key, label));
+        }
+
+        Ok(Self { entries })
+    }
+}
+
+impl<L: LeafLabel> FromIterator<(L, L)> for TranslationTable<L> {
+    /// Collects key-label pairs into a table; later pairs with a repeated key are ignored.
+    fn from_iter<I: IntoIterator<Item = (L, L)>>(iter: I) -> Self {
+        let mut table = Self::default();
+        for (key, label) in iter {
+            if table.get(key.borrow()).is_none() {
+                table.entries.push((key, label));
+            }
+        }
+        table
+    }
+}
+
+// =#========================================================================#=
+// TOKENIZER
+// =#========================================================================#=
+/// Token of a TRANSLATE command.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum TranslateToken<L = String> {
+    /// Key or label, quoted (with quotes removed and doubled quotes unescaped) or unquoted
+    Word(L),
+    /// Separator of entries
+    Comma,
+    /// End of the command
+    Semicolon,
+}
+
+impl<L: LeafLabel> TranslateToken<L> {
+    /// Parses the next token, skipping whitespace and comments before it,
+    /// and returns it with whether a line break preceded it, or `None` at end of input.
+    ///
+    /// Comments within an unquoted word (e.g. `Homo[x]_sapiens`) are skipped and continue the word
+    /// (which is then copied even if words are otherwise borrowed from the input).
+    pub(crate) fn next<S: ByteSource>(parser: &mut ByteParser<S>) -> Result<Option<(Self, bool)>, ParsingError>
+    where
+        L: ParseLabel<S>,
+    {
+        let after_line_break = skip_whitespace_and_comments(parser)?;
+        let token = match parser.peek() {
+            None => return Ok(None),
+            Some(b',') => {
+                parser.next();
+                TranslateToken::Comma
+            }
+            Some(b';') => {
+                parser.next();
+                TranslateToken::Semicolon
+            }
+            Some(b'\'') => TranslateToken::Word(L::parse_label(parser, TRANSLATE_DELIMITERS)?),
+            Some(_) => {
+                let mut word = L::parse_label(parser, TRANSLATE_DELIMITERS)?;
+                if parser.peek() == Some(b'[') {
+                    let mut joined: String = word.into();
+                    while parser.skip_comment()? {
+                        joined.push_str(&parser.parse_unquoted_label(TRANSLATE_DELIMITERS)?);
+                    }
+                    word = L::from(joined);
+                }
+                TranslateToken::Word(word)
+            }
+        };
+        Ok(Some((token, after_line_break)))
+    }
+}
+
+// ============================================================================
+// Little Helpers
+// ============================================================================
+/// Skips whitespace and comments and returns whether a line break was skipped.
+fn skip_whitespace_and_comments<S: ByteSource>(parser: &mut ByteParser<S>) -> Result<bool, ParsingError> {
+    let mut line_break = false;
+    loop {
+        while let Some(b) = parser.peek().filter(u8::is_ascii_whitespace) {
+            line_break |= b == b'\n' || b == b'\r';
+            parser.next();
+        }
+        if !parser.skip_comment()? {
+            return Ok(line_break);
+        }
+    }
+}
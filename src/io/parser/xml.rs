@@ -0,0 +1,320 @@
+//! Minimal XML reader for XML based tree formats (phyloXML, NeXML).
+//!
+//! - `XmlEvent`: Start tag (with attributes), end tag or text content
+//! - `XmlReader`: Reads [XmlEvent]s one by one from a [ByteParser]
+//! - `root_element_name`: Name of the root element of a document, e.g. to tell XML based formats apart
+//!
+//! Only what tree files need is supported: elements, attributes, text, comments,
+//! processing instructions, DOCTYPE declarations, CDATA sections and the predefined
+//! and numeric character references. Namespace prefixes are dropped from element and attribute names.
+
+use crate::io::parser::byte_parser::{ByteParser, ConsumeMode};
+use crate::io::parser::byte_source::{ByteSource, InMemoryByteSource};
+use crate::io::parser::parsing_error::ParsingError;
+
+// =#========================================================================#=
+// XML EVENT
+// =#========================================================================#=
+/// Event of an XML document; empty elements `<a/>` yield a start and an end event.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum XmlEvent {
+    /// Start tag with (local) element name and attributes in order of appearance
+    Start { name: String, attributes: Vec<(String, String)> },
+    /// End tag with (local) element name
+    End { name: String },
+    /// Text content (trimmed, never empty) with character references resolved
+    Text(String),
+}
+
+// =#========================================================================#=
+// XML READER
+// =#========================================================================#=
+/// Reads [XmlEvent]s from a [ByteParser], checking that tags are properly nested.
+pub(crate) struct XmlReader<'a, S: ByteSource> {
+    parser: &'a mut ByteParser<S>,
+    /// Names of elements opened but not closed yet
+    open_elements: Vec<String>,
+    /// Whether the last start tag was empty (`<a/>`), so an end event is due
+    pending_end: bool,
+}
+
+impl<'a, S: ByteSource> XmlReader<'a, S> {
+    /// Creates a reader starting at the current position of the parser.
+    pub(crate) fn new(parser: &'a mut ByteParser<S>) -> Self {
+        Self { parser, open_elements: Vec::new(), pending_end: false }
+    }
+
+    /// Returns the underlying parser (e.g. to create errors at the current position).
+    pub(crate) fn parser(&self) -> &ByteParser<S> {
+        self.parser
+    }
+
+    /// Returns the number of currently open elements.
+    pub(crate) fn depth(&self) -> usize {
+        self.open_elements.len()
+    }
+
+    /// Reads the next event.
+    ///
+    /// # Returns
+    /// The next event, or `None` at the end of the document
+    ///
+    /// # Errors
+    /// Returns a [ParsingError] if the document is not well-formed,
+    /// e.g. has unclosed tags, mismatched end tags or ends within an element.
+    pub(crate) fn next_event(&mut self) -> Result<Option<XmlEvent>, ParsingError> {
+        if self.pending_end {
+            self.pending_end = false;
+            let name = self.open_elements.pop().expect("Empty element was opened");
+            return Ok(Some(XmlEvent::End { name }));
+        }
+
+        loop {
+            if self.parser.is_eof() {
+                return match self.open_elements.last() {
+                    Some(name) => Err(self.error(format!("element <{}> is not closed", name))),
+                    None => Ok(None),
+                };
+            }
+
+            if !self.parser.peek_is(b'<') {
+                let text = self.parse_text()?;
+                if !text.is_empty() {
+                    return Ok(Some(XmlEvent::Text(text)));
+                }
+            } else if self.parser.consume_if_sequence(b"<!--") {
+                if !self.parser.consume_until_sequence(b"-->", ConsumeMode::Inclusive) {
+                    return Err(ParsingError::unclosed_comment(self.parser));
+                }
+            } else if self.parser.consume_if_sequence(b"<![CDATA[") {
+                let start = self.parser.position();
+                if !self.parser.consume_until_sequence(b"]]>", ConsumeMode::Exclusive) {
+                    return Err(ParsingError::unexpected_eof(self.parser));
+                }
+                let text = String::from_utf8_lossy(self.parser.slice_from(start)).trim().to_string();
+                self.parser.consume_if_sequence(b"]]>");
+                if !text.is_empty() {
+                    return Ok(Some(XmlEvent::Text(text)));
+                }
+            } else if self.parser.consume_if_sequence(b"<?") {
+                if !self.parser.consume_until_sequence(b"?>", ConsumeMode::Inclusive) {
+                    return Err(ParsingError::unexpected_eof(self.parser));
+                }
+            } else if self.parser.consume_if_sequence(b"<!") {
+                self.skip_declaration()?;
+            } else if self.parser.consume_if_sequence(b"</") {
+                return self.parse_end_tag().map(Some);
+            } else {
+                self.parser.next();
+                return self.parse_start_tag().map(Some);
+            }
+        }
+    }
+
+    /// Reads the (concatenated) text content of the element whose start tag was just read, up to its end tag.
+    ///
+    /// # Errors
+    /// Returns a [ParsingError] if the element contains child elements or the document is not well-formed.
+    pub(crate) fn read_text(&mut self) -> Result<String, ParsingError> {
+        let mut content = String::new();
+        loop {
+            match self.next_event()? {
+                Some(XmlEvent::Text(text)) => content.push_str(&text),
+                Some(XmlEvent::End { .. }) => return Ok(content),
+                Some(XmlEvent::Start { name, .. }) => {
+                    return Err(self.error(format!("unexpected element <{}> in text", name)));
+                }
+                None => return Err(ParsingError::unexpected_eof(self.parser)),
+            }
+        }
+    }
+
+    /// Skips the element whose start tag was just read, including all its content and its end tag.
+    ///
+    /// # Errors
+    /// Returns a [ParsingError] if the document is not well-formed.
+    pub(crate) fn skip_element(&mut self) -> Result<(), ParsingError> {
+        let depth = self.depth();
+        while self.depth() >= depth {
+            if self.next_event()?.is_none() {
+                return Err(ParsingError::unexpected_eof(self.parser));
+            }
+        }
+        Ok(())
+    }
+
+    /// Creates an error about malformed XML at the current position.
+    pub(crate) fn error(&self, msg: String) -> ParsingError {
+        ParsingError::invalid_xml(self.parser, msg)
+    }
+
+    // ============================================================================
+    // Little Helpers
+    // ============================================================================
+    /// Parses a start tag after its `<`, including attributes and the closing `>` or `/>`.
+    fn parse_start_tag(&mut self) -> Result<XmlEvent, ParsingError> {
+        let name = self.parse_name()?;
+        let mut attributes = Vec::new();
+        loop {
+            self.parser.skip_whitespace();
+            match self.parser.peek() {
+                Some(b'>') => {
+                    self.parser.next();
+                    break;
+                }
+                Some(b'/') => {
+                    self.parser.next();
+                    if !self.parser.consume_if(b'>') {
+                        return Err(self.error(format!("expected '>' after '/' in tag <{}>", name)));
+                    }
+                    self.pending_end = true;
+                    break;
+                }
+                Some(_) => attributes.push(self.parse_attribute()?),
+                None => return Err(ParsingError::unexpected_eof(self.parser)),
+            }
+        }
+
+        self.open_elements.push(name.clone());
+        Ok(XmlEvent::Start { name, attributes })
+    }
+
+    /// Parses an end tag after its `</`, checking that it closes the innermost open element.
+    fn parse_end_tag(&mut self) -> Result<XmlEvent, ParsingError> {
+        let name = self.parse_name()?;
+        self.parser.skip_whitespace();
+        if !self.parser.consume_if(b'>') {
+            return Err(self.error(format!("expected '>' to close end tag </{}>", name)));
+        }
+
+        match self.open_elements.pop() {
+            Some(open) if open == name => Ok(XmlEvent::End { name }),
+            Some(open) => Err(self.error(format!("end tag </{}> does not match <{}>", name, open))),
+            None => Err(self.error(format!("end tag </{}> without start tag", name))),
+        }
+    }
+
+    /// Parses an attribute `name="value"` (or with single quotes).
+    fn parse_attribute(&mut self) -> Result<(String, String), ParsingError> {
+        let name = self.parse_name()?;
+        self.parser.skip_whitespace();
+        if !self.parser.consume_if(b'=') {
+            return Err(self.error(format!("expected '=' after attribute '{}'", name)));
+        }
+        self.parser.skip_whitespace();
+
+        let quote = match self.parser.next() {
+            Some(quote @ (b'"' | b'\'')) => quote,
+            _ => return Err(self.error(format!("expected quoted value of attribute '{}'", name))),
+        };
+        let start = self.parser.position();
+        if !self.parser.consume_until(quote, ConsumeMode::Exclusive) {
+            return Err(ParsingError::unclosed_quote(self.parser));
+        }
+        let value = decode_references(&String::from_utf8_lossy(self.parser.slice_from(start)));
+        self.parser.next();
+
+        Ok((name, value))
+    }
+
+    /// Parses an element or attribute name and returns it without namespace prefix.
+    fn parse_name(&mut self) -> Result<String, ParsingError> {
+        let start = self.parser.position();
+        while let Some(b) = self.parser.peek() {
+            if b.is_ascii_whitespace() || matches!(b, b'>' | b'/' | b'=' | b'<') {
+                break;
+            }
+            self.parser.next();
+        }
+
+        let name = String::from_utf8_lossy(self.parser.slice_from(start)).to_string();
+        if name.is_empty() {
+            return Err(self.error("expected element or attribute name".to_string()));
+        }
+        Ok(match name.rsplit_once(':') {
+            Some((_, local)) => local.to_string(),
+            None => name,
+        })
+    }
+
+    /// Parses text up to the next `<` and returns it trimmed, with character references resolved.
+    fn parse_text(&mut self) -> Result<String, ParsingError> {
+        let start = self.parser.position();
+        self.parser.consume_until(b'<', ConsumeMode::Exclusive);
+        if self.open_elements.is_empty() && !self.parser.slice_from(start).iter().all(u8::is_ascii_whitespace) {
+            return Err(self.error("text outside of root element".to_string()));
+        }
+        Ok(decode_references(String::from_utf8_lossy(self.parser.slice_from(start)).trim()))
+    }
+
+    /// Skips a declaration like `<!DOCTYPE ...>` after its `<!`, including an internal subset `[...]`.
+    fn skip_declaration(&mut self) -> Result<(), ParsingError> {
+        match self.parser.consume_until_any(b"[>", ConsumeMode::Inclusive) {
+            Some(b'[') => {
+                if !self.parser.consume_until(b']', ConsumeMode::Inclusive)
+                    || !self.parser.consume_until(b'>', ConsumeMode::Inclusive) {
+                    return Err(ParsingError::unexpected_eof(self.parser));
+                }
+                Ok(())
+            }
+            Some(_) => Ok(()),
+            None => Err(ParsingError::unexpected_eof(self.parser)),
+        }
+    }
+}
+
+/// Returns the (local) name of the root element of an XML document,
+/// or `None` if the document does not start with a well-formed element.
+pub(crate) fn root_element_name(bytes: &[u8]) -> Option<String> {
+    let mut parser = ByteParser::new(InMemoryByteSource::new(bytes));
+    match XmlReader::new(&mut parser).next_event() {
+        Ok(Some(XmlEvent::Start { name, .. })) => Some(name),
+        _ => None,
+    }
+}
+
+/// Replaces the predefined entities (`&lt;`, `&gt;`, `&amp;`, `&quot;`, `&apos;`)
+/// and numeric character references (`&#65;`, `&#x41;`); unknown references are kept.
+fn decode_references(text: &str) -> String {
+    if !text.contains('&') {
+        return text.to_string();
+    }
+
+    let mut decoded = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(amp) = rest.find('&') {
+        decoded.push_str(&rest[..amp]);
+        rest = &rest[amp..];
+
+        let resolved = rest.find(';').and_then(|semicolon| {
+            let reference = &rest[1..semicolon];
+            let ch = match reference {
+                "lt" => Some('<'),
+                "gt" => Some('>'),
+                "amp" => Some('&'),
+                "quot" => Some('"'),
+                "apos" => Some('\''),
+                _ => reference.strip_prefix("#x").or_else(|| reference.strip_prefix("#X"))
+                    .map(|hex| u32::from_str_radix(hex, 16))
+                    .or_else(|| reference.strip_prefix('#').map(|dec| dec.parse::<u32>()))
+                    .and_then(|code| code.ok())
+                    .and_then(char::from_u32),
+            };
+            ch.map(|ch| (ch, semicolon))
+        });
+
+        match resolved {
+            Some((ch, semicolon)) => {
+                decoded.push(ch);
+                rest = &rest[semicolon + 1..];
+            }
+            None => {
+                decoded.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    decoded.push_str(rest);
+
+    decoded
+}
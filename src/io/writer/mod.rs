@@ -0,0 +1,6 @@
+/// Newick format tree writer
+pub mod newick;
+/// NEXUS format file writer
+pub mod nexus;
+/// ASCII cladograms and Graphviz DOT graphs of trees
+pub mod render;
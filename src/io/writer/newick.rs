@@ -0,0 +1,554 @@
+use crate::model::annotation::Annotation;
+use crate::model::leaf_label_map::LeafLabelMap;
+use crate::model::tree::{Tree, TreeIndex};
+use crate::model::vertex::BranchLength;
+use crate::io::parser::nexus::TreeReader;
+use std::error::Error;
+#[cfg(feature = "fs")]
+use std::fs::File;
+use std::io;
+use std::io::{BufRead, BufWriter, Write};
+
+/// Characters that require a label to be quoted in Newick/Nexus output
+const LABEL_CHARS_TO_QUOTE: &[char] = &[' ', ',', ';', '\t', '\n', '\r', '(', ')', ':', '[', ']', '\''];
+
+// =#========================================================================#=
+// NEWICK WRITER
+// =#========================================================================#=
+/// Converts the tree to a Newick string with full leaf labels.
+///
+/// Labels are quoted where needed and branch lengths are written with full (round-trip) precision,
+/// so parsing the result with a [NewickParser](crate::io::parser::newick::NewickParser)
+/// yields the same tree (up to the root branch length, which is not written).
+///
+/// # Example
+/// ```
+/// use nexus_parser::io::writer::newick::to_newick;
+/// use nexus_parser::model::tree::Tree;
+/// use nexus_parser::model::leaf_label_map::LeafLabelMap;
+/// use nexus_parser::model::vertex::BranchLength;
+///
+/// let mut tree = Tree::new(2);
+/// let mut labels = LeafLabelMap::new(2);
+/// let a = tree.add_leaf(Some(BranchLength::new(1.0)), labels.get_or_insert("Kiwi"));
+/// let b = tree.add_leaf(Some(BranchLength::new(2.5)), labels.get_or_insert("Great Spotted Kiwi"));
+/// tree.add_root(vec![a, b]);
+///
+/// assert_eq!(to_newick(&tree, &labels), "(Kiwi:1,'Great Spotted Kiwi':2.5);");
+/// ```
+pub fn to_newick(tree: &Tree, leaf_label_map: &LeafLabelMap) -> String {
+    tree.to_newick(&NewickStyle::Label, Some(leaf_label_map))
+}
+
+/// Writes the trees as Newick strings with full leaf labels to a file, one tree per line.
+///
+/// # Errors
+/// Returns an error if the file cannot be created or written.
+#[cfg(feature = "fs")]
+pub fn write_newick_file(path: &str, trees: &[Tree], leaf_label_map: &LeafLabelMap) -> io::Result<()> {
+    let mut bw = BufWriter::new(File::create(path)?);
+    for tree in trees {
+        bw.write_all(to_newick(tree, leaf_label_map).as_bytes())?;
+        bw.write_all(b"\n")?;
+    }
+    bw.flush()
+}
+
+// =#========================================================================#=
+// NEWICK STREAM WRITER
+// =#========================================================================#=
+/// Writer serializing trees one at a time as Newick strings, one tree per line.
+///
+/// Together with a streaming [TreeReader], trees can be converted or thinned
+/// with only a single tree in memory at any time, regardless of the number of trees.
+///
+/// # Example
+/// ```no_run
+/// use nexus_parser::io::parser::nexus::TreeReader;
+/// use nexus_parser::io::writer::newick::NewickStreamWriter;
+/// use std::fs::File;
+/// use std::io::BufReader;
+///
+/// let mut reader = TreeReader::new(BufReader::new(File::open("huge.trees").unwrap())).unwrap();
+/// let mut writer = NewickStreamWriter::new(File::create("thinned.nwk").unwrap());
+///
+/// // Keep every 100th tree
+/// let num_written = writer.write_from_reader(&mut reader, 100).unwrap();
+/// writer.finish().unwrap();
+/// ```
+pub struct NewickStreamWriter<W: Write> {
+    bw: BufWriter<W>,
+    options: NewickWriteOptions,
+    num_written: usize,
+}
+
+#[cfg(feature = "fs")]
+impl NewickStreamWriter<File> {
+    /// Creates a new [NewickStreamWriter] writing to a newly created (or truncated) file at `path`.
+    pub fn create(path: &str) -> io::Result<Self> {
+        Ok(NewickStreamWriter::new(File::create(path)?))
+    }
+}
+
+impl<W: Write> NewickStreamWriter<W> {
+    /// Creates a new [NewickStreamWriter] writing full leaf labels to the given writer (buffered internally).
+    pub fn new(writer: W) -> Self {
+        Self {
+            bw: BufWriter::new(writer),
+            options: NewickWriteOptions::default(),
+            num_written: 0,
+        }
+    }
+
+    /// Sets how leaf labels are represented (default [NewickStyle::Label]).
+    pub fn with_style(mut self, style: NewickStyle) -> Self {
+        self.options.style = style;
+        self
+    }
+
+    /// Sets all options of the Newick output, including the style (default [NewickWriteOptions::default]).
+    pub fn with_options(mut self, options: NewickWriteOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Returns the number of trees written so far.
+    pub fn num_written(&self) -> usize {
+        self.num_written
+    }
+
+    /// Writes the tree as Newick string on its own line.
+    ///
+    /// # Arguments
+    /// * `tree` - Tree to write
+    /// * `leaf_label_map` - Leaf labels of the tree (only used with [NewickStyle::Label])
+    ///
+    /// # Errors
+    /// Returns an error if writing fails.
+    pub fn write_tree(&mut self, tree: &Tree, leaf_label_map: &LeafLabelMap) -> io::Result<()> {
+        self.bw.write_all(tree.to_newick_with_options(&self.options, Some(leaf_label_map)).as_bytes())?;
+        self.bw.write_all(b"\n")?;
+        self.num_written += 1;
+        Ok(())
+    }
+
+    /// Writes the trees of the reader as they are read, only every `thin`-th tree
+    /// (the first, the `thin + 1`-th, ...), so `1` writes all trees.
+    ///
+    /// # Returns
+    /// The number of trees written.
+    ///
+    /// # Errors
+    /// Returns the first error of the reader (the trees up to it are written) or of writing.
+    ///
+    /// # Panics
+    /// Panics if `thin` is `0`.
+    pub fn write_from_reader<R: BufRead>(&mut self, reader: &mut TreeReader<R>, thin: usize) -> Result<usize, Box<dyn Error>> {
+        assert!(thin > 0, "Thinning interval must be positive");
+        let mut num_written = 0;
+        let mut num_read = 0;
+        // Label map of reader may grow (verbatim labels), so it is borrowed anew for each tree
+        while let Some(tree) = reader.next() {
+            let tree = tree?;
+            if num_read % thin == 0 {
+                self.write_tree(&tree, reader.leaf_label_map())?;
+                num_written += 1;
+            }
+            num_read += 1;
+        }
+        Ok(num_written)
+    }
+
+    /// Flushes and returns the underlying writer.
+    ///
+    /// # Errors
+    /// Returns an error if flushing fails.
+    pub fn finish(self) -> io::Result<W> {
+        self.bw.into_inner().map_err(|e| e.into_error())
+    }
+}
+
+impl Tree {
+    /// Converts the tree to Newick format string.
+    ///
+    /// The Newick format represents phylogenetic trees as nested parentheses with branch lengths.
+    /// For example: `(('Little Spotted Kiwi':1.0,'Great Spotted Kiwi':1.0):0.5,'Okarito Brown Kiwi':1.5);`
+    /// Labels (of leaves and internal vertices) are quoted if they contain whitespace or punctuation.
+    /// Annotations (see [Tree::annotation]) are written as `[&...]` comments after labels
+    /// and branch annotations directly after the colon of branch lengths.
+    /// See [Tree::to_newick_with_options] to customize the output further.
+    ///
+    /// # Arguments
+    /// * `style` - How to represent leaf labels in the output
+    /// * `leaf_label_map` - Required when using `NewickStyle::Label`, otherwise can be `None`
+    ///
+    /// # Returns
+    /// A Newick format string terminated with `;`. Returns an empty string if
+    /// `NewickStyle::Label` is used without providing a [LeafLabelMap].
+    ///
+    /// # Example
+    /// ```
+    /// use nexus_parser::io::writer::newick::NewickStyle;
+    /// use nexus_parser::model::tree::Tree;
+    /// use nexus_parser::model::leaf_label_map::LeafLabelMap;
+    /// use nexus_parser::model::vertex::BranchLength;
+    ///
+    /// let mut tree = Tree::new(2);
+    /// let mut labels = LeafLabelMap::new(2);
+    /// let a = tree.add_leaf(Some(BranchLength::new(1.0)), labels.get_or_insert("A"));
+    /// let b = tree.add_leaf(Some(BranchLength::new(2.0)), labels.get_or_insert("B"));
+    /// tree.add_root(vec![a, b]);
+    ///
+    /// let newick = tree.to_newick(&NewickStyle::Label, Some(&labels));
+    /// assert_eq!(newick, "(A:1,B:2);");
+    /// ```
+    pub fn to_newick(&self, style: &NewickStyle, leaf_label_map: Option<&LeafLabelMap>) -> String {
+        let options = NewickWriteOptions { style: *style, ..NewickWriteOptions::default() };
+        self.to_newick_with_options(&options, leaf_label_map)
+    }
+
+    /// Converts the tree to Newick format string as configured by the options,
+    /// e.g. to match what downstream programs (RAxML, IQ-TREE, FigTree) expect.
+    ///
+    /// # Arguments
+    /// * `options` - How to write labels, branch lengths, support values and annotations
+    /// * `leaf_label_map` - Required when using `NewickStyle::Label`, otherwise can be `None`
+    ///
+    /// # Returns
+    /// A Newick format string terminated with `;`. Returns an empty string if
+    /// `NewickStyle::Label` is used without providing a [LeafLabelMap].
+    ///
+    /// # Example
+    /// ```
+    /// use nexus_parser::io::writer::newick::{BranchLengthFormat, LabelQuoting, NewickWriteOptions, SupportFormat};
+    /// use nexus_parser::parse_newick_str;
+    ///
+    /// let (trees, labels) = parse_newick_str("(('Homo sapiens':0.123456,Pan:0.2)95:0.05,Gorilla:0.3);").unwrap();
+    ///
+    /// // RAxML/IQ-TREE style: no quotes, six decimals
+    /// let options = NewickWriteOptions {
+    ///     branch_lengths: BranchLengthFormat::Decimals(6),
+    ///     quoting: LabelQuoting::Underscores,
+    ///     ..NewickWriteOptions::default()
+    /// };
+    /// assert_eq!(
+    ///     trees[0].to_newick_with_options(&options, Some(&labels)),
+    ///     "((Homo_sapiens:0.123456,Pan:0.200000)95:0.050000,Gorilla:0.300000);"
+    /// );
+    ///
+    /// // FigTree style: support values as annotations
+    /// let options = NewickWriteOptions { support: SupportFormat::Comment("support".to_string()), ..NewickWriteOptions::default() };
+    /// assert_eq!(
+    ///     trees[0].to_newick_with_options(&options, Some(&labels)),
+    ///     "(('Homo sapiens':0.123456,Pan:0.2)[&support=95]:0.05,Gorilla:0.3);"
+    /// );
+    /// ```
+    pub fn to_newick_with_options(&self, options: &NewickWriteOptions, leaf_label_map: Option<&LeafLabelMap>) -> String {
+        // Helper for adding branch lengths (with branch annotation)
+        fn build_newick_branch_length(newick: &mut String, branch_length: Option<BranchLength>, annotation: Option<&Annotation>, options: &NewickWriteOptions) {
+            let Some(branch_length) = branch_length else {
+                return;
+            };
+            let length = match options.branch_lengths {
+                // Shortest representation at the f32 precision stored (e.g. `0.1` rather than `0.10000000149011612`)
+                #[cfg(feature = "f32-lengths")]
+                BranchLengthFormat::Full => (*branch_length as f32).to_string(),
+                #[cfg(not(feature = "f32-lengths"))]
+                BranchLengthFormat::Full => branch_length.to_string(),
+                BranchLengthFormat::Decimals(decimals) => format!("{:.*}", decimals, *branch_length),
+                BranchLengthFormat::Scientific(decimals) => format!("{:.*e}", decimals, *branch_length),
+                BranchLengthFormat::Omit => return,
+            };
+            newick.push(':');
+            if options.annotations {
+                build_newick_annotation(newick, annotation, None);
+            }
+            newick.push_str(&length);
+        }
+
+        // Helper for adding annotation comments, with an extra entry (e.g. a support value)
+        fn build_newick_annotation(newick: &mut String, annotation: Option<&Annotation>, extra: Option<(&str, &str)>) {
+            let raw = annotation.map(|annotation| annotation.raw()).filter(|raw| !raw.is_empty());
+            if raw.is_none() && extra.is_none() {
+                return;
+            }
+
+            newick.push_str("[&");
+            if let Some(raw) = raw {
+                newick.push_str(raw);
+            }
+            if let Some((key, value)) = extra {
+                if raw.is_some() {
+                    newick.push(',');
+                }
+                newick.push_str(key);
+                newick.push('=');
+                newick.push_str(value);
+            }
+            newick.push(']');
+        }
+
+        // Helper for building the Newick string, iteratively (explicit stack) so deep trees cannot overflow the call stack
+        fn build_newick(tree: &Tree, newick: &mut String, root: TreeIndex, options: &NewickWriteOptions, leaf_label_map: Option<&LeafLabelMap>) {
+            // Vertices to open (`false`) or, after their children, to close (`true`)
+            let mut stack = vec![(root, false)];
+            while let Some((index, children_done)) = stack.pop() {
+                let vertex = &tree[index];
+
+                if vertex.is_leaf() {
+                    // Add label based on style
+                    let label_index = vertex.label_index().unwrap();
+                    match options.style {
+                        NewickStyle::Label => {
+                            let label = &leaf_label_map.unwrap()[label_index];
+                            push_label_quoted(newick, label, options.quoting);
+                        }
+                        NewickStyle::ZeroIndexed => {
+                            newick.push_str(&label_index.to_string());
+                        }
+                        NewickStyle::OneIndexed => {
+                            newick.push_str(&(label_index + 1).to_string());
+                        }
+                    }
+                    if options.annotations {
+                        build_newick_annotation(newick, tree.annotation(index), None);
+                    }
+                    build_newick_branch_length(newick, vertex.branch_length(), tree.branch_annotation(index), options);
+                } else if !children_done {
+                    newick.push('(');
+                    // Push close first and children last first, so first child is processed first
+                    stack.push((index, true));
+                    stack.extend(vertex.children().unwrap().iter().rev().map(|&child| (child, false)));
+                    continue;
+                } else {
+                    newick.push(')');
+
+                    // Support values (numeric node labels) go into the comment if so configured
+                    let support_key = match &options.support {
+                        SupportFormat::Comment(key) if vertex.support().is_some() => Some(key.as_str()),
+                        _ => None,
+                    };
+                    if let Some(node_label) = vertex.node_label()
+                        && support_key.is_none()
+                        && (options.internal_labels || vertex.support().is_some()) {
+                        push_label_quoted(newick, node_label, options.quoting);
+                    }
+                    let support = support_key.zip(vertex.node_label().map(str::trim));
+                    let annotation = tree.annotation(index).filter(|_| options.annotations);
+                    build_newick_annotation(newick, annotation, support);
+
+                    if !vertex.is_root() {
+                        build_newick_branch_length(newick, vertex.branch_length(), tree.branch_annotation(index), options);
+                    }
+                }
+
+                // Separate from next sibling (if any), i.e. if the next vertex to process is not a parent to close
+                if stack.last().is_some_and(|&(_, close)| !close) {
+                    newick.push(',');
+                }
+            }
+        }
+
+        // Abort right away if arguments don't match
+        if matches!(options.style, NewickStyle::Label) && leaf_label_map.is_none() {
+            return String::new();
+        }
+
+        // Estimate capacity:
+        // - Each leaf: "label" (can compute total) or "id" ~= 2
+        const LEAF_ID_CHARS: usize = 2;  // "99" for indices
+        // - Each internal node: "(,)" ~= 3 chars
+        const INTERNAL_NODE_CHARS: usize = 3;  // "(,)"
+        // - Branch lengths: ~20 chars each (e.g., ":0.009529961339106089")
+        const BRANCH_LENGTH_CHARS: usize = 20;
+
+        // -> Structural
+        let num_internal = self.num_internal() + 1; // +1 for root
+        let structure_capacity = num_internal * INTERNAL_NODE_CHARS;
+
+        // -> Labels
+        let num_leaves = self.num_leaves();
+        let label_capacity = match options.style {
+            NewickStyle::Label => {
+                let total_label_len: usize = leaf_label_map.unwrap().labels().iter().map(|s| s.len()).sum();
+                total_label_len
+            }
+            NewickStyle::ZeroIndexed | NewickStyle::OneIndexed => {
+                num_leaves * LEAF_ID_CHARS
+            }
+        };
+
+        // -> Branch lengths
+        let branch_capacity = if self.vertices_have_branch_lengths() && options.branch_lengths != BranchLengthFormat::Omit {
+            (num_leaves + num_internal - 1) * BRANCH_LENGTH_CHARS
+        } else {
+            0
+        };
+
+        // => Total
+        let estimated_capacity = structure_capacity + label_capacity + branch_capacity;
+        let mut newick = String::with_capacity(estimated_capacity);
+
+        build_newick(self, &mut newick, self.root().index(), options, leaf_label_map);
+        newick.push(';');
+
+        newick
+    }
+}
+
+/// Style for serializing tree to Newick format,
+/// controlling how leaf labels are represented in the output string.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NewickStyle {
+    /// Use full leaf labels from the LeafLabelMap
+    #[default]
+    Label,
+    /// Use 0-based indices (0, 1, 2, ...)
+    ZeroIndexed,
+    /// Use 1-based indices (1, 2, 3, ...) (as in Nexus files)
+    OneIndexed,
+}
+
+// =#========================================================================#=
+// NEWICK WRITE OPTIONS
+// =#========================================================================#=
+/// Options for writing Newick strings (see [Tree::to_newick_with_options]), e.g. for downstream programs
+/// that cannot read comments (RAxML, IQ-TREE) or expect support values as annotations (FigTree).
+///
+/// The defaults write full labels, quoted where needed, round-trip branch lengths,
+/// internal labels as stored and all annotations, the same as [Tree::to_newick] with [NewickStyle::Label].
+///
+/// # Example
+/// ```
+/// use nexus_parser::io::writer::newick::{BranchLengthFormat, NewickStyle, NewickWriteOptions};
+/// use nexus_parser::parse_newick_str;
+///
+/// let (trees, _) = parse_newick_str("((A:1,B:1)clade:1,C:2);").unwrap();
+///
+/// // Translate keys (1-based) and topology only
+/// let options = NewickWriteOptions {
+///     style: NewickStyle::OneIndexed,
+///     branch_lengths: BranchLengthFormat::Omit,
+///     internal_labels: false,
+///     annotations: false,
+///     ..NewickWriteOptions::default()
+/// };
+/// assert_eq!(trees[0].to_newick_with_options(&options, None), "((1,2),3);");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NewickWriteOptions {
+    /// How to represent leaf labels, full labels or translate keys (default: [NewickStyle::Label])
+    pub style: NewickStyle,
+    /// How to write branch lengths (default: [BranchLengthFormat::Full])
+    pub branch_lengths: BranchLengthFormat,
+    /// Writes node labels of internal vertices that are not support values (default: `true`)
+    pub internal_labels: bool,
+    /// Where to write support values, i.e. numeric node labels (default: [SupportFormat::Label])
+    pub support: SupportFormat,
+    /// When to quote labels (default: [LabelQuoting::Needed])
+    pub quoting: LabelQuoting,
+    /// Writes vertex and branch annotations as `[&...]` comments (default: `true`)
+    pub annotations: bool,
+}
+
+impl Default for NewickWriteOptions {
+    fn default() -> Self {
+        Self {
+            style: NewickStyle::Label,
+            branch_lengths: BranchLengthFormat::Full,
+            internal_labels: true,
+            support: SupportFormat::Label,
+            quoting: LabelQuoting::Needed,
+            annotations: true,
+        }
+    }
+}
+
+/// Format of branch lengths in Newick output, see [NewickWriteOptions].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BranchLengthFormat {
+    /// Shortest representation that parses back to the same value, e.g. `0.1`
+    #[default]
+    Full,
+    /// Fixed number of decimal places, e.g. `0.100000` with 6 (as written by RAxML)
+    Decimals(usize),
+    /// Scientific notation with the number of decimal places of the mantissa, e.g. `1.00e-1` with 2
+    Scientific(usize),
+    /// No branch lengths (topology only)
+    Omit,
+}
+
+/// Where to write support values (numeric node labels, e.g. `95` or `0.98`) in Newick output, see [NewickWriteOptions].
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum SupportFormat {
+    /// As node labels after the closing parenthesis, e.g. `(A,B)95` (RAxML, IQ-TREE)
+    #[default]
+    Label,
+    /// As entry with the given key of the `[&...]` comment, e.g. `(A,B)[&posterior=0.95]` (FigTree);
+    /// written even if annotations are not
+    Comment(String),
+}
+
+/// When to quote labels in Newick output, see [NewickWriteOptions].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LabelQuoting {
+    /// Only labels containing whitespace or punctuation (or empty ones)
+    #[default]
+    Needed,
+    /// All labels
+    Always,
+    /// Spaces replaced by underscores instead of quoting (Newick convention, see
+    /// [with_underscores_to_spaces](crate::io::parser::nexus::NexusParserBuilder::with_underscores_to_spaces));
+    /// labels with other punctuation are still quoted
+    Underscores,
+}
+
+// ============================================================================
+// Label Escaping (pub(crate))
+// ============================================================================
+/// Returns label as is, or quoted if it contains whitespace, punctuation or quotes (or is empty).
+///
+/// Quoting uses single quotes, with single quotes in label doubled (e.g. `'Wilson''s storm petrel'`).
+pub(crate) fn escape_label(label: &str) -> String {
+    if needs_quotes(label) {
+        // Replace single quotes with double single quotes (SQL-style escaping)
+        let escaped = label.replace('\'', "''");
+        // Wrap in single quotes
+        format!("'{}'", escaped)
+    } else {
+        label.to_string()
+    }
+}
+
+/// Pushes label to Newick string, quoted if needed (see `escape_label`).
+fn push_label(newick: &mut String, label: &str) {
+    if needs_quotes(label) {
+        newick.push_str(&escape_label(label));
+    } else {
+        newick.push_str(label);
+    }
+}
+
+/// Pushes label to Newick string, quoted according to the policy.
+fn push_label_quoted(newick: &mut String, label: &str, quoting: LabelQuoting) {
+    match quoting {
+        LabelQuoting::Needed => push_label(newick, label),
+        LabelQuoting::Always => {
+            newick.push('\'');
+            newick.push_str(&label.replace('\'', "''"));
+            newick.push('\'');
+        }
+        LabelQuoting::Underscores => {
+            let underscored = label.replace(' ', "_");
+            if needs_quotes(&underscored) {
+                newick.push_str(&escape_label(label));
+            } else {
+                newick.push_str(&underscored);
+            }
+        }
+    }
+}
+
+fn needs_quotes(label: &str) -> bool {
+    label.is_empty() || label.contains(LABEL_CHARS_TO_QUOTE)
+}
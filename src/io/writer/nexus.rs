@@ -1,6 +1,9 @@
+use crate::io::defs::{BLOCK_BEGIN, BLOCK_END, DIMENSIONS, NEXUS_HEADER, NTAX, TAXA, TAXLABELS, TRANSLATE, TREE, TREES};
+use crate::io::parser::nexus::RawBlock;
+use crate::io::writer::newick::{escape_label, NewickStyle, NewickWriteOptions};
 use crate::model::leaf_label_map::LeafLabelMap;
-use crate::model::tree::{NewickStyle, Tree};
-use crate::parser::nexus::defs::{BLOCK_BEGIN, BLOCK_END, DIMENSIONS, NEXUS_HEADER, NTAX, TAXA, TAXLABELS, TRANSLATE, TREE};
+use crate::model::tree::Tree;
+#[cfg(feature = "fs")]
 use std::fs::File;
 use std::io;
 use std::io::{BufWriter, Write};
@@ -8,131 +11,241 @@ use std::io::{BufWriter, Write};
 // =#========================================================================#=
 // NEXUS WRITER
 // =#========================================================================#=
-pub struct NexusWriter {
-    bw: BufWriter<File>,
+/// Writer for NEXUS files with a TAXA and a TREES block.
+///
+/// By default, the TREES block contains a Translate table mapping 1-based keys to leaf labels
+/// (in order of the [LeafLabelMap]) and trees reference leaves by key.
+/// Without translate table, trees are written with (quoted) full leaf labels.
+/// Trees without a name are named `tree_<i>`.
+/// Further options of the Newick strings (e.g. branch length precision) are set with [NexusWriter::with_newick_options].
+/// Blocks retained by the parser (e.g. the FIGTREE block with the display settings of FigTree)
+/// are written verbatim after the TREES block, see [NexusWriter::with_raw_block].
+///
+/// # Example
+/// ```no_run
+/// use nexus_parser::io::writer::nexus::NexusWriter;
+/// use std::fs::File;
+///
+/// # let (trees, labels) = (Vec::new(), nexus_parser::model::leaf_label_map::LeafLabelMap::new(0));
+/// let mut writer = NexusWriter::new(File::create("filtered.trees").unwrap())
+///     .with_translate(false);
+/// writer.write_nexus(&trees, &labels).unwrap();
+/// ```
+pub struct NexusWriter<W: Write> {
+    bw: BufWriter<W>,
+    translate: bool,
+    newick_options: NewickWriteOptions,
+    raw_blocks: Vec<RawBlock>,
 }
 
 // ============================================================================
 // API
 // ============================================================================
-impl NexusWriter {
-    pub fn new(file: File) -> NexusWriter {
+#[cfg(feature = "fs")]
+impl NexusWriter<File> {
+    /// Creates a new [NexusWriter] writing to a newly created (or truncated) file at `path`.
+    pub fn create(path: &str) -> io::Result<Self> {
+        Ok(NexusWriter::new(File::create(path)?))
+    }
+}
+
+impl<W: Write> NexusWriter<W> {
+    /// Creates a new [NexusWriter] writing to the given writer (buffered internally).
+    pub fn new(writer: W) -> NexusWriter<W> {
         NexusWriter {
-            bw: BufWriter::new(file),
+            bw: BufWriter::new(writer),
+            translate: true,
+            newick_options: NewickWriteOptions::default(),
+            raw_blocks: Vec::new(),
         }
     }
 
-    pub fn write_nexus(&mut self, trees: &Vec<Tree>, leaf_label_map: &LeafLabelMap) -> io::Result<()> {
+    /// Sets whether to write a Translate table (default `true`).
+    pub fn with_translate(mut self, translate: bool) -> Self {
+        self.translate = translate;
+        self
+    }
+
+    /// Sets the options of the Newick strings of the trees (default [NewickWriteOptions::default]);
+    /// their style is ignored, as leaves are written by key or label depending on [NexusWriter::with_translate].
+    pub fn with_newick_options(mut self, options: NewickWriteOptions) -> Self {
+        self.newick_options = options;
+        self
+    }
+
+    /// Adds a block to write verbatim after the TREES block, e.g. the FIGTREE block of a [NexusDocument](crate::io::parser::nexus::NexusDocument)
+    /// parsed [with raw blocks](crate::io::parser::nexus::NexusParserBuilder::with_raw_blocks), so FigTree keeps its display settings
+    /// for filtered or annotated trees. Blocks are written in the order they were added.
+    ///
+    /// # Example
+    /// ```
+    /// use nexus_parser::io::parser::nexus::NexusParserBuilder;
+    /// use nexus_parser::io::writer::nexus::NexusWriter;
+    ///
+    /// let nexus = b"#NEXUS\nBEGIN TREES;\nTREE t = ((A,B),C);\nEND;\n\
+    ///               BEGIN FIGTREE;\nset appearance.branchLineWidth=2.0;\nEND;\n".to_vec();
+    /// let document = NexusParserBuilder::for_bytes(nexus).with_raw_blocks().build().unwrap()
+    ///     .into_document().unwrap();
+    ///
+    /// let mut writer = NexusWriter::new(Vec::new()).with_raw_block(document.block("figtree").unwrap().clone());
+    /// writer.write_nexus(&document.trees, &document.taxa).unwrap();
+    /// let output = String::from_utf8(writer.into_inner().unwrap()).unwrap();
+    /// assert!(output.ends_with("End;\n\nBEGIN FIGTREE;\nset appearance.branchLineWidth=2.0;\nEND;\n"));
+    /// ```
+    pub fn with_raw_block(mut self, block: RawBlock) -> Self {
+        self.raw_blocks.push(block);
+        self
+    }
+
+    /// Writes a complete NEXUS file with TAXA and TREES block and flushes the writer.
+    ///
+    /// # Arguments
+    /// * `trees` - Trees to write, all over the given leaf label map
+    /// * `leaf_label_map` - Shared leaf labels of the trees
+    ///
+    /// # Errors
+    /// Returns an error if writing fails.
+    pub fn write_nexus(&mut self, trees: &[Tree], leaf_label_map: &LeafLabelMap) -> io::Result<()> {
         self.header()?
             .taxa_block(leaf_label_map)?
-            .trees_block(trees, leaf_label_map)?;
-        Ok(())
+            .trees_block(trees, leaf_label_map)?
+            .raw_blocks()?;
+        self.bw.flush()
+    }
+
+    /// Consumes this writer and returns the underlying writer (after flushing).
+    ///
+    /// # Errors
+    /// Returns an error if flushing fails.
+    pub fn into_inner(self) -> io::Result<W> {
+        self.bw.into_inner().map_err(|e| e.into_error())
     }
 }
 
 // ============================================================================
 // Nexus Block & Command Writing
 // ============================================================================
-impl NexusWriter {
+impl<W: Write> NexusWriter<W> {
     fn header(&mut self) -> io::Result<&mut Self> {
-        // "Nexus\n"
-        self.write_all(NEXUS_HEADER)?.newline()?;
+        // "#NEXUS\n\n"
+        self.write_all(NEXUS_HEADER)?.newline()?.newline()?;
         Ok(self)
     }
 
     fn taxa_block(&mut self, map: &LeafLabelMap) -> io::Result<&mut Self> {
-        // - "Begin TAXA;"
+        // - "Begin taxa;"
         self.write_all(BLOCK_BEGIN)?
             .space()?
             .write_all(TAXA)?
-            .semicolon_ln()?;
+            .newline()?;
 
-        // - "\tDimensions ntaxa=n;"
-        self.tab()?.write_all(DIMENSIONS)?
+        // - "\tDimensions ntax=n;"
+        self.tab()?
+            .write_all(DIMENSIONS)?
             .space()?
             .write_all(NTAX)?
             .equals()?
             .write_all(map.num_labels().to_string().as_bytes())?
             .semicolon_ln()?;
 
-        // - "\tTaxlabels [label ...];"
+        // - "\tTaxlabels\n\t\t[label\n...]\t\t;"
         self.tab()?
-            .write_all(TAXLABELS)?;
+            .write_all(TAXLABELS)?
+            .newline()?;
         for label in map.labels() {
-            let escaped_label = escape_label(label);
-            self.space()?
-                .write_all(escaped_label.as_bytes())?;
+            self.tab()?.tab()?
+                .write_all(escape_label(label).as_bytes())?
+                .newline()?;
         }
-        self.newline()?;
+        self.tab()?.tab()?.semicolon_ln()?;
 
         // - "End;"
         self.write_all(BLOCK_END)?
+            .newline()?
             .newline()?;
 
         Ok(self)
     }
 
-    fn trees_block(&mut self, trees: &Vec<Tree>, leaf_label_map: &LeafLabelMap) -> io::Result<&mut Self> {
-        // - "Begin TREES;"
-        self.write_all(BLOCK_BEGIN)?.space()?.write_all(TAXA)?.semicolon_ln()?;
+    fn trees_block(&mut self, trees: &[Tree], leaf_label_map: &LeafLabelMap) -> io::Result<&mut Self> {
+        // - "Begin trees;"
+        self.write_all(BLOCK_BEGIN)?
+            .space()?
+            .write_all(TREES)?
+            .newline()?;
 
-        self.translate_cmd(leaf_label_map)?
-            .trees_cmd_list(trees)?;
+        if self.translate {
+            self.translate_cmd(leaf_label_map)?;
+        }
+        self.trees_cmd_list(trees, leaf_label_map)?;
+
+        // - "End;"
+        self.write_all(BLOCK_END)?
+            .newline()?;
+
+        Ok(self)
+    }
+
+    fn raw_blocks(&mut self) -> io::Result<&mut Self> {
+        // - "\n<BEGIN ...; ... END;>\n" for each block, verbatim
+        for block in &self.raw_blocks {
+            self.bw.write_all(b"\n")?;
+            self.bw.write_all(&block.content)?;
+            self.bw.write_all(b"\n")?;
+        }
 
         Ok(self)
     }
 
     fn translate_cmd(&mut self, leaf_label_map: &LeafLabelMap) -> io::Result<&mut Self> {
-        // - "TRANSLATE [<key, label], ...];
+        // - "\tTranslate\n[\t\t<key> <label>,\n...]\t\t;"
         self.tab()?
             .write_all(TRANSLATE)?
             .newline()?;
 
         let num_labels = leaf_label_map.num_labels();
-        let mut i = 0;
-
-        for (label, id) in leaf_label_map.map() {
-            i += 1;
-
-            // "(id + 1) escaped_label,\n"
-            let escaped_label = escape_label(label);
-
+        for (index, label) in leaf_label_map.labels().iter().enumerate() {
+            // "\t\t(index + 1) escaped_label,\n"
             self.tab()?.tab()?
-                .write_all((id + 1).to_string().as_bytes())?
+                .write_all((index + 1).to_string().as_bytes())?
                 .space()?
-                .write_all(escaped_label.as_bytes())?;
+                .write_all(escape_label(label).as_bytes())?;
 
             // No comma after last pair
-            if i < num_labels {
+            if index + 1 < num_labels {
                 self.comma()?;
             }
             self.newline()?;
         }
-        self.semicolon()?.newline()?;
-
-        // - "End;"
-        self.write_all(BLOCK_END)?
-            .newline()?;
+        self.tab()?.tab()?.semicolon_ln()?;
 
         Ok(self)
     }
 
-    fn trees_cmd_list(&mut self, trees: &Vec<Tree>) -> io::Result<&mut Self> {
-        // - "TREE <name> = <Newick;>
-        let mut i = 0;
-        for tree in trees {
-            let name = tree.name().map(|s| s.to_string())
+    fn trees_cmd_list(&mut self, trees: &[Tree], leaf_label_map: &LeafLabelMap) -> io::Result<&mut Self> {
+        // - "\ttree <name> [&<metadata>] = [&U] <Newick;>\n", with metadata comment only if present
+        //   and rooting comment only for unrooted trees
+        for (i, tree) in trees.iter().enumerate() {
+            let name = tree.name()
+                .map(|name| escape_label(name))
                 .unwrap_or_else(|| format!("tree_{}", i));
+            let style = if self.translate { NewickStyle::OneIndexed } else { NewickStyle::Label };
+            let options = NewickWriteOptions { style, ..self.newick_options.clone() };
+            let newick = tree.to_newick_with_options(&options, Some(leaf_label_map));
 
-            self.write_all(TREE)?
+            self.tab()?
+                .write_all(TREE)?
                 .space()?
                 .write_all(name.as_bytes())?
+                .space()?;
+            if let Some(metadata) = tree.metadata() {
+                self.write_all(format!("[&{}] ", metadata.raw()).as_bytes())?;
+            }
+            self.equals()?
                 .space()?
-                .equals()?
-                .space()?
-                .write_all(tree.to_newick(NewickStyle::OneIndexed, None).as_bytes())?;
-
-            i += 1;
+                .write_all(if tree.is_rooted() { b"" } else { b"[&U] " })?
+                .write_all(newick.as_bytes())?
+                .newline()?;
         }
 
         Ok(self)
@@ -142,55 +255,33 @@ impl NexusWriter {
 // ============================================================================
 // Little Helpers
 // ============================================================================
-impl NexusWriter {
+impl<W: Write> NexusWriter<W> {
     fn write_all(&mut self, buf: &[u8]) -> io::Result<&mut Self> {
         self.bw.write_all(buf)?;
         Ok(self)
     }
 
     fn space(&mut self) -> io::Result<&mut Self> {
-        self.bw.write_all(b" ")?;
-        Ok(self)
+        self.write_all(b" ")
     }
 
     fn tab(&mut self) -> io::Result<&mut Self> {
-        self.bw.write_all(b"\t")?;
-        Ok(self)
+        self.write_all(b"\t")
     }
 
     fn newline(&mut self) -> io::Result<&mut Self> {
-        self.bw.write_all(b"\n").unwrap();
-        Ok(self)
-    }
-
-    fn semicolon(&mut self) -> io::Result<&mut Self> {
-        self.bw.write_all(b";")?;
-        Ok(self)
+        self.write_all(b"\n")
     }
 
     fn semicolon_ln(&mut self) -> io::Result<&mut Self> {
-        self.semicolon()?.newline()?;
-        Ok(self)
+        self.write_all(b";\n")
     }
 
     fn comma(&mut self) -> io::Result<&mut Self> {
-        self.bw.write_all(b",")?;
-        Ok(self)
+        self.write_all(b",")
     }
 
     fn equals(&mut self) -> io::Result<&mut Self> {
-        self.bw.write_all(b"=")?;
-        Ok(self)
-    }
-}
-
-fn escape_label(label: &String) -> String {
-    if label.chars().any(|c| matches!(c, ' ' | ',' | ';' | '\t' | '\n' | '\r' | '(' | ')' | ':' | '[' | ']' | '\'')) {
-        // Replace single quotes with double single quotes (SQL-style escaping)
-        let escaped = label.replace('\'', "''");
-        // Wrap in single quotes
-        format!("'{}'", escaped)
-    } else {
-        label.to_string()
+        self.write_all(b"=")
     }
 }
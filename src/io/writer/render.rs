@@ -0,0 +1,387 @@
+//! Render module for quick visual inspection of trees.
+//!
+//! Extends [Tree] with:
+//! - `to_ascii`: Text cladogram with aligned leaf labels, e.g. for terminals
+//! - `to_dot`: Graphviz DOT graph with branch lengths as edge labels, e.g. for debugging parser output
+//! - `to_svg`: Standalone SVG drawing (phylogram or cladogram) configured by [RenderOptions], e.g. for notebooks and papers
+
+use crate::model::leaf_label_map::LeafLabelMap;
+use crate::model::tree::Tree;
+use crate::model::vertex::{from_stored, Vertex};
+
+/// Number of columns per level of the ASCII cladogram
+const ASCII_LEVEL_WIDTH: usize = 3;
+
+/// Margin around SVG drawings (in pixels)
+const SVG_MARGIN: f64 = 10.0;
+
+/// Estimated width of a character relative to the font size, to make room for labels in SVG drawings
+const SVG_CHAR_WIDTH: f64 = 0.6;
+
+// =#========================================================================#=
+// RENDER OPTIONS
+// =#========================================================================#=
+/// Options of SVG drawings by [Tree::to_svg].
+///
+/// # Example
+/// ```
+/// use nexus_parser::io::writer::render::RenderOptions;
+///
+/// let options = RenderOptions {
+///     font_size: 10.0,
+///     italic_labels: true,
+///     support_key: Some("posterior".to_string()),
+///     ..RenderOptions::default()
+/// };
+/// assert!(options.show_support);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct RenderOptions {
+    /// Width of the tree (without labels) in pixels (default: 600)
+    pub width: f64,
+    /// Vertical space per leaf in pixels (default: 20)
+    pub row_height: f64,
+    /// Font family of all labels (default: `sans-serif`)
+    pub font_family: String,
+    /// Font size of leaf labels in pixels; support values are drawn smaller (default: 12)
+    pub font_size: f64,
+    /// Draws leaf labels in italics, as usual for species names (default: `false`)
+    pub italic_labels: bool,
+    /// Ignores branch lengths and aligns all leaves (default: `false`, i.e. branch lengths to scale)
+    pub cladogram: bool,
+    /// Draws support values at internal vertices (default: `true`, if present)
+    pub show_support: bool,
+    /// Annotation key of support values (e.g. `posterior`); numeric node labels (e.g. `95`) if `None` (default)
+    pub support_key: Option<String>,
+    /// Draws a scale bar below phylograms (default: `true`)
+    pub scale_bar: bool,
+}
+
+impl Default for RenderOptions {
+    fn default() -> Self {
+        Self {
+            width: 600.0,
+            row_height: 20.0,
+            font_family: "sans-serif".to_string(),
+            font_size: 12.0,
+            italic_labels: false,
+            cladogram: false,
+            show_support: true,
+            support_key: None,
+            scale_bar: true,
+        }
+    }
+}
+
+// ============================================================================
+// ASCII Cladogram (pub)
+// ============================================================================
+impl Tree {
+    /// Renders the tree as a text cladogram (ignoring branch lengths), with one line per leaf
+    /// (and one in between) and all leaf labels aligned in one column.
+    ///
+    /// # Returns
+    /// The lines of the cladogram, each ending with a line break; empty if no root is set.
+    ///
+    /// # Example
+    /// ```
+    /// use nexus_parser::parse_newick_str;
+    ///
+    /// let (trees, labels) = parse_newick_str("((A,B),C);").unwrap();
+    /// let expected = concat!(
+    ///     "   /--A\n",
+    ///     "/--+\n",
+    ///     "+  \\--B\n",
+    ///     "|\n",
+    ///     "\\-----C\n",
+    /// );
+    /// assert_eq!(trees[0].to_ascii(&labels), expected);
+    /// ```
+    pub fn to_ascii(&self, labels: &LeafLabelMap) -> String {
+        if !self.is_root_set() {
+            return String::new();
+        }
+
+        // Levels above the leaves (deepest path in edges) and row of each vertex
+        let mut levels = vec![0; self.num_vertices()];
+        let mut rows = vec![0; self.num_vertices()];
+        let mut num_rows: usize = 0;
+        for vertex in self.post_order_iter() {
+            let index = vertex.index();
+            match vertex.children() {
+                Some(children) if !children.is_empty() => {
+                    levels[index] = 1 + children.iter().map(|&child| levels[child]).max().unwrap_or(0);
+                    rows[index] = (rows[children[0]] + rows[children[children.len() - 1]]) / 2;
+                }
+                _ => {
+                    rows[index] = num_rows;
+                    num_rows += 2;
+                }
+            }
+        }
+        let root = self.root().index();
+        let column = |index: usize| (levels[root] - levels[index]) * ASCII_LEVEL_WIDTH;
+
+        let mut grid = vec![Vec::<char>::new(); num_rows.saturating_sub(1)];
+        let mut put = |row: usize, col: usize, c: char| {
+            let line = &mut grid[row];
+            if line.len() <= col {
+                line.resize(col + 1, ' ');
+            }
+            line[col] = c;
+        };
+        for vertex in self.pre_order_iter() {
+            let (row, col) = (rows[vertex.index()], column(vertex.index()));
+            let children = match vertex {
+                Vertex::Leaf { label_index, .. } => {
+                    for (i, c) in labels.get_label(from_stored(*label_index)).unwrap_or("?").chars().enumerate() {
+                        put(row, col + i, c);
+                    }
+                    continue;
+                }
+                _ => vertex.children().unwrap_or(&[]),
+            };
+            let (Some(&first), Some(&last)) = (children.first(), children.last()) else {
+                continue;
+            };
+
+            // Vertical connector of the children, with a horizontal line to each of them
+            for r in rows[first]..=rows[last] {
+                put(r, col, '|');
+            }
+            put(row, col, '+');
+            for &child in children {
+                let child_row = rows[child];
+                for c in col + 1..column(child) {
+                    put(child_row, c, '-');
+                }
+            }
+            put(rows[first], col, if first == last { '-' } else { '/' });
+            if first != last {
+                put(rows[last], col, '\\');
+            }
+            for &child in children.iter().skip(1).take(children.len().saturating_sub(2)) {
+                put(rows[child], col, '+');
+            }
+        }
+
+        grid.iter()
+            .map(|line| format!("{}\n", line.iter().collect::<String>().trim_end()))
+            .collect()
+    }
+}
+
+// ============================================================================
+// Graphviz DOT (pub)
+// ============================================================================
+impl Tree {
+    /// Renders the tree as a directed Graphviz DOT graph, drawn left to right, with a node `v<index>`
+    /// per vertex (labeled by its leaf label or node label) and an edge from each vertex
+    /// to each of its children, labeled by the branch length of the child (if any).
+    ///
+    /// # Example
+    /// ```
+    /// use nexus_parser::parse_newick_str;
+    ///
+    /// let (trees, labels) = parse_newick_str("(A:0.5,'B C':1)root;").unwrap();
+    /// let dot = trees[0].to_dot(&labels);
+    /// assert!(dot.starts_with("digraph tree {\n"));
+    /// assert!(dot.contains("  v1 [label=\"B C\", shape=plaintext];\n"));
+    /// assert!(dot.contains("  v2 [label=\"root\"];\n"));
+    /// assert!(dot.contains("  v2 -> v0 [label=\"0.5\"];\n"));
+    /// ```
+    pub fn to_dot(&self, labels: &LeafLabelMap) -> String {
+        let mut dot = String::from("digraph tree {\n  rankdir=LR;\n  node [shape=point];\n");
+        if !self.is_root_set() {
+            dot.push_str("}\n");
+            return dot;
+        }
+
+        for vertex in self.pre_order_iter() {
+            let index = vertex.index();
+            match vertex {
+                Vertex::Leaf { label_index, .. } => {
+                    let label = labels.get_label(from_stored(*label_index)).unwrap_or("?");
+                    dot.push_str(&format!("  v{} [label=\"{}\", shape=plaintext];\n", index, escape_dot(label)));
+                }
+                _ => match vertex.node_label() {
+                    Some(label) => dot.push_str(&format!("  v{} [label=\"{}\"];\n", index, escape_dot(label))),
+                    None => dot.push_str(&format!("  v{};\n", index)),
+                },
+            }
+        }
+        for vertex in self.pre_order_iter() {
+            for &child in vertex.children().unwrap_or(&[]) {
+                match self[child].branch_length() {
+                    Some(length) => dot.push_str(&format!("  v{} -> v{} [label=\"{}\"];\n", vertex.index(), child, *length)),
+                    None => dot.push_str(&format!("  v{} -> v{};\n", vertex.index(), child)),
+                }
+            }
+        }
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+// ============================================================================
+// SVG Drawing (pub)
+// ============================================================================
+impl Tree {
+    /// Renders the tree as a standalone SVG drawing, with rectangular branches
+    /// from left (root) to right (leaves) and leaf labels right of the leaves.
+    ///
+    /// Branch lengths are drawn to scale (phylogram, missing lengths as `0`) with a scale bar,
+    /// unless [RenderOptions::cladogram] is set or no vertex has a branch length;
+    /// then leaves are aligned and vertices placed by their number of levels above the leaves.
+    ///
+    /// # Returns
+    /// The SVG document; without tree if no root is set.
+    ///
+    /// # Example
+    /// ```
+    /// use nexus_parser::io::writer::render::RenderOptions;
+    /// use nexus_parser::parse_newick_str;
+    ///
+    /// let (trees, labels) = parse_newick_str("((Homo_sapiens:1,Pan:1)95:2,Gorilla:3);").unwrap();
+    /// let svg = trees[0].to_svg(&labels, &RenderOptions::default());
+    ///
+    /// assert!(svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\""));
+    /// assert!(svg.contains(">Homo_sapiens</text>"));
+    /// assert!(svg.contains(">95</text>"));
+    /// ```
+    pub fn to_svg(&self, labels: &LeafLabelMap, options: &RenderOptions) -> String {
+        let num_leaves = self.post_order_iter().filter(|vertex| vertex.is_leaf()).count();
+        let max_label = self.post_order_iter()
+            .filter_map(|vertex| vertex.label_index())
+            .map(|label_index| labels.get_label(label_index).unwrap_or("?").chars().count())
+            .max()
+            .unwrap_or(0);
+        let phylogram = !options.cladogram && self.post_order_iter().any(|vertex| vertex.has_branch_length() && !vertex.is_root());
+        let scale_bar = phylogram && options.scale_bar;
+
+        let label_width = max_label as f64 * options.font_size * SVG_CHAR_WIDTH + options.font_size;
+        let bar_height = if scale_bar { 2.0 * options.font_size } else { 0.0 };
+        let width = options.width + label_width + 2.0 * SVG_MARGIN;
+        let height = num_leaves as f64 * options.row_height + bar_height + 2.0 * SVG_MARGIN;
+
+        let mut svg = format!("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{:.0}\" height=\"{:.0}\" viewBox=\"0 0 {:.0} {:.0}\">\n",
+                              width, height, width, height);
+        if !self.is_root_set() {
+            svg.push_str("</svg>\n");
+            return svg;
+        }
+
+        let (xs, ys, max_x) = self.svg_layout(phylogram, options.row_height);
+        let scale = if max_x > 0.0 { options.width / max_x } else { 0.0 };
+        let x = |index: usize| SVG_MARGIN + xs[index] * scale;
+        let y = |index: usize| SVG_MARGIN + ys[index];
+
+        // Branches: horizontal line to each vertex, vertical line over the children of each vertex
+        svg.push_str("<g stroke=\"black\" stroke-width=\"1\" stroke-linecap=\"square\" fill=\"none\">\n");
+        for vertex in self.pre_order_iter() {
+            let index = vertex.index();
+            if let Some(parent) = vertex.parent_index() {
+                svg.push_str(&format!("<line x1=\"{:.2}\" y1=\"{:.2}\" x2=\"{:.2}\" y2=\"{:.2}\"/>\n", x(parent), y(index), x(index), y(index)));
+            }
+            if let Some(&[first, .., last]) = vertex.children() {
+                svg.push_str(&format!("<line x1=\"{:.2}\" y1=\"{:.2}\" x2=\"{:.2}\" y2=\"{:.2}\"/>\n", x(index), y(first), x(index), y(last)));
+            }
+        }
+        if scale_bar && max_x > 0.0 {
+            let length = scale_bar_length(max_x);
+            let bar_y = SVG_MARGIN + num_leaves as f64 * options.row_height + options.font_size * 0.5;
+            svg.push_str(&format!("<line x1=\"{:.2}\" y1=\"{:.2}\" x2=\"{:.2}\" y2=\"{:.2}\"/>\n",
+                                  SVG_MARGIN, bar_y, SVG_MARGIN + length * scale, bar_y));
+            svg.push_str(&format!("<text x=\"{:.2}\" y=\"{:.2}\" stroke=\"none\" fill=\"black\" font-family=\"{}\" font-size=\"{}\" text-anchor=\"middle\">{}</text>\n",
+                                  SVG_MARGIN + length * scale / 2.0, bar_y + options.font_size * 1.2,
+                                  escape_xml(&options.font_family), options.font_size * 0.8, length));
+        }
+        svg.push_str("</g>\n");
+
+        // Leaf labels and support values
+        let font_style = if options.italic_labels { " font-style=\"italic\"" } else { "" };
+        svg.push_str(&format!("<g font-family=\"{}\" font-size=\"{}\" fill=\"black\">\n", escape_xml(&options.font_family), options.font_size));
+        for vertex in self.pre_order_iter() {
+            let index = vertex.index();
+            if let Some(label_index) = vertex.label_index() {
+                let label = labels.get_label(label_index).unwrap_or("?");
+                svg.push_str(&format!("<text x=\"{:.2}\" y=\"{:.2}\" dy=\"0.35em\"{}>{}</text>\n",
+                                      x(index) + options.font_size * 0.3, y(index), font_style, escape_xml(label)));
+            } else if options.show_support && !vertex.is_root() && let Some(support) = self.svg_support(index, options) {
+                svg.push_str(&format!("<text x=\"{:.2}\" y=\"{:.2}\" font-size=\"{}\" text-anchor=\"end\">{}</text>\n",
+                                      x(index) - 2.0, y(index) - 3.0, options.font_size * 0.8, support));
+            }
+        }
+        svg.push_str("</g>\n</svg>\n");
+        svg
+    }
+
+    /// Returns the horizontal position (in branch length or levels) and vertical position (in pixels)
+    /// of each vertex, and the maximal horizontal position.
+    fn svg_layout(&self, phylogram: bool, row_height: f64) -> (Vec<f64>, Vec<f64>, f64) {
+        let mut xs = vec![0.0; self.num_vertices()];
+        let mut ys = vec![0.0; self.num_vertices()];
+        let mut levels = vec![0usize; self.num_vertices()];
+        let mut num_rows = 0;
+        for vertex in self.post_order_iter() {
+            let index = vertex.index();
+            match vertex.children() {
+                Some(children) if !children.is_empty() => {
+                    levels[index] = 1 + children.iter().map(|&child| levels[child]).max().unwrap_or(0);
+                    ys[index] = (ys[children[0]] + ys[children[children.len() - 1]]) / 2.0;
+                }
+                _ => {
+                    ys[index] = (num_rows as f64 + 0.5) * row_height;
+                    num_rows += 1;
+                }
+            }
+        }
+
+        let root_level = levels[self.root().index()];
+        for vertex in self.pre_order_iter() {
+            let index = vertex.index();
+            xs[index] = match vertex.parent_index() {
+                None => 0.0,
+                Some(parent) if phylogram => xs[parent] + vertex.branch_length().map_or(0.0, |length| *length),
+                Some(_) => (root_level - levels[index]) as f64,
+            };
+        }
+        let max_x = xs.iter().copied().fold(0.0, f64::max);
+        (xs, ys, max_x)
+    }
+
+    /// Returns the support value of the vertex to draw, if any (see [RenderOptions::support_key]).
+    fn svg_support(&self, index: usize, options: &RenderOptions) -> Option<f64> {
+        match &options.support_key {
+            Some(key) => self.annotation(index)?.get(key)?.as_number(),
+            None => self[index].support(),
+        }
+    }
+}
+
+// ============================================================================
+// Little Helpers
+// ============================================================================
+/// Returns a round length (1, 2 or 5 times a power of 10) of about a fifth of the given width.
+fn scale_bar_length(width: f64) -> f64 {
+    let target = width / 5.0;
+    let magnitude = 10f64.powi(target.log10().floor() as i32);
+    [5.0, 2.0, 1.0].into_iter()
+        .map(|factor| factor * magnitude)
+        .find(|&length| length <= target)
+        .unwrap_or(magnitude)
+}
+
+/// Escapes the special characters of XML text and attribute values (also for HTML).
+pub(crate) fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+/// Escapes backslashes and double quotes for a quoted DOT string.
+fn escape_dot(label: &str) -> String {
+    label.replace('\\', "\\\\").replace('"', "\\\"")
+}
@@ -13,14 +13,53 @@
 
 /// Phylogenetic tree and data structures
 pub mod model;
-/// NEXUS and Newick format parsers
-pub mod parser;
+/// NEXUS and Newick format parsers and writers
+pub mod io;
+/// Statistical utilities for summarizing samples
+pub mod stats;
+/// Algorithms on trees, e.g. tree distances
+pub mod algorithms;
+/// Random trees under the Yule and coalescent models
+#[cfg(feature = "rand")]
+pub mod sim;
+/// Random Newick strings and round-trip checks for tests and fuzzing
+#[cfg(feature = "test-util")]
+pub mod testing;
 
+#[cfg(feature = "fs")]
+use crate::model::label_arena::LabelArena;
 use crate::model::leaf_label_map::LeafLabelMap;
 use crate::model::tree::Tree;
-use crate::parser::nexus::NexusParserBuilder;
+use crate::io::format::{TreeFileFormat, UTF8_BOM};
+use crate::io::parser::{newick, nexml, phyloxml};
+#[cfg(feature = "fs")]
+use crate::io::parser::{fasta, phylip, trace};
+#[cfg(feature = "fs")]
+use crate::io::parser::trace::Trace;
+#[cfg(feature = "fs")]
+use crate::io::parser::phylip::PhylipOptions;
+#[cfg(feature = "fs")]
+use crate::model::alignment::{Alignment, DataType};
+use crate::io::parser::xml::root_element_name;
+use crate::io::parser::nexus::NexusParserBuilder;
+#[cfg(feature = "fs")]
+use crate::io::parser::nexus::{Burnin, LoadOptions, LenientResults, NexusDocument, ParseOptions, TreeReader};
+use crate::io::parser::parsing_error::ParsingError;
+#[cfg(feature = "fs")]
+use crate::io::writer::newick::NewickStreamWriter;
+#[cfg(feature = "fs")]
+use crate::io::writer::nexus::NexusWriter;
+#[cfg(feature = "fs")]
+use crate::io::anonymize::Anonymizer;
+use crate::io::compression::{decompressed_reader, Compression};
+#[cfg(feature = "fs")]
+use crate::stats::report::SampleReport;
 use std::error::Error;
+#[cfg(feature = "fs")]
 use std::fs::File;
+use std::io::Read;
+#[cfg(feature = "fs")]
+use std::io::{BufWriter, Write};
 
 /// Parses a NEXUS file and returns all trees and their label mapping.
 ///
@@ -35,10 +74,507 @@ use std::fs::File;
 ///
 /// # Errors
 /// Returns an error if the file cannot be opened or parsed
+#[cfg(feature = "fs")]
 pub fn parse_nexus_file(path: &str) -> Result<(Vec<Tree>, LeafLabelMap), Box<dyn Error>> {
-    let nexus_parser = NexusParserBuilder::for_file(File::open(path).unwrap())?
+    let nexus_parser = NexusParserBuilder::for_file(File::open(path)?)?
         .eager().build()?;
     let (trees, map) = nexus_parser.into_results()?;
 
     Ok((trees, map))
-}
\ No newline at end of file
+}
+
+/// Parses NEXUS content read from any source (e.g. standard input of a pipeline or a network stream)
+/// and returns all trees and their label mapping, like [parse_nexus_file].
+///
+/// The source is read to its end first; compressed content is decompressed (see [decompressed_reader]).
+///
+/// # Arguments
+/// * `reader` - Source of the NEXUS content
+///
+/// # Returns
+/// A tuple of (trees, label_map) containing all parsed trees and their shared label mapping
+///
+/// # Errors
+/// Returns an error if the source cannot be read or decompressed, or its content cannot be parsed
+///
+/// # Example
+/// ```no_run
+/// use nexus_parser::parse_nexus_reader;
+///
+/// // e.g. `cat sample.trees | mytool`
+/// let (trees, labels) = parse_nexus_reader(std::io::stdin()).unwrap();
+/// ```
+pub fn parse_nexus_reader<R: Read>(mut reader: R) -> Result<(Vec<Tree>, LeafLabelMap), Box<dyn Error>> {
+    let mut content = Vec::new();
+    reader.read_to_end(&mut content)?;
+    if Compression::detect(&content) != Compression::None {
+        let mut decompressed = Vec::new();
+        decompressed_reader(content.as_slice())?.read_to_end(&mut decompressed)?;
+        content = decompressed;
+    }
+
+    Ok(parse_nexus_bytes(&content)?)
+}
+
+/// Parses in-memory NEXUS content (e.g. an upload held by a web service) without copying it
+/// and returns all trees and their label mapping, like [parse_nexus_file].
+///
+/// # Arguments
+/// * `bytes` - NEXUS content (not compressed, see [parse_nexus_reader] otherwise)
+///
+/// # Returns
+/// A tuple of (trees, label_map) containing all parsed trees and their shared label mapping
+///
+/// # Errors
+/// Returns a [ParsingError] if the content cannot be parsed
+///
+/// # Example
+/// ```
+/// use nexus_parser::parse_nexus_bytes;
+///
+/// let nexus = b"#NEXUS\nBegin trees;\n\ttree t1 = ((A,B),C);\n\ttree t2 = ((A,C),B);\nEnd;\n";
+/// let (trees, labels) = parse_nexus_bytes(nexus).unwrap();
+/// assert_eq!(trees.len(), 2);
+/// assert_eq!(labels.num_labels(), 3);
+/// ```
+pub fn parse_nexus_bytes(bytes: &[u8]) -> Result<(Vec<Tree>, LeafLabelMap), ParsingError> {
+    let bytes = bytes.strip_prefix(UTF8_BOM).unwrap_or(bytes);
+    NexusParserBuilder::for_buffer(bytes).eager().build()?.into_results()
+}
+
+/// Parses a NEXUS file like [parse_nexus_file], but skips malformed trees (e.g. a truncated last line
+/// of a killed run) instead of failing, see [NexusParserBuilder::with_lenient].
+///
+/// # Arguments
+/// * `path` - Path to the NEXUS file
+///
+/// # Returns
+/// A tuple of (trees, skipped, label_map) with all well-formed trees, the skipped trees with their errors,
+/// and the shared label mapping
+///
+/// # Errors
+/// Returns an error if the file cannot be opened, or its header, TAXA block or TRANSLATE command cannot be parsed
+#[cfg(feature = "fs")]
+pub fn parse_nexus_file_lenient(path: &str) -> Result<LenientResults, Box<dyn Error>> {
+    let nexus_parser = NexusParserBuilder::for_file(File::open(path)?)?
+        .with_lenient()
+        .eager().build()?;
+
+    Ok(nexus_parser.into_results_with_skipped()?)
+}
+
+/// Parses a NEXUS file like [parse_nexus_file], but also retains its blocks other than TAXA and TREES
+/// (e.g. DATA or FIGTREE) verbatim, see [NexusParserBuilder::with_raw_blocks],
+/// and parses the alignment of its DATA or CHARACTERS block, see [NexusParserBuilder::with_alignment].
+///
+/// # Arguments
+/// * `path` - Path to the NEXUS file
+///
+/// # Returns
+/// A [NexusDocument] with all trees, their label mapping, the other blocks in order of the file and the alignment (if any)
+///
+/// # Errors
+/// Returns an error if the file cannot be opened or parsed
+#[cfg(feature = "fs")]
+pub fn parse_nexus_document(path: &str) -> Result<NexusDocument, Box<dyn Error>> {
+    let nexus_parser = NexusParserBuilder::for_file(File::open(path)?)?
+        .with_raw_blocks()
+        .with_alignment()
+        .eager().build()?;
+
+    Ok(nexus_parser.into_document()?)
+}
+
+/// Parses a NEXUS file like [parse_nexus_file], configured by [ParseOptions],
+/// e.g. to check in strict mode that all trees contain exactly the taxa declared in the TAXA block.
+///
+/// # Arguments
+/// * `path` - Path to the NEXUS file
+/// * `options` - Options for parsing
+///
+/// # Returns
+/// A tuple of (trees, label_map) containing all parsed trees and their shared label mapping
+///
+/// # Errors
+/// Returns an error if the file cannot be opened or parsed, or (in strict mode) its trees
+/// do not match the TAXA block
+#[cfg(feature = "fs")]
+pub fn parse_nexus_file_with_options(path: &str, options: ParseOptions) -> Result<(Vec<Tree>, LeafLabelMap), Box<dyn Error>> {
+    let nexus_parser = NexusParserBuilder::for_file(File::open(path)?)?
+        .with_options(options)
+        .eager().build()?;
+
+    Ok(nexus_parser.into_results()?)
+}
+
+/// Parses a subset of the trees of a NEXUS file (e.g. of an MCMC sample),
+/// skipping trees before, during and after burnin as configured by [LoadOptions].
+///
+/// Like [parse_nexus_file], but only the selected trees are parsed and held in memory.
+///
+/// # Arguments
+/// * `path` - Path to the NEXUS file
+/// * `options` - Which trees to load
+///
+/// # Returns
+/// A tuple of (trees, label_map) containing the selected trees and their shared label mapping
+///
+/// # Errors
+/// Returns an error if the file cannot be opened or parsed
+///
+/// # Panics
+/// Panics if `options.skip_fraction` is not within `[0, 1]` or `options.thin_every` is `0`.
+#[cfg(feature = "fs")]
+pub fn parse_nexus_sample(path: &str, options: LoadOptions) -> Result<(Vec<Tree>, LeafLabelMap), Box<dyn Error>> {
+    let nexus_parser = NexusParserBuilder::for_file(File::open(path)?)?
+        .with_load_options(options)
+        .eager().build()?;
+
+    Ok(nexus_parser.into_results()?)
+}
+
+/// Parses a NEXUS file like [parse_nexus_file], but with leaf label indices of the given [LabelArena].
+///
+/// Parsing several files into the same arena keeps [LabelIndex](crate::model::tree::LabelIndex) values stable
+/// across files, so their trees (e.g. bipartitions) can be compared directly without remapping.
+///
+/// # Arguments
+/// * `path` - Path to the NEXUS file
+/// * `arena` - Labels shared with previously parsed files; new labels are added
+///
+/// # Returns
+/// All parsed trees, whose leaves refer to labels of `arena`
+///
+/// # Errors
+/// Returns an error if the file cannot be opened or parsed
+///
+/// # Example
+/// ```no_run
+/// use nexus_parser::model::label_arena::LabelArena;
+/// use nexus_parser::parse_nexus_file_with_labels;
+///
+/// let mut arena = LabelArena::new();
+/// let run_1 = parse_nexus_file_with_labels("run1.trees", &mut arena).unwrap();
+/// let run_2 = parse_nexus_file_with_labels("run2.trees", &mut arena).unwrap();
+/// println!("{} trees on {} taxa", run_1.len() + run_2.len(), arena.num_labels());
+/// ```
+#[cfg(feature = "fs")]
+pub fn parse_nexus_file_with_labels(path: &str, arena: &mut LabelArena) -> Result<Vec<Tree>, Box<dyn Error>> {
+    let (mut trees, map) = parse_nexus_file(path)?;
+    arena.adopt(&mut trees, &map);
+
+    Ok(trees)
+}
+
+/// Parses a plain Newick file (e.g. `.nwk` or `.newick`) and returns all trees and their label mapping.
+///
+/// The file may contain any number of trees, each terminated by `;`,
+/// typically one per line; comments and whitespace between trees are skipped.
+/// Leaf labels are taken verbatim and shared by all trees.
+///
+/// # Arguments
+/// * `path` - Path to the Newick file
+///
+/// # Returns
+/// A tuple of (trees, label_map) containing all parsed trees and their shared label mapping
+#[cfg(feature = "fs")]
+pub fn parse_newick_file(path: &str) -> Result<(Vec<Tree>, LeafLabelMap), Box<dyn Error>> {
+    Ok(newick::parse_file(File::open(path)?)?)
+}
+
+/// Parses a phyloXML file and returns all its phylogenies as trees and their label mapping.
+///
+/// Leaf clades are identified by their names; branch lengths and the confidence
+/// (or name) of internal clades are kept, see [phyloxml].
+///
+/// # Arguments
+/// * `path` - Path to the phyloXML file
+///
+/// # Returns
+/// A tuple of (trees, label_map) containing all parsed trees and their shared label mapping
+///
+/// # Errors
+/// Returns an error if the file cannot be opened or parsed
+#[cfg(feature = "fs")]
+pub fn parse_phyloxml_file(path: &str) -> Result<(Vec<Tree>, LeafLabelMap), Box<dyn Error>> {
+    Ok(phyloxml::parse_file(File::open(path)?)?)
+}
+
+/// Parses a NeXML file (e.g. exported from TreeBASE) and returns all its trees and their label mapping.
+///
+/// The label mapping contains all otus of the file; leaf nodes refer to them, see [nexml].
+///
+/// # Arguments
+/// * `path` - Path to the NeXML file
+///
+/// # Returns
+/// A tuple of (trees, label_map) containing all parsed trees and their shared label mapping
+///
+/// # Errors
+/// Returns an error if the file cannot be opened or parsed
+#[cfg(feature = "fs")]
+pub fn parse_nexml_file(path: &str) -> Result<(Vec<Tree>, LeafLabelMap), Box<dyn Error>> {
+    Ok(nexml::parse_file(File::open(path)?)?)
+}
+
+/// Parses an aligned FASTA file into an [Alignment] whose sequences are resolved through the given label map,
+/// e.g. of trees parsed before, so sequences and leaves are joined by label index, see [fasta].
+///
+/// # Arguments
+/// * `path` - Path to the FASTA file
+/// * `data_type` - Data type of the sequences
+/// * `labels` - Label map resolving the sequence names; names not yet in it are added
+///
+/// # Errors
+/// Returns an error if the file cannot be opened or parsed
+///
+/// # Example
+/// ```no_run
+/// use nexus_parser::model::alignment::DataType;
+/// use nexus_parser::{parse_fasta_file, parse_nexus_file};
+///
+/// let (trees, mut labels) = parse_nexus_file("sample.trees").unwrap();
+/// let num_taxa = labels.num_labels();
+/// let alignment = parse_fasta_file("alignment.fasta", DataType::Dna, &mut labels).unwrap();
+/// // Every sequence belongs to a taxon of the trees
+/// assert_eq!(labels.num_labels(), num_taxa);
+/// println!("{} trees, {} sequences of {} sites", trees.len(), alignment.num_sequences(), alignment.num_sites());
+/// ```
+#[cfg(feature = "fs")]
+pub fn parse_fasta_file(path: &str, data_type: DataType, labels: &mut LeafLabelMap) -> Result<Alignment, Box<dyn Error>> {
+    Ok(fasta::parse_file(File::open(path)?, data_type, labels)?)
+}
+
+/// Parses a parameter trace file (BEAST `.log`, MrBayes `.p`) of tab-separated values per sampled state
+/// into a [Trace], see [trace].
+///
+/// # Arguments
+/// * `path` - Path to the trace file
+///
+/// # Errors
+/// Returns an error if the file cannot be opened or parsed
+///
+/// # Example
+/// ```no_run
+/// use nexus_parser::io::parser::nexus::Burnin;
+/// use nexus_parser::parse_trace_file;
+///
+/// let mut trace = parse_trace_file("run1.log").unwrap();
+/// trace.discard_burnin(Burnin::Percentage(0.1));
+/// for summary in trace.summaries() {
+///     println!("{}: mean {}, ESS {:?}", summary.name(), summary.mean(), summary.ess());
+/// }
+/// ```
+#[cfg(feature = "fs")]
+pub fn parse_trace_file(path: &str) -> Result<Trace, Box<dyn Error>> {
+    Ok(trace::parse_file(File::open(path)?)?)
+}
+
+/// Parses a PHYLIP file (relaxed or strict names, interleaved or sequential, see [PhylipOptions])
+/// into an [Alignment] whose sequences are resolved through the given label map, see [phylip].
+///
+/// # Arguments
+/// * `path` - Path to the PHYLIP file
+/// * `data_type` - Data type of the sequences
+/// * `labels` - Label map resolving the sequence names; names not yet in it are added
+/// * `options` - Variant of the format
+///
+/// # Errors
+/// Returns an error if the file cannot be opened or parsed
+#[cfg(feature = "fs")]
+pub fn parse_phylip_file(path: &str, data_type: DataType, labels: &mut LeafLabelMap, options: PhylipOptions)
+                         -> Result<Alignment, Box<dyn Error>> {
+    Ok(phylip::parse_file(File::open(path)?, data_type, labels, options)?)
+}
+
+/// Parses all Newick trees of a string and returns them and their label mapping,
+/// see [parse_newick_file].
+///
+/// # Errors
+/// Returns a [ParsingError] if a tree is invalid
+///
+/// # Example
+/// ```
+/// use nexus_parser::parse_newick_str;
+///
+/// let (trees, labels) = parse_newick_str("((A:1,B:1):1,C:2);\n((A:1,C:1):1,B:2);\n").unwrap();
+/// assert_eq!(trees.len(), 2);
+/// assert_eq!(labels.num_labels(), 3);
+/// ```
+pub fn parse_newick_str(newicks: &str) -> Result<(Vec<Tree>, LeafLabelMap), ParsingError> {
+    newick::parse_str(newicks)
+}
+
+/// Parses a tree file of any supported format and returns all trees and their label mapping.
+///
+/// The format is detected from the first non-whitespace bytes (see [TreeFileFormat::detect]):
+/// NEXUS files (`#NEXUS`) are parsed like with [parse_nexus_file], plain Newick files (`(`)
+/// like with [parse_newick_file], and XML files with root element `<phyloxml>` or `<nexml>`
+/// like with [parse_phyloxml_file] or [parse_nexml_file], respectively.
+///
+/// # Arguments
+/// * `path` - Path to the tree file
+///
+/// # Returns
+/// A tuple of (trees, label_map) containing all parsed trees and their shared label mapping
+///
+/// # Errors
+/// Returns an error if the file cannot be read or parsed,
+/// is XML based but neither phyloXML nor NeXML, or of unknown format
+#[cfg(feature = "fs")]
+pub fn parse_trees_file(path: &str) -> Result<(Vec<Tree>, LeafLabelMap), Box<dyn Error>> {
+    parse_trees(std::fs::read(path)?, path)
+}
+
+/// Parses in-memory tree file content of any supported format, detected like with [parse_trees_file],
+/// e.g. read from standard input.
+///
+/// # Arguments
+/// * `bytes` - Content of a NEXUS, Newick, phyloXML or NeXML file
+///
+/// # Returns
+/// A tuple of (trees, label_map) containing all parsed trees and their shared label mapping
+///
+/// # Errors
+/// Returns an error if the content cannot be parsed, is XML based but neither phyloXML nor NeXML,
+/// or of unknown format
+pub fn parse_trees_bytes(bytes: Vec<u8>) -> Result<(Vec<Tree>, LeafLabelMap), Box<dyn Error>> {
+    parse_trees(bytes, "input")
+}
+
+/// Parses tree file content of the detected format; `source` names it in errors.
+fn parse_trees(mut bytes: Vec<u8>, source: &str) -> Result<(Vec<Tree>, LeafLabelMap), Box<dyn Error>> {
+    if bytes.starts_with(UTF8_BOM) {
+        bytes.drain(..UTF8_BOM.len());
+    }
+
+    match TreeFileFormat::detect(&bytes) {
+        Some(TreeFileFormat::Nexus) => Ok(NexusParserBuilder::for_bytes(bytes).eager().build()?.into_results()?),
+        Some(TreeFileFormat::Newick) => Ok(newick::parse_bytes(bytes)?),
+        Some(TreeFileFormat::Xml) => match root_element_name(&bytes).as_deref() {
+            Some(phyloxml::PHYLOXML) => Ok(phyloxml::parse_bytes(bytes)?),
+            Some(nexml::NEXML) => Ok(nexml::parse_bytes(bytes)?),
+            _ => Err(format!("XML tree files other than phyloXML and NeXML are not supported: {}", source).into()),
+        },
+        None => Err(format!("Unknown tree file format (expected #NEXUS or Newick): {}", source).into()),
+    }
+}
+
+/// Parses a memory-mapped NEXUS file and returns all trees and their label mapping.
+///
+/// Like [parse_nexus_file], but the file is not read into memory up front;
+/// the operating system pages it in while parsing, which keeps the memory footprint
+/// of multi-gigabyte files down to the parsed trees.
+/// The file must not be modified while parsing.
+///
+/// # Arguments
+/// * `path` - Path to the NEXUS file
+///
+/// # Returns
+/// A tuple of (trees, label_map) containing all parsed trees and their shared label mapping
+#[cfg(feature = "mmap")]
+pub fn parse_nexus_mmap(path: &str) -> Result<(Vec<Tree>, LeafLabelMap), Box<dyn Error>> {
+    let nexus_parser = NexusParserBuilder::for_mmap(&File::open(path)?)?
+        .eager().build()?;
+    let (trees, map) = nexus_parser.into_results()?;
+
+    Ok((trees, map))
+}
+
+/// Writes trees and their label mapping to a NEXUS file.
+///
+/// The file contains a TAXA block and a TREES block with Translate table,
+/// so it can be read again with [parse_nexus_file].
+///
+/// # Arguments
+/// * `path` - Path to the NEXUS file to create (or overwrite)
+/// * `trees` - Trees to write
+/// * `leaf_label_map` - Shared label mapping of the trees
+///
+/// # Errors
+/// Returns an error if the file cannot be created or written
+#[cfg(feature = "fs")]
+pub fn write_nexus_file(path: &str, trees: &[Tree], leaf_label_map: &LeafLabelMap) -> std::io::Result<()> {
+    NexusWriter::create(path)?.write_nexus(trees, leaf_label_map)
+}
+
+/// Converts a NEXUS file into a Newick file with one tree per line (and full leaf labels),
+/// streaming trees one at a time, so memory usage is independent of the number of trees.
+///
+/// # Arguments
+/// * `input_path` - Path to the NEXUS file to convert
+/// * `output_path` - Path to the Newick file to create (or overwrite)
+/// * `thin` - Only every `thin`-th tree is written, `1` keeps all trees
+///
+/// # Returns
+/// The number of trees written
+///
+/// # Errors
+/// Returns an error if the input cannot be parsed or the output cannot be written
+///
+/// # Panics
+/// Panics if `thin` is `0`.
+#[cfg(feature = "fs")]
+pub fn convert_nexus_to_newick(input_path: &str, output_path: &str, thin: usize) -> Result<usize, Box<dyn Error>> {
+    let mut reader = TreeReader::for_file(File::open(input_path)?)?;
+    let mut writer = NewickStreamWriter::create(output_path)?;
+
+    let num_written = writer.write_from_reader(&mut reader, thin)?;
+    writer.finish()?;
+
+    Ok(num_written)
+}
+
+/// Writes an anonymized copy of a NEXUS file and the mapping from taxon labels to pseudonyms.
+///
+/// All leaf labels and non-numeric node labels are replaced by stable pseudonyms (see [Anonymizer]),
+/// so the anonymized file can be shared, e.g. for debugging, without disclosing taxon names.
+///
+/// # Arguments
+/// * `input_path` - Path to the NEXUS file to anonymize
+/// * `output_path` - Path to the anonymized NEXUS file to create (or overwrite)
+/// * `mapping_path` - Path to the TSV mapping file to create (or overwrite); keep it private
+///
+/// # Errors
+/// Returns an error if the input cannot be parsed or an output file cannot be written
+#[cfg(feature = "fs")]
+pub fn anonymize_nexus_file(input_path: &str, output_path: &str, mapping_path: &str) -> Result<(), Box<dyn Error>> {
+    let (mut trees, leaf_label_map) = parse_nexus_file(input_path)?;
+
+    let mut anonymizer = Anonymizer::new();
+    let anonymized_map = anonymizer.anonymize(&mut trees, &leaf_label_map);
+
+    write_nexus_file(output_path, &trees, &anonymized_map)?;
+    anonymizer.write_mapping_tsv(File::create(mapping_path)?)?;
+
+    Ok(())
+}
+
+/// Writes a self-contained HTML report summarizing a tree file (see [SampleReport]),
+/// e.g. to share quality-control results of a posterior sample.
+///
+/// # Arguments
+/// * `input_path` - Path to the NEXUS or Newick file (see [parse_trees_file])
+/// * `output_path` - Path to the HTML file to create (or overwrite)
+/// * `burnin` - Trees to discard at the start of the sample
+///
+/// # Returns
+/// The number of trees summarized
+///
+/// # Errors
+/// Returns an error if the input cannot be parsed, contains no trees after burnin,
+/// or the output cannot be written
+#[cfg(feature = "fs")]
+pub fn write_report_file(input_path: &str, output_path: &str, burnin: Burnin) -> Result<usize, Box<dyn Error>> {
+    let (trees, leaf_label_map) = parse_trees_file(input_path)?;
+    let trees = &trees[burnin.num_discarded(trees.len())..];
+    if trees.is_empty() {
+        return Err(format!("No trees to summarize in {} after burnin", input_path).into());
+    }
+
+    let mut writer = BufWriter::new(File::create(output_path)?);
+    SampleReport::new(trees, &leaf_label_map).write_html(&mut writer)?;
+    writer.flush()?;
+
+    Ok(trees.len())
+}
@@ -1,11 +1,384 @@
-use nexus_parser::parse_nexus_file;
-
-fn main() {
-    let file = "path/to/your/trees/file/foo.trees";
-    // let start = Instant::now();
-    print!("Parse file: {file}\n");
-    let (trees, _) = parse_nexus_file(file).unwrap();
-    // let duration = start.elapsed();
-    // println!("Parsing took: {:?}", duration);
-    print!("num trees: {}", trees.len());
+//! Command line interface `phylo` for quick analyses of tree files.
+//!
+//! - `phylo info [<trees file>]`: Number of trees and taxa, and rootedness of the trees
+//! - `phylo convert [<trees file>] --to <nexus|newick> [-o <output>]`: Converts trees to NEXUS or Newick
+//! - `phylo subsample [<trees file>] [--skip-first <n>] [--burnin <fraction>] [--thin <n>] [--to <format>] [-o <output>]`:
+//!   Discards burnin and thins a (posterior) tree sample
+//! - `phylo consensus [<trees file>] [--threshold <fraction>] [--burnin <fraction>] [-o <output>]`:
+//!   Majority-rule (or strict) consensus tree as Newick string
+//! - `phylo rfdist <trees file> [<other trees file>] [--normalized] [-o <output>]`:
+//!   Robinson-Foulds distances between all pairs of trees (of one file, or of both files) as TSV matrix
+//! - `phylo report [<trees file>] [-o <report.html>] [--burnin <fraction>]`:
+//!   Writes a self-contained HTML summary of a (posterior) tree sample, see [SampleReport]
+//!
+//! Input files may be NEXUS, Newick, phyloXML or NeXML (detected from their content, possibly compressed);
+//! a missing input or `-` reads from standard input, a missing output writes to standard output.
+//! NEXUS input is read one tree at a time, so converting or subsampling to Newick does not hold the whole file
+//! in memory (unless a burnin fraction needs the number of trees up front).
+
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use nexus_parser::algorithms::consensus::{majority_rule, strict};
+use nexus_parser::algorithms::robinson_foulds::{normalized_robinson_foulds, robinson_foulds};
+use nexus_parser::io::compression::{decompressed_reader, open_decompressed};
+use nexus_parser::io::format::{TreeFileFormat, UTF8_BOM};
+use nexus_parser::io::parser::nexus::{LoadOptions, NexusParserBuilder, TreeReader};
+use nexus_parser::io::writer::newick::{to_newick, NewickStreamWriter};
+use nexus_parser::io::writer::nexus::NexusWriter;
+use nexus_parser::model::leaf_label_map::LeafLabelMap;
+use nexus_parser::model::tree::Tree;
+use nexus_parser::model::tree_sample::{remap_labels, LabelMapping};
+use nexus_parser::parse_trees_bytes;
+use nexus_parser::stats::report::SampleReport;
+use nexus_parser::stats::tree_summary::SampleSummary;
+use std::error::Error;
+use std::fs::File;
+use std::io;
+use std::io::{BufRead, BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+/// Default path of the HTML report
+const DEFAULT_REPORT_PATH: &str = "report.html";
+
+/// Path standing for standard input
+const STDIN_PATH: &str = "-";
+
+// =#========================================================================#=
+// ARGUMENTS
+// =#========================================================================#=
+/// Quick analyses of phylogenetic tree files (NEXUS, Newick, phyloXML, NeXML)
+#[derive(Parser)]
+#[command(name = "phylo", version)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Prints the number of trees and taxa, and the rootedness of the trees
+    Info {
+        /// Tree file (`-` or none for standard input)
+        input: Option<PathBuf>,
+    },
+    /// Converts trees to NEXUS or Newick
+    Convert {
+        /// Tree file (`-` or none for standard input)
+        input: Option<PathBuf>,
+        /// Output format
+        #[arg(long, value_enum)]
+        to: OutputFormat,
+        /// Output file (standard output if none)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Discards burnin and thins a (posterior) tree sample
+    Subsample {
+        /// Tree file (`-` or none for standard input)
+        input: Option<PathBuf>,
+        #[command(flatten)]
+        sample: SampleArgs,
+        /// Output format
+        #[arg(long, value_enum, default_value_t = OutputFormat::Nexus)]
+        to: OutputFormat,
+        /// Output file (standard output if none)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Writes the consensus tree of a tree sample as Newick string, with clade frequencies as node labels
+    Consensus {
+        /// Tree file (`-` or none for standard input)
+        input: Option<PathBuf>,
+        #[command(flatten)]
+        sample: SampleArgs,
+        /// Minimum frequency (exclusive) of clades in the consensus tree, `1` for the strict consensus
+        #[arg(long, default_value_t = 0.5, value_parser = parse_threshold)]
+        threshold: f64,
+        /// Output file (standard output if none)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Writes the Robinson-Foulds distances between all pairs of trees as tab-separated matrix
+    Rfdist {
+        /// Tree file (`-` for standard input)
+        input: PathBuf,
+        /// Second tree file on the same taxa; rows are then trees of the first, columns trees of the second file
+        other: Option<PathBuf>,
+        /// Divides distances by their maximum for binary trees
+        #[arg(long)]
+        normalized: bool,
+        /// Output file (standard output if none)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Writes a self-contained HTML summary of a (posterior) tree sample
+    Report {
+        /// Tree file (`-` or none for standard input)
+        input: Option<PathBuf>,
+        /// Output file
+        #[arg(short, long, default_value = DEFAULT_REPORT_PATH)]
+        output: PathBuf,
+        /// Fraction of trees to discard as burnin
+        #[arg(short, long, default_value_t = 0.0, value_parser = parse_fraction)]
+        burnin: f64,
+    },
+}
+
+/// Which trees of a sample to use (in this order: skipping, burnin, thinning)
+#[derive(Args)]
+struct SampleArgs {
+    /// Number of trees to skip first (e.g. the starting tree)
+    #[arg(long, default_value_t = 0)]
+    skip_first: usize,
+    /// Fraction of the remaining trees to discard as burnin
+    #[arg(short, long, default_value_t = 0.0, value_parser = parse_fraction)]
+    burnin: f64,
+    /// Keeps only every n-th tree after burnin
+    #[arg(long, default_value_t = 1, value_parser = clap::value_parser!(u64).range(1..))]
+    thin: u64,
+}
+
+impl SampleArgs {
+    /// Returns the [LoadOptions] selecting the trees of the sample.
+    fn load_options(&self) -> LoadOptions {
+        LoadOptions { skip_first: self.skip_first, skip_fraction: self.burnin, thin_every: self.thin as usize }
+    }
+}
+
+/// Format of written trees
+#[derive(Clone, Copy, PartialEq, ValueEnum)]
+enum OutputFormat {
+    /// NEXUS file with TAXA and TREES block
+    Nexus,
+    /// One Newick string per line
+    Newick,
+}
+
+fn main() -> ExitCode {
+    let result = match Cli::parse().command {
+        Command::Info { input } => info(input.as_deref()),
+        Command::Convert { input, to, output } => convert(input.as_deref(), to, output.as_deref()),
+        Command::Subsample { input, sample, to, output } => subsample(input.as_deref(), &sample, to, output.as_deref()),
+        Command::Consensus { input, sample, threshold, output } => consensus(input.as_deref(), &sample, threshold, output.as_deref()),
+        Command::Rfdist { input, other, normalized, output } => rfdist(&input, other.as_deref(), normalized, output.as_deref()),
+        Command::Report { input, output, burnin } => report(input.as_deref(), &output, burnin),
+    };
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        // Output piped into e.g. `head` was closed early
+        Err(e) if e.downcast_ref::<io::Error>().is_some_and(|e| e.kind() == io::ErrorKind::BrokenPipe) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("{}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+// =#========================================================================#=
+// SUBCOMMANDS
+// =#========================================================================#=
+/// Runs the `info` subcommand.
+fn info(input: Option<&Path>) -> Result<(), Box<dyn Error>> {
+    let (trees, labels) = read_trees(input, LoadOptions::default())?;
+    let num_rooted = trees.iter().filter(|tree| tree.is_rooted()).count();
+
+    let mut out = io::stdout().lock();
+    writeln!(out, "Trees:\t{}", trees.len())?;
+    writeln!(out, "Rooted:\t{}", num_rooted)?;
+    writeln!(out, "Unrooted:\t{}", trees.len() - num_rooted)?;
+    let summary = SampleSummary::new(&trees);
+    if let (Some(lengths), Some(heights)) = (summary.tree_length_stats(), summary.root_height_stats()) {
+        writeln!(out, "Tree length:\t{}", lengths)?;
+        writeln!(out, "Root height:\t{}", heights)?;
+    }
+    writeln!(out, "Taxa:\t{}", labels.num_labels())?;
+    for label in labels.labels() {
+        writeln!(out, "\t{}", label)?;
+    }
+    Ok(())
+}
+
+/// Runs the `convert` subcommand.
+fn convert(input: Option<&Path>, format: OutputFormat, output: Option<&Path>) -> Result<(), Box<dyn Error>> {
+    write_trees(input, LoadOptions::default(), format, output)
+}
+
+/// Runs the `subsample` subcommand.
+fn subsample(input: Option<&Path>, sample: &SampleArgs, format: OutputFormat, output: Option<&Path>) -> Result<(), Box<dyn Error>> {
+    write_trees(input, sample.load_options(), format, output)
+}
+
+/// Runs the `consensus` subcommand.
+fn consensus(input: Option<&Path>, sample: &SampleArgs, threshold: f64, output: Option<&Path>) -> Result<(), Box<dyn Error>> {
+    let (trees, labels) = read_trees(input, sample.load_options())?;
+    if trees.is_empty() {
+        return Err("No trees left for consensus".into());
+    }
+
+    let consensus = if threshold < 1.0 { majority_rule(&trees, threshold) } else { strict(&trees) };
+    let mut out = create_output(output)?;
+    writeln!(out, "{}", to_newick(&consensus, &labels))?;
+    out.flush()?;
+    Ok(())
+}
+
+/// Runs the `rfdist` subcommand.
+fn rfdist(input: &Path, other: Option<&Path>, normalized: bool, output: Option<&Path>) -> Result<(), Box<dyn Error>> {
+    let (trees, mut labels) = read_trees(Some(input), LoadOptions::default())?;
+    let columns = match other {
+        Some(other) => {
+            let (mut other_trees, other_labels) = read_trees(Some(other), LoadOptions::default())?;
+            relabel(&mut other_trees, &other_labels, &mut labels)?;
+            other_trees
+        }
+        None => trees.clone(),
+    };
+
+    let mut out = create_output(output)?;
+    let names: Vec<String> = columns.iter().enumerate().map(|(i, tree)| tree_name(tree, i)).collect();
+    writeln!(out, "\t{}", names.join("\t"))?;
+    for (i, tree) in trees.iter().enumerate() {
+        let distances: Vec<String> = columns.iter()
+            .map(|column| match normalized {
+                true => normalized_robinson_foulds(tree, column).to_string(),
+                false => robinson_foulds(tree, column).to_string(),
+            })
+            .collect();
+        writeln!(out, "{}\t{}", tree_name(tree, i), distances.join("\t"))?;
+    }
+    out.flush()?;
+    Ok(())
+}
+
+/// Runs the `report` subcommand.
+fn report(input: Option<&Path>, output: &Path, burnin: f64) -> Result<(), Box<dyn Error>> {
+    let (trees, labels) = read_trees(input, LoadOptions { skip_fraction: burnin, ..LoadOptions::default() })?;
+    if trees.is_empty() {
+        return Err("No trees to summarize after burnin".into());
+    }
+
+    let mut out = create_output(Some(output))?;
+    SampleReport::new(&trees, &labels).write_html(&mut out)?;
+    out.flush()?;
+    println!("Wrote report on {} trees to {}", trees.len(), output.display());
+    Ok(())
+}
+
+// =#========================================================================#=
+// LITTLE HELPERS
+// =#========================================================================#=
+/// Reads the trees of the given file, or of standard input if `None` or `-`, selected by the [LoadOptions].
+fn read_trees(input: Option<&Path>, options: LoadOptions) -> Result<(Vec<Tree>, LeafLabelMap), Box<dyn Error>> {
+    let mut trees = Vec::new();
+    let labels = for_each_tree(input, options, |tree, _| {
+        trees.push(tree);
+        Ok(())
+    })?;
+    Ok((trees, labels))
+}
+
+/// Reads the trees of the given file, or of standard input if `None` or `-`, selected by the [LoadOptions],
+/// and passes them one at a time to `on_tree` with the labels read so far; compressed input is decompressed.
+///
+/// NEXUS input is streamed by a [TreeReader], unless a burnin fraction needs the number of trees up front;
+/// other formats are parsed at once.
+///
+/// # Returns
+/// The shared [LeafLabelMap] of all trees
+fn for_each_tree(input: Option<&Path>, options: LoadOptions, mut on_tree: impl FnMut(Tree, &LeafLabelMap) -> Result<(), Box<dyn Error>>)
+    -> Result<LeafLabelMap, Box<dyn Error>> {
+    let mut reader = match input {
+        Some(path) if path != Path::new(STDIN_PATH) => open_decompressed(path)
+            .map_err(|e| format!("Cannot read {}: {}", path.display(), e))?,
+        _ => decompressed_reader(BufReader::new(io::stdin()))?,
+    };
+    let start = reader.fill_buf()?;
+    let is_nexus = TreeFileFormat::detect(start) == Some(TreeFileFormat::Nexus);
+    if start.starts_with(UTF8_BOM) {
+        reader.consume(UTF8_BOM.len());
+    }
+
+    if is_nexus && options.skip_fraction == 0.0 {
+        let mut tree_reader = TreeReader::new(reader)?.with_load_options(options);
+        while let Some(tree) = tree_reader.next() {
+            on_tree(tree?, tree_reader.leaf_label_map())?;
+        }
+        return Ok(tree_reader.into_leaf_label_map());
+    }
+
+    let mut bytes = Vec::new();
+    reader.read_to_end(&mut bytes)?;
+    if is_nexus {
+        let mut parser = NexusParserBuilder::for_bytes(bytes).lazy().with_load_options(options).build()?;
+        while let Some(tree) = parser.next_tree()? {
+            on_tree(tree, parser.leaf_label_map())?;
+        }
+        return Ok(parser.into_results()?.1);
+    }
+    let (trees, labels) = parse_trees_bytes(bytes)?;
+    for tree in options.select(trees) {
+        on_tree(tree, &labels)?;
+    }
+    Ok(labels)
+}
+
+/// Creates the given output file, or returns standard output if `None`.
+fn create_output(output: Option<&Path>) -> io::Result<Box<dyn Write>> {
+    Ok(match output {
+        Some(path) => Box::new(BufWriter::new(File::create(path)?)),
+        None => Box::new(BufWriter::new(io::stdout().lock())),
+    })
+}
+
+/// Writes the trees of the input selected by the [LoadOptions] in the given format to the given output file,
+/// or to standard output if `None`; Newick strings are written while the trees are read.
+fn write_trees(input: Option<&Path>, options: LoadOptions, format: OutputFormat, output: Option<&Path>) -> Result<(), Box<dyn Error>> {
+    match format {
+        OutputFormat::Nexus => {
+            // TAXA block precedes the trees, so all of them are read first
+            let (trees, labels) = read_trees(input, options)?;
+            NexusWriter::new(create_output(output)?).write_nexus(&trees, &labels)?;
+        }
+        OutputFormat::Newick => {
+            let mut writer = NewickStreamWriter::new(create_output(output)?);
+            for_each_tree(input, options, |tree, labels| Ok(writer.write_tree(&tree, labels)?))?;
+            writer.finish()?.flush()?;
+        }
+    }
+    Ok(())
+}
+
+/// Moves the leaves of the trees from `labels` to the label indices of `target`, which must have the same taxa.
+fn relabel(trees: &mut [Tree], labels: &LeafLabelMap, target: &mut LeafLabelMap) -> Result<(), Box<dyn Error>> {
+    if labels.num_labels() != target.num_labels() || labels.labels().iter().any(|label| !target.contains_label(label)) {
+        return Err("Tree files do not have the same taxa".into());
+    }
+
+    let mapping = LabelMapping::new(labels, target);
+    for tree in trees {
+        remap_labels(tree, &mapping);
+    }
+    Ok(())
+}
+
+/// Returns the name of a tree, or its (1-based) number in the file if unnamed.
+fn tree_name(tree: &Tree, index: usize) -> String {
+    tree.name().cloned().unwrap_or_else(|| (index + 1).to_string())
+}
+
+/// Parses a fraction in `[0, 1)`, e.g. of trees to discard as burnin.
+fn parse_fraction(value: &str) -> Result<f64, String> {
+    value.parse()
+        .ok()
+        .filter(|fraction| (0.0..1.0).contains(fraction))
+        .ok_or_else(|| format!("'{}' is not a fraction in [0, 1)", value))
+}
+
+/// Parses a consensus threshold in `[0.5, 1]`.
+fn parse_threshold(value: &str) -> Result<f64, String> {
+    value.parse()
+        .ok()
+        .filter(|threshold| (0.5..=1.0).contains(threshold))
+        .ok_or_else(|| format!("'{}' is not a threshold in [0.5, 1]", value))
 }
@@ -0,0 +1,263 @@
+//! Alignment module for character matrices (e.g. of a NEXUS DATA or CHARACTERS block) linked to trees by leaf labels.
+//!
+//! - `DataType`: Type of the characters (DNA, RNA, protein or standard/morphological)
+//! - `Alignment`: Sequences of equal length, indexed by the [LabelIndex] of a [LeafLabelMap](crate::model::leaf_label_map::LeafLabelMap)
+//! - `AlignmentError`: Sequence not matching the alignment (wrong length or repeated taxon)
+//!
+//! Sequences are stored as bytes as written in the file (nucleotides and amino acids upper case),
+//! with the alignment's gap and missing symbols; sets of states such as `{AG}` are stored
+//! as IUPAC ambiguity codes for nucleotides and as missing otherwise.
+
+use crate::model::leaf_label_map::LeafLabelMap;
+use crate::model::tree::LabelIndex;
+use std::error::Error;
+use std::fmt;
+
+/// IUPAC nucleotide codes indexed by their set of bases (bits `A = 1`, `C = 2`, `G = 4`, `T = 8`)
+pub(crate) const IUPAC_CODES: &[u8; 16] = b"?ACMGRSVTWYHKDBN";
+
+// =#========================================================================#=
+// DATA TYPE
+// =#========================================================================#=
+/// Type of the characters of an alignment, as given by `DATATYPE` of a NEXUS FORMAT command.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DataType {
+    /// Nucleotides `ACGT` (also `NUCLEOTIDE`) with IUPAC ambiguity codes
+    Dna,
+    /// Nucleotides `ACGU` with IUPAC ambiguity codes
+    Rna,
+    /// Amino acids
+    Protein,
+    /// Discrete (e.g. morphological) characters with symbols `0`-`9` by default
+    #[default]
+    Standard,
+}
+
+impl DataType {
+    /// Returns the data type of the given NEXUS name (case-insensitive), if known.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "dna" | "nucleotide" => Some(DataType::Dna),
+            "rna" => Some(DataType::Rna),
+            "protein" => Some(DataType::Protein),
+            "standard" => Some(DataType::Standard),
+            _ => None,
+        }
+    }
+
+    /// Returns whether the characters are nucleotides (DNA or RNA).
+    pub fn is_nucleotide(&self) -> bool {
+        matches!(self, DataType::Dna | DataType::Rna)
+    }
+
+    /// Returns the state as stored in an [Alignment]: upper case for nucleotides and amino acids, as is otherwise.
+    pub(crate) fn normalize(&self, state: u8) -> u8 {
+        if *self == DataType::Standard { state } else { state.to_ascii_uppercase() }
+    }
+}
+
+impl fmt::Display for DataType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DataType::Dna => write!(f, "DNA"),
+            DataType::Rna => write!(f, "RNA"),
+            DataType::Protein => write!(f, "Protein"),
+            DataType::Standard => write!(f, "Standard"),
+        }
+    }
+}
+
+// =#========================================================================#=
+// ALIGNMENT
+// =#========================================================================#=
+/// Sequences of equal length (number of sites), each belonging to a taxon by its [LabelIndex].
+///
+/// Taxa of the label map need not all have a sequence, so an alignment can be shared
+/// with trees on more taxa (or the other way around).
+///
+/// # Example
+/// ```
+/// use nexus_parser::model::alignment::{Alignment, DataType};
+///
+/// let mut alignment = Alignment::new(DataType::Dna, 4);
+/// alignment.insert(0, b"ACGT".to_vec()).unwrap();
+/// alignment.insert(2, b"AC-?".to_vec()).unwrap();
+///
+/// assert_eq!(alignment.num_sequences(), 2);
+/// assert_eq!(alignment.sequence(2), Some(&b"AC-?"[..]));
+/// assert_eq!(alignment.sequence(1), None);
+/// assert_eq!(alignment.site(3).collect::<Vec<_>>(), vec![(0, b'T'), (2, b'?')]);
+/// assert!(alignment.insert(1, b"ACG".to_vec()).is_err());
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Alignment {
+    data_type: DataType,
+    /// Length of every sequence
+    num_sites: usize,
+    /// Symbol of gaps (alignment indels)
+    gap: u8,
+    /// Symbol of missing data
+    missing: u8,
+    /// Sequence of each label index, `None` for taxa without sequence
+    sequences: Vec<Option<Vec<u8>>>,
+}
+
+impl Alignment {
+    /// Creates an empty alignment with the given number of sites, gap symbol `-` and missing symbol `?`.
+    pub fn new(data_type: DataType, num_sites: usize) -> Self {
+        Self {
+            data_type,
+            num_sites,
+            gap: b'-',
+            missing: b'?',
+            sequences: Vec::new(),
+        }
+    }
+
+    /// Creates an alignment of the named sequences, resolving their names through the label map
+    /// (adding names not yet in it), so it can be joined with trees over the same map by [LabelIndex].
+    /// The number of sites is the length of the first sequence (`0` without sequences).
+    ///
+    /// # Errors
+    /// Returns an [AlignmentError] if the sequences differ in length or a name is repeated.
+    ///
+    /// # Example
+    /// ```
+    /// use nexus_parser::model::alignment::{Alignment, DataType};
+    /// use nexus_parser::parse_newick_str;
+    ///
+    /// let (_, mut labels) = parse_newick_str("((A,B),C);").unwrap();
+    /// let sequences = vec![("C".to_string(), b"AC".to_vec()), ("A".to_string(), b"AG".to_vec())];
+    /// let alignment = Alignment::from_named_sequences(DataType::Dna, sequences, &mut labels).unwrap();
+    ///
+    /// assert_eq!(alignment.sequence(labels.get_index("C").unwrap()), Some(&b"AC"[..]));
+    /// assert_eq!(alignment.num_sequences(), 2);
+    /// ```
+    pub fn from_named_sequences(data_type: DataType, sequences: impl IntoIterator<Item = (String, Vec<u8>)>,
+                                labels: &mut LeafLabelMap) -> Result<Self, AlignmentError> {
+        let mut sequences = sequences.into_iter().peekable();
+        let num_sites = sequences.peek().map_or(0, |(_, sequence)| sequence.len());
+        let mut alignment = Self::new(data_type, num_sites);
+        for (name, sequence) in sequences {
+            alignment.insert(labels.get_or_insert(&name), sequence)?;
+        }
+        Ok(alignment)
+    }
+
+    /// Sets the symbol of gaps.
+    pub fn with_gap(mut self, gap: u8) -> Self {
+        self.gap = gap;
+        self
+    }
+
+    /// Sets the symbol of missing data.
+    pub fn with_missing(mut self, missing: u8) -> Self {
+        self.missing = missing;
+        self
+    }
+
+    /// Adds the sequence of the taxon with the given label index.
+    ///
+    /// # Errors
+    /// Returns an [AlignmentError] if the sequence does not have [num_sites](Alignment::num_sites) characters
+    /// or the taxon already has a sequence.
+    pub fn insert(&mut self, index: LabelIndex, sequence: Vec<u8>) -> Result<(), AlignmentError> {
+        if sequence.len() != self.num_sites {
+            return Err(AlignmentError::LengthMismatch { index, expected: self.num_sites, found: sequence.len() });
+        }
+        if self.contains(index) {
+            return Err(AlignmentError::DuplicateSequence(index));
+        }
+
+        if self.sequences.len() <= index {
+            self.sequences.resize(index + 1, None);
+        }
+        self.sequences[index] = Some(sequence);
+        Ok(())
+    }
+
+    /// Returns the data type of the characters.
+    pub fn data_type(&self) -> DataType {
+        self.data_type
+    }
+
+    /// Returns the number of sites (length of every sequence).
+    pub fn num_sites(&self) -> usize {
+        self.num_sites
+    }
+
+    /// Returns the number of sequences (taxa with a sequence).
+    pub fn num_sequences(&self) -> usize {
+        self.sequences.iter().flatten().count()
+    }
+
+    /// Returns the symbol of gaps.
+    pub fn gap(&self) -> u8 {
+        self.gap
+    }
+
+    /// Returns the symbol of missing data.
+    pub fn missing(&self) -> u8 {
+        self.missing
+    }
+
+    /// Returns whether the taxon with the given label index has a sequence.
+    pub fn contains(&self, index: LabelIndex) -> bool {
+        self.sequence(index).is_some()
+    }
+
+    /// Returns the sequence of the taxon with the given label index, if any.
+    pub fn sequence(&self, index: LabelIndex) -> Option<&[u8]> {
+        self.sequences.get(index)?.as_deref()
+    }
+
+    /// Returns an iterator over the label indices and sequences, by increasing label index.
+    pub fn iter(&self) -> impl Iterator<Item = (LabelIndex, &[u8])> {
+        self.sequences.iter()
+            .enumerate()
+            .filter_map(|(index, sequence)| sequence.as_deref().map(|sequence| (index, sequence)))
+    }
+
+    /// Returns an iterator over the label indices and characters of the given site (column),
+    /// by increasing label index.
+    ///
+    /// # Panics
+    /// Panics if `site >= num_sites()`.
+    pub fn site(&self, site: usize) -> impl Iterator<Item = (LabelIndex, u8)> + '_ {
+        assert!(site < self.num_sites, "Site {} out of range for {} sites", site, self.num_sites);
+        self.iter().map(move |(index, sequence)| (index, sequence[site]))
+    }
+}
+
+/// Returns the set of bases (bits `A = 1`, `C = 2`, `G = 4`, `T = 8`) of an IUPAC nucleotide code
+/// (`U` as `T`, case-insensitive), if it is one.
+pub(crate) fn nucleotide_bases(code: u8) -> Option<u8> {
+    match code.to_ascii_uppercase() {
+        b'U' => Some(8),
+        code => IUPAC_CODES.iter().skip(1).position(|&c| c == code).map(|bases| bases as u8 + 1),
+    }
+}
+
+// =#========================================================================#=
+// ALIGNMENT ERROR
+// =#========================================================================#=
+/// Error when adding a sequence to an [Alignment].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AlignmentError {
+    /// Sequence of the taxon with the label index does not have the number of sites of the alignment
+    LengthMismatch { index: LabelIndex, expected: usize, found: usize },
+    /// Taxon with the label index already has a sequence
+    DuplicateSequence(LabelIndex),
+}
+
+impl fmt::Display for AlignmentError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AlignmentError::LengthMismatch { index, expected, found } =>
+                write!(f, "Sequence of taxon {} has {} characters, expected {}", index, found, expected),
+            AlignmentError::DuplicateSequence(index) => write!(f, "Taxon {} already has a sequence", index),
+        }
+    }
+}
+
+impl Error for AlignmentError {}
@@ -0,0 +1,302 @@
+//! Annotation module for metadata comments on vertices and branches.
+//!
+//! BEAST, MrBayes and TreeAnnotator annotate vertices with comments like
+//! `[&rate=0.01,height_95%_HPD={1.2,3.4}]`:
+//! - `Annotation`: Raw content of such comments (without `[&` and `]`) and, if parsed, its entries
+//! - `AnnotationValue`: Value of an entry (number, integer, text, list or range)
+//! - `DroppedMetadata`: Node label and annotations of a vertex that an edit of a tree suppressed
+
+use crate::model::tree::LabelIndex;
+use std::fmt;
+
+// =#========================================================================#=
+// ANNOTATION VALUE
+// =#========================================================================#=
+/// Value of an annotation entry `key=value`, typed as e.g. TreeAnnotator and StarBEAST write them:
+/// `height=1.5` (number), `count=3` (integer), `dmv={0.1,0.2}` (list), `height_95%_HPD={1.2,3.4}` (range).
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum AnnotationValue {
+    /// Numeric value, e.g. `0.01` or `1e-5`
+    Number(f64),
+    /// Any other value, with enclosing double quotes removed, e.g. `"100"` or `blue`;
+    /// empty for keys without value (e.g. `&R`)
+    Text(String),
+    /// List of values in braces, e.g. `{0.1,0.2}` of the population sizes (`dmv`) of a StarBEAST species tree
+    List(Vec<AnnotationValue>),
+    /// Integer value, e.g. `3`
+    Int(i64),
+    /// Interval of two numbers in braces for keys ending in `HPD` or `_range`,
+    /// e.g. `{1.2,3.4}` of `height_95%_HPD` (BEAST), `length_95%HPD` (MrBayes) or `height_range`
+    Range(f64, f64),
+}
+
+impl AnnotationValue {
+    /// Returns the number if this is a numeric (or integer) value, else `None`.
+    pub fn as_number(&self) -> Option<f64> {
+        match self {
+            AnnotationValue::Number(number) => Some(*number),
+            AnnotationValue::Int(int) => Some(*int as f64),
+            _ => None,
+        }
+    }
+
+    /// Returns the integer if this is an integer value, else `None`.
+    pub fn as_int(&self) -> Option<i64> {
+        match self {
+            AnnotationValue::Int(int) => Some(*int),
+            _ => None,
+        }
+    }
+
+    /// Returns the text if this is a text value, else `None`.
+    pub fn as_text(&self) -> Option<&str> {
+        match self {
+            AnnotationValue::Text(text) => Some(text),
+            _ => None,
+        }
+    }
+
+    /// Returns the values if this is a list, else `None`.
+    pub fn as_list(&self) -> Option<&[AnnotationValue]> {
+        match self {
+            AnnotationValue::List(values) => Some(values),
+            _ => None,
+        }
+    }
+
+    /// Returns the lower and upper bound if this is a range, else `None`.
+    pub fn as_range(&self) -> Option<(f64, f64)> {
+        match self {
+            AnnotationValue::Range(lower, upper) => Some((*lower, *upper)),
+            _ => None,
+        }
+    }
+
+    /// Returns whether this text must be quoted to be read back as the same text.
+    fn needs_quotes(text: &str) -> bool {
+        text.parse::<f64>().is_ok() || text.contains(|c: char| c.is_whitespace() || ",={}[]\"".contains(c))
+    }
+
+    /// Parses the value of the entry with the given key: a range if the key ends in `HPD` (e.g. `_95%_HPD`
+    /// of BEAST or `_95%HPD` of MrBayes) or `_range` and the value is a list of two numbers,
+    /// else as in [AnnotationValue::parse].
+    fn parse_entry(key: &str, value: &str) -> Self {
+        let value = AnnotationValue::parse(value);
+        if (key.ends_with("HPD") || key.ends_with("_range"))
+            && let Some([lower, upper]) = value.as_list()
+            && let (Some(lower), Some(upper)) = (lower.as_number(), upper.as_number()) {
+            return AnnotationValue::Range(lower, upper);
+        }
+        value
+    }
+
+    /// Parses a single value: a list in braces, an integer, a number, or text (quotes removed).
+    fn parse(value: &str) -> Self {
+        let value = value.trim();
+        if let Some(inner) = value.strip_prefix('{').and_then(|v| v.strip_suffix('}')) {
+            return AnnotationValue::List(split_top_level(inner).into_iter().map(AnnotationValue::parse).collect());
+        }
+        if let Ok(int) = value.parse::<i64>() {
+            return AnnotationValue::Int(int);
+        }
+        if let Ok(number) = value.parse::<f64>() {
+            return AnnotationValue::Number(number);
+        }
+        let text = value.strip_prefix('"').and_then(|v| v.strip_suffix('"')).unwrap_or(value);
+        AnnotationValue::Text(text.to_string())
+    }
+}
+
+/// Writes the value as it appears in a comment, e.g. `0.01`, `3`, `blue`, `"a b"` or `{1.2,3.4}`.
+impl fmt::Display for AnnotationValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AnnotationValue::Number(number) => write!(f, "{}", number),
+            AnnotationValue::Int(int) => write!(f, "{}", int),
+            AnnotationValue::Range(lower, upper) => write!(f, "{{{},{}}}", lower, upper),
+            AnnotationValue::Text(text) if AnnotationValue::needs_quotes(text) => write!(f, "\"{}\"", text),
+            AnnotationValue::Text(text) => write!(f, "{}", text),
+            AnnotationValue::List(values) => {
+                write!(f, "{{")?;
+                for (i, value) in values.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ",")?;
+                    }
+                    write!(f, "{}", value)?;
+                }
+                write!(f, "}}")
+            }
+        }
+    }
+}
+
+// =#========================================================================#=
+// ANNOTATION
+// =#========================================================================#=
+/// Metadata comment(s) `[&...]` of a vertex or branch.
+///
+/// Always keeps the raw content (without `[&` and `]`; several comments joined by `,`).
+/// Parsed annotations additionally provide their `key=value` entries in order of appearance.
+///
+/// # Example
+/// ```
+/// use nexus_parser::model::annotation::Annotation;
+///
+/// let annotation = Annotation::parse("rate=0.01,height_95%_HPD={1.2,3.4},!color=\"#ff0000\"");
+/// assert_eq!(annotation.get("rate").unwrap().as_number(), Some(0.01));
+/// assert_eq!(annotation.get("height_95%_HPD").unwrap().as_range(), Some((1.2, 3.4)));
+/// assert_eq!(annotation.get("!color").unwrap().as_text(), Some("#ff0000"));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Annotation {
+    raw: String,
+    entries: Option<Vec<(String, AnnotationValue)>>,
+}
+
+impl Annotation {
+    /// Creates an annotation keeping only the raw content.
+    pub fn from_raw(raw: String) -> Self {
+        Self { raw, entries: None }
+    }
+
+    /// Creates an annotation from the raw content, parsing its comma-separated `key=value` entries.
+    ///
+    /// Commas within braces `{...}` and double quotes do not separate entries.
+    pub fn parse(raw: &str) -> Self {
+        let entries = split_top_level(raw).into_iter()
+            .filter(|entry| !entry.trim().is_empty())
+            .map(|entry| match entry.split_once('=') {
+                Some((key, value)) => (key.trim().to_string(), AnnotationValue::parse_entry(key.trim(), value)),
+                None => (entry.trim().to_string(), AnnotationValue::Text(String::new())),
+            })
+            .collect();
+        Self { raw: raw.to_string(), entries: Some(entries) }
+    }
+
+    /// Returns the raw content (without `[&` and `]`).
+    pub fn raw(&self) -> &str {
+        &self.raw
+    }
+
+    /// Returns whether the entries have been parsed.
+    pub fn is_parsed(&self) -> bool {
+        self.entries.is_some()
+    }
+
+    /// Returns the parsed entries in order of appearance (empty if not parsed).
+    pub fn entries(&self) -> &[(String, AnnotationValue)] {
+        self.entries.as_deref().unwrap_or(&[])
+    }
+
+    /// Returns the value of the first entry with the given key, or `None` if absent (or not parsed).
+    pub fn get(&self, key: &str) -> Option<&AnnotationValue> {
+        self.entries().iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, value)| value)
+    }
+
+    /// Sets the value of the entry with the given key, replacing its first occurrence or appending it.
+    ///
+    /// Parses the raw content first if not parsed yet; the raw content is then rewritten from the entries
+    /// (values in their canonical form, e.g. `1e-5` as `0.00001`).
+    ///
+    /// # Example
+    /// ```
+    /// use nexus_parser::model::annotation::{Annotation, AnnotationValue};
+    ///
+    /// let mut annotation = Annotation::from_raw("rate=0.01".to_string());
+    /// annotation.set("height_95%_HPD", AnnotationValue::List(vec![AnnotationValue::Number(1.5), AnnotationValue::Number(2.0)]));
+    /// assert_eq!(annotation.raw(), "rate=0.01,height_95%_HPD={1.5,2}");
+    /// ```
+    pub fn set(&mut self, key: &str, value: AnnotationValue) {
+        let entries = self.entries.get_or_insert_with(|| Annotation::parse(&self.raw).entries.unwrap_or_default());
+        match entries.iter_mut().find(|(k, _)| k == key) {
+            Some((_, existing)) => *existing = value,
+            None => entries.push((key.to_string(), value)),
+        }
+
+        self.raw = entries.iter()
+            .map(|(key, value)| match value {
+                AnnotationValue::Text(text) if text.is_empty() => key.clone(),
+                _ => format!("{}={}", key, value),
+            })
+            .collect::<Vec<_>>()
+            .join(",");
+    }
+
+    /// Adds the entries of the other annotation whose keys are missing in this one,
+    /// e.g. to merge the annotations of two branches joined into one (this one taking precedence).
+    ///
+    /// Parses both annotations first if not parsed yet; the raw content is rewritten (see [Annotation::set])
+    /// only if entries are added.
+    ///
+    /// # Example
+    /// ```
+    /// use nexus_parser::model::annotation::Annotation;
+    ///
+    /// let mut annotation = Annotation::from_raw("rate=0.01".to_string());
+    /// annotation.merge(&Annotation::from_raw("rate=0.5,color=red".to_string()));
+    /// assert_eq!(annotation.raw(), "rate=0.01,color=red");
+    /// ```
+    pub fn merge(&mut self, other: &Annotation) {
+        if self.entries.is_none() {
+            self.entries = Annotation::parse(&self.raw).entries;
+        }
+        let parsed;
+        let other_entries = match &other.entries {
+            Some(entries) => entries,
+            None => {
+                parsed = Annotation::parse(&other.raw).entries.unwrap_or_default();
+                &parsed
+            }
+        };
+
+        for (key, value) in other_entries {
+            if self.get(key).is_none() {
+                self.set(key, value.clone());
+            }
+        }
+    }
+}
+
+/// Splits at commas not enclosed in braces or double quotes.
+fn split_top_level(s: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut depth = 0usize;
+    let mut in_quote = false;
+    let mut start = 0;
+    for (i, c) in s.char_indices() {
+        match c {
+            '"' => in_quote = !in_quote,
+            '{' if !in_quote => depth += 1,
+            '}' if !in_quote => depth = depth.saturating_sub(1),
+            ',' if !in_quote && depth == 0 => {
+                parts.push(&s[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    parts.push(&s[start..]);
+    parts
+}
+
+// =#========================================================================#=
+// DROPPED METADATA
+// =#========================================================================#=
+/// Node label and annotations of a vertex suppressed by an edit of a tree, which have no vertex or branch to stay with,
+/// e.g. of a binary root suppressed by [Tree::unroot](crate::model::tree::Tree::unroot)
+/// (see [Tree::dropped_metadata](crate::model::tree::Tree::dropped_metadata)).
+#[derive(Debug, Clone, PartialEq)]
+pub struct DroppedMetadata {
+    /// Label indices of the taxa below the vertex before the edit, sorted
+    pub taxa: Vec<LabelIndex>,
+    /// Node label of the vertex (e.g. a support value)
+    pub node_label: Option<String>,
+    /// Annotation of the vertex
+    pub annotation: Option<Annotation>,
+    /// Annotation of the branch above the vertex, if that branch was dropped rather than merged
+    pub branch_annotation: Option<Annotation>,
+}
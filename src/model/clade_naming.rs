@@ -0,0 +1,135 @@
+//! Clade naming module deriving human-readable names for taxon sets.
+//!
+//! - `CladeNamer`: Names clades (e.g. in clade tables or annotations) alphabetically or via a [Taxonomy].
+
+use crate::model::leaf_label_map::LeafLabelMap;
+use crate::model::taxon_set::TaxonSet;
+use crate::model::taxonomy::Taxonomy;
+use std::collections::HashMap;
+
+// =#========================================================================#=
+// CLADE NAMER
+// =#========================================================================#=
+/// Derives human-readable names for clades given as [TaxonSet]s.
+///
+/// # Naming Scheme
+/// - A single taxon is named by its label
+/// - With a [Taxonomy], a clade is named by the lowest-rank group shared by all its taxa,
+///   e.g. `Apterygidae`; if the clade contains only some of the group's taxa (of the [LeafLabelMap]),
+///   the name is qualified, e.g. `Apterygidae (part, 3/5)`
+/// - Otherwise, a clade is named by its two alphabetically smallest labels plus its size,
+///   e.g. `Apteryx haastii+Apteryx owenii (5 taxa)`
+///
+/// # Example
+/// ```
+/// use nexus_parser::model::clade_naming::CladeNamer;
+/// use nexus_parser::model::leaf_label_map::LeafLabelMap;
+/// use nexus_parser::model::taxon_set::TaxonSet;
+///
+/// let mut labels = LeafLabelMap::new(3);
+/// let kea = labels.get_or_insert("Nestor notabilis");
+/// let kaka = labels.get_or_insert("Nestor meridionalis");
+/// let kakapo = labels.get_or_insert("Strigops habroptilus");
+///
+/// let namer = CladeNamer::new(&labels);
+/// let clade = TaxonSet::from_indices(3, [kea, kaka, kakapo]);
+/// assert_eq!(namer.name(&clade), "Nestor meridionalis+Nestor notabilis (3 taxa)");
+/// ```
+pub struct CladeNamer<'a> {
+    leaf_label_map: &'a LeafLabelMap,
+    taxonomy: Option<&'a Taxonomy>,
+}
+
+impl<'a> CladeNamer<'a> {
+    /// Creates a new [CladeNamer] naming clades alphabetically based on the given [LeafLabelMap].
+    pub fn new(leaf_label_map: &'a LeafLabelMap) -> Self {
+        CladeNamer {
+            leaf_label_map,
+            taxonomy: None,
+        }
+    }
+
+    /// Sets a [Taxonomy] to name clades by shared groups where possible.
+    pub fn with_taxonomy(mut self, taxonomy: &'a Taxonomy) -> Self {
+        self.taxonomy = Some(taxonomy);
+        self
+    }
+
+    /// Returns the name of the given clade.
+    pub fn name(&self, clade: &TaxonSet) -> String {
+        match clade.len() {
+            0 => "(empty)".to_string(),
+            1 => self.label(clade.first().unwrap()).to_string(),
+            _ => self.taxonomy_name(clade)
+                .unwrap_or_else(|| self.alphabetical_name(clade)),
+        }
+    }
+
+    /// Returns the names of the given clades, made unique by appending ` #2`, ` #3`, ...
+    /// to repeated names (in order of appearance).
+    pub fn name_all(&self, clades: &[TaxonSet]) -> Vec<String> {
+        let mut counts: HashMap<String, usize> = HashMap::new();
+        clades.iter()
+            .map(|clade| {
+                let name = self.name(clade);
+                let count = counts.entry(name.clone()).or_insert(0);
+                *count += 1;
+                if *count == 1 { name } else { format!("{} #{}", name, count) }
+            })
+            .collect()
+    }
+
+    /// Names clade by the lowest-rank taxonomic group shared by all its taxa, if any.
+    fn taxonomy_name(&self, clade: &TaxonSet) -> Option<String> {
+        let taxonomy = self.taxonomy?;
+
+        for rank_index in 0..taxonomy.ranks().len() {
+            let Some(group) = self.shared_group(taxonomy, clade, rank_index) else {
+                continue;
+            };
+
+            // Compare with all taxa of the group present in the label map
+            let group_size = self.leaf_label_map.labels().iter()
+                .filter(|label| taxonomy.group(label, rank_index) == Some(group))
+                .count();
+
+            return if group_size == clade.len() {
+                Some(group.to_string())
+            } else {
+                Some(format!("{} (part, {}/{})", group, clade.len(), group_size))
+            };
+        }
+
+        None
+    }
+
+    /// Returns the group at the given rank if all taxa of the clade are assigned to it.
+    fn shared_group(&self, taxonomy: &'a Taxonomy, clade: &TaxonSet, rank_index: usize) -> Option<&'a str> {
+        let mut shared: Option<&str> = None;
+        for index in clade.iter() {
+            let group = taxonomy.group(self.label(index), rank_index)?;
+            match shared {
+                None => shared = Some(group),
+                Some(shared_group) if shared_group != group => return None,
+                _ => {}
+            }
+        }
+        shared
+    }
+
+    /// Names clade by its two alphabetically smallest labels and its size.
+    fn alphabetical_name(&self, clade: &TaxonSet) -> String {
+        let mut labels: Vec<&str> = clade.iter().map(|index| self.label(index)).collect();
+        labels.sort_unstable();
+
+        if labels.len() == 2 {
+            format!("{}+{}", labels[0], labels[1])
+        } else {
+            format!("{}+{} ({} taxa)", labels[0], labels[1], labels.len())
+        }
+    }
+
+    fn label(&self, index: usize) -> &'a str {
+        self.leaf_label_map.get_label(index).unwrap_or("?")
+    }
+}
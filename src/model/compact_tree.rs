@@ -0,0 +1,386 @@
+//! Compact tree module for a structure-of-arrays representation of trees.
+//!
+//! - `CompactTree`: Tree stored as parallel arrays (parents, first children, next siblings,
+//!   branch lengths, label indices) with vertices numbered in pre-order
+//! - `CompactPostOrderIter`: Post-order traversal without a stack
+//! - `CompactIndex`: Integer type the indices are stored as (`u32` by default)
+//! - `CompactFloat`: Float type the branch lengths are stored as (`f64` by default, or `f32`)
+//!
+//! Compared to the [Vertex](crate::model::vertex::Vertex) arena of a [Tree], every array holds
+//! one plain value per vertex and no vertex owns a children list, so tight loops over one
+//! property (e.g. summing branch lengths or collecting leaves) only touch the memory they need.
+//! Indices are stored as `u32` unless chosen otherwise, halving the index arrays on 64-bit machines
+//! compared to `usize`; the API still takes and returns [TreeIndex] and [LabelIndex].
+//! Branch lengths can be stored as `f32` (about 7 significant digits, plenty for posterior samples)
+//! to halve their array as well; the API still returns them as `f64`.
+
+use crate::model::tree::{LabelIndex, Tree, TreeIndex};
+use crate::model::vertex::BranchLength;
+use std::fmt::Debug;
+
+// =#========================================================================#=
+// COMPACT INDEX
+// =#========================================================================#=
+/// Unsigned integer type the indices of a [CompactTree] are stored as:
+/// `u16`, `u32` (default), `u64` or `usize`.
+///
+/// Its maximal value marks a missing index (e.g. the parent of the root),
+/// so a tree can have at most `MAX - 1` vertices and label indices up to `MAX - 1`.
+pub trait CompactIndex: Copy + Eq + Debug {
+    /// Value marking a missing index
+    const NONE: Self;
+
+    /// Converts an index into the stored type.
+    ///
+    /// # Panics
+    /// Panics if the index is too large for the type.
+    fn from_index(index: usize) -> Self;
+
+    /// Converts a stored index back.
+    fn to_index(self) -> usize;
+}
+
+macro_rules! impl_compact_index {
+    ($($t:ty),*) => {$(
+        impl CompactIndex for $t {
+            const NONE: Self = <$t>::MAX;
+
+            #[inline(always)]
+            fn from_index(index: usize) -> Self {
+                match <$t>::try_from(index) {
+                    Ok(index) if index != Self::NONE => index,
+                    _ => panic!("Index {} too large for {}", index, stringify!($t)),
+                }
+            }
+
+            #[inline(always)]
+            fn to_index(self) -> usize {
+                self as usize
+            }
+        }
+    )*};
+}
+
+impl_compact_index!(u16, u32, u64, usize);
+
+// =#========================================================================#=
+// COMPACT FLOAT
+// =#========================================================================#=
+/// Float type the branch lengths of a [CompactTree] are stored as: `f64` (default) or `f32`.
+///
+/// `NaN` marks a missing branch length.
+pub trait CompactFloat: Copy + Debug {
+    /// Converts a branch length into the stored type
+    /// (rounding to the nearest `f32` and saturating at `f32::MAX`, so finite lengths stay finite).
+    fn from_length(length: f64) -> Self;
+
+    /// Converts a stored branch length back.
+    fn to_length(self) -> f64;
+}
+
+impl CompactFloat for f64 {
+    #[inline(always)]
+    fn from_length(length: f64) -> Self {
+        length
+    }
+
+    #[inline(always)]
+    fn to_length(self) -> f64 {
+        self
+    }
+}
+
+impl CompactFloat for f32 {
+    #[inline(always)]
+    fn from_length(length: f64) -> Self {
+        length.clamp(-f32::MAX as f64, f32::MAX as f64) as f32
+    }
+
+    #[inline(always)]
+    fn to_length(self) -> f64 {
+        self as f64
+    }
+}
+
+// =#========================================================================#=
+// COMPACT TREE
+// =#========================================================================#=
+/// A phylogenetic tree stored as parallel arrays, indexed by [TreeIndex].
+///
+/// Vertices are numbered in pre-order: the root is `0`, every vertex comes before its children,
+/// and children are listed (from first child over next siblings) in the order of the [Tree] converted.
+/// The tree is immutable; convert it back with [CompactTree::to_tree] to edit it.
+/// The name and whether the tree is rooted are kept, node labels and annotations are not.
+///
+/// Indices are stored as the [CompactIndex] type `I`, `u32` by default, and branch lengths
+/// as the [CompactFloat] type `F`, `f64` by default; other types are chosen by converting with `From`,
+/// e.g. `CompactTree::<u64>::from(&tree)` or `CompactTree::<u32, f32>::from(&tree)`.
+///
+/// # Example
+/// ```
+/// use nexus_parser::model::compact_tree::CompactTree;
+/// use nexus_parser::parse_newick_str;
+///
+/// let (trees, labels) = parse_newick_str("((A:1,B:1):2,C:3);").unwrap();
+/// let compact = CompactTree::from_tree(&trees[0]);
+///
+/// assert_eq!(compact.root(), 0);
+/// assert_eq!(compact.children(0).collect::<Vec<_>>(), vec![1, 4]);
+/// assert_eq!(compact.label_index(4), labels.get_index("C"));
+/// assert_eq!(compact.post_order_iter().collect::<Vec<_>>(), vec![2, 3, 1, 4, 0]);
+/// assert_eq!(compact.total_branch_length(), 7.0);
+/// assert_eq!(compact.to_tree().total_branch_length(), 7.0);
+///
+/// // Indices stored as u16 for small trees
+/// let small = CompactTree::<u16>::from(&trees[0]);
+/// assert_eq!(small.post_order_iter().collect::<Vec<_>>(), vec![2, 3, 1, 4, 0]);
+///
+/// // Branch lengths stored as f32
+/// let single = CompactTree::<u32, f32>::from(&trees[0]);
+/// assert_eq!(single.branch_length(1), Some(2.0));
+/// ```
+#[derive(Debug, Clone)]
+pub struct CompactTree<I: CompactIndex = u32, F: CompactFloat = f64> {
+    /// Number of leaves the tree was initialized to hold, see [Tree::num_leaves_init]
+    num_leaves_init: usize,
+    /// Parent of each vertex (`I::NONE` for the root)
+    parents: Vec<I>,
+    /// First child of each vertex (`I::NONE` for leaves)
+    first_children: Vec<I>,
+    /// Next sibling of each vertex (`I::NONE` for last children and the root)
+    next_siblings: Vec<I>,
+    /// Branch length above each vertex (`NaN` if not given, always for the root)
+    branch_lengths: Vec<F>,
+    /// Label index of each leaf (`I::NONE` for the root and internal vertices)
+    label_indices: Vec<I>,
+    /// Name of the tree, if any
+    name: Option<String>,
+    /// Whether the root is meaningful, see [Tree::is_rooted]
+    rooted: bool,
+}
+
+// ============================================================================
+// Conversion (pub)
+// ============================================================================
+impl CompactTree {
+    /// Converts the tree into parallel arrays with `u32` indices, numbering its vertices in pre-order
+    /// (see `From<&Tree>` for other index types).
+    ///
+    /// # Panics
+    /// Panics if the root of the tree is not set, or the tree has `u32::MAX` or more vertices or label indices.
+    pub fn from_tree(tree: &Tree) -> Self {
+        Self::from(tree)
+    }
+}
+
+impl<I: CompactIndex, F: CompactFloat> From<&Tree> for CompactTree<I, F> {
+    /// Converts the tree into parallel arrays, numbering its vertices in pre-order.
+    ///
+    /// # Panics
+    /// Panics if the root of the tree is not set, or it has too many vertices or too large label indices for `I`.
+    fn from(tree: &Tree) -> Self {
+        let pre_order: Vec<_> = tree.pre_order_iter().collect();
+        let num_vertices = pre_order.len();
+        let mut compact = Self {
+            num_leaves_init: tree.num_leaves_init(),
+            parents: vec![I::NONE; num_vertices],
+            first_children: vec![I::NONE; num_vertices],
+            next_siblings: vec![I::NONE; num_vertices],
+            branch_lengths: vec![F::from_length(f64::NAN); num_vertices],
+            label_indices: vec![I::NONE; num_vertices],
+            name: tree.name().cloned(),
+            rooted: tree.is_rooted(),
+        };
+
+        // New index of each vertex of the tree, assigned in pre-order (parents before children)
+        let mut new_indices = vec![I::NONE; tree.num_vertices()];
+        for (new_index, vertex) in pre_order.iter().enumerate() {
+            new_indices[vertex.index()] = I::from_index(new_index);
+        }
+
+        for (new_index, vertex) in pre_order.iter().enumerate() {
+            if let Some(length) = vertex.branch_length() {
+                compact.branch_lengths[new_index] = F::from_length(*length);
+            }
+            if let Some(label_index) = vertex.label_index() {
+                compact.label_indices[new_index] = I::from_index(label_index);
+            }
+            let children = vertex.children().unwrap_or(&[]);
+            for &child in children {
+                compact.parents[new_indices[child].to_index()] = new_indices[vertex.index()];
+            }
+            if let Some(&first) = children.first() {
+                compact.first_children[new_index] = new_indices[first];
+            }
+            for pair in children.windows(2) {
+                compact.next_siblings[new_indices[pair[0]].to_index()] = new_indices[pair[1]];
+            }
+        }
+        compact
+    }
+}
+
+impl<I: CompactIndex, F: CompactFloat> CompactTree<I, F> {
+    /// Converts back into a [Tree] (with vertices numbered in post-order).
+    ///
+    /// Branch lengths stored as `f32` come back rounded; lengths beyond `f32::MAX` were saturated
+    /// to `f32::MAX` when converting the tree (see [CompactFloat::from_length]), so they stay finite.
+    pub fn to_tree(&self) -> Tree {
+        let mut tree = Tree::with_capacity(self.num_leaves_init, self.num_vertices())
+            .with_rooted(self.rooted);
+        if let Some(name) = &self.name {
+            tree = tree.with_name(name.clone());
+        }
+
+        // Index in the new tree of each vertex
+        let mut tree_indices = vec![0; self.num_vertices()];
+        for index in self.post_order_iter() {
+            let children = self.children(index).map(|child| tree_indices[child]).collect();
+            tree_indices[index] = if index == self.root() {
+                tree.add_root(children)
+            } else {
+                let branch_length = self.branch_length(index).map(BranchLength::new_unchecked);
+                match self.label_index(index) {
+                    Some(label_index) => tree.add_leaf(branch_length, label_index),
+                    None => tree.add_internal_vertex(children, branch_length),
+                }
+            };
+        }
+        tree
+    }
+}
+
+// ============================================================================
+// Getters / Accessors (pub)
+// ============================================================================
+impl<I: CompactIndex, F: CompactFloat> CompactTree<I, F> {
+    /// Returns the number of vertices.
+    pub fn num_vertices(&self) -> usize {
+        self.parents.len()
+    }
+
+    /// Returns the number of leaves.
+    pub fn num_leaves(&self) -> usize {
+        self.label_indices.iter().filter(|&&label_index| label_index != I::NONE).count()
+    }
+
+    /// Returns the number of leaves the tree was initialized to hold, see [Tree::num_leaves_init].
+    pub fn num_leaves_init(&self) -> usize {
+        self.num_leaves_init
+    }
+
+    /// Returns the index of the root, which is always `0`.
+    pub fn root(&self) -> TreeIndex {
+        0
+    }
+
+    /// Returns the name of the tree, or `None` if not set.
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// Returns whether the root of the tree is meaningful, see [Tree::is_rooted].
+    pub fn is_rooted(&self) -> bool {
+        self.rooted
+    }
+
+    /// Returns the parent of the vertex, or `None` for the root.
+    pub fn parent(&self, index: TreeIndex) -> Option<TreeIndex> {
+        some_index(self.parents[index])
+    }
+
+    /// Returns the first child of the vertex, or `None` for leaves.
+    pub fn first_child(&self, index: TreeIndex) -> Option<TreeIndex> {
+        some_index(self.first_children[index])
+    }
+
+    /// Returns the next sibling of the vertex, or `None` for last children and the root.
+    pub fn next_sibling(&self, index: TreeIndex) -> Option<TreeIndex> {
+        some_index(self.next_siblings[index])
+    }
+
+    /// Returns an iterator over the children of the vertex, in order.
+    pub fn children(&self, index: TreeIndex) -> impl Iterator<Item = TreeIndex> + '_ {
+        std::iter::successors(self.first_child(index), |&child| self.next_sibling(child))
+    }
+
+    /// Returns the branch length above the vertex, if given (never for the root).
+    pub fn branch_length(&self, index: TreeIndex) -> Option<f64> {
+        Some(self.branch_lengths[index].to_length()).filter(|length| !length.is_nan())
+    }
+
+    /// Returns the label index of the vertex if it is a leaf, `None` otherwise.
+    pub fn label_index(&self, index: TreeIndex) -> Option<LabelIndex> {
+        some_index(self.label_indices[index])
+    }
+
+    /// Returns whether the vertex is a leaf.
+    pub fn is_leaf(&self, index: TreeIndex) -> bool {
+        self.label_indices[index] != I::NONE
+    }
+
+    /// Returns the sum of all branch lengths (vertices without branch length count as `0`).
+    pub fn total_branch_length(&self) -> f64 {
+        self.branch_lengths.iter()
+            .map(|length| length.to_length())
+            .filter(|length| !length.is_nan())
+            .sum()
+    }
+}
+
+// ============================================================================
+// Traversals (pub)
+// ============================================================================
+impl<I: CompactIndex, F: CompactFloat> CompactTree<I, F> {
+    /// Returns an iterator over the vertices in pre-order (parents before children),
+    /// which is the order of their indices.
+    pub fn pre_order_iter(&self) -> std::ops::Range<TreeIndex> {
+        0..self.num_vertices()
+    }
+
+    /// Returns an iterator over the vertices in post-order (children before parents, children in order),
+    /// walking along parents and siblings without a stack.
+    pub fn post_order_iter(&self) -> CompactPostOrderIter<'_, I, F> {
+        CompactPostOrderIter {
+            tree: self,
+            next: (self.num_vertices() > 0).then(|| self.leftmost_leaf(self.root())),
+        }
+    }
+
+    /// Returns the first leaf below the vertex (following first children).
+    fn leftmost_leaf(&self, mut index: TreeIndex) -> TreeIndex {
+        while let Some(child) = self.first_child(index) {
+            index = child;
+        }
+        index
+    }
+}
+
+/// Post-order iterator over the vertices of a [CompactTree], see [CompactTree::post_order_iter].
+pub struct CompactPostOrderIter<'a, I: CompactIndex = u32, F: CompactFloat = f64> {
+    tree: &'a CompactTree<I, F>,
+    next: Option<TreeIndex>,
+}
+
+impl<I: CompactIndex, F: CompactFloat> Iterator for CompactPostOrderIter<'_, I, F> {
+    type Item = TreeIndex;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let index = self.next?;
+        // After a vertex follows the first leaf below its next sibling, or else its parent
+        self.next = match self.tree.next_sibling(index) {
+            Some(sibling) => Some(self.tree.leftmost_leaf(sibling)),
+            None => self.tree.parent(index),
+        };
+        Some(index)
+    }
+}
+
+// ============================================================================
+// Little Helpers
+// ============================================================================
+/// Returns the stored index unless it marks a missing one.
+#[inline(always)]
+fn some_index<I: CompactIndex>(index: I) -> Option<usize> {
+    (index != I::NONE).then(|| index.to_index())
+}
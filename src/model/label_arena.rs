@@ -0,0 +1,122 @@
+//! Label arena module for trees of several files on the same taxa.
+//!
+//! - `LabelArena`: Interns leaf labels across files, so [LabelIndex] values are stable
+//!   and trees of different files can be compared directly (e.g. their bipartitions).
+
+use crate::model::leaf_label_map::LeafLabelMap;
+use crate::model::tree::{LabelIndex, Tree};
+
+/// Default guess for number of labels, when unknown
+const DEFAULT_NUM_LABELS_GUESS: usize = 10;
+
+// =#========================================================================#=
+// LABEL ARENA
+// =#========================================================================#=
+/// Interns leaf labels shared by trees of several files (or other sources).
+///
+/// Each file parsed on its own gets its own [LeafLabelMap], whose indices depend on
+/// the order of its TAXA block or of the leaves in its first tree. Adopting the trees
+/// of each file into the same arena moves their leaves to the arena's indices instead:
+/// the first occurrence of a label fixes its index, later files reuse it and only append new labels.
+///
+/// Adopted trees range over all labels of the arena at the time of adoption
+/// (see [Tree::num_leaves_init]), so trees of files on the same taxa have the same
+/// number of leaves and comparable clades.
+///
+/// # Example
+/// ```
+/// use nexus_parser::model::label_arena::LabelArena;
+/// use nexus_parser::model::taxon_set::TaxonSet;
+/// use nexus_parser::parse_newick_str;
+///
+/// let mut arena = LabelArena::new();
+/// let (mut first, labels) = parse_newick_str("((A,B),C);").unwrap();
+/// arena.adopt(&mut first, &labels);
+/// let (mut second, labels) = parse_newick_str("((C,B),A);").unwrap();
+/// arena.adopt(&mut second, &labels);
+///
+/// // Indices refer to the same labels in both trees
+/// assert_eq!(arena.num_labels(), 3);
+/// let (a, b, c) = (arena.intern("A"), arena.intern("B"), arena.intern("C"));
+/// assert!(first[0].is_monophyletic(&TaxonSet::from_indices(3, [a, b])));
+/// assert!(second[0].is_monophyletic(&TaxonSet::from_indices(3, [b, c])));
+/// ```
+#[derive(Debug, Clone)]
+pub struct LabelArena {
+    labels: LeafLabelMap,
+}
+
+impl LabelArena {
+    /// Creates a new, empty arena.
+    pub fn new() -> Self {
+        Self::with_capacity(DEFAULT_NUM_LABELS_GUESS)
+    }
+
+    /// Creates a new, empty arena with pre-allocated capacity for `num_labels` labels.
+    pub fn with_capacity(num_labels: usize) -> Self {
+        Self { labels: LeafLabelMap::new(num_labels) }
+    }
+
+    /// Creates an arena starting with the labels of the given [LeafLabelMap] (keeping their indices).
+    pub fn from_leaf_label_map(labels: LeafLabelMap) -> Self {
+        Self { labels }
+    }
+
+    /// Returns the stable index of the given label, interning it first if new.
+    pub fn intern(&mut self, label: &str) -> LabelIndex {
+        self.labels.get_or_insert(label)
+    }
+
+    /// Moves the leaves of the given trees from their own `leaf_label_map` to the labels of this arena,
+    /// interning labels not seen before.
+    ///
+    /// # Arguments
+    /// * `trees` - Trees whose leaves refer to `leaf_label_map`
+    /// * `leaf_label_map` - Labels the trees were parsed with
+    ///
+    /// # Returns
+    /// The mapping from indices of `leaf_label_map` to indices of this arena
+    ///
+    /// # Panics
+    /// Panics if a leaf refers to a label index not in `leaf_label_map`.
+    pub fn adopt(&mut self, trees: &mut [Tree], leaf_label_map: &LeafLabelMap) -> Vec<LabelIndex> {
+        let mapping: Vec<LabelIndex> = leaf_label_map.labels().iter()
+            .map(|label| self.labels.get_or_insert(label))
+            .collect();
+        for tree in trees.iter_mut() {
+            tree.remap_label_indices(&mapping, self.num_labels());
+        }
+        mapping
+    }
+
+    /// Returns the index of the given label, if interned.
+    pub fn get_index(&self, label: &str) -> Option<LabelIndex> {
+        self.labels.get_index(label)
+    }
+
+    /// Returns the label with the given index, if interned.
+    pub fn get_label(&self, index: LabelIndex) -> Option<&str> {
+        self.labels.get_label(index)
+    }
+
+    /// Returns the number of interned labels.
+    pub fn num_labels(&self) -> usize {
+        self.labels.num_labels()
+    }
+
+    /// Returns the interned labels as [LeafLabelMap], e.g. to write or print adopted trees.
+    pub fn leaf_label_map(&self) -> &LeafLabelMap {
+        &self.labels
+    }
+
+    /// Consumes the arena and returns the interned labels as [LeafLabelMap].
+    pub fn into_leaf_label_map(self) -> LeafLabelMap {
+        self.labels
+    }
+}
+
+impl Default for LabelArena {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,187 @@
+//! Lowest common ancestor module for many MRCA queries on the same tree.
+//!
+//! - `LcaIndex`: Euler tour with sparse table over a tree, answering LCA queries in constant time
+//! - `Tree::lca_index`: Preprocesses a tree in `O(n log n)` time
+//!
+//! For a single query, [Tree::mrca] is cheaper; the index pays off when querying
+//! the same tree many times (e.g. counting clade support over a sample).
+
+use crate::model::tree::{LabelIndex, Tree, TreeIndex};
+
+/// Marker for vertices not reachable from the root
+const NOT_IN_TREE: usize = usize::MAX;
+
+// =#========================================================================#=
+// LCA INDEX
+// =#========================================================================#=
+/// Preprocessed structure answering lowest common ancestor (LCA) queries of a tree in `O(1)` time.
+///
+/// Stores the Euler tour of the tree (vertices in order of visits, including returns from children)
+/// and a sparse table of the shallowest vertex in each range of the tour of length a power of two.
+/// The LCA of two vertices is the shallowest vertex visited between their first visits,
+/// found by the minimum of two overlapping ranges.
+///
+/// The index reflects the tree at the time of creation and must be recreated after the tree changes.
+///
+/// # Example
+/// ```
+/// use nexus_parser::model::tree::Tree;
+///
+/// // ((0,1),2)
+/// let mut tree = Tree::new(3);
+/// let a = tree.add_leaf(None, 0);
+/// let b = tree.add_leaf(None, 1);
+/// let c = tree.add_leaf(None, 2);
+/// let ab = tree.add_internal_vertex(vec![a, b], None);
+/// let root = tree.add_root(vec![ab, c]);
+///
+/// let lca = tree.lca_index();
+/// assert_eq!(lca.query(a, b), ab);
+/// assert_eq!(lca.query(b, c), root);
+/// assert_eq!(lca.query(ab, a), ab);
+/// assert_eq!(lca.mrca(&[0, 1]), ab);
+/// ```
+#[derive(Debug, Clone)]
+pub struct LcaIndex {
+    /// Vertices in order of the Euler tour
+    euler_tour: Vec<TreeIndex>,
+    /// Depth of each vertex, indexed by [TreeIndex]
+    depths: Vec<usize>,
+    /// Position of the first visit of each vertex in the Euler tour, indexed by [TreeIndex]
+    first_visits: Vec<usize>,
+    /// Leaf of each taxon, indexed by [LabelIndex]
+    leaves: Vec<Option<TreeIndex>>,
+    /// Level `k` holds, for each position `i`, the position of the shallowest vertex in the tour range `[i, i + 2^k)`
+    sparse_table: Vec<Vec<usize>>,
+}
+
+impl LcaIndex {
+    /// Creates the index of the given tree.
+    fn new(tree: &Tree) -> Self {
+        let num_vertices = tree.num_vertices();
+        let mut euler_tour = Vec::with_capacity(2 * num_vertices);
+        let mut depths = vec![0; num_vertices];
+        let mut first_visits = vec![NOT_IN_TREE; num_vertices];
+        let mut leaves = vec![None; tree.num_leaves_init()];
+
+        // Iterative depth-first traversal, keeping the position in the children of each vertex on the stack
+        let root = tree.root().index();
+        let mut stack: Vec<(TreeIndex, usize)> = vec![(root, 0)];
+        first_visits[root] = 0;
+        euler_tour.push(root);
+        while let Some((index, next_child)) = stack.pop() {
+            let children = tree[index].children().unwrap_or(&[]);
+            if let Some(&child) = children.get(next_child) {
+                stack.push((index, next_child + 1));
+                stack.push((child, 0));
+                depths[child] = depths[index] + 1;
+                first_visits[child] = euler_tour.len();
+                euler_tour.push(child);
+                if let Some(label_index) = tree[child].label_index() {
+                    if label_index >= leaves.len() {
+                        leaves.resize(label_index + 1, None);
+                    }
+                    leaves[label_index] = Some(child);
+                }
+            } else if let Some(&(parent, _)) = stack.last() {
+                // Return to parent after its child is done
+                euler_tour.push(parent);
+            }
+        }
+
+        let mut index = Self { euler_tour, depths, first_visits, leaves, sparse_table: Vec::new() };
+        index.build_sparse_table();
+        index
+    }
+
+    /// Fills the sparse table from the Euler tour, doubling the range length per level.
+    fn build_sparse_table(&mut self) {
+        let tour_length = self.euler_tour.len();
+        self.sparse_table.push((0..tour_length).collect());
+        let mut range = 1;
+        while 2 * range <= tour_length {
+            let previous = self.sparse_table.last().unwrap();
+            let level = (0..=tour_length - 2 * range)
+                .map(|i| self.shallower(previous[i], previous[i + range]))
+                .collect();
+            self.sparse_table.push(level);
+            range *= 2;
+        }
+    }
+
+    // ============================================================================
+    // Queries (pub)
+    // ============================================================================
+    /// Returns the lowest common ancestor of the given vertices (the vertex itself if both are equal,
+    /// the ancestor if one is an ancestor of the other).
+    ///
+    /// # Panics
+    /// Panics if a vertex is not in the tree.
+    pub fn query(&self, u: TreeIndex, v: TreeIndex) -> TreeIndex {
+        let (mut from, mut to) = (self.first_visit(u), self.first_visit(v));
+        if from > to {
+            std::mem::swap(&mut from, &mut to);
+        }
+
+        // Two ranges of length 2^level cover [from, to]
+        let level = (to - from + 1).ilog2() as usize;
+        let positions = &self.sparse_table[level];
+        let position = self.shallower(positions[from], positions[to + 1 - (1 << level)]);
+        self.euler_tour[position]
+    }
+
+    /// Returns the most recent common ancestor of the given taxa, like [Tree::mrca],
+    /// using `|taxa| - 1` queries.
+    ///
+    /// # Panics
+    /// Panics if `taxa` is empty or contains a taxon that is not a leaf of the tree.
+    pub fn mrca(&self, taxa: &[LabelIndex]) -> TreeIndex {
+        assert!(!taxa.is_empty(), "MRCA requires at least one taxon");
+        taxa.iter()
+            .map(|&taxon| self.leaf(taxon)
+                .unwrap_or_else(|| panic!("Taxon {} is not a leaf of the tree", taxon)))
+            .reduce(|lca, leaf| self.query(lca, leaf))
+            .unwrap()
+    }
+
+    /// Returns the leaf of the given taxon, if in the tree.
+    pub fn leaf(&self, taxon: LabelIndex) -> Option<TreeIndex> {
+        self.leaves.get(taxon).copied().flatten()
+    }
+
+    /// Returns the depth of the given vertex, i.e. its number of ancestors (`0` for the root).
+    ///
+    /// # Panics
+    /// Panics if the vertex is not in the tree.
+    pub fn depth(&self, index: TreeIndex) -> usize {
+        self.first_visit(index);
+        self.depths[index]
+    }
+
+    // ============================================================================
+    // Little Helpers
+    // ============================================================================
+    /// Returns the position of the first visit of the given vertex in the Euler tour.
+    fn first_visit(&self, index: TreeIndex) -> usize {
+        match self.first_visits.get(index) {
+            Some(&position) if position != NOT_IN_TREE => position,
+            _ => panic!("Vertex {} is not in the tree", index),
+        }
+    }
+
+    /// Returns the position (of the two in the Euler tour) with the shallower vertex.
+    fn shallower(&self, i: usize, j: usize) -> usize {
+        if self.depths[self.euler_tour[j]] < self.depths[self.euler_tour[i]] { j } else { i }
+    }
+}
+
+// =#========================================================================#=
+// TREE EXTENSION
+// =#========================================================================#=
+impl Tree {
+    /// Returns an [LcaIndex] of this tree, preprocessed in `O(n log n)` time for `n` vertices,
+    /// to answer lowest common ancestor queries in constant time.
+    pub fn lca_index(&self) -> LcaIndex {
+        LcaIndex::new(self)
+    }
+}
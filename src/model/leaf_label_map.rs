@@ -1,10 +1,26 @@
 //! Leaf label module for phylogenetic tree representation.
 //!
-//! - `LeafLabelMap`: Joined storage and lookup for leaf labels for trees on same labels.
+//! - `LeafLabelMap`: Joined storage and lookup for leaf labels for trees on same labels,
+//!   owned (default) or borrowed from the parsed input where possible (`LeafLabelMap<Cow<str>>`).
+//! - `LeafLabel`: Types a `LeafLabelMap` can store its labels as (`String` or `Cow<str>`).
+//! - `DuplicateTaxonPolicy`: Handling of labels appearing more than once in the same tree.
+//! - `LabelRenaming`: Mapping of old to new labels, e.g. accession numbers to species names, read from TSV files.
+//! - `RenameError`: Renaming that would lose a label or merge two labels.
+//!
+//! Since trees refer to labels by [LabelIndex], renaming labels of a [LeafLabelMap] renames the leaves of all its trees.
 
 use crate::model::tree::LabelIndex;
+use std::borrow::{Borrow, Cow};
 use std::collections::HashMap;
+use std::error::Error;
 use std::fmt;
+use std::hash::Hash;
+#[cfg(feature = "fs")]
+use std::fs::File;
+use std::io;
+use std::io::BufRead;
+#[cfg(feature = "fs")]
+use std::io::BufReader;
 
 // =#========================================================================#=
 // LEAF LABEL MAP
@@ -29,22 +45,75 @@ use std::fmt;
 /// assert_eq!(idx_a, idx_a2);
 /// assert_eq!(labels.get_label(idx_a), Some("A"));
 /// ```
+///
+/// # Borrowed Labels
+/// A `LeafLabelMap<Cow<str>>` (see [LeafLabelMap::new_borrowed]) stores labels as slices of the parsed input
+/// where possible instead of copying them, e.g. filled by `newick::parse_str_borrowed` or `nexus::parse_str_borrowed`.
+/// Labels that differ from their bytes in the input (e.g. quoted labels with escaped quotes,
+/// or with underscores converted to spaces) are owned. Use [into_owned](LeafLabelMap::into_owned)
+/// to get a `LeafLabelMap` with the same indices that outlives the input.
+///
+/// ```
+/// use nexus_parser::model::leaf_label_map::LeafLabelMap;
+///
+/// let input = String::from("A B");
+/// let mut labels = LeafLabelMap::new_borrowed(2);
+/// let a = labels.get_or_insert_label(input[..1].into());
+/// let b = labels.get_or_insert_label(input[2..].into());
+/// assert_eq!(labels.get_or_insert("A"), a);
+/// assert_eq!(labels.num_borrowed(), 2);
+///
+/// let owned = labels.into_owned();
+/// drop(input);
+/// assert_eq!(owned.get_label(b), Some("B"));
+/// ```
 #[derive(Debug, Clone)]
-pub struct LeafLabelMap {
+pub struct LeafLabelMap<L = String> {
     /// Expected number of unique labels
     num_leaves: usize,
     /// List of unique labels
-    labels: Vec<String>,
+    labels: Vec<L>,
     /// Map from label to its index
-    map: HashMap<String, usize>,
+    map: HashMap<L, usize>,
 }
 
+/// Types a [LeafLabelMap] can store its labels as:
+/// `String` (owned, default) or `Cow<str>` (borrowed from the parsed input where possible).
+pub trait LeafLabel: Borrow<str> + Clone + Eq + Hash + From<String> + Into<String> {}
+
+impl<L: Borrow<str> + Clone + Eq + Hash + From<String> + Into<String>> LeafLabel for L {}
+
 impl LeafLabelMap {
     /// Creates a new LeafLabelMap with pre-allocated capacity.
     ///
     /// # Arguments
     /// * `num_leaves` - Expected number of unique leaf labels
     pub fn new(num_leaves: usize) -> Self {
+        Self::with_capacity(num_leaves)
+    }
+}
+
+impl<'a> LeafLabelMap<Cow<'a, str>> {
+    /// Creates a new LeafLabelMap with pre-allocated capacity
+    /// for labels borrowed from the parsed input where possible (see [LeafLabelMap]).
+    ///
+    /// # Arguments
+    /// * `num_leaves` - Expected number of unique leaf labels
+    pub fn new_borrowed(num_leaves: usize) -> Self {
+        Self::with_capacity(num_leaves)
+    }
+
+    /// Returns the number of labels borrowed from the input (i.e. not copied).
+    pub fn num_borrowed(&self) -> usize {
+        self.labels.iter()
+            .filter(|label| matches!(label, Cow::Borrowed(_)))
+            .count()
+    }
+}
+
+impl<L: LeafLabel> LeafLabelMap<L> {
+    /// Creates a new LeafLabelMap with pre-allocated capacity, see [LeafLabelMap::new].
+    pub(crate) fn with_capacity(num_leaves: usize) -> Self {
         LeafLabelMap {
             num_leaves,
             labels: Vec::with_capacity(num_leaves),
@@ -59,13 +128,13 @@ impl LeafLabelMap {
     ///
     /// # Arguments
     /// * `label` - The label to insert
-    pub fn insert(&mut self, label: String) {
+    pub fn insert(&mut self, label: L) {
         let idx = self.labels.len();
         self.labels.push(label.clone());
         self.map.insert(label, idx);
     }
 
-    /// Gets the index for a label, inserting it if it doesn't exist.
+    /// Gets the index for a label, inserting (a copy of) it if it doesn't exist.
     ///
     /// If the label already exists, returns its existing index.
     /// If the label is new, assigns it the next available index.
@@ -76,18 +145,78 @@ impl LeafLabelMap {
     /// # Returns
     /// The index associated with this label
     pub fn get_or_insert(&mut self, s: &str) -> usize {
-        if let Some(&index) = self.map.get(s) {
-            index
-        } else {
-            let idx = self.labels.len();
-            self.labels.push(s.to_string());
-            self.map.insert(s.to_string(), idx);
+        match self.map.get(s) {
+            Some(&index) => index,
+            None => self.push(L::from(s.to_string())),
+        }
+    }
+
+    /// Gets the index for a label like [get_or_insert](LeafLabelMap::get_or_insert),
+    /// but takes the label, so a new (e.g. borrowed) label is stored without copying it.
+    ///
+    /// # Arguments
+    /// * `label` - The label to look up or insert
+    ///
+    /// # Returns
+    /// The index associated with this label
+    pub fn get_or_insert_label(&mut self, label: L) -> LabelIndex {
+        match self.map.get(label.borrow()) {
+            Some(&index) => index,
+            None => self.push(label),
+        }
+    }
 
-            // Should not add more labels than specified by capacity `num_leaves`
-            debug_assert!(idx < self.num_leaves);
+    /// Appends a new label and returns its index.
+    fn push(&mut self, label: L) -> LabelIndex {
+        let idx = self.labels.len();
+        self.labels.push(label.clone());
+        self.map.insert(label, idx);
 
-            idx
+        // Grow expected number of labels if exceeded (e.g. verbatim labels of unknown number)
+        if idx >= self.num_leaves {
+            self.num_leaves = idx + 1;
         }
+
+        idx
+    }
+
+    /// Gets the index for the first label `<label>_2`, `<label>_3`, ... not taken yet,
+    /// inserting it if it doesn't exist, e.g. to rename a duplicate occurrence of `label` in a tree.
+    ///
+    /// # Arguments
+    /// * `label` - The label to rename
+    /// * `is_taken` - Whether an index is already taken (e.g. by another leaf of the same tree)
+    ///
+    /// # Returns
+    /// The index associated with the renamed label
+    ///
+    /// # Example
+    /// ```
+    /// use nexus_parser::model::leaf_label_map::LeafLabelMap;
+    ///
+    /// let mut labels = LeafLabelMap::new(2);
+    /// let a = labels.get_or_insert("A");
+    /// let a_2 = labels.get_or_insert_renamed("A", |index| index == a);
+    /// assert_eq!(labels.get_label(a_2), Some("A_2"));
+    /// let a_3 = labels.get_or_insert_renamed("A", |index| index == a || index == a_2);
+    /// assert_eq!(labels.get_label(a_3), Some("A_3"));
+    /// ```
+    pub fn get_or_insert_renamed(&mut self, label: &str, is_taken: impl Fn(LabelIndex) -> bool) -> LabelIndex {
+        let renamed = self.renamed_label(label, is_taken);
+        self.get_or_insert_label(L::from(renamed))
+    }
+
+    /// Returns the first label `<label>_2`, `<label>_3`, ... not taken yet,
+    /// see [get_or_insert_renamed](LeafLabelMap::get_or_insert_renamed) (without inserting it).
+    ///
+    /// # Arguments
+    /// * `label` - The label to rename
+    /// * `is_taken` - Whether an index is already taken (e.g. by another leaf of the same tree)
+    pub fn renamed_label(&self, label: &str, is_taken: impl Fn(LabelIndex) -> bool) -> String {
+        (2..)
+            .map(|suffix| format!("{}_{}", label, suffix))
+            .find(|renamed| self.get_index(renamed).is_none_or(|index| !is_taken(index)))
+            .expect("Some suffix is not taken")
     }
 
     /// Retrieves the index for a given label.
@@ -98,7 +227,7 @@ impl LeafLabelMap {
     /// # Returns
     /// `Some(index)` if the label exists, `None` otherwise
     pub fn get_index(&self, s: &str) -> Option<LabelIndex> {
-        self.map.get(s).map(|&index| index)
+        self.map.get(s).copied()
     }
 
     /// Retrieves the leaf label for a given index.
@@ -109,7 +238,7 @@ impl LeafLabelMap {
     /// # Returns
     /// `Some(&str)` if the index is valid, `None` otherwise
     pub fn get_label(&self, index: usize) -> Option<&str> {
-        self.labels.get(index).map(|s| s.as_str())
+        self.labels.get(index).map(|s| s.borrow())
     }
 
     /// Checks if a label exists in the map.
@@ -134,7 +263,7 @@ impl LeafLabelMap {
     }
 
     /// Returns reference to the labels in this map.
-    pub fn labels(&self) -> &Vec<String> {
+    pub fn labels(&self) -> &Vec<L> {
         &self.labels
     }
 
@@ -142,39 +271,311 @@ impl LeafLabelMap {
     /// - Same length
     /// - All labels in `translation` appear in this map
     ///
-    ///# Arguments
-    ///* `translation` - Translation map (likely from Nexus TRANSLATE command) to test,
-    ///                  with leaf labels being the map's values
-    pub fn check_consistency_with_translation(&self, translation: &HashMap<String, String>) -> bool {
+    /// # Arguments
+    /// * `translation` - Translation map (likely from Nexus TRANSLATE command) to test,
+    ///   with leaf labels being the map's values
+    pub fn check_consistency_with_translation<K, T: Borrow<str>>(&self, translation: &HashMap<K, T>) -> bool {
         // Need to have same number of labels
         if translation.len() != self.num_labels() {
             return false;
         }
         // Each label in map needs to appear
         for test_label in translation.values() {
-            if !self.contains_label(test_label) {
+            if !self.contains_label(test_label.borrow()) {
                 return false;
             }
         }
 
         true
     }
+
+    /// Renames a label, keeping its index (so all leaves referring to it are renamed).
+    ///
+    /// # Returns
+    /// The index of the renamed label
+    ///
+    /// # Errors
+    /// Returns [RenameError::UnknownLabel] if `old` is not a label of this map,
+    /// or [RenameError::DuplicateLabel] if `new` is already another label.
+    ///
+    /// # Example
+    /// ```
+    /// use nexus_parser::model::leaf_label_map::LeafLabelMap;
+    ///
+    /// let mut labels = LeafLabelMap::new(2);
+    /// labels.get_or_insert("AB123");
+    /// labels.get_or_insert("AB124");
+    ///
+    /// assert_eq!(labels.rename("AB124", "Apteryx haastii"), Ok(1));
+    /// assert_eq!(labels.get_index("Apteryx haastii"), Some(1));
+    /// assert!(!labels.contains_label("AB124"));
+    /// assert!(labels.rename("AB123", "Apteryx haastii").is_err());
+    /// ```
+    pub fn rename(&mut self, old: &str, new: &str) -> Result<LabelIndex, RenameError> {
+        let index = self.get_index(old).ok_or_else(|| RenameError::UnknownLabel(old.to_string()))?;
+        if old == new {
+            return Ok(index);
+        }
+        if self.contains_label(new) {
+            return Err(RenameError::DuplicateLabel(new.to_string()));
+        }
+
+        self.map.remove(old);
+        self.map.insert(L::from(new.to_string()), index);
+        self.labels[index] = L::from(new.to_string());
+        Ok(index)
+    }
+
+    /// Renames all labels with the given function (e.g. to strip a prefix), keeping their indices.
+    ///
+    /// The labels are only changed if all new labels are distinct.
+    ///
+    /// # Errors
+    /// Returns [RenameError::DuplicateLabel] with the first new label that two labels would share.
+    ///
+    /// # Example
+    /// ```
+    /// use nexus_parser::model::leaf_label_map::LeafLabelMap;
+    ///
+    /// let mut labels = LeafLabelMap::new(2);
+    /// labels.get_or_insert("Apteryx_haastii");
+    /// labels.get_or_insert("Nestor_notabilis");
+    ///
+    /// labels.rename_with(|label| label.replace('_', " ")).unwrap();
+    /// assert_eq!(labels.get_label(1), Some("Nestor notabilis"));
+    /// assert!(labels.rename_with(|_| "Bird".to_string()).is_err());
+    /// ```
+    pub fn rename_with(&mut self, rename: impl Fn(&str) -> String) -> Result<(), RenameError> {
+        let labels: Vec<L> = self.labels.iter().map(|label| L::from(rename(label.borrow()))).collect();
+        let mut map = HashMap::with_capacity(labels.len());
+        for (index, label) in labels.iter().enumerate() {
+            if map.insert(label.clone(), index).is_some() {
+                return Err(RenameError::DuplicateLabel(label.borrow().to_string()));
+            }
+        }
+
+        self.labels = labels;
+        self.map = map;
+        Ok(())
+    }
+
+    /// Renames all labels found in the renaming (e.g. read from a TSV mapping file), keeping their indices;
+    /// labels not in the renaming stay as they are (see [LeafLabelMap::rename_with]).
+    ///
+    /// # Returns
+    /// The number of renamed labels
+    ///
+    /// # Errors
+    /// Returns [RenameError::DuplicateLabel] with the first new label that two labels would share;
+    /// the labels are then left unchanged.
+    pub fn rename_all(&mut self, renaming: &LabelRenaming) -> Result<usize, RenameError> {
+        let num_renamed = self.labels.iter()
+            .filter(|&label| renaming.get(label.borrow()).is_some_and(|new| new != label.borrow()))
+            .count();
+        self.rename_with(|label| renaming.get(label).unwrap_or(label).to_string())?;
+        Ok(num_renamed)
+    }
+
+    /// Converts all labels into owned strings, returning a [LeafLabelMap] with the same indices
+    /// that is independent of the parsed input (without copying labels that are already owned).
+    pub fn into_owned(self) -> LeafLabelMap {
+        let mut leaf_label_map = LeafLabelMap::new(self.num_leaves);
+        for label in self.labels {
+            leaf_label_map.insert(label.into());
+        }
+        leaf_label_map
+    }
+}
+
+/// Serialized form of a [LeafLabelMap]: its labels in order of their indices
+/// (the map from labels to indices is rebuilt when deserializing).
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct LeafLabelMapData {
+    num_leaves: usize,
+    labels: Vec<String>,
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for LeafLabelMap {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+        let mut state = serializer.serialize_struct("LeafLabelMap", 2)?;
+        state.serialize_field("num_leaves", &self.num_leaves)?;
+        state.serialize_field("labels", &self.labels)?;
+        state.end()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for LeafLabelMap {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        LeafLabelMapData::deserialize(deserializer).map(Self::from)
+    }
 }
 
-impl fmt::Display for LeafLabelMap {
+#[cfg(feature = "serde")]
+impl From<LeafLabelMapData> for LeafLabelMap {
+    fn from(data: LeafLabelMapData) -> Self {
+        let mut leaf_label_map = LeafLabelMap::new(data.num_leaves.max(data.labels.len()));
+        for label in data.labels {
+            leaf_label_map.insert(label);
+        }
+        leaf_label_map
+    }
+}
+
+// =#========================================================================#=
+// LABEL RENAMING
+// =#========================================================================#=
+/// Mapping of old to new labels, applied with [LeafLabelMap::rename_all]
+/// (or [TreeSample::rename_labels](crate::model::tree_sample::TreeSample::rename_labels)).
+///
+/// # TSV Format
+/// One `old<TAB>new` line per label, e.g. accession number and species name; empty lines and lines starting with `#`
+/// are ignored. Columns beyond the second are ignored as well, so mapping files with further information can be used.
+///
+/// # Example
+/// ```
+/// use nexus_parser::model::leaf_label_map::{LabelRenaming, LeafLabelMap};
+///
+/// let tsv = "# accession\tspecies\nAB123\tApteryx haastii\nAB124\tApteryx owenii\n";
+/// let renaming = LabelRenaming::from_tsv(tsv.as_bytes()).unwrap();
+/// assert_eq!(renaming.get("AB123"), Some("Apteryx haastii"));
+///
+/// let mut labels = LeafLabelMap::new(3);
+/// labels.get_or_insert("AB124");
+/// labels.get_or_insert("XY999");
+/// assert_eq!(labels.rename_all(&renaming), Ok(1));
+/// assert_eq!(labels.get_label(0), Some("Apteryx owenii"));
+/// assert_eq!(labels.get_label(1), Some("XY999"));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LabelRenaming {
+    /// Map from old to new label
+    new_labels: HashMap<String, String>,
+}
+
+impl LabelRenaming {
+    /// Creates an empty renaming.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Reads a renaming from TSV formatted input (see [LabelRenaming] for format).
+    ///
+    /// # Errors
+    /// Returns an error if reading fails, a line does not have two tab-separated columns or an old label appears twice.
+    pub fn from_tsv<R: BufRead>(reader: R) -> io::Result<Self> {
+        let mut renaming = LabelRenaming::new();
+
+        for (line_number, line) in reader.lines().enumerate() {
+            let line = line?;
+            let line = line.trim_end_matches('\r');
+            if line.trim().is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let mut columns = line.split('\t').map(str::trim);
+            let (Some(old), Some(new)) = (columns.next(), columns.next()) else {
+                return Err(invalid_data(format!("Line {} does not have two tab-separated columns", line_number + 1)));
+            };
+            if renaming.new_labels.contains_key(old) {
+                return Err(invalid_data(format!("Label '{}' renamed twice in line {}", old, line_number + 1)));
+            }
+            renaming.insert(old, new);
+        }
+
+        Ok(renaming)
+    }
+
+    /// Reads a renaming from a TSV file (see [LabelRenaming] for format).
+    #[cfg(feature = "fs")]
+    pub fn from_tsv_file(path: &str) -> io::Result<Self> {
+        Self::from_tsv(BufReader::new(File::open(path)?))
+    }
+
+    /// Sets the new label of a label, replacing any previous one.
+    pub fn insert(&mut self, old: &str, new: &str) {
+        self.new_labels.insert(old.to_string(), new.to_string());
+    }
+
+    /// Returns the new label of a label, if renamed.
+    pub fn get(&self, old: &str) -> Option<&str> {
+        self.new_labels.get(old).map(String::as_str)
+    }
+
+    /// Returns the number of renamed labels.
+    pub fn len(&self) -> usize {
+        self.new_labels.len()
+    }
+
+    /// Returns whether no label is renamed.
+    pub fn is_empty(&self) -> bool {
+        self.new_labels.is_empty()
+    }
+}
+
+/// Error for a renaming of labels of a [LeafLabelMap] that cannot be applied.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RenameError {
+    /// The label to rename is not in the map
+    UnknownLabel(String),
+    /// The new label is already taken, or given to several labels
+    DuplicateLabel(String),
+}
+
+impl fmt::Display for RenameError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RenameError::UnknownLabel(label) => write!(f, "Label '{}' to rename is not in the label map", label),
+            RenameError::DuplicateLabel(label) => write!(f, "Label '{}' would be given to more than one taxon", label),
+        }
+    }
+}
+
+impl Error for RenameError {}
+
+// =#========================================================================#=
+// DUPLICATE TAXON POLICY
+// =#========================================================================#=
+/// Handling of taxa (labels or keys) appearing more than once in the same tree while parsing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DuplicateTaxonPolicy {
+    /// Fail with a duplicate taxon parsing error (default)
+    #[default]
+    Error,
+    /// Map all leaves of the taxon to the same [LabelIndex],
+    /// so the tree has fewer distinct taxa than leaves
+    AllowDuplicates,
+    /// Rename further occurrences to `<label>_2`, `<label>_3`, ...,
+    /// adding them to the [LeafLabelMap] (see [LeafLabelMap::get_or_insert_renamed]);
+    /// once the number of leaves is known (e.g. after the first tree, or from a TAXA block),
+    /// a renamed taxon beyond it is a duplicate taxon parsing error, since all trees are on the same number of taxa
+    AutoRename,
+}
+
+impl<L: LeafLabel> fmt::Display for LeafLabelMap<L> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         writeln!(f, "LeafLabelMap ({}/{} labels):", self.labels.len(), self.num_leaves)?;
         for (idx, label) in self.labels.iter().enumerate() {
-            writeln!(f, "  [{}] {}", idx, label)?;
+            writeln!(f, "  [{}] {}", idx, label.borrow())?;
         }
         Ok(())
     }
 }
 
-impl std::ops::Index<LabelIndex> for LeafLabelMap {
+impl<L: LeafLabel> std::ops::Index<LabelIndex> for LeafLabelMap<L> {
     type Output = str;
 
     fn index(&self, index: LabelIndex) -> &Self::Output {
-        &self.labels[index]
+        self.labels[index].borrow()
     }
-}
\ No newline at end of file
+}
+
+// ============================================================================
+// Little Helpers
+// ============================================================================
+fn invalid_data(msg: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
@@ -1,6 +1,45 @@
 /// Phylogenetic tree structure and operations
 pub mod tree;
+/// Reuse of vertex arenas and children lists across trees of the same size
+pub mod tree_builder;
+/// Structure-of-arrays representation of trees for tight loops
+pub mod compact_tree;
+/// Diagnostics of malformed trees (dangling references, cycles, invalid branch lengths)
+pub mod validation;
 /// Tree vertex types (root, internal, leaf)
 pub mod vertex;
 /// Leaf label mapping to compact indices
-pub mod leaf_label_map;
\ No newline at end of file
+pub mod leaf_label_map;
+/// Interning of leaf labels across files for stable indices
+pub mod label_arena;
+/// Taxon sets (bitsets over leaf labels) for clades and splits
+pub mod taxon_set;
+/// Taxonomic groups of taxa per rank
+pub mod taxonomy;
+/// Character matrices (alignments) of taxa, e.g. of a NEXUS DATA block
+pub mod alignment;
+/// Human-readable names for clades
+pub mod clade_naming;
+/// Rooting, unrooting and rerooting of trees
+pub mod rooting;
+/// Scaling and transformation of branch lengths of trees and samples
+pub mod rescaling;
+/// NNI and SPR moves and neighborhoods
+pub mod rearrangement;
+/// Ladderizing and canonical ordering of children
+pub mod ordering;
+/// Clade queries (MRCA, leaf sets), subtree extraction and pruning of taxa
+pub mod subtree;
+/// Lowest common ancestor queries in constant time after preprocessing
+pub mod lca;
+/// Selection of trees of a sample by name and of taxa by label
+pub mod tree_selection;
+/// Samples of trees owning their shared leaf label map
+pub mod tree_sample;
+/// Metadata annotations (e.g. `[&rate=0.01]`) of vertices and branches
+pub mod annotation;
+/// Rooted phylogenetic networks with reticulations (extended Newick `#H1` tags)
+pub mod network;
+/// Random perturbations of trees for robustness testing
+#[cfg(feature = "rand")]
+pub mod perturbation;
@@ -0,0 +1,364 @@
+//! Network module for rooted phylogenetic networks, e.g. of hybridization or lateral gene transfer.
+//!
+//! - `Network`: A [Tree] with additional parents of some vertices (reticulations)
+//! - `Reticulation`: Additional edge from a parent to a reticulation vertex
+//! - `ReticulationKind`: Type of a reticulation as tagged in extended Newick (`#H1`, `#LGT1`, `#R1`)
+//! - `split_reticulation_tag`: Splits a label like `B#H1` into name, kind and number
+//!
+//! In extended Newick (Cardona et al. 2008), a reticulation vertex is written once with its subtree
+//! and tagged label, e.g. `(B)#H1`, and once per further parent as a leaf with just the tag, e.g. `#H1`:
+//! `((A,(B)#H1),(#H1,C));`. Parse networks with
+//! [NewickParser::parse_network](crate::io::parser::newick::NewickParser::parse_network);
+//! parsing such a string as tree fails with a
+//! [ReticulateNetwork](crate::io::parser::parsing_error::ParsingErrorType::ReticulateNetwork) error.
+//!
+//! The tree of a network keeps the first parent of every vertex (usually where its subtree is written),
+//! so all tree methods apply to it; the reticulations are kept beside it. Vertices whose children are all
+//! reticulation vertices written elsewhere (e.g. `p` of `(((A)#H1,D),(#H1)p);`) get one of them as tree child,
+//! or are suppressed if that is not possible.
+
+use crate::model::annotation::Annotation;
+use crate::model::rooting::merge_branch_annotations;
+use crate::model::tree::{Tree, TreeIndex};
+use crate::model::vertex::BranchLength;
+use std::collections::HashMap;
+use std::fmt;
+
+// =#========================================================================#=
+// RETICULATION
+// =#========================================================================#=
+/// Type of a reticulation, as tagged in extended Newick.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ReticulationKind {
+    /// Hybridization, tagged `#H`
+    Hybridization,
+    /// Lateral (horizontal) gene transfer, tagged `#LGT`
+    LateralGeneTransfer,
+    /// Recombination, tagged `#R`
+    Recombination,
+}
+
+impl ReticulationKind {
+    /// Returns the prefix of tags of this kind, e.g. `H` of `#H1`.
+    pub fn prefix(&self) -> &'static str {
+        match self {
+            ReticulationKind::Hybridization => "H",
+            ReticulationKind::LateralGeneTransfer => "LGT",
+            ReticulationKind::Recombination => "R",
+        }
+    }
+}
+
+/// Writes the kind as its tag prefix, e.g. `H`.
+impl fmt::Display for ReticulationKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.prefix())
+    }
+}
+
+/// Edge from an additional parent to a reticulation vertex of a [Network].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Reticulation {
+    /// Index of the additional parent
+    pub parent: TreeIndex,
+    /// Index of the reticulation vertex, whose first parent is its parent in the tree of the network
+    pub child: TreeIndex,
+    /// Length of the edge, if any
+    pub branch_length: Option<BranchLength>,
+    /// Type of the reticulation
+    pub kind: ReticulationKind,
+    /// Number of the tag, e.g. `1` of `#H1`, the same for all edges to the same vertex
+    pub number: usize,
+    /// Annotation of the edge, if collected when parsing
+    pub annotation: Option<Annotation>,
+}
+
+impl Reticulation {
+    /// Returns the tag of this reticulation, e.g. `#H1`.
+    pub fn tag(&self) -> String {
+        format!("#{}{}", self.kind, self.number)
+    }
+}
+
+/// Splits a label with an extended Newick reticulation tag, e.g. `B#H1`, into the name (`B`, possibly empty),
+/// the kind and the number of the tag.
+///
+/// # Returns
+/// The parts, or `None` if the label does not end in a tag `#H<n>`, `#LGT<n>` or `#R<n>`
+///
+/// # Example
+/// ```
+/// use nexus_parser::model::network::{split_reticulation_tag, ReticulationKind};
+///
+/// assert_eq!(split_reticulation_tag("B#H1"), Some(("B", ReticulationKind::Hybridization, 1)));
+/// assert_eq!(split_reticulation_tag("#LGT12"), Some(("", ReticulationKind::LateralGeneTransfer, 12)));
+/// assert_eq!(split_reticulation_tag("sample#1"), None);
+/// ```
+pub fn split_reticulation_tag(label: &str) -> Option<(&str, ReticulationKind, usize)> {
+    let (name, tag) = label.rsplit_once('#')?;
+    let (kind, number) = [ReticulationKind::LateralGeneTransfer, ReticulationKind::Hybridization, ReticulationKind::Recombination]
+        .into_iter()
+        .find_map(|kind| Some((kind, tag.strip_prefix(kind.prefix())?)))?;
+    if number.is_empty() || !number.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    Some((name, kind, number.parse().ok()?))
+}
+
+// =#========================================================================#=
+// NETWORK
+// =#========================================================================#=
+/// A rooted phylogenetic network: a [Tree] with additional parents of some of its vertices.
+///
+/// Every vertex has its first parent in the tree; a reticulation vertex additionally has one
+/// [Reticulation] edge per further parent. Vertices of the tree may thus have a single child in the tree,
+/// if their other children are reticulation vertices.
+///
+/// # Example
+/// ```
+/// use nexus_parser::io::parser::byte_parser::ByteParser;
+/// use nexus_parser::io::parser::newick::NewickParser;
+///
+/// let mut newick_parser = NewickParser::new();
+/// let network = newick_parser.parse_network(&mut ByteParser::from_str("((A,(B)#H1),(#H1,C));")).unwrap();
+///
+/// assert_eq!(network.num_reticulations(), 1);
+/// let hybrid = network.reticulations()[0].child;
+/// assert_eq!(network.parents(hybrid).len(), 2);
+/// assert_eq!(network.tree().num_leaves(), 3);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Network {
+    /// Tree of the first parents of all vertices
+    tree: Tree,
+    /// Edges to additional parents
+    reticulations: Vec<Reticulation>,
+}
+
+impl Network {
+    /// Creates a network without reticulations from a tree.
+    pub fn from_tree(tree: Tree) -> Self {
+        Self { tree, reticulations: Vec::new() }
+    }
+
+    /// Adds an edge from an additional parent to a vertex of the tree.
+    ///
+    /// # Panics
+    /// Panics if an index is not a vertex of the tree, the child is the root,
+    /// or the edge would create a directed cycle.
+    pub fn add_reticulation(&mut self, reticulation: Reticulation) {
+        let num_vertices = self.tree.num_vertices();
+        assert!(reticulation.parent < num_vertices && reticulation.child < num_vertices,
+                "Reticulation {} -> {} is not between vertices of the tree", reticulation.parent, reticulation.child);
+        assert!(!self.tree[reticulation.child].is_root(), "Root cannot be a reticulation vertex");
+        self.reticulations.push(reticulation);
+        if !self.is_acyclic() {
+            let reticulation = self.reticulations.pop().unwrap();
+            panic!("Reticulation {} -> {} creates a cycle", reticulation.parent, reticulation.child);
+        }
+    }
+
+    /// Returns the tree of the first parents of all vertices.
+    pub fn tree(&self) -> &Tree {
+        &self.tree
+    }
+
+    /// Returns the tree of the network, dropping the reticulations.
+    pub fn into_tree(self) -> Tree {
+        self.tree
+    }
+
+    /// Returns the edges to additional parents.
+    pub fn reticulations(&self) -> &[Reticulation] {
+        &self.reticulations
+    }
+
+    /// Returns the number of edges to additional parents.
+    pub fn num_reticulations(&self) -> usize {
+        self.reticulations.len()
+    }
+
+    /// Returns whether this network has no reticulations, i.e. is a tree.
+    pub fn is_tree(&self) -> bool {
+        self.reticulations.is_empty()
+    }
+
+    /// Returns whether the vertex has more than one parent.
+    pub fn is_reticulation(&self, index: TreeIndex) -> bool {
+        self.reticulations.iter().any(|reticulation| reticulation.child == index)
+    }
+
+    /// Returns the parents of the vertex: the one in the tree (if not the root) first, then the additional ones.
+    pub fn parents(&self, index: TreeIndex) -> Vec<TreeIndex> {
+        self.tree[index].parent_index().into_iter()
+            .chain(self.reticulations.iter().filter(|r| r.child == index).map(|r| r.parent))
+            .collect()
+    }
+
+    /// Returns the children of the vertex: those in the tree first, then reticulation vertices below it.
+    pub fn children(&self, index: TreeIndex) -> Vec<TreeIndex> {
+        self.tree[index].children().unwrap_or(&[]).iter().copied()
+            .chain(self.reticulations.iter().filter(|r| r.parent == index).map(|r| r.child))
+            .collect()
+    }
+
+    /// Returns whether no vertex is its own descendant (Kahn's algorithm over all edges).
+    fn is_acyclic(&self) -> bool {
+        let num_vertices = self.tree.num_vertices();
+        let mut num_parents = vec![0; num_vertices];
+        for index in 0..num_vertices {
+            for child in self.children(index) {
+                num_parents[child] += 1;
+            }
+        }
+
+        let mut stack: Vec<TreeIndex> = (0..num_vertices).filter(|&index| num_parents[index] == 0).collect();
+        let mut num_visited = 0;
+        while let Some(index) = stack.pop() {
+            num_visited += 1;
+            for child in self.children(index) {
+                num_parents[child] -= 1;
+                if num_parents[child] == 0 {
+                    stack.push(child);
+                }
+            }
+        }
+        num_visited == num_vertices
+    }
+}
+
+// ============================================================================
+// Network Parsing (pub(crate))
+// ============================================================================
+/// Vertex of a tree parsed in network mode whose label carried a reticulation tag.
+#[derive(Debug, Clone)]
+pub(crate) struct TaggedVertex {
+    /// Index of the vertex in the parsed tree
+    pub(crate) index: TreeIndex,
+    /// Kind of the tag
+    pub(crate) kind: ReticulationKind,
+    /// Number of the tag
+    pub(crate) number: usize,
+    /// Whether the vertex is a placeholder leaf with just the tag, standing for an additional parent edge
+    pub(crate) is_reference: bool,
+}
+
+impl Network {
+    /// Creates a network from a tree parsed in network mode, turning placeholder leaves
+    /// into reticulation edges to the vertex with the same tag and compacting the tree.
+    ///
+    /// Vertices whose only children were placeholders (e.g. `p` of `(((A)#H1,D),(#H1)p);`) would be left
+    /// without children in the tree, see [Network::attach_childless].
+    ///
+    /// # Errors
+    /// Returns a message if a tag has no or several vertices besides placeholders,
+    /// tags the root, the reticulations form a cycle, or childless vertices cannot be resolved.
+    pub(crate) fn from_tagged_tree(mut tree: Tree, tagged: Vec<TaggedVertex>) -> Result<Self, String> {
+        let tag = |kind: ReticulationKind, number: usize| format!("#{}{}", kind, number);
+
+        // Vertex of each tag: the one written with its subtree or taxon
+        let mut vertices: HashMap<(ReticulationKind, usize), TreeIndex> = HashMap::new();
+        for vertex in tagged.iter().filter(|vertex| !vertex.is_reference) {
+            if tree[vertex.index].is_root() {
+                return Err(format!("Root cannot be reticulation {}", tag(vertex.kind, vertex.number)));
+            }
+            if vertices.insert((vertex.kind, vertex.number), vertex.index).is_some() {
+                return Err(format!("Reticulation {} is written with a subtree or taxon more than once", tag(vertex.kind, vertex.number)));
+            }
+        }
+
+        // Detach placeholders, keeping their branches as reticulation edges
+        let mut reticulations = Vec::new();
+        for vertex in tagged.iter().filter(|vertex| vertex.is_reference) {
+            let child = *vertices.get(&(vertex.kind, vertex.number))
+                .ok_or_else(|| format!("Reticulation {} has no vertex with a subtree or taxon", tag(vertex.kind, vertex.number)))?;
+            let parent = tree[vertex.index].parent_index().expect("Placeholders are leaves below some vertex");
+            tree[parent].children_mut().expect("Parents have children").retain(|&c| c != vertex.index);
+            reticulations.push(Reticulation {
+                parent,
+                child,
+                branch_length: tree[vertex.index].branch_length(),
+                kind: vertex.kind,
+                number: vertex.number,
+                annotation: tree.branch_annotation(vertex.index).cloned(),
+            });
+        }
+
+        let mut network = Self { tree, reticulations };
+        if !network.is_acyclic() {
+            return Err("Reticulations form a cycle".to_string());
+        }
+        let mut parents: Vec<TreeIndex> = network.reticulations.iter().map(|r| r.parent).collect();
+        parents.sort_unstable();
+        parents.dedup();
+        network.attach_childless(parents)?;
+
+        let mapping = network.tree.compact();
+        for reticulation in &mut network.reticulations {
+            reticulation.parent = mapping[reticulation.parent].expect("Parents are reachable");
+            reticulation.child = mapping[reticulation.child].expect("Reticulation vertices are reachable");
+        }
+        Ok(network)
+    }
+
+    /// Gives each of the candidate vertices that has no children in the tree (but reticulation edges) a child:
+    /// - if the tree parent of one of its reticulation vertices has other children, that vertex is moved below it
+    ///   in the tree, swapping the tree edge and the reticulation edge (the network stays the same)
+    /// - otherwise, if it has a single reticulation edge, it is suppressed: the edge starts at its tree parent instead,
+    ///   with both lengths added and annotations merged; its node label and annotation are recorded as
+    ///   [dropped](Tree::dropped_metadata), and its tree parent becomes a candidate
+    ///
+    /// # Errors
+    /// Returns a message if such a vertex is the root or has several reticulation edges, none of which can be swapped.
+    fn attach_childless(&mut self, mut candidates: Vec<TreeIndex>) -> Result<(), String> {
+        while let Some(vertex) = candidates.pop() {
+            if self.tree[vertex].children().is_none_or(|children| !children.is_empty()) {
+                continue;
+            }
+
+            let edges: Vec<usize> = (0..self.reticulations.len()).filter(|&i| self.reticulations[i].parent == vertex).collect();
+            let swappable = edges.iter().copied().find(|&i| {
+                let child = self.reticulations[i].child;
+                self.tree[child].parent_index().is_some_and(|parent| self.tree[parent].children().is_some_and(|c| c.len() > 1))
+            });
+            if let Some(i) = swappable {
+                let child = self.reticulations[i].child;
+                let parent = self.tree[child].parent_index().expect("Reticulation vertices are not the root");
+                let reticulation = &mut self.reticulations[i];
+                reticulation.parent = parent;
+                let branch_length = std::mem::replace(&mut reticulation.branch_length, self.tree[child].branch_length());
+                let annotation = std::mem::replace(&mut reticulation.annotation, self.tree.branch_annotation(child).cloned());
+
+                self.tree[parent].children_mut().expect("Parents have children").retain(|&c| c != child);
+                self.tree[vertex].children_mut().expect("Vertex is internal").push(child);
+                self.tree[child].set_parent(vertex);
+                self.tree[child].set_branch_length(branch_length);
+                self.tree.set_branch_annotation(child, annotation);
+                continue;
+            }
+
+            let tags = || edges.iter().map(|&i| self.reticulations[i].tag()).collect::<Vec<_>>().join(", ");
+            let [i] = edges[..] else {
+                return Err(format!("Vertex with only reticulation edges ({}) cannot be part of the tree", tags()));
+            };
+            let Some(parent) = self.tree[vertex].parent_index() else {
+                return Err(format!("Root has only reticulation edges ({})", tags()));
+            };
+            let dropped = self.tree.metadata_of(vertex, true, false);
+            self.tree.record_dropped(dropped);
+            let length = self.tree[vertex].branch_length();
+            let branch_annotation = self.tree.branch_annotation(vertex).cloned();
+            let reticulation = &mut self.reticulations[i];
+            reticulation.parent = parent;
+            if let (Some(a), Some(b)) = (reticulation.branch_length, length) {
+                reticulation.branch_length = Some(BranchLength::new_unchecked(*a + *b));
+            } else {
+                reticulation.branch_length = reticulation.branch_length.or(length);
+            }
+            reticulation.annotation = merge_branch_annotations(reticulation.annotation.take(), branch_annotation);
+            self.tree[parent].children_mut().expect("Parents have children").retain(|&c| c != vertex);
+            candidates.push(parent);
+        }
+        Ok(())
+    }
+}
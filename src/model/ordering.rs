@@ -0,0 +1,152 @@
+//! Ordering module for deterministic orders of children, independent of how a tree was written.
+//!
+//! Extends [Tree] with:
+//! - `ladderize`: Orders children by the number of leaves below them (as "increasing/decreasing node order" in FigTree)
+//! - `canonicalize`: Orders children by the smallest [LabelIndex] below them
+//! - `order_leaves`: Orders children so leaves follow a given order as closely as possible
+//!   (e.g. found by [untangle](crate::algorithms::tanglegram::untangle))
+//! - `leaf_order`: Label indices of the leaves in the order they are drawn
+//!
+//! Both only permute children lists; indices, labels, branch lengths and annotations stay as they are.
+//! Since they also break ties by the smallest label index, trees with the same topology
+//! (up to the order of children) and label map are written as the same Newick string.
+
+use crate::model::tree::{LabelIndex, Tree, TreeIndex};
+
+// ============================================================================
+// Child Ordering (pub)
+// ============================================================================
+impl Tree {
+    /// Orders the children of every vertex by the number of leaves below them,
+    /// ties by the smallest [LabelIndex] below them.
+    ///
+    /// # Arguments
+    /// * `ascending` - Whether smaller subtrees come first (and the larger ones last, i.e. down in drawings)
+    ///
+    /// # Example
+    /// ```
+    /// use nexus_parser::io::writer::newick::NewickStyle;
+    /// use nexus_parser::parse_newick_str;
+    ///
+    /// let (mut trees, labels) = parse_newick_str("(((A,B),C),(D,E));").unwrap();
+    /// trees[0].ladderize(true);
+    /// assert_eq!(trees[0].to_newick(&NewickStyle::Label, Some(&labels)), "((D,E),(C,(A,B)));");
+    /// trees[0].ladderize(false);
+    /// assert_eq!(trees[0].to_newick(&NewickStyle::Label, Some(&labels)), "(((A,B),C),(D,E));");
+    /// ```
+    pub fn ladderize(&mut self, ascending: bool) {
+        let (num_leaves, min_labels) = self.subtree_keys();
+        self.sort_children(|child| {
+            let size = if ascending { num_leaves[child] as isize } else { -(num_leaves[child] as isize) };
+            (size, min_labels[child])
+        });
+    }
+
+    /// Orders the children of every vertex by the smallest [LabelIndex] below them,
+    /// so that trees with the same topology get the same order of children
+    /// (label indices follow the first occurrence of labels when parsing, or a TRANSLATE command).
+    ///
+    /// # Example
+    /// ```
+    /// use nexus_parser::io::writer::newick::NewickStyle;
+    /// use nexus_parser::parse_newick_str;
+    ///
+    /// let (mut trees, labels) = parse_newick_str("((A,B),(C,D));\n((D,C),(B,A));\n").unwrap();
+    /// for tree in &mut trees {
+    ///     tree.canonicalize();
+    /// }
+    /// assert_eq!(trees[1].to_newick(&NewickStyle::Label, Some(&labels)), "((A,B),(C,D));");
+    /// ```
+    pub fn canonicalize(&mut self) {
+        let (_, min_labels) = self.subtree_keys();
+        self.sort_children(|child| min_labels[child]);
+    }
+
+    /// Orders the children of every vertex by the mean position of the leaves below them in the given order
+    /// (barycenter heuristic), ties by the smallest [LabelIndex] below them; subtrees without leaves in the order come last.
+    ///
+    /// If the order is one of this tree's own (e.g. found by [untangle](crate::algorithms::tanglegram::untangle)),
+    /// the leaves then follow it exactly, so drawings by [Tree::to_svg] or [Tree::to_ascii] show them in that order.
+    ///
+    /// # Arguments
+    /// * `leaf_order` - Label indices of the leaves in the desired order
+    ///
+    /// # Example
+    /// ```
+    /// use nexus_parser::parse_newick_str;
+    ///
+    /// let (mut trees, labels) = parse_newick_str("(((A,B),C),(D,E));").unwrap();
+    /// let order: Vec<usize> = ["E", "D", "C", "A", "B"].iter().map(|label| labels.get_index(label).unwrap()).collect();
+    /// trees[0].order_leaves(&order);
+    /// assert_eq!(trees[0].leaf_order(), order);
+    /// ```
+    pub fn order_leaves(&mut self, leaf_order: &[LabelIndex]) {
+        let mut positions = vec![None; self.num_leaves_init()];
+        for (position, &label_index) in leaf_order.iter().enumerate() {
+            if label_index < positions.len() {
+                positions[label_index] = Some(position as f64);
+            }
+        }
+
+        // Sum and number of positions of the leaves below each vertex
+        let mut barycenters = vec![(0.0, 0); self.num_vertices()];
+        for vertex in self.post_order_iter() {
+            let index = vertex.index();
+            barycenters[index] = match vertex.children() {
+                None => vertex.label_index()
+                    .and_then(|label_index| positions.get(label_index).copied().flatten())
+                    .map_or((0.0, 0), |position| (position, 1)),
+                Some(children) => children.iter().fold((0.0, 0), |(sum, count), &child| (sum + barycenters[child].0, count + barycenters[child].1)),
+            };
+        }
+        let key = |child: TreeIndex| match barycenters[child] {
+            (_, 0) => f64::INFINITY,
+            (sum, count) => sum / count as f64,
+        };
+
+        let (_, min_labels) = self.subtree_keys();
+        for index in 0..self.num_vertices() {
+            if let Some(children) = self[index].children_mut() {
+                children.sort_by(|&a, &b| key(a).total_cmp(&key(b)).then_with(|| min_labels[a].cmp(&min_labels[b])));
+            }
+        }
+    }
+
+    /// Returns the label indices of the leaves in the order of the children lists,
+    /// i.e. top to bottom in drawings; empty if no root is set.
+    pub fn leaf_order(&self) -> Vec<LabelIndex> {
+        if !self.is_root_set() {
+            return Vec::new();
+        }
+        self.pre_order_iter().filter_map(|vertex| vertex.label_index()).collect()
+    }
+
+    /// Returns the number of leaves and the smallest [LabelIndex] below each vertex.
+    fn subtree_keys(&self) -> (Vec<usize>, Vec<LabelIndex>) {
+        let mut num_leaves = vec![0; self.num_vertices()];
+        let mut min_labels = vec![LabelIndex::MAX; self.num_vertices()];
+        for vertex in self.post_order_iter() {
+            let index = vertex.index();
+            match vertex.children() {
+                None => {
+                    num_leaves[index] = 1;
+                    min_labels[index] = vertex.label_index().expect("Leaf has label index");
+                }
+                Some(children) => {
+                    num_leaves[index] = children.iter().map(|&child| num_leaves[child]).sum();
+                    min_labels[index] = children.iter().map(|&child| min_labels[child]).min().unwrap_or(LabelIndex::MAX);
+                }
+            }
+        }
+        (num_leaves, min_labels)
+    }
+
+    /// Sorts the children of every vertex by the given key.
+    fn sort_children<K: Ord>(&mut self, key: impl Fn(TreeIndex) -> K) {
+        for index in 0..self.num_vertices() {
+            if let Some(children) = self[index].children_mut() {
+                children.sort_by_key(|&child| key(child));
+            }
+        }
+    }
+}
@@ -0,0 +1,106 @@
+//! Perturbation module for testing robustness of downstream summaries.
+//!
+//! Extends [Tree] with random perturbations (requires feature `rand`):
+//! - `perturb_branch_lengths`: Multiplies branch lengths by random factors (noise injection)
+//! - `random_nni_moves`: Changes the topology by random nearest neighbor interchanges (NNI),
+//!   see [Tree::nni_move]
+
+use crate::model::tree::{Tree, TreeIndex};
+use crate::model::vertex::BranchLength;
+use rand::distr::Distribution;
+use rand::seq::IndexedRandom;
+use rand::Rng;
+
+// ============================================================================
+// Branch Length Noise (pub)
+// ============================================================================
+impl Tree {
+    /// Multiplies every branch length with an independent sample of the given distribution.
+    ///
+    /// For example, factors from `Uniform(0.9, 1.1)` add up to ±10% noise to every branch.
+    /// Negative products are truncated to `0.0`; vertices without branch length are left as is.
+    /// Note that trees generally do not stay ultrametric.
+    ///
+    /// # Arguments
+    /// * `distribution` - Distribution of (typically positive) factors around `1.0`
+    /// * `rng` - Random number generator
+    ///
+    /// # Panics
+    /// Panics if a product is not finite.
+    ///
+    /// # Example
+    /// ```
+    /// use nexus_parser::model::tree::Tree;
+    /// use nexus_parser::model::vertex::BranchLength;
+    /// use rand::distr::Uniform;
+    ///
+    /// let mut tree = Tree::new(2);
+    /// let a = tree.add_leaf(Some(BranchLength::new(1.0)), 0);
+    /// let b = tree.add_leaf(Some(BranchLength::new(1.0)), 1);
+    /// tree.add_root(vec![a, b]);
+    ///
+    /// tree.perturb_branch_lengths(&Uniform::new(0.9, 1.1).unwrap(), &mut rand::rng());
+    /// let length = *tree[a].branch_length().unwrap();
+    /// assert!((0.9..1.1).contains(&length));
+    /// ```
+    pub fn perturb_branch_lengths<D, R>(&mut self, distribution: &D, rng: &mut R)
+    where
+        D: Distribution<f64>,
+        R: Rng + ?Sized,
+    {
+        for index in 0..self.num_vertices() {
+            let Some(length) = self[index].branch_length() else {
+                continue;
+            };
+            let perturbed = (*length * distribution.sample(rng)).max(0.0);
+            self[index].set_branch_length(Some(BranchLength::new(perturbed)));
+        }
+    }
+}
+
+// ============================================================================
+// NNI Moves (pub)
+// ============================================================================
+impl Tree {
+    /// Performs `num_moves` random nearest neighbor interchanges (NNI).
+    ///
+    /// Each move picks a uniformly random internal (non-root) vertex `v`,
+    /// a random child of `v` and a random sibling of `v`, and swaps these two subtrees (see [Tree::nni_move]).
+    /// Subtrees keep their branch lengths, so trees generally do not stay ultrametric.
+    /// Moves are independent, so a later move may undo an earlier one.
+    ///
+    /// # Arguments
+    /// * `num_moves` - Number of NNI moves to perform
+    /// * `rng` - Random number generator
+    ///
+    /// # Returns
+    /// The number of moves performed, i.e. `0` if the tree has no internal edge, else `num_moves`.
+    pub fn random_nni_moves<R: Rng + ?Sized>(&mut self, num_moves: usize, rng: &mut R) -> usize {
+        let internal: Vec<TreeIndex> = (0..self.num_vertices())
+            .filter(|&index| self[index].is_internal())
+            .collect();
+
+        let mut num_performed = 0;
+        for _ in 0..num_moves {
+            // NNI does not change which vertices are internal
+            let Some(&vertex) = internal.choose(rng) else {
+                break;
+            };
+            let parent = self[vertex].parent_index().expect("Internal vertex must have parent");
+
+            let child = *self[vertex].children().unwrap().choose(rng).unwrap();
+            let siblings: Vec<TreeIndex> = self[parent].children().unwrap().iter()
+                .copied()
+                .filter(|&sibling| sibling != vertex)
+                .collect();
+            let Some(&sibling) = siblings.choose(rng) else {
+                continue;
+            };
+
+            self.nni_move(vertex, child, sibling);
+            num_performed += 1;
+        }
+
+        num_performed
+    }
+}
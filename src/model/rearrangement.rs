@@ -0,0 +1,279 @@
+//! Rearrangement module for moving between neighboring tree topologies.
+//!
+//! Extends [Tree] with the moves of tree search and distance heuristics:
+//! - `nni_move`: Performs a single, given nearest neighbor interchange (NNI) in place
+//! - `nni`: All trees one NNI across a given branch away
+//! - `nni_neighborhood`: All trees one NNI away
+//! - `spr`: Subtree prune and regraft (SPR) of a given subtree onto a given branch, in place
+//! - `spr_neighborhood`: All distinct topologies one SPR away
+//!
+//! Branches are identified by the vertex below them, as in [Tree::root_at].
+//! All moves keep the arena valid (see [Tree::is_valid]) and treat trees as rooted.
+
+use crate::model::rooting::{replace_neighbor, suppress, Adjacency};
+use crate::model::tree::{Tree, TreeIndex};
+use std::collections::HashSet;
+
+// ============================================================================
+// NNI (pub)
+// ============================================================================
+impl Tree {
+    /// Performs a nearest neighbor interchange (NNI) on the edge above `vertex`,
+    /// swapping the subtree of `child` (a child of `vertex`) with the subtree of `sibling` (a sibling of `vertex`).
+    ///
+    /// # Panics
+    /// Panics if `vertex` is not internal, `child` is not a child of `vertex`,
+    /// or `sibling` is not another child of the parent of `vertex`.
+    ///
+    /// # Example
+    /// ```
+    /// use nexus_parser::model::tree::Tree;
+    /// use nexus_parser::model::taxon_set::TaxonSet;
+    ///
+    /// // ((A,B),C) -> ((C,B),A)
+    /// let mut tree = Tree::new(3);
+    /// let a = tree.add_leaf(None, 0);
+    /// let b = tree.add_leaf(None, 1);
+    /// let c = tree.add_leaf(None, 2);
+    /// let ab = tree.add_internal_vertex(vec![a, b], None);
+    /// tree.add_root(vec![ab, c]);
+    ///
+    /// tree.nni_move(ab, a, c);
+    /// assert!(tree.is_valid());
+    /// assert!(tree.is_monophyletic(&TaxonSet::from_indices(3, [1, 2])));
+    /// ```
+    pub fn nni_move(&mut self, vertex: TreeIndex, child: TreeIndex, sibling: TreeIndex) {
+        assert!(self[vertex].is_internal(), "NNI requires internal vertex, got {}", vertex);
+        let parent = self[vertex].parent_index().expect("Internal vertex must have parent");
+        assert_ne!(sibling, vertex, "Sibling must differ from vertex");
+
+        replace_child(self[vertex].children_mut().unwrap(), child, sibling);
+        replace_child(self[parent].children_mut().unwrap(), sibling, child);
+        self[child].set_parent(parent);
+        self[sibling].set_parent(vertex);
+    }
+
+    /// Returns all trees one NNI across the branch above the given internal vertex away,
+    /// i.e. with a child of `edge` swapped with a sibling of `edge` (see [Tree::nni_move]).
+    ///
+    /// For a binary tree, these are the two NNI neighbors across the branch.
+    /// Vertex indices stay the same; this tree is left unchanged.
+    ///
+    /// # Panics
+    /// Panics if `edge` is not an internal vertex.
+    ///
+    /// # Example
+    /// ```
+    /// use nexus_parser::io::parser::byte_parser::ByteParser;
+    /// use nexus_parser::io::parser::newick::NewickParser;
+    /// use nexus_parser::io::writer::newick::NewickStyle;
+    ///
+    /// let mut newick_parser = NewickParser::new().with_num_leaves(3);
+    /// let tree = newick_parser.parse(&mut ByteParser::from_str("((A,B),C);")).unwrap();
+    ///
+    /// let neighbors: Vec<String> = tree.nni(tree.mrca(&[0, 1])).iter()
+    ///     .map(|tree| tree.to_newick(&NewickStyle::ZeroIndexed, None))
+    ///     .collect();
+    /// assert_eq!(neighbors, vec!["((2,1),0);", "((0,2),1);"]);
+    /// ```
+    pub fn nni(&self, edge: TreeIndex) -> Vec<Tree> {
+        assert!(self[edge].is_internal(), "NNI requires internal vertex, got {}", edge);
+        let parent = self[edge].parent_index().expect("Internal vertex must have parent");
+
+        let mut neighbors = Vec::new();
+        for &child in self[edge].children().unwrap() {
+            for &sibling in self[parent].children().unwrap().iter().filter(|&&sibling| sibling != edge) {
+                let mut neighbor = self.clone();
+                neighbor.nni_move(edge, child, sibling);
+                neighbors.push(neighbor);
+            }
+        }
+
+        neighbors
+    }
+
+    /// Returns all trees one NNI away, i.e. [Tree::nni] across all internal branches,
+    /// which are `2 (n - 2)` trees for a binary tree on `n` leaves.
+    pub fn nni_neighborhood(&self) -> Vec<Tree> {
+        (0..self.num_vertices())
+            .filter(|&index| self[index].is_internal())
+            .flat_map(|index| self.nni(index))
+            .collect()
+    }
+}
+
+// ============================================================================
+// SPR (pub)
+// ============================================================================
+impl Tree {
+    /// Prunes the subtree below the branch above `prune_edge` and regrafts it onto the middle
+    /// of the branch above `regraft_edge` (subtree prune and regraft, SPR).
+    ///
+    /// The pruned subtree keeps the length of its branch; the vertex it was attached to is suppressed
+    /// if left with a single child (summing the lengths of its two branches, see [Annotations](Tree#annotations))
+    /// and reused to attach the subtree, otherwise a new vertex is added. Regrafting onto the root
+    /// makes the attachment vertex the new root. Indices of all other vertices stay the same.
+    ///
+    /// # Arguments
+    /// * `prune_edge` - Index of the vertex below the branch to cut, i.e. of the root of the moved subtree
+    /// * `regraft_edge` - Index of the vertex below the branch to attach the subtree to,
+    ///   or of the root to attach the subtree above it
+    ///
+    /// # Panics
+    /// Panics if `prune_edge` is the root, or `regraft_edge` is within the pruned subtree.
+    ///
+    /// # Example
+    /// ```
+    /// use nexus_parser::io::parser::byte_parser::ByteParser;
+    /// use nexus_parser::io::parser::newick::NewickParser;
+    /// use nexus_parser::io::writer::newick::NewickStyle;
+    ///
+    /// let mut newick_parser = NewickParser::new().with_num_leaves(4);
+    /// let mut tree = newick_parser.parse(&mut ByteParser::from_str("(((A:1,B:1):1,C:2):1,D:3);")).unwrap();
+    ///
+    /// // Move A onto the branch of D
+    /// let (a, d) = (tree.mrca(&[0]), tree.mrca(&[3]));
+    /// tree.spr(a, d);
+    /// assert!(tree.is_valid());
+    /// assert_eq!(tree.to_newick(&NewickStyle::ZeroIndexed, None), "((1:2,2:2):1,(3:1.5,0:1):1.5);");
+    /// ```
+    pub fn spr(&mut self, prune_edge: TreeIndex, regraft_edge: TreeIndex) {
+        let attached_to = self[prune_edge].parent_index()
+            .expect("Cannot prune the root");
+        assert!(!self.is_in_subtree(regraft_edge, prune_edge),
+                "Cannot regraft subtree of {} onto its own branch {}", prune_edge, regraft_edge);
+
+        let old_root = self.root().index();
+        let mut adjacency = self.adjacency();
+
+        // Prune
+        let position = adjacency[prune_edge].iter()
+            .position(|(neighbor, _, _)| *neighbor == attached_to)
+            .expect("Vertices are neighbors");
+        let (_, pruned_length, pruned_annotation) = adjacency[prune_edge].remove(position);
+        adjacency[attached_to].retain(|(neighbor, _, _)| *neighbor != prune_edge);
+
+        // Suppress vertex left with a single child (and reuse its index), or add a new one
+        let mut root = old_root;
+        let mut regraft_edge = regraft_edge;
+        let new_vertex = if attached_to == old_root && adjacency[attached_to].len() == 1 {
+            let (child, _, _) = adjacency[attached_to].pop().unwrap();
+            adjacency[child].retain(|(neighbor, _, _)| *neighbor != attached_to);
+            self.record_dropped(self.metadata_of(attached_to, true, false));
+            self.record_dropped(self.metadata_of(child, false, true));
+            root = child;
+            if regraft_edge == attached_to {
+                regraft_edge = child;
+            }
+            attached_to
+        } else if attached_to != old_root && adjacency[attached_to].len() == 2 {
+            // Branch above the suppressed vertex merges with the one of its remaining child
+            let (child, _, _) = adjacency[attached_to][0];
+            suppress(&mut adjacency, attached_to);
+            self.record_dropped(self.metadata_of(attached_to, true, false));
+            if regraft_edge == attached_to {
+                regraft_edge = child;
+            }
+            attached_to
+        } else {
+            adjacency.push(Vec::new());
+            adjacency.len() - 1
+        };
+
+        // Regraft
+        adjacency[prune_edge].push((new_vertex, pruned_length, pruned_annotation.clone()));
+        let pruned = (prune_edge, pruned_length, pruned_annotation);
+        if regraft_edge == root {
+            adjacency[root].push((new_vertex, None, None));
+            adjacency[new_vertex] = vec![(root, None, None), pruned];
+            root = new_vertex;
+        } else {
+            let above = self.current_parent(&adjacency, regraft_edge, attached_to);
+            let (length, annotation) = adjacency[regraft_edge].iter()
+                .find(|(neighbor, _, _)| *neighbor == above)
+                .map(|(_, length, annotation)| (*length, annotation.clone()))
+                .expect("Vertices are neighbors");
+            let half = length.map(|length| length / 2.0);
+            replace_neighbor(&mut adjacency, regraft_edge, above, (new_vertex, half, annotation.clone()));
+            replace_neighbor(&mut adjacency, above, regraft_edge, (new_vertex, half, annotation.clone()));
+            adjacency[new_vertex] = vec![(regraft_edge, half, annotation.clone()), pruned, (above, half, annotation)];
+        }
+
+        // Reused vertex starts without label and annotation
+        if new_vertex < self.num_vertices() {
+            self[new_vertex].set_node_label(None);
+            self.set_annotation(new_vertex, None);
+        }
+        self.rebuild(adjacency, root, None);
+    }
+
+    /// Returns all distinct topologies one SPR away (see [Tree::spr]), excluding this tree's topology,
+    /// in order of the pruned and then the regraft branch.
+    ///
+    /// Topologies are told apart as in [Tree::topology_hash], i.e. by their clades if this tree is
+    /// [rooted](Tree::is_rooted), otherwise by their splits.
+    ///
+    /// # Example
+    /// ```
+    /// use nexus_parser::io::parser::byte_parser::ByteParser;
+    /// use nexus_parser::io::parser::newick::NewickParser;
+    ///
+    /// let mut newick_parser = NewickParser::new().with_num_leaves(4);
+    /// let tree = newick_parser.parse(&mut ByteParser::from_str("(((A,B),C),D);")).unwrap();
+    ///
+    /// // All 3 balanced trees and 7 of the 11 other caterpillar trees
+    /// assert_eq!(tree.spr_neighborhood().len(), 10);
+    /// ```
+    pub fn spr_neighborhood(&self) -> Vec<Tree> {
+        let mut seen = HashSet::from([self.topology_hash()]);
+        let mut neighbors = Vec::new();
+
+        for prune_edge in (0..self.num_vertices()).filter(|&index| !self[index].is_root()) {
+            for regraft_edge in 0..self.num_vertices() {
+                if self.is_in_subtree(regraft_edge, prune_edge) {
+                    continue;
+                }
+                let mut neighbor = self.clone();
+                neighbor.spr(prune_edge, regraft_edge);
+                if seen.insert(neighbor.topology_hash()) {
+                    neighbors.push(neighbor);
+                }
+            }
+        }
+
+        neighbors
+    }
+
+    // ============================================================================
+    // Little Helpers
+    // ============================================================================
+    /// Returns whether `vertex` is `root` of the subtree or below it.
+    fn is_in_subtree(&self, vertex: TreeIndex, root: TreeIndex) -> bool {
+        let mut current = Some(vertex);
+        while let Some(index) = current {
+            if index == root {
+                return true;
+            }
+            current = self[index].parent_index();
+        }
+        false
+    }
+
+    /// Returns the parent of `vertex` after `suppressed` may have been suppressed in the undirected view.
+    fn current_parent(&self, adjacency: &Adjacency, vertex: TreeIndex, suppressed: TreeIndex) -> TreeIndex {
+        let parent = self[vertex].parent_index().expect("Non-root vertex has parent");
+        if parent == suppressed && !adjacency[vertex].iter().any(|(neighbor, _, _)| *neighbor == parent) {
+            self[suppressed].parent_index().expect("Suppressed vertex is not the root")
+        } else {
+            parent
+        }
+    }
+}
+
+/// Replaces child `old` by `new`, keeping the order of children.
+fn replace_child(children: &mut [TreeIndex], old: TreeIndex, new: TreeIndex) {
+    let position = children.iter()
+        .position(|&c| c == old)
+        .unwrap_or_else(|| panic!("Vertex {} is not a child", old));
+    children[position] = new;
+}
@@ -0,0 +1,107 @@
+//! Rescaling module for converting branch lengths between units (e.g. substitutions per site and time).
+//!
+//! Extends [Tree] with:
+//! - `scale_branch_lengths`: Multiplies every branch length by a factor
+//! - `set_all_branch_lengths`: Sets every branch length to the same value
+//! - `transform_branch_lengths`: Applies a function to every branch length, validating the results
+//!
+//! - `rescale_trees`: Multiplies the branch lengths of every tree of a sample by a factor
+
+use crate::model::tree::Tree;
+use crate::model::vertex::{BranchLength, BranchLengthError};
+
+// ============================================================================
+// Branch Length Transformations (pub)
+// ============================================================================
+impl Tree {
+    /// Multiplies every branch length by `factor`, e.g. a substitution rate to convert
+    /// time into substitutions per site (or its inverse for the other way around).
+    /// Vertices without branch length are left as is; ultrametric trees stay ultrametric.
+    ///
+    /// # Panics
+    /// Panics if `factor` is negative or not finite, or a scaled length is not finite.
+    ///
+    /// # Example
+    /// ```
+    /// use nexus_parser::parse_newick_str;
+    ///
+    /// let (mut trees, _) = parse_newick_str("((A:1,B:1):2,C:3);").unwrap();
+    /// trees[0].scale_branch_lengths(0.5);
+    /// assert_eq!(trees[0].root_height(), Some(1.5));
+    /// ```
+    pub fn scale_branch_lengths(&mut self, factor: f64) {
+        assert!(factor >= 0.0 && factor.is_finite(), "Scaling factor must be non-negative and finite, got {}", factor);
+        self.transform_branch_lengths(|length| length * factor)
+            .unwrap_or_else(|error| panic!("{}", error));
+    }
+
+    /// Sets the branch length of every non-root vertex to `length`, including vertices without one.
+    ///
+    /// # Panics
+    /// Panics if `length` is negative or not finite.
+    pub fn set_all_branch_lengths(&mut self, length: f64) {
+        let length = BranchLength::new(length);
+        let root = self.root().index();
+        for index in (0..self.num_vertices()).filter(|&index| index != root) {
+            self[index].set_branch_length(Some(length));
+        }
+    }
+
+    /// Replaces every branch length `x` by `transform(x)`, e.g. `f64::ln_1p` or a unit conversion.
+    /// Vertices without branch length are left as is.
+    ///
+    /// All results are validated before the tree is changed, so it stays untouched on error.
+    ///
+    /// # Errors
+    /// Returns a [BranchLengthError] for the first result that is negative or not finite.
+    ///
+    /// # Example
+    /// ```
+    /// use nexus_parser::model::vertex::BranchLengthError;
+    /// use nexus_parser::parse_newick_str;
+    ///
+    /// let (mut trees, _) = parse_newick_str("((A:1,B:4):9,C);").unwrap();
+    /// trees[0].transform_branch_lengths(f64::sqrt).unwrap();
+    /// assert_eq!(trees[0].total_branch_length(), 6.0);
+    ///
+    /// assert_eq!(trees[0].transform_branch_lengths(|x| x - 2.0), Err(BranchLengthError::Negative(-1.0)));
+    /// assert_eq!(trees[0].total_branch_length(), 6.0);
+    /// ```
+    pub fn transform_branch_lengths(&mut self, transform: impl Fn(f64) -> f64) -> Result<(), BranchLengthError> {
+        let lengths = (0..self.num_vertices())
+            .map(|index| self[index].branch_length().map(|length| BranchLength::try_new(transform(*length))).transpose())
+            .collect::<Result<Vec<Option<BranchLength>>, BranchLengthError>>()?;
+
+        for (index, length) in lengths.into_iter().enumerate() {
+            if length.is_some() {
+                self[index].set_branch_length(length);
+            }
+        }
+        Ok(())
+    }
+}
+
+// =#========================================================================#=
+// SAMPLE RESCALING
+// =#========================================================================#=
+/// Multiplies the branch lengths of every tree by `factor`, see [Tree::scale_branch_lengths].
+///
+/// # Panics
+/// Panics if `factor` is negative or not finite, or a scaled length is not finite.
+///
+/// # Example
+/// ```
+/// use nexus_parser::model::rescaling::rescale_trees;
+/// use nexus_parser::parse_newick_str;
+///
+/// // Time trees in years to millions of years
+/// let (mut trees, _) = parse_newick_str("(A:2000000,B:2000000);\n(A:500000,B:500000);\n").unwrap();
+/// rescale_trees(&mut trees, 1e-6);
+/// assert_eq!(trees[0].root_height(), Some(2.0));
+/// assert_eq!(trees[1].root_height(), Some(0.5));
+/// ```
+pub fn rescale_trees(trees: &mut [Tree], factor: f64) {
+    for tree in trees {
+        tree.scale_branch_lengths(factor);
+    }
+}
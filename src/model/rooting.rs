@@ -0,0 +1,478 @@
+//! Rooting module for switching trees between rooted and unrooted.
+//!
+//! Whether the root of a tree is meaningful is stored as flag, see [Tree::is_rooted];
+//! NEXUS files mark it per tree with `[&R]` or `[&U]`. Extends [Tree] with:
+//! - `unroot`: Suppresses a binary root (merging its two branches) and marks the tree unrooted
+//! - `root_at`: Places the root on the midpoint of a branch and marks the tree rooted
+//! - `reroot_at_edge`: Places the root on a branch, splitting its length at a given fraction
+//! - `reroot_at_vertex`: Makes an internal vertex the root
+//! - `root_with_outgroup`: Places the root on the branch separating an outgroup from the other taxa
+//!   (`root_with_outgroup_clade` for the smallest clade containing a non-monophyletic outgroup)
+//! - `OutgroupError`: Outgroup that cannot root a tree
+//!
+//! Rerooting flips parent/child relations along the path between the old and new root,
+//! e.g. to compare trees from tools that root differently, or for outgroup rooting.
+
+use crate::model::annotation::Annotation;
+use crate::model::taxon_set::TaxonSet;
+use crate::model::tree::{LabelIndex, Tree, TreeIndex};
+use crate::model::vertex::{to_stored, to_stored_length, BranchLength, Vertex};
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+
+/// Undirected view of a tree: per vertex, its neighbors with the branch length and annotation of the connecting branch
+pub(crate) type Adjacency = Vec<Vec<(TreeIndex, Option<f64>, Option<Annotation>)>>;
+
+// ============================================================================
+// Rooting (pub)
+// ============================================================================
+impl Tree {
+    /// Marks this tree as unrooted and, if the root is binary, suppresses it:
+    /// its two branches are merged into one (lengths summed) and an internal child becomes the root,
+    /// which then has three or more children.
+    ///
+    /// Indices of other vertices stay the same, except the vertex with the last index,
+    /// which takes over the index of the removed root. Node labels and annotations stay with their vertices;
+    /// those of the removed root are recorded in [Tree::dropped_metadata].
+    ///
+    /// # Example
+    /// ```
+    /// use nexus_parser::io::parser::byte_parser::ByteParser;
+    /// use nexus_parser::io::parser::newick::NewickParser;
+    /// use nexus_parser::io::writer::newick::NewickStyle;
+    ///
+    /// let mut newick_parser = NewickParser::new().with_num_leaves(4);
+    /// let mut tree = newick_parser.parse(&mut ByteParser::from_str("((A:1,B:1):1,(C:1,D:1):2);")).unwrap();
+    ///
+    /// tree.unroot();
+    /// assert!(!tree.is_rooted());
+    /// assert_eq!(tree.to_newick(&NewickStyle::ZeroIndexed, None), "(0:1,1:1,(2:1,3:1):3);");
+    /// ```
+    pub fn unroot(&mut self) {
+        self.set_rooted(false);
+        let Some((left, right)) = self.binary_root_children() else {
+            return;
+        };
+        let new_root = if !self[left].is_leaf() {
+            left
+        } else if !self[right].is_leaf() {
+            right
+        } else {
+            // Two leaves only, nothing to suppress
+            return;
+        };
+
+        let old_root = self.root().index();
+        let mut adjacency = self.adjacency();
+        suppress(&mut adjacency, old_root);
+        self.record_dropped(self.metadata_of(old_root, true, false));
+        self.rebuild(adjacency, new_root, Some(old_root));
+    }
+
+    /// Places the root on the midpoint of the branch above the given vertex and marks the tree rooted.
+    ///
+    /// A binary root is suppressed first (as in [Tree::unroot]) and its index reused for the new root,
+    /// otherwise the new root gets the next free index. Indices of all other vertices stay the same;
+    /// branch lengths and annotations move along with their branches,
+    /// while node labels and annotations of vertices stay with their vertices
+    /// (those of a suppressed root are recorded in [Tree::dropped_metadata]).
+    ///
+    /// # Arguments
+    /// * `edge` - Index of the vertex below the branch to place the root on
+    ///
+    /// # Panics
+    /// Panics if `edge` is the root, which has no branch above.
+    ///
+    /// # Example
+    /// ```
+    /// use nexus_parser::io::parser::byte_parser::ByteParser;
+    /// use nexus_parser::io::parser::newick::NewickParser;
+    /// use nexus_parser::io::writer::newick::NewickStyle;
+    ///
+    /// let mut newick_parser = NewickParser::new().with_num_leaves(4);
+    /// let mut tree = newick_parser.parse(&mut ByteParser::from_str("(A:2,B:1,(C:1,D:1):1);")).unwrap();
+    ///
+    /// // Root on branch of A
+    /// tree.root_at(0);
+    /// assert!(tree.is_valid());
+    /// assert_eq!(tree.to_newick(&NewickStyle::ZeroIndexed, None), "(0:1,(1:1,(2:1,3:1):1):1);");
+    /// ```
+    pub fn root_at(&mut self, edge: TreeIndex) {
+        self.reroot_at_edge(edge, 0.5);
+    }
+
+    /// Places the root on the branch above the given vertex and marks the tree rooted,
+    /// splitting the branch length such that the new root is `fraction` of it above the vertex.
+    ///
+    /// Indices, labels and annotations behave as in [Tree::root_at];
+    /// the annotation of the branch is copied to both of its parts.
+    ///
+    /// # Arguments
+    /// * `edge` - Index of the vertex below the branch to place the root on
+    /// * `fraction` - Position of the new root on the branch, from 0 (at `edge`) to 1 (at its parent)
+    ///
+    /// # Panics
+    /// Panics if `edge` is the root, which has no branch above, or `fraction` is not within `[0, 1]`.
+    ///
+    /// # Example
+    /// ```
+    /// use nexus_parser::io::parser::byte_parser::ByteParser;
+    /// use nexus_parser::io::parser::newick::NewickParser;
+    /// use nexus_parser::io::writer::newick::NewickStyle;
+    ///
+    /// let mut newick_parser = NewickParser::new().with_num_leaves(4);
+    /// let mut tree = newick_parser.parse(&mut ByteParser::from_str("(A:4,B:1,(C:1,D:1):1);")).unwrap();
+    ///
+    /// // Root on branch of A, closer to A
+    /// tree.reroot_at_edge(0, 0.25);
+    /// assert_eq!(tree.to_newick(&NewickStyle::ZeroIndexed, None), "(0:1,(1:1,(2:1,3:1):1):3);");
+    /// ```
+    pub fn reroot_at_edge(&mut self, edge: TreeIndex, fraction: f64) {
+        assert!((0.0..=1.0).contains(&fraction), "Fraction {} to place root on branch is not within [0, 1]", fraction);
+        let parent = self[edge].parent_index()
+            .expect("Cannot place root above the root");
+
+        let mut adjacency = self.adjacency();
+        let (below, above, new_root) = match self.binary_root_children() {
+            Some((left, right)) => {
+                let old_root = self.root().index();
+                suppress(&mut adjacency, old_root);
+                // Branches of the old root merged into one; its index is reused without its label and annotation
+                self.record_dropped(self.metadata_of(old_root, true, false));
+                self[old_root].set_node_label(None);
+                self.set_annotation(old_root, None);
+                let above = if parent != old_root { parent } else if edge == left { right } else { left };
+                (edge, above, old_root)
+            }
+            None => {
+                adjacency.push(Vec::new());
+                (edge, parent, adjacency.len() - 1)
+            }
+        };
+
+        // Split branch
+        let (length, annotation) = adjacency[below].iter()
+            .find(|(neighbor, _, _)| *neighbor == above)
+            .map(|(_, length, annotation)| (*length, annotation.clone()))
+            .expect("Vertices are neighbors");
+        let length_below = length.map(|length| length * fraction);
+        let length_above = length.map(|length| length * (1.0 - fraction));
+        replace_neighbor(&mut adjacency, below, above, (new_root, length_below, annotation.clone()));
+        replace_neighbor(&mut adjacency, above, below, (new_root, length_above, annotation.clone()));
+        adjacency[new_root] = vec![(below, length_below, annotation.clone()), (above, length_above, annotation)];
+
+        self.rebuild(adjacency, new_root, None);
+        self.set_rooted(true);
+    }
+
+    /// Makes the given internal vertex the root and marks the tree rooted.
+    ///
+    /// A binary old root is suppressed (as in [Tree::unroot]), otherwise it becomes an internal vertex.
+    /// Indices of other vertices stay the same, except the vertex with the last index,
+    /// which takes over the index of a suppressed root. Branch lengths and annotations move along
+    /// with their branches, while node labels and annotations of vertices stay with their vertices
+    /// (those of a suppressed root are recorded in [Tree::dropped_metadata]).
+    ///
+    /// # Panics
+    /// Panics if the given vertex is a leaf, since the root needs children.
+    ///
+    /// # Example
+    /// ```
+    /// use nexus_parser::io::parser::byte_parser::ByteParser;
+    /// use nexus_parser::io::parser::newick::NewickParser;
+    /// use nexus_parser::io::writer::newick::NewickStyle;
+    ///
+    /// let mut newick_parser = NewickParser::new().with_num_leaves(4);
+    /// let mut tree = newick_parser.parse(&mut ByteParser::from_str("(A:2,B:1,(C:1,D:1)x:1);")).unwrap();
+    ///
+    /// tree.reroot_at_vertex(tree.mrca(&[2, 3]));
+    /// assert_eq!(tree.to_newick(&NewickStyle::ZeroIndexed, None), "(2:1,3:1,(0:2,1:1):1)x;");
+    /// ```
+    pub fn reroot_at_vertex(&mut self, index: TreeIndex) {
+        assert!(!self[index].is_leaf(), "Cannot make leaf {} the root", index);
+        self.set_rooted(true);
+        let old_root = self.root().index();
+        if index == old_root {
+            return;
+        }
+
+        let mut adjacency = self.adjacency();
+        let removed = self.binary_root_children().map(|_| {
+            suppress(&mut adjacency, old_root);
+            old_root
+        });
+        if removed.is_some() {
+            self.record_dropped(self.metadata_of(old_root, true, false));
+        }
+        self.rebuild(adjacency, index, removed);
+    }
+}
+
+// ============================================================================
+// Outgroup Rooting (pub)
+// ============================================================================
+impl Tree {
+    /// Places the root on the midpoint of the branch separating the outgroup from the other taxa
+    /// (see [Tree::root_at]) and marks the tree rooted, so that the outgroup is one of the two clades below the root.
+    ///
+    /// The outgroup only needs to be monophyletic in the unrooted tree, i.e. it may contain the current root.
+    /// If the root already separates the outgroup (a binary root), only the tree is marked rooted.
+    ///
+    /// # Arguments
+    /// * `outgroup` - Label indices of the outgroup taxa
+    ///
+    /// # Errors
+    /// Returns an [OutgroupError] (leaving the tree unchanged) if the outgroup is empty, contains a taxon
+    /// not in the tree or all taxa of the tree, or if no branch separates it from the other taxa
+    /// ([OutgroupError::NotMonophyletic], see [Tree::root_with_outgroup_clade] to root anyway).
+    ///
+    /// # Example
+    /// ```
+    /// use nexus_parser::io::writer::newick::NewickStyle;
+    /// use nexus_parser::model::rooting::OutgroupError;
+    /// use nexus_parser::parse_newick_str;
+    ///
+    /// let (mut trees, labels) = parse_newick_str("(A:1,B:1,(C:1,(D:1,E:1):1):2);").unwrap();
+    /// let taxa = |names: &[&str]| names.iter().map(|name| labels.get_index(name).unwrap()).collect::<Vec<_>>();
+    ///
+    /// trees[0].root_with_outgroup(&taxa(&["D", "E"])).unwrap();
+    /// assert!(trees[0].is_rooted());
+    /// assert_eq!(trees[0].to_newick(&NewickStyle::Label, Some(&labels)), "((D:1,E:1):0.5,(C:1,(A:1,B:1):2):0.5);");
+    ///
+    /// assert_eq!(trees[0].root_with_outgroup(&taxa(&["B", "E"])), Err(OutgroupError::NotMonophyletic));
+    /// ```
+    pub fn root_with_outgroup(&mut self, outgroup: &[LabelIndex]) -> Result<(), OutgroupError> {
+        self.root_at_outgroup_split(outgroup, false)
+    }
+
+    /// Roots the tree with the outgroup as [Tree::root_with_outgroup] does if it is monophyletic, and otherwise on the
+    /// branch of the smallest clade (of the unrooted tree) containing it, i.e. the outgroup plus as few ingroup taxa as possible.
+    ///
+    /// # Errors
+    /// Returns an [OutgroupError] (leaving the tree unchanged) if the outgroup is empty, contains a taxon
+    /// not in the tree or all taxa of the tree, or if no clade but all taxa contains it
+    ///
+    /// # Example
+    /// ```
+    /// use nexus_parser::io::writer::newick::NewickStyle;
+    /// use nexus_parser::parse_newick_str;
+    ///
+    /// let (mut trees, labels) = parse_newick_str("(A,B,(C,(D,(E,F))));").unwrap();
+    /// let outgroup = [labels.get_index("D").unwrap(), labels.get_index("F").unwrap()];
+    ///
+    /// trees[0].root_with_outgroup_clade(&outgroup).unwrap();
+    /// assert_eq!(trees[0].to_newick(&NewickStyle::Label, Some(&labels)), "((D,(E,F)),(C,(A,B)));");
+    /// ```
+    pub fn root_with_outgroup_clade(&mut self, outgroup: &[LabelIndex]) -> Result<(), OutgroupError> {
+        self.root_at_outgroup_split(outgroup, true)
+    }
+
+    /// Roots the tree on the branch whose smaller side is the outgroup or, if `smallest_clade`,
+    /// the side containing the outgroup with the fewest taxa.
+    fn root_at_outgroup_split(&mut self, outgroup: &[LabelIndex], smallest_clade: bool) -> Result<(), OutgroupError> {
+        if outgroup.is_empty() {
+            return Err(OutgroupError::EmptyOutgroup);
+        }
+        let taxa = self.clade_taxa(self.root().index());
+        if let Some(&missing) = outgroup.iter().find(|&&label_index| label_index >= taxa.num_taxa() || !taxa.contains(label_index)) {
+            return Err(OutgroupError::MissingTaxon(missing));
+        }
+        let outgroup = TaxonSet::from_indices(taxa.num_taxa(), outgroup.iter().copied());
+        if outgroup.len() == taxa.len() {
+            return Err(OutgroupError::NoIngroup);
+        }
+
+        // Branch (by vertex below it) with the fewest taxa on the side containing the outgroup
+        let mut best: Option<(TreeIndex, usize)> = None;
+        for index in 0..self.num_vertices() {
+            if self[index].is_root() {
+                continue;
+            }
+            let clade = self.clade_taxa(index);
+            let rest = taxa.difference(clade);
+            for side in [clade, &rest] {
+                let fits = if smallest_clade { outgroup.is_subset_of(side) } else { side == &outgroup };
+                if fits && best.is_none_or(|(_, size)| side.len() < size) {
+                    best = Some((index, side.len()));
+                }
+            }
+        }
+        let Some((edge, _)) = best else {
+            return Err(OutgroupError::NotMonophyletic);
+        };
+
+        let separated_by_root = self.binary_root_children().is_some_and(|(left, right)| edge == left || edge == right);
+        if separated_by_root {
+            self.set_rooted(true);
+        } else {
+            self.root_at(edge);
+        }
+        Ok(())
+    }
+}
+
+// ============================================================================
+// Outgroup Error (pub)
+// ============================================================================
+/// Reasons why an outgroup cannot root a tree, see [Tree::root_with_outgroup].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OutgroupError {
+    /// Outgroup has no taxa
+    EmptyOutgroup,
+    /// Taxon with the label index is not a leaf of the tree
+    MissingTaxon(LabelIndex),
+    /// Outgroup contains all taxa of the tree, leaving no ingroup
+    NoIngroup,
+    /// No branch separates the outgroup from the other taxa
+    NotMonophyletic,
+}
+
+impl fmt::Display for OutgroupError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OutgroupError::EmptyOutgroup => write!(f, "Outgroup is empty"),
+            OutgroupError::MissingTaxon(index) => write!(f, "Outgroup taxon {} is not in the tree", index),
+            OutgroupError::NoIngroup => write!(f, "Outgroup contains all taxa of the tree"),
+            OutgroupError::NotMonophyletic => write!(f, "Outgroup is not monophyletic"),
+        }
+    }
+}
+
+impl Error for OutgroupError {}
+
+// ============================================================================
+// Little Helpers
+// ============================================================================
+impl Tree {
+    /// Returns the two children of the root if it is binary.
+    fn binary_root_children(&self) -> Option<(TreeIndex, TreeIndex)> {
+        match self.root().children()? {
+            &[left, right] => Some((left, right)),
+            _ => None,
+        }
+    }
+
+    /// Returns the undirected view of this tree; each vertex lists its children first, then its parent.
+    pub(crate) fn adjacency(&self) -> Adjacency {
+        let mut adjacency: Adjacency = vec![Vec::new(); self.num_vertices()];
+        for index in 0..self.num_vertices() {
+            for &child in self[index].children().unwrap_or(&[]) {
+                let length = self[child].branch_length().map(|length| *length);
+                adjacency[index].push((child, length, self.branch_annotation(child).cloned()));
+            }
+        }
+        for index in 0..self.num_vertices() {
+            if let Some(parent) = self[index].parent_index() {
+                let length = self[index].branch_length().map(|length| *length);
+                adjacency[index].push((parent, length, self.branch_annotation(index).cloned()));
+            }
+        }
+        adjacency
+    }
+
+    /// Replaces the vertices by those of the undirected view oriented away from `root`.
+    ///
+    /// If `removed` is given, that (unreachable) index is dropped and the last vertex moved there.
+    pub(crate) fn rebuild(&mut self, adjacency: Adjacency, root: TreeIndex, removed: Option<TreeIndex>) {
+        let num_vertices = adjacency.len();
+        let new_index = |index: TreeIndex| match removed {
+            Some(removed) if index == num_vertices - 1 => removed,
+            _ => index,
+        };
+
+        let mut vertices: Vec<Option<Vertex>> = vec![None; num_vertices - removed.map_or(0, |_| 1)];
+        let mut branch_annotations = HashMap::new();
+        let mut stack = vec![(root, None)];
+        while let Some((index, parent)) = stack.pop() {
+            let mut children = Vec::new();
+            for (neighbor, length, annotation) in &adjacency[index] {
+                match parent {
+                    Some((parent, _)) if parent == *neighbor => {}
+                    _ => {
+                        children.push(new_index(*neighbor));
+                        stack.push((*neighbor, Some((index, (*length, annotation.clone())))));
+                    }
+                }
+            }
+
+            let node_label = (index < self.num_vertices())
+                .then(|| self[index].node_label().map(str::to_string))
+                .flatten();
+            let vertex = match parent {
+                None => Vertex::Root { index: to_stored(new_index(index)), children, node_label },
+                Some((parent, (length, annotation))) => {
+                    if let Some(annotation) = annotation {
+                        branch_annotations.insert(new_index(index), annotation);
+                    }
+                    let branch_length = length.map(|length| to_stored_length(BranchLength::new(length)));
+                    if children.is_empty() {
+                        let label_index = self[index].label_index()
+                            .expect("Only leaves of the tree can end up without children");
+                        Vertex::Leaf {
+                            index: to_stored(new_index(index)),
+                            label_index: to_stored(label_index),
+                            parent: to_stored(new_index(parent)),
+                            branch_length,
+                        }
+                    } else {
+                        Vertex::Internal {
+                            index: to_stored(new_index(index)),
+                            parent: to_stored(new_index(parent)),
+                            children,
+                            branch_length,
+                            node_label,
+                        }
+                    }
+                }
+            };
+            vertices[new_index(index)] = Some(vertex);
+        }
+
+        let annotations = (0..self.num_vertices())
+            .filter(|&index| Some(index) != removed)
+            .filter_map(|index| Some((new_index(index), self.annotation(index)?.clone())))
+            .collect();
+        let vertices = vertices.into_iter()
+            .map(|vertex| vertex.expect("All vertices are reachable from the new root"))
+            .collect();
+        self.replace_vertices(vertices, new_index(root), annotations, branch_annotations);
+    }
+}
+
+/// Suppresses the vertex of degree two, connecting its two neighbors directly (summing branch lengths
+/// and merging annotations, the one of the first neighbor taking precedence, see [merge_branch_annotations]).
+pub(crate) fn suppress(adjacency: &mut Adjacency, vertex: TreeIndex) {
+    let [(a, length_a, annotation_a), (b, length_b, annotation_b)]: [_; 2] = std::mem::take(&mut adjacency[vertex])
+        .try_into()
+        .expect("Only vertices of degree two can be suppressed");
+    let length = match (length_a, length_b) {
+        (None, None) => None,
+        _ => Some(length_a.unwrap_or(0.0) + length_b.unwrap_or(0.0)),
+    };
+    let annotation = merge_branch_annotations(annotation_a, annotation_b);
+
+    replace_neighbor(adjacency, a, vertex, (b, length, annotation.clone()));
+    replace_neighbor(adjacency, b, vertex, (a, length, annotation));
+}
+
+/// Replaces `old` among the neighbors of `vertex` (keeping its position).
+pub(crate) fn replace_neighbor(adjacency: &mut Adjacency, vertex: TreeIndex, old: TreeIndex,
+                               new: (TreeIndex, Option<f64>, Option<Annotation>)) {
+    let neighbor = adjacency[vertex].iter_mut()
+        .find(|(neighbor, _, _)| *neighbor == old)
+        .expect("Vertices are neighbors");
+    *neighbor = new;
+}
+
+/// Returns the annotation of a branch merged from the branch below and the one above a suppressed vertex:
+/// the entries of the lower one, completed by those of the upper one (see [Annotation::merge]).
+pub(crate) fn merge_branch_annotations(below: Option<Annotation>, above: Option<Annotation>) -> Option<Annotation> {
+    match (below, above) {
+        (Some(mut below), Some(above)) => {
+            below.merge(&above);
+            Some(below)
+        }
+        (below, above) => below.or(above),
+    }
+}
@@ -0,0 +1,384 @@
+//! Subtree module for clade queries on and extraction from trees.
+//!
+//! Extends [Tree] with:
+//! - `mrca`: Most recent common ancestor of given taxa
+//! - `leaves_under`: Leaves of the subtree below a vertex
+//! - `extract_subtree`: Copy of the subtree below a vertex, sharing the [LeafLabelMap]
+//! - `extract_subtree_remapped`: Same, with its own [LeafLabelMap] of only the taxa in the subtree
+//! - `prune_taxa`: Copy without the given leaves, suppressing resulting degree-2 vertices
+//!
+//! And provides [restrict_sample] to subset all trees of a sample to a common taxon set.
+
+use crate::model::leaf_label_map::LeafLabelMap;
+use crate::model::rooting::merge_branch_annotations;
+use crate::model::tree::{LabelIndex, PostOrderIter, PreOrderIter, Tree, TreeIndex};
+use crate::model::vertex::{BranchLength, Vertex};
+
+// ============================================================================
+// Clade Queries (pub)
+// ============================================================================
+impl Tree {
+    /// Returns the most recent common ancestor (MRCA) of the given taxa,
+    /// i.e. the lowest vertex with all of them below it (the leaf itself for a single taxon).
+    ///
+    /// # Arguments
+    /// * `taxa` - Label indices of the taxa (duplicates are ignored)
+    ///
+    /// # Panics
+    /// Panics if `taxa` is empty or contains a taxon that is not a leaf of this tree.
+    ///
+    /// # Example
+    /// ```
+    /// use nexus_parser::model::tree::Tree;
+    ///
+    /// // ((0,1),2)
+    /// let mut tree = Tree::new(3);
+    /// let a = tree.add_leaf(None, 0);
+    /// let b = tree.add_leaf(None, 1);
+    /// let c = tree.add_leaf(None, 2);
+    /// let ab = tree.add_internal_vertex(vec![a, b], None);
+    /// let root = tree.add_root(vec![ab, c]);
+    ///
+    /// assert_eq!(tree.mrca(&[0, 1]), ab);
+    /// assert_eq!(tree.mrca(&[1, 2]), root);
+    /// assert_eq!(tree.mrca(&[2]), c);
+    /// ```
+    pub fn mrca(&self, taxa: &[LabelIndex]) -> TreeIndex {
+        assert!(!taxa.is_empty(), "MRCA requires at least one taxon");
+
+        // Mark wanted taxa (deduplicated) and count how many are below each vertex
+        let mut wanted = vec![false; taxa.iter().max().unwrap() + 1];
+        for &taxon in taxa {
+            wanted[taxon] = true;
+        }
+        let num_wanted = wanted.iter().filter(|&&w| w).count();
+
+        // First vertex in post-order with all taxa below is the lowest
+        let mut counts = vec![0; self.num_vertices()];
+        for vertex in self.post_order_iter() {
+            let index = vertex.index();
+            counts[index] = match (vertex.label_index(), vertex.children()) {
+                (Some(label_index), _) => usize::from(wanted.get(label_index).copied().unwrap_or(false)),
+                (None, Some(children)) => children.iter().map(|&child| counts[child]).sum(),
+                (None, None) => 0,
+            };
+            if counts[index] == num_wanted {
+                return index;
+            }
+        }
+
+        panic!("Not all taxa {:?} are leaves of the tree", taxa);
+    }
+
+    /// Returns an iterator over the leaves in the subtree below (and including) the given vertex,
+    /// in pre-order (from first to last child).
+    ///
+    /// # Example
+    /// ```
+    /// use nexus_parser::model::tree::Tree;
+    ///
+    /// // ((0,1),2)
+    /// let mut tree = Tree::new(3);
+    /// let a = tree.add_leaf(None, 0);
+    /// let b = tree.add_leaf(None, 1);
+    /// let c = tree.add_leaf(None, 2);
+    /// let ab = tree.add_internal_vertex(vec![a, b], None);
+    /// tree.add_root(vec![ab, c]);
+    ///
+    /// let taxa: Vec<usize> = tree.leaves_under(ab).filter_map(|leaf| leaf.label_index()).collect();
+    /// assert_eq!(taxa, vec![0, 1]);
+    /// ```
+    pub fn leaves_under(&self, index: TreeIndex) -> impl Iterator<Item = &Vertex> {
+        PreOrderIter::starting_at(self, index).filter(|vertex| vertex.is_leaf())
+    }
+
+    // ============================================================================
+    // Subtree Extraction (pub)
+    // ============================================================================
+    /// Returns a copy of the subtree below the given vertex, which becomes its root.
+    ///
+    /// Leaves keep their label indices into the [LeafLabelMap] of this tree
+    /// and the subtree is created for the same number of leaves, so its clades
+    /// (see [Tree::clades]) range over all taxa and are comparable to those of this tree.
+    /// Branch lengths, node labels and annotations are copied, except the branch above the given vertex.
+    ///
+    /// Note that [Tree::is_valid] fails for the subtree if it lacks some taxa;
+    /// see [Tree::extract_subtree_remapped] for a standalone tree.
+    ///
+    /// # Panics
+    /// Panics if the given vertex is a leaf, since a tree needs a root with children.
+    ///
+    /// # Example
+    /// ```
+    /// use nexus_parser::io::parser::byte_parser::ByteParser;
+    /// use nexus_parser::io::parser::newick::NewickParser;
+    /// use nexus_parser::io::writer::newick::NewickStyle;
+    ///
+    /// let mut newick_parser = NewickParser::new().with_num_leaves(4);
+    /// let tree = newick_parser.parse(&mut ByteParser::from_str("((A:1,B:2):1,(C:1,D:1):1);")).unwrap();
+    /// let labels = newick_parser.into_leaf_label_map();
+    ///
+    /// let subtree = tree.extract_subtree(tree.mrca(&[2, 3]));
+    /// assert_eq!(subtree.to_newick(&NewickStyle::Label, Some(&labels)), "(C:1,D:1);");
+    /// assert_eq!(subtree.num_leaves_init(), 4);
+    /// ```
+    pub fn extract_subtree(&self, index: TreeIndex) -> Tree {
+        self.copy_subtree(index, self.num_leaves_init(), |label_index| label_index)
+    }
+
+    /// Returns a copy of the subtree below the given vertex (see [Tree::extract_subtree])
+    /// with its own [LeafLabelMap], containing only the taxa of the subtree
+    /// (indexed in order of appearance), so the subtree is a standalone valid tree.
+    ///
+    /// # Arguments
+    /// * `index` - Vertex to become the root of the subtree
+    /// * `leaf_label_map` - Labels of the taxa of this tree
+    ///
+    /// # Panics
+    /// Panics if the given vertex is a leaf, since a tree needs a root with children.
+    ///
+    /// # Example
+    /// ```
+    /// use nexus_parser::io::parser::byte_parser::ByteParser;
+    /// use nexus_parser::io::parser::newick::NewickParser;
+    /// use nexus_parser::io::writer::newick::NewickStyle;
+    ///
+    /// let mut newick_parser = NewickParser::new().with_num_leaves(4);
+    /// let tree = newick_parser.parse(&mut ByteParser::from_str("((A,B),(C,D));")).unwrap();
+    /// let labels = newick_parser.into_leaf_label_map();
+    ///
+    /// let (subtree, sublabels) = tree.extract_subtree_remapped(tree.mrca(&[2, 3]), &labels);
+    /// assert!(subtree.is_valid());
+    /// assert_eq!(sublabels.labels(), &vec!["C".to_string(), "D".to_string()]);
+    /// assert_eq!(subtree.to_newick(&NewickStyle::ZeroIndexed, None), "(0,1);");
+    /// ```
+    pub fn extract_subtree_remapped(&self, index: TreeIndex, leaf_label_map: &LeafLabelMap) -> (Tree, LeafLabelMap) {
+        let num_leaves = self.leaves_under(index).count();
+        let mut sub_label_map = LeafLabelMap::new(num_leaves);
+        let subtree = self.copy_subtree(index, num_leaves, |label_index| {
+            sub_label_map.get_or_insert(&leaf_label_map[label_index])
+        });
+        (subtree, sub_label_map)
+    }
+
+    // ============================================================================
+    // Pruning (pub)
+    // ============================================================================
+    /// Returns a copy of this tree without the leaves of the given taxa.
+    ///
+    /// Vertices left with a single child are suppressed, merging their branch into the one of the child
+    /// (the merged branch has a length only if both had one), and vertices without children are removed;
+    /// if the root is left with a single child, the highest remaining vertex with two children becomes the root.
+    /// Annotations of merged branches are merged (see [Annotations](Tree#annotations)); node labels and annotations
+    /// of suppressed vertices and the annotation of the branch above a new root are dropped and recorded
+    /// in [Tree::dropped_metadata] of the pruned tree. All others are copied.
+    ///
+    /// As with [Tree::extract_subtree], leaves keep their label indices and the pruned tree is
+    /// created for the same number of leaves; use [restrict_sample] for standalone trees.
+    ///
+    /// # Arguments
+    /// * `taxa` - Label indices of the taxa to remove (taxa not in this tree are ignored)
+    ///
+    /// # Panics
+    /// Panics if fewer than two taxa remain, since a tree needs a root with children.
+    ///
+    /// # Example
+    /// ```
+    /// use nexus_parser::io::parser::byte_parser::ByteParser;
+    /// use nexus_parser::io::parser::newick::NewickParser;
+    /// use nexus_parser::io::writer::newick::NewickStyle;
+    ///
+    /// let mut newick_parser = NewickParser::new().with_num_leaves(4);
+    /// let tree = newick_parser.parse(&mut ByteParser::from_str("((A:1,B:1):2,(C:1,D:2):1);")).unwrap();
+    /// let labels = newick_parser.into_leaf_label_map();
+    ///
+    /// let pruned = tree.prune_taxa(&[labels.get_index("C").unwrap()]);
+    /// assert_eq!(pruned.to_newick(&NewickStyle::Label, Some(&labels)), "((A:1,B:1):2,D:3);");
+    /// ```
+    pub fn prune_taxa(&self, taxa: &[LabelIndex]) -> Tree {
+        let mut removed = vec![false; self.num_leaves_init()];
+        for &taxon in taxa {
+            if taxon < removed.len() {
+                removed[taxon] = true;
+            }
+        }
+
+        self.copy_pruned(self.num_leaves_init(),
+                         |label_index| !removed.get(label_index).copied().unwrap_or(false),
+                         |label_index| label_index)
+    }
+}
+
+// =#========================================================================#=
+// SAMPLE RESTRICTION
+// =#========================================================================#=
+/// Restricts all trees of a sample to the given taxa, pruning all others (see [Tree::prune_taxa]).
+///
+/// The returned trees share a new [LeafLabelMap] of exactly the kept taxa,
+/// indexed in the order given, so they are standalone trees over the common taxon set.
+///
+/// # Arguments
+/// * `trees` - Trees of the sample, sharing `leaf_label_map`
+/// * `leaf_label_map` - Labels of the taxa of the sample
+/// * `keep` - Labels of the taxa to keep (duplicates are ignored)
+///
+/// # Panics
+/// Panics if a label in `keep` is not in `leaf_label_map`,
+/// or fewer than two of the kept taxa are in some tree.
+///
+/// # Example
+/// ```
+/// use nexus_parser::model::subtree::restrict_sample;
+/// use nexus_parser::io::writer::newick::NewickStyle;
+/// use nexus_parser::parse_newick_str;
+///
+/// let (trees, labels) = parse_newick_str("((A,B),(C,D));\n(A,(B,(C,D)));").unwrap();
+///
+/// let (restricted, sub_labels) = restrict_sample(&trees, &labels, &["D", "B", "A"]);
+/// assert_eq!(sub_labels.labels(), &vec!["D".to_string(), "B".to_string(), "A".to_string()]);
+/// assert_eq!(restricted[0].to_newick(&NewickStyle::Label, Some(&sub_labels)), "((A,B),D);");
+/// assert_eq!(restricted[1].to_newick(&NewickStyle::Label, Some(&sub_labels)), "(A,(B,D));");
+/// ```
+pub fn restrict_sample(trees: &[Tree], leaf_label_map: &LeafLabelMap, keep: &[&str]) -> (Vec<Tree>, LeafLabelMap) {
+    let mut sub_label_map = LeafLabelMap::new(keep.len());
+    let mut new_label_index = vec![None; leaf_label_map.num_labels()];
+    for &label in keep {
+        let label_index = leaf_label_map.get_index(label)
+            .unwrap_or_else(|| panic!("Taxon '{}' to keep is not in the sample", label));
+        new_label_index[label_index] = Some(sub_label_map.get_or_insert(label));
+    }
+
+    let restricted = trees.iter()
+        .map(|tree| tree.copy_pruned(sub_label_map.num_labels(),
+                                     |label_index| new_label_index[label_index].is_some(),
+                                     |label_index| new_label_index[label_index].unwrap()))
+        .collect();
+    (restricted, sub_label_map)
+}
+
+// ============================================================================
+// Little Helpers
+// ============================================================================
+impl Tree {
+    /// Copies the subtree below the given vertex bottom-up into a new tree for `num_leaves` leaves,
+    /// mapping label indices of leaves with `map_label` (called in pre-order of the leaves).
+    fn copy_subtree(&self, index: TreeIndex, num_leaves: usize, mut map_label: impl FnMut(LabelIndex) -> LabelIndex) -> Tree {
+        assert!(!self[index].is_leaf(), "Cannot extract subtree below leaf {}, a tree needs a root with children", index);
+
+        // Map labels in pre-order first, so taxa are indexed in order of appearance
+        let label_indices: Vec<(TreeIndex, LabelIndex)> = self.leaves_under(index)
+            .map(|leaf| (leaf.index(), map_label(leaf.label_index().unwrap())))
+            .collect();
+        let mut new_label_index = vec![0; self.num_vertices()];
+        for (leaf, label_index) in label_indices {
+            new_label_index[leaf] = label_index;
+        }
+
+        let mut subtree = Tree::new(num_leaves);
+        let mut new_index = vec![0; self.num_vertices()];
+        for vertex in PostOrderIter::starting_at(self, index) {
+            let old = vertex.index();
+            let new = if vertex.is_leaf() {
+                subtree.add_leaf(vertex.branch_length(), new_label_index[old])
+            } else {
+                let children = vertex.children().unwrap().iter().map(|&child| new_index[child]).collect();
+                let new = if old == index {
+                    subtree.add_root(children)
+                } else {
+                    subtree.add_internal_vertex(children, vertex.branch_length())
+                };
+                subtree[new].set_node_label(vertex.node_label().map(str::to_string));
+                new
+            };
+
+            subtree.set_annotation(new, self.annotation(old).cloned());
+            if old != index {
+                subtree.set_branch_annotation(new, self.branch_annotation(old).cloned());
+            }
+            new_index[old] = new;
+        }
+
+        subtree.with_rooted(self.is_rooted())
+    }
+
+    /// Copies this tree bottom-up into a new tree for `num_leaves` leaves, keeping only leaves
+    /// whose label index satisfies `is_kept` (mapped with `map_label`) and suppressing vertices
+    /// left with a single child (see [Tree::prune_taxa]).
+    fn copy_pruned(&self, num_leaves: usize, is_kept: impl Fn(LabelIndex) -> bool,
+                   map_label: impl Fn(LabelIndex) -> LabelIndex) -> Tree {
+        // Count kept leaves below each vertex
+        let mut counts = vec![0; self.num_vertices()];
+        for vertex in self.post_order_iter() {
+            counts[vertex.index()] = match (vertex.label_index(), vertex.children()) {
+                (Some(label_index), _) => usize::from(is_kept(label_index)),
+                (None, Some(children)) => children.iter().map(|&child| counts[child]).sum(),
+                (None, None) => 0,
+            };
+        }
+
+        // New root is the highest vertex with kept leaves below more than one child
+        let kept_children = |index: TreeIndex| -> Vec<TreeIndex> {
+            self[index].children().map_or(vec![], |children| {
+                children.iter().copied().filter(|&child| counts[child] > 0).collect()
+            })
+        };
+        assert!(counts[self.root().index()] >= 2, "Cannot prune to fewer than two taxa, a tree needs a root with children");
+        let mut root = self.root().index();
+        let mut dropped = Vec::new();
+        while let [child] = kept_children(root)[..] {
+            dropped.push(self.metadata_of(root, true, true));
+            root = child;
+        }
+        if root != self.root().index() {
+            dropped.push(self.metadata_of(root, false, true));
+        }
+
+        let mut pruned = Tree::new(num_leaves);
+        for metadata in dropped {
+            pruned.record_dropped(metadata);
+        }
+        let mut new_index: Vec<Option<TreeIndex>> = vec![None; self.num_vertices()];
+        for vertex in PostOrderIter::starting_at(self, root) {
+            let old = vertex.index();
+            if counts[old] == 0 {
+                continue;
+            }
+
+            let new = if let Some(label_index) = vertex.label_index() {
+                pruned.add_leaf(vertex.branch_length(), map_label(label_index))
+            } else {
+                let children: Vec<TreeIndex> = kept_children(old).iter().map(|&child| new_index[child].unwrap()).collect();
+                if let [child] = children[..] {
+                    // Suppress vertex, merging its branch into the one of its child
+                    let merged = match (pruned[child].branch_length(), vertex.branch_length()) {
+                        (Some(below), Some(above)) => Some(BranchLength::new(*below + *above)),
+                        _ => None,
+                    };
+                    pruned[child].set_branch_length(merged);
+                    let annotation = merge_branch_annotations(pruned.branch_annotation(child).cloned(),
+                                                              self.branch_annotation(old).cloned());
+                    pruned.set_branch_annotation(child, annotation);
+                    pruned.record_dropped(self.metadata_of(old, true, false));
+                    new_index[old] = Some(child);
+                    continue;
+                }
+
+                let new = if old == root {
+                    pruned.add_root(children)
+                } else {
+                    pruned.add_internal_vertex(children, vertex.branch_length())
+                };
+                pruned[new].set_node_label(vertex.node_label().map(str::to_string));
+                new
+            };
+
+            pruned.set_annotation(new, self.annotation(old).cloned());
+            if old != root {
+                pruned.set_branch_annotation(new, self.branch_annotation(old).cloned());
+            }
+            new_index[old] = Some(new);
+        }
+
+        pruned.with_rooted(self.is_rooted())
+    }
+}
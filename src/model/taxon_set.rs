@@ -0,0 +1,209 @@
+//! Taxon set module for clades and splits.
+//!
+//! - `TaxonSet`: Fixed-size bitset over [LabelIndex] of a shared [LeafLabelMap](crate::model::leaf_label_map::LeafLabelMap).
+
+use crate::model::tree::LabelIndex;
+use std::fmt;
+
+/// Number of bits per block
+const BLOCK_BITS: usize = u64::BITS as usize;
+
+// =#========================================================================#=
+// TAXON SET
+// =#========================================================================#=
+/// A set of taxa represented as fixed-size bitset over [LabelIndex].
+///
+/// Used to represent clades (the leaves below a vertex) and splits/bipartitions.
+/// All taxon sets compared with each other should be created with the same `num_taxa`,
+/// typically the number of labels of the shared leaf label map.
+/// Taxon sets are hashable and ordered, so they can be counted in maps and sorted.
+///
+/// # Example
+/// ```
+/// use nexus_parser::model::taxon_set::TaxonSet;
+///
+/// let mut clade = TaxonSet::new(5);
+/// clade.insert(0);
+/// clade.insert(3);
+///
+/// assert!(clade.contains(3));
+/// assert_eq!(clade.len(), 2);
+/// assert_eq!(clade.to_string(), "10010");
+/// assert_eq!(clade.complement(), TaxonSet::from_indices(5, [1, 2, 4]));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TaxonSet {
+    /// Bits of set, with taxon `i` in block `i / 64` at bit `i % 64`
+    blocks: Vec<u64>,
+    /// Number of taxa this set ranges over
+    num_taxa: usize,
+}
+
+impl TaxonSet {
+    /// Creates a new empty taxon set over `num_taxa` taxa.
+    pub fn new(num_taxa: usize) -> Self {
+        TaxonSet {
+            blocks: vec![0; num_taxa.div_ceil(BLOCK_BITS)],
+            num_taxa,
+        }
+    }
+
+    /// Creates a new taxon set over `num_taxa` taxa containing all taxa.
+    pub fn full(num_taxa: usize) -> Self {
+        let mut set = TaxonSet::new(num_taxa);
+        set.blocks.fill(u64::MAX);
+        set.clear_unused_bits();
+        set
+    }
+
+    /// Creates a new taxon set over `num_taxa` taxa containing the given taxa.
+    ///
+    /// # Panics
+    /// Panics if an index is not smaller than `num_taxa`.
+    pub fn from_indices(num_taxa: usize, indices: impl IntoIterator<Item = LabelIndex>) -> Self {
+        let mut set = TaxonSet::new(num_taxa);
+        for index in indices {
+            set.insert(index);
+        }
+        set
+    }
+
+    /// Returns the number of taxa this set ranges over (not the number of contained taxa).
+    pub fn num_taxa(&self) -> usize {
+        self.num_taxa
+    }
+
+    /// Adds a taxon to this set.
+    ///
+    /// # Panics
+    /// Panics if `index` is not smaller than `num_taxa`.
+    pub fn insert(&mut self, index: LabelIndex) {
+        assert!(index < self.num_taxa, "Taxon index {} out of bounds for {} taxa", index, self.num_taxa);
+        self.blocks[index / BLOCK_BITS] |= 1 << (index % BLOCK_BITS);
+    }
+
+    /// Removes a taxon from this set.
+    ///
+    /// # Panics
+    /// Panics if `index` is not smaller than `num_taxa`.
+    pub fn remove(&mut self, index: LabelIndex) {
+        assert!(index < self.num_taxa, "Taxon index {} out of bounds for {} taxa", index, self.num_taxa);
+        self.blocks[index / BLOCK_BITS] &= !(1 << (index % BLOCK_BITS));
+    }
+
+    /// Returns `true` if this set contains the taxon (`false` if out of bounds).
+    pub fn contains(&self, index: LabelIndex) -> bool {
+        index < self.num_taxa && self.blocks[index / BLOCK_BITS] & (1 << (index % BLOCK_BITS)) != 0
+    }
+
+    /// Returns the number of taxa in this set.
+    pub fn len(&self) -> usize {
+        self.blocks.iter().map(|b| b.count_ones() as usize).sum()
+    }
+
+    /// Returns `true` if this set contains no taxa.
+    pub fn is_empty(&self) -> bool {
+        self.blocks.iter().all(|&b| b == 0)
+    }
+
+    /// Returns an iterator over the contained taxa in increasing order.
+    pub fn iter(&self) -> impl Iterator<Item = LabelIndex> + '_ {
+        self.blocks.iter().enumerate().flat_map(|(block_index, &block)| {
+            let mut bits = block;
+            std::iter::from_fn(move || {
+                if bits == 0 {
+                    return None;
+                }
+                let bit = bits.trailing_zeros() as usize;
+                bits &= bits - 1;
+                Some(block_index * BLOCK_BITS + bit)
+            })
+        })
+    }
+
+    /// Returns the smallest contained taxon, or `None` if empty.
+    pub fn first(&self) -> Option<LabelIndex> {
+        self.iter().next()
+    }
+
+    /// Adds all taxa of `other` to this set.
+    ///
+    /// # Panics
+    /// Panics if sets range over different numbers of taxa.
+    pub fn union_with(&mut self, other: &TaxonSet) {
+        self.assert_same_num_taxa(other);
+        for (a, b) in self.blocks.iter_mut().zip(&other.blocks) {
+            *a |= b;
+        }
+    }
+
+    /// Returns the union of this set and `other`.
+    pub fn union(&self, other: &TaxonSet) -> TaxonSet {
+        let mut result = self.clone();
+        result.union_with(other);
+        result
+    }
+
+    /// Returns the intersection of this set and `other`.
+    pub fn intersection(&self, other: &TaxonSet) -> TaxonSet {
+        self.assert_same_num_taxa(other);
+        TaxonSet {
+            blocks: self.blocks.iter().zip(&other.blocks).map(|(a, b)| a & b).collect(),
+            num_taxa: self.num_taxa,
+        }
+    }
+
+    /// Returns the taxa of this set that are not in `other`.
+    pub fn difference(&self, other: &TaxonSet) -> TaxonSet {
+        self.assert_same_num_taxa(other);
+        TaxonSet {
+            blocks: self.blocks.iter().zip(&other.blocks).map(|(a, b)| a & !b).collect(),
+            num_taxa: self.num_taxa,
+        }
+    }
+
+    /// Returns the complement of this set, i.e. all other taxa.
+    pub fn complement(&self) -> TaxonSet {
+        let mut result = TaxonSet {
+            blocks: self.blocks.iter().map(|b| !b).collect(),
+            num_taxa: self.num_taxa,
+        };
+        result.clear_unused_bits();
+        result
+    }
+
+    /// Returns `true` if all taxa of this set are in `other`.
+    pub fn is_subset_of(&self, other: &TaxonSet) -> bool {
+        self.assert_same_num_taxa(other);
+        self.blocks.iter().zip(&other.blocks).all(|(a, b)| a & !b == 0)
+    }
+
+    /// Returns `true` if this set and `other` have no taxon in common.
+    pub fn is_disjoint(&self, other: &TaxonSet) -> bool {
+        self.assert_same_num_taxa(other);
+        self.blocks.iter().zip(&other.blocks).all(|(a, b)| a & b == 0)
+    }
+
+    /// Zeroes bits beyond `num_taxa` in last block, so that equal sets have equal blocks.
+    fn clear_unused_bits(&mut self) {
+        let used_bits = self.num_taxa % BLOCK_BITS;
+        if used_bits > 0 && let Some(last) = self.blocks.last_mut() {
+            *last &= (1 << used_bits) - 1;
+        }
+    }
+
+    fn assert_same_num_taxa(&self, other: &TaxonSet) {
+        assert_eq!(self.num_taxa, other.num_taxa, "Taxon sets range over different numbers of taxa");
+    }
+}
+
+impl fmt::Display for TaxonSet {
+    /// Writes the set as bitstring, with taxon `0` first.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for index in 0..self.num_taxa {
+            write!(f, "{}", if self.contains(index) { '1' } else { '0' })?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,185 @@
+//! Taxonomy module mapping taxa to higher-level groups.
+//!
+//! - `Taxonomy`: Assignment of leaf labels to named groups per rank (e.g. genus, family),
+//!   typically loaded from a TSV file, and source of constraint [TaxonSet]s per rank.
+
+use crate::model::leaf_label_map::LeafLabelMap;
+use crate::model::taxon_set::TaxonSet;
+use std::collections::{BTreeMap, HashMap};
+#[cfg(feature = "fs")]
+use std::fs::File;
+use std::io;
+use std::io::BufRead;
+#[cfg(feature = "fs")]
+use std::io::BufReader;
+
+// =#========================================================================#=
+// TAXONOMY
+// =#========================================================================#=
+/// Assignment of taxa (by leaf label) to named groups for a list of ranks.
+///
+/// Ranks are ordered from lowest (e.g. genus) to highest (e.g. order);
+/// a taxon may be unassigned at some ranks.
+///
+/// # TSV Format
+/// The first non-empty line is a header with `taxon` (or any name) followed by the rank names,
+/// every other line contains a leaf label followed by its group per rank. Columns are tab-separated,
+/// empty cells mean unassigned, and lines starting with `#` are ignored
+/// (`<TAB>` denoting tab characters):
+/// ```text
+/// taxon<TAB>genus<TAB>family
+/// Apteryx haastii<TAB>Apteryx<TAB>Apterygidae
+/// Apteryx owenii<TAB>Apteryx<TAB>Apterygidae
+/// Casuarius casuarius<TAB>Casuarius<TAB>Casuariidae
+/// ```
+///
+/// # Example
+/// ```
+/// use nexus_parser::model::taxonomy::Taxonomy;
+///
+/// let tsv = "taxon\tgenus\tfamily\nApteryx haastii\tApteryx\tApterygidae\n";
+/// let taxonomy = Taxonomy::from_tsv(tsv.as_bytes()).unwrap();
+///
+/// assert_eq!(taxonomy.ranks(), &["genus", "family"]);
+/// assert_eq!(taxonomy.group("Apteryx haastii", 1), Some("Apterygidae"));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Taxonomy {
+    /// Rank names from lowest to highest
+    ranks: Vec<String>,
+    /// Map from leaf label to its group per rank
+    groups: HashMap<String, Vec<Option<String>>>,
+}
+
+impl Taxonomy {
+    /// Creates a new empty taxonomy with the given ranks (lowest first).
+    pub fn new(ranks: Vec<String>) -> Self {
+        Taxonomy {
+            ranks,
+            groups: HashMap::new(),
+        }
+    }
+
+    /// Reads a taxonomy from TSV formatted input (see [Taxonomy] for format).
+    ///
+    /// # Errors
+    /// Returns an error if reading fails, the header is missing,
+    /// a line has more columns than the header or a label appears twice.
+    pub fn from_tsv<R: BufRead>(reader: R) -> io::Result<Self> {
+        let mut taxonomy: Option<Taxonomy> = None;
+
+        for (line_number, line) in reader.lines().enumerate() {
+            let line = line?;
+            let line = line.trim_end_matches('\r');
+            if line.trim().is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let mut columns = line.split('\t').map(str::trim);
+            let first = columns.next().unwrap_or_default();
+
+            let Some(taxonomy) = taxonomy.as_mut() else {
+                // Header line
+                taxonomy = Some(Taxonomy::new(columns.map(str::to_string).collect()));
+                continue;
+            };
+
+            let groups: Vec<Option<String>> = columns
+                .map(|group| if group.is_empty() { None } else { Some(group.to_string()) })
+                .collect();
+            if groups.len() > taxonomy.ranks.len() {
+                return Err(invalid_data(format!("Line {} has more columns than header", line_number + 1)));
+            }
+            if taxonomy.groups.contains_key(first) {
+                return Err(invalid_data(format!("Taxon '{}' assigned twice in line {}", first, line_number + 1)));
+            }
+            taxonomy.insert(first, groups);
+        }
+
+        taxonomy.ok_or_else(|| invalid_data("Missing header line".to_string()))
+    }
+
+    /// Reads a taxonomy from a TSV file (see [Taxonomy] for format).
+    #[cfg(feature = "fs")]
+    pub fn from_tsv_file(path: &str) -> io::Result<Self> {
+        Self::from_tsv(BufReader::new(File::open(path)?))
+    }
+
+    /// Assigns a taxon to groups, one per rank (lowest first); missing trailing ranks are unassigned.
+    ///
+    /// # Panics
+    /// Panics if more groups than ranks are provided.
+    pub fn insert(&mut self, label: &str, mut groups: Vec<Option<String>>) {
+        assert!(groups.len() <= self.ranks.len(), "More groups than ranks given for taxon '{}'", label);
+        groups.resize(self.ranks.len(), None);
+        self.groups.insert(label.to_string(), groups);
+    }
+
+    /// Returns the rank names from lowest to highest.
+    pub fn ranks(&self) -> &[String] {
+        &self.ranks
+    }
+
+    /// Returns the index of the rank with the given name (case-insensitive).
+    pub fn rank_index(&self, rank: &str) -> Option<usize> {
+        self.ranks.iter().position(|r| r.eq_ignore_ascii_case(rank))
+    }
+
+    /// Returns the group of the taxon at the given rank, or `None` if unknown or unassigned.
+    pub fn group(&self, label: &str, rank_index: usize) -> Option<&str> {
+        self.groups.get(label)?.get(rank_index)?.as_deref()
+    }
+
+    /// Returns whether the taxon is part of this taxonomy.
+    pub fn contains_taxon(&self, label: &str) -> bool {
+        self.groups.contains_key(label)
+    }
+
+    /// Returns the number of taxa in this taxonomy.
+    pub fn num_taxa(&self) -> usize {
+        self.groups.len()
+    }
+
+    /// Returns the constraints of the given rank, i.e. for each group its taxa in the [LeafLabelMap],
+    /// sorted by group name.
+    ///
+    /// Only groups with at least two taxa in the leaf label map are returned,
+    /// since single taxa are trivially monophyletic.
+    ///
+    /// # Example
+    /// ```
+    /// use nexus_parser::model::leaf_label_map::LeafLabelMap;
+    /// use nexus_parser::model::taxon_set::TaxonSet;
+    /// use nexus_parser::model::taxonomy::Taxonomy;
+    ///
+    /// let tsv = "taxon\tgenus\nKea\tNestor\nKaka\tNestor\nKakapo\tStrigops\n";
+    /// let taxonomy = Taxonomy::from_tsv(tsv.as_bytes()).unwrap();
+    /// let mut labels = LeafLabelMap::new(3);
+    /// for label in ["Kea", "Kakapo", "Kaka"] {
+    ///     labels.get_or_insert(label);
+    /// }
+    ///
+    /// let constraints = taxonomy.constraints(0, &labels);
+    /// assert_eq!(constraints, vec![("Nestor".to_string(), TaxonSet::from_indices(3, [0, 2]))]);
+    /// ```
+    pub fn constraints(&self, rank_index: usize, leaf_label_map: &LeafLabelMap) -> Vec<(String, TaxonSet)> {
+        let num_taxa = leaf_label_map.num_labels();
+        let mut constraints: BTreeMap<&str, TaxonSet> = BTreeMap::new();
+        for (label_index, label) in leaf_label_map.labels().iter().enumerate() {
+            if let Some(group) = self.group(label, rank_index) {
+                constraints.entry(group)
+                    .or_insert_with(|| TaxonSet::new(num_taxa))
+                    .insert(label_index);
+            }
+        }
+
+        constraints.into_iter()
+            .filter(|(_, taxa)| taxa.len() >= 2)
+            .map(|(group, taxa)| (group.to_string(), taxa))
+            .collect()
+    }
+}
+
+fn invalid_data(msg: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
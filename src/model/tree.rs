@@ -4,15 +4,33 @@
 //! - `Tree`: The main tree structure using the arena pattern for efficient memory layout.
 //! - `TreeIndex` is used to index vertices.
 //! - `LabelIndex` is used to index labels.
+//! - `StoredIndex` is the integer type vertices store their indices as (`u32` by default).
 
+use crate::model::annotation::{Annotation, DroppedMetadata};
 use crate::model::leaf_label_map::LeafLabelMap;
+use crate::model::taxon_set::TaxonSet;
 use crate::model::vertex::{BranchLength, Vertex};
+use std::collections::HashMap;
+use std::sync::OnceLock;
 
 /// Float comparison tolerance
-const EPSILON: f64 = 1e-7;
+pub(crate) const EPSILON: f64 = 1e-7;
 // Consider using relative epsilon/comparison, e.g.:
 // (d1 - d2).abs < abs_tol.max(rel_tol * d1.max(d2))
 
+/// Returns the tolerance for comparing sums of branch lengths of magnitude `length`:
+/// [EPSILON], or the rounding of the summed lengths if they are stored as `f32` (`f32-lengths` feature).
+#[cfg(feature = "f32-lengths")]
+pub(crate) fn length_tolerance(length: f64) -> f64 {
+    EPSILON.max(2.0 * length.abs() * f32::EPSILON as f64)
+}
+
+/// Returns the tolerance for comparing sums of branch lengths of magnitude `length`: [EPSILON].
+#[cfg(not(feature = "f32-lengths"))]
+pub(crate) fn length_tolerance(_length: f64) -> f64 {
+    EPSILON
+}
+
 /// Index of a vertex in a tree (arena).
 pub type TreeIndex = usize;
 
@@ -22,11 +40,24 @@ const NO_ROOT_SET_INDEX: TreeIndex = usize::MAX;
 /// Index of a leaf label in a [LeafLabelMap].
 pub type LabelIndex = usize;
 
+/// Integer type a [Vertex] stores its own, its parent's and its label's index as, `u32` by default
+/// (trees and label maps of at most `u32::MAX` entries), halving their memory on 64-bit machines;
+/// the `wide-index` feature stores them as `usize`. The API always uses [TreeIndex] and [LabelIndex].
+#[cfg(not(feature = "wide-index"))]
+pub type StoredIndex = u32;
+
+/// Integer type a [Vertex] stores its own, its parent's and its label's index as, `usize` with the `wide-index` feature.
+/// The API always uses [TreeIndex] and [LabelIndex].
+#[cfg(feature = "wide-index")]
+pub type StoredIndex = usize;
+
 
 // =#========================================================================#=
 // TREE
 // =#========================================================================#=
-/// A binary phylogenetic tree represented using the arena pattern on [Vertex].
+/// A phylogenetic tree represented using the arena pattern on [Vertex].
+///
+/// Trees are typically binary, but multifurcating trees (with polytomies) are supported as well.
 ///
 /// Vertices are stored in a contiguous vector and referenced by [TreeIndex].
 /// Aim is to avoid referencing troubles as well as to provide efficient memory layout
@@ -44,6 +75,14 @@ pub type LabelIndex = usize;
 /// Bottom-up construction is likely easiest, but indices can also be managed otherwise.
 /// Test validity with [Tree::is_valid].
 ///
+/// # Annotations
+/// Annotations of vertices and branches are kept by index and follow every edit: they stay with their vertex
+/// or move along with their branch. If an edit suppresses a vertex (e.g. [Tree::prune_taxa], [Tree::unroot]),
+/// its two branches are merged into one with the branch lengths summed and the annotation of the lower branch,
+/// completed by the entries of the upper one (see [Annotation::merge]). The node label and annotation of the
+/// suppressed vertex, as well as the annotation of a branch above a new root, have nowhere to go and are dropped,
+/// but recorded in [Tree::dropped_metadata].
+///
 /// # Example
 /// ```
 /// use nexus_parser::model::tree::Tree;
@@ -61,14 +100,15 @@ pub type LabelIndex = usize;
 /// let index_c = tree.add_leaf(Some(BranchLength::new(0.4)), labels.get_or_insert("C"));
 ///
 /// // Add internal vertex with A and B as children
-/// let index_internal = tree.add_internal_vertex((index_a, index_b), Some(BranchLength::new(0.2)));
+/// let index_internal = tree.add_internal_vertex(vec![index_a, index_b], Some(BranchLength::new(0.2)));
 ///
 /// // Add root with internal node and C as children
-/// tree.add_root((index_internal, index_c));
+/// tree.add_root(vec![index_internal, index_c]);
 ///
 /// assert!(tree.is_valid());
 /// ```
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(try_from = "TreeData"))]
 pub struct Tree {
     /// Number of leaf nodes in the tree
     num_leaves_init: usize,
@@ -81,6 +121,30 @@ pub struct Tree {
 
     /// Name of tree; optional, e.g. when parsed from Nexus file
     name: Option<String>,
+
+    /// Metadata of the whole tree, e.g. `[&lnP=-1234.5]` before the Newick string of a Nexus TREE command
+    metadata: Option<Annotation>,
+
+    /// Whether the root is meaningful; `true` unless marked unrooted (e.g. `[&U]`) or [unrooted](Tree::unroot)
+    rooted: bool,
+
+    /// Annotations of vertices (e.g. `A[&rate=0.1]`); only if collected when parsing
+    annotations: HashMap<TreeIndex, Annotation>,
+
+    /// Annotations of branches above vertices (e.g. `A:[&rate=0.1]0.5`); only if collected when parsing
+    branch_annotations: HashMap<TreeIndex, Annotation>,
+
+    /// Taxa below each vertex over `num_leaves_init` taxa; computed on first per-vertex query, cleared on edits
+    #[cfg_attr(feature = "serde", serde(skip))]
+    clade_cache: OnceLock<Vec<TaxonSet>>,
+
+    /// Height of each vertex (`None` if not ultrametric); computed on first query, cleared on edits
+    #[cfg_attr(feature = "serde", serde(skip))]
+    height_cache: OnceLock<Option<Vec<f64>>>,
+
+    /// Node labels and annotations dropped by edits suppressing vertices, oldest first
+    #[cfg_attr(feature = "serde", serde(skip))]
+    dropped_metadata: Vec<DroppedMetadata>,
 }
 
 // ============================================================================
@@ -89,39 +153,96 @@ pub struct Tree {
 impl Tree {
     /// Creates a new tree with capacity for a binary tree with `num_leaves` leaves.
     ///
+    /// A multifurcating tree on `num_leaves` leaves has fewer vertices, so the capacity suffices as well.
+    ///
     /// # Arguments
-    /// `num_leaves` - number of leaves of the new binary tree, implying number of vertices; must be positive
+    /// `num_leaves` - number of leaves of the new tree, implying (maximum) number of vertices; must be positive
     pub fn new(num_leaves: usize) -> Self {
         assert!(num_leaves > 0);
-        let capacity = 2 * num_leaves - 1;
+        Self::with_capacity(num_leaves, 2 * num_leaves - 1)
+    }
+
+    /// Creates a new tree on `num_leaves` leaves with capacity for exactly `num_vertices` vertices,
+    /// e.g. fewer than [Tree::new] reserves for trees with polytomies, or more for trees with unary vertices.
+    ///
+    /// # Arguments
+    /// * `num_leaves` - Number of leaves of the new tree
+    /// * `num_vertices` - Number of vertices to reserve space for in the arena
+    ///
+    /// # Example
+    /// ```
+    /// use nexus_parser::model::tree::Tree;
+    ///
+    /// // Star tree on 4 leaves: root and leaves only
+    /// let mut tree = Tree::with_capacity(4, 5);
+    /// let leaves = (0..4).map(|label_index| tree.add_leaf(None, label_index)).collect();
+    /// tree.add_root(leaves);
+    /// assert!(tree.is_valid());
+    /// ```
+    pub fn with_capacity(num_leaves: usize, num_vertices: usize) -> Self {
+        Self::with_vertex_arena(num_leaves, Vec::with_capacity(num_vertices))
+    }
+
+    /// Creates a new tree on `num_leaves` leaves reusing the given vertex arena (cleared first),
+    /// e.g. of a tree no longer needed (see [TreeBuilder](crate::model::tree_builder::TreeBuilder)).
+    pub(crate) fn with_vertex_arena(num_leaves: usize, mut vertices: Vec<Vertex>) -> Self {
+        vertices.clear();
         Tree {
             num_leaves_init: num_leaves,
             name: None,
+            metadata: None,
+            rooted: true,
             root_index: NO_ROOT_SET_INDEX,
-            vertices: Vec::with_capacity(capacity),
+            vertices,
+            annotations: HashMap::new(),
+            branch_annotations: HashMap::new(),
+            clade_cache: OnceLock::new(),
+            height_cache: OnceLock::new(),
+            dropped_metadata: Vec::new(),
         }
     }
 
+    /// Corrects the number of leaves this tree was created for,
+    /// e.g. once parsing revealed it (capacity is not adjusted).
+    pub(crate) fn set_num_leaves_init(&mut self, num_leaves: usize) {
+        self.num_leaves_init = num_leaves;
+        self.invalidate_caches();
+    }
+
     /// Attaches a name to this tree.
     pub fn with_name(mut self, name: String) -> Self {
         self.name = Some(name);
         self
     }
 
+    /// Attaches metadata of the whole tree (e.g. from comments of a NEXUS TREE command).
+    pub fn with_metadata(mut self, metadata: Annotation) -> Self {
+        self.metadata = Some(metadata);
+        self
+    }
+
+    /// Marks this tree as rooted or unrooted (e.g. from `[&R]` or `[&U]` in a NEXUS file).
+    pub fn with_rooted(mut self, rooted: bool) -> Self {
+        self.rooted = rooted;
+        self
+    }
+
     /// Adds a root to the tree, assigning a unique index, which gets returned.
     ///
     /// # Arguments
-    /// * `children` - Tuple of child indices
+    /// * `children` - Indices of children (two for binary trees)
     ///
     /// # Returns
     /// The index of the newly created root vertex.
-    pub fn add_root(&mut self, children: (TreeIndex, TreeIndex)) -> TreeIndex {
+    pub fn add_root(&mut self, children: Vec<TreeIndex>) -> TreeIndex {
         let index = self.vertices.len();
+        for &child in &children {
+            self[child].set_parent(index);
+        }
         self.vertices.push(Vertex::new_root(index, children));
+        self.invalidate_caches();
 
         self.root_index = index;
-        self[children.0].set_parent(index);
-        self[children.1].set_parent(index);
 
         index
     }
@@ -129,7 +250,7 @@ impl Tree {
     /// Adds an internal vertex to the tree, assigning a unique index, which gets returned.
     ///
     /// # Arguments
-    /// * `children` - Tuple of child indices
+    /// * `children` - Indices of children (two for binary trees)
     /// * `branch_length` - Length of incoming branch, i.e. distance to parent (non-negative)
     ///
     /// # Returns
@@ -137,12 +258,13 @@ impl Tree {
     ///
     /// # Panics
     /// Panics if `branch_length` is negative.
-    pub fn add_internal_vertex(&mut self, children: (TreeIndex, TreeIndex), branch_length: Option<BranchLength>) -> TreeIndex {
+    pub fn add_internal_vertex(&mut self, children: Vec<TreeIndex>, branch_length: Option<BranchLength>) -> TreeIndex {
         let index = self.vertices.len();
+        for &child in &children {
+            self[child].set_parent(index);
+        }
         self.vertices.push(Vertex::new_internal(index, children, branch_length));
-
-        self[children.0].set_parent(index);
-        self[children.1].set_parent(index);
+        self.invalidate_caches();
 
         index
     }
@@ -161,6 +283,7 @@ impl Tree {
     pub fn add_leaf(&mut self, branch_length: Option<BranchLength>, label_index: LabelIndex) -> usize {
         let index = self.vertices.len();
         self.vertices.push(Vertex::new_leaf(index, branch_length, label_index));
+        self.invalidate_caches();
         index
     }
 
@@ -170,10 +293,13 @@ impl Tree {
     /// - Root index is valid and points to a Root vertex
     /// - All vertex indices match their position in the arena
     /// - There are the right number of leaves and only one root
+    /// - Root and internal vertices have at least one child
     /// - All child indices are valid and point back to correct parent
     /// - All parent indices are valid and include this vertex as a child
     /// - Root vertex has no parent set, all others have valid parent set
     ///
+    /// See [Tree::validate] for a report of all issues.
+    ///
     /// # Returns
     /// `true` if tree is valid, `false` otherwise
     pub fn is_valid(&self) -> bool {
@@ -194,6 +320,8 @@ impl Tree {
 
         let mut leaf_count = 0;
         let mut found_root = false;
+        // Number of times each vertex is listed as child (linear check also for huge polytomies)
+        let mut times_listed = vec![0_u8; self.vertices.len()];
 
         // Validate each vertex
         for (index, vertex) in self.vertices.iter().enumerate() {
@@ -220,18 +348,27 @@ impl Tree {
             }
 
             // Check children references
-            if let Some((left, right)) = vertex.children() {
-                // Check child indices are in bounds
-                if left >= self.vertices.len() || right >= self.vertices.len() {
+            if let Some(children) = vertex.children() {
+                if children.is_empty() {
                     return false;
                 }
 
-                // Check children point back to this vertex as parent
-                let left_parent = self.vertices[left].parent_index();
-                let right_parent = self.vertices[right].parent_index();
+                for &child in children {
+                    // Check child index is in bounds
+                    if child >= self.vertices.len() {
+                        return false;
+                    }
 
-                if left_parent != Some(index) || right_parent != Some(index) {
-                    return false;
+                    // Check child points back to this vertex as parent
+                    if self.vertices[child].parent_index() != Some(index) {
+                        return false;
+                    }
+
+                    // Check child is not listed twice
+                    times_listed[child] += 1;
+                    if times_listed[child] > 1 {
+                        return false;
+                    }
                 }
             }
 
@@ -251,13 +388,9 @@ impl Tree {
                             return false;
                         }
 
-                        // Check parent includes this vertex in its children
-                        if let Some((left, right)) = self.vertices[parent_index].children() {
-                            if left != index && right != index {
-                                return false;
-                            }
-                        } else {
-                            // Parent has no children - invalid
+                        // Check parent has children; that they include this vertex follows from
+                        // children pointing back to their parent and each vertex being listed once (below)
+                        if self.vertices[parent_index].children().is_none() {
                             return false;
                         }
                     }
@@ -278,6 +411,11 @@ impl Tree {
             return false;
         }
 
+        // Check that each non-root vertex is listed as child (of its parent, as checked above)
+        if self.vertices.iter().any(|vertex| !vertex.is_root() && times_listed[vertex.index()] == 0) {
+            return false;
+        }
+
         true
     }
 
@@ -286,6 +424,19 @@ impl Tree {
         self.name.as_ref()
     }
 
+    /// Returns whether the root of this tree is meaningful.
+    ///
+    /// Trees are rooted unless marked unrooted, e.g. with `[&U]` in a NEXUS file or by [Tree::unroot].
+    /// Note that an unrooted tree is still stored with a (then arbitrary) root vertex.
+    pub fn is_rooted(&self) -> bool {
+        self.rooted
+    }
+
+    /// Marks this tree as rooted or unrooted without changing its structure.
+    pub fn set_rooted(&mut self, rooted: bool) {
+        self.rooted = rooted;
+    }
+
     /// Returns whether root of tree has been set.
     pub fn is_root_set(&self) -> bool {
         self.root_index != NO_ROOT_SET_INDEX
@@ -304,6 +455,7 @@ impl Tree {
     /// # Panics
     /// Panics if the root hasn't been set and thus tree hasn't been fully constructed yet.
     pub fn root_mut(&mut self) -> &mut Vertex {
+        self.invalidate_caches();
         &mut self.vertices[self.root_index]
     }
 
@@ -324,6 +476,7 @@ impl Tree {
     ///
     /// `Some(&Vertex)` if the index is valid, `None` otherwise
     pub fn vertex_mut(&mut self, index: usize) -> &mut Vertex {
+        self.invalidate_caches();
         &mut self.vertices[index]
     }
 
@@ -347,6 +500,11 @@ impl Tree {
         self.vertices.len()
     }
 
+    /// Returns whether this tree is binary, i.e. the root and all internal vertices have exactly two children.
+    pub fn is_binary(&self) -> bool {
+        self.vertices.iter().all(|v| v.is_leaf() || v.num_children() == 2)
+    }
+
     /// Returns the height of this tree (assuming it is ultrametric; undefined otherwise),
     /// that is, the distance of the root to any/each leaf.
     pub fn height(&self) -> f64 {
@@ -357,14 +515,11 @@ impl Tree {
     /// that is, the distance of the given vertex to any/each leaf.
     pub fn height_of(&self, vertex: &Vertex) -> f64 {
         let mut height = 0.0;
-        loop {
-            let child_index = vertex.children().unwrap().0;
-            let vertex: &Vertex = &self.vertices[child_index];
-            height = height + *vertex.branch_length().unwrap();
-
-            if vertex.is_leaf() {
-                break;
-            }
+        let mut vertex = vertex;
+        while !vertex.is_leaf() {
+            let child_index = vertex.children().unwrap()[0];
+            vertex = &self.vertices[child_index];
+            height += *vertex.branch_length().unwrap();
         }
 
         height
@@ -387,16 +542,16 @@ impl Tree {
             if vertex.is_leaf() {
                 distances[vertex.index()] = *vertex.branch_length().unwrap();
             } else {
-                let (left, right) = vertex.children().unwrap();
-                let left_dist = distances[left];
-                let right_dist = distances[right];
+                let children = vertex.children().unwrap();
+                let first_dist = distances[children[0]];
 
-                if (left_dist - right_dist).abs() > EPSILON {
+                let tolerance = length_tolerance(first_dist);
+                if children.iter().any(|&child| (distances[child] - first_dist).abs() > tolerance) {
                     return false;
                 }
 
                 if !vertex.is_root() {
-                    distances[vertex.index()] = left_dist + *vertex.branch_length().unwrap();
+                    distances[vertex.index()] = first_dist + *vertex.branch_length().unwrap();
                 }
             }
         }
@@ -442,7 +597,7 @@ impl Tree {
     /// let mut labels = LeafLabelMap::new(2);
     /// let a = tree.add_leaf(Some(BranchLength::new(1.0)), labels.get_or_insert("A"));
     /// let b = tree.add_leaf(Some(BranchLength::new(1.0)), labels.get_or_insert("B"));
-    /// tree.add_root((a, b));
+    /// tree.add_root(vec![a, b]);
     ///
     /// let indices: Vec<_> = tree.post_order_iter().map(|v| v.index()).collect();
     /// // Leaves come before root
@@ -466,7 +621,7 @@ impl Tree {
     /// let mut labels = LeafLabelMap::new(2);
     /// let a = tree.add_leaf(Some(BranchLength::new(1.0)), labels.get_or_insert("A"));
     /// let b = tree.add_leaf(Some(BranchLength::new(1.0)), labels.get_or_insert("B"));
-    /// tree.add_root((a, b));
+    /// tree.add_root(vec![a, b]);
     ///
     /// let indices: Vec<_> = tree.pre_order_iter().map(|v| v.index()).collect();
     /// // Root comes before leaves
@@ -476,142 +631,474 @@ impl Tree {
     }
 }
 
-impl std::ops::Index<TreeIndex> for Tree {
-    type Output = Vertex;
+// ============================================================================
+// Clades (pub)
+// ============================================================================
+impl Tree {
+    /// Returns the clade (set of leaf labels below) of every vertex, indexed by [TreeIndex].
+    ///
+    /// # Arguments
+    /// * `num_taxa` - Number of taxa the [TaxonSet]s range over, typically the number of labels
+    ///   of the shared leaf label map
+    ///
+    /// # Panics
+    /// Panics if a leaf has a label index not smaller than `num_taxa`.
+    ///
+    /// # Example
+    /// ```
+    /// use nexus_parser::model::tree::Tree;
+    /// use nexus_parser::model::taxon_set::TaxonSet;
+    ///
+    /// let mut tree = Tree::new(3);
+    /// let a = tree.add_leaf(None, 0);
+    /// let b = tree.add_leaf(None, 1);
+    /// let c = tree.add_leaf(None, 2);
+    /// let ab = tree.add_internal_vertex(vec![a, b], None);
+    /// let root = tree.add_root(vec![ab, c]);
+    ///
+    /// let clades = tree.clades(3);
+    /// assert_eq!(clades[ab], TaxonSet::from_indices(3, [0, 1]));
+    /// assert_eq!(clades[root], TaxonSet::full(3));
+    /// ```
+    pub fn clades(&self, num_taxa: usize) -> Vec<TaxonSet> {
+        let mut clades = vec![TaxonSet::new(num_taxa); self.vertices.len()];
+        for vertex in self.post_order_iter() {
+            let index = vertex.index();
+            if let Some(label_index) = vertex.label_index() {
+                clades[index].insert(label_index);
+            } else if let Some(children) = vertex.children() {
+                // Children are visited before their parent (post-order)
+                let mut clade = TaxonSet::new(num_taxa);
+                for &child in children {
+                    clade.union_with(&clades[child]);
+                }
+                clades[index] = clade;
+            }
+        }
+        clades
+    }
 
-    fn index(&self, index: TreeIndex) -> &Self::Output {
-        &self.vertices[index]
+    /// Returns the taxa below the given vertex, ranging over the [number of leaves](Tree::num_leaves_init).
+    ///
+    /// The clades of all vertices are computed on the first call and cached,
+    /// so repeated clade-based queries on the same tree are cheap;
+    /// mutable access to vertices (e.g. adding vertices, [Tree::vertex_mut]) clears the cache.
+    /// Operations over whole samples (e.g. [Tree::bipartitions]) compute clades without caching them,
+    /// so the trees of a sample do not keep `num_vertices * num_leaves` bits each.
+    ///
+    /// # Panics
+    /// Panics if `index` is not a vertex of this tree, or a leaf has a label index
+    /// not smaller than the number of leaves.
+    ///
+    /// # Example
+    /// ```
+    /// use nexus_parser::model::tree::Tree;
+    /// use nexus_parser::model::taxon_set::TaxonSet;
+    ///
+    /// let mut tree = Tree::new(3);
+    /// let a = tree.add_leaf(None, 0);
+    /// let b = tree.add_leaf(None, 1);
+    /// let c = tree.add_leaf(None, 2);
+    /// let ab = tree.add_internal_vertex(vec![a, b], None);
+    /// tree.add_root(vec![ab, c]);
+    ///
+    /// assert_eq!(tree.clade_taxa(ab), &TaxonSet::from_indices(3, [0, 1]));
+    /// ```
+    pub fn clade_taxa(&self, index: TreeIndex) -> &TaxonSet {
+        &self.cached_clades()[index]
     }
-}
 
-impl std::ops::IndexMut<TreeIndex> for Tree {
-    fn index_mut(&mut self, index: TreeIndex) -> &mut Self::Output {
-        &mut self.vertices[index]
+    /// Returns the (cached) clades of all vertices, indexed by [TreeIndex],
+    /// i.e. [Tree::clades] over the [number of leaves](Tree::num_leaves_init), see [Tree::clade_taxa].
+    ///
+    /// Use [Tree::clades] instead to not keep the clades, e.g. when going once over each tree of a sample.
+    pub fn cached_clades(&self) -> &[TaxonSet] {
+        self.clade_cache.get_or_init(|| self.clades(self.num_leaves_init))
+    }
+
+    /// Returns the taxa at the leaves of this tree, ranging over the [number of leaves](Tree::num_leaves_init),
+    /// i.e. the clade of the root (without computing or caching the other clades).
+    ///
+    /// # Panics
+    /// Panics if a leaf has a label index not smaller than the number of leaves.
+    pub fn leaf_taxa(&self) -> TaxonSet {
+        let label_indices = self.post_order_iter().filter_map(|vertex| vertex.label_index());
+        TaxonSet::from_indices(self.num_leaves_init, label_indices)
+    }
+
+    /// Checks if the given taxa form a clade of this (rooted) tree,
+    /// i.e. if some vertex has exactly these taxa below it.
+    ///
+    /// Empty sets are never monophyletic, single taxa are if they are in the tree.
+    pub fn is_monophyletic(&self, taxa: &TaxonSet) -> bool {
+        if taxa.is_empty() {
+            return false;
+        }
+        if taxa.num_taxa() == self.num_leaves_init {
+            return self.cached_clades().contains(taxa);
+        }
+        self.clades(taxa.num_taxa()).contains(taxa)
+    }
+
+    /// Clears the cached clades and node heights, to be called whenever the structure may change.
+    fn invalidate_caches(&mut self) {
+        self.clade_cache.take();
+        self.height_cache.take();
+    }
+
+    /// Replaces all vertices and their annotations at once, e.g. after restructuring the tree
+    /// (see [root_at](Tree::root_at)); callers must keep indices and references consistent.
+    pub(crate) fn replace_vertices(&mut self, vertices: Vec<Vertex>, root_index: TreeIndex,
+                                   annotations: HashMap<TreeIndex, Annotation>,
+                                   branch_annotations: HashMap<TreeIndex, Annotation>) {
+        self.vertices = vertices;
+        self.root_index = root_index;
+        self.annotations = annotations;
+        self.branch_annotations = branch_annotations;
+        self.invalidate_caches();
+    }
+
+    /// Consumes the tree and returns its vertex arena, e.g. to reuse its buffers for another tree.
+    pub(crate) fn into_vertex_arena(self) -> Vec<Vertex> {
+        self.vertices
+    }
+
+    /// Moves the leaves to another label mapping, replacing label index `i` by `mapping[i]`,
+    /// and sets the number of leaves to the `num_labels` of the new mapping.
+    ///
+    /// # Panics
+    /// Panics if a label index of a leaf is not covered by `mapping`.
+    pub(crate) fn remap_label_indices(&mut self, mapping: &[LabelIndex], num_labels: usize) {
+        for vertex in &mut self.vertices {
+            if let Some(label_index) = vertex.label_index() {
+                vertex.set_label_index(mapping[label_index]);
+            }
+        }
+        self.set_num_leaves_init(num_labels);
     }
 }
 
 // ============================================================================
-// Printing (pub) + NEWICK STYLE
+// Node Heights (pub)
 // ============================================================================
 impl Tree {
-    /// Converts the tree to Newick format string.
+    /// Returns the height of every vertex, indexed by [TreeIndex],
+    /// that is, its distance to the leaves below it (leaves have height 0).
     ///
-    /// The Newick format represents phylogenetic trees as nested parentheses with branch lengths.
-    /// For example: `(('Little Spotted Kiwi;'1.0,'Great Spotted Kiwi':1.0):0.5,'Okarito Brown Kiwi':1.5);`
-    ///
-    /// # Arguments
-    /// * `style` - How to represent leaf labels in the output
-    /// * `leaf_label_map` - Required when using `NewickStyle::Label`, otherwise can be `None`
+    /// For time trees, these are the divergence times of the vertices (before the present).
+    /// The heights are computed on the first call and cached, so repeated queries are O(1);
+    /// mutable access to vertices (e.g. [Tree::vertex_mut]) clears the cache.
     ///
     /// # Returns
-    /// A Newick format string terminated with `;`. Returns an empty string if
-    /// `NewickStyle::Label` is used without providing a [LeafLabelMap].
+    /// `None` if not all vertices (besides root) have a [BranchLength]
+    /// or the tree is not ultrametric, i.e. the children of some vertex disagree on its height
+    /// beyond a small tolerance (absolute, or relative for large heights).
     ///
     /// # Example
     /// ```
-    /// use nexus_parser::model::tree::{Tree, NewickStyle};
-    /// use nexus_parser::model::leaf_label_map::LeafLabelMap;
+    /// use nexus_parser::model::tree::Tree;
     /// use nexus_parser::model::vertex::BranchLength;
     ///
-    /// let mut tree = Tree::new(2);
-    /// let mut labels = LeafLabelMap::new(2);
-    /// let a = tree.add_leaf(Some(BranchLength::new(1.0)), labels.get_or_insert("A"));
-    /// let b = tree.add_leaf(Some(BranchLength::new(2.0)), labels.get_or_insert("B"));
-    /// tree.add_root((a, b));
+    /// // ((0:1,1:1):2,2:3)
+    /// let mut tree = Tree::new(3);
+    /// let a = tree.add_leaf(Some(BranchLength::new(1.0)), 0);
+    /// let b = tree.add_leaf(Some(BranchLength::new(1.0)), 1);
+    /// let c = tree.add_leaf(Some(BranchLength::new(3.0)), 2);
+    /// let ab = tree.add_internal_vertex(vec![a, b], Some(BranchLength::new(2.0)));
+    /// let root = tree.add_root(vec![ab, c]);
     ///
-    /// let newick = tree.to_newick(NewickStyle::Label, Some(&labels));
-    /// assert_eq!(newick, "(A:1,B:2);");
+    /// let heights = tree.node_heights().unwrap();
+    /// assert_eq!(heights[a], 0.0);
+    /// assert_eq!(heights[ab], 1.0);
+    /// assert_eq!(heights[root], 3.0);
     /// ```
-    pub fn to_newick(&self, style: NewickStyle, leaf_label_map: Option<&LeafLabelMap>) -> String {
-        // Helper for adding branch lengths
-        fn build_newick_branch_length(newick: &mut String, branch_length: Option<BranchLength>) {
-            if let Some(branch_length) = branch_length {
-                newick.push(':');
-                newick.push_str(&branch_length.to_string());
-            }
-        }
+    pub fn node_heights(&self) -> Option<&[f64]> {
+        self.height_cache.get_or_init(|| self.compute_node_heights()).as_deref()
+    }
 
-        // Recursive helper for building the Newick string
-        fn build_newick(tree: &Tree, newick: &mut String, index: TreeIndex, style: &NewickStyle, leaf_label_map: Option<&LeafLabelMap>) {
-            let vertex = &tree[index];
+    /// Returns the height of the root, that is, the age of the tree (see [Tree::node_heights]).
+    ///
+    /// # Returns
+    /// `None` if branch lengths are missing or the tree is not ultrametric.
+    pub fn root_height(&self) -> Option<f64> {
+        self.node_heights().map(|heights| heights[self.root_index])
+    }
 
-            if vertex.is_leaf() {
-                // Add label based on style
-                let label_index = vertex.label_index().unwrap();
-                match style {
-                    NewickStyle::Label => {
-                        let label = &leaf_label_map.unwrap()[label_index];
-                        newick.push_str(label);
-                    }
-                    NewickStyle::ZeroIndexed => {
-                        newick.push_str(&label_index.to_string());
-                    }
-                    NewickStyle::OneIndexed => {
-                        newick.push_str(&(label_index + 1).to_string());
-                    }
-                }
-                build_newick_branch_length(newick, vertex.branch_length());
-            } else {
-                let (left, right) = vertex.children().unwrap();
+    /// Returns the height of the given vertex (see [Tree::node_heights]).
+    ///
+    /// # Returns
+    /// `None` if branch lengths are missing or the tree is not ultrametric.
+    ///
+    /// # Panics
+    /// Panics if `index` is not a vertex of this tree.
+    pub fn node_height(&self, index: TreeIndex) -> Option<f64> {
+        self.node_heights().map(|heights| heights[index])
+    }
 
-                newick.push('(');
-                build_newick(tree, newick, left, style, leaf_label_map);
-                newick.push(',');
-                build_newick(tree, newick, right, style, leaf_label_map);
-                newick.push(')');
+    /// Computes the heights of all vertices bottom-up, checking that children agree.
+    fn compute_node_heights(&self) -> Option<Vec<f64>> {
+        let mut heights = vec![0.0; self.num_vertices()];
+        for vertex in self.post_order_iter() {
+            let Some(children) = vertex.children() else {
+                continue;
+            };
 
-                if !vertex.is_root() {
-                    build_newick_branch_length(newick, vertex.branch_length());
+            // Height of this vertex as seen from each child
+            let mut via_children = children.iter()
+                .map(|&child| self.vertices[child].branch_length().map(|bl| heights[child] + *bl));
+            let height = via_children.next()??;
+            for other in via_children {
+                let other = other?;
+                if (other - height).abs() > EPSILON.max(EPSILON * height.abs().max(other.abs())) {
+                    return None;
                 }
             }
+            heights[vertex.index()] = height;
         }
 
-        // Abort right away if arguments don't match
-        if matches!(style, NewickStyle::Label) && leaf_label_map.is_none() {
-            return String::new();
-        }
+        Some(heights)
+    }
+}
 
-        // Estimate capacity:
-        // - Each leaf: "label" (can compute total) or "id" ~= 2
-        const LEAF_ID_CHARS: usize = 2;  // "99" for indices
-        // - Each internal node: "(,)" ~= 3 chars
-        const INTERNAL_NODE_CHARS: usize = 3;  // "(,)"
-        // - Branch lengths: ~20 chars each (e.g., ":0.009529961339106089")
-        const BRANCH_LENGTH_CHARS: usize = 20;
-
-        // -> Structural
-        let num_internal = self.num_internal() + 1; // +1 for root
-        let structure_capacity = num_internal * INTERNAL_NODE_CHARS;
-
-        // -> Labels
-        let num_leaves = self.num_leaves();
-        let label_capacity = match style {
-            NewickStyle::Label => {
-                let total_label_len: usize = leaf_label_map.unwrap().labels().iter().map(|s| s.len()).sum();
-                total_label_len
-            }
-            NewickStyle::ZeroIndexed | NewickStyle::OneIndexed => {
-                num_leaves * LEAF_ID_CHARS
-            }
+// ============================================================================
+// Annotations (pub)
+// ============================================================================
+impl Tree {
+    /// Returns the annotation of the vertex with the given index, if any.
+    pub fn annotation(&self, index: TreeIndex) -> Option<&Annotation> {
+        self.annotations.get(&index)
+    }
+
+    /// Returns the annotation of the branch above the vertex with the given index, if any.
+    pub fn branch_annotation(&self, index: TreeIndex) -> Option<&Annotation> {
+        self.branch_annotations.get(&index)
+    }
+
+    /// Sets (or removes) the annotation of the vertex with the given index.
+    pub fn set_annotation(&mut self, index: TreeIndex, annotation: Option<Annotation>) {
+        debug_assert!(index < self.vertices.len(), "Annotated vertex {} is not in the arena of {} vertices (stale index?)", index, self.vertices.len());
+        match annotation {
+            Some(annotation) => self.annotations.insert(index, annotation),
+            None => self.annotations.remove(&index),
+        };
+    }
+
+    /// Sets (or removes) the annotation of the branch above the vertex with the given index.
+    pub fn set_branch_annotation(&mut self, index: TreeIndex, annotation: Option<Annotation>) {
+        debug_assert!(index < self.vertices.len(), "Annotated vertex {} is not in the arena of {} vertices (stale index?)", index, self.vertices.len());
+        match annotation {
+            Some(annotation) => self.branch_annotations.insert(index, annotation),
+            None => self.branch_annotations.remove(&index),
         };
+    }
 
-        // -> Branch lengths
-        let branch_capacity = if self.vertices_have_branch_lengths() {
-            (num_leaves + num_internal - 1) * BRANCH_LENGTH_CHARS
-        } else {
-            0
+    /// Returns the metadata of the whole tree, if any,
+    /// e.g. `lnP=-1234.5` of `tree STATE_1000 [&lnP=-1234.5] = [&R] (...)` in a NEXUS file.
+    pub fn metadata(&self) -> Option<&Annotation> {
+        self.metadata.as_ref()
+    }
+
+    /// Sets (or removes) the metadata of the whole tree.
+    pub fn set_metadata(&mut self, metadata: Option<Annotation>) {
+        self.metadata = metadata;
+    }
+
+    /// Returns whether any vertex or branch of this tree is annotated.
+    pub fn has_annotations(&self) -> bool {
+        !self.annotations.is_empty() || !self.branch_annotations.is_empty()
+    }
+
+    /// Returns the node labels and annotations that edits suppressing vertices had to drop, oldest first,
+    /// see [Annotations](Tree#annotations).
+    ///
+    /// # Example
+    /// ```
+    /// use nexus_parser::io::parser::byte_parser::ByteParser;
+    /// use nexus_parser::io::parser::newick::NewickParser;
+    /// use nexus_parser::io::parser::newick::AnnotationMode;
+    ///
+    /// let mut newick_parser = NewickParser::new().with_num_leaves(3).with_annotations(AnnotationMode::Parsed);
+    /// let mut tree = newick_parser.parse(&mut ByteParser::from_str("((A,B)[&rate=2],C)[&rate=1];")).unwrap();
+    ///
+    /// tree.unroot();
+    /// assert_eq!(tree.dropped_metadata().len(), 1);
+    /// assert_eq!(tree.dropped_metadata()[0].taxa, vec![0, 1, 2]);
+    /// assert_eq!(tree.dropped_metadata()[0].annotation.as_ref().unwrap().raw(), "rate=1");
+    /// ```
+    pub fn dropped_metadata(&self) -> &[DroppedMetadata] {
+        &self.dropped_metadata
+    }
+
+    /// Returns and clears the node labels and annotations dropped by edits, see [Tree::dropped_metadata].
+    pub fn take_dropped_metadata(&mut self) -> Vec<DroppedMetadata> {
+        std::mem::take(&mut self.dropped_metadata)
+    }
+
+    /// Returns the node label and annotation of the vertex (with `node`) and the annotation of its branch (with `branch`)
+    /// as dropped by an edit, or `None` if it has none of them.
+    pub(crate) fn metadata_of(&self, index: TreeIndex, node: bool, branch: bool) -> Option<DroppedMetadata> {
+        let node_label = self[index].node_label().filter(|_| node).map(str::to_string);
+        let annotation = self.annotation(index).filter(|_| node).cloned();
+        let branch_annotation = self.branch_annotation(index).filter(|_| branch).cloned();
+        if node_label.is_none() && annotation.is_none() && branch_annotation.is_none() {
+            return None;
+        }
+
+        let mut taxa: Vec<LabelIndex> = self.leaves_under(index).filter_map(|leaf| leaf.label_index()).collect();
+        taxa.sort_unstable();
+        Some(DroppedMetadata { taxa, node_label, annotation, branch_annotation })
+    }
+
+    /// Records metadata dropped by an edit, if any (see [Tree::metadata_of]).
+    pub(crate) fn record_dropped(&mut self, dropped: Option<DroppedMetadata>) {
+        self.dropped_metadata.extend(dropped);
+    }
+}
+
+impl std::ops::Index<TreeIndex> for Tree {
+    type Output = Vertex;
+
+    fn index(&self, index: TreeIndex) -> &Self::Output {
+        &self.vertices[index]
+    }
+}
+
+impl std::ops::IndexMut<TreeIndex> for Tree {
+    fn index_mut(&mut self, index: TreeIndex) -> &mut Self::Output {
+        // Vertex might be edited (e.g. its children), so cached clades may become stale
+        self.invalidate_caches();
+        &mut self.vertices[index]
+    }
+}
+
+/// Serialized form of a [Tree] (without caches), validated when deserializing.
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct TreeData {
+    num_leaves_init: usize,
+    vertices: Vec<Vertex>,
+    root_index: TreeIndex,
+    name: Option<String>,
+    metadata: Option<Annotation>,
+    rooted: bool,
+    annotations: HashMap<TreeIndex, Annotation>,
+    branch_annotations: HashMap<TreeIndex, Annotation>,
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<TreeData> for Tree {
+    type Error = String;
+
+    /// Restores a tree, failing unless it is [valid](Tree::is_valid) and annotates only its vertices.
+    fn try_from(data: TreeData) -> Result<Self, Self::Error> {
+        let tree = Tree {
+            num_leaves_init: data.num_leaves_init,
+            vertices: data.vertices,
+            root_index: data.root_index,
+            name: data.name,
+            metadata: data.metadata,
+            rooted: data.rooted,
+            annotations: data.annotations,
+            branch_annotations: data.branch_annotations,
+            clade_cache: OnceLock::new(),
+            height_cache: OnceLock::new(),
+            dropped_metadata: Vec::new(),
         };
 
-        // => Total
-        let estimated_capacity = structure_capacity + label_capacity + branch_capacity;
-        let mut newick = String::with_capacity(estimated_capacity);
+        if !tree.is_valid() {
+            return Err("Deserialized tree is not a valid tree".to_string());
+        }
+        if tree.annotations.keys().chain(tree.branch_annotations.keys()).any(|&index| index >= tree.num_vertices()) {
+            return Err("Deserialized tree annotates a vertex it does not contain".to_string());
+        }
+        Ok(tree)
+    }
+}
 
-        build_newick(&self, &mut newick, self.root_index, &style, leaf_label_map);
-        newick.push(';');
+// ============================================================================
+// Arena Compaction (pub)
+// ============================================================================
+impl Tree {
+    /// Removes all vertices not reachable from the root (dead slots, e.g. left by detaching subtrees
+    /// via [Tree::vertex_mut]) from the arena and renumbers the others, keeping their relative order.
+    ///
+    /// Parent and child indices, the root index and annotations are updated; annotations of removed vertices are dropped.
+    /// Parents are set from the children lists, so a stale parent index of a reachable vertex is fixed as well.
+    /// As with [Tree::prune_taxa], the [number of leaves](Tree::num_leaves_init) the tree was created for stays the same.
+    ///
+    /// # Returns
+    /// The mapping of old to new indices, `None` for removed vertices, to update indices held outside the tree
+    /// (e.g. maps keyed by [TreeIndex]); all previously obtained indices are stale after compaction.
+    ///
+    /// # Panics
+    /// Panics if no root is set.
+    ///
+    /// # Example
+    /// ```
+    /// use nexus_parser::model::tree::Tree;
+    ///
+    /// let mut tree = Tree::new(3);
+    /// let a = tree.add_leaf(None, 0);
+    /// let unused = tree.add_leaf(None, 1);
+    /// let c = tree.add_leaf(None, 2);
+    /// tree.add_root(vec![a, c]);
+    /// assert_eq!(tree.num_leaves(), 3);
+    ///
+    /// let mapping = tree.compact();
+    /// assert_eq!(mapping, vec![Some(0), None, Some(1), Some(2)]);
+    /// assert_eq!(tree.num_leaves(), 2);
+    /// assert_eq!(tree.root().index(), 2);
+    /// ```
+    pub fn compact(&mut self) -> Vec<Option<TreeIndex>> {
+        assert!(self.is_root_set(), "Cannot compact tree without root");
+        debug_assert!(self.annotations.keys().chain(self.branch_annotations.keys()).all(|&index| index < self.vertices.len()),
+                      "Annotations of vertices outside the arena");
+
+        // Parents of reachable vertices, as given by the children lists
+        let num_vertices = self.vertices.len();
+        let mut parents: Vec<Option<Option<TreeIndex>>> = vec![None; num_vertices];
+        parents[self.root_index] = Some(None);
+        for vertex in self.pre_order_iter() {
+            for &child in vertex.children().unwrap_or_default() {
+                parents[child] = Some(Some(vertex.index()));
+            }
+        }
 
-        newick
+        let mut mapping = vec![None; num_vertices];
+        let mut num_reachable = 0;
+        for (index, parent) in parents.iter().enumerate() {
+            if parent.is_some() {
+                mapping[index] = Some(num_reachable);
+                num_reachable += 1;
+            }
+        }
+
+        let new_index = |index: TreeIndex| mapping[index].expect("Children of reachable vertices are reachable");
+        let vertices = std::mem::take(&mut self.vertices).into_iter()
+            .zip(&parents)
+            .filter_map(|(mut vertex, parent)| {
+                if let Some(parent) = (*parent)? {
+                    vertex.set_parent(parent);
+                }
+                vertex.renumber(new_index);
+                Some(vertex)
+            })
+            .collect();
+        let remap = |annotations: HashMap<TreeIndex, Annotation>| annotations.into_iter()
+            .filter_map(|(index, annotation)| Some((mapping[index]?, annotation)))
+            .collect();
+        let annotations = remap(std::mem::take(&mut self.annotations));
+        let branch_annotations = remap(std::mem::take(&mut self.branch_annotations));
+        self.replace_vertices(vertices, new_index(self.root_index), annotations, branch_annotations);
+
+        mapping
     }
+}
 
+// ============================================================================
+// Printing (pub)
+// ============================================================================
+impl Tree {
     /// Prints a visual representation of the tree to the console.
     ///
     /// # Arguments
@@ -633,13 +1120,29 @@ impl Tree {
 
         if self.root_index != NO_ROOT_SET_INDEX {
             println!("Root: vertex {}", self.root_index);
-            self.print_vertex(self.root_index, "", true, label_map);
+            // Iteratively (explicit stack) so deep trees cannot overflow the call stack
+            let mut stack = vec![(self.root_index, String::new(), true)];
+            while let Some((idx, prefix, is_last)) = stack.pop() {
+                self.print_vertex(idx, &prefix, is_last, label_map);
+
+                // Push children (last first, so first is printed first)
+                if let Some(children) = self.vertices[idx].children() {
+                    let new_prefix = if prefix.is_empty() {
+                        "  ".to_string()
+                    } else {
+                        format!("{}{}  ", prefix, if is_last { " " } else { "│" })
+                    };
+                    for (i, &child) in children.iter().enumerate().rev() {
+                        stack.push((child, new_prefix.clone(), i + 1 == children.len()));
+                    }
+                }
+            }
         } else {
             println!("(No root set)");
         }
     }
 
-    /// Helper function to recursively print a vertex and its children.
+    /// Helper function to print a single vertex (children are printed by the caller).
     fn print_vertex(&self, idx: usize, prefix: &str, is_last: bool, label_map: Option<&LeafLabelMap>) {
         let vertex = &self.vertices[idx];
 
@@ -671,35 +1174,14 @@ impl Tree {
                 "(no branch)".to_string()
             };
 
-            println!("{}{}[{}] Internal {}", prefix, connector, idx, branch_str);
-
-            // Print children if they exist
-            if let Some((left, right)) = vertex.children() {
-                let new_prefix = if prefix.is_empty() {
-                    "  ".to_string()
-                } else {
-                    format!("{}{}  ", prefix, if is_last { " " } else { "│" })
-                };
-
-                self.print_vertex(left, &new_prefix, false, label_map);
-                self.print_vertex(right, &new_prefix, true, label_map);
+            match vertex.node_label() {
+                Some(node_label) => println!("{}{}[{}] Internal \"{}\" {}", prefix, connector, idx, node_label, branch_str),
+                None => println!("{}{}[{}] Internal {}", prefix, connector, idx, branch_str),
             }
         }
     }
 }
 
-/// Style for serializing tree to Newick format,
-/// controlling how leaf labels are represented in the output string.
-#[derive(Debug, Clone, Copy)]
-pub enum NewickStyle {
-    /// Use full leaf labels from the LeafLabelMap
-    Label,
-    /// Use 0-based indices (0, 1, 2, ...)
-    ZeroIndexed,
-    /// Use 1-based indices (1, 2, 3, ...) (as in Nexus files)
-    OneIndexed,
-}
-
 
 // =#========================================================================#=
 // ITERATORS
@@ -721,6 +1203,11 @@ impl<'a> PostOrderIter<'a> {
         }
         PostOrderIter { tree, stack }
     }
+
+    /// Creates an iterator over the subtree below (and including) the given vertex.
+    pub(crate) fn starting_at(tree: &'a Tree, index: TreeIndex) -> Self {
+        PostOrderIter { tree, stack: vec![(index, false)] }
+    }
 }
 
 impl<'a> Iterator for PostOrderIter<'a> {
@@ -737,10 +1224,9 @@ impl<'a> Iterator for PostOrderIter<'a> {
                 // Mark this vertex as "children will be visited"
                 self.stack.push((index, true));
 
-                // Push children (right first, so left is processed first)
-                if let Some((left, right)) = vertex.children() {
-                    self.stack.push((right, false));
-                    self.stack.push((left, false));
+                // Push children (last first, so first is processed first)
+                if let Some(children) = vertex.children() {
+                    self.stack.extend(children.iter().rev().map(|&child| (child, false)));
                 }
             }
         }
@@ -765,6 +1251,11 @@ impl<'a> PreOrderIter<'a> {
         }
         PreOrderIter { tree, stack }
     }
+
+    /// Creates an iterator over the subtree below (and including) the given vertex.
+    pub(crate) fn starting_at(tree: &'a Tree, index: TreeIndex) -> Self {
+        PreOrderIter { tree, stack: vec![index] }
+    }
 }
 
 impl<'a> Iterator for PreOrderIter<'a> {
@@ -774,10 +1265,9 @@ impl<'a> Iterator for PreOrderIter<'a> {
         let index = self.stack.pop()?;
         let vertex = &self.tree[index];
 
-        // Push children onto stack (right first, so left is processed first)
-        if let Some((left, right)) = vertex.children() {
-            self.stack.push(right);
-            self.stack.push(left);
+        // Push children onto stack (last first, so first is processed first)
+        if let Some(children) = vertex.children() {
+            self.stack.extend(children.iter().rev());
         }
 
         Some(vertex)
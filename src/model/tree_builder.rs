@@ -0,0 +1,106 @@
+//! Tree builder module for reusing the buffers of trees across many trees of the same size.
+//!
+//! - `TreeBuilder`: Hands out empty trees and children lists, reusing the vertex arenas
+//!   and children lists of recycled trees instead of allocating fresh ones
+//!
+//! Posterior samples consist of many trees of identical size, so when trees are processed
+//! one at a time (e.g. streamed and discarded), recycling them avoids most allocations of the next tree.
+
+use crate::model::tree::{Tree, TreeIndex};
+use crate::model::vertex::Vertex;
+
+/// Maximal number of recycled vertex arenas kept for reuse
+const MAX_SPARE_ARENAS: usize = 4;
+
+// =#========================================================================#=
+// TREE BUILDER
+// =#========================================================================#=
+/// Source of empty trees and children lists that reuses the buffers of recycled trees.
+///
+/// Trees are created with capacity for exactly `2 * num_leaves - 1` vertices (a binary tree),
+/// and children lists with capacity for two children; buffers of recycled trees keep their capacity.
+/// At most a few recycled trees (and their children lists) are kept, further ones are dropped.
+///
+/// # Example
+/// ```
+/// use nexus_parser::model::tree_builder::TreeBuilder;
+///
+/// let mut builder = TreeBuilder::new();
+/// for _ in 0..3 {
+///     let mut tree = builder.tree(2);
+///     let a = tree.add_leaf(None, 0);
+///     let b = tree.add_leaf(None, 1);
+///     let mut children = builder.children();
+///     children.extend([a, b]);
+///     tree.add_root(children);
+///     assert!(tree.is_valid());
+///
+///     // Done with the tree, so the next one reuses its buffers
+///     builder.recycle(tree);
+/// }
+/// assert_eq!(builder.num_spare_arenas(), 1);
+/// ```
+#[derive(Debug, Default)]
+pub struct TreeBuilder {
+    /// Vertex arenas of recycled trees (cleared when reused)
+    arenas: Vec<Vec<Vertex>>,
+    /// Empty children lists of vertices of recycled trees
+    children: Vec<Vec<TreeIndex>>,
+}
+
+impl TreeBuilder {
+    /// Creates a new builder without buffers to reuse yet.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns a new empty tree on `num_leaves` leaves, reusing the vertex arena of a recycled tree if any.
+    ///
+    /// # Panics
+    /// Panics if `num_leaves` is `0`.
+    pub fn tree(&mut self, num_leaves: usize) -> Tree {
+        assert!(num_leaves > 0);
+        let capacity = 2 * num_leaves - 1;
+        match self.arenas.pop() {
+            Some(mut vertices) => {
+                vertices.clear();
+                vertices.reserve_exact(capacity);
+                Tree::with_vertex_arena(num_leaves, vertices)
+            }
+            None => Tree::with_capacity(num_leaves, capacity),
+        }
+    }
+
+    /// Returns an empty children list, reusing one of a vertex of a recycled tree if any.
+    pub fn children(&mut self) -> Vec<TreeIndex> {
+        self.children.pop().unwrap_or_else(|| Vec::with_capacity(2))
+    }
+
+    /// Takes back a tree that is no longer needed, keeping its vertex arena and children lists for reuse.
+    pub fn recycle(&mut self, tree: Tree) {
+        if self.arenas.len() >= MAX_SPARE_ARENAS {
+            return;
+        }
+
+        let mut vertices = tree.into_vertex_arena();
+        // Children lists are only kept for as many trees as arenas, in case they are not taken
+        let max_spare_children = MAX_SPARE_ARENAS * vertices.len();
+        for vertex in &mut vertices {
+            if self.children.len() >= max_spare_children {
+                break;
+            }
+            if let Some(children) = vertex.children_mut() {
+                let mut children = std::mem::take(children);
+                children.clear();
+                self.children.push(children);
+            }
+        }
+        vertices.clear();
+        self.arenas.push(vertices);
+    }
+
+    /// Returns the number of vertex arenas of recycled trees available for reuse.
+    pub fn num_spare_arenas(&self) -> usize {
+        self.arenas.len()
+    }
+}
@@ -0,0 +1,346 @@
+//! Tree sample module for keeping the trees of a sample together with their labels.
+//!
+//! - `TreeSample`: Trees (with their names and metadata) owning the [LeafLabelMap] their leaves refer to,
+//!   with iteration, indexing by position and name, subsetting, concatenation and merging
+//! - `TreeSampleError`: Trees and labels that do not fit together
+//! - `LabelMapping`: Label indices of one [LeafLabelMap] mapped to those of another by label
+//! - `remap_labels`: Moves the leaves of a tree to another [LeafLabelMap] by a [LabelMapping]
+//!
+//! Parsers return trees and their labels as a pair `(Vec<Tree>, LeafLabelMap)`, which is easily
+//! mixed up with the labels of another file; a [TreeSample] keeps them together (`TreeSample::from(pair)`).
+
+use crate::model::leaf_label_map::{LabelRenaming, LeafLabelMap, RenameError};
+use crate::model::tree::{LabelIndex, Tree};
+use crate::model::tree_selection::find_tree;
+use std::error::Error;
+use std::fmt;
+use std::ops::Index;
+
+// =#========================================================================#=
+// TREE SAMPLE
+// =#========================================================================#=
+/// Trees of a sample (e.g. a posterior sample of an MCMC run) together with the labels of their leaves.
+///
+/// Every tree keeps its own name and metadata (see [Tree::name] and [Tree::metadata]),
+/// while all trees share the [LeafLabelMap] owned by the sample.
+///
+/// # Example
+/// ```
+/// use nexus_parser::io::parser::nexus::NexusParserBuilder;
+/// use nexus_parser::model::tree_sample::TreeSample;
+///
+/// let nexus = b"#NEXUS\nBEGIN TREES;\nTREE STATE_0 = ((A,B),C);\nTREE STATE_1000 = ((A,C),B);\nEND;\n";
+/// let sample = TreeSample::from(NexusParserBuilder::for_bytes(nexus.to_vec()).build().unwrap().into_results().unwrap());
+///
+/// assert_eq!(sample.len(), 2);
+/// assert_eq!(sample.names().collect::<Vec<_>>(), vec![Some("STATE_0"), Some("STATE_1000")]);
+/// assert!(std::ptr::eq(sample.get_by_name("STATE_1000").unwrap(), &sample[1]));
+/// assert_eq!(sample.labels().get_index("C"), Some(2));
+///
+/// // Subsets share the labels
+/// let last = sample.subset([1]);
+/// assert_eq!(last[0].name().map(String::as_str), Some("STATE_1000"));
+/// assert_eq!(last.labels().num_labels(), 3);
+/// ```
+#[derive(Debug, Clone)]
+pub struct TreeSample {
+    /// Labels the leaves of all trees refer to
+    labels: LeafLabelMap,
+    /// Trees in order of the sample
+    trees: Vec<Tree>,
+}
+
+impl TreeSample {
+    /// Creates a sample of the trees, whose leaves refer to the given labels.
+    ///
+    /// # Errors
+    /// Returns [TreeSampleError::UnknownLabelIndex] if a leaf refers to a label index not in `labels`.
+    pub fn new(trees: Vec<Tree>, labels: LeafLabelMap) -> Result<Self, TreeSampleError> {
+        for (tree_index, tree) in trees.iter().enumerate() {
+            check_label_indices(tree, &labels).map_err(|label_index| TreeSampleError::UnknownLabelIndex { tree_index, label_index })?;
+        }
+        Ok(Self { labels, trees })
+    }
+
+    /// Creates an empty sample over the given labels.
+    pub fn empty(labels: LeafLabelMap) -> Self {
+        Self { labels, trees: Vec::new() }
+    }
+
+    /// Consumes the sample and returns its trees and labels.
+    pub fn into_parts(self) -> (Vec<Tree>, LeafLabelMap) {
+        (self.trees, self.labels)
+    }
+
+    /// Returns the labels the leaves of the trees refer to.
+    pub fn labels(&self) -> &LeafLabelMap {
+        &self.labels
+    }
+
+    /// Returns the trees, in order.
+    pub fn trees(&self) -> &[Tree] {
+        &self.trees
+    }
+
+    /// Returns the trees for modification, e.g. to reroot or rescale them.
+    /// Their leaves must keep referring to the labels of the sample.
+    pub fn trees_mut(&mut self) -> &mut [Tree] {
+        &mut self.trees
+    }
+
+    /// Returns the number of trees.
+    pub fn len(&self) -> usize {
+        self.trees.len()
+    }
+
+    /// Returns whether the sample has no trees.
+    pub fn is_empty(&self) -> bool {
+        self.trees.is_empty()
+    }
+
+    /// Returns an iterator over the trees, in order.
+    pub fn iter(&self) -> std::slice::Iter<'_, Tree> {
+        self.trees.iter()
+    }
+
+    /// Returns the tree at the given position, if any.
+    pub fn get(&self, index: usize) -> Option<&Tree> {
+        self.trees.get(index)
+    }
+
+    /// Returns the first tree with the given name, if any (see [find_tree]).
+    pub fn get_by_name(&self, name: &str) -> Option<&Tree> {
+        find_tree(&self.trees, name)
+    }
+
+    /// Returns an iterator over the names of the trees (`None` for unnamed trees), in order.
+    pub fn names(&self) -> impl Iterator<Item = Option<&str>> {
+        self.trees.iter().map(|tree| tree.name().map(String::as_str))
+    }
+
+    /// Renames the labels of the sample found in the renaming (e.g. accession numbers to species names
+    /// read with [LabelRenaming::from_tsv_file]), which renames the leaves of all trees (see [LeafLabelMap::rename_all]).
+    ///
+    /// # Returns
+    /// The number of renamed labels
+    ///
+    /// # Errors
+    /// Returns [RenameError::DuplicateLabel] if two taxa would get the same label; the labels are then left unchanged.
+    ///
+    /// # Example
+    /// ```
+    /// use nexus_parser::io::writer::newick::to_newick;
+    /// use nexus_parser::model::leaf_label_map::LabelRenaming;
+    /// use nexus_parser::model::tree_sample::TreeSample;
+    /// use nexus_parser::parse_newick_str;
+    ///
+    /// let mut sample = TreeSample::from(parse_newick_str("((AB123,AB124),XY999);\n(AB123,(AB124,XY999));\n").unwrap());
+    /// let renaming = LabelRenaming::from_tsv("AB123\tApteryx haastii\nAB124\tApteryx owenii\n".as_bytes()).unwrap();
+    ///
+    /// assert_eq!(sample.rename_labels(&renaming), Ok(2));
+    /// assert_eq!(to_newick(&sample[1], sample.labels()), "('Apteryx haastii',('Apteryx owenii',XY999));");
+    /// ```
+    pub fn rename_labels(&mut self, renaming: &LabelRenaming) -> Result<usize, RenameError> {
+        self.labels.rename_all(renaming)
+    }
+
+    /// Adds a tree at the end.
+    ///
+    /// # Errors
+    /// Returns [TreeSampleError::UnknownLabelIndex] if a leaf refers to a label index not in the labels of the sample.
+    pub fn push(&mut self, tree: Tree) -> Result<(), TreeSampleError> {
+        check_label_indices(&tree, &self.labels)
+            .map_err(|label_index| TreeSampleError::UnknownLabelIndex { tree_index: self.trees.len(), label_index })?;
+        self.trees.push(tree);
+        Ok(())
+    }
+
+    /// Returns a new sample of (copies of) the trees at the given positions, in the given order,
+    /// sharing (a copy of) the labels.
+    ///
+    /// # Panics
+    /// Panics if a position is out of range.
+    pub fn subset(&self, indices: impl IntoIterator<Item = usize>) -> TreeSample {
+        Self {
+            labels: self.labels.clone(),
+            trees: indices.into_iter().map(|index| self.trees[index].clone()).collect(),
+        }
+    }
+
+    /// Keeps only the trees satisfying the predicate, in order.
+    pub fn retain(&mut self, predicate: impl FnMut(&Tree) -> bool) {
+        self.trees.retain(predicate);
+    }
+
+    /// Appends the trees of another sample over the same labels, e.g. of another run of the same analysis.
+    ///
+    /// # Errors
+    /// Returns [TreeSampleError::DifferentLabels] (leaving this sample unchanged) if the samples do not have
+    /// the same labels with the same indices.
+    pub fn concat(&mut self, other: TreeSample) -> Result<(), TreeSampleError> {
+        let (mine, theirs) = (self.labels.labels(), other.labels.labels());
+        if let Some(label_index) = (0..mine.len().max(theirs.len())).find(|&i| mine.get(i) != theirs.get(i)) {
+            return Err(TreeSampleError::DifferentLabels(label_index));
+        }
+        self.trees.extend(other.trees);
+        Ok(())
+    }
+
+    /// Appends the trees of another sample whose labels may differ, e.g. of an independent MCMC chain
+    /// parsed on its own: labels are matched by name, labels not in this sample are added,
+    /// and the leaves of the other trees are moved to the labels of this sample.
+    ///
+    /// All trees then range over the labels of the merged sample (see [Tree::num_leaves_init]).
+    ///
+    /// # Returns
+    /// The mapping from label indices of `other` to those of this sample
+    ///
+    /// # Example
+    /// ```
+    /// use nexus_parser::io::writer::newick::to_newick;
+    /// use nexus_parser::model::tree_sample::TreeSample;
+    /// use nexus_parser::parse_newick_str;
+    ///
+    /// let mut chain_1 = TreeSample::from(parse_newick_str("((A,B),C);").unwrap());
+    /// let chain_2 = TreeSample::from(parse_newick_str("((C,B),A);").unwrap());
+    /// let mapping = chain_1.merge(chain_2);
+    ///
+    /// assert_eq!(chain_1.len(), 2);
+    /// assert_eq!(mapping.get(0), chain_1.labels().get_index("C"));
+    /// assert_eq!(to_newick(&chain_1[1], chain_1.labels()), "((C,B),A);");
+    /// ```
+    pub fn merge(&mut self, other: TreeSample) -> LabelMapping {
+        let num_labels = self.labels.num_labels();
+        let mapping = LabelMapping::new(&other.labels, &mut self.labels);
+        if self.labels.num_labels() > num_labels {
+            for tree in &mut self.trees {
+                tree.set_num_leaves_init(self.labels.num_labels());
+            }
+        }
+
+        for mut tree in other.trees {
+            remap_labels(&mut tree, &mapping);
+            self.trees.push(tree);
+        }
+        mapping
+    }
+}
+
+impl From<(Vec<Tree>, LeafLabelMap)> for TreeSample {
+    /// Keeps trees and labels as returned by a parser together (without checking them, see [TreeSample::new]).
+    fn from((trees, labels): (Vec<Tree>, LeafLabelMap)) -> Self {
+        Self { labels, trees }
+    }
+}
+
+impl Index<usize> for TreeSample {
+    type Output = Tree;
+
+    fn index(&self, index: usize) -> &Tree {
+        &self.trees[index]
+    }
+}
+
+impl<'a> IntoIterator for &'a TreeSample {
+    type Item = &'a Tree;
+    type IntoIter = std::slice::Iter<'a, Tree>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.trees.iter()
+    }
+}
+
+// ============================================================================
+// Little Helpers
+// ============================================================================
+/// Checks that all leaves of the tree refer to labels of the map, returning the first label index that does not.
+fn check_label_indices(tree: &Tree, labels: &LeafLabelMap) -> Result<(), LabelIndex> {
+    match tree.post_order_iter().filter_map(|vertex| vertex.label_index()).find(|&i| i >= labels.num_labels()) {
+        Some(label_index) => Err(label_index),
+        None => Ok(()),
+    }
+}
+
+// =#========================================================================#=
+// LABEL MAPPING
+// =#========================================================================#=
+/// Mapping of the label indices of one [LeafLabelMap] to the indices of the same labels in another one.
+///
+/// # Example
+/// ```
+/// use nexus_parser::io::writer::newick::to_newick;
+/// use nexus_parser::model::tree_sample::{remap_labels, LabelMapping};
+/// use nexus_parser::parse_newick_str;
+///
+/// let (_, mut labels) = parse_newick_str("((A,B),C);").unwrap();
+/// let (mut trees, other_labels) = parse_newick_str("((D,C),B);").unwrap();
+///
+/// // D is added to the labels, C and B keep their indices there
+/// let mapping = LabelMapping::new(&other_labels, &mut labels);
+/// assert_eq!(mapping.get(0), Some(3));
+/// assert_eq!(mapping.get(1), labels.get_index("C"));
+///
+/// remap_labels(&mut trees[0], &mapping);
+/// assert_eq!(to_newick(&trees[0], &labels), "((D,C),B);");
+/// assert_eq!(trees[0].num_leaves_init(), 4);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LabelMapping {
+    /// Index in the target map of each label index of the source map
+    indices: Vec<LabelIndex>,
+    /// Number of labels of the target map
+    num_labels: usize,
+}
+
+impl LabelMapping {
+    /// Maps the labels of `from` to the same labels in `to`, adding those not in `to` yet (in order).
+    pub fn new(from: &LeafLabelMap, to: &mut LeafLabelMap) -> Self {
+        let indices = from.labels().iter().map(|label| to.get_or_insert(label)).collect();
+        Self { indices, num_labels: to.num_labels() }
+    }
+
+    /// Returns the index in the target map of the given label index of the source map, if it is one.
+    pub fn get(&self, label_index: LabelIndex) -> Option<LabelIndex> {
+        self.indices.get(label_index).copied()
+    }
+
+    /// Returns the number of labels of the target map (when the mapping was created).
+    pub fn num_labels(&self) -> usize {
+        self.num_labels
+    }
+}
+
+/// Moves the leaves of the tree from the source to the target map of the mapping,
+/// rewriting their label indices; the tree then ranges over the labels of the target map
+/// (see [Tree::num_leaves_init]).
+///
+/// # Panics
+/// Panics if a leaf refers to a label index not in the source map.
+pub fn remap_labels(tree: &mut Tree, mapping: &LabelMapping) {
+    tree.remap_label_indices(&mapping.indices, mapping.num_labels);
+}
+
+// =#========================================================================#=
+// TREE SAMPLE ERROR
+// =#========================================================================#=
+/// Error for trees and labels that do not fit together in a [TreeSample].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TreeSampleError {
+    /// Tree at the position has a leaf with a label index not in the labels of the sample
+    UnknownLabelIndex { tree_index: usize, label_index: LabelIndex },
+    /// Labels of two samples differ (first at the label index)
+    DifferentLabels(LabelIndex),
+}
+
+impl fmt::Display for TreeSampleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TreeSampleError::UnknownLabelIndex { tree_index, label_index } =>
+                write!(f, "Tree {} has a leaf with unknown label index {}", tree_index, label_index),
+            TreeSampleError::DifferentLabels(label_index) =>
+                write!(f, "Samples have different labels at label index {}", label_index),
+        }
+    }
+}
+
+impl Error for TreeSampleError {}
@@ -0,0 +1,149 @@
+//! Tree selection module for picking trees of a sample by name, and taxa by label.
+//!
+//! Trees parsed from NEXUS files are named as in their TREE command, e.g. `STATE_1000` or `gen.1000`:
+//! - `find_tree`: First tree with the given name
+//! - `select_trees`: Trees whose name satisfies a predicate
+//! - `select_trees_matching`: Trees whose name matches a regular expression (feature `regex`)
+//!
+//! Taxa are selected by their labels, e.g. by lineage or country codes embedded in tip names like `B.1.1.7|GBR|2021-01-04`,
+//! as label indices for [Tree::prune_taxa], [Tree::mrca] or (as [TaxonSet](crate::model::taxon_set::TaxonSet)) [Tree::is_monophyletic]:
+//! - `Tree::leaves_where`/`Tree::leaves_matching`: Leaves of a tree whose label satisfies a predicate/matches a regular expression
+//! - `TreeSample::select_taxa`/`TreeSample::select_taxa_matching`: Taxa of a sample, likewise
+
+use crate::model::leaf_label_map::LeafLabelMap;
+use crate::model::tree::{LabelIndex, Tree};
+use crate::model::tree_sample::TreeSample;
+
+/// Returns the first tree with the given name, if any.
+///
+/// # Example
+/// ```
+/// use nexus_parser::io::parser::nexus::NexusParserBuilder;
+/// use nexus_parser::model::tree_selection::find_tree;
+///
+/// let nexus = b"#NEXUS\nBEGIN TREES;\nTREE STATE_0 = ((A,B),C);\nTREE STATE_1000 = ((A,C),B);\nEND;\n";
+/// let (trees, _) = NexusParserBuilder::for_bytes(nexus.to_vec()).build().unwrap().into_results().unwrap();
+///
+/// assert!(std::ptr::eq(find_tree(&trees, "STATE_1000").unwrap(), &trees[1]));
+/// assert!(find_tree(&trees, "STATE_2000").is_none());
+/// ```
+pub fn find_tree<'a>(trees: &'a [Tree], name: &str) -> Option<&'a Tree> {
+    trees.iter().find(|tree| tree.name().is_some_and(|tree_name| tree_name == name))
+}
+
+/// Returns the trees whose name satisfies the predicate, in order; unnamed trees are never selected.
+///
+/// # Example
+/// ```
+/// use nexus_parser::io::parser::nexus::NexusParserBuilder;
+/// use nexus_parser::model::tree_selection::select_trees;
+///
+/// let nexus = b"#NEXUS\nBEGIN TREES;\nTREE STATE_0 = ((A,B),C);\nTREE STATE_1000 = ((A,C),B);\nEND;\n";
+/// let (trees, _) = NexusParserBuilder::for_bytes(nexus.to_vec()).build().unwrap().into_results().unwrap();
+///
+/// let selected = select_trees(&trees, |name| name.ends_with("000"));
+/// assert_eq!(selected.len(), 1);
+/// ```
+pub fn select_trees(trees: &[Tree], predicate: impl Fn(&str) -> bool) -> Vec<&Tree> {
+    trees.iter()
+        .filter(|tree| tree.name().is_some_and(|name| predicate(name)))
+        .collect()
+}
+
+/// Returns the trees whose name matches the regular expression (anywhere, unless anchored), in order;
+/// unnamed trees are never selected.
+///
+/// # Example
+/// ```
+/// use nexus_parser::io::parser::nexus::NexusParserBuilder;
+/// use nexus_parser::model::tree_selection::select_trees_matching;
+/// use regex::Regex;
+///
+/// let nexus = b"#NEXUS\nBEGIN TREES;\nTREE STATE_0 = ((A,B),C);\nTREE STATE_1000 = ((A,C),B);\nEND;\n";
+/// let (trees, _) = NexusParserBuilder::for_bytes(nexus.to_vec()).build().unwrap().into_results().unwrap();
+///
+/// let selected = select_trees_matching(&trees, &Regex::new(r"^STATE_\d{4,}$").unwrap());
+/// assert_eq!(selected.len(), 1);
+/// ```
+#[cfg(feature = "regex")]
+pub fn select_trees_matching<'a>(trees: &'a [Tree], regex: &regex::Regex) -> Vec<&'a Tree> {
+    select_trees(trees, |name| regex.is_match(name))
+}
+
+// ============================================================================
+// Taxon Selection (pub)
+// ============================================================================
+impl Tree {
+    /// Returns the label indices of the leaves of this tree whose label satisfies the predicate, in increasing order.
+    ///
+    /// # Example
+    /// ```
+    /// use nexus_parser::parse_newick_str;
+    ///
+    /// let (trees, labels) = parse_newick_str("((A|NZL,B|AUS),(C|NZL,D|NZL));").unwrap();
+    /// let nz = trees[0].leaves_where(&labels, |label| label.ends_with("|NZL"));
+    /// assert_eq!(nz, vec![0, 2, 3]);
+    /// ```
+    pub fn leaves_where(&self, labels: &LeafLabelMap, predicate: impl Fn(&str) -> bool) -> Vec<LabelIndex> {
+        let mut selected: Vec<LabelIndex> = (0..self.num_vertices())
+            .filter_map(|index| self[index].label_index())
+            .filter(|&label_index| labels.get_label(label_index).is_some_and(&predicate))
+            .collect();
+        selected.sort_unstable();
+        selected
+    }
+
+    /// Returns the label indices of the leaves of this tree whose label matches the regular expression
+    /// (anywhere, unless anchored), in increasing order.
+    ///
+    /// # Example
+    /// ```
+    /// use nexus_parser::model::taxon_set::TaxonSet;
+    /// use nexus_parser::parse_newick_str;
+    /// use regex::Regex;
+    ///
+    /// let (trees, labels) = parse_newick_str("((('B.1.1.7|GBR','B.1.1.7|USA'),'B.1.351|ZAF'),'P.1|BRA');").unwrap();
+    /// let alpha = trees[0].leaves_matching(&labels, &Regex::new(r"^B\.1\.1\.7\|").unwrap());
+    /// assert_eq!(alpha, vec![0, 1]);
+    ///
+    /// // Feeds into clade queries and pruning
+    /// assert!(trees[0].is_monophyletic(&TaxonSet::from_indices(labels.num_labels(), alpha.iter().copied())));
+    /// let pruned = trees[0].prune_taxa(&alpha);
+    /// assert_eq!(pruned.num_leaves(), 2);
+    /// ```
+    #[cfg(feature = "regex")]
+    pub fn leaves_matching(&self, labels: &LeafLabelMap, regex: &regex::Regex) -> Vec<LabelIndex> {
+        self.leaves_where(labels, |label| regex.is_match(label))
+    }
+}
+
+impl TreeSample {
+    /// Returns the label indices of the taxa of this sample whose label satisfies the predicate, in increasing order,
+    /// whether or not every tree contains them.
+    pub fn select_taxa(&self, predicate: impl Fn(&str) -> bool) -> Vec<LabelIndex> {
+        (0..self.labels().num_labels())
+            .filter(|&label_index| predicate(&self.labels()[label_index]))
+            .collect()
+    }
+
+    /// Returns the label indices of the taxa of this sample whose label matches the regular expression
+    /// (anywhere, unless anchored), in increasing order, e.g. to prune a lineage from all trees.
+    ///
+    /// # Example
+    /// ```
+    /// use nexus_parser::model::tree_sample::TreeSample;
+    /// use nexus_parser::parse_newick_str;
+    /// use regex::Regex;
+    ///
+    /// let sample = TreeSample::from(parse_newick_str("((A|NZL,B|AUS),(C|NZL,D|AUS));\n((A|NZL,C|NZL),(B|AUS,D|AUS));\n").unwrap());
+    /// let nz = sample.select_taxa_matching(&Regex::new(r"\|NZL$").unwrap());
+    /// assert_eq!(nz, vec![0, 2]);
+    ///
+    /// let without_nz: Vec<_> = sample.iter().map(|tree| tree.prune_taxa(&nz)).collect();
+    /// assert!(without_nz.iter().all(|tree| tree.num_leaves() == 2));
+    /// ```
+    #[cfg(feature = "regex")]
+    pub fn select_taxa_matching(&self, regex: &regex::Regex) -> Vec<LabelIndex> {
+        self.select_taxa(|label| regex.is_match(label))
+    }
+}
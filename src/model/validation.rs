@@ -0,0 +1,252 @@
+//! Validation module for diagnosing malformed trees, e.g. from third-party files or own edits.
+//!
+//! Extends [Tree] with:
+//! - `validate`: Reports all issues found, in contrast to the plain check [Tree::is_valid]
+//!
+//! Issues are described by `ValidationIssue`, which distinguishes structural errors
+//! from properties that are only noteworthy (missing branch lengths, non-ultrametric leaves).
+
+use crate::model::tree::{LabelIndex, Tree, TreeIndex, EPSILON};
+use std::collections::HashMap;
+use std::fmt;
+
+// =#========================================================================#=
+// VALIDATION ISSUE
+// =#========================================================================#=
+/// Issue of a tree found by [Tree::validate].
+#[derive(Debug, Clone, PartialEq)]
+pub enum ValidationIssue {
+    /// No root has been set (tree not fully constructed)
+    MissingRoot,
+    /// Vertex of root type other than the root of the tree
+    ExtraRoot { vertex: TreeIndex },
+    /// Non-root vertex without parent
+    MissingParent { vertex: TreeIndex },
+    /// Parent of a vertex is out of bounds or does not list the vertex as child
+    DanglingParent { vertex: TreeIndex, parent: TreeIndex },
+    /// Child of a vertex is out of bounds or does not have the vertex as parent
+    DanglingChild { vertex: TreeIndex, child: TreeIndex },
+    /// Parents of a vertex lead back to it instead of to the root
+    Cycle { vertex: TreeIndex },
+    /// Label index of a leaf is not below the number of leaves the tree was initialized for
+    LabelOutOfRange { vertex: TreeIndex, label_index: LabelIndex },
+    /// Several leaves have the same label
+    DuplicateLeafLabel { label_index: LabelIndex, leaves: Vec<TreeIndex> },
+    /// Branch length that is negative (including `-0.0`) or not finite, e.g. after arithmetic on lengths
+    NegativeBranchLength { vertex: TreeIndex, length: f64 },
+    /// Non-root vertex without branch length while others have one
+    MissingBranchLength { vertex: TreeIndex },
+    /// Leaves closer to the root than the farthest leaf, and the difference of the closest and farthest leaf
+    NonUltrametric { leaves: Vec<TreeIndex>, max_deviation: f64 },
+}
+
+impl ValidationIssue {
+    /// Returns whether this issue is an error, i.e. makes [Tree::is_valid] fail or breaks the model,
+    /// or only noteworthy (missing branch lengths, non-ultrametric leaves).
+    pub fn is_error(&self) -> bool {
+        !matches!(self, ValidationIssue::MissingBranchLength { .. } | ValidationIssue::NonUltrametric { .. })
+    }
+}
+
+impl fmt::Display for ValidationIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ValidationIssue::MissingRoot => write!(f, "No root set"),
+            ValidationIssue::ExtraRoot { vertex } => write!(f, "Vertex {} is a root besides the root of the tree", vertex),
+            ValidationIssue::MissingParent { vertex } => write!(f, "Vertex {} has no parent", vertex),
+            ValidationIssue::DanglingParent { vertex, parent } =>
+                write!(f, "Parent {} of vertex {} is out of bounds or does not list it as child", parent, vertex),
+            ValidationIssue::DanglingChild { vertex, child } =>
+                write!(f, "Child {} of vertex {} is out of bounds or does not have it as parent", child, vertex),
+            ValidationIssue::Cycle { vertex } => write!(f, "Vertex {} is on a cycle of parents", vertex),
+            ValidationIssue::LabelOutOfRange { vertex, label_index } =>
+                write!(f, "Label index {} of leaf {} is out of range", label_index, vertex),
+            ValidationIssue::DuplicateLeafLabel { label_index, leaves } =>
+                write!(f, "Label index {} is shared by leaves {:?}", label_index, leaves),
+            ValidationIssue::NegativeBranchLength { vertex, length } =>
+                write!(f, "Branch length {} of vertex {} is negative or not finite", length, vertex),
+            ValidationIssue::MissingBranchLength { vertex } => write!(f, "Vertex {} has no branch length", vertex),
+            ValidationIssue::NonUltrametric { leaves, max_deviation } =>
+                write!(f, "Tree is not ultrametric ({} leaves deviate, by up to {})", leaves.len(), max_deviation),
+        }
+    }
+}
+
+// ============================================================================
+// Validation (pub)
+// ============================================================================
+impl Tree {
+    /// Validates this tree and reports all issues found, for debugging trees that [Tree::is_valid] rejects
+    /// or that behave unexpectedly.
+    ///
+    /// Structure (root, parent and child references, cycles) and labels are checked first.
+    /// Only if the structure is sound, branch lengths are checked as well, where missing lengths are reported
+    /// only if some vertices have one (trees without any lengths are plain topologies)
+    /// and ultrametricity only if all have one.
+    ///
+    /// # Returns
+    /// All issues found, empty if there are none; see [ValidationIssue::is_error] to filter errors
+    ///
+    /// # Example
+    /// ```
+    /// use nexus_parser::model::tree::Tree;
+    /// use nexus_parser::model::validation::ValidationIssue;
+    /// use nexus_parser::model::vertex::BranchLength;
+    ///
+    /// let mut tree = Tree::new(3);
+    /// let a = tree.add_leaf(Some(BranchLength::new(1.0)), 0);
+    /// let b = tree.add_leaf(Some(BranchLength::new(1.0)), 0);
+    /// let c = tree.add_leaf(Some(BranchLength::new(2.5)), 2);
+    /// let ab = tree.add_internal_vertex(vec![a, b], Some(BranchLength::new(1.0)));
+    /// tree.add_root(vec![ab, c]);
+    ///
+    /// let issues = tree.validate();
+    /// assert_eq!(issues[0], ValidationIssue::DuplicateLeafLabel { label_index: 0, leaves: vec![a, b] });
+    /// assert_eq!(issues[1], ValidationIssue::NonUltrametric { leaves: vec![a, b], max_deviation: 0.5 });
+    /// ```
+    pub fn validate(&self) -> Vec<ValidationIssue> {
+        let mut issues = self.structural_issues();
+        let is_sound = issues.is_empty();
+        issues.extend(self.label_issues());
+        if is_sound {
+            issues.extend(self.branch_length_issues());
+        }
+
+        issues
+    }
+}
+
+// ============================================================================
+// Little Helpers
+// ============================================================================
+impl Tree {
+    /// Returns issues with the root, parent and child references and cycles.
+    fn structural_issues(&self) -> Vec<ValidationIssue> {
+        let mut issues = Vec::new();
+        let n = self.num_vertices();
+        let root = if self.is_root_set() {
+            Some(self.root().index())
+        } else {
+            issues.push(ValidationIssue::MissingRoot);
+            None
+        };
+
+        for index in 0..n {
+            let vertex = self.vertex(index);
+            if vertex.is_root() && root != Some(index) {
+                issues.push(ValidationIssue::ExtraRoot { vertex: index });
+            }
+
+            match vertex.parent_index() {
+                None if !vertex.is_root() => issues.push(ValidationIssue::MissingParent { vertex: index }),
+                Some(parent) if parent >= n || !self.vertex(parent).children().unwrap_or(&[]).contains(&index) =>
+                    issues.push(ValidationIssue::DanglingParent { vertex: index, parent }),
+                _ => {}
+            }
+
+            for &child in vertex.children().unwrap_or(&[]) {
+                if child >= n || self.vertex(child).parent_index() != Some(index) {
+                    issues.push(ValidationIssue::DanglingChild { vertex: index, child });
+                }
+            }
+        }
+
+        // Follow parents of each vertex until reaching a vertex without (valid) parent or one already known
+        // to get there; revisiting a vertex of the current path closes a cycle
+        const UNKNOWN: u8 = 0;
+        const ON_PATH: u8 = 1;
+        const DONE: u8 = 2;
+        let mut state = vec![UNKNOWN; n];
+        let mut path = Vec::new();
+        for start in 0..n {
+            let mut current = start;
+            loop {
+                match state[current] {
+                    DONE => break,
+                    ON_PATH => {
+                        issues.push(ValidationIssue::Cycle { vertex: current });
+                        break;
+                    }
+                    _ => {}
+                }
+                state[current] = ON_PATH;
+                path.push(current);
+                match self.vertex(current).parent_index() {
+                    Some(parent) if parent < n => current = parent,
+                    _ => break,
+                }
+            }
+            for index in path.drain(..) {
+                state[index] = DONE;
+            }
+        }
+
+        issues
+    }
+
+    /// Returns issues with the labels of leaves (out of range or duplicate).
+    fn label_issues(&self) -> Vec<ValidationIssue> {
+        let mut issues = Vec::new();
+        let mut leaves_by_label: HashMap<LabelIndex, Vec<TreeIndex>> = HashMap::new();
+        for vertex in (0..self.num_vertices()).map(|index| self.vertex(index)) {
+            if let Some(label_index) = vertex.label_index() {
+                if label_index >= self.num_leaves_init() {
+                    issues.push(ValidationIssue::LabelOutOfRange { vertex: vertex.index(), label_index });
+                }
+                leaves_by_label.entry(label_index).or_default().push(vertex.index());
+            }
+        }
+
+        let mut duplicates: Vec<_> = leaves_by_label.into_iter().filter(|(_, leaves)| leaves.len() > 1).collect();
+        duplicates.sort_unstable_by_key(|&(label_index, _)| label_index);
+        issues.extend(duplicates.into_iter()
+            .map(|(label_index, leaves)| ValidationIssue::DuplicateLeafLabel { label_index, leaves }));
+
+        issues
+    }
+
+    /// Returns issues with branch lengths (negative, missing, non-ultrametric); assumes a sound structure.
+    fn branch_length_issues(&self) -> Vec<ValidationIssue> {
+        let mut issues = Vec::new();
+        let non_root = || (0..self.num_vertices()).map(|index| self.vertex(index)).filter(|v| !v.is_root());
+
+        for vertex in non_root() {
+            if let Some(length) = vertex.branch_length().map(|length| *length)
+                && (length.is_sign_negative() || !length.is_finite()) {
+                issues.push(ValidationIssue::NegativeBranchLength { vertex: vertex.index(), length });
+            }
+        }
+
+        let num_missing = non_root().filter(|v| !v.has_branch_length()).count();
+        if num_missing > 0 {
+            if num_missing < non_root().count() {
+                issues.extend(non_root()
+                    .filter(|v| !v.has_branch_length())
+                    .map(|v| ValidationIssue::MissingBranchLength { vertex: v.index() }));
+            }
+            return issues;
+        }
+
+        // Distance of each vertex to the root
+        let mut depths = vec![0.0; self.num_vertices()];
+        for vertex in self.pre_order_iter() {
+            if let Some(parent) = vertex.parent_index() {
+                depths[vertex.index()] = depths[parent] + *vertex.branch_length().unwrap();
+            }
+        }
+
+        let leaves: Vec<TreeIndex> = self.pre_order_iter().filter(|v| v.is_leaf()).map(|v| v.index()).collect();
+        let max_depth = leaves.iter().map(|&leaf| depths[leaf]).fold(f64::NEG_INFINITY, f64::max);
+        let min_depth = leaves.iter().map(|&leaf| depths[leaf]).fold(f64::INFINITY, f64::min);
+        let tolerance = EPSILON.max(EPSILON * max_depth.abs());
+        let mut deviating: Vec<TreeIndex> = leaves.into_iter()
+            .filter(|&leaf| max_depth - depths[leaf] > tolerance)
+            .collect();
+        if !deviating.is_empty() {
+            deviating.sort_unstable();
+            issues.push(ValidationIssue::NonUltrametric { leaves: deviating, max_deviation: max_depth - min_depth });
+        }
+
+        issues
+    }
+}
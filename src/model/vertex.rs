@@ -2,11 +2,24 @@
 #![allow(dead_code)]
 // use crate::model::tree::Tree;
 
-use crate::model::tree::{LabelIndex, TreeIndex};
+use crate::model::tree::{LabelIndex, StoredIndex, TreeIndex};
+use std::error::Error;
+use std::fmt;
 use std::ops::Deref;
 
 /// During construction, Internal and Leaf vertex might not have parent set yet.
-const NO_PARENT_SET: TreeIndex = usize::MAX;
+const NO_PARENT_SET: StoredIndex = StoredIndex::MAX;
+
+/// Float type a [Vertex] stores its [BranchLength] as, `f64` by default;
+/// the `f32-lengths` feature stores them as `f32` (about 7 significant digits), 8 bytes less per vertex.
+/// The API always uses [BranchLength] (an `f64`).
+#[cfg(not(feature = "f32-lengths"))]
+pub type StoredLength = f64;
+
+/// Float type a [Vertex] stores its [BranchLength] as, `f32` with the `f32-lengths` feature
+/// (rounded to the nearest `f32`, saturating at `f32::MAX`). The API always uses [BranchLength] (an `f64`).
+#[cfg(feature = "f32-lengths")]
+pub type StoredLength = f32;
 
 // =#========================================================================#=
 // VERTEX
@@ -14,46 +27,60 @@ const NO_PARENT_SET: TreeIndex = usize::MAX;
 /// Represents a vertex (node) in a phylogenetic tree.
 ///
 /// A vertex can be either:
-/// - **Root**: Has two children, no parent and no branch_length
-/// - **Internal**: Has two children, no label, might have branch_length
+/// - **Root**: Has children, no parent and no branch_length, might have node label
+/// - **Internal**: Has children, no leaf label, might have branch_length and node label
 /// - **Leaf**: Has no children, has label (via index) and might have branch_length
 ///
+/// Root and internal vertices of binary trees have exactly two children,
+/// but vertices of multifurcating trees (polytomies) may have more.
+///
 /// # Invariants
-/// - `index` is index in arena; non-negative (guaranteed by `TreeIndex = usize` type)
+/// - `index` is index in arena; non-negative (guaranteed by the unsigned [StoredIndex] type)
+/// - `index`, `parent` and `label_index` are stored as [StoredIndex] (`u32` by default) to keep vertices small,
+///   `children` as [TreeIndex] to hand them out as slice; accessors convert to [TreeIndex] and [LabelIndex]
+/// - `branch_length` is stored as [StoredLength] (`f64` by default); accessors convert to [BranchLength]
 /// - `branch_length` is non-negative (enforced); might not be set
-/// - Internal vertices and Leaf have `parent` is `TreeIndex` of parent in arena; `NO_PARENT_SET = usize::MAX` only during construction
-/// - Internal vertices have `children` as tuple of `TreeIndex`
+/// - Internal vertices and Leaf have `parent` is index of parent in arena; `NO_PARENT_SET = StoredIndex::MAX` only during construction
+/// - Root and internal vertices have `children` as non-empty list of `TreeIndex`
 /// - Leaf vertices have a `label_index`, since many trees share labels
+/// - Root and internal vertices might have a `node_label` (e.g. clade name or support value), stored per tree
 #[derive(PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Vertex {
-    /// Root vertex of the tree (has no parent, has two children)
+    /// Root vertex of the tree (has no parent, has children)
     Root {
         /// Index of this vertex in the tree arena
-        index: TreeIndex,
-        /// Indices of the two child vertices
-        children: (TreeIndex, TreeIndex),
+        index: StoredIndex,
+        /// Indices of the child vertices
+        children: Vec<TreeIndex>,
+        /// Label of vertex (optional), e.g. clade name or support value
+        node_label: Option<String>,
     },
-    /// Internal vertex (has parent and two children, no label)
+    /// Internal vertex (has parent and children, no leaf label)
     Internal {
         /// Index of this vertex in the tree arena
-        index: TreeIndex,
+        index: StoredIndex,
         /// Index of the parent vertex
-        parent: TreeIndex,
-        /// Indices of the two child vertices
-        children: (TreeIndex, TreeIndex),
+        parent: StoredIndex,
+        /// Indices of the child vertices
+        children: Vec<TreeIndex>,
         /// Distance to parent node (optional, non-negative if present)
-        branch_length: Option<BranchLength>,
+        #[cfg_attr(feature = "serde", serde(default, deserialize_with = "deserialize_length"))]
+        branch_length: Option<StoredLength>,
+        /// Label of vertex (optional), e.g. clade name or support value
+        node_label: Option<String>,
     },
     /// Leaf vertex (has parent and label, no children)
     Leaf {
         /// Index of this vertex in the tree arena
-        index: TreeIndex,
+        index: StoredIndex,
         /// Index into the shared label map
-        label_index: LabelIndex,
+        label_index: StoredIndex,
         /// Index of the parent vertex
-        parent: TreeIndex,
+        parent: StoredIndex,
         /// Distance to parent node (optional, non-negative if present)
-        branch_length: Option<BranchLength>,
+        #[cfg_attr(feature = "serde", serde(default, deserialize_with = "deserialize_length"))]
+        branch_length: Option<StoredLength>,
     },
 }
 
@@ -62,11 +89,12 @@ impl Vertex {
     ///
     /// # Arguments
     /// * `index` - The unique index of this vertex in the tree (arena)
-    /// * `children` - Tuple of child indices
-    pub fn new_root(index: TreeIndex, children: (TreeIndex, TreeIndex)) -> Self {
+    /// * `children` - Indices of children
+    pub fn new_root(index: TreeIndex, children: Vec<TreeIndex>) -> Self {
         Vertex::Root {
-            index,
+            index: to_stored(index),
             children,
+            node_label: None,
         }
     }
 
@@ -74,14 +102,15 @@ impl Vertex {
     ///
     /// # Arguments
     /// * `index` - The unique index of this vertex in the tree (arena)
-    /// * `children` - Tuple of child indices
+    /// * `children` - Indices of children
     /// * `branch_length` - Distance to parent node (non-negative)
-    pub fn new_internal(index: TreeIndex, children: (TreeIndex, TreeIndex), branch_length: Option<BranchLength>) -> Self {
+    pub fn new_internal(index: TreeIndex, children: Vec<TreeIndex>, branch_length: Option<BranchLength>) -> Self {
         Vertex::Internal {
-            index,
+            index: to_stored(index),
             parent: NO_PARENT_SET,
             children,
-            branch_length,
+            branch_length: branch_length.map(to_stored_length),
+            node_label: None,
         }
     }
 
@@ -93,19 +122,19 @@ impl Vertex {
     /// * `label_index` - Index into the label map for this leaf's label
     pub fn new_leaf(index: TreeIndex, branch_length: Option<BranchLength>, label_index: LabelIndex) -> Self {
         Vertex::Leaf {
-            index,
-            label_index,
+            index: to_stored(index),
+            label_index: to_stored(label_index),
             parent: NO_PARENT_SET,
-            branch_length,
+            branch_length: branch_length.map(to_stored_length),
         }
     }
 
     /// Returns the index of this vertex.
     pub fn index(&self) -> TreeIndex {
         match self {
-            Vertex::Root { index, .. } => *index,
-            Vertex::Internal { index, .. } => *index,
-            Vertex::Leaf { index, .. } => *index,
+            Vertex::Root { index, .. } => from_stored(*index),
+            Vertex::Internal { index, .. } => from_stored(*index),
+            Vertex::Leaf { index, .. } => from_stored(*index),
         }
     }
 
@@ -122,19 +151,68 @@ impl Vertex {
     pub fn branch_length(&self) -> Option<BranchLength> {
         match self {
             Vertex::Root { .. } => None,
-            Vertex::Internal { branch_length, .. } => *branch_length,
-            Vertex::Leaf { branch_length, .. } => *branch_length,
+            Vertex::Internal { branch_length, .. } | Vertex::Leaf { branch_length, .. } => branch_length.map(from_stored_length),
+        }
+    }
+
+    /// Sets (or removes) the branch length of a non-root vertex.
+    ///
+    /// # Panics
+    /// Panics if called on root.
+    pub fn set_branch_length(&mut self, length: Option<BranchLength>) {
+        match self {
+            Vertex::Root { .. } => panic!("Cannot set branch length on root vertex"),
+            Vertex::Internal { branch_length, .. } | Vertex::Leaf { branch_length, .. } => {
+                *branch_length = length.map(to_stored_length)
+            }
         }
     }
 
     /// Returns label index if this is a leaf, else `None`.
     pub fn label_index(&self) -> Option<usize> {
         match self {
-            Vertex::Leaf { label_index, .. } => Some(*label_index),
+            Vertex::Leaf { label_index, .. } => Some(from_stored(*label_index)),
             _ => None,
         }
     }
 
+    /// Sets the label index of a leaf, e.g. when moving it to another [LeafLabelMap](crate::model::leaf_label_map::LeafLabelMap).
+    ///
+    /// # Panics
+    /// Panics if called on root or internal vertex.
+    pub(crate) fn set_label_index(&mut self, index: LabelIndex) {
+        match self {
+            Vertex::Leaf { label_index, .. } => *label_index = to_stored(index),
+            _ => panic!("Cannot set label index on non-leaf vertex"),
+        }
+    }
+
+    /// Returns the node label if this is a root or internal vertex with label, else `None`.
+    ///
+    /// Leaf labels are not stored on vertices, see [label_index](Vertex::label_index).
+    pub fn node_label(&self) -> Option<&str> {
+        match self {
+            Vertex::Root { node_label, .. } | Vertex::Internal { node_label, .. } => node_label.as_deref(),
+            Vertex::Leaf { .. } => None,
+        }
+    }
+
+    /// Returns the support value if the node label is numeric (e.g. `95` or `0.98`), else `None`.
+    pub fn support(&self) -> Option<f64> {
+        self.node_label()?.trim().parse::<f64>().ok()
+    }
+
+    /// Sets (or removes) the node label of a root or internal vertex.
+    ///
+    /// # Panics
+    /// Panics if called on leaf.
+    pub fn set_node_label(&mut self, label: Option<String>) {
+        match self {
+            Vertex::Root { node_label, .. } | Vertex::Internal { node_label, .. } => *node_label = label,
+            Vertex::Leaf { .. } => panic!("Cannot set node label on leaf vertex"),
+        }
+    }
+
     /// Returns `true` if this vertex is a leaf.
     pub fn is_leaf(&self) -> bool {
         matches!(self, Vertex::Leaf { .. })
@@ -145,15 +223,35 @@ impl Vertex {
         matches!(self, Vertex::Internal { .. })
     }
 
-    /// Returns the children if this is an internal vertex, else `None`.
-    pub fn children(&self) -> Option<(usize, usize)> {
+    /// Returns the children if this is a root or internal vertex, else `None`.
+    pub fn children(&self) -> Option<&[TreeIndex]> {
         match self {
-            Vertex::Root { children, .. } => Some(*children),
-            Vertex::Internal { children, .. } => Some(*children),
+            Vertex::Root { children, .. } => Some(children),
+            Vertex::Internal { children, .. } => Some(children),
             Vertex::Leaf { .. } => None,
         }
     }
 
+    /// Returns the children mutably if this is a root or internal vertex, else `None`.
+    ///
+    /// Callers must keep parent references of (former) children consistent.
+    pub(crate) fn children_mut(&mut self) -> Option<&mut Vec<TreeIndex>> {
+        match self {
+            Vertex::Root { children, .. } | Vertex::Internal { children, .. } => Some(children),
+            Vertex::Leaf { .. } => None,
+        }
+    }
+
+    /// Returns the number of children (0 for leaves).
+    pub fn num_children(&self) -> usize {
+        self.children().map_or(0, |children| children.len())
+    }
+
+    /// Returns `true` if this vertex has more than two children (polytomy).
+    pub fn is_multifurcating(&self) -> bool {
+        self.num_children() > 2
+    }
+
     /// Returns `true` if this vertex is a root.
     pub fn is_root(&self) -> bool {
         matches!(self, Vertex::Root { .. })
@@ -166,8 +264,31 @@ impl Vertex {
     pub fn set_parent(&mut self, parent: TreeIndex) {
         match self {
             Vertex::Root { .. } => panic!("Cannot set parent on root vertex"),
-            Vertex::Internal { parent: p, .. } => *p = parent,
-            Vertex::Leaf { parent: p, .. } => *p = parent,
+            Vertex::Internal { parent: p, .. } => *p = to_stored(parent),
+            Vertex::Leaf { parent: p, .. } => *p = to_stored(parent),
+        }
+    }
+
+    /// Renumbers this vertex, its children and its parent (if set) with the given mapping of indices.
+    pub(crate) fn renumber(&mut self, new_index: impl Fn(TreeIndex) -> TreeIndex) {
+        match self {
+            Vertex::Root { index, children, .. } => {
+                *index = to_stored(new_index(from_stored(*index)));
+                children.iter_mut().for_each(|child| *child = new_index(*child));
+            }
+            Vertex::Internal { index, parent, children, .. } => {
+                *index = to_stored(new_index(from_stored(*index)));
+                if *parent != NO_PARENT_SET {
+                    *parent = to_stored(new_index(from_stored(*parent)));
+                }
+                children.iter_mut().for_each(|child| *child = new_index(*child));
+            }
+            Vertex::Leaf { index, parent, .. } => {
+                *index = to_stored(new_index(from_stored(*index)));
+                if *parent != NO_PARENT_SET {
+                    *parent = to_stored(new_index(from_stored(*parent)));
+                }
+            }
         }
     }
 
@@ -180,7 +301,7 @@ impl Vertex {
                 if *parent == NO_PARENT_SET {
                     None
                 } else {
-                    Some(*parent)
+                    Some(from_stored(*parent))
                 }
             }
             Vertex::Root { .. } => None,
@@ -198,6 +319,47 @@ impl Vertex {
     }
 }
 
+// ============================================================================
+// Little Helpers
+// ============================================================================
+/// Converts an arena or label index to the [StoredIndex] kept in a vertex.
+///
+/// # Panics
+/// Panics if the index does not fit into [StoredIndex] (more than `u32::MAX` vertices or labels
+/// without the `wide-index` feature).
+#[allow(clippy::useless_conversion, clippy::unnecessary_fallible_conversions)]
+pub(crate) fn to_stored(index: usize) -> StoredIndex {
+    StoredIndex::try_from(index).expect("Index exceeds StoredIndex, consider the `wide-index` feature")
+}
+
+/// Converts a [StoredIndex] kept in a vertex back to an arena or label index.
+#[allow(clippy::unnecessary_cast)]
+pub(crate) const fn from_stored(index: StoredIndex) -> usize {
+    index as usize
+}
+
+/// Converts a branch length to the [StoredLength] kept in a vertex,
+/// rounding to the nearest `f32` and saturating at `f32::MAX` with the `f32-lengths` feature
+/// (so a stored length never becomes infinite).
+#[allow(clippy::unnecessary_cast)]
+pub(crate) fn to_stored_length(length: BranchLength) -> StoredLength {
+    (*length).clamp(-StoredLength::MAX as f64, StoredLength::MAX as f64) as StoredLength
+}
+
+/// Converts a [StoredLength] kept in a vertex back to a branch length
+/// (unchecked, as it was checked when stored or deliberately kept negative).
+#[allow(clippy::unnecessary_cast)]
+pub(crate) fn from_stored_length(length: StoredLength) -> BranchLength {
+    BranchLength::new_unchecked(length as f64)
+}
+
+/// Deserializes an optional [BranchLength] (rejecting negative and non-finite lengths) into its [StoredLength].
+#[cfg(feature = "serde")]
+fn deserialize_length<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<Option<StoredLength>, D::Error> {
+    let length = <Option<BranchLength> as serde::Deserialize>::deserialize(deserializer)?;
+    Ok(length.map(to_stored_length))
+}
+
 
 // =#========================================================================#=
 // BRANCH LENGTH
@@ -207,10 +369,11 @@ impl Vertex {
 /// Represents the evolutionary distance between a vertex and its parent.
 /// The value is guaranteed to be non-negative and finite.
 #[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize), serde(transparent))]
 pub struct BranchLength(f64);
 
 impl BranchLength {
-    /// Creates a new branch length.
+    /// Creates a new branch length; use [BranchLength::try_new] for lengths from untrusted input.
     ///
     /// # Arguments
     /// * `length` - The branch length value (must be non-negative)
@@ -218,8 +381,36 @@ impl BranchLength {
     /// # Panics
     /// Panics if `length` is negative or not finite.
     pub fn new(length: f64) -> Self {
-        assert!(length >= 0.0, "Branch length must be non-negative, got {}", length);
-        assert!(length.is_finite(), "Branch length must be finite, got {}", length);
+        Self::try_new(length).unwrap_or_else(|error| panic!("{}", error))
+    }
+
+    /// Creates a new branch length, or returns an error if `length` is negative or not finite.
+    ///
+    /// # Errors
+    /// Returns [BranchLengthError::NotFinite] for NaN and infinite lengths,
+    /// and [BranchLengthError::Negative] for negative ones.
+    ///
+    /// # Example
+    /// ```
+    /// use nexus_parser::model::vertex::{BranchLength, BranchLengthError};
+    ///
+    /// assert_eq!(*BranchLength::try_new(0.5).unwrap(), 0.5);
+    /// assert_eq!(BranchLength::try_new(-1.0), Err(BranchLengthError::Negative(-1.0)));
+    /// assert!(BranchLength::try_from(f64::NAN).is_err());
+    /// ```
+    pub fn try_new(length: f64) -> Result<Self, BranchLengthError> {
+        if !length.is_finite() {
+            Err(BranchLengthError::NotFinite(length))
+        } else if length < 0.0 {
+            Err(BranchLengthError::Negative(length))
+        } else {
+            Ok(BranchLength(length))
+        }
+    }
+
+    /// Creates a branch length without checking that it is non-negative,
+    /// for parsers keeping negative lengths (see [Tree::validate](crate::model::tree::Tree::validate)).
+    pub(crate) fn new_unchecked(length: f64) -> Self {
         BranchLength(length)
     }
 }
@@ -231,8 +422,39 @@ impl Deref for BranchLength {
     }
 }
 
-// impl From<f64> for BranchLength {
-//     fn from(value: f64) -> Self {
-//         BranchLength::new(value)
-//     }
-// }
+impl TryFrom<f64> for BranchLength {
+    type Error = BranchLengthError;
+
+    fn try_from(length: f64) -> Result<Self, Self::Error> {
+        Self::try_new(length)
+    }
+}
+
+/// Deserializes a plain number, rejecting negative and non-finite lengths (instead of panicking).
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for BranchLength {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let length = f64::deserialize(deserializer)?;
+        BranchLength::try_new(length).map_err(serde::de::Error::custom)
+    }
+}
+
+/// Error for a value that is not a valid [BranchLength].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BranchLengthError {
+    /// The length is negative
+    Negative(f64),
+    /// The length is NaN or infinite
+    NotFinite(f64),
+}
+
+impl fmt::Display for BranchLengthError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BranchLengthError::Negative(length) => write!(f, "Branch length must be non-negative, got {}", length),
+            BranchLengthError::NotFinite(length) => write!(f, "Branch length must be finite, got {}", length),
+        }
+    }
+}
+
+impl Error for BranchLengthError {}
@@ -0,0 +1,120 @@
+//! Simulation module for random ultrametric trees, e.g. for benchmarks and simulation studies
+//! (requires feature `rand`).
+//!
+//! - `yule_tree`: Tree under the Yule (pure birth) process
+//! - `coalescent_tree`: Tree under the (Kingman) coalescent with constant population size
+//!
+//! Both return a [Tree] with branch lengths and a [LeafLabelMap] with the generated labels `t1`, ..., `tn`.
+//! Trees are built backwards in time from the `n` leaves at the present: while there are `k` lineages,
+//! the waiting time to the next event is exponential with a rate depending on `k`, after which two random
+//! lineages merge. Both models have the same distribution of ranked topologies and differ only in these rates.
+
+use crate::model::leaf_label_map::LeafLabelMap;
+use crate::model::tree::{Tree, TreeIndex};
+use crate::model::vertex::BranchLength;
+use rand::Rng;
+
+// =#========================================================================#=
+// MODELS
+// =#========================================================================#=
+/// Returns a random tree on `n` leaves under the Yule (pure birth) process with the given birth rate.
+///
+/// The process starts with two lineages at the root, each splitting at rate `birth_rate`,
+/// and is observed at a random time after the split creating the `n`-th lineage
+/// (before the next one); so, going back in time, `k` lineages merge at rate `k * birth_rate`.
+/// The expected height is `sum_{k=2..n} 1 / (k * birth_rate)`.
+///
+/// # Arguments
+/// * `n` - Number of leaves, at least `2`
+/// * `birth_rate` - Rate at which each lineage splits (speciation rate)
+/// * `rng` - Random number generator
+///
+/// # Panics
+/// Panics if `n < 2` or `birth_rate` is not positive and finite.
+///
+/// # Example
+/// ```
+/// use nexus_parser::sim::yule_tree;
+/// use rand::rngs::StdRng;
+/// use rand::SeedableRng;
+///
+/// let (tree, labels) = yule_tree(10, 1.0, &mut StdRng::seed_from_u64(42));
+/// assert_eq!(tree.num_leaves(), 10);
+/// assert_eq!(labels.get_label(0), Some("t1"));
+/// assert!(tree.is_valid() && tree.is_binary() && tree.is_ultrametric());
+/// ```
+pub fn yule_tree<R: Rng + ?Sized>(n: usize, birth_rate: f64, rng: &mut R) -> (Tree, LeafLabelMap) {
+    assert!(birth_rate > 0.0 && birth_rate.is_finite(), "Birth rate must be positive and finite, got {}", birth_rate);
+    random_merge_tree(n, |k| k as f64 * birth_rate, rng)
+}
+
+/// Returns a random tree on `n` leaves under the Kingman coalescent with constant population size.
+///
+/// Going back in time, each pair of the `k` lineages coalesces at rate `1 / pop_size`,
+/// so the next coalescence occurs at rate `k (k - 1) / (2 pop_size)`.
+/// Branch lengths are in the time units of `pop_size` (e.g. generations for a haploid population
+/// of `pop_size` individuals, or `2N` for a diploid one of `N`); the expected height is `2 pop_size (1 - 1/n)`.
+///
+/// # Arguments
+/// * `n` - Number of leaves, at least `2`
+/// * `pop_size` - (Effective) population size
+/// * `rng` - Random number generator
+///
+/// # Panics
+/// Panics if `n < 2` or `pop_size` is not positive and finite.
+///
+/// # Example
+/// ```
+/// use nexus_parser::sim::coalescent_tree;
+/// use rand::rngs::StdRng;
+/// use rand::SeedableRng;
+///
+/// let (tree, labels) = coalescent_tree(10, 1000.0, &mut StdRng::seed_from_u64(42));
+/// assert_eq!(labels.num_labels(), 10);
+/// assert!(tree.is_valid() && tree.is_ultrametric());
+/// ```
+pub fn coalescent_tree<R: Rng + ?Sized>(n: usize, pop_size: f64, rng: &mut R) -> (Tree, LeafLabelMap) {
+    assert!(pop_size > 0.0 && pop_size.is_finite(), "Population size must be positive and finite, got {}", pop_size);
+    random_merge_tree(n, |k| (k * (k - 1)) as f64 / (2.0 * pop_size), rng)
+}
+
+// ============================================================================
+// Little Helpers
+// ============================================================================
+/// Builds a tree backwards in time by merging two random lineages after exponential waiting times
+/// with the given rate for `k` lineages.
+fn random_merge_tree<R: Rng + ?Sized>(n: usize, rate: impl Fn(usize) -> f64, rng: &mut R) -> (Tree, LeafLabelMap) {
+    assert!(n >= 2, "Random trees need at least 2 leaves, got {}", n);
+
+    let mut tree = Tree::new(n);
+    let mut labels = LeafLabelMap::new(n);
+    // Active lineages with the height of their top vertex
+    let mut lineages: Vec<(TreeIndex, f64)> = (1..=n)
+        .map(|i| (tree.add_leaf(None, labels.get_or_insert(&format!("t{}", i))), 0.0))
+        .collect();
+
+    let mut height = 0.0;
+    while lineages.len() > 1 {
+        let k = lineages.len();
+        height += exponential(rate(k), rng);
+
+        let first = lineages.swap_remove(rng.random_range(0..k));
+        let second = lineages.swap_remove(rng.random_range(0..k - 1));
+        for &(child, child_height) in &[first, second] {
+            tree[child].set_branch_length(Some(BranchLength::new(height - child_height)));
+        }
+
+        let children = vec![first.0, second.0];
+        let parent = if k > 2 { tree.add_internal_vertex(children, None) } else { tree.add_root(children) };
+        lineages.push((parent, height));
+    }
+
+    (tree, labels)
+}
+
+/// Samples from the exponential distribution with the given rate (by inversion).
+fn exponential<R: Rng + ?Sized>(rate: f64, rng: &mut R) -> f64 {
+    // Uniform in (0, 1] to avoid ln(0)
+    let uniform = 1.0 - rng.random::<f64>();
+    -uniform.ln() / rate
+}
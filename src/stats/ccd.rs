@@ -0,0 +1,122 @@
+//! CCD module for the conditional clade distribution of a tree sample (Höhna and Drummond 2012, Larget 2013).
+//!
+//! - `Ccd`: Clade and clade split counts of a reference sample, scoring the probability of topologies
+//!
+//! The probability of a rooted topology is the product, over its internal vertices, of the conditional
+//! probability of the split of the vertex's clade into the clades of its children, estimated as the number of
+//! trees of the reference sample with this split divided by the number of trees with this clade.
+//! Topologies containing a clade or split never observed have probability `0`.
+
+use crate::model::taxon_set::TaxonSet;
+use crate::model::tree::Tree;
+use std::collections::HashMap;
+
+// =#========================================================================#=
+// CCD
+// =#========================================================================#=
+/// Conditional clade distribution of a (reference) tree sample, see the [module](self).
+///
+/// # Example
+/// ```
+/// use nexus_parser::parse_newick_str;
+/// use nexus_parser::stats::ccd::Ccd;
+///
+/// let (trees, _) = parse_newick_str("(((A,B),C),D);(((A,B),C),D);(((A,C),B),D);((A,B),(C,D));").unwrap();
+/// let ccd = Ccd::from_trees(&trees);
+///
+/// // ABC|D in 3 of 4 trees, then AB|C in 2 of the 3 trees with clade ABC
+/// let log_probabilities = ccd.score_sample(&trees);
+/// assert!((log_probabilities[0] - (0.75 * 2.0 / 3.0f64).ln()).abs() < 1e-12);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Ccd {
+    /// Number of trees containing each observed clade (of internal vertices, including the root)
+    clade_counts: HashMap<TaxonSet, usize>,
+    /// Number of trees containing each observed clade split, keyed by clade and sorted child clades
+    split_counts: HashMap<(TaxonSet, Vec<TaxonSet>), usize>,
+    /// Number of trees of the reference sample
+    num_trees: usize,
+    /// Number of taxa of the trees
+    num_taxa: usize,
+}
+
+impl Ccd {
+    /// Counts the clades and clade splits of the reference sample (e.g. a posterior sample after burnin).
+    ///
+    /// # Panics
+    /// Panics if the trees do not all have the same number of leaves.
+    pub fn from_trees(trees: &[Tree]) -> Self {
+        let num_taxa = trees.first().map_or(0, Tree::num_leaves_init);
+        let mut ccd = Ccd { clade_counts: HashMap::new(), split_counts: HashMap::new(), num_trees: trees.len(), num_taxa };
+        for tree in trees {
+            assert_eq!(tree.num_leaves_init(), num_taxa, "Trees must have the same number of leaves");
+            for (clade, children) in clade_splits(tree) {
+                *ccd.clade_counts.entry(clade.clone()).or_insert(0) += 1;
+                *ccd.split_counts.entry((clade, children)).or_insert(0) += 1;
+            }
+        }
+        ccd
+    }
+
+    /// Returns the number of trees of the reference sample.
+    pub fn num_trees(&self) -> usize {
+        self.num_trees
+    }
+
+    /// Returns the number of taxa of the trees.
+    pub fn num_taxa(&self) -> usize {
+        self.num_taxa
+    }
+
+    /// Returns the number of distinct clades observed (of internal vertices, including the root).
+    pub fn num_clades(&self) -> usize {
+        self.clade_counts.len()
+    }
+
+    /// Returns the natural logarithm of the probability of the tree's topology under this CCD,
+    /// `f64::NEG_INFINITY` if it contains a clade split never observed (or the reference sample is empty).
+    ///
+    /// # Panics
+    /// Panics if the tree does not have the same number of leaves as the trees of the reference sample.
+    pub fn log_probability(&self, tree: &Tree) -> f64 {
+        if self.num_trees > 0 {
+            assert_eq!(tree.num_leaves_init(), self.num_taxa, "Tree must have the same number of leaves as the sample");
+        }
+
+        let mut log_probability = 0.0;
+        for split in clade_splits(tree) {
+            let Some(&split_count) = self.split_counts.get(&split) else {
+                return f64::NEG_INFINITY;
+            };
+            log_probability += (split_count as f64 / self.clade_counts[&split.0] as f64).ln();
+        }
+        log_probability
+    }
+
+    /// Returns the log-probability trace of a sample (see [Ccd::log_probability]), one value per tree in order,
+    /// e.g. to plot like a likelihood trace and spot jumps of a chain between topology modes
+    /// that parameter traces do not reveal.
+    ///
+    /// # Panics
+    /// Panics if the trees do not have the same number of leaves as the trees of the reference sample.
+    pub fn score_sample(&self, trees: &[Tree]) -> Vec<f64> {
+        trees.iter().map(|tree| self.log_probability(tree)).collect()
+    }
+}
+
+// ============================================================================
+// Little Helpers
+// ============================================================================
+/// Returns the clade of each internal vertex (including the root) with the sorted clades of its children.
+fn clade_splits(tree: &Tree) -> Vec<(TaxonSet, Vec<TaxonSet>)> {
+    let clades = tree.clades(tree.num_leaves_init());
+    let mut splits = Vec::new();
+    for vertex in tree.post_order_iter() {
+        if let Some(children) = vertex.children() {
+            let mut child_clades: Vec<TaxonSet> = children.iter().map(|&child| clades[child].clone()).collect();
+            child_clades.sort_unstable();
+            splits.push((clades[vertex.index()].clone(), child_clades));
+        }
+    }
+    splits
+}
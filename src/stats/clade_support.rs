@@ -0,0 +1,191 @@
+//! Clade support module for summarizing how often clades occur across a tree sample.
+//!
+//! - `CladeFrequencies`: Posterior frequency of each clade observed in a sample
+//! - `clade_support`: Counts the (non-trivial) clades of a sample into `CladeFrequencies`
+//! - `annotate_support`: Stamps the frequencies onto the clades of a target tree (e.g. the MAP tree),
+//!   like TreeAnnotator does for the posterior support of a summary tree
+//! - `clade_credibility`: Log-product of the frequencies of a tree's clades
+//! - `mcc_tree`: Maximum clade credibility (MCC) tree of a sample, as chosen by TreeAnnotator
+//!
+//! Clades are those of the rooted trees, as for [consensus trees](crate::algorithms::consensus).
+
+use crate::algorithms::consensus;
+use crate::model::taxon_set::TaxonSet;
+use crate::model::tree::Tree;
+use std::collections::HashMap;
+
+// =#========================================================================#=
+// CLADE FREQUENCIES
+// =#========================================================================#=
+/// Frequencies of the non-trivial clades (at least two but not all taxa) observed in a tree sample.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CladeFrequencies {
+    /// Number of trees containing each observed clade
+    counts: HashMap<TaxonSet, usize>,
+    /// Number of trees in the sample
+    num_trees: usize,
+    /// Number of taxa of the trees
+    num_taxa: usize,
+}
+
+impl CladeFrequencies {
+    /// Returns the number of trees in the sample.
+    pub fn num_trees(&self) -> usize {
+        self.num_trees
+    }
+
+    /// Returns the number of taxa of the trees.
+    pub fn num_taxa(&self) -> usize {
+        self.num_taxa
+    }
+
+    /// Returns the number of distinct clades observed.
+    pub fn num_clades(&self) -> usize {
+        self.counts.len()
+    }
+
+    /// Returns the number of trees containing the clade (`0` if never observed).
+    pub fn count(&self, clade: &TaxonSet) -> usize {
+        self.counts.get(clade).copied().unwrap_or(0)
+    }
+
+    /// Returns the fraction of trees containing the clade, i.e. its posterior probability
+    /// (`0` if never observed or the sample is empty).
+    pub fn frequency(&self, clade: &TaxonSet) -> f64 {
+        if self.num_trees == 0 {
+            return 0.0;
+        }
+        self.count(clade) as f64 / self.num_trees as f64
+    }
+
+    /// Returns all observed clades with their frequencies, most frequent first
+    /// (ties broken by clade size and taxa, so the order is deterministic).
+    pub fn sorted(&self) -> Vec<(TaxonSet, f64)> {
+        let mut clades: Vec<(&TaxonSet, usize)> = self.counts.iter().map(|(clade, &count)| (clade, count)).collect();
+        clades.sort_unstable_by(|(a, count_a), (b, count_b)| count_b.cmp(count_a)
+            .then_with(|| a.len().cmp(&b.len()))
+            .then_with(|| a.cmp(b)));
+        clades.into_iter().map(|(clade, _)| (clade.clone(), self.frequency(clade))).collect()
+    }
+}
+
+/// Counts the non-trivial clades of the given tree sample (e.g. after burnin).
+///
+/// # Panics
+/// Panics if the trees do not all have the same number of leaves.
+///
+/// # Example
+/// ```
+/// use nexus_parser::model::taxon_set::TaxonSet;
+/// use nexus_parser::parse_newick_str;
+/// use nexus_parser::stats::clade_support::clade_support;
+///
+/// let (trees, labels) = parse_newick_str("((A,B),C);\n((A,B),C);\n((A,C),B);\n").unwrap();
+/// let frequencies = clade_support(&trees);
+///
+/// let ab = TaxonSet::from_indices(3, [labels.get_index("A").unwrap(), labels.get_index("B").unwrap()]);
+/// assert_eq!(frequencies.count(&ab), 2);
+/// assert!((frequencies.frequency(&ab) - 2.0 / 3.0).abs() < 1e-12);
+/// ```
+pub fn clade_support(trees: &[Tree]) -> CladeFrequencies {
+    CladeFrequencies {
+        counts: consensus::clade_frequencies(trees),
+        num_trees: trees.len(),
+        num_taxa: trees.first().map_or(0, |tree| tree.num_leaves_init()),
+    }
+}
+
+// =#========================================================================#=
+// ANNOTATION
+// =#========================================================================#=
+/// Sets the node label of every internal vertex of the target tree to the frequency of its clade
+/// in the sample (`0` for clades never observed), replacing previous labels.
+///
+/// The labels are read back by [Vertex::support](crate::model::vertex::Vertex::support)
+/// and written by the Newick and NEXUS writers (e.g. `((A,B)0.95,C);`). The root and leaves
+/// (trivial clades) are not labelled.
+///
+/// # Arguments
+/// * `tree` - Target tree over the same leaf label map as the sample, e.g. its MAP tree
+/// * `frequencies` - Clade frequencies of the sample, see [clade_support]
+///
+/// # Panics
+/// Panics if the tree does not have the same number of leaves as the trees of the sample.
+///
+/// # Example
+/// ```
+/// use nexus_parser::io::writer::newick::to_newick;
+/// use nexus_parser::parse_newick_str;
+/// use nexus_parser::stats::clade_support::{annotate_support, clade_support};
+///
+/// let (mut trees, labels) = parse_newick_str("(((A,B),C),D);\n(((A,B),D),C);\n").unwrap();
+/// let frequencies = clade_support(&trees);
+///
+/// let mut map_tree = trees.remove(0);
+/// annotate_support(&mut map_tree, &frequencies);
+/// assert_eq!(to_newick(&map_tree, &labels), "(((A,B)1,C)0.5,D);");
+/// ```
+pub fn annotate_support(tree: &mut Tree, frequencies: &CladeFrequencies) {
+    if frequencies.num_trees > 0 {
+        assert_eq!(tree.num_leaves_init(), frequencies.num_taxa, "Tree must have the same number of leaves as the sample");
+    }
+
+    let supports: Vec<Option<f64>> = (0..tree.num_vertices())
+        .map(|index| tree[index].is_internal().then(|| frequencies.frequency(tree.clade_taxa(index))))
+        .collect();
+    for (index, support) in supports.into_iter().enumerate() {
+        if let Some(support) = support {
+            tree[index].set_node_label(Some(support.to_string()));
+        }
+    }
+}
+
+// =#========================================================================#=
+// MCC TREE
+// =#========================================================================#=
+/// Returns the clade credibility of a tree, i.e. the (natural) logarithm of the product of the frequencies
+/// of its non-trivial clades in the sample; `f64::NEG_INFINITY` if it has a clade never observed.
+///
+/// # Panics
+/// Panics if the tree does not have the same number of leaves as the trees of the sample.
+pub fn clade_credibility(tree: &Tree, frequencies: &CladeFrequencies) -> f64 {
+    if frequencies.num_trees > 0 {
+        assert_eq!(tree.num_leaves_init(), frequencies.num_taxa, "Tree must have the same number of leaves as the sample");
+    }
+
+    let clades = tree.clades(tree.num_leaves_init());
+    (0..tree.num_vertices())
+        .filter(|&index| tree[index].is_internal())
+        .map(|index| frequencies.frequency(&clades[index]).ln())
+        .sum()
+}
+
+/// Returns the index of the maximum clade credibility (MCC) tree of the sample, i.e. of the tree
+/// with the highest [clade credibility](clade_credibility), the first one on ties (as TreeAnnotator).
+///
+/// # Returns
+/// The index of the MCC tree in `trees`, or `None` if the sample is empty
+///
+/// # Panics
+/// Panics if the trees do not all have the same number of leaves as the trees of the sample.
+///
+/// # Example
+/// ```
+/// use nexus_parser::parse_newick_str;
+/// use nexus_parser::stats::clade_support::{clade_support, mcc_tree};
+///
+/// // AB and ABC are each in 3 of 4 trees, AC and ABD only in 1
+/// let (trees, _) = parse_newick_str("(((A,B),C),D);(((A,C),B),D);(((A,B),D),C);(((A,B),C),D);").unwrap();
+/// let frequencies = clade_support(&trees);
+/// assert_eq!(mcc_tree(&trees, &frequencies), Some(0));
+/// ```
+pub fn mcc_tree(trees: &[Tree], frequencies: &CladeFrequencies) -> Option<usize> {
+    let mut best: Option<(usize, f64)> = None;
+    for (index, tree) in trees.iter().enumerate() {
+        let credibility = clade_credibility(tree, frequencies);
+        if best.is_none_or(|(_, best_credibility)| credibility > best_credibility) {
+            best = Some((index, credibility));
+        }
+    }
+    best.map(|(index, _)| index)
+}
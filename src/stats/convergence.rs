@@ -0,0 +1,324 @@
+//! Convergence module for diagnosing MCMC chains of trees by their topologies.
+//!
+//! - `topological_ess`: ESS of the trace of Robinson-Foulds distances to a focal tree
+//! - `pseudo_ess`: Median topological ESS over several focal trees of the chain
+//! - `split_ess`: ESS of the presence trace of each split
+//! - `asdsf`: Average standard deviation of split frequencies between two samples (e.g. independent runs)
+//! - `compare_samples`: Clade frequencies of two samples side by side ([SampleComparison]), e.g. for a scatter plot,
+//!   with the ASDSF of their clade frequencies and the clades the samples disagree on
+//!
+//! Topologies have no natural scalar value, so their ESS is that of a derived trace (Lanfear et al. 2016,
+//! as in RWTY); the ASDSF is the convergence statistic reported by MrBayes. Splits are the unrooted,
+//! non-trivial ones of [Tree::bipartitions], so trees need to share a [LeafLabelMap](crate::model::leaf_label_map::LeafLabelMap).
+
+use crate::model::taxon_set::TaxonSet;
+use crate::model::tree::Tree;
+use crate::stats::clade_support::clade_support;
+use crate::stats::summary::{effective_sample_size, median};
+use std::collections::{HashMap, HashSet};
+
+// =#========================================================================#=
+// TOPOLOGICAL ESS
+// =#========================================================================#=
+/// Returns the topological ESS of a chain of trees, i.e. the ESS of the trace of Robinson-Foulds distances
+/// of its trees to a focal tree (see [effective_sample_size]).
+///
+/// # Arguments
+/// * `trees` - Trees of the chain, in sample order (e.g. after burnin)
+/// * `focal` - Tree to measure the distances to, e.g. a tree of the chain
+///
+/// # Returns
+/// The ESS, or `None` if there are fewer than two trees
+///
+/// # Panics
+/// Panics if the trees do not all have the same number of leaves.
+///
+/// # Example
+/// ```
+/// use nexus_parser::parse_newick_str;
+/// use nexus_parser::stats::convergence::topological_ess;
+///
+/// let (trees, _) = parse_newick_str("((A,B),(C,D));((A,C),(B,D));((A,B),(C,D));((A,C),(B,D));").unwrap();
+/// // Alternating topologies are anticorrelated, so the ESS is capped at the number of trees
+/// assert_eq!(topological_ess(&trees, &trees[0]), Some(4.0));
+/// ```
+pub fn topological_ess(trees: &[Tree], focal: &Tree) -> Option<f64> {
+    let focal_splits = focal.bipartitions();
+    let distances: Vec<f64> = trees.iter()
+        .map(|tree| {
+            assert_eq!(tree.num_leaves_init(), focal.num_leaves_init(), "Trees must have the same number of leaves");
+            tree.bipartitions().symmetric_difference(&focal_splits).count() as f64
+        })
+        .collect();
+    effective_sample_size(&distances)
+}
+
+/// Returns the pseudo-ESS of a chain of trees, i.e. the median [topological ESS](topological_ess)
+/// over `num_focal` focal trees spread evenly over the chain (the first, ..., the last).
+///
+/// # Returns
+/// The median ESS, or `None` if there are fewer than two trees
+///
+/// # Panics
+/// Panics if `num_focal` is `0` or the trees do not all have the same number of leaves.
+///
+/// # Example
+/// ```
+/// use nexus_parser::parse_newick_str;
+/// use nexus_parser::stats::convergence::pseudo_ess;
+///
+/// // A chain stuck on one topology before moving on to another mixes poorly
+/// let newick = "((A,B),(C,D));".repeat(10) + &"((A,C),(B,D));".repeat(10);
+/// let (trees, _) = parse_newick_str(&newick).unwrap();
+/// assert!(pseudo_ess(&trees, 5).unwrap() < 5.0);
+/// ```
+pub fn pseudo_ess(trees: &[Tree], num_focal: usize) -> Option<f64> {
+    assert!(num_focal > 0, "Pseudo-ESS needs at least one focal tree");
+    if trees.len() < 2 {
+        return None;
+    }
+
+    let num_focal = num_focal.min(trees.len());
+    let ess: Vec<f64> = (0..num_focal)
+        .map(|i| if num_focal == 1 { 0 } else { i * (trees.len() - 1) / (num_focal - 1) })
+        .filter_map(|focal| topological_ess(trees, &trees[focal]))
+        .collect();
+    median(&ess)
+}
+
+// =#========================================================================#=
+// SPLIT ESS
+// =#========================================================================#=
+/// Returns the ESS of the presence trace (`1` if a tree contains the split, else `0`) of each split
+/// observed in at least the fraction `min_frequency` of the trees, lowest ESS first.
+///
+/// Splits in all or none of the trees have a constant trace and thus an ESS of the number of trees.
+///
+/// # Returns
+/// Pairs of split (as in [Tree::bipartitions]) and ESS; empty if there are fewer than two trees
+///
+/// # Panics
+/// Panics if the trees do not all have the same number of leaves.
+///
+/// # Example
+/// ```
+/// use nexus_parser::parse_newick_str;
+/// use nexus_parser::stats::convergence::split_ess;
+///
+/// let newick = "((A,B),(C,D));".repeat(10) + &"((A,C),(B,D));".repeat(10);
+/// let (trees, _) = parse_newick_str(&newick).unwrap();
+///
+/// // Both splits are present in long runs of trees, so their traces are highly autocorrelated
+/// let ess = split_ess(&trees, 0.1);
+/// assert_eq!(ess.len(), 2);
+/// assert!(ess.iter().all(|(_, ess)| *ess < 5.0));
+/// ```
+pub fn split_ess(trees: &[Tree], min_frequency: f64) -> Vec<(TaxonSet, f64)> {
+    if trees.len() < 2 {
+        return Vec::new();
+    }
+
+    let tree_splits = bipartitions_of(trees);
+    let counts = split_counts(&tree_splits);
+    let mut ess: Vec<(TaxonSet, f64)> = counts.into_iter()
+        .filter(|&(_, count)| count as f64 >= min_frequency * trees.len() as f64)
+        .map(|(split, _)| {
+            let presence: Vec<f64> = tree_splits.iter().map(|splits| if splits.contains(split) { 1.0 } else { 0.0 }).collect();
+            let ess = effective_sample_size(&presence).expect("At least two trees");
+            (split.clone(), ess)
+        })
+        .collect();
+    ess.sort_by(|(a, ess_a), (b, ess_b)| ess_a.total_cmp(ess_b).then_with(|| a.cmp(b)));
+    ess
+}
+
+// =#========================================================================#=
+// ASDSF
+// =#========================================================================#=
+/// Returns the average standard deviation of split frequencies (ASDSF) of two samples,
+/// e.g. of independent runs after burnin, as reported by MrBayes.
+///
+/// For each split with a frequency of at least `min_frequency` in one of the samples (MrBayes uses `0.1`),
+/// the (sample) standard deviation of its two frequencies is `|f_1 - f_2| / sqrt(2)`; the ASDSF is their mean.
+/// Values close to `0` (e.g. below `0.01`) indicate that the runs sample the same distribution.
+///
+/// # Returns
+/// The ASDSF, `0.0` if no split is frequent enough, or `None` if a sample is empty
+///
+/// # Panics
+/// Panics if the trees do not all have the same number of leaves.
+///
+/// # Example
+/// ```
+/// use nexus_parser::parse_newick_str;
+/// use nexus_parser::stats::convergence::asdsf;
+///
+/// let (trees, _) = parse_newick_str("((A,B),(C,D));((A,B),(C,D));((A,B),(C,D));((A,C),(B,D));").unwrap();
+/// let (run_1, run_2) = trees.split_at(2);
+///
+/// // AB|CD: 1.0 vs. 0.5, AC|BD: 0.0 vs. 0.5
+/// let expected = 0.5 / 2f64.sqrt();
+/// assert!((asdsf(run_1, run_2, 0.1).unwrap() - expected).abs() < 1e-12);
+/// ```
+pub fn asdsf(trees: &[Tree], other: &[Tree], min_frequency: f64) -> Option<f64> {
+    if trees.is_empty() || other.is_empty() {
+        return None;
+    }
+    assert_eq!(trees[0].num_leaves_init(), other[0].num_leaves_init(), "Trees must have the same number of leaves");
+
+    let frequencies = split_frequencies(trees);
+    let other_frequencies = split_frequencies(other);
+    let splits: HashSet<&TaxonSet> = frequencies.keys().chain(other_frequencies.keys()).collect();
+
+    let frequency_pairs = splits.into_iter()
+        .map(|split| (frequencies.get(split).copied().unwrap_or(0.0), other_frequencies.get(split).copied().unwrap_or(0.0)));
+    Some(average_std_dev(frequency_pairs, min_frequency))
+}
+
+// =#========================================================================#=
+// SAMPLE COMPARISON
+// =#========================================================================#=
+/// Minimum frequency in one of the samples of clades included in [SampleComparison::asdsf], as in MrBayes
+pub const ASDSF_MIN_FREQUENCY: f64 = 0.1;
+
+/// Clade frequencies of two samples side by side, see [compare_samples].
+#[derive(Debug, Clone, PartialEq)]
+pub struct SampleComparison {
+    /// Each clade observed in a sample with its frequency in the first and second sample,
+    /// largest difference first
+    clades: Vec<(TaxonSet, f64, f64)>,
+    /// Number of trees of the first and second sample
+    num_trees: (usize, usize),
+}
+
+impl SampleComparison {
+    /// Returns each clade observed in one of the samples with its frequency in the first and in the second sample,
+    /// largest difference of frequencies first (ties broken by clade), e.g. as data of a scatter plot.
+    pub fn clades(&self) -> &[(TaxonSet, f64, f64)] {
+        &self.clades
+    }
+
+    /// Returns the number of trees of the first and second sample.
+    pub fn num_trees(&self) -> (usize, usize) {
+        self.num_trees
+    }
+
+    /// Returns the average standard deviation of the clade frequencies over the clades
+    /// with a frequency of at least [ASDSF_MIN_FREQUENCY] in one of the samples, `0.0` if there are none.
+    ///
+    /// This is the statistic of [asdsf], but over the rooted clades of [SampleComparison::clades]
+    /// instead of the unrooted splits; the two values thus differ unless the trees are unrooted
+    /// (i.e. differ only in root position), and MrBayes reports the latter.
+    pub fn asdsf(&self) -> f64 {
+        let frequency_pairs = self.clades.iter().map(|&(_, frequency, other_frequency)| (frequency, other_frequency));
+        average_std_dev(frequency_pairs, ASDSF_MIN_FREQUENCY)
+    }
+
+    /// Returns the clades whose frequencies differ by more than `threshold` between the samples,
+    /// largest difference first, e.g. to find the parts of the tree where independent runs disagree.
+    pub fn disagreements(&self, threshold: f64) -> Vec<&(TaxonSet, f64, f64)> {
+        self.clades.iter()
+            .take_while(|(_, frequency, other_frequency)| (frequency - other_frequency).abs() > threshold)
+            .collect()
+    }
+}
+
+/// Compares the clade frequencies of two samples (see [clade_support]), e.g. of independent runs after burnin.
+///
+/// Clades are those of the rooted trees, as for [clade support](crate::stats::clade_support);
+/// for the splits of unrooted trees, see [asdsf].
+///
+/// # Panics
+/// Panics if the trees do not all have the same number of leaves.
+///
+/// # Example
+/// ```
+/// use nexus_parser::parse_newick_str;
+/// use nexus_parser::stats::convergence::compare_samples;
+///
+/// let (trees, _) = parse_newick_str("(((A,B),C),D);(((A,B),C),D);(((A,B),C),D);(((A,C),B),D);").unwrap();
+/// let (run_1, run_2) = trees.split_at(2);
+/// let comparison = compare_samples(run_1, run_2);
+///
+/// // AB: 1.0 vs. 0.5, AC: 0.0 vs. 0.5, ABC: 1.0 vs. 1.0
+/// assert_eq!(comparison.clades().len(), 3);
+/// assert!((comparison.asdsf() - 1.0 / 3.0 / 2f64.sqrt()).abs() < 1e-12);
+///
+/// let disagreements = comparison.disagreements(0.25);
+/// assert_eq!(disagreements.len(), 2);
+/// assert_eq!(disagreements[0].0.len(), 2);
+/// ```
+pub fn compare_samples(trees: &[Tree], other: &[Tree]) -> SampleComparison {
+    if let (Some(tree), Some(other_tree)) = (trees.first(), other.first()) {
+        assert_eq!(tree.num_leaves_init(), other_tree.num_leaves_init(), "Trees must have the same number of leaves");
+    }
+
+    let frequencies = clade_support(trees);
+    let other_frequencies = clade_support(other);
+    let observed: HashSet<TaxonSet> = frequencies.sorted().into_iter()
+        .chain(other_frequencies.sorted())
+        .map(|(clade, _)| clade)
+        .collect();
+
+    let mut clades: Vec<(TaxonSet, f64, f64)> = observed.into_iter()
+        .map(|clade| {
+            let (frequency, other_frequency) = (frequencies.frequency(&clade), other_frequencies.frequency(&clade));
+            (clade, frequency, other_frequency)
+        })
+        .collect();
+    clades.sort_by(|(a, frequency_a, other_a), (b, frequency_b, other_b)| (frequency_b - other_b).abs()
+        .total_cmp(&(frequency_a - other_a).abs())
+        .then_with(|| a.cmp(b)));
+
+    SampleComparison { clades, num_trees: (trees.len(), other.len()) }
+}
+
+// ============================================================================
+// Little Helpers
+// ============================================================================
+/// Returns the splits of each tree.
+///
+/// # Panics
+/// Panics if the trees do not all have the same number of leaves.
+fn bipartitions_of(trees: &[Tree]) -> Vec<HashSet<TaxonSet>> {
+    let num_taxa = trees.first().map_or(0, Tree::num_leaves_init);
+    trees.iter()
+        .map(|tree| {
+            assert_eq!(tree.num_leaves_init(), num_taxa, "Trees must have the same number of leaves");
+            tree.bipartitions()
+        })
+        .collect()
+}
+
+/// Returns the number of trees containing each observed split.
+fn split_counts(tree_splits: &[HashSet<TaxonSet>]) -> HashMap<&TaxonSet, usize> {
+    let mut counts = HashMap::new();
+    for split in tree_splits.iter().flatten() {
+        *counts.entry(split).or_insert(0) += 1;
+    }
+    counts
+}
+
+/// Returns the mean (sample) standard deviation `|f_1 - f_2| / sqrt(2)` of the pairs of frequencies
+/// of which at least one is at least `min_frequency`, or `0.0` if there are none.
+fn average_std_dev(frequency_pairs: impl Iterator<Item = (f64, f64)>, min_frequency: f64) -> f64 {
+    let std_devs: Vec<f64> = frequency_pairs
+        .filter(|&(frequency, other_frequency)| frequency.max(other_frequency) >= min_frequency)
+        .map(|(frequency, other_frequency)| (frequency - other_frequency).abs() / 2f64.sqrt())
+        .collect();
+    if std_devs.is_empty() {
+        return 0.0;
+    }
+    std_devs.iter().sum::<f64>() / std_devs.len() as f64
+}
+
+/// Returns the fraction of trees containing each observed split.
+///
+/// # Panics
+/// Panics if the trees do not all have the same number of leaves.
+fn split_frequencies(trees: &[Tree]) -> HashMap<TaxonSet, f64> {
+    let tree_splits = bipartitions_of(trees);
+    split_counts(&tree_splits).into_iter()
+        .map(|(split, count)| (split.clone(), count as f64 / trees.len() as f64))
+        .collect()
+}
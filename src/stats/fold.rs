@@ -0,0 +1,286 @@
+//! Fold module for custom summaries over (streamed) tree samples.
+//!
+//! - `TreeFold`: User-defined summary with init/accumulate/merge/finalize,
+//!   combinable by running several folds simultaneously as a tuple
+//! - `fold_trees`: Runs a fold over in-memory trees, in parallel
+//! - `fold_stream`: Runs a fold over a stream of trees (e.g. a [TreeReader](crate::io::parser::nexus::TreeReader)),
+//!   in parallel, with memory independent of the number of trees
+
+use crate::model::tree::Tree;
+use std::num::NonZeroUsize;
+use std::sync::mpsc::{sync_channel, Receiver};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+/// Number of trees sent to a worker thread at once when folding a stream
+const BATCH_SIZE: usize = 64;
+
+// =#========================================================================#=
+// TREE FOLD
+// =#========================================================================#=
+/// A summary of a tree sample computed by folding over the trees.
+///
+/// Trees are split among threads: each thread starts with its own accumulator (`init`),
+/// adds its trees (`accumulate`), then accumulators are combined (`merge`)
+/// and turned into the result (`finalize`).
+/// Since trees are distributed in arbitrary order, `merge` must be associative and commutative
+/// with respect to the result (e.g. sums, counts, maxima); order-dependent summaries
+/// (e.g. traces for ESS) should run with a single thread.
+///
+/// Several folds can be run simultaneously (in one pass over the trees) as tuple, e.g. `(a, b)`,
+/// whose output is the tuple of outputs.
+///
+/// # Example
+/// ```
+/// use nexus_parser::model::tree::Tree;
+/// use nexus_parser::model::vertex::BranchLength;
+/// use nexus_parser::stats::fold::{fold_trees, TreeFold};
+///
+/// /// Mean tree height
+/// struct MeanHeight;
+///
+/// impl TreeFold for MeanHeight {
+///     type Accumulator = (f64, usize);
+///     type Output = Option<f64>;
+///
+///     fn init(&self) -> (f64, usize) { (0.0, 0) }
+///     fn accumulate(&self, acc: &mut (f64, usize), tree: &Tree) {
+///         acc.0 += tree.height();
+///         acc.1 += 1;
+///     }
+///     fn merge(&self, a: (f64, usize), b: (f64, usize)) -> (f64, usize) { (a.0 + b.0, a.1 + b.1) }
+///     fn finalize(&self, acc: (f64, usize)) -> Option<f64> {
+///         (acc.1 > 0).then(|| acc.0 / acc.1 as f64)
+///     }
+/// }
+///
+/// let mut tree = Tree::new(2);
+/// let a = tree.add_leaf(Some(BranchLength::new(2.0)), 0);
+/// let b = tree.add_leaf(Some(BranchLength::new(2.0)), 1);
+/// tree.add_root(vec![a, b]);
+///
+/// let trees = vec![tree; 100];
+/// assert_eq!(fold_trees(&MeanHeight, &trees, 4), Some(2.0));
+/// ```
+pub trait TreeFold: Sync {
+    /// Intermediate state of the fold, one per thread
+    type Accumulator: Send;
+    /// Result of the fold
+    type Output;
+
+    /// Returns a new, empty accumulator.
+    fn init(&self) -> Self::Accumulator;
+
+    /// Adds a tree to the accumulator.
+    fn accumulate(&self, accumulator: &mut Self::Accumulator, tree: &Tree);
+
+    /// Combines two accumulators (of disjoint sets of trees) into one.
+    fn merge(&self, accumulator: Self::Accumulator, other: Self::Accumulator) -> Self::Accumulator;
+
+    /// Turns the (merged) accumulator of all trees into the result.
+    fn finalize(&self, accumulator: Self::Accumulator) -> Self::Output;
+}
+
+/// Implements [TreeFold] for tuples of folds, running them simultaneously.
+macro_rules! impl_tree_fold_for_tuple {
+    ($($fold:ident: $index:tt),+) => {
+        impl<$($fold: TreeFold),+> TreeFold for ($($fold,)+) {
+            type Accumulator = ($($fold::Accumulator,)+);
+            type Output = ($($fold::Output,)+);
+
+            fn init(&self) -> Self::Accumulator {
+                ($(self.$index.init(),)+)
+            }
+
+            fn accumulate(&self, accumulator: &mut Self::Accumulator, tree: &Tree) {
+                $(self.$index.accumulate(&mut accumulator.$index, tree);)+
+            }
+
+            fn merge(&self, accumulator: Self::Accumulator, other: Self::Accumulator) -> Self::Accumulator {
+                ($(self.$index.merge(accumulator.$index, other.$index),)+)
+            }
+
+            fn finalize(&self, accumulator: Self::Accumulator) -> Self::Output {
+                ($(self.$index.finalize(accumulator.$index),)+)
+            }
+        }
+    };
+}
+
+impl_tree_fold_for_tuple!(A: 0, B: 1);
+impl_tree_fold_for_tuple!(A: 0, B: 1, C: 2);
+impl_tree_fold_for_tuple!(A: 0, B: 1, C: 2, D: 3);
+impl_tree_fold_for_tuple!(A: 0, B: 1, C: 2, D: 3, E: 4);
+
+// =#========================================================================#=
+// DRIVERS
+// =#========================================================================#=
+/// Runs the fold over the given trees, split into contiguous chunks among threads.
+///
+/// # Arguments
+/// * `fold` - Fold (or tuple of folds) to run
+/// * `trees` - Trees to fold over
+/// * `num_threads` - Number of threads; `0` uses the available parallelism
+///
+/// # Returns
+/// The finalized result of the fold.
+pub fn fold_trees<F: TreeFold>(fold: &F, trees: &[Tree], num_threads: usize) -> F::Output {
+    let num_threads = resolve_num_threads(num_threads);
+    if num_threads == 1 || trees.len() <= 1 {
+        let mut accumulator = fold.init();
+        for tree in trees {
+            fold.accumulate(&mut accumulator, tree);
+        }
+        return fold.finalize(accumulator);
+    }
+
+    let chunk_size = trees.len().div_ceil(num_threads);
+    let accumulators: Vec<F::Accumulator> = thread::scope(|scope| {
+        let handles: Vec<_> = trees.chunks(chunk_size)
+            .map(|chunk| scope.spawn(move || {
+                let mut accumulator = fold.init();
+                for tree in chunk {
+                    fold.accumulate(&mut accumulator, tree);
+                }
+                accumulator
+            }))
+            .collect();
+        handles.into_iter()
+            .map(|handle| handle.join().expect("Fold worker thread panicked"))
+            .collect()
+    });
+
+    merge_and_finalize(fold, accumulators)
+}
+
+/// Runs the fold over a stream of trees, e.g. from a [TreeReader](crate::io::parser::nexus::TreeReader).
+///
+/// The calling thread reads trees and hands them in batches to worker threads,
+/// so only a few batches of trees are in memory at any time.
+/// Stops at the first error of the stream.
+///
+/// # Arguments
+/// * `fold` - Fold (or tuple of folds) to run
+/// * `trees` - Stream of trees (or errors)
+/// * `num_threads` - Number of worker threads; `0` uses the available parallelism,
+///   `1` folds in the calling thread
+///
+/// # Returns
+/// The finalized result of the fold.
+///
+/// # Errors
+/// Returns the first error of the stream.
+///
+/// # Example
+/// ```no_run
+/// use nexus_parser::io::parser::nexus::TreeReader;
+/// use nexus_parser::stats::fold::fold_stream;
+/// # use nexus_parser::stats::fold::TreeFold;
+/// # use nexus_parser::model::tree::Tree;
+/// # struct Count;
+/// # impl TreeFold for Count {
+/// #     type Accumulator = usize;
+/// #     type Output = usize;
+/// #     fn init(&self) -> usize { 0 }
+/// #     fn accumulate(&self, acc: &mut usize, _: &Tree) { *acc += 1; }
+/// #     fn merge(&self, a: usize, b: usize) -> usize { a + b }
+/// #     fn finalize(&self, acc: usize) -> usize { acc }
+/// # }
+/// use std::fs::File;
+/// use std::io::BufReader;
+///
+/// let reader = TreeReader::new(BufReader::new(File::open("huge.trees").unwrap())).unwrap();
+/// let num_trees = fold_stream(&Count, reader, 0).unwrap();
+/// ```
+pub fn fold_stream<F, I, E>(fold: &F, trees: I, num_threads: usize) -> Result<F::Output, E>
+where
+    F: TreeFold,
+    I: IntoIterator<Item = Result<Tree, E>>,
+{
+    let num_threads = resolve_num_threads(num_threads);
+    if num_threads == 1 {
+        let mut accumulator = fold.init();
+        for tree in trees {
+            fold.accumulate(&mut accumulator, &tree?);
+        }
+        return Ok(fold.finalize(accumulator));
+    }
+
+    let (sender, receiver) = sync_channel::<Vec<Tree>>(2 * num_threads);
+    // Only workers hold the receiver, so sending fails (instead of blocking) if all of them panicked
+    let receiver = Arc::new(Mutex::new(receiver));
+
+    let (accumulators, result) = thread::scope(|scope| {
+        let handles: Vec<_> = (0..num_threads)
+            .map(|_| {
+                let receiver = Arc::clone(&receiver);
+                scope.spawn(move || fold_batches(fold, &receiver))
+            })
+            .collect();
+        drop(receiver);
+
+        // Read trees in calling thread and distribute them in batches
+        let mut result = Ok(());
+        let mut batch = Vec::with_capacity(BATCH_SIZE);
+        for tree in trees {
+            match tree {
+                Ok(tree) => batch.push(tree),
+                Err(e) => {
+                    result = Err(e);
+                    break;
+                }
+            }
+            if batch.len() == BATCH_SIZE {
+                let full = std::mem::replace(&mut batch, Vec::with_capacity(BATCH_SIZE));
+                if sender.send(full).is_err() {
+                    break; // Workers panicked, reported when joining
+                }
+            }
+        }
+        if result.is_ok() && !batch.is_empty() {
+            let _ = sender.send(batch);
+        }
+        // Closing the channel lets workers finish
+        drop(sender);
+
+        let accumulators: Vec<F::Accumulator> = handles.into_iter()
+            .map(|handle| handle.join().expect("Fold worker thread panicked"))
+            .collect();
+        (accumulators, result)
+    });
+
+    result.map(|_| merge_and_finalize(fold, accumulators))
+}
+
+// ============================================================================
+// Helpers
+// ============================================================================
+/// Accumulates batches from the shared receiver until the channel is closed.
+fn fold_batches<F: TreeFold>(fold: &F, receiver: &Mutex<Receiver<Vec<Tree>>>) -> F::Accumulator {
+    let mut accumulator = fold.init();
+    loop {
+        // Release lock before accumulating, so other workers can receive meanwhile
+        let batch = receiver.lock().expect("Fold receiver poisoned").recv();
+        let Ok(batch) = batch else {
+            break;
+        };
+        for tree in &batch {
+            fold.accumulate(&mut accumulator, tree);
+        }
+    }
+    accumulator
+}
+
+fn merge_and_finalize<F: TreeFold>(fold: &F, accumulators: Vec<F::Accumulator>) -> F::Output {
+    let merged = accumulators.into_iter()
+        .reduce(|a, b| fold.merge(a, b))
+        .unwrap_or_else(|| fold.init());
+    fold.finalize(merged)
+}
+
+fn resolve_num_threads(num_threads: usize) -> usize {
+    if num_threads > 0 {
+        return num_threads;
+    }
+    thread::available_parallelism().map_or(1, NonZeroUsize::get)
+}
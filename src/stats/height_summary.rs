@@ -0,0 +1,190 @@
+//! Height summary module for summarizing the node heights of a tree sample onto a summary tree.
+//!
+//! - `HeightMethod`: How to set the heights of the summary tree (keep, mean or median)
+//! - `summarize_heights`: Collects the heights of each clade of a target tree (e.g. the MAP tree)
+//!   across a sample and stores their statistics (mean, median, 95% HPD, range) as vertex annotations
+//!
+//! Together with [annotate_support](crate::stats::clade_support::annotate_support), this gives
+//! the summary trees of TreeAnnotator, e.g. `(A[&height=0,...],B[&height=0,...])[&height=1.2,...]`.
+
+use crate::model::annotation::{Annotation, AnnotationValue};
+use crate::model::taxon_set::TaxonSet;
+use crate::model::tree::{Tree, TreeIndex};
+use crate::model::vertex::BranchLength;
+use crate::stats::summary::{hpd_interval, mean, median};
+use std::collections::HashMap;
+
+/// Probability mass of the reported HPD intervals
+const HPD_MASS: f64 = 0.95;
+
+// =#========================================================================#=
+// HEIGHT METHOD
+// =#========================================================================#=
+/// How [summarize_heights] sets the heights of the vertices of the target tree.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum HeightMethod {
+    /// Keep the heights (branch lengths) of the target tree
+    #[default]
+    Keep,
+    /// Set each vertex to the mean height of its clade in the sample
+    Mean,
+    /// Set each vertex to the median height of its clade in the sample
+    Median,
+}
+
+// =#========================================================================#=
+// SUMMARIZE HEIGHTS
+// =#========================================================================#=
+/// Summarizes the heights of the clades of the target tree across the sample
+/// and stores them as annotations of its vertices (see [Tree::annotation]).
+///
+/// For every vertex whose clade occurs in some sample tree, the heights of that clade
+/// (see [Tree::node_heights]) are annotated as entries `height_mean`, `height_median`,
+/// `height_95%_HPD={lower,upper}` and `height_range={min,max}`, keeping other entries of existing annotations.
+/// Afterwards, every vertex with a height gets the entry `height`.
+///
+/// With [HeightMethod::Mean] or [HeightMethod::Median], branch lengths of the target are set so that
+/// vertices have the respective height; vertices of clades not in the sample keep their height
+/// (if the target is ultrametric) or are placed at the highest of their children.
+/// Branches that would get negative lengths (a clade summarized as older than its parent) get length `0`.
+///
+/// # Arguments
+/// * `target` - Summary tree over the same leaf label map as the sample, e.g. its MAP tree
+/// * `sample` - Tree sample (e.g. after burnin); trees that are not ultrametric are skipped
+/// * `method` - How to set the heights of the target
+///
+/// # Panics
+/// Panics if the trees of the sample do not have the same number of leaves as the target.
+///
+/// # Example
+/// ```
+/// use nexus_parser::parse_newick_str;
+/// use nexus_parser::stats::height_summary::{summarize_heights, HeightMethod};
+///
+/// let (trees, _) = parse_newick_str("((A:1,B:1):1,C:2);\n((A:2,B:2):1,C:3);\n").unwrap();
+/// let mut target = trees[0].clone();
+/// summarize_heights(&mut target, &trees, HeightMethod::Mean);
+///
+/// let root = target.root().index();
+/// assert_eq!(target.root_height(), Some(2.5));
+/// assert_eq!(target.annotation(root).unwrap().raw(),
+///            "height_mean=2.5,height_median=2.5,height_95%_HPD={2,3},height_range={2,3},height=2.5");
+/// ```
+pub fn summarize_heights(target: &mut Tree, sample: &[Tree], method: HeightMethod) {
+    assert!(sample.iter().all(|tree| tree.num_leaves_init() == target.num_leaves_init()),
+            "Sample trees must have the same number of leaves as the target");
+    let clade_heights = collect_clade_heights(target, sample);
+
+    // Summary of each vertex; vertices with the same clade (e.g. unary vertices) share the heights
+    let summaries: Vec<Option<HeightStats>> = (0..target.num_vertices())
+        .map(|index| clade_heights.get(target.clade_taxa(index))
+            .and_then(|heights| HeightStats::new(heights)))
+        .collect();
+
+    if method != HeightMethod::Keep {
+        let heights = new_heights(target, &summaries, method);
+        set_heights(target, &heights);
+    }
+
+    let heights: Option<Vec<f64>> = target.node_heights().map(<[f64]>::to_vec);
+    for (index, summary) in summaries.into_iter().enumerate() {
+        let mut annotation = target.annotation(index).cloned().unwrap_or_else(|| Annotation::from_raw(String::new()));
+        if let Some(summary) = summary {
+            summary.annotate(&mut annotation);
+        }
+        if let Some(heights) = &heights {
+            annotation.set("height", AnnotationValue::Number(heights[index]));
+        }
+        if !annotation.raw().is_empty() {
+            target.set_annotation(index, Some(annotation));
+        }
+    }
+}
+
+// ============================================================================
+// Little Helpers
+// ============================================================================
+/// Statistics of the heights of a clade across a sample.
+struct HeightStats {
+    mean: f64,
+    median: f64,
+    hpd: (f64, f64),
+    range: (f64, f64),
+}
+
+impl HeightStats {
+    /// Computes the statistics, or returns `None` if there are no heights.
+    fn new(heights: &[f64]) -> Option<Self> {
+        Some(Self {
+            mean: mean(heights)?,
+            median: median(heights)?,
+            hpd: hpd_interval(heights, HPD_MASS)?,
+            range: (heights.iter().cloned().fold(f64::INFINITY, f64::min),
+                    heights.iter().cloned().fold(f64::NEG_INFINITY, f64::max)),
+        })
+    }
+
+    /// Sets the entries of these statistics in the annotation.
+    fn annotate(&self, annotation: &mut Annotation) {
+        let interval = |(lower, upper): (f64, f64)| AnnotationValue::Range(lower, upper);
+        annotation.set("height_mean", AnnotationValue::Number(self.mean));
+        annotation.set("height_median", AnnotationValue::Number(self.median));
+        annotation.set("height_95%_HPD", interval(self.hpd));
+        annotation.set("height_range", interval(self.range));
+    }
+}
+
+/// Returns the heights of each clade of the target across the (ultrametric) sample trees.
+fn collect_clade_heights(target: &Tree, sample: &[Tree]) -> HashMap<TaxonSet, Vec<f64>> {
+    let mut clade_heights: HashMap<TaxonSet, Vec<f64>> = target.cached_clades().iter()
+        .map(|clade| (clade.clone(), Vec::new()))
+        .collect();
+
+    for tree in sample {
+        let Some(heights) = tree.node_heights() else {
+            continue;
+        };
+
+        let tree_clades = tree.clades(tree.num_leaves_init());
+        let mut clades = tree_clades.iter().zip(heights).collect::<Vec<_>>();
+        // Count a clade once per tree, also if repeated by unary vertices
+        clades.sort_unstable_by_key(|&(clade, _)| clade);
+        clades.dedup_by(|(a, _), (b, _)| a == b);
+        for (clade, &height) in clades {
+            if let Some(heights) = clade_heights.get_mut(clade) {
+                heights.push(height);
+            }
+        }
+    }
+
+    clade_heights
+}
+
+/// Returns the new height of each vertex of the target by the method (mean or median),
+/// falling back to its current height or the highest of its children.
+fn new_heights(target: &Tree, summaries: &[Option<HeightStats>], method: HeightMethod) -> Vec<f64> {
+    let current = target.node_heights();
+    let mut heights = vec![0.0; target.num_vertices()];
+    for vertex in target.post_order_iter() {
+        let index = vertex.index();
+        heights[index] = match (&summaries[index], current) {
+            (Some(summary), _) if method == HeightMethod::Median => summary.median,
+            (Some(summary), _) => summary.mean,
+            (None, Some(current)) => current[index],
+            (None, None) => vertex.children().unwrap_or(&[]).iter()
+                .map(|&child| heights[child])
+                .fold(0.0, f64::max),
+        };
+    }
+    heights
+}
+
+/// Sets the branch lengths of the tree so that its vertices have the given heights (as far as non-negative).
+fn set_heights(tree: &mut Tree, heights: &[f64]) {
+    let lengths: Vec<(TreeIndex, f64)> = (0..tree.num_vertices())
+        .filter_map(|index| tree[index].parent_index().map(|parent| (index, (heights[parent] - heights[index]).max(0.0))))
+        .collect();
+    for (index, length) in lengths {
+        tree.vertex_mut(index).set_branch_length(Some(BranchLength::new(length)));
+    }
+}
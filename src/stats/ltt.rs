@@ -0,0 +1,155 @@
+//! Lineages-through-time (LTT) module for exploring diversification in time trees.
+//!
+//! - `Tree::ltt`: Number of lineages after each branching (and sampling) event of a tree
+//! - `sample_ltt`: Mean, median and 95% HPD interval of the number of lineages over a sample of trees
+//!   at evenly spaced times ([LttPoint])
+//!
+//! Times are measured before the youngest leaf (the present), so trees of a sample, e.g. from BEAST,
+//! are aligned at their youngest leaves. Leaves older than the present (serially sampled trees) end their lineage.
+
+use crate::model::tree::Tree;
+use crate::stats::summary::{hpd_interval, mean, median};
+
+// ============================================================================
+// Lineages Through Time (pub)
+// ============================================================================
+impl Tree {
+    /// Returns the lineages-through-time curve of this tree: for each event from the root to the present,
+    /// its time before the youngest leaf and the number of lineages after it.
+    ///
+    /// Events are branchings (internal vertices, including the root) and samplings (leaves older than the present);
+    /// simultaneous events form one point. The curve starts with the root and ends at time `0`
+    /// with the number of leaves at the present.
+    ///
+    /// # Returns
+    /// The points in order of decreasing time, or `None` if not all vertices (besides the root) have a branch length
+    ///
+    /// # Example
+    /// ```
+    /// use nexus_parser::parse_newick_str;
+    ///
+    /// let (trees, _) = parse_newick_str("(((A:1,B:1):1,C:2):2,(D:3,E:1):1);").unwrap();
+    /// // Root at 4 (2 lineages), then branchings at 3, 2 and 1, and E sampled at 2 (before D, A, B and C)
+    /// assert_eq!(trees[0].ltt().unwrap(), vec![(4.0, 2), (3.0, 3), (2.0, 3), (1.0, 4), (0.0, 4)]);
+    /// ```
+    pub fn ltt(&self) -> Option<Vec<(f64, usize)>> {
+        if !self.vertices_have_branch_lengths() {
+            return None;
+        }
+
+        let (times, height) = vertex_times(self);
+        let epsilon = present_tolerance(height);
+        let mut events: Vec<(f64, isize)> = (0..self.num_vertices())
+            .filter_map(|index| match self[index].children() {
+                Some(children) => Some((times[index], children.len() as isize - 1 + self[index].is_root() as isize)),
+                None => (times[index] > epsilon).then_some((times[index], -1)),
+            })
+            .collect();
+        events.sort_by(|(a, _), (b, _)| b.total_cmp(a));
+
+        let mut points: Vec<(f64, usize)> = Vec::with_capacity(events.len() + 1);
+        let mut num_lineages: isize = 0;
+        for (time, change) in events {
+            num_lineages += change;
+            match points.last_mut() {
+                Some((last_time, count)) if *last_time == time => *count = num_lineages as usize,
+                _ => points.push((time, num_lineages as usize)),
+            }
+        }
+        if points.last().is_some_and(|&(time, _)| time > 0.0) {
+            points.push((0.0, num_lineages as usize));
+        }
+        Some(points)
+    }
+
+    /// Returns the number of branches of this tree crossing the given time before the youngest leaf,
+    /// given the times of its vertices (see [vertex_times]).
+    fn lineages_at(&self, times: &[f64], time: f64, epsilon: f64) -> usize {
+        (0..self.num_vertices())
+            .filter(|&index| self[index].parent_index().is_some_and(|parent| times[index] <= time + epsilon && time < times[parent]))
+            .count()
+    }
+}
+
+// =#========================================================================#=
+// SAMPLE LTT
+// =#========================================================================#=
+/// Number of lineages at a time over the trees of a sample, see [sample_ltt].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LttPoint {
+    /// Time before the youngest leaf (of each tree)
+    pub time: f64,
+    /// Mean number of lineages
+    pub mean: f64,
+    /// Median number of lineages
+    pub median: f64,
+    /// 95% HPD interval of the number of lineages
+    pub hpd: (f64, f64),
+}
+
+/// Returns the distribution of the number of lineages over the trees of a sample at `num_points` evenly spaced times,
+/// from the present (`0`) up to (excluding) the oldest root, e.g. to plot the LTT curve of a BEAST sample with its credible band.
+///
+/// # Returns
+/// The points in order of increasing time, or `None` if `trees` is empty
+/// or not all vertices (besides the roots) have a branch length
+///
+/// # Example
+/// ```
+/// use nexus_parser::parse_newick_str;
+/// use nexus_parser::stats::ltt::sample_ltt;
+///
+/// let (trees, _) = parse_newick_str("((A:1,B:1):1,C:2);\n((A:1,C:1):3,B:4);\n").unwrap();
+/// let ltt = sample_ltt(&trees, 4).unwrap();
+///
+/// // Times 0, 1, 2 and 3: both trees have three lineages at the present, only the second one reaches time 3
+/// assert_eq!(ltt.iter().map(|point| point.time).collect::<Vec<_>>(), vec![0.0, 1.0, 2.0, 3.0]);
+/// assert_eq!(ltt[0].mean, 3.0);
+/// assert_eq!(ltt[1].hpd, (2.0, 2.0));
+/// assert_eq!(ltt[3].mean, 1.0);
+/// ```
+pub fn sample_ltt(trees: &[Tree], num_points: usize) -> Option<Vec<LttPoint>> {
+    if trees.is_empty() || !trees.iter().all(Tree::vertices_have_branch_lengths) {
+        return None;
+    }
+
+    let times: Vec<(Vec<f64>, f64)> = trees.iter().map(vertex_times).collect();
+    let max_height = times.iter().map(|&(_, height)| height).fold(0.0, f64::max);
+    let epsilon = present_tolerance(max_height);
+    let points = (0..num_points)
+        .map(|i| {
+            let time = max_height * i as f64 / num_points as f64;
+            let counts: Vec<f64> = trees.iter().zip(&times)
+                .map(|(tree, (vertex_times, _))| tree.lineages_at(vertex_times, time, epsilon) as f64)
+                .collect();
+            LttPoint {
+                time,
+                mean: mean(&counts).expect("At least one tree"),
+                median: median(&counts).expect("At least one tree"),
+                hpd: hpd_interval(&counts, 0.95).expect("At least one tree"),
+            }
+        })
+        .collect();
+    Some(points)
+}
+
+// ============================================================================
+// Little Helpers
+// ============================================================================
+/// Returns the time of each vertex before the youngest leaf (farthest from the root) and the tree height;
+/// branches without length count as length `0`.
+pub(crate) fn vertex_times(tree: &Tree) -> (Vec<f64>, f64) {
+    let mut depths = vec![0.0; tree.num_vertices()];
+    for vertex in tree.pre_order_iter() {
+        if let Some(parent) = vertex.parent_index() {
+            depths[vertex.index()] = depths[parent] + vertex.branch_length().map_or(0.0, |length| *length);
+        }
+    }
+    let height = depths.iter().cloned().fold(0.0, f64::max);
+    (depths.iter().map(|depth| height - depth).collect(), height)
+}
+
+/// Returns the tolerance of times at the present, since leaves of ultrametric trees are there only up to rounding.
+fn present_tolerance(height: f64) -> f64 {
+    1e-9 * height
+}
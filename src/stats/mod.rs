@@ -0,0 +1,24 @@
+/// Summary statistics of samples (mean, median, HPD, ESS, ...)
+pub mod summary;
+/// Branch length statistics of trees, tree lengths and root heights of samples
+pub mod tree_summary;
+/// Posterior frequencies of clades across tree samples, stamped onto summary trees
+pub mod clade_support;
+/// Node heights of tree samples (mean, median, HPD) summarized onto summary trees
+pub mod height_summary;
+/// Monophyly of constraint clades (e.g. taxonomic groups) across tree samples
+pub mod monophyly;
+/// Conditional clade distributions of tree samples, scoring the probability of topologies
+pub mod ccd;
+/// Convergence diagnostics of tree samples (topological ESS, ASDSF, split frequencies of runs)
+pub mod convergence;
+/// Tree shape statistics (gamma, Colless, Sackin, cherries) of trees and samples
+pub mod tree_shape;
+/// Lineages-through-time curves of trees and samples
+pub mod ltt;
+/// Sampling dates of leaves and root-to-tip regression (temporal signal)
+pub mod tip_dating;
+/// Custom summaries folded over (streamed) tree samples, in parallel
+pub mod fold;
+/// Self-contained HTML quality-control reports of tree samples
+pub mod report;
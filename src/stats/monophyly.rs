@@ -0,0 +1,136 @@
+//! Monophyly module for checking constraint clades across a tree sample.
+//!
+//! - `MonophylyResult`: How often a named constraint (e.g. a family) forms a clade in a sample
+//! - `monophyly_report`: Checks a list of constraints against all trees in one pass
+//! - `taxonomy_monophyly_report`: Same for all groups of a rank of a [Taxonomy]
+
+use crate::model::leaf_label_map::LeafLabelMap;
+use crate::model::taxon_set::TaxonSet;
+use crate::model::taxonomy::Taxonomy;
+use crate::model::tree::Tree;
+use std::collections::HashSet;
+
+// =#========================================================================#=
+// MONOPHYLY RESULT
+// =#========================================================================#=
+/// Monophyly of a named constraint across a sample of trees.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MonophylyResult {
+    /// Name of constraint, e.g. the taxonomic group
+    name: String,
+    /// Taxa of constraint
+    taxa: TaxonSet,
+    /// Number of trees in which the taxa form a clade
+    num_monophyletic: usize,
+    /// Number of trees checked
+    num_trees: usize,
+}
+
+impl MonophylyResult {
+    /// Returns the name of the constraint.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the taxa of the constraint.
+    pub fn taxa(&self) -> &TaxonSet {
+        &self.taxa
+    }
+
+    /// Returns the number of trees in which the constraint is monophyletic.
+    pub fn num_monophyletic(&self) -> usize {
+        self.num_monophyletic
+    }
+
+    /// Returns the number of trees checked.
+    pub fn num_trees(&self) -> usize {
+        self.num_trees
+    }
+
+    /// Returns the fraction of trees in which the constraint is monophyletic
+    /// (i.e. its posterior probability for a posterior sample), or `0.0` if no trees were checked.
+    pub fn fraction(&self) -> f64 {
+        if self.num_trees == 0 {
+            return 0.0;
+        }
+        self.num_monophyletic as f64 / self.num_trees as f64
+    }
+}
+
+// =#========================================================================#=
+// REPORTS
+// =#========================================================================#=
+/// Checks for each named constraint in how many of the trees its taxa form a clade.
+///
+/// Monophyly is checked in the rooted sense, i.e. a vertex must have exactly the constraint's taxa below it
+/// (see [Tree::is_monophyletic]). The clades of each tree are computed only once for all constraints.
+///
+/// # Arguments
+/// * `trees` - Tree sample, all over the same leaf label map
+/// * `constraints` - Named taxon sets, all ranging over the number of labels of that map
+///
+/// # Returns
+/// One [MonophylyResult] per constraint, in the given order.
+///
+/// # Example
+/// ```
+/// use nexus_parser::model::taxon_set::TaxonSet;
+/// use nexus_parser::model::tree::Tree;
+/// use nexus_parser::stats::monophyly::monophyly_report;
+///
+/// let mut tree = Tree::new(3);
+/// let a = tree.add_leaf(None, 0);
+/// let b = tree.add_leaf(None, 1);
+/// let c = tree.add_leaf(None, 2);
+/// let ab = tree.add_internal_vertex(vec![a, b], None);
+/// tree.add_root(vec![ab, c]);
+///
+/// let constraints = vec![
+///     ("AB".to_string(), TaxonSet::from_indices(3, [0, 1])),
+///     ("BC".to_string(), TaxonSet::from_indices(3, [1, 2])),
+/// ];
+/// let report = monophyly_report(&[tree], &constraints);
+/// assert_eq!(report[0].fraction(), 1.0);
+/// assert_eq!(report[1].fraction(), 0.0);
+/// ```
+pub fn monophyly_report(trees: &[Tree], constraints: &[(String, TaxonSet)]) -> Vec<MonophylyResult> {
+    let mut results: Vec<MonophylyResult> = constraints.iter()
+        .map(|(name, taxa)| MonophylyResult {
+            name: name.clone(),
+            taxa: taxa.clone(),
+            num_monophyletic: 0,
+            num_trees: trees.len(),
+        })
+        .collect();
+
+    let Some((_, first_taxa)) = constraints.first() else {
+        return results;
+    };
+    let num_taxa = first_taxa.num_taxa();
+
+    for tree in trees {
+        let clades: HashSet<TaxonSet> = tree.clades(num_taxa).into_iter().collect();
+        for result in results.iter_mut() {
+            if !result.taxa.is_empty() && clades.contains(&result.taxa) {
+                result.num_monophyletic += 1;
+            }
+        }
+    }
+
+    results
+}
+
+/// Checks for each group of the given rank of the taxonomy in how many of the trees it is monophyletic,
+/// e.g. answering "what fraction of the posterior recovers family X as monophyletic".
+///
+/// Groups are derived with [Taxonomy::constraints], so only groups with at least two taxa
+/// in the leaf label map are reported, sorted by name (see [monophyly_report] for details).
+///
+/// # Arguments
+/// * `trees` - Tree sample over the given leaf label map
+/// * `leaf_label_map` - Shared leaf labels of the trees
+/// * `taxonomy` - Taxonomy assigning leaf labels to groups
+/// * `rank_index` - Index of rank in taxonomy (see [Taxonomy::rank_index])
+pub fn taxonomy_monophyly_report(trees: &[Tree], leaf_label_map: &LeafLabelMap, taxonomy: &Taxonomy, rank_index: usize) -> Vec<MonophylyResult> {
+    monophyly_report(trees, &taxonomy.constraints(rank_index, leaf_label_map))
+}
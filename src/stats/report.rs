@@ -0,0 +1,263 @@
+//! Report module for sharing a quick quality-control summary of a posterior tree sample.
+//!
+//! - `SampleReport`: Summary of a tree sample, i.e. trace diagnostics (ESS), clade table, majority-rule consensus tree,
+//!   maximum clade credibility (MCC) tree and lineages-through-time (LTT) plot
+//! - `SampleReport::write_html`: Self-contained HTML page of the summary, e.g. to send to collaborators
+//! - `TraceSummary`: Mean, HPD interval and ESS of a per-tree statistic (e.g. tree length), see `stats::summary`
+
+use crate::algorithms::consensus::majority_rule;
+use crate::io::writer::newick::NewickStyle;
+use crate::io::writer::render::{escape_xml, RenderOptions};
+use crate::model::clade_naming::CladeNamer;
+use crate::model::leaf_label_map::LeafLabelMap;
+use crate::model::taxon_set::TaxonSet;
+use crate::model::tree::Tree;
+use crate::stats::clade_support::{annotate_support, clade_support, mcc_tree};
+use crate::stats::ltt::{sample_ltt, vertex_times};
+pub use crate::stats::summary::{TraceSummary, ESS_THRESHOLD};
+use std::io;
+use std::io::Write;
+
+/// Maximum number of rows of the clade table (most frequent clades)
+const MAX_CLADE_ROWS: usize = 50;
+
+/// Number of time points of the lineages-through-time data
+const NUM_LTT_POINTS: usize = 20;
+
+/// Width and height of the lineages-through-time plot area (in pixels)
+const LTT_PLOT_SIZE: (f64, f64) = (400.0, 200.0);
+
+/// Margin around the lineages-through-time plot area, for axis labels (in pixels)
+const LTT_PLOT_MARGIN: f64 = 40.0;
+
+// =#========================================================================#=
+// SAMPLE REPORT
+// =#========================================================================#=
+/// Quality-control summary of a (posterior) tree sample.
+///
+/// Traces and lineages-through-time data require branch lengths on all trees and are empty otherwise.
+///
+/// # Example
+/// ```
+/// use nexus_parser::parse_newick_str;
+/// use nexus_parser::stats::report::SampleReport;
+///
+/// let (trees, labels) = parse_newick_str("((A:1,B:1):1,C:2);\n((A:1,C:1):2,B:3);\n((A:2,B:2):1,C:3);").unwrap();
+/// let report = SampleReport::new(&trees, &labels);
+///
+/// assert_eq!(report.num_trees(), 3);
+/// assert_eq!(report.clades()[0].2, 2.0 / 3.0);
+///
+/// let mut html = Vec::new();
+/// report.write_html(&mut html).unwrap();
+/// assert!(String::from_utf8(html).unwrap().contains("<svg"));
+/// ```
+#[derive(Debug, Clone)]
+pub struct SampleReport {
+    num_trees: usize,
+    num_taxa: usize,
+    traces: Vec<TraceSummary>,
+    /// Name, taxa and frequency of the most frequent clades
+    clades: Vec<(String, TaxonSet, f64)>,
+    consensus: Tree,
+    consensus_newick: String,
+    consensus_svg: String,
+    /// MCC tree of the sample with clade frequencies as node labels
+    mcc: Tree,
+    mcc_svg: String,
+    /// Time before the youngest leaf and mean number of lineages at that time
+    lineages_through_time: Vec<(f64, f64)>,
+}
+
+impl SampleReport {
+    /// Summarizes the given tree sample (e.g. after burnin).
+    ///
+    /// # Panics
+    /// Panics if `trees` is empty or the trees do not have the same number of leaves.
+    pub fn new(trees: &[Tree], leaf_label_map: &LeafLabelMap) -> Self {
+        assert!(!trees.is_empty(), "Report requires at least one tree");
+        let num_trees = trees.len();
+
+        // Traces and LTT need branch lengths
+        let mut traces = Vec::new();
+        let mut lineages_through_time = Vec::new();
+        if trees.iter().all(Tree::vertices_have_branch_lengths) {
+            let lengths: Vec<f64> = trees.iter().map(Tree::total_branch_length).collect();
+            let heights: Vec<f64> = trees.iter().map(|tree| vertex_times(tree).1).collect();
+            traces.push(TraceSummary::new("Tree length", &lengths));
+            traces.push(TraceSummary::new("Tree height", &heights));
+            lineages_through_time = sample_ltt(trees, NUM_LTT_POINTS)
+                .expect("Trees have branch lengths")
+                .into_iter()
+                .map(|point| (point.time, point.mean))
+                .collect();
+        }
+
+        // Most frequent clades first (deterministic order for equal counts)
+        let namer = CladeNamer::new(leaf_label_map);
+        let frequencies = clade_support(trees);
+        let clades = frequencies.sorted().into_iter()
+            .take(MAX_CLADE_ROWS)
+            .map(|(clade, frequency)| (namer.name(&clade), clade, frequency))
+            .collect();
+
+        let consensus = majority_rule(trees, 0.5);
+        let consensus_newick = consensus.to_newick(&NewickStyle::Label, Some(leaf_label_map));
+        let consensus_svg = consensus.to_svg(leaf_label_map, &RenderOptions { cladogram: true, ..RenderOptions::default() });
+
+        let mut mcc = trees[mcc_tree(trees, &frequencies).expect("Sample is not empty")].clone();
+        annotate_support(&mut mcc, &frequencies);
+        let mcc_options = RenderOptions { cladogram: !mcc.vertices_have_branch_lengths(), ..RenderOptions::default() };
+        let mcc_svg = mcc.to_svg(leaf_label_map, &mcc_options);
+
+        SampleReport {
+            num_trees,
+            num_taxa: trees[0].num_leaves_init(),
+            traces,
+            clades,
+            consensus,
+            consensus_newick,
+            consensus_svg,
+            mcc,
+            mcc_svg,
+            lineages_through_time,
+        }
+    }
+
+    /// Returns the number of trees summarized.
+    pub fn num_trees(&self) -> usize {
+        self.num_trees
+    }
+
+    /// Returns the number of taxa of the trees.
+    pub fn num_taxa(&self) -> usize {
+        self.num_taxa
+    }
+
+    /// Returns the summaries of tree length and height (empty without branch lengths).
+    pub fn traces(&self) -> &[TraceSummary] {
+        &self.traces
+    }
+
+    /// Returns whether all traces reach [ESS_THRESHOLD] (`false` without traces).
+    pub fn is_converged(&self) -> bool {
+        !self.traces.is_empty() && self.traces.iter().all(TraceSummary::is_converged)
+    }
+
+    /// Returns name, taxa and frequency of the most frequent non-trivial clades, most frequent first.
+    pub fn clades(&self) -> &[(String, TaxonSet, f64)] {
+        &self.clades
+    }
+
+    /// Returns the majority-rule consensus tree, see [majority_rule].
+    pub fn consensus(&self) -> &Tree {
+        &self.consensus
+    }
+
+    /// Returns the maximum clade credibility tree of the sample (see [mcc_tree]),
+    /// with the frequencies of its clades as node labels (see [annotate_support]).
+    pub fn mcc(&self) -> &Tree {
+        &self.mcc
+    }
+
+    /// Returns the mean number of lineages at evenly spaced times before the youngest leaf,
+    /// from the present up to the oldest root (empty without branch lengths).
+    pub fn lineages_through_time(&self) -> &[(f64, f64)] {
+        &self.lineages_through_time
+    }
+
+    // ============================================================================
+    // HTML (pub)
+    // ============================================================================
+    /// Writes the report as self-contained HTML page (inline CSS and SVG, no external resources).
+    ///
+    /// # Errors
+    /// Returns an error if writing fails.
+    pub fn write_html<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        writeln!(writer, "<!DOCTYPE html>")?;
+        writeln!(writer, "<html><head><meta charset=\"utf-8\"><title>Tree sample report</title>")?;
+        writeln!(writer, "<style>body{{font-family:sans-serif;margin:2em;}}table{{border-collapse:collapse;}}\
+            td,th{{border:1px solid #ccc;padding:2px 8px;text-align:right;}}td:first-child{{text-align:left;}}\
+            .low{{color:#b00;}}.ok{{color:#080;}}code{{word-break:break-all;}}</style>")?;
+        writeln!(writer, "</head><body>")?;
+
+        writeln!(writer, "<h1>Tree sample report</h1>")?;
+        writeln!(writer, "<p>{} trees on {} taxa</p>", self.num_trees, self.num_taxa)?;
+
+        writeln!(writer, "<h2>Convergence</h2>")?;
+        if self.traces.is_empty() {
+            writeln!(writer, "<p>No traces, since not all trees have branch lengths.</p>")?;
+        } else {
+            writeln!(writer, "<table><tr><th>Statistic</th><th>Mean</th><th>95% HPD</th><th>ESS</th></tr>")?;
+            for trace in &self.traces {
+                let hpd = trace.hpd().map_or("-".to_string(), |(lower, upper)| format!("[{:.4}, {:.4}]", lower, upper));
+                let ess = trace.ess().map_or("-".to_string(), |ess| format!("{:.1}", ess));
+                let class = if trace.is_converged() { "ok" } else { "low" };
+                writeln!(writer, "<tr><td>{}</td><td>{:.4}</td><td>{}</td><td class=\"{}\">{}</td></tr>",
+                         escape_xml(trace.name()), trace.mean(), hpd, class, ess)?;
+            }
+            writeln!(writer, "</table>")?;
+            writeln!(writer, "<p>ESS below {} is marked as too low.</p>", ESS_THRESHOLD)?;
+        }
+
+        writeln!(writer, "<h2>Majority-rule consensus tree</h2>")?;
+        writeln!(writer, "{}", self.consensus_svg)?;
+        writeln!(writer, "<p><code>{}</code></p>", escape_xml(&self.consensus_newick))?;
+
+        writeln!(writer, "<h2>Maximum clade credibility tree</h2>")?;
+        writeln!(writer, "{}", self.mcc_svg)?;
+        writeln!(writer, "<p>Clade frequencies are shown at internal vertices.</p>")?;
+
+        writeln!(writer, "<h2>Clades</h2>")?;
+        if self.clades.is_empty() {
+            writeln!(writer, "<p>No non-trivial clades.</p>")?;
+        } else {
+            writeln!(writer, "<table><tr><th>Clade</th><th>Taxa</th><th>Frequency</th></tr>")?;
+            for (name, clade, frequency) in &self.clades {
+                writeln!(writer, "<tr><td>{}</td><td>{}</td><td>{:.3}</td></tr>", escape_xml(name), clade.len(), frequency)?;
+            }
+            writeln!(writer, "</table>")?;
+        }
+
+        writeln!(writer, "<h2>Lineages through time</h2>")?;
+        if self.lineages_through_time.is_empty() {
+            writeln!(writer, "<p>No data, since not all trees have branch lengths.</p>")?;
+        } else {
+            writeln!(writer, "{}", ltt_svg(&self.lineages_through_time))?;
+            writeln!(writer, "<table><tr><th>Time before present</th><th>Mean lineages</th></tr>")?;
+            for (time, lineages) in &self.lineages_through_time {
+                writeln!(writer, "<tr><td>{:.4}</td><td>{:.2}</td></tr>", time, lineages)?;
+            }
+            writeln!(writer, "</table>")?;
+        }
+
+        writeln!(writer, "</body></html>")
+    }
+}
+
+// ============================================================================
+// Little Helpers
+// ============================================================================
+/// Draws the mean number of lineages through time as line plot, from the oldest root (left) to the present (right).
+fn ltt_svg(points: &[(f64, f64)]) -> String {
+    let (width, height) = LTT_PLOT_SIZE;
+    let max_time = points.iter().map(|&(time, _)| time).fold(0.0, f64::max);
+    let max_lineages = points.iter().map(|&(_, lineages)| lineages).fold(1.0, f64::max);
+    let x = |time: f64| LTT_PLOT_MARGIN + if max_time > 0.0 { (1.0 - time / max_time) * width } else { width };
+    let y = |lineages: f64| LTT_PLOT_MARGIN + (1.0 - lineages / max_lineages) * height;
+
+    let line: Vec<String> = points.iter().map(|&(time, lineages)| format!("{:.1},{:.1}", x(time), y(lineages))).collect();
+    let (left, right, top, bottom) = (x(max_time), x(0.0), y(max_lineages), y(0.0));
+
+    let mut svg = format!("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{:.0}\" height=\"{:.0}\" font-family=\"sans-serif\" font-size=\"11\">\n",
+                          width + 2.0 * LTT_PLOT_MARGIN, height + 2.0 * LTT_PLOT_MARGIN);
+    // Axes, then curve, then axis labels (oldest time and present, maximal and no lineages)
+    svg.push_str(&format!("<path d=\"M{:.1},{:.1}V{:.1}H{:.1}\" fill=\"none\" stroke=\"black\"/>\n", left, top, bottom, right));
+    svg.push_str(&format!("<polyline points=\"{}\" fill=\"none\" stroke=\"#36c\" stroke-width=\"2\"/>\n", line.join(" ")));
+    svg.push_str(&format!("<text x=\"{:.1}\" y=\"{:.1}\" text-anchor=\"middle\">{:.3}</text>\n", left, bottom + 16.0, max_time));
+    svg.push_str(&format!("<text x=\"{:.1}\" y=\"{:.1}\" text-anchor=\"middle\">0</text>\n", right, bottom + 16.0));
+    svg.push_str(&format!("<text x=\"{:.1}\" y=\"{:.1}\" text-anchor=\"end\">{:.0}</text>\n", left - 6.0, top, max_lineages));
+    svg.push_str(&format!("<text x=\"{:.1}\" y=\"{:.1}\" text-anchor=\"end\">0</text>\n", left - 6.0, bottom));
+    svg.push_str("</svg>\n");
+    svg
+}
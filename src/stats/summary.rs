@@ -0,0 +1,268 @@
+//! Summary statistics module for samples of real values.
+//!
+//! Provides the basic statistics used when summarizing posterior samples,
+//! e.g. node heights, parameter traces or clade lengths:
+//! - `mean`, `median`, `variance` and `std_dev`
+//! - `hpd_interval`: Highest posterior density interval for arbitrary mass
+//! - `effective_sample_size`: ESS of an autocorrelated series (e.g. MCMC trace)
+//! - `TraceSummary`: Mean, 95% HPD interval and ESS of a trace, e.g. a parameter or per-tree statistic
+//!
+//! All functions return `None` for samples too small to compute the statistic.
+
+/// Conventional minimum ESS of a trace to consider it converged
+pub const ESS_THRESHOLD: f64 = 200.0;
+
+// =#========================================================================#=
+// BASIC STATISTICS
+// =#========================================================================#=
+/// Returns the arithmetic mean of the given values, or `None` if empty.
+pub fn mean(values: &[f64]) -> Option<f64> {
+    if values.is_empty() {
+        return None;
+    }
+    Some(values.iter().sum::<f64>() / values.len() as f64)
+}
+
+/// Returns the median of the given values, or `None` if empty.
+///
+/// For an even number of values, the mean of the two central values is returned.
+pub fn median(values: &[f64]) -> Option<f64> {
+    if values.is_empty() {
+        return None;
+    }
+    let sorted = sorted_copy(values);
+    let n = sorted.len();
+    if n % 2 == 1 {
+        Some(sorted[n / 2])
+    } else {
+        Some((sorted[n / 2 - 1] + sorted[n / 2]) / 2.0)
+    }
+}
+
+/// Returns the (unbiased) sample variance of the given values,
+/// or `None` if there are fewer than two values.
+pub fn variance(values: &[f64]) -> Option<f64> {
+    if values.len() < 2 {
+        return None;
+    }
+    let mean = mean(values)?;
+    let sum_sq: f64 = values.iter().map(|x| (x - mean) * (x - mean)).sum();
+    Some(sum_sq / (values.len() - 1) as f64)
+}
+
+/// Returns the (unbiased) sample standard deviation of the given values,
+/// or `None` if there are fewer than two values.
+pub fn std_dev(values: &[f64]) -> Option<f64> {
+    variance(values).map(f64::sqrt)
+}
+
+// =#========================================================================#=
+// HPD INTERVAL
+// =#========================================================================#=
+/// Returns the highest posterior density (HPD) interval containing `mass` of the values.
+///
+/// The HPD interval is the shortest interval containing `ceil(mass * n)` of the `n` sorted values,
+/// e.g. `mass = 0.95` gives the usual 95% HPD interval as reported by Tracer or TreeAnnotator.
+/// Note that this differs from the central (equal-tailed) credible interval for skewed samples.
+///
+/// # Arguments
+/// * `values` - Sample values, need not be sorted
+/// * `mass` - Fraction of values the interval should contain, in `(0, 1]`
+///
+/// # Returns
+/// `Some((lower, upper))` or `None` if `values` is empty
+///
+/// # Panics
+/// Panics if `mass` is not in `(0, 1]`.
+pub fn hpd_interval(values: &[f64], mass: f64) -> Option<(f64, f64)> {
+    assert!(mass > 0.0 && mass <= 1.0, "HPD mass must be in (0, 1], got {}", mass);
+    if values.is_empty() {
+        return None;
+    }
+
+    let sorted = sorted_copy(values);
+    let n = sorted.len();
+    let num_in_interval = ((mass * n as f64).ceil() as usize).clamp(1, n);
+
+    // Slide window of `num_in_interval` values over sorted values and keep narrowest
+    let mut best = (sorted[0], sorted[num_in_interval - 1]);
+    for start in 1..=(n - num_in_interval) {
+        let lower = sorted[start];
+        let upper = sorted[start + num_in_interval - 1];
+        if upper - lower < best.1 - best.0 {
+            best = (lower, upper);
+        }
+    }
+
+    Some(best)
+}
+
+// =#========================================================================#=
+// EFFECTIVE SAMPLE SIZE
+// =#========================================================================#=
+/// Returns the effective sample size (ESS) of an autocorrelated series, e.g. an MCMC trace.
+///
+/// Uses `ESS = n / τ` with the integrated autocorrelation time `τ = -1 + 2 Σ Γ(m)`,
+/// summing the pairs of autocorrelations `Γ(m) = ρ(2m) + ρ(2m + 1)` from `m = 0` (Geyer's initial positive sequence),
+/// up to the first pair that is no longer positive. Autocorrelations are computed by FFT in `O(n log n)`.
+/// The result is capped at `n`.
+///
+/// # Example
+/// ```
+/// use nexus_parser::stats::summary::effective_sample_size;
+///
+/// // Values come in pairs, so neighbors are correlated
+/// let values = [0.0, 0.0, 1.0, 1.0, 0.0, 0.0, 1.0, 1.0];
+/// assert!(effective_sample_size(&values).unwrap() < 8.0);
+/// assert_eq!(effective_sample_size(&[0.0, 1.0, 0.0, 1.0]), Some(4.0));
+/// ```
+///
+/// # Returns
+/// The ESS, or `None` if there are fewer than two values;
+/// a constant series has an ESS of `n`.
+pub fn effective_sample_size(values: &[f64]) -> Option<f64> {
+    let n = values.len();
+    if n < 2 {
+        return None;
+    }
+
+    let mean = mean(values)?;
+    let centered: Vec<f64> = values.iter().map(|x| x - mean).collect();
+    let autocovariance = autocovariances(&centered);
+    let variance = autocovariance[0];
+    if variance <= 0.0 {
+        return Some(n as f64);
+    }
+
+    // Sum pairs of autocorrelations (Geyer's initial positive sequence)
+    let mut sum_pairs = 0.0;
+    for pair in autocovariance.chunks_exact(2) {
+        let pair = (pair[0] + pair[1]) / variance;
+        if pair <= 0.0 {
+            break;
+        }
+        sum_pairs += pair;
+    }
+
+    // Strongly negatively autocorrelated (e.g. alternating) series get no reduction
+    let autocorrelation_time = -1.0 + 2.0 * sum_pairs;
+    if autocorrelation_time <= 0.0 {
+        return Some(n as f64);
+    }
+    Some((n as f64 / autocorrelation_time).min(n as f64))
+}
+
+// =#========================================================================#=
+// TRACE SUMMARY
+// =#========================================================================#=
+/// Summary of a statistic computed per tree (in sample order, i.e. as trace).
+#[derive(Debug, Clone, PartialEq)]
+pub struct TraceSummary {
+    /// Name of statistic, e.g. `Tree length`
+    name: String,
+    /// Mean over the sample
+    mean: f64,
+    /// 95% HPD interval
+    hpd: Option<(f64, f64)>,
+    /// Effective sample size
+    ess: Option<f64>,
+}
+
+impl TraceSummary {
+    /// Summarizes the values of a statistic, given in sample order.
+    pub fn new(name: &str, values: &[f64]) -> Self {
+        TraceSummary {
+            name: name.to_string(),
+            mean: mean(values).unwrap_or(f64::NAN),
+            hpd: hpd_interval(values, 0.95),
+            ess: effective_sample_size(values),
+        }
+    }
+
+    /// Returns the name of the statistic.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the mean of the statistic.
+    pub fn mean(&self) -> f64 {
+        self.mean
+    }
+
+    /// Returns the 95% HPD interval of the statistic, or `None` if the sample is too small.
+    pub fn hpd(&self) -> Option<(f64, f64)> {
+        self.hpd
+    }
+
+    /// Returns the effective sample size of the trace, or `None` if the sample is too small.
+    pub fn ess(&self) -> Option<f64> {
+        self.ess
+    }
+
+    /// Returns whether the ESS reaches [ESS_THRESHOLD].
+    pub fn is_converged(&self) -> bool {
+        self.ess.is_some_and(|ess| ess >= ESS_THRESHOLD)
+    }
+}
+
+// ============================================================================
+// Helpers (private)
+// ============================================================================
+/// Returns the autocovariances of centered values at lags `0..n` (normalized by `n`),
+/// as inverse FFT of the power spectrum of the values padded with zeros.
+fn autocovariances(centered: &[f64]) -> Vec<f64> {
+    let n = centered.len();
+    let size = (2 * n).next_power_of_two();
+    let mut spectrum: Vec<(f64, f64)> = centered.iter().map(|&x| (x, 0.0)).collect();
+    spectrum.resize(size, (0.0, 0.0));
+
+    fft(&mut spectrum, false);
+    for value in &mut spectrum {
+        *value = (value.0 * value.0 + value.1 * value.1, 0.0);
+    }
+    fft(&mut spectrum, true);
+    spectrum[..n].iter().map(|(re, _)| re / (size * n) as f64).collect()
+}
+
+/// In-place iterative radix-2 FFT of complex values `(re, im)`, whose number must be a power of two;
+/// the inverse transform is not scaled by `1 / len`.
+fn fft(values: &mut [(f64, f64)], inverse: bool) {
+    let len = values.len();
+    let mut j = 0;
+    for i in 1..len {
+        // Bit-reversal permutation
+        let mut bit = len >> 1;
+        while j & bit != 0 {
+            j ^= bit;
+            bit >>= 1;
+        }
+        j |= bit;
+        if i < j {
+            values.swap(i, j);
+        }
+    }
+
+    let sign = if inverse { 1.0 } else { -1.0 };
+    let mut size = 2;
+    while size <= len {
+        let angle = sign * 2.0 * std::f64::consts::PI / size as f64;
+        for start in (0..len).step_by(size) {
+            for k in 0..size / 2 {
+                let (sin, cos) = (angle * k as f64).sin_cos();
+                let (re, im) = values[start + k + size / 2];
+                let twiddled = (re * cos - im * sin, re * sin + im * cos);
+                let (u_re, u_im) = values[start + k];
+                values[start + k] = (u_re + twiddled.0, u_im + twiddled.1);
+                values[start + k + size / 2] = (u_re - twiddled.0, u_im - twiddled.1);
+            }
+        }
+        size <<= 1;
+    }
+}
+
+/// Returns a sorted copy of the values (NaN values sorted last).
+fn sorted_copy(values: &[f64]) -> Vec<f64> {
+    let mut sorted = values.to_vec();
+    sorted.sort_by(|a, b| a.total_cmp(b));
+    sorted
+}
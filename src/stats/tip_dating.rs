@@ -0,0 +1,280 @@
+//! Tip dating module for checking the temporal signal of trees with dated leaves (e.g. virus samples).
+//!
+//! - `TipDates`: Sampling date of each taxon by [LabelIndex], e.g. parsed from labels like `virus|2021-03-04`
+//! - `parse_date`: Decimal year of a date (`2021-03-04`, `2021-03`, `2021` or `2021.17`)
+//! - `root_to_tip_distances`: Distance of every leaf to the root
+//! - `RootToTipRegression`: Linear regression of root-to-tip distances on sampling dates
+//!   (slope as clock rate, R² and the inferred date of the root), as done by TempEst
+//!
+//! Dates are decimal years, with days at their midpoint (e.g. `2021-01-01` is `2021.00137`).
+
+use crate::model::leaf_label_map::LeafLabelMap;
+use crate::model::tree::{LabelIndex, Tree};
+
+// =#========================================================================#=
+// TIP DATES
+// =#========================================================================#=
+/// Sampling dates (decimal years) of the taxa of a [LeafLabelMap], indexed by [LabelIndex].
+///
+/// # Example
+/// ```
+/// use nexus_parser::parse_newick_str;
+/// use nexus_parser::stats::tip_dating::TipDates;
+///
+/// let (_, labels) = parse_newick_str("(('A|2020-07-02',B|2021),C);").unwrap();
+/// let dates = TipDates::from_delimited(&labels, '|');
+///
+/// assert_eq!(dates.get(labels.get_index("B|2021").unwrap()), Some(2021.5));
+/// assert!((dates.get(0).unwrap() - 2020.5).abs() < 0.01);
+/// assert_eq!(dates.num_dated(), 2);
+/// assert_eq!(dates.undated(), vec![labels.get_index("C").unwrap()]);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct TipDates {
+    /// Date of each taxon, if known
+    dates: Vec<Option<f64>>,
+}
+
+impl TipDates {
+    /// Creates tip dates of `num_labels` taxa, none of them dated yet.
+    pub fn new(num_labels: usize) -> Self {
+        Self { dates: vec![None; num_labels] }
+    }
+
+    /// Extracts a date from every label with the given function (e.g. a part of the label) and parses it
+    /// (see [parse_date]); taxa whose label yields no valid date stay undated.
+    pub fn from_labels<'a>(labels: &'a LeafLabelMap, extract: impl Fn(&'a str) -> Option<&'a str>) -> Self {
+        let dates = labels.labels().iter()
+            .map(|label| extract(label).and_then(parse_date))
+            .collect();
+        Self { dates }
+    }
+
+    /// Parses the date of every label from its last field after the delimiter (e.g. `'|'` in `virus|2021-03-04`),
+    /// see [TipDates::from_labels]; labels without the delimiter stay undated.
+    pub fn from_delimited(labels: &LeafLabelMap, delimiter: char) -> Self {
+        Self::from_labels(labels, |label| label.rsplit_once(delimiter).map(|(_, date)| date))
+    }
+
+    /// Parses the date of every label from the first capture group of the regular expression
+    /// (or the whole match if it has none), see [TipDates::from_labels]; labels not matching stay undated.
+    ///
+    /// # Example
+    /// ```
+    /// use nexus_parser::parse_newick_str;
+    /// use nexus_parser::stats::tip_dating::TipDates;
+    /// use regex::Regex;
+    ///
+    /// let (_, labels) = parse_newick_str("(hCoV-19/Wuhan/2019-12-30/EPI_1,hCoV-19/Italy/2020-02-20/EPI_2);").unwrap();
+    /// let dates = TipDates::from_regex(&labels, &Regex::new(r"/(\d{4}-\d{2}-\d{2})/").unwrap());
+    /// assert_eq!(dates.num_dated(), 2);
+    /// ```
+    #[cfg(feature = "regex")]
+    pub fn from_regex(labels: &LeafLabelMap, regex: &regex::Regex) -> Self {
+        Self::from_labels(labels, |label| {
+            let captures = regex.captures(label)?;
+            captures.get(1).or_else(|| captures.get(0)).map(|date| date.as_str())
+        })
+    }
+
+    /// Returns the date of the taxon, if known.
+    pub fn get(&self, label_index: LabelIndex) -> Option<f64> {
+        self.dates.get(label_index).copied().flatten()
+    }
+
+    /// Sets (or with `None` removes) the date of the taxon.
+    ///
+    /// # Panics
+    /// Panics if the label index is not one of the taxa.
+    pub fn set(&mut self, label_index: LabelIndex, date: Option<f64>) {
+        self.dates[label_index] = date;
+    }
+
+    /// Returns the number of taxa.
+    pub fn num_labels(&self) -> usize {
+        self.dates.len()
+    }
+
+    /// Returns the number of taxa with a date.
+    pub fn num_dated(&self) -> usize {
+        self.dates.iter().filter(|date| date.is_some()).count()
+    }
+
+    /// Returns the label indices of the taxa without date, in order.
+    pub fn undated(&self) -> Vec<LabelIndex> {
+        (0..self.dates.len()).filter(|&label_index| self.dates[label_index].is_none()).collect()
+    }
+}
+
+/// Parses a date as decimal year: `YYYY-MM-DD` (midpoint of the day), `YYYY-MM` (midpoint of the month),
+/// `YYYY` (midpoint of the year), or a decimal year such as `2021.17`.
+///
+/// # Returns
+/// The decimal year, or `None` if the text is none of these formats or not a valid date
+///
+/// # Example
+/// ```
+/// use nexus_parser::stats::tip_dating::parse_date;
+///
+/// assert_eq!(parse_date("2021"), Some(2021.5));
+/// assert_eq!(parse_date("2021.17"), Some(2021.17));
+/// assert_eq!(parse_date("2020-01-01"), Some(2020.0 + 0.5 / 366.0));
+/// assert_eq!(parse_date("2021-02"), Some(2021.0 + (31.0 + 14.0) / 365.0));
+/// assert_eq!(parse_date("2021-02-29"), None);
+/// ```
+pub fn parse_date(text: &str) -> Option<f64> {
+    let text = text.trim();
+    if text.contains('.') {
+        return text.parse::<f64>().ok().filter(|year| year.is_finite());
+    }
+
+    let mut parts = text.split('-');
+    let year: i32 = parse_digits(parts.next()?)?;
+    let month: Option<usize> = match parts.next() {
+        Some(month) => Some(parse_digits(month)?),
+        None => None,
+    };
+    let day: Option<usize> = match parts.next() {
+        Some(day) => Some(parse_digits(day)?),
+        None => None,
+    };
+    if parts.next().is_some() {
+        return None;
+    }
+
+    let days_in_year = if is_leap_year(year) { 366.0 } else { 365.0 };
+    let Some(month) = month else {
+        return Some(year as f64 + 0.5);
+    };
+    if !(1..=12).contains(&month) {
+        return None;
+    }
+    let days_before: usize = (1..month).map(|m| days_in_month(year, m)).sum();
+    let day_of_year = match day {
+        Some(day) if (1..=days_in_month(year, month)).contains(&day) => days_before as f64 + day as f64 - 0.5,
+        Some(_) => return None,
+        None => days_before as f64 + days_in_month(year, month) as f64 / 2.0,
+    };
+    Some(year as f64 + day_of_year / days_in_year)
+}
+
+// =#========================================================================#=
+// ROOT-TO-TIP REGRESSION
+// =#========================================================================#=
+/// Returns the distance of every leaf to the root (sum of branch lengths), as pairs of label index and distance
+/// in pre-order, or `None` if not all vertices (besides the root) have a branch length.
+///
+/// # Example
+/// ```
+/// use nexus_parser::parse_newick_str;
+/// use nexus_parser::stats::tip_dating::root_to_tip_distances;
+///
+/// let (trees, _) = parse_newick_str("((A:1,B:2):0.5,C:1);").unwrap();
+/// assert_eq!(root_to_tip_distances(&trees[0]), Some(vec![(0, 1.5), (1, 2.5), (2, 1.0)]));
+/// ```
+pub fn root_to_tip_distances(tree: &Tree) -> Option<Vec<(LabelIndex, f64)>> {
+    let mut depths = vec![0.0; tree.num_vertices()];
+    let mut distances = Vec::with_capacity(tree.num_leaves());
+    for vertex in tree.pre_order_iter() {
+        if let Some(parent) = vertex.parent_index() {
+            depths[vertex.index()] = depths[parent] + *vertex.branch_length()?;
+        }
+        if let Some(label_index) = vertex.label_index() {
+            distances.push((label_index, depths[vertex.index()]));
+        }
+    }
+    Some(distances)
+}
+
+/// Least-squares regression of root-to-tip distances on sampling dates, the usual check for temporal signal
+/// of a tree with branch lengths in substitutions per site.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RootToTipRegression {
+    /// Number of dated leaves in the regression
+    pub num_tips: usize,
+    /// Increase of the root-to-tip distance per year, i.e. the estimated clock rate
+    pub slope: f64,
+    /// Root-to-tip distance at year `0`
+    pub intercept: f64,
+    /// Coefficient of determination, the fraction of variance of the distances explained by the dates
+    pub r_squared: f64,
+}
+
+impl RootToTipRegression {
+    /// Fits the regression of the root-to-tip distances of the dated leaves of the tree on their dates.
+    ///
+    /// # Returns
+    /// The regression, or `None` if a branch length is missing,
+    /// fewer than two leaves are dated, or all dated leaves have the same date
+    ///
+    /// # Example
+    /// ```
+    /// use nexus_parser::parse_newick_str;
+    /// use nexus_parser::stats::tip_dating::{RootToTipRegression, TipDates};
+    ///
+    /// // Clock rate 0.01 per year since the root in 2000
+    /// let (trees, labels) = parse_newick_str("((A|2010.0:0.05,B|2020.0:0.15):0.05,C|2015.0:0.15);").unwrap();
+    /// let regression = RootToTipRegression::fit(&trees[0], &TipDates::from_delimited(&labels, '|')).unwrap();
+    ///
+    /// assert!((regression.slope - 0.01).abs() < 1e-9);
+    /// assert!((regression.r_squared - 1.0).abs() < 1e-9);
+    /// assert!((regression.root_date().unwrap() - 2000.0).abs() < 1e-6);
+    /// ```
+    pub fn fit(tree: &Tree, dates: &TipDates) -> Option<Self> {
+        let points: Vec<(f64, f64)> = root_to_tip_distances(tree)?.into_iter()
+            .filter_map(|(label_index, distance)| Some((dates.get(label_index)?, distance)))
+            .collect();
+        if points.len() < 2 {
+            return None;
+        }
+
+        let n = points.len() as f64;
+        let mean_date = points.iter().map(|(date, _)| date).sum::<f64>() / n;
+        let mean_distance = points.iter().map(|(_, distance)| distance).sum::<f64>() / n;
+        let (mut covariance, mut date_variance, mut distance_variance) = (0.0, 0.0, 0.0);
+        for (date, distance) in &points {
+            covariance += (date - mean_date) * (distance - mean_distance);
+            date_variance += (date - mean_date) * (date - mean_date);
+            distance_variance += (distance - mean_distance) * (distance - mean_distance);
+        }
+        if date_variance <= 0.0 {
+            return None;
+        }
+
+        let slope = covariance / date_variance;
+        let r_squared = if distance_variance > 0.0 { covariance * covariance / (date_variance * distance_variance) } else { 1.0 };
+        Some(Self { num_tips: points.len(), slope, intercept: mean_distance - slope * mean_date, r_squared })
+    }
+
+    /// Returns the date at which the regression line reaches distance `0`, i.e. the inferred date of the root (TMRCA),
+    /// or `None` if the slope is not positive (no temporal signal).
+    pub fn root_date(&self) -> Option<f64> {
+        (self.slope > 0.0).then(|| -self.intercept / self.slope)
+    }
+}
+
+// ============================================================================
+// Little Helpers
+// ============================================================================
+/// Parses a non-empty string of ASCII digits.
+fn parse_digits<T: std::str::FromStr>(text: &str) -> Option<T> {
+    if text.is_empty() || !text.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    text.parse().ok()
+}
+
+/// Returns whether the year has 366 days (Gregorian calendar).
+fn is_leap_year(year: i32) -> bool {
+    (year % 4 == 0 && year % 100 != 0) || year % 400 == 0
+}
+
+/// Returns the number of days of the month (1 to 12) in the year.
+fn days_in_month(year: i32, month: usize) -> usize {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
@@ -0,0 +1,245 @@
+//! Tree shape module for statistics of the balance and branching times of trees.
+//!
+//! Extends [Tree] with:
+//! - `gamma_statistic`: Pybus-Harvey gamma of the branching times of an ultrametric binary tree
+//! - `colless_index`: Sum of the differences of the numbers of leaves below both children of every vertex
+//! - `sackin_index`: Sum of the depths (numbers of edges to the root) of all leaves
+//! - `num_cherries`: Number of pairs of leaves sharing their parent
+//!
+//! And provides `ShapeSummary`, the statistics of each tree of a sample with their distributions.
+//! Colless and Sackin indices are unnormalized, as in `apTreeshape`; gamma is negative if branchings
+//! concentrate towards the root (e.g. slowing diversification), and positive if towards the present.
+
+use crate::model::tree::Tree;
+use crate::stats::tree_summary::ValueStats;
+
+// ============================================================================
+// Tree Shape (pub)
+// ============================================================================
+impl Tree {
+    /// Returns the gamma statistic of Pybus and Harvey (2000) of the branching times of this tree,
+    /// which follows a standard normal distribution under a pure-birth (Yule) process.
+    ///
+    /// # Returns
+    /// The gamma statistic, or `None` if the tree is not binary, has fewer than three leaves,
+    /// misses branch lengths or is not ultrametric (see [Tree::node_heights])
+    ///
+    /// # Example
+    /// ```
+    /// use nexus_parser::parse_newick_str;
+    ///
+    /// // Internode intervals g_2 = 1, g_3 = 0, g_4 = 1
+    /// let (trees, _) = parse_newick_str("((A:1,B:1):1,(C:1,D:1):1);").unwrap();
+    /// let gamma = trees[0].gamma_statistic().unwrap();
+    /// assert!((gamma - (-(2f64 / 3.0).sqrt())).abs() < 1e-12);
+    /// ```
+    pub fn gamma_statistic(&self) -> Option<f64> {
+        let num_leaves = self.num_leaves();
+        if num_leaves < 3 || !self.is_binary() {
+            return None;
+        }
+        let heights = self.node_heights()?;
+
+        // Branching times from the root to the present, followed by the present
+        let mut times: Vec<f64> = (0..self.num_vertices())
+            .filter(|&index| !self[index].is_leaf())
+            .map(|index| heights[index])
+            .collect();
+        times.sort_by(|a, b| b.total_cmp(a));
+        times.push(0.0);
+
+        // Cumulative sums of k * g_k, where g_k is the time with k lineages (k = 2, ..., n)
+        let mut cumulative = Vec::with_capacity(num_leaves - 1);
+        let mut sum = 0.0;
+        for (i, pair) in times.windows(2).enumerate() {
+            sum += (i + 2) as f64 * (pair[0] - pair[1]);
+            cumulative.push(sum);
+        }
+        let total = sum;
+        if total <= 0.0 {
+            return None;
+        }
+
+        let num_inner = (num_leaves - 2) as f64;
+        let mean_inner = cumulative[..num_leaves - 2].iter().sum::<f64>() / num_inner;
+        Some((mean_inner - total / 2.0) / (total * (1.0 / (12.0 * num_inner)).sqrt()))
+    }
+
+    /// Returns the Colless index of this tree, the sum over all internal vertices of the absolute difference
+    /// of the numbers of leaves below their two children (`0` for perfectly balanced trees,
+    /// `(n - 1)(n - 2) / 2` for caterpillars on `n` leaves), or `None` if the tree is not binary.
+    ///
+    /// # Example
+    /// ```
+    /// use nexus_parser::parse_newick_str;
+    ///
+    /// let (trees, _) = parse_newick_str("(((A,B),C),D);\n((A,B),(C,D));\n").unwrap();
+    /// assert_eq!(trees[0].colless_index(), Some(3));
+    /// assert_eq!(trees[1].colless_index(), Some(0));
+    /// ```
+    pub fn colless_index(&self) -> Option<usize> {
+        if !self.is_binary() {
+            return None;
+        }
+
+        let num_leaves = self.subtree_leaf_counts();
+        let index = (0..self.num_vertices())
+            .filter_map(|index| match self[index].children() {
+                Some(&[left, right]) => Some(num_leaves[left].abs_diff(num_leaves[right])),
+                _ => None,
+            })
+            .sum();
+        Some(index)
+    }
+
+    /// Returns the Sackin index of this tree, the sum of the depths (numbers of edges to the root) of all leaves.
+    ///
+    /// # Example
+    /// ```
+    /// use nexus_parser::parse_newick_str;
+    ///
+    /// let (trees, _) = parse_newick_str("(((A,B),C),D);\n(A,B,C,D);\n").unwrap();
+    /// assert_eq!(trees[0].sackin_index(), 3 + 3 + 2 + 1);
+    /// assert_eq!(trees[1].sackin_index(), 4);
+    /// ```
+    pub fn sackin_index(&self) -> usize {
+        let mut depths = vec![0; self.num_vertices()];
+        let mut index = 0;
+        for vertex in self.pre_order_iter() {
+            if let Some(parent) = vertex.parent_index() {
+                depths[vertex.index()] = depths[parent] + 1;
+            }
+            if vertex.is_leaf() {
+                index += depths[vertex.index()];
+            }
+        }
+        index
+    }
+
+    /// Returns the number of cherries of this tree, i.e. of vertices whose only children are two leaves.
+    ///
+    /// # Example
+    /// ```
+    /// use nexus_parser::parse_newick_str;
+    ///
+    /// let (trees, _) = parse_newick_str("(((A,B),C),(D,E));").unwrap();
+    /// assert_eq!(trees[0].num_cherries(), 2);
+    /// ```
+    pub fn num_cherries(&self) -> usize {
+        (0..self.num_vertices())
+            .filter(|&index| matches!(self[index].children(), Some(&[left, right]) if self[left].is_leaf() && self[right].is_leaf()))
+            .count()
+    }
+
+    /// Returns the number of leaves below each vertex, indexed by [TreeIndex](crate::model::tree::TreeIndex).
+    fn subtree_leaf_counts(&self) -> Vec<usize> {
+        let mut num_leaves = vec![0; self.num_vertices()];
+        for vertex in self.post_order_iter() {
+            num_leaves[vertex.index()] = match vertex.children() {
+                None => 1,
+                Some(children) => children.iter().map(|&child| num_leaves[child]).sum(),
+            };
+        }
+        num_leaves
+    }
+}
+
+// =#========================================================================#=
+// SHAPE SUMMARY
+// =#========================================================================#=
+/// Shape statistics of each tree of a sample (e.g. an MCMC sample), in sample order.
+///
+/// Trees for which a statistic is not defined (e.g. gamma for trees without branch lengths) are left out of it.
+///
+/// # Example
+/// ```
+/// use nexus_parser::parse_newick_str;
+/// use nexus_parser::stats::tree_shape::ShapeSummary;
+///
+/// let (trees, _) = parse_newick_str("(((A:1,B:1):1,C:2):1,D:3);\n((A:1,B:1):2,(C:2,D:2):1);\n").unwrap();
+/// let summary = ShapeSummary::new(&trees);
+///
+/// assert_eq!(summary.colless(), &[3, 0]);
+/// assert_eq!(summary.cherries(), &[1, 2]);
+/// assert_eq!(summary.gammas().len(), 2);
+/// assert_eq!(summary.sackin_stats().unwrap().mean, 8.5);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct ShapeSummary {
+    /// Number of trees summarized
+    num_trees: usize,
+    /// Gamma statistic of each tree for which it is defined
+    gammas: Vec<f64>,
+    /// Colless index of each binary tree
+    colless: Vec<usize>,
+    /// Sackin index of each tree
+    sackin: Vec<usize>,
+    /// Number of cherries of each tree
+    cherries: Vec<usize>,
+}
+
+impl ShapeSummary {
+    /// Computes the shape statistics of the given trees.
+    pub fn new(trees: &[Tree]) -> Self {
+        Self {
+            num_trees: trees.len(),
+            gammas: trees.iter().filter_map(Tree::gamma_statistic).collect(),
+            colless: trees.iter().filter_map(Tree::colless_index).collect(),
+            sackin: trees.iter().map(Tree::sackin_index).collect(),
+            cherries: trees.iter().map(Tree::num_cherries).collect(),
+        }
+    }
+
+    /// Returns the number of trees summarized.
+    pub fn num_trees(&self) -> usize {
+        self.num_trees
+    }
+
+    /// Returns the gamma statistic of each tree for which it is defined, see [Tree::gamma_statistic].
+    pub fn gammas(&self) -> &[f64] {
+        &self.gammas
+    }
+
+    /// Returns the Colless index of each binary tree, see [Tree::colless_index].
+    pub fn colless(&self) -> &[usize] {
+        &self.colless
+    }
+
+    /// Returns the Sackin index of each tree, see [Tree::sackin_index].
+    pub fn sackin(&self) -> &[usize] {
+        &self.sackin
+    }
+
+    /// Returns the number of cherries of each tree, see [Tree::num_cherries].
+    pub fn cherries(&self) -> &[usize] {
+        &self.cherries
+    }
+
+    /// Returns statistics of the gamma statistics, or `None` if it is defined for no tree.
+    pub fn gamma_stats(&self) -> Option<ValueStats> {
+        ValueStats::from_values(&self.gammas)
+    }
+
+    /// Returns statistics of the Colless indices, or `None` if no tree is binary.
+    pub fn colless_stats(&self) -> Option<ValueStats> {
+        ValueStats::from_values(&to_f64(&self.colless))
+    }
+
+    /// Returns statistics of the Sackin indices, or `None` if there are no trees.
+    pub fn sackin_stats(&self) -> Option<ValueStats> {
+        ValueStats::from_values(&to_f64(&self.sackin))
+    }
+
+    /// Returns statistics of the numbers of cherries, or `None` if there are no trees.
+    pub fn cherry_stats(&self) -> Option<ValueStats> {
+        ValueStats::from_values(&to_f64(&self.cherries))
+    }
+}
+
+// ============================================================================
+// Little Helpers
+// ============================================================================
+/// Converts counts to values for statistics.
+fn to_f64(counts: &[usize]) -> Vec<f64> {
+    counts.iter().map(|&count| count as f64).collect()
+}
@@ -0,0 +1,184 @@
+//! Tree summary module for the branch lengths of trees and the tree lengths and root heights of samples,
+//! the usual sanity check after loading an MCMC sample.
+//!
+//! - `ValueStats`: Count, minimum, maximum, mean and median of values
+//! - `Tree::branch_length_stats`: Statistics of the branch lengths of a tree
+//! - `Tree::max_root_distance`: Root height, also of non-ultrametric trees
+//! - `SampleSummary`: Tree lengths and root heights over a sample, with their statistics
+
+use crate::model::tree::Tree;
+use crate::stats::summary::{hpd_interval, mean, median};
+use std::fmt;
+
+// =#========================================================================#=
+// VALUE STATS
+// =#========================================================================#=
+/// Basic statistics of a (non-empty) collection of values.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ValueStats {
+    /// Number of values
+    pub count: usize,
+    /// Smallest value
+    pub min: f64,
+    /// Largest value
+    pub max: f64,
+    /// Arithmetic mean
+    pub mean: f64,
+    /// Median (mean of the two central values for an even count)
+    pub median: f64,
+}
+
+impl ValueStats {
+    /// Computes the statistics of the given values, or returns `None` if empty.
+    ///
+    /// # Example
+    /// ```
+    /// use nexus_parser::stats::tree_summary::ValueStats;
+    ///
+    /// let stats = ValueStats::from_values(&[0.5, 3.0, 1.0]).unwrap();
+    /// assert_eq!((stats.count, stats.min, stats.max, stats.mean, stats.median), (3, 0.5, 3.0, 1.5, 1.0));
+    /// ```
+    pub fn from_values(values: &[f64]) -> Option<Self> {
+        Some(Self {
+            count: values.len(),
+            min: values.iter().cloned().fold(f64::INFINITY, f64::min),
+            max: values.iter().cloned().fold(f64::NEG_INFINITY, f64::max),
+            mean: mean(values)?,
+            median: median(values)?,
+        })
+    }
+}
+
+impl fmt::Display for ValueStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "mean {:.6}, median {:.6}, range [{:.6}, {:.6}]", self.mean, self.median, self.min, self.max)
+    }
+}
+
+// ============================================================================
+// Branch Lengths (pub)
+// ============================================================================
+impl Tree {
+    /// Returns statistics of the branch lengths of this tree (over all vertices that have one),
+    /// or `None` if it has none; see [Tree::total_branch_length] for their sum.
+    ///
+    /// # Example
+    /// ```
+    /// use nexus_parser::model::tree::Tree;
+    /// use nexus_parser::model::vertex::BranchLength;
+    ///
+    /// let mut tree = Tree::new(3);
+    /// let a = tree.add_leaf(Some(BranchLength::new(1.0)), 0);
+    /// let b = tree.add_leaf(Some(BranchLength::new(1.0)), 1);
+    /// let c = tree.add_leaf(Some(BranchLength::new(3.0)), 2);
+    /// let ab = tree.add_internal_vertex(vec![a, b], Some(BranchLength::new(2.0)));
+    /// tree.add_root(vec![ab, c]);
+    ///
+    /// let stats = tree.branch_length_stats().unwrap();
+    /// assert_eq!((stats.count, stats.min, stats.max, stats.mean, stats.median), (4, 1.0, 3.0, 1.75, 1.5));
+    /// assert_eq!(tree.total_branch_length(), 7.0);
+    /// ```
+    pub fn branch_length_stats(&self) -> Option<ValueStats> {
+        let lengths: Vec<f64> = (0..self.num_vertices())
+            .filter_map(|index| self[index].branch_length())
+            .map(|length| *length)
+            .collect();
+        ValueStats::from_values(&lengths)
+    }
+
+    /// Returns the distance of the root to the farthest leaf, which is the [root height](Tree::root_height)
+    /// for ultrametric trees but also defined for others, or `None` if not all vertices (besides the root)
+    /// have a branch length.
+    pub fn max_root_distance(&self) -> Option<f64> {
+        if !self.vertices_have_branch_lengths() {
+            return None;
+        }
+
+        let mut depths = vec![0.0; self.num_vertices()];
+        let mut height: f64 = 0.0;
+        for vertex in self.pre_order_iter() {
+            if let Some(parent) = vertex.parent_index() {
+                depths[vertex.index()] = depths[parent] + *vertex.branch_length().unwrap();
+                height = height.max(depths[vertex.index()]);
+            }
+        }
+        Some(height)
+    }
+}
+
+// =#========================================================================#=
+// SAMPLE SUMMARY
+// =#========================================================================#=
+/// Tree lengths and root heights over a sample of trees (e.g. an MCMC sample), in sample order.
+///
+/// Trees without (complete) branch lengths are left out, see [SampleSummary::num_without_branch_lengths].
+///
+/// # Example
+/// ```
+/// use nexus_parser::parse_newick_str;
+/// use nexus_parser::stats::tree_summary::SampleSummary;
+///
+/// let (trees, _) = parse_newick_str("((A:1,B:1):1,C:2);\n((A:2,C:2):1,B:3);\n").unwrap();
+/// let summary = SampleSummary::new(&trees);
+/// assert_eq!(summary.tree_lengths(), &[5.0, 8.0]);
+/// assert_eq!(summary.root_height_stats().unwrap().mean, 2.5);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct SampleSummary {
+    /// Number of trees summarized
+    num_trees: usize,
+    /// Sum of branch lengths of each tree with branch lengths
+    tree_lengths: Vec<f64>,
+    /// Root height of each tree with branch lengths
+    root_heights: Vec<f64>,
+}
+
+impl SampleSummary {
+    /// Summarizes the tree lengths and root heights of the given trees.
+    pub fn new(trees: &[Tree]) -> Self {
+        let (tree_lengths, root_heights) = trees.iter()
+            .filter_map(|tree| tree.max_root_distance().map(|height| (tree.total_branch_length(), height)))
+            .unzip();
+        Self { num_trees: trees.len(), tree_lengths, root_heights }
+    }
+
+    /// Returns the number of trees summarized.
+    pub fn num_trees(&self) -> usize {
+        self.num_trees
+    }
+
+    /// Returns the number of trees left out because not all their vertices have a branch length.
+    pub fn num_without_branch_lengths(&self) -> usize {
+        self.num_trees - self.tree_lengths.len()
+    }
+
+    /// Returns the tree length (sum of branch lengths) of each tree with branch lengths.
+    pub fn tree_lengths(&self) -> &[f64] {
+        &self.tree_lengths
+    }
+
+    /// Returns the root height of each tree with branch lengths, see [Tree::max_root_distance].
+    pub fn root_heights(&self) -> &[f64] {
+        &self.root_heights
+    }
+
+    /// Returns statistics of the tree lengths, or `None` if no tree has branch lengths.
+    pub fn tree_length_stats(&self) -> Option<ValueStats> {
+        ValueStats::from_values(&self.tree_lengths)
+    }
+
+    /// Returns statistics of the root heights, or `None` if no tree has branch lengths.
+    pub fn root_height_stats(&self) -> Option<ValueStats> {
+        ValueStats::from_values(&self.root_heights)
+    }
+
+    /// Returns the 95% HPD interval of the tree lengths, or `None` if no tree has branch lengths.
+    pub fn tree_length_hpd(&self) -> Option<(f64, f64)> {
+        hpd_interval(&self.tree_lengths, 0.95)
+    }
+
+    /// Returns the 95% HPD interval of the root heights, or `None` if no tree has branch lengths.
+    pub fn root_height_hpd(&self) -> Option<(f64, f64)> {
+        hpd_interval(&self.root_heights, 0.95)
+    }
+}
@@ -0,0 +1,275 @@
+//! Testing module with random Newick strings and round-trip checks for property-based tests and fuzzing
+//! (requires feature `test-util`).
+//!
+//! - `NewickGenerator`: Generates random valid Newick strings exercising the edge cases of the format
+//!   (quoted labels, scientific-notation branch lengths, comments, whitespace, polytomies)
+//! - `check_round_trip`: Checks that parsing, writing and parsing again gives the same trees,
+//!   e.g. as body of a `cargo fuzz` target on arbitrary input
+//!
+//! # Example
+//! ```
+//! use nexus_parser::testing::{check_round_trip, NewickGenerator};
+//! use rand::rngs::StdRng;
+//! use rand::SeedableRng;
+//!
+//! let generator = NewickGenerator::new().with_max_leaves(30).with_max_children(4);
+//! let mut rng = StdRng::seed_from_u64(42);
+//! for _ in 0..100 {
+//!     let newick = generator.generate(&mut rng);
+//!     check_round_trip(&newick).unwrap();
+//! }
+//! ```
+
+use crate::io::parser::newick;
+use crate::io::writer::newick::to_newick;
+use rand::Rng;
+
+/// Unquoted labels are built from these characters (no Newick punctuation)
+const UNQUOTED_LABEL_CHARS: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789_.-/|";
+
+/// Quoted labels additionally contain these characters, which need quoting (single quotes doubled)
+const QUOTED_LABEL_CHARS: &[u8] = b" ,;:()[]'";
+
+/// Content of (non-metadata) comments
+const COMMENT_CHARS: &[u8] = b"abc xyz 123,;:()'&=";
+
+// =#========================================================================#=
+// NEWICK GENERATOR
+// =#========================================================================#=
+/// Generator of random valid Newick strings (each a single tree ending with `;`).
+///
+/// Each feature is used with a configurable probability per vertex, so that generated trees
+/// combine edge cases: quoted labels (with punctuation and doubled quotes), node labels,
+/// branch lengths in plain and scientific notation (e.g. `1.5e-7`, `2E+3`),
+/// comments and whitespace between tokens, and polytomies.
+/// Leaf labels are unique within a tree.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NewickGenerator {
+    /// Maximal number of leaves (at least 2)
+    max_leaves: usize,
+    /// Maximal number of children of a vertex (2 for binary trees)
+    max_children: usize,
+    /// Probability of a label being quoted
+    quoted_labels: f64,
+    /// Probability of an internal vertex having a node label
+    node_labels: f64,
+    /// Probability of a vertex having a branch length
+    branch_lengths: f64,
+    /// Probability of a branch length being in scientific notation
+    scientific_notation: f64,
+    /// Probability of a comment (and whitespace) between two tokens
+    comments: f64,
+}
+
+impl Default for NewickGenerator {
+    fn default() -> Self {
+        Self {
+            max_leaves: 20,
+            max_children: 3,
+            quoted_labels: 0.2,
+            node_labels: 0.3,
+            branch_lengths: 0.8,
+            scientific_notation: 0.2,
+            comments: 0.05,
+        }
+    }
+}
+
+impl NewickGenerator {
+    /// Creates a generator with default settings (up to 20 leaves, polytomies of up to 3 children).
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the maximal number of leaves.
+    ///
+    /// # Panics
+    /// Panics if `max_leaves < 2`.
+    pub fn with_max_leaves(mut self, max_leaves: usize) -> Self {
+        assert!(max_leaves >= 2, "Trees need at least 2 leaves, got {}", max_leaves);
+        self.max_leaves = max_leaves;
+        self
+    }
+
+    /// Sets the maximal number of children of a vertex (`2` for binary trees).
+    ///
+    /// # Panics
+    /// Panics if `max_children < 2`.
+    pub fn with_max_children(mut self, max_children: usize) -> Self {
+        assert!(max_children >= 2, "Vertices need at least 2 children, got {}", max_children);
+        self.max_children = max_children;
+        self
+    }
+
+    /// Sets the probability of a label being quoted.
+    pub fn with_quoted_labels(mut self, probability: f64) -> Self {
+        self.quoted_labels = probability;
+        self
+    }
+
+    /// Sets the probability of an internal vertex having a node label.
+    pub fn with_node_labels(mut self, probability: f64) -> Self {
+        self.node_labels = probability;
+        self
+    }
+
+    /// Sets the probability of a vertex having a branch length.
+    pub fn with_branch_lengths(mut self, probability: f64) -> Self {
+        self.branch_lengths = probability;
+        self
+    }
+
+    /// Sets the probability of a branch length being written in scientific notation.
+    pub fn with_scientific_notation(mut self, probability: f64) -> Self {
+        self.scientific_notation = probability;
+        self
+    }
+
+    /// Sets the probability of a comment (and whitespace) between two tokens.
+    pub fn with_comments(mut self, probability: f64) -> Self {
+        self.comments = probability;
+        self
+    }
+
+    /// Generates a random Newick string of a single tree.
+    ///
+    /// # Panics
+    /// Panics if a probability is not in `[0, 1]`.
+    pub fn generate<R: Rng + ?Sized>(&self, rng: &mut R) -> String {
+        let num_leaves = rng.random_range(2..=self.max_leaves);
+        // Newick strings of the subtrees not yet joined
+        let mut subtrees: Vec<String> = (0..num_leaves)
+            .map(|i| {
+                let mut leaf = self.gap(rng);
+                leaf.push_str(&self.label(i, rng));
+                leaf.push_str(&self.branch_length(rng));
+                leaf
+            })
+            .collect();
+
+        // Join random subtrees until only the children of the root are left
+        loop {
+            let num_children = rng.random_range(2..=self.max_children.min(subtrees.len()));
+            let is_root = num_children == subtrees.len();
+            let children: Vec<String> = (0..num_children)
+                .map(|_| subtrees.swap_remove(rng.random_range(0..subtrees.len())))
+                .collect();
+
+            let mut vertex = format!("{}({}){}", self.gap(rng), children.join(","), self.gap(rng));
+            if rng.random_bool(self.node_labels) {
+                vertex.push_str(&self.node_label(rng));
+            }
+            if is_root {
+                vertex.push_str(&self.gap(rng));
+                vertex.push(';');
+                return vertex;
+            }
+            vertex.push_str(&self.branch_length(rng));
+            subtrees.push(vertex);
+        }
+    }
+}
+
+// ============================================================================
+// Little Helpers
+// ============================================================================
+impl NewickGenerator {
+    /// Returns a unique leaf label (ending with `t` and its number before any quoted characters), possibly quoted.
+    fn label<R: Rng + ?Sized>(&self, number: usize, rng: &mut R) -> String {
+        let mut label = random_chars(UNQUOTED_LABEL_CHARS, 0..4, rng);
+        label.push('t');
+        label.push_str(&number.to_string());
+        if rng.random_bool(self.quoted_labels) {
+            label.push_str(&random_chars(QUOTED_LABEL_CHARS, 1..4, rng));
+            format!("'{}'", label.replace('\'', "''"))
+        } else {
+            label
+        }
+    }
+
+    /// Returns a node label: a support value or a (possibly quoted) name.
+    fn node_label<R: Rng + ?Sized>(&self, rng: &mut R) -> String {
+        match rng.random_range(0..3) {
+            0 => rng.random_range(0..=100).to_string(),
+            1 => format!("{:.3}", rng.random::<f64>()),
+            _ => self.label(rng.random_range(0..1000), rng),
+        }
+    }
+
+    /// Returns a branch length `:number` (possibly with gaps), or nothing.
+    fn branch_length<R: Rng + ?Sized>(&self, rng: &mut R) -> String {
+        if !rng.random_bool(self.branch_lengths) {
+            return String::new();
+        }
+
+        let number = if rng.random_bool(self.scientific_notation) {
+            let mantissa = format!("{:.*}", rng.random_range(0..6), rng.random_range(0.0..10.0));
+            let exponent = rng.random_range(-12..=12);
+            let e = if rng.random_bool(0.5) { 'e' } else { 'E' };
+            let sign = if exponent >= 0 && rng.random_bool(0.5) { "+" } else { "" };
+            format!("{}{}{}{}", mantissa, e, sign, exponent)
+        } else if rng.random_bool(0.2) {
+            rng.random_range(0..100).to_string()
+        } else {
+            format!("{:.*}", rng.random_range(1..10), rng.random_range(0.0..2.0))
+        };
+        format!("{}:{}{}", self.gap(rng), self.gap(rng), number)
+    }
+
+    /// Returns a comment with surrounding whitespace, or nothing.
+    fn gap<R: Rng + ?Sized>(&self, rng: &mut R) -> String {
+        if rng.random_bool(self.comments) {
+            format!(" [{}]\n", random_chars(COMMENT_CHARS, 0..10, rng))
+        } else {
+            String::new()
+        }
+    }
+}
+
+/// Returns a string of random length in the range, of random characters from the given ones.
+fn random_chars<R: Rng + ?Sized>(chars: &[u8], length: std::ops::Range<usize>, rng: &mut R) -> String {
+    let length = rng.random_range(length);
+    (0..length).map(|_| chars[rng.random_range(0..chars.len())] as char).collect()
+}
+
+// =#========================================================================#=
+// ROUND TRIP
+// =#========================================================================#=
+/// Checks that the trees of the Newick string survive a round trip: parsing them, writing them
+/// (see [to_newick]) and parsing the output again gives the same labels, the same trees
+/// (including branch lengths, exactly; for [valid](crate::model::tree::Tree::is_valid) trees)
+/// and the same output when written again.
+///
+/// Input that cannot be parsed passes, so arbitrary (fuzzed) input can be checked;
+/// parsing it must however return an error rather than panic.
+///
+/// # Errors
+/// Returns a description of the first difference found.
+pub fn check_round_trip(newicks: &str) -> Result<(), String> {
+    let Ok((trees, labels)) = newick::parse_str(newicks) else {
+        return Ok(());
+    };
+
+    let written: Vec<String> = trees.iter().map(|tree| to_newick(tree, &labels)).collect();
+    let (reparsed, relabels) = newick::parse_str(&written.join("\n"))
+        .map_err(|e| format!("Written trees cannot be parsed: {}\n{}", e, written.join("\n")))?;
+
+    if relabels.labels() != labels.labels() {
+        return Err(format!("Labels differ: {:?} vs {:?}", labels.labels(), relabels.labels()));
+    }
+    if reparsed.len() != trees.len() {
+        return Err(format!("Number of trees differs: {} vs {}", trees.len(), reparsed.len()));
+    }
+    for (i, (tree, again)) in trees.iter().zip(&reparsed).enumerate() {
+        // Trees of strings on differing taxa have label indices beyond their number of leaves; compare valid ones
+        if tree.is_valid() && !tree.exact_eq(again, 0.0) {
+            return Err(format!("Tree {} differs after round trip: {}", i, written[i]));
+        }
+        let rewritten = to_newick(again, &relabels);
+        if rewritten != written[i] {
+            return Err(format!("Tree {} is written differently: {} vs {}", i, written[i], rewritten));
+        }
+    }
+
+    Ok(())
+}
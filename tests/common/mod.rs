@@ -0,0 +1,88 @@
+//! Helpers shared by the integration tests (`mod common;`).
+
+use nexus_parser::io::parser::byte_parser::ByteParser;
+use nexus_parser::io::parser::newick::{AnnotationMode, NewickParser};
+use nexus_parser::io::writer::newick::NewickStyle;
+use nexus_parser::model::leaf_label_map::LeafLabelMap;
+use nexus_parser::model::tree::Tree;
+use nexus_parser::model::vertex::StoredLength;
+use std::path::{Path, PathBuf};
+
+/// Returns the path of the fixture file `name` in `tests/fixtures`.
+#[allow(dead_code)]
+pub fn fixture(name: &str) -> PathBuf {
+    Path::new("tests").join("fixtures").join(name)
+}
+
+/// Parses a single Newick tree with its own leaf label map.
+#[allow(dead_code)]
+pub fn parse_newick(newick: &str) -> (Tree, LeafLabelMap) {
+    let mut newick_parser = NewickParser::new();
+    let tree = newick_parser.parse(&mut ByteParser::from_str(newick)).unwrap();
+    (tree, newick_parser.into_leaf_label_map())
+}
+
+/// Parses a single Newick tree with its own leaf label map, keeping annotations as given by `mode`.
+#[allow(dead_code)]
+pub fn parse_annotated(newick: &str, mode: AnnotationMode) -> (Tree, LeafLabelMap) {
+    let mut newick_parser = NewickParser::new().with_annotations(mode);
+    let tree = newick_parser.parse(&mut ByteParser::from_str(newick)).unwrap();
+    (tree, newick_parser.into_leaf_label_map())
+}
+
+/// Parses a single Newick tree on `num_leaves` taxa.
+#[allow(dead_code)]
+pub fn parse_tree(newick: &str, num_leaves: usize) -> Tree {
+    parse_tree_with_labels(newick, num_leaves).0
+}
+
+/// Parses a single Newick tree on `num_leaves` taxa, and returns it with its leaf label map.
+#[allow(dead_code)]
+pub fn parse_tree_with_labels(newick: &str, num_leaves: usize) -> (Tree, LeafLabelMap) {
+    let mut newick_parser = NewickParser::new().with_num_leaves(num_leaves);
+    let tree = newick_parser.parse(&mut ByteParser::from_str(newick)).unwrap();
+    (tree, newick_parser.into_leaf_label_map())
+}
+
+/// Parses Newick trees on `num_leaves` taxa sharing one leaf label map.
+#[allow(dead_code)]
+pub fn parse_trees(newicks: &[&str], num_leaves: usize) -> Vec<Tree> {
+    parse_trees_with_labels(newicks, num_leaves).0
+}
+
+/// Parses Newick trees on `num_leaves` taxa sharing one leaf label map, and returns them with the map.
+#[allow(dead_code)]
+pub fn parse_trees_with_labels(newicks: &[&str], num_leaves: usize) -> (Vec<Tree>, LeafLabelMap) {
+    let mut newick_parser = NewickParser::new().with_num_leaves(num_leaves);
+    let trees = newicks.iter()
+        .map(|newick| newick_parser.parse(&mut ByteParser::from_str(newick)).unwrap())
+        .collect();
+    (trees, newick_parser.into_leaf_label_map())
+}
+
+/// Returns the index of the leaf with the given label index.
+#[allow(dead_code)]
+pub fn leaf(tree: &Tree, label_index: usize) -> usize {
+    (0..tree.num_vertices())
+        .find(|&index| tree[index].label_index() == Some(label_index))
+        .unwrap()
+}
+
+/// Returns the Newick string of the tree with zero-indexed labels, for comparing topologies.
+#[allow(dead_code)]
+pub fn newick(tree: &Tree) -> String {
+    tree.to_newick(&NewickStyle::ZeroIndexed, None)
+}
+
+/// Returns the rounding tolerance of values computed from branch lengths of magnitude `magnitude`,
+/// as vertices store them as [StoredLength] (`f32` with the `f32-lengths` feature).
+#[allow(dead_code, clippy::unnecessary_cast)]
+pub fn length_tolerance(magnitude: f64) -> f64 {
+    2.0 * magnitude.abs() * StoredLength::EPSILON as f64
+}
+
+/// Returns whether `a` and `b` are equal within `tolerance`, or within the [length_tolerance] of their magnitude.
+#[allow(dead_code)]
+pub fn approx_eq(a: f64, b: f64, tolerance: f64) -> bool {
+    (a - b).abs() <= tolerance.max(length_tolerance(a.abs().max(b.abs())))
+}
@@ -0,0 +1,7 @@
+[ID: 9409050143]
+Gen	LnL	LnPr	TL	r(A<->C)	pi(A)
+1	-5234.120	12.301	0.9871	0.1667	0.2500
+1000	-4321.876	10.552	1.0234	0.1421	0.2611
+2000	-4310.455	10.871	1.0412	0.1389	0.2587
+3000	-4312.913	10.698	1.0377	0.1402	0.2634
+4000	-4309.210	11.023	1.0451	0.1398	0.2602
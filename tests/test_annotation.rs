@@ -0,0 +1,175 @@
+mod common;
+use common::{approx_eq, leaf, parse_annotated};
+
+use nexus_parser::io::parser::newick::AnnotationMode;
+use nexus_parser::io::parser::nexus::{NexusParserBuilder, TreeReader};
+use nexus_parser::io::writer::newick::to_newick;
+use nexus_parser::model::annotation::{Annotation, AnnotationValue};
+use std::io::Cursor;
+
+const BEAST_NEWICK: &str = "((A[&rate=0.5,height_95%_HPD={0.1,0.25}]:1,B[&rate=1.5]:1)[&posterior=0.98]:0.5,C:[&rate=2]1.5)[&R];";
+
+#[test]
+fn test_annotation_parse() {
+    let annotation = Annotation::parse("rate=0.01,height_95%_HPD={1.2,3.4},set={\"a,b\",c},prob(percent)=\"100\",R");
+    assert!(annotation.is_parsed());
+    assert_eq!(annotation.entries().len(), 5);
+    assert_eq!(annotation.get("rate"), Some(&AnnotationValue::Number(0.01)));
+    assert_eq!(annotation.get("height_95%_HPD"), Some(&AnnotationValue::Range(1.2, 3.4)));
+    assert_eq!(annotation.get("set").unwrap().as_list().unwrap(),
+               &[AnnotationValue::Text("a,b".to_string()), AnnotationValue::Text("c".to_string())]);
+    assert_eq!(annotation.get("prob(percent)").unwrap().as_text(), Some("100"));
+    assert_eq!(annotation.get("R").unwrap().as_text(), Some(""));
+    assert_eq!(annotation.get("missing"), None);
+
+    let raw = Annotation::from_raw("rate=0.01".to_string());
+    assert!(!raw.is_parsed());
+    assert_eq!(raw.raw(), "rate=0.01");
+    assert_eq!(raw.get("rate"), None);
+}
+
+#[test]
+fn test_annotation_typed_values() {
+    // StarBEAST species tree vertex, as summarized by TreeAnnotator
+    let annotation = Annotation::parse("dmv={0.25,0.5},dmv_range={0.1,0.9},height_95%_HPD={1,2.5},count=3,height=1e-3,tags={1,a}");
+    assert_eq!(annotation.get("dmv").unwrap().as_list().unwrap(),
+               &[AnnotationValue::Number(0.25), AnnotationValue::Number(0.5)]);
+    assert_eq!(annotation.get("dmv_range"), Some(&AnnotationValue::Range(0.1, 0.9)));
+    assert_eq!(annotation.get("height_95%_HPD").unwrap().as_range(), Some((1.0, 2.5)));
+    assert_eq!(annotation.get("count"), Some(&AnnotationValue::Int(3)));
+    assert_eq!(annotation.get("count").unwrap().as_number(), Some(3.0));
+    assert_eq!(annotation.get("height"), Some(&AnnotationValue::Number(0.001)));
+    assert_eq!(annotation.get("height").unwrap().as_int(), None);
+    assert_eq!(annotation.get("tags").unwrap().as_list().unwrap(),
+               &[AnnotationValue::Int(1), AnnotationValue::Text("a".to_string())]);
+
+    // Ranges need exactly two numbers, and are written back in braces
+    let annotation = Annotation::parse("length_range={1,2,3},rate_95%_HPD={a,b}");
+    assert_eq!(annotation.get("length_range").unwrap().as_list().unwrap().len(), 3);
+    assert!(annotation.get("rate_95%_HPD").unwrap().as_range().is_none());
+    assert_eq!(AnnotationValue::Range(0.5, 2.0).to_string(), "{0.5,2}");
+}
+
+#[test]
+fn test_annotations_mrbayes_consensus() {
+    // Vertex and branch annotations of a MrBayes `.con.tre` (sumt), with HPD keys without underscore
+    let newick = "((A[&prob=1.00000000e+00,prob_range={1.00000000e+00,1.00000000e+00},height_95%HPD={0.0,0.0}]\
+                  :[&length_mean=1.02e-01,length_95%HPD={7.50e-02,1.31e-01}]1.00e-01,\
+                  B:[&length_95%HPD={0.05,0.08}]0.06)[&prob=8.5e-01,prob(percent)=\"85\",prob+-sd=\"85+-2\"]:0.5,C:1.5);";
+    let (tree, _) = parse_annotated(newick, AnnotationMode::Parsed);
+    let a = leaf(&tree, 0);
+    let ab = tree[a].parent_index().unwrap();
+
+    assert_eq!(tree.annotation(a).unwrap().get("height_95%HPD"), Some(&AnnotationValue::Range(0.0, 0.0)));
+    assert_eq!(tree.annotation(a).unwrap().get("prob_range"), Some(&AnnotationValue::Range(1.0, 1.0)));
+    let length = tree.branch_annotation(a).unwrap();
+    assert_eq!(length.get("length_95%HPD").unwrap().as_range(), Some((0.075, 0.131)));
+    assert_eq!(length.get("length_mean").unwrap().as_number(), Some(0.102));
+    assert!(approx_eq(*tree[a].branch_length().unwrap(), 0.1, 0.0));
+    assert_eq!(tree.branch_annotation(leaf(&tree, 1)).unwrap().get("length_95%HPD").unwrap().as_range(), Some((0.05, 0.08)));
+    assert_eq!(tree.annotation(ab).unwrap().get("prob").unwrap().as_number(), Some(0.85));
+    assert_eq!(tree.annotation(ab).unwrap().get("prob+-sd"), Some(&AnnotationValue::Text("85+-2".to_string())));
+}
+
+#[test]
+fn test_annotation_merge() {
+    let mut annotation = Annotation::from_raw("rate=0.01, height=2".to_string());
+    annotation.merge(&Annotation::parse("height=3"));
+    assert!(annotation.is_parsed());
+    assert_eq!(annotation.raw(), "rate=0.01, height=2");
+
+    annotation.merge(&Annotation::from_raw("color=red,rate=1,color=blue".to_string()));
+    assert_eq!(annotation.raw(), "rate=0.01,height=2,color=red");
+    assert_eq!(annotation.get("color").unwrap().as_text(), Some("red"));
+}
+
+#[test]
+fn test_annotations_skipped_by_default() {
+    let (tree, _) = parse_annotated(BEAST_NEWICK, AnnotationMode::Skip);
+    assert!(tree.is_valid());
+    assert!(!tree.has_annotations());
+}
+
+#[test]
+fn test_annotations_raw() {
+    let (tree, _) = parse_annotated(BEAST_NEWICK, AnnotationMode::Raw);
+    let a = leaf(&tree, 0);
+
+    assert_eq!(tree.annotation(a).unwrap().raw(), "rate=0.5,height_95%_HPD={0.1,0.25}");
+    assert!(!tree.annotation(a).unwrap().is_parsed());
+    assert_eq!(tree.annotation(tree.root().index()).unwrap().raw(), "R");
+}
+
+#[test]
+fn test_annotations_parsed() {
+    let (tree, _) = parse_annotated(BEAST_NEWICK, AnnotationMode::Parsed);
+    let (a, b, c) = (leaf(&tree, 0), leaf(&tree, 1), leaf(&tree, 2));
+    let ab = tree[a].parent_index().unwrap();
+
+    assert_eq!(tree.annotation(a).unwrap().get("rate").unwrap().as_number(), Some(0.5));
+    assert_eq!(tree.annotation(b).unwrap().get("rate").unwrap().as_number(), Some(1.5));
+    assert_eq!(tree.annotation(ab).unwrap().get("posterior").unwrap().as_number(), Some(0.98));
+    assert_eq!(*tree[ab].branch_length().unwrap(), 0.5);
+
+    // Annotation after colon belongs to branch
+    assert!(tree.annotation(c).is_none());
+    assert_eq!(tree.branch_annotation(c).unwrap().get("rate").unwrap().as_number(), Some(2.0));
+    assert_eq!(*tree[c].branch_length().unwrap(), 1.5);
+}
+
+#[test]
+fn test_annotations_with_node_labels_and_comments() {
+    let newick = "((A[plain comment][&a=1]:1,B:1)95[&b=2]:1,C:1)Root[&c=3]:0.0;";
+    let (tree, _) = parse_annotated(newick, AnnotationMode::Parsed);
+    let a = leaf(&tree, 0);
+    let ab = tree[a].parent_index().unwrap();
+
+    assert_eq!(tree.annotation(a).unwrap().raw(), "a=1");
+    assert_eq!(tree[ab].support(), Some(95.0));
+    assert_eq!(tree.annotation(ab).unwrap().raw(), "b=2");
+    assert_eq!(tree.root().node_label(), Some("Root"));
+    assert_eq!(tree.annotation(tree.root().index()).unwrap().raw(), "c=3");
+}
+
+#[test]
+fn test_annotations_round_trip() {
+    let (tree, labels) = parse_annotated(BEAST_NEWICK, AnnotationMode::Raw);
+    let written = to_newick(&tree, &labels);
+    assert_eq!(written, BEAST_NEWICK);
+
+    // Without annotations, nothing is written
+    let (tree, labels) = parse_annotated(BEAST_NEWICK, AnnotationMode::Skip);
+    assert_eq!(to_newick(&tree, &labels), "((A:1,B:1):0.5,C:1.5);");
+}
+
+#[test]
+fn test_annotations_nexus() {
+    let nexus = format!("#NEXUS
+Begin trees;
+\tTranslate 1 A, 2 B, 3 C;
+\ttree STATE_0 [&lnP=-12.5] = [&R] {};
+End;
+", BEAST_NEWICK.replace('A', "1").replace('B', "2").replace("C:", "3:"));
+
+    let (trees, _) = NexusParserBuilder::for_bytes(nexus.as_bytes().to_vec())
+        .with_annotations(AnnotationMode::Parsed)
+        .build().unwrap()
+        .into_results().unwrap();
+    let a = leaf(&trees[0], 0);
+    assert_eq!(trees[0].annotation(a).unwrap().get("rate").unwrap().as_number(), Some(0.5));
+
+    let mut reader = TreeReader::new(Cursor::new(nexus)).unwrap().with_annotations(AnnotationMode::Raw);
+    let tree = reader.next().unwrap().unwrap();
+    assert_eq!(tree.annotation(a).unwrap().raw(), "rate=0.5,height_95%_HPD={0.1,0.25}");
+}
+
+#[test]
+fn test_set_annotation_entries() {
+    let mut annotation = Annotation::parse("rate=0.01,!color=\"#ff0000\",&R");
+    annotation.set("rate", AnnotationValue::Number(0.5));
+    annotation.set("label", AnnotationValue::Text("a b".to_string()));
+    assert_eq!(annotation.raw(), "rate=0.5,!color=#ff0000,&R,label=\"a b\"");
+
+    // Rewritten content is parsed back to the same entries
+    assert_eq!(Annotation::parse(annotation.raw()).entries(), annotation.entries());
+}
@@ -0,0 +1,109 @@
+mod common;
+use common::parse_newick;
+
+use nexus_parser::io::anonymize::Anonymizer;
+use nexus_parser::io::writer::newick::to_newick;
+use nexus_parser::io::writer::nexus::NexusWriter;
+#[cfg(feature = "fs")]
+use nexus_parser::{anonymize_nexus_file, parse_nexus_file};
+use std::io::Cursor;
+
+#[test]
+fn test_anonymize_label_map_alphabetical_and_stable() {
+    let (_, labels) = parse_newick("((Kea:1,Kiwi:1):1,Emu:2);");
+
+    let mut anonymizer = Anonymizer::new();
+    let anonymized = anonymizer.anonymize_label_map(&labels);
+
+    assert_eq!(anonymized.labels(), &["taxon_2", "taxon_3", "taxon_1"]);
+    assert_eq!(anonymizer.original("taxon_1"), Some("Emu"));
+    assert_eq!(anonymizer.pseudonym("Kiwi"), "taxon_3");
+    assert_eq!(anonymizer.pseudonym("Kakapo"), "taxon_4");
+    assert_eq!(anonymizer.num_pseudonyms(), 4);
+}
+
+#[test]
+fn test_anonymize_with_prefix() {
+    let mut anonymizer = Anonymizer::new().with_prefix("sp");
+    assert_eq!(anonymizer.pseudonym("Kea"), "sp1");
+    assert_eq!(anonymizer.original("taxon_1"), None);
+}
+
+#[test]
+fn test_anonymize_node_labels_keeps_support() {
+    let (mut tree, labels) = parse_newick("((Kea:1,Kiwi:1)Secret_clade:1,(Emu:1,Moa:1)95:1)Root;");
+
+    let mut anonymizer = Anonymizer::new();
+    let anonymized = anonymizer.anonymize(std::slice::from_mut(&mut tree), &labels);
+
+    let newick = to_newick(&tree, &anonymized);
+    assert_eq!(newick, "((taxon_2:1,taxon_3:1)taxon_5:1,(taxon_1:1,taxon_4:1)95:1)taxon_6;");
+    assert_eq!(anonymizer.original("taxon_5"), Some("Secret_clade"));
+}
+
+#[test]
+fn test_mapping_tsv_round_trip() {
+    let mut anonymizer = Anonymizer::new();
+    anonymizer.pseudonym("Nestor notabilis");
+    anonymizer.pseudonym("Apteryx owenii");
+
+    let mut tsv = Vec::new();
+    anonymizer.write_mapping_tsv(&mut tsv).unwrap();
+    assert_eq!(String::from_utf8(tsv.clone()).unwrap(), "Nestor notabilis\ttaxon_1\nApteryx owenii\ttaxon_2\n");
+
+    // Continue with known pseudonyms
+    let mut continued = Anonymizer::from_mapping_tsv(Cursor::new(tsv)).unwrap();
+    assert_eq!(continued.pseudonym("Apteryx owenii"), "taxon_2");
+    assert_eq!(continued.pseudonym("Strigops habroptilus"), "taxon_3");
+}
+
+#[test]
+fn test_mapping_tsv_errors() {
+    assert!(Anonymizer::from_mapping_tsv(Cursor::new("Kea taxon_1\n")).is_err());
+    assert!(Anonymizer::from_mapping_tsv(Cursor::new("Kea\ttaxon_1\nKiwi\ttaxon_1\n")).is_err());
+    assert!(Anonymizer::from_mapping_tsv(Cursor::new("Kea\ttaxon_1\nKea\ttaxon_2\n")).is_err());
+}
+
+#[test]
+fn test_anonymized_nexus_hides_labels() {
+    let (mut tree, labels) = parse_newick("(('Nestor notabilis':1,'Apteryx owenii':1)Clade_X:1,Emu:2);");
+
+    let mut anonymizer = Anonymizer::new();
+    let anonymized = anonymizer.anonymize(std::slice::from_mut(&mut tree), &labels);
+
+    for translate in [true, false] {
+        let mut writer = NexusWriter::new(Vec::new()).with_translate(translate);
+        writer.write_nexus(std::slice::from_ref(&tree), &anonymized).unwrap();
+        let output = String::from_utf8(writer.into_inner().unwrap()).unwrap();
+
+        assert!(output.contains("taxon_1"));
+        for original in ["Nestor", "Apteryx", "Emu", "Clade_X"] {
+            assert!(!output.contains(original));
+        }
+    }
+}
+
+#[cfg(feature = "fs")]
+#[test]
+fn test_anonymize_nexus_file() {
+    let dir = std::env::temp_dir();
+    let output_path = dir.join("nexus_parser_test_anonymized.trees");
+    let mapping_path = dir.join("nexus_parser_test_anonymized_mapping.tsv");
+    let (output_path, mapping_path) = (output_path.to_str().unwrap(), mapping_path.to_str().unwrap());
+
+    let input_path = "tests/fixtures/nexus_t11_n20_translate.trees";
+    anonymize_nexus_file(input_path, output_path, mapping_path).unwrap();
+
+    let (trees, labels) = parse_nexus_file(input_path).unwrap();
+    let (anonymized_trees, anonymized_labels) = parse_nexus_file(output_path).unwrap();
+    let mapping = std::fs::read(mapping_path).unwrap();
+    std::fs::remove_file(output_path).unwrap();
+    std::fs::remove_file(mapping_path).unwrap();
+
+    let anonymizer = Anonymizer::from_mapping_tsv(Cursor::new(mapping)).unwrap();
+    assert_eq!(anonymizer.num_pseudonyms(), labels.num_labels());
+    assert_eq!(anonymized_trees.len(), trees.len());
+    for (index, pseudonym) in anonymized_labels.labels().iter().enumerate() {
+        assert_eq!(anonymizer.original(pseudonym), labels.get_label(index));
+    }
+}
@@ -145,6 +145,56 @@ fn test_parse_label_chooses_unquoted() {
     assert_eq!(parser.peek(), Some(b':'));
 }
 
+#[test]
+fn test_parse_quoted_label_with_delimiters() {
+    let mut parser = ByteParser::from_str("'Homo ''sapiens'' (L., 1758)':0.5");
+    let label = parser.parse_label(b"(),:; \t\n\r").unwrap();
+    assert_eq!(label, "Homo 'sapiens' (L., 1758)");
+    assert_eq!(parser.peek(), Some(b':'));
+}
+
+#[test]
+fn test_parse_quoted_label_unclosed() {
+    let mut parser = ByteParser::from_str("'Homo sapiens:0.5");
+    assert!(parser.parse_quoted_label().is_err());
+}
+
+#[test]
+fn test_parse_label_utf8() {
+    let mut parser = ByteParser::from_str("Müller's_Ämeise,'Größe'");
+    let delimiters = b"(),:; \t\n\r";
+    assert_eq!(parser.parse_label(delimiters).unwrap(), "Müller's_Ämeise");
+    parser.next();
+    assert_eq!(parser.parse_label(delimiters).unwrap(), "Größe");
+}
+
+#[test]
+fn test_parse_label_underscores_to_spaces() {
+    let delimiters = b"(),:; \t\n\r";
+
+    let mut parser = ByteParser::from_str("Homo_sapiens,'Pan_troglodytes'").with_underscores_to_spaces(true);
+    assert!(parser.underscores_to_spaces());
+    assert_eq!(parser.parse_label(delimiters).unwrap(), "Homo sapiens");
+    parser.next();
+    // Quoted labels keep underscores
+    assert_eq!(parser.parse_label(delimiters).unwrap(), "Pan_troglodytes");
+
+    // Default keeps underscores
+    let mut parser = ByteParser::from_str("Homo_sapiens");
+    assert_eq!(parser.parse_label(delimiters).unwrap(), "Homo_sapiens");
+}
+
+#[test]
+fn test_parse_unsigned_integer() {
+    let mut parser = ByteParser::from_str("1234:0.5");
+    assert_eq!(parser.parse_unsigned_integer(), Some(1234));
+    assert_eq!(parser.peek(), Some(b':'));
+    assert_eq!(parser.parse_unsigned_integer(), None);
+
+    let mut parser = ByteParser::from_str("99999999999999999999999");
+    assert_eq!(parser.parse_unsigned_integer(), None);
+}
+
 #[test]
 fn test_slice_from_basic() {
     let mut parser = ByteParser::from_str("BEGIN TREES;");
@@ -201,4 +251,74 @@ fn test_get_context_as_string() {
     parser.consume_if_word("BEGIN");
     parser.skip_whitespace();
     assert_eq!(parser.get_context_as_string(10), "TREES;");
-}
\ No newline at end of file
+}
+#[test]
+fn test_peek_is_keyword() {
+    let parser = ByteParser::from_str("TREES;");
+    assert!(parser.peek_is_keyword(b"trees"));
+    assert!(!parser.peek_is_keyword(b"tree"));
+
+    let parser = ByteParser::from_str("tree");
+    assert!(parser.peek_is_keyword(b"TREE"));
+
+    let parser = ByteParser::from_str("endblock;");
+    assert!(parser.peek_is_keyword(b"endblock"));
+    assert!(!parser.peek_is_keyword(b"end"));
+}
+
+#[test]
+fn test_consume_command() {
+    let mut parser = ByteParser::from_str("taxlabels 'A;B' [C;D] E; tree");
+    assert!(parser.consume_command(true));
+    parser.skip_whitespace();
+    assert!(parser.peek_is_word("tree"));
+
+    // Apostrophes in free text
+    let mut parser = ByteParser::from_str("I don't know; End;");
+    assert!(parser.consume_command(false));
+    parser.skip_whitespace();
+    assert!(parser.peek_is_word("End;"));
+
+    let mut parser = ByteParser::from_str("I don't know; End;");
+    assert!(!parser.consume_command(true));
+    assert!(parser.is_eof());
+}
+
+#[test]
+fn test_long_label_and_comment() {
+    // Longer than the blocks scanned at once
+    let name = "Homo_sapiens_".repeat(40);
+    let input = format!("[{}] \n\t'{}''s':1.0,{};", "x".repeat(500), name, name);
+    let mut parser = ByteParser::from_str(&input).with_underscores_to_spaces(true);
+    let delimiters = b"(),:; \t\n\r";
+
+    assert_eq!(parser.parse_comment().unwrap(), Some("x".repeat(500)));
+    assert_eq!(parser.parse_label(delimiters).unwrap(), format!("{}'s", name));
+    assert!(parser.consume_until(b',', Inclusive));
+    assert_eq!(parser.parse_label(delimiters).unwrap(), name.replace('_', " "));
+    assert_eq!(parser.peek(), Some(b';'));
+}
+
+#[test]
+fn test_scan_to_eof() {
+    let mut parser = ByteParser::from_str("[never closed");
+    assert!(parser.skip_comment().is_err());
+    assert!(parser.is_eof());
+
+    let mut parser = ByteParser::from_str("no target here");
+    assert!(!parser.consume_until(b'@', Inclusive));
+    assert!(parser.is_eof());
+
+    let mut parser = ByteParser::from_str("no targets here");
+    assert_eq!(parser.consume_until_any(b"@#", Exclusive), None);
+    assert_eq!(parser.position(), 15);
+}
+
+#[test]
+fn test_consume_command_with_quote_in_comment() {
+    // Apostrophe within a comment and comment brackets within a quoted label do not count
+    let mut parser = ByteParser::from_str("tree a = [don't;] ('[x;':1); end;");
+    assert!(parser.consume_command(true));
+    parser.skip_whitespace();
+    assert!(parser.peek_is_word("end;"));
+}
@@ -0,0 +1,113 @@
+#![cfg(feature = "fs")]
+
+use nexus_parser::io::cache::{read_cache, read_cache_from, write_cache, write_cache_to, CacheError, LengthPrecision};
+use nexus_parser::io::parser::byte_parser::ByteParser;
+use nexus_parser::io::parser::newick::{AnnotationMode, NewickParser};
+use nexus_parser::io::writer::newick::NewickStyle;
+use nexus_parser::model::leaf_label_map::LeafLabelMap;
+use nexus_parser::model::tree::Tree;
+use nexus_parser::parse_nexus_file;
+use std::path::{Path, PathBuf};
+
+fn sample() -> (Vec<Tree>, LeafLabelMap) {
+    let path = Path::new("tests").join("fixtures").join("nexus_t11_n20_translate.trees");
+    parse_nexus_file(path.to_str().unwrap()).unwrap()
+}
+
+/// Cache path unique to the test, without leftovers of previous runs
+fn cache_path(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("nexus_parser_{}_{}.cache", name, std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    path
+}
+
+fn to_cache(trees: &[Tree], labels: &LeafLabelMap, precision: LengthPrecision) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    write_cache_to(&mut bytes, trees, labels, precision).unwrap();
+    bytes
+}
+
+#[test]
+fn test_cache_roundtrip_file() {
+    let (trees, labels) = sample();
+    let path = cache_path("roundtrip");
+
+    write_cache(&path, &trees, &labels, LengthPrecision::F64).unwrap();
+    let (cached_trees, cached_labels) = read_cache(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(cached_labels.labels(), labels.labels());
+    assert_eq!(cached_trees.len(), trees.len());
+    for (tree, cached) in trees.iter().zip(&cached_trees) {
+        assert!(cached.is_valid());
+        assert_eq!(cached.name(), tree.name());
+        assert_eq!(cached.is_rooted(), tree.is_rooted());
+        assert!(cached.exact_eq(tree, 0.0));
+        assert_eq!(cached.to_newick(&NewickStyle::Label, Some(&cached_labels)),
+                   tree.to_newick(&NewickStyle::Label, Some(&labels)));
+    }
+
+    // Precision is honored for files too
+    write_cache(&path, &trees, &labels, LengthPrecision::F32).unwrap();
+    let size_f32 = std::fs::metadata(&path).unwrap().len() as usize;
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(size_f32, to_cache(&trees, &labels, LengthPrecision::F32).len());
+    assert!(size_f32 < to_cache(&trees, &labels, LengthPrecision::F64).len());
+}
+
+#[test]
+fn test_cache_roundtrip_annotations_and_node_labels() {
+    let mut newick_parser = NewickParser::new().with_num_leaves(3).with_annotations(AnnotationMode::Parsed);
+    let tree = newick_parser.parse(&mut ByteParser::from_str(
+        "((A[&rate=0.1]:1,B:[&color=red]2)95:0.5,C:3)root;")).unwrap().with_rooted(false);
+    let labels = newick_parser.into_leaf_label_map();
+
+    let bytes = to_cache(std::slice::from_ref(&tree), &labels, LengthPrecision::F64);
+    let (cached, _) = read_cache_from(bytes.as_slice()).unwrap();
+    let cached = &cached[0];
+
+    assert!(!cached.is_rooted());
+    assert_eq!(cached.root().node_label(), Some("root"));
+    assert_eq!(cached[tree.mrca(&[0, 1])].node_label(), Some("95"));
+    let a = tree.mrca(&[0]);
+    assert_eq!(cached.annotation(a), tree.annotation(a));
+    assert_eq!(cached.annotation(a).unwrap().get("rate").unwrap().as_number(), Some(0.1));
+    let b = tree.mrca(&[1]);
+    assert_eq!(cached.branch_annotation(b), tree.branch_annotation(b));
+}
+
+#[test]
+fn test_cache_f32_precision() {
+    let (trees, labels) = sample();
+    let bytes_f64 = to_cache(&trees, &labels, LengthPrecision::F64);
+    let bytes_f32 = to_cache(&trees, &labels, LengthPrecision::F32);
+    assert!(bytes_f32.len() < bytes_f64.len());
+
+    let (cached, _) = read_cache_from(bytes_f32.as_slice()).unwrap();
+    for (tree, cached) in trees.iter().zip(&cached) {
+        assert!(cached.exact_eq(tree, 1e-5 * tree.height().max(1.0)));
+    }
+}
+
+#[test]
+fn test_cache_errors() {
+    let (trees, labels) = sample();
+    let bytes = to_cache(&trees, &labels, LengthPrecision::F64);
+
+    assert!(matches!(read_cache_from(&b"#NEXUS"[..]), Err(CacheError::NotACache)));
+
+    let mut other_version = bytes.clone();
+    other_version[4] = 99;
+    assert!(matches!(read_cache_from(other_version.as_slice()), Err(CacheError::UnsupportedVersion(99))));
+
+    // Flip a bit in the last branch length (before the checksum)
+    let mut corrupted = bytes.clone();
+    let position = corrupted.len() - 20;
+    corrupted[position] ^= 0x01;
+    assert!(read_cache_from(corrupted.as_slice()).is_err());
+
+    let truncated = &bytes[..bytes.len() / 2];
+    assert!(matches!(read_cache_from(truncated), Err(CacheError::Io(_))));
+
+    assert!(matches!(read_cache(cache_path("missing")), Err(CacheError::Io(_))));
+}
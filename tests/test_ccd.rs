@@ -0,0 +1,37 @@
+use nexus_parser::parse_newick_str;
+use nexus_parser::stats::ccd::Ccd;
+
+const EPSILON: f64 = 1e-12;
+
+#[test]
+fn test_ccd_score_sample() {
+    let (trees, _) = parse_newick_str("(((A,B),C),D);(((A,B),C),D);(((A,C),B),D);((A,B),(C,D));").unwrap();
+    let ccd = Ccd::from_trees(&trees);
+    assert_eq!(ccd.num_trees(), 4);
+    assert_eq!(ccd.num_taxa(), 4);
+    // ABCD, ABC, AB, AC, CD
+    assert_eq!(ccd.num_clades(), 5);
+
+    // ABCD -> ABC|D: 3/4, AB|CD: 1/4; ABC -> AB|C: 2/3, AC|B: 1/3; cherries: 1
+    let expected: [f64; 4] = [0.75 * 2.0 / 3.0, 0.75 * 2.0 / 3.0, 0.75 / 3.0, 0.25];
+    let scores = ccd.score_sample(&trees);
+    assert_eq!(scores.len(), 4);
+    for (score, expected) in scores.iter().zip(expected) {
+        assert!((score - expected.ln()).abs() < EPSILON, "{score} vs. {}", expected.ln());
+    }
+}
+
+#[test]
+fn test_ccd_unobserved_topologies() {
+    let (trees, _) = parse_newick_str("((A,B),(C,D));(((A,B),C),D);((D,C),(B,A));(((A,B),D),C);((A,(B,C)),D);").unwrap();
+    let (sample, others) = trees.split_at(2);
+    let ccd = Ccd::from_trees(sample);
+
+    // Order of children does not matter
+    assert!((ccd.log_probability(&others[0]) - 0.5f64.ln()).abs() < EPSILON);
+
+    // Unobserved splits (ABCD into ABD|C, ABCD into A|BC)
+    assert_eq!(ccd.score_sample(&others[1..]), vec![f64::NEG_INFINITY, f64::NEG_INFINITY]);
+
+    assert_eq!(Ccd::from_trees(&[]).log_probability(&trees[0]), f64::NEG_INFINITY);
+}
@@ -0,0 +1,112 @@
+#![cfg(feature = "fs")]
+
+mod common;
+use common::parse_trees;
+
+use nexus_parser::algorithms::distance_matrix::{distance_matrix, distance_matrix_checkpointed};
+use nexus_parser::algorithms::robinson_foulds::robinson_foulds;
+use nexus_parser::io::checkpoint::Checkpoint;
+use nexus_parser::model::tree::Tree;
+use std::cell::Cell;
+use std::path::PathBuf;
+use std::time::Duration;
+
+fn sample() -> Vec<Tree> {
+    parse_trees(&[
+        "(((A,B),C),(D,E));",
+        "(((A,C),B),(D,E));",
+        "(((A,B),E),(D,C));",
+        "((A,B),(C,(D,E)));",
+    ], 5)
+}
+
+/// Checkpoint path unique to the test, without leftovers of previous runs
+fn checkpoint_path(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("nexus_parser_{}_{}.ckpt", name, std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    path
+}
+
+fn rf(a: &Tree, b: &Tree) -> f64 {
+    robinson_foulds(a, b) as f64
+}
+
+#[test]
+fn test_checkpointed_matches_plain_and_removes_checkpoint() {
+    let trees = sample();
+    let path = checkpoint_path("complete");
+    let mut checkpoint = Checkpoint::new(&path).with_interval(Duration::ZERO);
+
+    let matrix = distance_matrix_checkpointed(&trees, rf, &mut checkpoint).unwrap();
+    assert_eq!(matrix, distance_matrix(&trees, rf));
+    assert_eq!(matrix[0][1], 2.0);
+    assert_eq!(matrix[1][0], 2.0);
+    assert!(!path.exists());
+}
+
+#[test]
+fn test_resume_after_crash() {
+    let trees = sample();
+    let path = checkpoint_path("resume");
+
+    // Crash while computing the third row (two rows saved)
+    let calls = Cell::new(0);
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        let mut checkpoint = Checkpoint::new(&path).with_interval(Duration::ZERO);
+        distance_matrix_checkpointed(&trees, |a, b| {
+            calls.set(calls.get() + 1);
+            if calls.get() > 5 {
+                panic!("Crash");
+            }
+            rf(a, b)
+        }, &mut checkpoint)
+    }));
+    assert!(result.is_err());
+    assert!(path.exists());
+
+    // Resumed run only computes the remaining pairs (third row)
+    let calls = Cell::new(0);
+    let mut checkpoint = Checkpoint::new(&path);
+    let matrix = distance_matrix_checkpointed(&trees, |a, b| {
+        calls.set(calls.get() + 1);
+        rf(a, b)
+    }, &mut checkpoint).unwrap();
+    assert_eq!(calls.get(), 1);
+    assert_eq!(matrix, distance_matrix(&trees, rf));
+    assert!(!path.exists());
+}
+
+#[test]
+fn test_checkpoint_of_other_sample_is_error() {
+    let trees = sample();
+    let path = checkpoint_path("other");
+    let mut checkpoint = Checkpoint::new(&path).with_interval(Duration::ZERO);
+    checkpoint.save(&format!("distance_matrix 3 {:016x}\n", Checkpoint::fingerprint(&trees[..3]))).unwrap();
+
+    let error = distance_matrix_checkpointed(&trees, rf, &mut checkpoint).unwrap_err();
+    assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+    checkpoint.remove().unwrap();
+}
+
+#[test]
+fn test_checkpoint_save_load_remove() {
+    let path = checkpoint_path("basic");
+    let mut checkpoint = Checkpoint::new(&path).with_interval(Duration::from_secs(3600));
+
+    assert_eq!(checkpoint.load().unwrap(), None);
+    assert!(!checkpoint.save_if_due(|| "too early".to_string()).unwrap());
+    checkpoint.save("state").unwrap();
+    assert_eq!(checkpoint.load().unwrap().as_deref(), Some("state"));
+
+    checkpoint.remove().unwrap();
+    assert!(!path.exists());
+    // Removing again is fine
+    checkpoint.remove().unwrap();
+}
+
+#[test]
+fn test_fingerprint() {
+    let trees = sample();
+    assert_eq!(Checkpoint::fingerprint(&trees), Checkpoint::fingerprint(&sample()));
+    assert_ne!(Checkpoint::fingerprint(&trees), Checkpoint::fingerprint(&trees[1..]));
+}
@@ -0,0 +1,75 @@
+use nexus_parser::model::clade_naming::CladeNamer;
+use nexus_parser::model::leaf_label_map::LeafLabelMap;
+use nexus_parser::model::taxon_set::TaxonSet;
+use nexus_parser::model::taxonomy::Taxonomy;
+
+const RATITES_TSV: &str = "taxon\tgenus\tfamily
+# Kiwi
+Apteryx haastii\tApteryx\tApterygidae
+Apteryx owenii\tApteryx\tApterygidae
+Apteryx mantelli\tApteryx\tApterygidae
+# Cassowaries and emus
+Casuarius casuarius\tCasuarius\tCasuariidae
+Dromaius novaehollandiae\tDromaius\tCasuariidae
+Struthio camelus\t\t
+";
+
+fn ratite_labels() -> LeafLabelMap {
+    let mut labels = LeafLabelMap::new(6);
+    for label in ["Apteryx haastii", "Apteryx owenii", "Apteryx mantelli",
+        "Casuarius casuarius", "Dromaius novaehollandiae", "Struthio camelus"] {
+        labels.get_or_insert(label);
+    }
+    labels
+}
+
+#[test]
+fn test_taxonomy_from_tsv() {
+    let taxonomy = Taxonomy::from_tsv(RATITES_TSV.as_bytes()).unwrap();
+    assert_eq!(taxonomy.ranks(), &["genus", "family"]);
+    assert_eq!(taxonomy.num_taxa(), 6);
+    assert_eq!(taxonomy.rank_index("Family"), Some(1));
+    assert_eq!(taxonomy.group("Dromaius novaehollandiae", 1), Some("Casuariidae"));
+    assert_eq!(taxonomy.group("Struthio camelus", 0), None);
+    assert_eq!(taxonomy.group("Rhea americana", 0), None);
+}
+
+#[test]
+fn test_taxonomy_from_invalid_tsv() {
+    assert!(Taxonomy::from_tsv("".as_bytes()).is_err());
+    assert!(Taxonomy::from_tsv("taxon\tgenus\nA\tX\tY\n".as_bytes()).is_err());
+    assert!(Taxonomy::from_tsv("taxon\tgenus\nA\tX\nA\tY\n".as_bytes()).is_err());
+}
+
+#[test]
+fn test_alphabetical_names() {
+    let labels = ratite_labels();
+    let namer = CladeNamer::new(&labels);
+
+    assert_eq!(namer.name(&TaxonSet::from_indices(6, [5])), "Struthio camelus");
+    assert_eq!(namer.name(&TaxonSet::from_indices(6, [4, 3])), "Casuarius casuarius+Dromaius novaehollandiae");
+    assert_eq!(namer.name(&TaxonSet::from_indices(6, [5, 1, 0])), "Apteryx haastii+Apteryx owenii (3 taxa)");
+}
+
+#[test]
+fn test_taxonomy_names() {
+    let labels = ratite_labels();
+    let taxonomy = Taxonomy::from_tsv(RATITES_TSV.as_bytes()).unwrap();
+    let namer = CladeNamer::new(&labels).with_taxonomy(&taxonomy);
+
+    assert_eq!(namer.name(&TaxonSet::from_indices(6, [0, 1, 2])), "Apteryx");
+    assert_eq!(namer.name(&TaxonSet::from_indices(6, [0, 1])), "Apteryx (part, 2/3)");
+    assert_eq!(namer.name(&TaxonSet::from_indices(6, [3, 4])), "Casuariidae");
+    // No shared group: fall back to alphabetical
+    assert_eq!(namer.name(&TaxonSet::from_indices(6, [3, 4, 5])), "Casuarius casuarius+Dromaius novaehollandiae (3 taxa)");
+}
+
+#[test]
+fn test_name_all_unique() {
+    let labels = ratite_labels();
+    let taxonomy = Taxonomy::from_tsv(RATITES_TSV.as_bytes()).unwrap();
+    let namer = CladeNamer::new(&labels).with_taxonomy(&taxonomy);
+
+    let clades = [TaxonSet::from_indices(6, [0, 1]), TaxonSet::from_indices(6, [1, 2]), TaxonSet::from_indices(6, [3, 4])];
+    assert_eq!(namer.name_all(&clades), vec!["Apteryx (part, 2/3)", "Apteryx (part, 2/3) #2", "Casuariidae"]);
+}
@@ -0,0 +1,68 @@
+use nexus_parser::model::taxon_set::TaxonSet;
+use nexus_parser::parse_newick_str;
+use nexus_parser::stats::clade_support::{annotate_support, clade_credibility, clade_support, mcc_tree};
+
+const SAMPLE: &str = "((((A,B),C),D),(E,F));\n((((A,B),C),D),(E,F));\n((((A,B),D),C),(E,F));\n(((A,(B,C)),D),(E,F));\n";
+
+#[test]
+fn test_clade_support_of_sample() {
+    let (trees, labels) = parse_newick_str(SAMPLE).unwrap();
+    let frequencies = clade_support(&trees);
+    let clade = |taxa: &[&str]| TaxonSet::from_indices(6, taxa.iter().map(|taxon| labels.get_index(taxon).unwrap()));
+
+    assert_eq!(frequencies.num_trees(), 4);
+    assert_eq!(frequencies.num_taxa(), 6);
+    assert_eq!(frequencies.frequency(&clade(&["A", "B"])), 0.75);
+    assert_eq!(frequencies.frequency(&clade(&["B", "C"])), 0.25);
+    assert_eq!(frequencies.frequency(&clade(&["A", "E"])), 0.0);
+    assert_eq!(frequencies.count(&clade(&["E", "F"])), 4);
+
+    // Most frequent first, smaller clades first among equally frequent ones
+    let sorted = frequencies.sorted();
+    assert_eq!(sorted.len(), frequencies.num_clades());
+    assert_eq!(sorted[0], (clade(&["E", "F"]), 1.0));
+    assert_eq!(sorted[1], (clade(&["A", "B", "C", "D"]), 1.0));
+    assert!(sorted.windows(2).all(|pair| pair[0].1 >= pair[1].1));
+}
+
+#[test]
+fn test_annotate_support_on_target_tree() {
+    // Target with clades the sample never saw, parsed along to share the taxa
+    let (mut trees, _) = parse_newick_str(&format!("{}((((A,B),C),E),(D,F));\n", SAMPLE)).unwrap();
+    let mut target = trees.pop().unwrap();
+    let frequencies = clade_support(&trees);
+
+    annotate_support(&mut target, &frequencies);
+    let mut supports: Vec<f64> = (0..target.num_vertices())
+        .filter(|&index| target[index].is_internal())
+        .map(|index| target[index].support().unwrap())
+        .collect();
+    supports.sort_by(f64::total_cmp);
+    assert_eq!(supports, vec![0.0, 0.0, 0.75, 0.75]);
+    assert_eq!(target.root().node_label(), None);
+    assert!(target.is_valid());
+}
+
+#[test]
+#[should_panic(expected = "same number of leaves")]
+fn test_annotate_support_panics_on_other_taxa() {
+    let (trees, _) = parse_newick_str(SAMPLE).unwrap();
+    let (mut other, _) = parse_newick_str("((A,B),C);").unwrap();
+    annotate_support(&mut other[0], &clade_support(&trees));
+}
+
+#[test]
+fn test_mcc_tree() {
+    let (trees, _) = parse_newick_str(SAMPLE).unwrap();
+    let frequencies = clade_support(&trees);
+
+    // AB and ABC in 3/4 of the trees, ABD and BC in 1/4, ABCD and EF in all
+    let expected = [0.75 * 0.75, 0.75 * 0.75, 0.75 * 0.25, 0.25 * 0.75];
+    for (tree, expected) in trees.iter().zip(expected) {
+        assert!((clade_credibility(tree, &frequencies) - f64::ln(expected)).abs() < 1e-12);
+    }
+    // First of the two best trees
+    assert_eq!(mcc_tree(&trees, &frequencies), Some(0));
+    assert_eq!(mcc_tree(&trees[2..], &frequencies), Some(0));
+    assert_eq!(mcc_tree(&[], &frequencies), None);
+}
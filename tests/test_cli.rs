@@ -0,0 +1,125 @@
+#![cfg(feature = "cli")]
+
+mod common;
+use common::fixture;
+
+use nexus_parser::{parse_newick_str, parse_nexus_file, parse_trees_bytes};
+use std::io::{ErrorKind, Write};
+use std::process::{Command, Output, Stdio};
+
+/// Runs `phylo` with the given arguments and standard input, returning its output
+fn phylo(args: &[&str], stdin: &[u8]) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_phylo"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    // Invalid arguments exit before reading standard input, closing the pipe early
+    if let Err(error) = child.stdin.take().unwrap().write_all(stdin) {
+        assert_eq!(error.kind(), ErrorKind::BrokenPipe);
+    }
+    child.wait_with_output().unwrap()
+}
+
+fn stdout(output: &Output) -> String {
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    String::from_utf8(output.stdout.clone()).unwrap()
+}
+
+#[test]
+fn test_info() {
+    let path = fixture("nexus_t2_n4_translate.trees");
+    let by_path = stdout(&phylo(&["info", path.to_str().unwrap()], b""));
+    assert!(by_path.starts_with("Trees:\t2\n"));
+    assert!(by_path.contains("Taxa:\t4\n"));
+    assert!(by_path.contains("Tree length:\tmean "));
+
+    // Same from standard input
+    let by_stdin = stdout(&phylo(&["info"], &std::fs::read(&path).unwrap()));
+    assert_eq!(by_stdin, by_path);
+}
+
+#[test]
+fn test_convert_roundtrip() {
+    let path = fixture("nexus_t11_n20_translate.trees");
+    let (trees, _) = parse_nexus_file(path.to_str().unwrap()).unwrap();
+
+    let newick = stdout(&phylo(&["convert", path.to_str().unwrap(), "--to", "newick"], b""));
+    assert_eq!(newick.lines().count(), 11);
+    let (converted, labels) = parse_newick_str(&newick).unwrap();
+    assert_eq!(converted.len(), trees.len());
+    assert_eq!(labels.num_labels(), 20);
+
+    let nexus = stdout(&phylo(&["convert", "-", "--to", "nexus"], newick.as_bytes()));
+    let (reconverted, _) = parse_trees_bytes(nexus.into_bytes()).unwrap();
+    assert_eq!(reconverted.len(), trees.len());
+}
+
+#[test]
+fn test_subsample() {
+    let path = fixture("nexus_t11_n20_translate.trees");
+    let output = std::env::temp_dir().join(format!("nexus_parser_subsample_cli_{}.trees", std::process::id()));
+    stdout(&phylo(&["subsample", path.to_str().unwrap(), "--skip-first", "1", "--burnin", "0.2", "--thin", "3",
+        "-o", output.to_str().unwrap()], b""));
+
+    // 11 trees, skip 1, burnin 2 of 10, every 3rd of 8
+    let (trees, _) = parse_nexus_file(output.to_str().unwrap()).unwrap();
+    let names: Vec<_> = trees.iter().map(|tree| tree.name().unwrap().as_str()).collect();
+    assert_eq!(names, vec!["STATE_3000", "STATE_6000", "STATE_9000"]);
+    std::fs::remove_file(&output).unwrap();
+}
+
+#[test]
+fn test_consensus() {
+    let newicks = b"(((A,B),C),D);\n(((A,B),D),C);\n((A,(B,C)),D);\n";
+    let majority = stdout(&phylo(&["consensus"], newicks));
+    assert!(majority.contains("(A,B)0.6666666666666666"), "{}", majority);
+
+    let strict = stdout(&phylo(&["consensus", "--threshold", "1"], newicks));
+    assert_eq!(strict.trim(), "(A,B,C,D);");
+
+    assert!(!phylo(&["consensus", "--threshold", "0.3"], newicks).status.success());
+}
+
+#[test]
+fn test_rfdist() {
+    let newicks = b"(((A,B),C),(D,E));\n(((A,C),B),(D,E));\n";
+    let matrix = stdout(&phylo(&["rfdist", "-"], newicks));
+    assert_eq!(matrix, "\t1\t2\n1\t0\t2\n2\t2\t0\n");
+
+    // Second file with other order of taxa
+    let other = std::env::temp_dir().join(format!("nexus_parser_rfdist_cli_{}.nwk", std::process::id()));
+    std::fs::write(&other, "((E,D),(C,(B,A)));\n").unwrap();
+    let matrix = stdout(&phylo(&["rfdist", "-", other.to_str().unwrap()], newicks));
+    assert_eq!(matrix, "\t1\n1\t0\n2\t2\n");
+
+    std::fs::write(&other, "((E,D),(C,(B,F)));\n").unwrap();
+    assert!(!phylo(&["rfdist", "-", other.to_str().unwrap()], newicks).status.success());
+    std::fs::remove_file(&other).unwrap();
+}
+
+#[test]
+fn test_subsample_streamed_to_newick() {
+    // Without burnin fraction, NEXUS input is read one tree at a time
+    let path = fixture("nexus_t11_n20_translate.trees");
+    let newick = stdout(&phylo(&["subsample", path.to_str().unwrap(), "--skip-first", "1", "--thin", "4", "--to", "newick"], b""));
+    assert_eq!(newick.lines().count(), 3);
+
+    // Same selection of Newick input
+    let newicks = b"((A,B),C);\n((A,C),B);\n((B,C),A);\n((A,B),C);\n((A,C),B);\n";
+    let selected = stdout(&phylo(&["subsample", "--burnin", "0.5", "--to", "newick"], newicks));
+    // Burnin of 2.5 trees rounded down
+    assert_eq!(selected, "((B,C),A);\n((A,B),C);\n((A,C),B);\n");
+}
+
+#[test]
+fn test_report_from_stdin() {
+    let output = std::env::temp_dir().join(format!("nexus_parser_report_cli_{}.html", std::process::id()));
+    let newicks = b"((A:1,B:1):1,C:2);\n((A:1,C:1):1,B:2);\n";
+    let message = stdout(&phylo(&["report", "-o", output.to_str().unwrap()], newicks));
+    assert!(message.starts_with("Wrote report on 2 trees"), "{}", message);
+    assert!(std::fs::read_to_string(&output).unwrap().contains("<html"));
+    std::fs::remove_file(&output).unwrap();
+}
@@ -0,0 +1,112 @@
+use nexus_parser::io::writer::newick::NewickStyle;
+use nexus_parser::model::compact_tree::CompactTree;
+use nexus_parser::parse_newick_str;
+
+#[test]
+fn test_round_trip() {
+    let input = "(((A:1,B:2):0.5,(C:1,D:1,E:0.25):1):2,F:4);";
+    let (trees, labels) = parse_newick_str(input).unwrap();
+    let compact: CompactTree = CompactTree::from(&trees[0]);
+    assert_eq!(compact.num_vertices(), trees[0].num_vertices());
+    assert_eq!(compact.num_leaves(), 6);
+
+    let tree = compact.to_tree();
+    assert!(tree.is_valid());
+    assert_eq!(tree.to_newick(&NewickStyle::Label, Some(&labels)), input);
+}
+
+#[test]
+fn test_traversals_match_tree() {
+    let (trees, _) = parse_newick_str("((A,(B,C)),(D,E,(F,G)));").unwrap();
+    let tree = &trees[0];
+    let compact = CompactTree::from_tree(tree);
+
+    // Same leaves in the same order in both traversals
+    let leaves = |labels: Vec<Option<usize>>| labels.into_iter().flatten().collect::<Vec<_>>();
+    let tree_post_order = leaves(tree.post_order_iter().map(|v| v.label_index()).collect());
+    let compact_post_order = leaves(compact.post_order_iter().map(|i| compact.label_index(i)).collect());
+    assert_eq!(compact_post_order, tree_post_order);
+    let tree_pre_order = leaves(tree.pre_order_iter().map(|v| v.label_index()).collect());
+    let compact_pre_order = leaves(compact.pre_order_iter().map(|i| compact.label_index(i)).collect());
+    assert_eq!(compact_pre_order, tree_pre_order);
+
+    // Children before parents, every vertex once, root last
+    let post_order: Vec<_> = compact.post_order_iter().collect();
+    assert_eq!(post_order.len(), compact.num_vertices());
+    assert_eq!(post_order.last(), Some(&compact.root()));
+    let mut seen = vec![false; compact.num_vertices()];
+    for index in post_order {
+        assert!(compact.children(index).all(|child| seen[child]));
+        seen[index] = true;
+    }
+}
+
+#[test]
+fn test_accessors() {
+    let (mut trees, _) = parse_newick_str("(A:1,(B:2,C:3)95:0.5);").unwrap();
+    trees[0].set_rooted(false);
+    let compact = CompactTree::from_tree(&trees[0]);
+
+    assert!(!compact.is_rooted());
+    assert_eq!(compact.parent(0), None);
+    assert_eq!(compact.branch_length(0), None);
+    assert_eq!(compact.first_child(0), Some(1));
+    assert_eq!(compact.next_sibling(1), Some(2));
+    assert_eq!(compact.next_sibling(2), None);
+    assert!(compact.is_leaf(1));
+    assert!(!compact.is_leaf(2));
+    assert_eq!(compact.branch_length(2), Some(0.5));
+    assert_eq!(compact.parent(4), Some(2));
+    assert_eq!(compact.total_branch_length(), 6.5);
+    assert_eq!(compact.to_tree().num_leaves_init(), 3);
+}
+
+#[test]
+fn test_index_types() {
+    let (trees, _) = parse_newick_str("((A:1,B:2):0.5,(C:1,D:1):1);").unwrap();
+    let default = CompactTree::from_tree(&trees[0]);
+    let small = CompactTree::<u16>::from(&trees[0]);
+    let wide: CompactTree<usize> = (&trees[0]).into();
+
+    for index in default.pre_order_iter() {
+        assert_eq!(small.parent(index), default.parent(index));
+        assert_eq!(wide.label_index(index), default.label_index(index));
+        assert_eq!(wide.children(index).collect::<Vec<_>>(), small.children(index).collect::<Vec<_>>());
+    }
+    assert_eq!(small.to_tree().total_branch_length(), 6.5);
+}
+
+#[test]
+#[should_panic(expected = "too large for u16")]
+fn test_index_type_too_small() {
+    let newick = format!("({});", (0..70_000).map(|i| format!("t{}", i)).collect::<Vec<_>>().join(","));
+    let (trees, _) = parse_newick_str(&newick).unwrap();
+    let _ = CompactTree::<u16>::from(&trees[0]);
+}
+
+#[test]
+fn test_f32_branch_lengths() {
+    let (trees, _) = parse_newick_str("((A:0.1,B):0.25,C:1e-3);").unwrap();
+    let compact = CompactTree::<u32, f32>::from(&trees[0]);
+
+    // Rounded to the nearest f32, missing lengths stay missing
+    let a = compact.children(1).next().unwrap();
+    assert_eq!(compact.branch_length(a), Some(0.1_f32 as f64));
+    assert_eq!(compact.branch_length(3), None);
+    assert!((compact.total_branch_length() - 0.351).abs() < 1e-6);
+
+    let tree = compact.to_tree();
+    assert!(tree.is_valid());
+    let (expected, _) = parse_newick_str("((A:0.1,B):0.25,C:1e-3);").unwrap();
+    assert!(tree.exact_eq(&expected[0], f32::EPSILON as f64));
+}
+
+#[test]
+fn test_f32_branch_lengths_saturate() {
+    let (trees, _) = parse_newick_str("(A:1e300,B:1);").unwrap();
+    let compact = CompactTree::<u32, f32>::from(&trees[0]);
+
+    // Lengths beyond f32::MAX are stored as f32::MAX instead of becoming infinite
+    assert_eq!(compact.branch_length(1), Some(f32::MAX as f64));
+    assert!(compact.to_tree().is_valid());
+}
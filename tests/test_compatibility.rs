@@ -0,0 +1,57 @@
+mod common;
+use common::parse_trees;
+
+#[cfg(feature = "fs")]
+use nexus_parser::algorithms::compatibility::filter_compatible;
+use nexus_parser::algorithms::compatibility::splits_compatible;
+use nexus_parser::model::taxon_set::TaxonSet;
+#[cfg(feature = "fs")]
+use nexus_parser::parse_nexus_file;
+
+#[test]
+fn test_splits_compatible() {
+    let bc = TaxonSet::from_indices(6, [1, 2]);
+    let bcd = TaxonSet::from_indices(6, [1, 2, 3]);
+    let ef = TaxonSet::from_indices(6, [4, 5]);
+    let cd = TaxonSet::from_indices(6, [2, 3]);
+
+    assert!(splits_compatible(&bc, &bcd));
+    assert!(splits_compatible(&bcd, &bc));
+    assert!(splits_compatible(&bc, &ef));
+    assert!(!splits_compatible(&bc, &cd));
+}
+
+#[test]
+fn test_is_compatible_with() {
+    let trees = parse_trees(&[
+        "((((A,B),C),D),(E,F));",
+        "((((A,C),B),D),(E,F));",
+        "(((A,B),C),D,E,F);",   // backbone with clade ABC
+        "(A,B,C,D,E,F);",       // star backbone
+        "((A,B,C),(D,E),F);",   // partially resolved tree, compatible with (AB)C
+    ], 6);
+    let (tree_1, tree_2, backbone, star, partial) = (&trees[0], &trees[1], &trees[2], &trees[3], &trees[4]);
+
+    assert!(tree_1.is_compatible_with(backbone));
+    assert!(!tree_2.is_compatible_with(backbone));
+    assert!(tree_1.is_compatible_with(star));
+    assert!(tree_2.is_compatible_with(star));
+    assert!(partial.is_compatible_with(backbone));
+    assert!(tree_1.is_compatible_with(tree_1));
+}
+
+#[cfg(feature = "fs")]
+#[test]
+fn test_filter_compatible_posterior_sample() {
+    let (trees, _) = parse_nexus_file("tests/fixtures/nexus_t11_n20_translate.trees").unwrap();
+
+    // Binary trees are only compatible with a binary backbone if they have the same splits
+    let (compatible, posterior_mass) = filter_compatible(&trees, &trees[0]);
+    assert!(!compatible.is_empty());
+    assert!(compatible.iter().all(|tree| tree.bipartitions() == trees[0].bipartitions()));
+    assert_eq!(posterior_mass, compatible.len() as f64 / trees.len() as f64);
+
+    let (compatible, posterior_mass) = filter_compatible(&[], &trees[0]);
+    assert!(compatible.is_empty());
+    assert_eq!(posterior_mass, 0.0);
+}
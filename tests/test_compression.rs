@@ -0,0 +1,83 @@
+mod common;
+use common::fixture;
+
+use nexus_parser::io::compression::{decompressed_reader, Compression};
+#[cfg(feature = "fs")]
+use nexus_parser::io::parser::nexus::TreeReader;
+#[cfg(feature = "fs")]
+use nexus_parser::{parse_nexus_file, parse_nexus_reader};
+use std::fs::File;
+use std::io::Read;
+#[cfg(not(feature = "gzip"))]
+use std::io::ErrorKind;
+
+/// Checks that the compressed fixture parses (eagerly and streamed) like the plain one.
+#[cfg(feature = "fs")]
+#[allow(dead_code)]
+fn assert_same_as_plain(compressed: &str) {
+    let (trees, labels) = parse_nexus_file(fixture("nexus_t11_n20_translate.trees").to_str().unwrap()).unwrap();
+    let (decompressed_trees, decompressed_labels) = parse_nexus_file(fixture(compressed).to_str().unwrap()).unwrap();
+    assert_eq!(decompressed_labels.labels(), labels.labels());
+    assert_eq!(decompressed_trees.len(), trees.len());
+    for (tree, decompressed) in trees.iter().zip(&decompressed_trees) {
+        assert!(decompressed.exact_eq(tree, 0.0));
+    }
+
+    let reader = TreeReader::for_file(File::open(fixture(compressed)).unwrap()).unwrap();
+    let streamed: Vec<_> = reader.map(|tree| tree.unwrap()).collect();
+    assert_eq!(streamed.len(), trees.len());
+
+    let (read_trees, _) = parse_nexus_reader(File::open(fixture(compressed)).unwrap()).unwrap();
+    assert_eq!(read_trees.len(), trees.len());
+}
+
+#[test]
+fn test_detect_compression() {
+    for (name, compression) in [
+        ("nexus_t11_n20_translate.trees", Compression::None),
+        ("nexus_t11_n20_translate.trees.gz", Compression::Gzip),
+        ("nexus_t11_n20_translate.trees.xz", Compression::Xz),
+        ("nexus_t11_n20_translate.trees.zst", Compression::Zstd),
+    ] {
+        let bytes = std::fs::read(fixture(name)).unwrap();
+        assert_eq!(Compression::detect(&bytes), compression, "{}", name);
+    }
+    assert_eq!(Compression::detect(&[]), Compression::None);
+}
+
+#[test]
+fn test_plain_input_unchanged() {
+    let mut reader = decompressed_reader(&b"#NEXUS\nBEGIN TREES;"[..]).unwrap();
+    let mut content = String::new();
+    reader.read_to_string(&mut content).unwrap();
+    assert_eq!(content, "#NEXUS\nBEGIN TREES;");
+}
+
+#[cfg(all(feature = "gzip", feature = "fs"))]
+#[test]
+fn test_gzip() {
+    assert_same_as_plain("nexus_t11_n20_translate.trees.gz");
+}
+
+#[cfg(all(feature = "xz", feature = "fs"))]
+#[test]
+fn test_xz() {
+    assert_same_as_plain("nexus_t11_n20_translate.trees.xz");
+}
+
+#[cfg(all(feature = "zstd", feature = "fs"))]
+#[test]
+fn test_zstd() {
+    assert_same_as_plain("nexus_t11_n20_translate.trees.zst");
+}
+
+#[cfg(not(feature = "gzip"))]
+#[test]
+fn test_gzip_without_feature() {
+    let file = std::io::BufReader::new(File::open(fixture("nexus_t11_n20_translate.trees.gz")).unwrap());
+    let error = decompressed_reader(file).err().unwrap();
+    assert_eq!(error.kind(), ErrorKind::Unsupported);
+    assert!(error.to_string().contains("`gzip`"));
+    #[cfg(feature = "fs")]
+    assert!(parse_nexus_file(fixture("nexus_t11_n20_translate.trees.gz").to_str().unwrap()).is_err());
+}
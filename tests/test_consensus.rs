@@ -0,0 +1,83 @@
+mod common;
+use common::parse_trees;
+
+use nexus_parser::algorithms::consensus::{clade_frequencies, majority_rule, strict};
+use nexus_parser::io::writer::newick::NewickStyle;
+use nexus_parser::model::taxon_set::TaxonSet;
+use nexus_parser::model::tree::Tree;
+#[cfg(feature = "fs")]
+use nexus_parser::parse_nexus_file;
+
+fn sample() -> Vec<Tree> {
+    parse_trees(&[
+        "((((A,B),C),D),(E,F));",
+        "((((A,B),C),D),(E,F));",
+        "((((A,B),D),C),(E,F));",
+        "(((A,(B,C)),D),(E,F));",
+    ], 6)
+}
+
+#[test]
+fn test_clade_frequencies() {
+    let frequencies = clade_frequencies(&sample());
+
+    assert_eq!(frequencies[&TaxonSet::from_indices(6, [0, 1])], 3);
+    assert_eq!(frequencies[&TaxonSet::from_indices(6, [0, 1, 2])], 3);
+    assert_eq!(frequencies[&TaxonSet::from_indices(6, [0, 1, 2, 3])], 4);
+    assert_eq!(frequencies[&TaxonSet::from_indices(6, [4, 5])], 4);
+    assert_eq!(frequencies[&TaxonSet::from_indices(6, [1, 2])], 1);
+    assert!(!frequencies.contains_key(&TaxonSet::full(6)));
+}
+
+#[test]
+fn test_majority_rule() {
+    let trees = sample();
+
+    let consensus = majority_rule(&trees, 0.5);
+    assert!(consensus.is_valid());
+    assert_eq!(consensus.to_newick(&NewickStyle::ZeroIndexed, None), "((((0,1)0.75,2)0.75,3)1,(4,5)1);");
+
+    // Higher threshold drops clades with frequency 0.75
+    let consensus = majority_rule(&trees, 0.8);
+    assert!(consensus.is_valid());
+    assert_eq!(consensus.to_newick(&NewickStyle::ZeroIndexed, None), "((0,1,2,3)1,(4,5)1);");
+}
+
+#[test]
+fn test_strict() {
+    let trees = sample();
+
+    let consensus = strict(&trees);
+    assert!(consensus.is_valid());
+    assert_eq!(consensus.to_newick(&NewickStyle::ZeroIndexed, None), "((0,1,2,3)1,(4,5)1);");
+    for index in 0..consensus.num_vertices() {
+        if consensus[index].is_internal() {
+            assert_eq!(consensus[index].support(), Some(1.0));
+        }
+    }
+
+    // Strict consensus of a single tree is the tree itself
+    let consensus = strict(&trees[..1]);
+    assert_eq!(consensus.clades(6).iter().filter(|c| c.len() > 1).count(), 5);
+}
+
+#[cfg(feature = "fs")]
+#[test]
+fn test_consensus_posterior_sample() {
+    let (trees, _) = parse_nexus_file("tests/fixtures/nexus_t11_n20_translate.trees").unwrap();
+
+    let majority = majority_rule(&trees, 0.5);
+    let strict = strict(&trees);
+    assert!(majority.is_valid());
+    assert!(strict.is_valid());
+    assert_eq!(majority.num_leaves(), 20);
+    // Strict consensus is never more resolved than majority-rule consensus
+    assert!(strict.num_internal() <= majority.num_internal());
+    assert!(strict.bipartitions().is_subset(&majority.bipartitions()));
+}
+
+#[test]
+#[should_panic]
+fn test_majority_rule_threshold_too_low() {
+    majority_rule(&sample(), 0.3);
+}
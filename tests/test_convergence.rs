@@ -0,0 +1,101 @@
+use nexus_parser::model::tree::Tree;
+use nexus_parser::parse_newick_str;
+use nexus_parser::stats::convergence::{asdsf, compare_samples, pseudo_ess, split_ess, topological_ess};
+
+const EPSILON: f64 = 1e-9;
+
+/// Parses trees over the taxa A to E, alternating between the given topologies in runs of the given length.
+fn chain(topologies: &[&str], run_length: usize, num_trees: usize) -> Vec<Tree> {
+    let newick: String = (0..num_trees).map(|i| topologies[(i / run_length) % topologies.len()]).collect();
+    parse_newick_str(&format!("(A,B,C,D,E);{}", newick)).unwrap().0.split_off(1)
+}
+
+#[test]
+fn test_topological_ess_of_well_and_poorly_mixing_chains() {
+    let topologies = ["(((A,B),C),(D,E));", "(((A,C),B),(D,E));", "((A,B),(C,(D,E)));"];
+    let well_mixing = chain(&topologies, 1, 60);
+    let poorly_mixing = chain(&topologies, 20, 60);
+
+    let good = topological_ess(&well_mixing, &well_mixing[0]).unwrap();
+    let poor = topological_ess(&poorly_mixing, &poorly_mixing[0]).unwrap();
+    assert!(good > poor, "{} vs. {}", good, poor);
+    assert!(good <= 60.0);
+
+    // Identical trees have a constant distance trace
+    let constant = chain(&topologies[..1], 1, 10);
+    assert_eq!(topological_ess(&constant, &constant[3]), Some(10.0));
+    assert_eq!(topological_ess(&constant[..1], &constant[0]), None);
+}
+
+#[test]
+fn test_pseudo_ess() {
+    let topologies = ["(((A,B),C),(D,E));", "(((A,C),B),(D,E));"];
+    let well_mixing = chain(&topologies, 1, 40);
+    let poorly_mixing = chain(&topologies, 10, 40);
+
+    assert!(pseudo_ess(&well_mixing, 10).unwrap() > pseudo_ess(&poorly_mixing, 10).unwrap());
+    // More focal trees than trees are capped
+    assert!(pseudo_ess(&poorly_mixing[..3], 10).is_some());
+    assert_eq!(pseudo_ess(&poorly_mixing[..1], 10), None);
+}
+
+#[test]
+fn test_split_ess() {
+    let trees = chain(&["(((A,B),C),(D,E));", "(((A,C),B),(D,E));"], 5, 20);
+    let ess = split_ess(&trees, 0.1);
+
+    // AB, AC and ABC (= DE) observed; DE in every tree, so its ESS is maximal
+    assert_eq!(ess.len(), 3);
+    assert!(ess[0].1 < 20.0);
+    assert_eq!(ess[2].1, 20.0);
+    assert_eq!(ess[2].0.len(), 2);
+
+    assert_eq!(split_ess(&trees, 0.6).len(), 1);
+    assert!(split_ess(&trees[..1], 0.1).is_empty());
+}
+
+#[test]
+fn test_asdsf() {
+    let topologies = ["(((A,B),C),(D,E));", "(((A,C),B),(D,E));"];
+    let run_1 = chain(&topologies, 1, 20);
+    let run_2 = chain(&topologies, 2, 20);
+
+    // Same split frequencies
+    assert!(asdsf(&run_1, &run_2, 0.1).unwrap().abs() < EPSILON);
+
+    // AB: 0.5 vs. 1.0, AC: 0.5 vs. 0.0, DE: 1.0 vs. 1.0
+    let run_3 = chain(&topologies[..1], 1, 10);
+    let expected = (0.5 + 0.5 + 0.0) / 3.0 / 2f64.sqrt();
+    assert!((asdsf(&run_1, &run_3, 0.1).unwrap() - expected).abs() < EPSILON);
+
+    // AC is not frequent enough in either run
+    let expected = (0.5 + 0.0) / 2.0 / 2f64.sqrt();
+    assert!((asdsf(&run_1, &run_3, 0.9).unwrap() - expected).abs() < EPSILON);
+    assert_eq!(asdsf(&run_1, &[], 0.1), None);
+}
+
+#[test]
+fn test_compare_samples() {
+    let topologies = ["(((A,B),C),(D,E));", "(((A,C),B),(D,E));"];
+    let run_1 = chain(&topologies, 1, 20);
+    let run_2 = chain(&topologies[..1], 1, 10);
+    let comparison = compare_samples(&run_1, &run_2);
+    assert_eq!(comparison.num_trees(), (20, 10));
+
+    // AB: 0.5 vs. 1.0, AC: 0.5 vs. 0.0, ABC and DE: 1.0 vs. 1.0
+    assert_eq!(comparison.clades().len(), 4);
+    assert!(comparison.clades()[..2].iter().all(|(_, frequency, _)| (frequency - 0.5).abs() < EPSILON));
+    assert!(comparison.clades()[2..].iter().all(|(_, frequency, other_frequency)| frequency == other_frequency));
+    assert!((comparison.asdsf() - 1.0 / 4.0 / 2f64.sqrt()).abs() < EPSILON);
+    // Unrooted, the clades ABC and DE are the same split
+    assert!((asdsf(&run_1, &run_2, 0.1).unwrap() - 1.0 / 3.0 / 2f64.sqrt()).abs() < EPSILON);
+
+    assert_eq!(comparison.disagreements(0.4).len(), 2);
+    assert!(comparison.disagreements(0.5).is_empty());
+
+    // Identical runs agree on all clades
+    let same = compare_samples(&run_1, &run_1);
+    assert_eq!(same.asdsf(), 0.0);
+    assert!(same.disagreements(0.0).is_empty());
+    assert_eq!(compare_samples(&[], &[]).clades().len(), 0);
+}
@@ -0,0 +1,149 @@
+mod common;
+use common::parse_trees_with_labels;
+
+use nexus_parser::algorithms::embedding::{feature_matrix, write_feature_tsv, FeatureScheme, SplitDictionary};
+use nexus_parser::model::taxon_set::TaxonSet;
+
+#[test]
+fn test_kendall_colijn_vector() {
+    let (trees, _) = parse_trees_with_labels(&["(((A:1,B:1):1,C:2):1,D:3);"], 4);
+    let tree = &trees[0];
+
+    // Pairs AB, AC, AD, BC, BD, CD, then A, B, C, D
+    let topology = tree.to_feature_vector(&FeatureScheme::KendallColijn { lambda: 0.0 });
+    assert_eq!(topology, vec![2.0, 1.0, 0.0, 1.0, 0.0, 0.0, 1.0, 1.0, 1.0, 1.0]);
+
+    let lengths = tree.to_feature_vector(&FeatureScheme::KendallColijn { lambda: 1.0 });
+    assert_eq!(lengths, vec![2.0, 1.0, 0.0, 1.0, 0.0, 0.0, 1.0, 1.0, 2.0, 3.0]);
+
+    let mixed = tree.to_feature_vector(&FeatureScheme::KendallColijn { lambda: 0.5 });
+    assert_eq!(mixed[8], 1.5);
+}
+
+#[test]
+fn test_kendall_colijn_distinguishes_rootings() {
+    // Same unrooted tree, different roots
+    let (trees, _) = parse_trees_with_labels(&["((A,B),(C,D));", "(A,(B,(C,D)));"], 4);
+    let scheme = FeatureScheme::KendallColijn { lambda: 0.0 };
+    assert_ne!(trees[0].to_feature_vector(&scheme), trees[1].to_feature_vector(&scheme));
+}
+
+#[test]
+#[should_panic(expected = "Lambda")]
+fn test_kendall_colijn_invalid_lambda() {
+    let (trees, _) = parse_trees_with_labels(&["((A,B),C);"], 3);
+    trees[0].to_feature_vector(&FeatureScheme::KendallColijn { lambda: 1.5 });
+}
+
+#[test]
+fn test_pairwise_distances() {
+    let (trees, _) = parse_trees_with_labels(&["(((A:1,B:1):1,C:2):1,D:3);"], 4);
+    let tree = &trees[0];
+
+    let patristic = tree.to_feature_vector(&FeatureScheme::PairwiseDistances { topological: false });
+    assert_eq!(patristic, vec![2.0, 4.0, 6.0, 4.0, 6.0, 6.0]);
+
+    let topological = tree.to_feature_vector(&FeatureScheme::PairwiseDistances { topological: true });
+    assert_eq!(topological, vec![2.0, 3.0, 4.0, 3.0, 4.0, 3.0]);
+}
+
+#[test]
+fn test_split_indicators() {
+    let (trees, _) = parse_trees_with_labels(&[
+        "(((A,B),C),(D,E));",
+        "(((A,B),C),(D,E));",
+        "(((A,C),B),(D,E));",
+    ], 5);
+
+    let dictionary = SplitDictionary::from_trees(&trees);
+    // AB|CDE and DE|ABC in two resp. three trees, AC|BDE in one
+    assert_eq!(dictionary.len(), 3);
+    assert_eq!(dictionary.splits()[0], TaxonSet::from_indices(5, [3, 4]));
+    assert_eq!(dictionary.index_of(&TaxonSet::from_indices(5, [2, 3, 4])), Some(1));
+
+    let matrix = feature_matrix(&trees, &FeatureScheme::SplitIndicators(&dictionary));
+    assert_eq!(matrix, vec![
+        vec![1.0, 1.0, 0.0],
+        vec![1.0, 1.0, 0.0],
+        vec![1.0, 0.0, 1.0],
+    ]);
+}
+
+#[test]
+fn test_split_indicators_ignore_unknown_splits() {
+    let (trees, _) = parse_trees_with_labels(&["(((A,B),C),(D,E));"], 5);
+    let dictionary = SplitDictionary::new([TaxonSet::from_indices(5, [1, 2])]);
+
+    let vector = trees[0].to_feature_vector(&FeatureScheme::SplitIndicators(&dictionary));
+    assert_eq!(vector, vec![0.0]);
+}
+
+#[test]
+fn test_feature_names_match_vector_length() {
+    let (trees, label_map) = parse_trees_with_labels(&["((A,B),C);"], 3);
+    let dictionary = SplitDictionary::from_trees(&trees);
+
+    for scheme in [
+        FeatureScheme::KendallColijn { lambda: 0.5 },
+        FeatureScheme::SplitIndicators(&dictionary),
+        FeatureScheme::PairwiseDistances { topological: true },
+    ] {
+        let names = scheme.feature_names(&label_map);
+        assert_eq!(names.len(), trees[0].to_feature_vector(&scheme).len());
+        assert_eq!(names.len(), scheme.num_features(3));
+    }
+
+    let names = FeatureScheme::PairwiseDistances { topological: true }.feature_names(&label_map);
+    assert_eq!(names, vec!["A|B", "A|C", "B|C"]);
+}
+
+#[test]
+fn test_write_feature_tsv() {
+    let (trees, label_map) = parse_trees_with_labels(&["((A:1,B:1):1,C:2);", "((A:1,C:1):1,B:2);"], 3);
+
+    let mut output = Vec::new();
+    write_feature_tsv(&trees, &FeatureScheme::PairwiseDistances { topological: false }, &label_map, &mut output).unwrap();
+
+    let output = String::from_utf8(output).unwrap();
+    assert_eq!(output, "tree\tA|B\tA|C\tB|C\n0\t2\t4\t4\n1\t4\t2\t4\n");
+}
+
+#[cfg(feature = "ndarray")]
+#[test]
+fn test_feature_array() {
+    use nexus_parser::algorithms::embedding::feature_array;
+
+    let (trees, _) = parse_trees_with_labels(&["((A:1,B:1):1,C:2);", "((A:1,C:1):1,B:2);"], 3);
+    let array = feature_array(&trees, &FeatureScheme::PairwiseDistances { topological: false });
+
+    assert_eq!(array.shape(), &[2, 3]);
+    assert_eq!(array[[1, 1]], 2.0);
+}
+
+#[cfg(feature = "parquet")]
+#[test]
+fn test_write_feature_parquet() {
+    use arrow_array::{Array, Float64Array, StringArray};
+    use nexus_parser::algorithms::embedding::write_feature_parquet;
+    use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+
+    let (trees, label_map) = parse_trees_with_labels(&["((A:1,B:1):1,C:2);", "((A:1,C:1):1,B:2);"], 3);
+    let path = std::env::temp_dir().join(format!("nexus_parser_features_{}.parquet", std::process::id()));
+    write_feature_parquet(&trees, &FeatureScheme::PairwiseDistances { topological: false }, &label_map,
+                          std::fs::File::create(&path).unwrap()).unwrap();
+
+    let reader = ParquetRecordBatchReaderBuilder::try_new(std::fs::File::open(&path).unwrap()).unwrap().build().unwrap();
+    let batches: Vec<_> = reader.map(Result::unwrap).collect();
+    std::fs::remove_file(&path).unwrap();
+
+    // Same table as TSV: tree names, then A|B, A|C, B|C
+    let batch = &batches[0];
+    let columns: Vec<&str> = batch.schema_ref().fields().iter().map(|field| field.name().as_str()).collect();
+    assert_eq!(columns, vec!["tree", "A|B", "A|C", "B|C"]);
+    assert_eq!(batch.num_rows(), 2);
+    let names = batch.column(0).as_any().downcast_ref::<StringArray>().unwrap();
+    assert_eq!((names.value(0), names.value(1)), ("0", "1"));
+    let a_c = batch.column(2).as_any().downcast_ref::<Float64Array>().unwrap();
+    assert_eq!(a_c.values().to_vec(), vec![4.0, 2.0]);
+    assert_eq!(a_c.null_count(), 0);
+}
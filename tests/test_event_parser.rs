@@ -0,0 +1,138 @@
+use nexus_parser::io::parser::nexus::{NexusEventParser, TreeEvent};
+use nexus_parser::io::parser::parsing_error::ParsingErrorType;
+use nexus_parser::io::writer::newick::NewickStyle;
+use nexus_parser::parse_nexus_bytes;
+use std::cell::RefCell;
+use std::path::Path;
+
+/// Feeds the bytes in chunks of the given size and returns the Newick strings of all trees reported.
+fn feed_in_chunks(bytes: &[u8], chunk_size: usize) -> Vec<String> {
+    let mut newicks = Vec::new();
+    let mut parser = NexusEventParser::new(|event| {
+        if let TreeEvent::EndTree(tree) = event {
+            newicks.push(tree.to_newick(&NewickStyle::ZeroIndexed, None));
+        }
+    });
+    for chunk in bytes.chunks(chunk_size) {
+        parser.feed(chunk).unwrap();
+    }
+    parser.finish().unwrap();
+    newicks
+}
+
+#[test]
+fn test_event_parser_matches_parser() {
+    for file in ["nexus_t11_n20_translate.trees", "nexus_t3_n10_comments.trees", "nexus_t2_n4_beast.trees", "nexus_t2_n4_mrbayes.trees"] {
+        let bytes = std::fs::read(Path::new("tests").join("fixtures").join(file)).unwrap();
+        let (trees, _) = parse_nexus_bytes(&bytes).unwrap();
+        let expected: Vec<String> = trees.iter().map(|tree| tree.to_newick(&NewickStyle::ZeroIndexed, None)).collect();
+
+        for chunk_size in [1, 7, 100, bytes.len()] {
+            assert_eq!(feed_in_chunks(&bytes, chunk_size), expected, "{} in chunks of {}", file, chunk_size);
+        }
+    }
+}
+
+#[test]
+fn test_event_parser_events() {
+    let nexus = "#NEXUS\nBEGIN TREES;\nTRANSLATE 1 'Homo; sapiens', 2 Pan, 3 Gorilla;\nTREE t1 = [&U] ((1:1,2:1)clade:0.5,3:1.5);\nEND;\n";
+    let mut events = Vec::new();
+    let mut parser = NexusEventParser::new(|event| events.push(match event {
+        TreeEvent::EndTree(tree) => format!("EndTree with {} vertices", tree.num_vertices()),
+        event => format!("{:?}", event),
+    }));
+    for chunk in nexus.as_bytes().chunks(5) {
+        parser.feed(chunk).unwrap();
+    }
+    assert_eq!(parser.num_trees(), 1);
+    let labels = parser.finish().unwrap();
+    assert_eq!(labels.get_index("Homo; sapiens"), Some(0));
+
+    assert_eq!(events, vec![
+        "BeginTree { name: \"t1\", rooted: false }",
+        "Leaf { index: 0, label: \"Homo; sapiens\", label_index: 0, branch_length: Some(1.0) }",
+        "Leaf { index: 1, label: \"Pan\", label_index: 1, branch_length: Some(1.0) }",
+        "Internal { index: 2, children: [0, 1], label: Some(\"clade\"), branch_length: Some(0.5) }",
+        "Leaf { index: 3, label: \"Gorilla\", label_index: 2, branch_length: Some(1.5) }",
+        "Internal { index: 4, children: [2, 3], label: None, branch_length: None }",
+        "EndTree with 5 vertices",
+    ]);
+}
+
+#[test]
+fn test_event_parser_reports_vertices_before_tree_ends() {
+    let events = RefCell::new(Vec::new());
+    let mut parser = NexusEventParser::new(|event| events.borrow_mut().push(match event {
+        TreeEvent::BeginTree { .. } => "Begin".to_string(),
+        TreeEvent::Leaf { label, .. } => label.to_string(),
+        TreeEvent::Internal { children, .. } => format!("{:?}", children),
+        TreeEvent::EndTree(_) => "End".to_string(),
+    }));
+
+    parser.feed(b"#NEXUS\nBEGIN TREES;\nTREE t = ((A:1,B").unwrap();
+    assert_eq!(*events.borrow(), vec!["Begin", "A"]);
+    parser.feed(b"ee:1),C").unwrap();
+    assert_eq!(*events.borrow(), vec!["Begin", "A", "Bee", "[0, 1]"]);
+    parser.feed(b");\nEND;\n").unwrap();
+    assert_eq!(*events.borrow(), vec!["Begin", "A", "Bee", "[0, 1]", "C", "[2, 3]", "End"]);
+    assert_eq!(parser.finish().unwrap().num_labels(), 3);
+}
+
+#[test]
+fn test_event_parser_many_trees_in_one_chunk() {
+    let mut nexus = String::from("#NEXUS\nBEGIN TREES;\n");
+    for i in 0..2000 {
+        nexus.push_str(&format!("TREE t{} = ((A:{},B:1):1,C:2);\n", i, i));
+    }
+    nexus.push_str("END;\n");
+
+    let mut num_ended = 0;
+    let mut parser = NexusEventParser::new(|event| {
+        if let TreeEvent::EndTree(_) = event {
+            num_ended += 1;
+        }
+    });
+    parser.feed(nexus.as_bytes()).unwrap();
+    assert_eq!(parser.num_trees(), 2000);
+    parser.finish().unwrap();
+    assert_eq!(num_ended, 2000);
+}
+
+#[test]
+fn test_event_parser_errors() {
+    // Malformed trees are skipped, later trees still reported
+    let bytes = std::fs::read(Path::new("tests").join("fixtures").join("nexus_t6_n4_malformed.trees")).unwrap();
+    let mut names = Vec::new();
+    let mut parser = NexusEventParser::new(|event| match event {
+        TreeEvent::BeginTree { name, .. } => names.push(format!("Begin {}", name)),
+        TreeEvent::EndTree(tree) => names.push(format!("End {}", tree.name().unwrap())),
+        _ => {}
+    });
+    let error = parser.feed(&bytes).unwrap_err();
+    assert!(error.to_string().contains("TREE_1"), "{}", error);
+    assert_eq!(parser.num_trees(), 1);
+
+    // Remaining input is kept, so feeding nothing continues after the error
+    let mut errors = vec![error];
+    while let Err(error) = parser.feed(&[]) {
+        errors.push(error);
+    }
+    assert_eq!(parser.num_trees(), 2);
+    parser.finish().unwrap();
+    assert_eq!(errors.len(), 3);
+    assert!(errors[1].to_string().contains("TREE_3"), "{}", errors[1]);
+    assert!(errors[2].to_string().contains("TREE_5"), "{}", errors[2]);
+    // Malformed trees end without EndTree (TREE_2 is skipped with the unterminated TREE_1)
+    assert_eq!(names, vec!["Begin TREE_0", "End TREE_0", "Begin TREE_1", "Begin TREE_3", "Begin TREE_4", "End TREE_4", "Begin TREE_5"]);
+
+    // Missing header is fatal
+    let mut parser = NexusEventParser::new(|_| panic!("No events expected"));
+    let error = parser.feed(b"BEGIN TREES;\nTREE t = (A,B);\n").unwrap_err();
+    assert_eq!(error.kind(), &ParsingErrorType::MissingNexusHeader);
+    assert!(parser.feed(b"TREE t = (A,B);\n").is_ok());
+
+    // Input ending within a tree
+    let mut parser = NexusEventParser::new(|_| {});
+    parser.feed(b"#NEXUS\nBEGIN TREES;\nTREE t = ((A,B),").unwrap();
+    assert!(parser.finish().is_err());
+}
@@ -0,0 +1,63 @@
+use nexus_parser::io::parser::fasta;
+use nexus_parser::io::parser::parsing_error::ParsingErrorType;
+use nexus_parser::model::alignment::DataType;
+use nexus_parser::model::leaf_label_map::LeafLabelMap;
+#[cfg(feature = "fs")]
+use nexus_parser::{parse_fasta_file, parse_nexus_file};
+#[cfg(feature = "fs")]
+use std::path::Path;
+
+fn fasta_error(fasta: &str) -> String {
+    let error = fasta::parse_str(fasta, DataType::Dna, &mut LeafLabelMap::new(0)).expect_err("Invalid FASTA is rejected");
+    assert!(matches!(error.kind(), ParsingErrorType::InvalidFasta(_)), "{}", error);
+    error.to_string()
+}
+
+#[cfg(feature = "fs")]
+#[test]
+fn test_fasta_file_joined_with_trees() {
+    let trees_path = Path::new("tests").join("fixtures").join("nexus_t2_n4_mrbayes.trees");
+    let (trees, mut labels) = parse_nexus_file(trees_path.to_str().unwrap()).unwrap();
+
+    let path = Path::new("tests").join("fixtures").join("fasta_n4.fasta");
+    let alignment = parse_fasta_file(path.to_str().unwrap(), DataType::Dna, &mut labels).unwrap();
+
+    // All sequences belong to taxa of the trees
+    assert_eq!(labels.num_labels(), 4);
+    assert_eq!(alignment.num_sequences(), 4);
+    assert_eq!(alignment.num_sites(), 12);
+    for tree in &trees {
+        for index in 0..tree.num_vertices() {
+            if let Some(label_index) = tree[index].label_index() {
+                assert!(alignment.contains(label_index));
+            }
+        }
+    }
+    let sequence = |label: &str| alignment.sequence(labels.get_index(label).unwrap()).unwrap();
+    assert_eq!(sequence("Homo_sapiens"), b"ACGTACGTTTGA");
+    assert_eq!(sequence("Pan_troglodytes"), b"ACGT-CGTTTAA");
+    assert_eq!(sequence("Pongo_abelii"), b"ACGTAC?TTTGA");
+}
+
+#[test]
+fn test_fasta_names_and_data_types() {
+    let mut labels = LeafLabelMap::new(0);
+    labels.get_or_insert("B");
+    let alignment = fasta::parse_str(">A\r\n01-?\r\n\r\n>B  second\r\n1 1 0 0\r\n", DataType::Standard, &mut labels).unwrap();
+    assert_eq!(labels.labels(), &["B", "A"]);
+    assert_eq!(alignment.sequence(0), Some(&b"1100"[..]));
+    assert_eq!(alignment.sequence(1), Some(&b"01-?"[..]));
+
+    let alignment = fasta::parse_str(">A\nmkv\n", DataType::Protein, &mut labels).unwrap();
+    assert_eq!(alignment.sequence(1), Some(&b"MKV"[..]));
+}
+
+#[test]
+fn test_invalid_fasta() {
+    assert!(fasta_error("").contains("No sequences"));
+    assert!(fasta_error("ACGT\n>A\nACGT\n").contains("Expected '>'"));
+    assert!(fasta_error("> \nACGT\n").contains("Missing sequence name"));
+    assert!(fasta_error(">A\nACGT\n>A\nACGT\n").contains("Duplicate sequence 'A'"));
+    assert!(fasta_error(">A\nACGT\n>B\nACG\n>C\nACGT\n").contains("'B' has 3 characters, expected 4"));
+    assert!(fasta_error(">A\nACGT\n>B\nACGTA").contains("'B' has 5 characters"));
+}
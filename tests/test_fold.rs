@@ -0,0 +1,103 @@
+#[cfg(feature = "fs")]
+use nexus_parser::io::parser::nexus::TreeReader;
+use nexus_parser::model::tree::Tree;
+#[cfg(feature = "fs")]
+use nexus_parser::parse_nexus_file;
+#[cfg(feature = "fs")]
+use nexus_parser::stats::fold::fold_stream;
+use nexus_parser::stats::fold::{fold_trees, TreeFold};
+#[cfg(feature = "fs")]
+use std::fs::File;
+
+/// Counts trees
+struct Count;
+
+impl TreeFold for Count {
+    type Accumulator = usize;
+    type Output = usize;
+
+    fn init(&self) -> usize { 0 }
+    fn accumulate(&self, accumulator: &mut usize, _: &Tree) { *accumulator += 1; }
+    fn merge(&self, accumulator: usize, other: usize) -> usize { accumulator + other }
+    fn finalize(&self, accumulator: usize) -> usize { accumulator }
+}
+
+/// Maximum total branch length
+struct MaxLength;
+
+impl TreeFold for MaxLength {
+    type Accumulator = Option<f64>;
+    type Output = Option<f64>;
+
+    fn init(&self) -> Option<f64> { None }
+    fn accumulate(&self, accumulator: &mut Option<f64>, tree: &Tree) {
+        let length = tree.total_branch_length();
+        *accumulator = Some(accumulator.map_or(length, |max| max.max(length)));
+    }
+    fn merge(&self, accumulator: Option<f64>, other: Option<f64>) -> Option<f64> {
+        match (accumulator, other) {
+            (Some(a), Some(b)) => Some(a.max(b)),
+            (a, b) => a.or(b),
+        }
+    }
+    fn finalize(&self, accumulator: Option<f64>) -> Option<f64> { accumulator }
+}
+
+#[cfg(feature = "fs")]
+const FILE: &str = "tests/fixtures/nexus_t11_n20_translate.trees";
+
+#[cfg(feature = "fs")]
+fn expected_max_length(trees: &[Tree]) -> f64 {
+    trees.iter().map(Tree::total_branch_length).fold(f64::MIN, f64::max)
+}
+
+#[cfg(feature = "fs")]
+#[test]
+fn test_fold_trees_sequential_and_parallel() {
+    let (trees, _) = parse_nexus_file(FILE).unwrap();
+    let expected = expected_max_length(&trees);
+
+    for num_threads in [0, 1, 3, 32] {
+        let (count, max) = fold_trees(&(Count, MaxLength), &trees, num_threads);
+        assert_eq!(count, trees.len());
+        assert_eq!(max, Some(expected));
+    }
+}
+
+#[test]
+fn test_fold_trees_empty() {
+    assert_eq!(fold_trees(&(Count, MaxLength), &[], 4), (0, None));
+}
+
+#[cfg(feature = "fs")]
+#[test]
+fn test_fold_stream_matches_in_memory() {
+    let (trees, _) = parse_nexus_file(FILE).unwrap();
+
+    for num_threads in [1, 4] {
+        let reader = TreeReader::for_file(File::open(FILE).unwrap()).unwrap();
+        let (count, max, _) = fold_stream(&(Count, MaxLength, Count), reader, num_threads).unwrap();
+        assert_eq!(count, trees.len());
+        assert_eq!(max, Some(expected_max_length(&trees)));
+    }
+}
+
+#[cfg(feature = "fs")]
+#[test]
+fn test_fold_stream_many_trees() {
+    let (trees, _) = parse_nexus_file(FILE).unwrap();
+    let many: Vec<Result<Tree, String>> = trees.iter().cycle().take(1000).cloned().map(Ok).collect();
+
+    assert_eq!(fold_stream(&Count, many, 4), Ok(1000));
+}
+
+#[cfg(feature = "fs")]
+#[test]
+fn test_fold_stream_stops_at_error() {
+    let (trees, _) = parse_nexus_file(FILE).unwrap();
+    let mut stream: Vec<Result<Tree, String>> = trees.into_iter().map(Ok).collect();
+    stream.insert(3, Err("broken tree".to_string()));
+
+    assert_eq!(fold_stream(&Count, stream.clone(), 1), Err("broken tree".to_string()));
+    assert_eq!(fold_stream(&Count, stream, 4), Err("broken tree".to_string()));
+}
@@ -0,0 +1,82 @@
+mod common;
+#[cfg(feature = "fs")]
+use common::fixture;
+
+use nexus_parser::io::format::TreeFileFormat;
+use nexus_parser::parse_trees_bytes;
+#[cfg(feature = "fs")]
+use nexus_parser::{parse_newick_file, parse_nexus_file, parse_trees_file};
+#[cfg(feature = "fs")]
+use std::path::PathBuf;
+
+/// Writes the content to a temporary file unique to the test
+#[cfg(feature = "fs")]
+fn temp_file(name: &str, content: &[u8]) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("nexus_parser_{}_{}", std::process::id(), name));
+    std::fs::write(&path, content).unwrap();
+    path
+}
+
+#[test]
+fn test_detect() {
+    assert_eq!(TreeFileFormat::detect(b"#nexus\n"), Some(TreeFileFormat::Nexus));
+    assert_eq!(TreeFileFormat::detect(b"\xEF\xBB\xBF#NEXUS\n"), Some(TreeFileFormat::Nexus));
+    assert_eq!(TreeFileFormat::detect(b"[comment]((A,B),C);"), Some(TreeFileFormat::Newick));
+    assert_eq!(TreeFileFormat::detect(b"\t<nex:nexml>"), Some(TreeFileFormat::Xml));
+    assert_eq!(TreeFileFormat::detect(b"#NEX"), None);
+    assert_eq!(TreeFileFormat::detect(b"  \n"), None);
+    assert_eq!(TreeFileFormat::detect(b""), None);
+}
+
+#[cfg(feature = "fs")]
+#[test]
+fn test_parse_trees_file_dispatches() {
+    for name in ["nexus_t1_n10.trees", "nexus_t11_n20_translate.trees", "nexus_t3_n10_comments.trees"] {
+        let path = fixture(name);
+        let (trees, labels) = parse_trees_file(path.to_str().unwrap()).unwrap();
+        let (expected_trees, expected_labels) = parse_nexus_file(path.to_str().unwrap()).unwrap();
+        assert_eq!(trees.len(), expected_trees.len());
+        assert_eq!(labels.labels(), expected_labels.labels());
+    }
+
+    let path = fixture("newick_t4_n10.nwk");
+    let (trees, labels) = parse_trees_file(path.to_str().unwrap()).unwrap();
+    let (expected_trees, expected_labels) = parse_newick_file(path.to_str().unwrap()).unwrap();
+    assert_eq!(trees.len(), expected_trees.len());
+    assert_eq!(labels.labels(), expected_labels.labels());
+}
+
+#[cfg(feature = "fs")]
+#[test]
+fn test_parse_trees_file_with_bom() {
+    let path = temp_file("bom.trees", b"\xEF\xBB\xBF#NEXUS\nBegin trees;\n\ttree t = ((A,B),C);\nEnd;\n");
+    let (trees, labels) = parse_trees_file(path.to_str().unwrap()).unwrap();
+    assert_eq!(trees.len(), 1);
+    assert_eq!(labels.num_labels(), 3);
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn test_parse_trees_bytes() {
+    let (trees, labels) = parse_trees_bytes(b"\xEF\xBB\xBF ((A,B),C);\n(A,(B,C));\n".to_vec()).unwrap();
+    assert_eq!(trees.len(), 2);
+    assert_eq!(labels.num_labels(), 3);
+
+    let error = parse_trees_bytes(b"A,B,C\n".to_vec()).unwrap_err();
+    assert!(error.to_string().contains("Unknown tree file format"));
+}
+
+#[cfg(feature = "fs")]
+#[test]
+fn test_parse_trees_file_unsupported() {
+    let xml = temp_file("trees.xml", b"<?xml version=\"1.0\"?>\n<svg/>\n");
+    let error = parse_trees_file(xml.to_str().unwrap()).unwrap_err();
+    assert!(error.to_string().contains("XML"));
+    std::fs::remove_file(xml).unwrap();
+
+    let unknown = temp_file("trees.csv", b"A,B,C\n");
+    assert!(parse_trees_file(unknown.to_str().unwrap()).is_err());
+    std::fs::remove_file(unknown).unwrap();
+
+    assert!(parse_trees_file("does/not/exist.trees").is_err());
+}
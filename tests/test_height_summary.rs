@@ -0,0 +1,53 @@
+use nexus_parser::io::writer::newick::to_newick;
+use nexus_parser::parse_newick_str;
+use nexus_parser::stats::clade_support::{annotate_support, clade_support};
+use nexus_parser::stats::height_summary::{summarize_heights, HeightMethod};
+
+const SAMPLE: &str = "((A:1,B:1):2,C:3);\n((A:2,B:2):2,C:4);\n((A:3,B:3):3,C:6);\n((A:1,C:1):4,B:5);\n";
+
+#[test]
+fn test_summarize_heights_keeps_target_heights() {
+    let (trees, _) = parse_newick_str(SAMPLE).unwrap();
+    let mut target = trees[0].clone();
+    summarize_heights(&mut target, &trees, HeightMethod::Keep);
+    assert_eq!(target.root_height(), Some(3.0));
+
+    // Clade (A,B) is in three trees, at heights 1, 2 and 3
+    let ab = target.post_order_iter().find(|v| v.is_internal()).unwrap().index();
+    let annotation = target.annotation(ab).unwrap();
+    assert_eq!(annotation.get("height").unwrap().as_number(), Some(1.0));
+    assert_eq!(annotation.get("height_mean").unwrap().as_number(), Some(2.0));
+    assert_eq!(annotation.get("height_median").unwrap().as_number(), Some(2.0));
+    assert_eq!(annotation.get("height_range").unwrap().as_range(), Some((1.0, 3.0)));
+    assert!(annotation.get("height_95%_HPD").unwrap().as_range().is_some());
+
+    // Leaves are all at height 0
+    let leaf = target.post_order_iter().find(|v| v.is_leaf()).unwrap().index();
+    assert_eq!(target.annotation(leaf).unwrap().get("height_mean").unwrap().as_number(), Some(0.0));
+}
+
+#[test]
+fn test_summarize_heights_by_median_sets_branch_lengths() {
+    let (trees, labels) = parse_newick_str(SAMPLE).unwrap();
+    let mut target = trees[0].clone();
+    summarize_heights(&mut target, &trees, HeightMethod::Median);
+    annotate_support(&mut target, &clade_support(&trees));
+
+    // Root heights 3, 4, 5 and 6: median 4.5; clade (A,B): median 2
+    assert_eq!(target.root_height(), Some(4.5));
+    let newick = to_newick(&target, &labels);
+    assert!(newick.starts_with("((A[&height_mean=0,"), "{}", newick);
+    assert!(newick.contains(")0.75[&height_mean=2,height_median=2,"), "{}", newick);
+    assert!(newick.contains(",height=2]:2.5,C[&"), "{}", newick);
+    assert!(newick.ends_with(",height=4.5];"), "{}", newick);
+}
+
+#[test]
+fn test_summarize_heights_skips_non_ultrametric_trees() {
+    let (trees, _) = parse_newick_str("((A:1,B:1):1,C:2);\n((A:1,B:2):1,C:2);\n").unwrap();
+    let mut target = trees[0].clone();
+    summarize_heights(&mut target, &trees, HeightMethod::Mean);
+    assert_eq!(target.root_height(), Some(2.0));
+    let root = target.root().index();
+    assert_eq!(target.annotation(root).unwrap().get("height_range").unwrap().as_range().unwrap().0, 2.0);
+}
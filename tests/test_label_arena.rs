@@ -0,0 +1,57 @@
+use nexus_parser::model::label_arena::LabelArena;
+#[cfg(feature = "fs")]
+use nexus_parser::model::taxon_set::TaxonSet;
+use nexus_parser::parse_newick_str;
+#[cfg(feature = "fs")]
+use nexus_parser::{parse_nexus_file, parse_nexus_file_with_labels};
+#[cfg(feature = "fs")]
+use std::path::Path;
+
+#[cfg(feature = "fs")]
+#[test]
+fn test_nexus_files_share_label_indices() {
+    let path = Path::new("tests").join("fixtures").join("nexus_t2_n4_translate.trees");
+    let path = path.to_str().unwrap();
+
+    // On its own, the file's indices follow its TAXA block (D, C, B, A)
+    let (_, labels) = parse_nexus_file(path).unwrap();
+    assert_eq!(labels.get_index("A"), Some(3));
+
+    let mut arena = LabelArena::new();
+    let (mut first, labels) = parse_newick_str("((A,B),(C,D));").unwrap();
+    arena.adopt(&mut first, &labels);
+    let trees = parse_nexus_file_with_labels(path, &mut arena).unwrap();
+
+    assert_eq!(arena.num_labels(), 4);
+    assert_eq!(arena.leaf_label_map().labels(), &vec!["A", "B", "C", "D"]);
+    assert_eq!(trees.len(), 2);
+    for tree in &trees {
+        assert!(tree.is_valid());
+        assert_eq!(tree.num_leaves_init(), 4);
+    }
+
+    // Clades can be compared directly across files
+    assert_eq!(trees[0].cached_clades(), first[0].cached_clades());
+    assert_ne!(trees[1].cached_clades(), first[0].cached_clades());
+    assert!(trees[1].is_monophyletic(&TaxonSet::from_indices(4, [0, 2])));
+}
+
+#[test]
+fn test_arena_grows_with_new_labels() {
+    let mut arena = LabelArena::new();
+    let (mut first, labels) = parse_newick_str("((A,B),C);").unwrap();
+    let mapping = arena.adopt(&mut first, &labels);
+    assert_eq!(mapping, vec![0, 1, 2]);
+
+    let (mut second, labels) = parse_newick_str("((E,C),(B,A));").unwrap();
+    let mapping = arena.adopt(&mut second, &labels);
+    assert_eq!(mapping, vec![3, 2, 1, 0]);
+    assert_eq!(arena.get_label(3), Some("E"));
+    assert_eq!(second[0].num_leaves_init(), 4);
+    assert!(second[0].is_valid());
+
+    // Trees adopted earlier keep their number of leaves
+    assert_eq!(first[0].num_leaves_init(), 3);
+    assert_eq!(arena.intern("F"), 4);
+    assert_eq!(arena.into_leaf_label_map().num_labels(), 5);
+}
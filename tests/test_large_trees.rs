@@ -0,0 +1,67 @@
+use nexus_parser::algorithms::consensus::strict;
+use nexus_parser::io::writer::newick::NewickStyle;
+use nexus_parser::parse_newick_str;
+
+/// Number of leaves of generated trees, far beyond what recursion on the call stack can handle
+const NUM_LEAVES: usize = 200_000;
+
+/// Ultrametric caterpillar `((...((t0:1,t1:1):1,t2:2):1,...),t{n-1}:n-1);` of depth `n - 1`
+fn caterpillar(num_leaves: usize) -> String {
+    let mut newick = "(".repeat(num_leaves - 1);
+    newick.push_str("t0:1,t1:1)");
+    for k in 2..num_leaves {
+        newick.push_str(&format!(":1,t{}:{})", k, k));
+    }
+    newick.push(';');
+    newick
+}
+
+/// Star tree `(t0:1,...,t{n-1}:1);`, i.e. a single polytomy
+fn star(num_leaves: usize) -> String {
+    let leaves: Vec<String> = (0..num_leaves).map(|i| format!("t{}:1", i)).collect();
+    format!("({});", leaves.join(","))
+}
+
+#[test]
+fn test_deep_caterpillar() {
+    let newick = caterpillar(NUM_LEAVES);
+    let (trees, labels) = parse_newick_str(&newick).unwrap();
+    let tree = &trees[0];
+
+    assert_eq!(labels.num_labels(), NUM_LEAVES);
+    assert_eq!(tree.num_leaves(), NUM_LEAVES);
+    assert_eq!(tree.num_vertices(), 2 * NUM_LEAVES - 1);
+    assert!(tree.is_valid());
+    assert!(tree.is_binary());
+
+    assert!(tree.is_ultrametric());
+    assert_eq!(tree.height(), (NUM_LEAVES - 1) as f64);
+    assert_eq!(tree.post_order_iter().count(), tree.num_vertices());
+    assert_eq!(tree.pre_order_iter().count(), tree.num_vertices());
+
+    assert_eq!(tree.to_newick(&NewickStyle::Label, Some(&labels)), newick);
+}
+
+#[test]
+fn test_huge_polytomy() {
+    let newick = star(NUM_LEAVES);
+    let (trees, labels) = parse_newick_str(&newick).unwrap();
+    let tree = &trees[0];
+
+    assert_eq!(tree.num_leaves(), NUM_LEAVES);
+    assert_eq!(tree.num_vertices(), NUM_LEAVES + 1);
+    assert_eq!(tree.root().num_children(), NUM_LEAVES);
+    assert!(tree.is_valid());
+    assert!(tree.is_ultrametric());
+
+    assert_eq!(tree.to_newick(&NewickStyle::Label, Some(&labels)), newick);
+}
+
+#[test]
+fn test_consensus_of_huge_polytomy() {
+    let (trees, _) = parse_newick_str(&star(20_000)).unwrap();
+
+    let consensus = strict(&trees);
+    assert!(consensus.is_valid());
+    assert_eq!(consensus.root().num_children(), 20_000);
+}
@@ -0,0 +1,68 @@
+#[cfg(feature = "fs")]
+use nexus_parser::model::tree::Tree;
+use nexus_parser::parse_newick_str;
+#[cfg(feature = "fs")]
+use nexus_parser::parse_nexus_file;
+#[cfg(feature = "fs")]
+use std::path::Path;
+
+/// Returns the LCA by walking up from both vertices, as reference.
+#[cfg(feature = "fs")]
+fn naive_lca(tree: &Tree, u: usize, v: usize) -> usize {
+    let ancestors = |mut index: usize| {
+        let mut path = vec![index];
+        while let Some(parent) = tree[index].parent_index() {
+            path.push(parent);
+            index = parent;
+        }
+        path
+    };
+    let ancestors_u = ancestors(u);
+    ancestors(v).into_iter().find(|index| ancestors_u.contains(index)).unwrap()
+}
+
+#[cfg(feature = "fs")]
+#[test]
+fn test_lca_query_matches_naive() {
+    let path = Path::new("tests").join("fixtures").join("nexus_t1_n10.trees");
+    let (trees, _) = parse_nexus_file(path.to_str().unwrap()).unwrap();
+    let tree = &trees[0];
+    let lca = tree.lca_index();
+
+    for u in 0..tree.num_vertices() {
+        for v in 0..tree.num_vertices() {
+            assert_eq!(lca.query(u, v), naive_lca(tree, u, v), "LCA of {} and {}", u, v);
+        }
+    }
+}
+
+#[test]
+fn test_lca_mrca_matches_tree_mrca() {
+    let (trees, _) = parse_newick_str("(((A,B),C),((D,E),F,G));").unwrap();
+    let tree = &trees[0];
+    let lca = tree.lca_index();
+
+    for taxa in [vec![0], vec![0, 1], vec![1, 2], vec![3, 4, 5], vec![4, 6], vec![0, 6], vec![2, 1, 0]] {
+        assert_eq!(lca.mrca(&taxa), tree.mrca(&taxa), "MRCA of {:?}", taxa);
+    }
+}
+
+#[test]
+fn test_lca_depth_and_leaf() {
+    let (trees, _) = parse_newick_str("((A,B),C);").unwrap();
+    let tree = &trees[0];
+    let lca = tree.lca_index();
+
+    let a = lca.leaf(0).unwrap();
+    assert_eq!(tree[a].label_index(), Some(0));
+    assert_eq!(lca.depth(a), 2);
+    assert_eq!(lca.depth(tree.root().index()), 0);
+    assert_eq!(lca.leaf(3), None);
+}
+
+#[test]
+#[should_panic]
+fn test_lca_mrca_unknown_taxon() {
+    let (trees, _) = parse_newick_str("((A,B),C);").unwrap();
+    trees[0].lca_index().mrca(&[0, 5]);
+}
@@ -1,4 +1,4 @@
-use nexus_parser::model::leaf_label_map::LeafLabelMap;
+use nexus_parser::model::leaf_label_map::{LabelRenaming, LeafLabelMap, RenameError};
 
 #[test]
 fn test_get_or_insert_new_label() {
@@ -43,4 +43,78 @@ fn test_get_label_returns_correct_label() {
 fn test_get_label_returns_none_for_invalid_index() {
     let map = LeafLabelMap::new(5);
     assert_eq!(map.get_label(0), None);
-}
\ No newline at end of file
+}
+#[test]
+fn test_get_or_insert_beyond_expected_number() {
+    let mut map = LeafLabelMap::new(1);
+    map.get_or_insert("Apteryx haastii");
+    assert!(map.is_full());
+
+    assert_eq!(map.get_or_insert("Apteryx owenii"), 1);
+    assert_eq!(map.num_labels(), 2);
+    assert!(map.is_full());
+}
+
+#[test]
+fn test_rename_keeps_index() {
+    let mut map = LeafLabelMap::new(2);
+    map.get_or_insert("Kaki");
+    map.get_or_insert("Poaka");
+
+    assert_eq!(map.rename("Kaki", "Himantopus novaezelandiae"), Ok(0));
+    assert_eq!(map.get_label(0), Some("Himantopus novaezelandiae"));
+    assert_eq!(map.get_index("Himantopus novaezelandiae"), Some(0));
+    assert_eq!(map.get_index("Kaki"), None);
+    assert_eq!(map.rename("Poaka", "Poaka"), Ok(1));
+
+    assert_eq!(map.rename("Kaki", "Black stilt"), Err(RenameError::UnknownLabel("Kaki".to_string())));
+    assert_eq!(map.rename("Poaka", "Himantopus novaezelandiae"), Err(RenameError::DuplicateLabel("Himantopus novaezelandiae".to_string())));
+    assert_eq!(map.get_label(1), Some("Poaka"));
+}
+
+#[test]
+fn test_rename_with_is_all_or_nothing() {
+    let mut map = LeafLabelMap::new(3);
+    map.get_or_insert("kea|NZ");
+    map.get_or_insert("kaka|NZ");
+    map.get_or_insert("kea|AU");
+
+    // Stripping the country would merge both keas
+    let result = map.rename_with(|label| label.split('|').next().unwrap().to_string());
+    assert_eq!(result, Err(RenameError::DuplicateLabel("kea".to_string())));
+    assert_eq!(map.get_label(2), Some("kea|AU"));
+
+    map.rename_with(|label| label.to_uppercase()).unwrap();
+    assert_eq!(map.labels(), &vec!["KEA|NZ".to_string(), "KAKA|NZ".to_string(), "KEA|AU".to_string()]);
+    assert_eq!(map.get_index("KAKA|NZ"), Some(1));
+}
+
+#[test]
+fn test_label_renaming_from_tsv() {
+    let renaming = LabelRenaming::from_tsv("A\tAlpha\r\n\n# comment\nB\tBeta\textra\n".as_bytes()).unwrap();
+    assert_eq!(renaming.len(), 2);
+    assert_eq!(renaming.get("B"), Some("Beta"));
+    assert_eq!(renaming.get("C"), None);
+
+    assert!(LabelRenaming::from_tsv("A\tAlpha\nB\n".as_bytes()).is_err());
+    assert!(LabelRenaming::from_tsv("A\tAlpha\nA\tAleph\n".as_bytes()).is_err());
+}
+
+#[cfg(feature = "fs")]
+#[test]
+fn test_rename_all_from_tsv_file() {
+    let renaming = LabelRenaming::from_tsv_file("tests/fixtures/label_mapping.tsv").unwrap();
+    assert_eq!(renaming.len(), 3);
+
+    let mut map = LeafLabelMap::new(3);
+    map.get_or_insert("MN908947");
+    map.get_or_insert("MK123456");
+    map.get_or_insert("JX869059");
+    assert_eq!(map.rename_all(&renaming), Ok(2));
+    assert_eq!(map.labels(), &vec!["SARS-CoV-2".to_string(), "MK123456".to_string(), "MERS-CoV".to_string()]);
+
+    // New labels must not clash with kept ones
+    let mut clashing = LabelRenaming::new();
+    clashing.insert("SARS-CoV-2", "MK123456");
+    assert_eq!(map.rename_all(&clashing), Err(RenameError::DuplicateLabel("MK123456".to_string())));
+}
@@ -0,0 +1,109 @@
+mod common;
+use common::approx_eq;
+
+use nexus_parser::algorithms::likelihood::{log_likelihood, SubstitutionModel};
+use nexus_parser::io::parser::fasta;
+use nexus_parser::model::alignment::DataType;
+use nexus_parser::parse_newick_str;
+
+const GTR_RATES: [f64; 6] = [1.2, 3.5, 0.8, 0.6, 4.1, 1.0];
+const GTR_FREQUENCIES: [f64; 4] = [0.35, 0.15, 0.2, 0.3];
+
+/// Parses the trees and a FASTA alignment over their labels, and returns the log-likelihood of each tree.
+fn log_likelihoods(newicks: &str, fasta: &str, model: &SubstitutionModel) -> Vec<f64> {
+    let (trees, mut labels) = parse_newick_str(newicks).unwrap();
+    let alignment = fasta::parse_str(fasta, DataType::Dna, &mut labels).unwrap();
+    trees.iter().map(|tree| log_likelihood(tree, &alignment, model)).collect()
+}
+
+fn assert_close(a: f64, b: f64) {
+    assert!(approx_eq(a, b, 1e-9), "{} != {}", a, b);
+}
+
+#[test]
+fn test_transition_matrix() {
+    let jc69 = SubstitutionModel::jc69();
+    let p = jc69.transition_matrix(0.2);
+    for (i, row) in p.iter().enumerate() {
+        for (j, &probability) in row.iter().enumerate() {
+            let expected = if i == j { 0.25 + 0.75 * (-0.8f64 / 3.0).exp() } else { 0.25 - 0.25 * (-0.8f64 / 3.0).exp() };
+            assert_close(probability, expected);
+        }
+    }
+    assert_eq!(jc69.transition_matrix(0.0).map(|row| row.map(f64::round)), [[1.0, 0.0, 0.0, 0.0], [0.0, 1.0, 0.0, 0.0], [0.0, 0.0, 1.0, 0.0], [0.0, 0.0, 0.0, 1.0]]);
+
+    // Rows sum to 1, the frequencies are stationary, and one substitution is expected per unit of time
+    let gtr = SubstitutionModel::gtr(GTR_RATES, GTR_FREQUENCIES);
+    let p = gtr.transition_matrix(0.7);
+    for row in &p {
+        assert_close(row.iter().sum(), 1.0);
+    }
+    for j in 0..4 {
+        assert_close((0..4).map(|i| GTR_FREQUENCIES[i] * p[i][j]).sum(), GTR_FREQUENCIES[j]);
+    }
+    let p = gtr.transition_matrix(1e-6);
+    let rate: f64 = (0..4).map(|i| GTR_FREQUENCIES[i] * (1.0 - p[i][i])).sum::<f64>() / 1e-6;
+    assert!((rate - 1.0).abs() < 1e-4, "{}", rate);
+
+    // Long branches reach the stationary distribution
+    let p = gtr.transition_matrix(100.0);
+    for (j, &frequency) in GTR_FREQUENCIES.iter().enumerate() {
+        assert_close(p[1][j], frequency);
+    }
+}
+
+#[test]
+fn test_log_likelihood_brute_force() {
+    let newick = "((A:0.1,B:0.2):0.05,C:0.3);\n";
+    let fasta = ">A\nAG\n>B\nAT\n>C\nGT\n";
+    let model = SubstitutionModel::gtr(GTR_RATES, GTR_FREQUENCIES);
+
+    // Sum over the states of the root and its internal child
+    let (a, c, g, t) = (0, 1, 2, 3);
+    let (p05, p1, p2, p3) = (model.transition_matrix(0.05), model.transition_matrix(0.1),
+                             model.transition_matrix(0.2), model.transition_matrix(0.3));
+    let site = |leaf_a: usize, leaf_b: usize, leaf_c: usize| -> f64 {
+        (0..4).map(|r| GTR_FREQUENCIES[r] * p3[r][leaf_c]
+            * (0..4).map(|x| p05[r][x] * p1[x][leaf_a] * p2[x][leaf_b]).sum::<f64>()).sum()
+    };
+    let expected = site(a, a, g).ln() + site(g, t, t).ln();
+    assert_close(log_likelihoods(newick, fasta, &model)[0], expected);
+
+    // Unknown states and leaves without sequence sum over all states
+    let expected = (0..4).map(|b| site(a, b, g)).sum::<f64>().ln() + site(g, t, t).ln();
+    assert_close(log_likelihoods(newick, ">A\nAG\n>B\n-T\n>C\nGT\n", &model)[0], expected);
+    let expected = (0..4).map(|b| site(a, b, c) + site(a, b, t)).sum::<f64>().ln();
+    assert_close(log_likelihoods(newick, ">A\nA\n>C\nY\n", &model)[0], expected);
+}
+
+#[test]
+fn test_log_likelihood_independent_of_root() {
+    let newicks = "((A:0.1,B:0.2):0.05,(C:0.3,D:0.15):0.25);\n(A:0.1,B:0.2,(C:0.3,D:0.15):0.3);\n(((A:0.1,B:0.2):0.3,C:0.3):0.1,D:0.05);\n";
+    let fasta = ">A\nACGTTGCA\n>B\nACGTAGCA\n>C\nACCTAGGA\n>D\nTCCTAGGN\n";
+    for model in [SubstitutionModel::jc69(), SubstitutionModel::gtr(GTR_RATES, GTR_FREQUENCIES)] {
+        let values = log_likelihoods(newicks, fasta, &model);
+        assert_close(values[0], values[1]);
+        assert_close(values[0], values[2]);
+    }
+
+    // Equal rates and frequencies are JC69
+    let jc69 = log_likelihoods(newicks, fasta, &SubstitutionModel::jc69());
+    let gtr = log_likelihoods(newicks, fasta, &SubstitutionModel::gtr([2.0; 6], [0.25; 4]));
+    assert_close(jc69[0], gtr[0]);
+}
+
+#[test]
+fn test_log_likelihood_many_leaves() {
+    // Caterpillar on 300 leaves with long branches, whose site likelihoods underflow without rescaling
+    let mut newick = "(L0:1.5,L1:1.5)".to_string();
+    for i in 2..300 {
+        newick = format!("({}:1.5,L{}:1.5)", newick, i);
+    }
+    newick.push_str(";\n");
+    let fasta: String = (0..300).map(|i| format!(">L{}\n{}\n", i, ["AC", "CG", "GT", "TA"][i % 4])).collect();
+
+    let value = log_likelihoods(&newick, &fasta, &SubstitutionModel::jc69())[0];
+    assert!(value.is_finite(), "{}", value);
+    // Almost independent leaves: close to 2 * 300 * ln(1/4)
+    assert!(value < 2.0 * 300.0 * 0.25f64.ln() + 50.0 && value > 2.0 * 300.0 * 0.25f64.ln() - 100.0, "{}", value);
+}
@@ -0,0 +1,43 @@
+use nexus_parser::parse_newick_str;
+use nexus_parser::stats::ltt::sample_ltt;
+
+#[test]
+fn test_ltt_of_ultrametric_tree() {
+    let (trees, _) = parse_newick_str("(((A:1,B:1):2,(C:2,D:2):1):1,E:4);").unwrap();
+    let ltt = trees[0].ltt().unwrap();
+    assert_eq!(ltt, vec![(4.0, 2), (3.0, 3), (2.0, 4), (1.0, 5), (0.0, 5)]);
+}
+
+#[test]
+fn test_ltt_of_polytomy_and_missing_lengths() {
+    // Multifurcating root and simultaneous branchings
+    let (trees, _) = parse_newick_str("((A:1,B:1):1,(C:1,D:1):1,E:2);").unwrap();
+    assert_eq!(trees[0].ltt().unwrap(), vec![(2.0, 3), (1.0, 5), (0.0, 5)]);
+
+    let (trees, _) = parse_newick_str("((A:1,B):1,C:2);").unwrap();
+    assert_eq!(trees[0].ltt(), None);
+}
+
+#[test]
+fn test_sample_ltt() {
+    let (trees, _) = parse_newick_str("((A:1,B:1):1,C:2);\n((A:2,C:2):2,B:4);\n((B:1,C:1):3,A:4);\n").unwrap();
+    let ltt = sample_ltt(&trees, 8).unwrap();
+    assert_eq!(ltt.len(), 8);
+    assert_eq!(ltt[0].time, 0.0);
+    assert!(ltt.windows(2).all(|pair| pair[0].time < pair[1].time));
+    assert!(ltt.windows(2).all(|pair| pair[0].mean >= pair[1].mean));
+
+    // At time 1.5: 2, 3 and 2 lineages
+    assert_eq!(ltt[3].time, 1.5);
+    assert!((ltt[3].mean - 7.0 / 3.0).abs() < 1e-12);
+    assert_eq!((ltt[3].median, ltt[3].hpd), (2.0, (2.0, 3.0)));
+    // At time 2.5: 0, 2 and 2 lineages
+    assert_eq!(ltt[5].time, 2.5);
+    assert!((ltt[5].mean - 4.0 / 3.0).abs() < 1e-12);
+    assert_eq!(ltt[5].median, 2.0);
+    assert_eq!(ltt[5].hpd, (0.0, 2.0));
+
+    assert!(sample_ltt(&[], 8).is_none());
+    let (without_lengths, _) = parse_newick_str("((A,B),C);").unwrap();
+    assert!(sample_ltt(&without_lengths, 8).is_none());
+}
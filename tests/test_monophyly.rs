@@ -0,0 +1,78 @@
+mod common;
+use common::parse_trees_with_labels;
+
+use nexus_parser::model::leaf_label_map::LeafLabelMap;
+use nexus_parser::model::taxon_set::TaxonSet;
+use nexus_parser::model::taxonomy::Taxonomy;
+use nexus_parser::model::tree::Tree;
+use nexus_parser::stats::monophyly::{monophyly_report, taxonomy_monophyly_report};
+
+const RATITES_TSV: &str = "taxon\tgenus\tfamily
+Apteryx haastii\tApteryx\tApterygidae
+Apteryx owenii\tApteryx\tApterygidae
+Casuarius casuarius\tCasuarius\tCasuariidae
+Dromaius novaehollandiae\tDromaius\tCasuariidae
+Struthio camelus\tStruthio\tStruthionidae
+";
+
+fn ratite_trees() -> (Vec<Tree>, LeafLabelMap) {
+    parse_trees_with_labels(&[
+        "(('Apteryx haastii','Apteryx owenii'),('Casuarius casuarius','Dromaius novaehollandiae'),'Struthio camelus');",
+        "(('Apteryx haastii','Apteryx owenii'),(('Casuarius casuarius','Struthio camelus'),'Dromaius novaehollandiae'));",
+        "(('Apteryx haastii','Casuarius casuarius'),('Apteryx owenii','Dromaius novaehollandiae'),'Struthio camelus');",
+        "((('Apteryx haastii','Apteryx owenii'),'Struthio camelus'),('Casuarius casuarius','Dromaius novaehollandiae'));",
+    ], 5)
+}
+
+#[test]
+fn test_taxonomy_constraints() {
+    let taxonomy = Taxonomy::from_tsv(RATITES_TSV.as_bytes()).unwrap();
+    let (_, labels) = ratite_trees();
+    let kiwi = TaxonSet::from_indices(5, [labels.get_index("Apteryx haastii").unwrap(), labels.get_index("Apteryx owenii").unwrap()]);
+    let casuariids = TaxonSet::from_indices(5, [labels.get_index("Casuarius casuarius").unwrap(), labels.get_index("Dromaius novaehollandiae").unwrap()]);
+
+    // Genus: only Apteryx has more than one taxon
+    assert_eq!(taxonomy.constraints(0, &labels), vec![("Apteryx".to_string(), kiwi.clone())]);
+    // Family: sorted by name, singleton Struthionidae skipped
+    assert_eq!(taxonomy.constraints(1, &labels), vec![
+        ("Apterygidae".to_string(), kiwi),
+        ("Casuariidae".to_string(), casuariids),
+    ]);
+}
+
+#[test]
+fn test_taxonomy_monophyly_report() {
+    let taxonomy = Taxonomy::from_tsv(RATITES_TSV.as_bytes()).unwrap();
+    let (trees, labels) = ratite_trees();
+
+    let family = taxonomy.rank_index("family").unwrap();
+    let report = taxonomy_monophyly_report(&trees, &labels, &taxonomy, family);
+
+    assert_eq!(report.len(), 2);
+    assert_eq!(report[0].name(), "Apterygidae");
+    assert_eq!(report[0].num_monophyletic(), 3);
+    assert_eq!(report[0].num_trees(), 4);
+    assert_eq!(report[0].fraction(), 0.75);
+    assert_eq!(report[1].name(), "Casuariidae");
+    assert_eq!(report[1].num_monophyletic(), 2);
+    assert_eq!(report[1].fraction(), 0.5);
+}
+
+#[test]
+fn test_monophyly_report_edge_cases() {
+    let (trees, _) = ratite_trees();
+
+    // No constraints
+    assert!(monophyly_report(&trees, &[]).is_empty());
+
+    // No trees
+    let constraints = vec![("All".to_string(), TaxonSet::full(5)), ("None".to_string(), TaxonSet::new(5))];
+    let report = monophyly_report(&[], &constraints);
+    assert_eq!(report[0].num_trees(), 0);
+    assert_eq!(report[0].fraction(), 0.0);
+
+    // All taxa always, no taxa never monophyletic
+    let report = monophyly_report(&trees, &constraints);
+    assert_eq!(report[0].fraction(), 1.0);
+    assert_eq!(report[1].fraction(), 0.0);
+}
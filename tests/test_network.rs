@@ -0,0 +1,138 @@
+use nexus_parser::io::parser::byte_parser::ByteParser;
+use nexus_parser::io::parser::newick::NewickParser;
+use nexus_parser::io::parser::parsing_error::ParsingErrorType;
+use nexus_parser::model::network::{split_reticulation_tag, Network, Reticulation, ReticulationKind};
+use nexus_parser::model::tree::{Tree, TreeIndex};
+
+fn parse_network(newick: &str) -> (Network, NewickParser) {
+    let mut newick_parser = NewickParser::new();
+    let network = newick_parser.parse_network(&mut ByteParser::from_str(newick)).unwrap();
+    (network, newick_parser)
+}
+
+/// Index of the leaf of the taxon with the given label
+fn leaf(tree: &Tree, newick_parser: &NewickParser, label: &str) -> TreeIndex {
+    let label_index = newick_parser.leaf_label_map().get_index(label).unwrap();
+    (0..tree.num_vertices())
+        .find(|&index| tree[index].label_index() == Some(label_index))
+        .unwrap()
+}
+
+#[test]
+fn test_tree_parsing_reports_reticulation_tags() {
+    let error = NewickParser::new().parse(&mut ByteParser::from_str("((A,(B)#H1),(#H1,C));")).unwrap_err();
+    assert_eq!(error.kind(), &ParsingErrorType::ReticulateNetwork("#H1".to_string()));
+
+    let error = NewickParser::new().parse(&mut ByteParser::from_str("((A,B#H1),(#H1,C));")).unwrap_err();
+    assert_eq!(error.kind(), &ParsingErrorType::ReticulateNetwork("B#H1".to_string()));
+}
+
+#[test]
+fn test_parse_network_hybrid_subtree() {
+    let (network, newick_parser) = parse_network("((A,(B)#H1),(#H1,C));");
+    let tree = network.tree();
+
+    assert!(tree.is_valid());
+    assert_eq!(tree.num_leaves(), 3);
+    assert_eq!(newick_parser.leaf_label_map().num_labels(), 3);
+    assert_eq!(network.num_reticulations(), 1);
+
+    let reticulation = &network.reticulations()[0];
+    assert_eq!(reticulation.kind, ReticulationKind::Hybridization);
+    assert_eq!(reticulation.tag(), "#H1");
+    let hybrid = reticulation.child;
+    assert!(network.is_reticulation(hybrid));
+    assert_eq!(tree[hybrid].node_label(), None);
+    assert_eq!(tree[hybrid].children(), Some(&[leaf(tree, &newick_parser, "B")][..]));
+
+    let parents = network.parents(hybrid);
+    assert_eq!(parents.len(), 2);
+    assert_eq!(parents[1], tree[leaf(tree, &newick_parser, "C")].parent_index().unwrap());
+    assert_eq!(network.children(parents[1]), vec![leaf(tree, &newick_parser, "C"), hybrid]);
+}
+
+#[test]
+fn test_parse_network_transfer_to_leaf_with_lengths() {
+    let (network, newick_parser) = parse_network("((A:1,B#LGT1:2):1,(#LGT1:0.5,C:1)X:1);");
+    let tree = network.tree();
+
+    let transfer = &network.reticulations()[0];
+    assert_eq!(transfer.kind, ReticulationKind::LateralGeneTransfer);
+    assert_eq!(transfer.child, leaf(tree, &newick_parser, "B"));
+    assert_eq!(transfer.branch_length.map(|length| *length), Some(0.5));
+    assert_eq!(tree[transfer.child].branch_length().map(|length| *length), Some(2.0));
+    assert_eq!(tree[transfer.parent].node_label(), Some("X"));
+
+    // Placeholder leaf is removed from the tree
+    assert!(tree.is_valid());
+    assert_eq!(tree.num_vertices(), 6);
+    assert_eq!(tree[transfer.parent].children().unwrap().len(), 1);
+}
+
+#[test]
+fn test_parse_network_vertex_with_only_reticulation_children() {
+    // Reticulation vertex moves below p in the tree, its edge from (A)#H1's first parent becomes the reticulation
+    let (network, newick_parser) = parse_network("(((A)#H1:1,D),(#H1:2)p:1,E);");
+    let tree = network.tree();
+    assert!(tree.is_valid());
+    let hybrid = tree[leaf(tree, &newick_parser, "A")].parent_index().unwrap();
+    let p = tree[hybrid].parent_index().unwrap();
+    assert_eq!(tree[p].node_label(), Some("p"));
+    assert_eq!(*tree[hybrid].branch_length().unwrap(), 2.0);
+    let reticulation = &network.reticulations()[0];
+    assert_eq!((reticulation.child, reticulation.parent), (hybrid, tree[leaf(tree, &newick_parser, "D")].parent_index().unwrap()));
+    assert_eq!(*reticulation.branch_length.unwrap(), 1.0);
+
+    // Both tree parents of the reticulation vertex would be left childless, so p is suppressed
+    let (network, newick_parser) = parse_network("(((A)#H1:1)q:1,(#H1:2)p[&x=1]:1);");
+    let tree = network.tree();
+    assert!(tree.is_valid());
+    assert_eq!(tree.num_vertices(), 4);
+    let reticulation = &network.reticulations()[0];
+    assert_eq!(reticulation.parent, tree.root().index());
+    assert_eq!(*reticulation.branch_length.unwrap(), 3.0);
+    assert_eq!(network.parents(tree[leaf(tree, &newick_parser, "A")].parent_index().unwrap()).len(), 2);
+    assert_eq!(tree.dropped_metadata()[0].node_label.as_deref(), Some("p"));
+}
+
+#[test]
+fn test_parse_network_without_tags_is_tree() {
+    let (network, _) = parse_network("((A,B),C);");
+    assert!(network.is_tree());
+    assert_eq!(network.into_tree().num_leaves(), 3);
+}
+
+#[test]
+fn test_parse_network_errors() {
+    for newick in ["((A,B),(#H1,C));", "((A,(B)#H1),((C)#H1,D));", "((A)#H1,B)#H2;", "((A,(B,#H2)#H1),(C,#H1)#H2);"] {
+        let error = NewickParser::new().parse_network(&mut ByteParser::from_str(newick)).unwrap_err();
+        assert!(matches!(error.kind(), ParsingErrorType::InvalidNewickString(_)), "{newick}: {error}");
+    }
+}
+
+#[test]
+fn test_split_reticulation_tag() {
+    assert_eq!(split_reticulation_tag("(B)#R3"), Some(("(B)", ReticulationKind::Recombination, 3)));
+    assert_eq!(split_reticulation_tag("#H"), None);
+    assert_eq!(split_reticulation_tag("A#Hx1"), None);
+    assert_eq!(split_reticulation_tag("A"), None);
+}
+
+#[test]
+#[should_panic(expected = "creates a cycle")]
+fn test_add_reticulation_cycle_panics() {
+    let (network, newick_parser) = parse_network("((A,B),C);");
+    let tree = network.tree();
+    let a = leaf(tree, &newick_parser, "A");
+    let parent = tree[a].parent_index().unwrap();
+
+    let mut network = Network::from_tree(tree.clone());
+    network.add_reticulation(Reticulation {
+        parent: a,
+        child: parent,
+        branch_length: None,
+        kind: ReticulationKind::Hybridization,
+        number: 1,
+        annotation: None,
+    });
+}
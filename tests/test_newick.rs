@@ -1,6 +1,19 @@
+mod common;
+use common::approx_eq;
+
 use nexus_parser::io::parser::byte_parser::ByteParser;
-use nexus_parser::io::parser::newick::{parse_file, NewickParser};
+#[cfg(feature = "fs")]
+use nexus_parser::io::parser::newick::parse_file;
+use nexus_parser::io::parser::newick::{parse_str_borrowed, NegativeBranchLengthPolicy, NewickParser};
+use nexus_parser::io::parser::parsing_error::ParsingErrorType;
+use nexus_parser::io::writer::newick::NewickStyle;
+use nexus_parser::model::leaf_label_map::DuplicateTaxonPolicy;
+use nexus_parser::parse_newick_str;
+#[cfg(feature = "fs")]
+use nexus_parser::parse_newick_file;
+#[cfg(feature = "fs")]
 use std::fs::File;
+#[cfg(feature = "fs")]
 use std::path::Path;
 // --- TESTS NEWICK STRING PARSING ---
 
@@ -27,12 +40,12 @@ fn test_basic_tree() {
     // - Root has children (internal, C)
     let root = tree.root();
     let root_index = root.index();
-    let (root_left, root_right) = root.children().unwrap();
+    let (root_left, root_right) = (root.children().unwrap()[0], root.children().unwrap()[1]);
 
     // - Internal node has children (A, B)
     let internal = tree.vertex(root_left);
     assert!(internal.is_internal());
-    let (internal_left, internal_right) = internal.children().unwrap();
+    let (internal_left, internal_right) = (internal.children().unwrap()[0], internal.children().unwrap()[1]);
 
     // - Three leaves
     let leaf_a = tree.vertex(internal_left);
@@ -61,6 +74,45 @@ fn test_basic_tree_without_root_branch() {
     assert_eq!(tree.num_vertices(), 5);
 }
 
+#[test]
+fn test_multifurcating_tree() {
+    let newick = "((A:1.0,B:1.0,C:1.0):0.5,(D:0.5,E:0.5):1.0,F:1.5);";
+    let mut parser = ByteParser::from_str(newick);
+    let tree = NewickParser::new().with_num_leaves(6).parse(&mut parser).unwrap();
+
+    assert!(tree.is_valid());
+    assert!(!tree.is_binary());
+    assert_eq!(tree.num_leaves(), 6);
+    assert_eq!(tree.num_internal(), 2);
+    assert_eq!(tree.root().num_children(), 3);
+    assert!(tree.root().is_multifurcating());
+
+    let polytomy = tree.vertex(tree.root().children().unwrap()[0]);
+    assert_eq!(polytomy.num_children(), 3);
+    assert!(tree.is_ultrametric());
+}
+
+#[test]
+fn test_internal_node_labels() {
+    let newick = "((A:1.0,B:1.0)95:0.5,(C:0.5,D:0.5)'Clade X' [comment]:1.0)Root;";
+    let mut parser = ByteParser::from_str(newick);
+    let tree = NewickParser::new().with_num_leaves(4).parse(&mut parser).unwrap();
+    assert!(tree.is_valid());
+
+    let root = tree.root();
+    assert_eq!(root.node_label(), Some("Root"));
+    assert_eq!(root.support(), None);
+
+    let left = tree.vertex(root.children().unwrap()[0]);
+    assert_eq!(left.node_label(), Some("95"));
+    assert_eq!(left.support(), Some(95.0));
+    assert_eq!(*left.branch_length().unwrap(), 0.5);
+
+    let right = tree.vertex(root.children().unwrap()[1]);
+    assert_eq!(right.node_label(), Some("Clade X"));
+    assert_eq!(*right.branch_length().unwrap(), 1.0);
+}
+
 #[test]
 fn test_tree_with_quoted_labels() {
     let newick = "(('Taxon one':1.5,'Second''s taxon':2.5):3.0,'3rd Taxon':4.0):0.0;";
@@ -123,6 +175,38 @@ fn test_newick_with_comment_2() {
     assert!(tree.is_ok());
 }
 
+#[test]
+fn test_topology_only() {
+    let newick = "((1:1.0,3:2.0):3.0[&rate=0.1],2:4.5e-1):0.0;";
+    let mut parser = ByteParser::from_str(newick);
+    let mut newick_parser = NewickParser::new().with_num_leaves(3).with_topology_only();
+    let tree = newick_parser.parse(&mut parser).unwrap();
+
+    assert!(tree.is_valid());
+    assert_eq!(tree.num_leaves(), 3);
+    assert!(!tree.vertices_have_branch_lengths());
+
+    // Keys map directly to label indices, label map untouched
+    let leaf_indices: Vec<_> = tree.post_order_iter()
+        .filter_map(|v| v.label_index())
+        .collect();
+    assert_eq!(leaf_indices, vec![0, 2, 1]);
+    assert_eq!(newick_parser.into_leaf_label_map().num_labels(), 0);
+}
+
+#[test]
+fn test_topology_only_rejects_labels() {
+    let newick = "((A,B),C);";
+    let mut parser = ByteParser::from_str(newick);
+    let tree = NewickParser::new().with_topology_only().parse(&mut parser);
+    assert!(tree.is_err());
+
+    let newick = "((1,4),2);";
+    let mut parser = ByteParser::from_str(newick);
+    let tree = NewickParser::new().with_num_leaves(3).with_topology_only().parse(&mut parser);
+    assert!(tree.is_err());
+}
+
 // --- TESTS DEALING WITH CORRUPT NEWICK STRINGS ---
 
 #[test]
@@ -157,7 +241,41 @@ fn test_invalid_branch_length() {
     assert!(tree.is_err());
 }
 
+#[test]
+fn test_non_finite_and_negative_branch_lengths_are_errors() {
+    let error = parse_newick_str("((A:1.0,B:1e999):3.0,C:4.0);").unwrap_err();
+    assert_eq!(error.kind(), &ParsingErrorType::InvalidNewickString("Branch length is not finite: 1e999".to_string()));
+
+    // Beyond the tolerance for numerical noise, also when keeping tiny negative lengths
+    let error = parse_newick_str("((A:1.0,B:-0.5):3.0,C:4.0);").unwrap_err();
+    assert_eq!(error.kind(), &ParsingErrorType::InvalidNewickString("Negative branch length: -0.5".to_string()));
+    let mut newick_parser = NewickParser::new().with_negative_branch_lengths(NegativeBranchLengthPolicy::Keep);
+    assert!(newick_parser.parse(&mut ByteParser::from_str("((A:1.0,B:-0.5):3.0,C:4.0);")).is_err());
+}
+
+#[test]
+fn test_tiny_negative_branch_lengths() {
+    let newick = "((A:1.0,B:-1e-17):3.0,C:4.0);";
+
+    // Clamped to zero by default
+    let (trees, _) = parse_newick_str(newick).unwrap();
+    assert_eq!(trees[0].total_branch_length(), 8.0);
+    assert!(!trees[0].validate().iter().any(|issue| issue.is_error()));
+
+    let mut newick_parser = NewickParser::new().with_negative_branch_lengths(NegativeBranchLengthPolicy::Error);
+    let error = newick_parser.parse(&mut ByteParser::from_str(newick)).unwrap_err();
+    assert_eq!(error.kind(), &ParsingErrorType::InvalidNewickString("Negative branch length: -1e-17".to_string()));
+
+    // Kept, and reported by validation
+    let mut newick_parser = NewickParser::new().with_negative_branch_lengths(NegativeBranchLengthPolicy::Keep);
+    let tree = newick_parser.parse(&mut ByteParser::from_str(newick)).unwrap();
+    let lengths: Vec<f64> = (0..tree.num_vertices()).filter_map(|i| tree[i].branch_length()).map(|length| *length).collect();
+    assert!(lengths.iter().any(|&length| approx_eq(length, -1e-17, 0.0)));
+    assert!(tree.validate().iter().any(|issue| issue.is_error()));
+}
+
 // --- TESTS PARSING WHOLE FILE ---
+#[cfg(feature = "fs")]
 #[test]
 fn test_parsing_newick_file() {
     let path = Path::new("tests").join("fixtures").join("newick_t4_n10.nwk");
@@ -171,4 +289,107 @@ fn test_parsing_newick_file() {
         assert_eq!(tree.num_leaves(), 10);
         assert!(tree.is_valid());
     }
-}
\ No newline at end of file
+}
+#[cfg(feature = "fs")]
+#[test]
+fn test_parse_newick_file_from_lib() {
+    let path = Path::new("tests").join("fixtures").join("newick_t4_n10.nwk");
+    let (trees, leaf_map) = parse_newick_file(path.to_str().unwrap()).unwrap();
+
+    assert_eq!(trees.len(), 3);
+    assert_eq!(leaf_map.num_labels(), 10);
+    assert!(parse_newick_file("does/not/exist.nwk").is_err());
+}
+
+#[test]
+fn test_parse_newick_str_multiple_trees() {
+    // Trees on one line, across lines, with comments and blank lines in between
+    let newicks = "((A:1,B:1):1,C:2); ((A:1,C:1):1,B:2);\n\n[second line]\n((B:1,C:1):1,\n A:2);\n";
+    let (trees, leaf_map) = parse_newick_str(newicks).unwrap();
+
+    assert_eq!(trees.len(), 3);
+    assert_eq!(leaf_map.num_labels(), 3);
+    assert!(trees.iter().all(|tree| tree.is_valid()));
+
+    assert_eq!(parse_newick_str("  \n").unwrap().0.len(), 0);
+    assert!(parse_newick_str("((A,B),C);\n((A,B),C)\n").is_err());
+}
+
+#[test]
+fn test_duplicate_taxa() {
+    let newick = "((A:1,B:1):1,(A:1,C:1):1);";
+
+    // Error by default
+    let error = parse_newick_str(newick).unwrap_err();
+    assert_eq!(error.kind(), &ParsingErrorType::DuplicateTaxon("A".to_string()));
+
+    // Allowed: both leaves map to the same taxon
+    let mut newick_parser = NewickParser::new().with_duplicate_taxa(DuplicateTaxonPolicy::AllowDuplicates);
+    let tree = newick_parser.parse(&mut ByteParser::from_str(newick)).unwrap();
+    assert_eq!(tree.num_leaves(), 4);
+    assert_eq!(newick_parser.leaf_label_map().num_labels(), 3);
+
+    // Renamed: further occurrences become new taxa
+    let mut newick_parser = NewickParser::new().with_duplicate_taxa(DuplicateTaxonPolicy::AutoRename);
+    let tree = newick_parser.parse(&mut ByteParser::from_str(newick)).unwrap();
+    let tree2 = newick_parser.parse(&mut ByteParser::from_str("((A,A),(C,B));")).unwrap();
+    assert!(tree.is_valid() && tree2.is_valid());
+    assert_eq!(tree2.bipartitions().len(), tree.bipartitions().len());
+
+    // Renamed taxon beyond the number of leaves of the first tree
+    let error = newick_parser.parse(&mut ByteParser::from_str("((A,A),(A,B));")).unwrap_err();
+    assert_eq!(error.kind(), &ParsingErrorType::DuplicateTaxon("A".to_string()));
+
+    let labels = newick_parser.into_leaf_label_map();
+    assert_eq!(labels.labels(), &["A", "B", "A_2", "C"]);
+    assert_eq!(tree.to_newick(&NewickStyle::Label, Some(&labels)), "((A:1,B:1):1,(A_2:1,C:1):1);");
+    assert_eq!(tree2.to_newick(&NewickStyle::Label, Some(&labels)), "((A,A_2),(C,B));");
+}
+
+#[test]
+fn test_duplicate_keys_in_topology_only_mode() {
+    let mut newick_parser = NewickParser::new().with_topology_only()
+        .with_duplicate_taxa(DuplicateTaxonPolicy::AutoRename);
+    let error = newick_parser.parse(&mut ByteParser::from_str("((1,2),(2,3));")).unwrap_err();
+    assert_eq!(error.kind(), &ParsingErrorType::DuplicateTaxon("2".to_string()));
+
+    let mut newick_parser = NewickParser::new().with_topology_only()
+        .with_duplicate_taxa(DuplicateTaxonPolicy::AllowDuplicates);
+    assert!(newick_parser.parse(&mut ByteParser::from_str("((1,2),(2,3));")).is_ok());
+}
+
+#[test]
+fn test_borrowed_labels() {
+    let newicks = "((Apteryx_haastii:1,'Apteryx owenii':1):1,'Strigops ''kakapo''':2);\n(('Apteryx owenii',Apteryx_haastii),'Strigops ''kakapo''');";
+    let (trees, labels) = parse_str_borrowed(newicks).unwrap();
+    let (expected_trees, expected_labels) = parse_newick_str(newicks).unwrap();
+
+    // Only the escaped quotes require an owned label
+    assert_eq!(labels.num_labels(), 3);
+    assert_eq!(labels.num_borrowed(), 2);
+    assert_eq!(labels.get_label(0), Some("Apteryx_haastii"));
+    assert_eq!(labels.get_label(1), Some("Apteryx owenii"));
+    assert_eq!(labels.get_label(2), Some("Strigops 'kakapo'"));
+
+    let labels = labels.into_owned();
+    assert_eq!(labels.labels(), expected_labels.labels());
+    for (tree, expected) in trees.iter().zip(&expected_trees) {
+        assert_eq!(tree.to_newick(&NewickStyle::Label, Some(&labels)),
+                   expected.to_newick(&NewickStyle::Label, Some(&expected_labels)));
+    }
+}
+
+#[test]
+fn test_borrowed_labels_duplicate_taxa() {
+    let input = b"((A,B),A);";
+    let mut newick_parser = NewickParser::new_borrowed();
+    let error = newick_parser.parse(&mut ByteParser::from_slice(input)).unwrap_err();
+    assert!(matches!(error.kind(), ParsingErrorType::DuplicateTaxon(label) if label == "A"));
+
+    let mut newick_parser = NewickParser::new_borrowed().with_duplicate_taxa(DuplicateTaxonPolicy::AutoRename);
+    let tree = newick_parser.parse(&mut ByteParser::from_slice(input)).unwrap();
+    let labels = newick_parser.into_leaf_label_map();
+    assert_eq!(tree.num_leaves(), 3);
+    assert_eq!(labels.labels(), &vec!["A", "B", "A_2"]);
+    assert_eq!(labels.num_borrowed(), 2);
+}
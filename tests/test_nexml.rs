@@ -0,0 +1,107 @@
+use nexus_parser::io::parser::nexml;
+use nexus_parser::io::parser::parsing_error::ParsingErrorType;
+#[cfg(feature = "fs")]
+use nexus_parser::io::writer::newick::NewickStyle;
+#[cfg(feature = "fs")]
+use nexus_parser::{parse_nexml_file, parse_trees_file};
+#[cfg(feature = "fs")]
+use std::path::Path;
+
+fn nexml_error(xml: &str) -> ParsingErrorType {
+    nexml::parse_str(xml).expect_err("Invalid NeXML is rejected").kind().clone()
+}
+
+/// NeXML document with taxa A, B and C and a single tree with the given nodes and edges
+fn nexml_tree(nodes_and_edges: &str) -> String {
+    format!("<nexml><otus id=\"taxa\"><otu id=\"a\" label=\"A\"/><otu id=\"b\" label=\"B\"/><otu id=\"c\" label=\"C\"/></otus>\
+             <trees otus=\"taxa\"><tree id=\"t\">{}</tree></trees></nexml>", nodes_and_edges)
+}
+
+#[cfg(feature = "fs")]
+#[test]
+fn test_nexml_file() {
+    let path = Path::new("tests").join("fixtures").join("nexml_t2_n4.xml");
+    let (trees, labels) = parse_nexml_file(path.to_str().unwrap()).unwrap();
+
+    assert_eq!(trees.len(), 2);
+    assert_eq!(labels.labels(), &vec!["Homo sapiens".to_string(), "Pan troglodytes".to_string(),
+                                      "Gorilla gorilla".to_string(), "Tl4".to_string()]);
+    for tree in &trees {
+        assert!(tree.is_valid());
+        assert_eq!(tree.num_leaves(), 4);
+        assert_eq!(tree.num_leaves_init(), 4);
+    }
+
+    let first = &trees[0];
+    assert_eq!(first.name().unwrap(), "Bayesian consensus");
+    assert!(first.is_rooted());
+    assert_eq!(first.to_newick(&NewickStyle::Label, Some(&labels)),
+               "(('Homo sapiens':0.1,'Pan troglodytes':0.1)0.97:0.2,'Gorilla gorilla':0.3,Tl4:0.3);");
+    assert!(first.is_ultrametric());
+
+    let second = &trees[1];
+    assert_eq!(second.name().unwrap(), "Tr2");
+    assert!(!second.is_rooted());
+    assert_eq!(second.to_newick(&NewickStyle::ZeroIndexed, None), "((1,3),0,2);");
+
+    // Format is detected
+    let (detected, _) = parse_trees_file(path.to_str().unwrap()).unwrap();
+    assert_eq!(detected.len(), 2);
+}
+
+#[test]
+fn test_nexml_deep_tree() {
+    let num_leaves = 50_000;
+    let mut xml = String::from("<nexml><otus id=\"taxa\">");
+    for k in 0..num_leaves {
+        xml.push_str(&format!("<otu id=\"o{}\"/>", k));
+    }
+    xml.push_str("</otus><trees otus=\"taxa\"><tree id=\"caterpillar\">");
+    // Internal node i has children i + 1 (internal) and leaf i
+    for i in 0..num_leaves - 1 {
+        xml.push_str(&format!("<node id=\"i{}\"/><node id=\"l{}\" otu=\"o{}\"/><edge id=\"e{}\" source=\"i{}\" target=\"l{}\"/>", i, i, i, i, i, i));
+        if i > 0 {
+            xml.push_str(&format!("<edge id=\"f{}\" source=\"i{}\" target=\"i{}\"/>", i, i - 1, i));
+        }
+    }
+    let last = num_leaves - 1;
+    xml.push_str(&format!("<node id=\"l{}\" otu=\"o{}\"/><edge id=\"e{}\" source=\"i{}\" target=\"l{}\"/>", last, last, last, last - 1, last));
+    xml.push_str("</tree></trees></nexml>");
+
+    let (trees, labels) = nexml::parse_str(&xml).unwrap();
+    assert_eq!(labels.num_labels(), num_leaves);
+    assert!(trees[0].is_valid());
+    assert!(trees[0].is_binary());
+    assert_eq!(trees[0].num_leaves(), num_leaves);
+}
+
+#[test]
+fn test_nexml_errors() {
+    assert!(matches!(nexml_error("<phyloxml></phyloxml>"), ParsingErrorType::InvalidNeXml(_)));
+
+    // Unknown otu and node
+    assert!(matches!(nexml_error(&nexml_tree("<node id=\"r\"/><node id=\"x\" otu=\"z\"/><edge id=\"e\" source=\"r\" target=\"x\"/>")),
+                     ParsingErrorType::UnresolvedLabel(_)));
+    assert!(matches!(nexml_error(&nexml_tree("<node id=\"r\"/><node id=\"x\" otu=\"a\"/><edge id=\"e\" source=\"r\" target=\"y\"/>")),
+                     ParsingErrorType::InvalidNeXml(_)));
+
+    // Not a tree: disconnected, cycle, second parent, leaf without otu
+    assert!(matches!(nexml_error(&nexml_tree("<node id=\"r\"/><node id=\"x\" otu=\"a\"/><node id=\"y\" otu=\"b\"/>\
+                                              <edge id=\"e\" source=\"r\" target=\"x\"/>")),
+                     ParsingErrorType::InvalidNeXml(_)));
+    assert!(matches!(nexml_error(&nexml_tree("<node id=\"r\"/><node id=\"x\" otu=\"a\"/><node id=\"u\"/><node id=\"v\"/>\
+                                              <edge id=\"e\" source=\"r\" target=\"x\"/><edge id=\"f\" source=\"u\" target=\"v\"/>\
+                                              <edge id=\"g\" source=\"v\" target=\"u\"/>")),
+                     ParsingErrorType::InvalidNeXml(_)));
+    assert!(matches!(nexml_error(&nexml_tree("<node id=\"r\"/><node id=\"u\"/><node id=\"x\" otu=\"a\"/>\
+                                              <edge id=\"e\" source=\"r\" target=\"u\"/><edge id=\"f\" source=\"r\" target=\"x\"/>\
+                                              <edge id=\"g\" source=\"u\" target=\"x\"/>")),
+                     ParsingErrorType::InvalidNeXml(_)));
+    assert!(matches!(nexml_error(&nexml_tree("<node id=\"r\"/><node id=\"x\"/><edge id=\"e\" source=\"r\" target=\"x\"/>")),
+                     ParsingErrorType::InvalidNeXml(_)));
+
+    // Duplicate taxon
+    assert!(matches!(nexml_error(&nexml_tree("<node id=\"r\"/><node id=\"x\" otu=\"a\"/><node id=\"y\" otu=\"a\"/>\
+                                              <edge id=\"e\" source=\"r\" target=\"x\"/><edge id=\"f\" source=\"r\" target=\"y\"/>")),
+                     ParsingErrorType::DuplicateTaxon(label) if label == "A"));
+}
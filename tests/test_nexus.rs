@@ -1,9 +1,23 @@
-use nexus_parser::io::parser::nexus::{Burnin, NexusParserBuilder};
+use nexus_parser::io::parser::newick::{AnnotationMode, NegativeBranchLengthPolicy};
+#[cfg(feature = "fs")]
+use nexus_parser::io::parser::nexus;
+#[cfg(feature = "fs")]
+use nexus_parser::io::parser::nexus::Burnin;
+use nexus_parser::io::parser::nexus::{LoadOptions, NexusParserBuilder, ParseOptions, TreeReader};
+use nexus_parser::io::parser::parsing_error::ParsingErrorType;
 use nexus_parser::io::writer::newick::NewickStyle;
-use nexus_parser::parse_nexus_file;
+use nexus_parser::model::alignment::DataType;
+use nexus_parser::model::leaf_label_map::DuplicateTaxonPolicy;
+use nexus_parser::model::tree::Tree;
+use nexus_parser::parse_nexus_bytes;
+#[cfg(feature = "fs")]
+use nexus_parser::{parse_nexus_document, parse_nexus_file, parse_nexus_file_lenient, parse_nexus_file_with_options, parse_nexus_reader, parse_nexus_sample};
 use std::fs::File;
+use std::io::Cursor;
 use std::path::Path;
+use std::sync::{Arc, Mutex};
 
+#[cfg(feature = "fs")]
 #[test]
 fn test_single_tree() {
     let path = Path::new("tests").join("fixtures").join("nexus_t1_n10.trees");
@@ -22,6 +36,7 @@ fn test_single_tree() {
     assert!(tree.is_valid());
 }
 
+#[cfg(feature = "fs")]
 #[test]
 fn test_multiple_trees_with_translate() {
     let path = Path::new("tests").join("fixtures").join("nexus_t11_n20_translate.trees");
@@ -41,6 +56,7 @@ fn test_multiple_trees_with_translate() {
     }
 }
 
+#[cfg(feature = "fs")]
 #[test]
 fn test_comments_and_unknown_blocks() {
     let path = Path::new("tests").join("fixtures").join("nexus_t3_n10_comments.trees");
@@ -60,6 +76,7 @@ fn test_comments_and_unknown_blocks() {
     }
 }
 
+#[cfg(feature = "fs")]
 #[test]
 fn test_skip_first() {
     let path = Path::new("tests").join("fixtures").join("nexus_t11_n20_translate.trees");
@@ -84,6 +101,7 @@ fn test_skip_first() {
     assert_eq!(count, 10);
 }
 
+#[cfg(feature = "fs")]
 #[test]
 fn test_burnin_count() {
     let path = Path::new("tests").join("fixtures").join("nexus_t11_n20_translate.trees");
@@ -108,13 +126,14 @@ fn test_burnin_count() {
     assert_eq!(count, 6);
 }
 
+#[cfg(feature = "fs")]
 #[test]
 fn test_burnin_percentage() {
     let path = Path::new("tests").join("fixtures").join("nexus_t11_n20_translate.trees");
     let file = File::open(&path).unwrap();
 
     // 25% of 11 trees = 2.75 -> floor to 2
-    let mut parser = NexusParserBuilder::for_file(file).unwrap()
+    let parser = NexusParserBuilder::for_file(file).unwrap()
         .with_burnin(Burnin::Percentage(0.25))
         .eager()
         .build()
@@ -124,12 +143,13 @@ fn test_burnin_percentage() {
     assert_eq!(parser.num_total_trees(), 11);
 }
 
+#[cfg(feature = "fs")]
 #[test]
 fn test_skip_first_and_burnin() {
     let path = Path::new("tests").join("fixtures").join("nexus_t11_n20_translate.trees");
     let file = File::open(&path).unwrap();
 
-    let mut parser = NexusParserBuilder::for_file(file).unwrap()
+    let parser = NexusParserBuilder::for_file(file).unwrap()
         .with_skip_first()
         .with_burnin(Burnin::Count(2))
         .eager()
@@ -141,6 +161,7 @@ fn test_skip_first_and_burnin() {
     assert_eq!(parser.num_total_trees(), 11);
 }
 
+#[cfg(feature = "fs")]
 #[test]
 fn test_lazy_mode() {
     let path = Path::new("tests").join("fixtures").join("nexus_t11_n20_translate.trees");
@@ -163,6 +184,7 @@ fn test_lazy_mode() {
     assert_eq!(count, 11);
 }
 
+#[cfg(feature = "fs")]
 #[test]
 fn test_lazy_mode_with_burnin() {
     let path = Path::new("tests").join("fixtures").join("nexus_t11_n20_translate.trees");
@@ -185,6 +207,7 @@ fn test_lazy_mode_with_burnin() {
     assert_eq!(count, 8);
 }
 
+#[cfg(feature = "fs")]
 #[test]
 fn test_lazy_mode_reset() {
     let path = Path::new("tests").join("fixtures").join("nexus_t3_n10_comments.trees");
@@ -209,6 +232,7 @@ fn test_lazy_mode_reset() {
         tree1_again.to_newick(&NewickStyle::ZeroIndexed, None));
 }
 
+#[cfg(feature = "fs")]
 #[test]
 fn test_lazy_mode_reset_with_burnin() {
     let path = Path::new("tests").join("fixtures").join("nexus_t11_n20_translate.trees");
@@ -239,3 +263,729 @@ fn test_lazy_mode_reset_with_burnin() {
     assert_eq!(first_tree.to_newick(&NewickStyle::ZeroIndexed, None),
         first_tree_again.to_newick(&NewickStyle::ZeroIndexed, None));
 }
+
+#[cfg(feature = "fs")]
+#[test]
+fn test_tree_reader_matches_eager_parsing() {
+    for fixture in ["nexus_t1_n10.trees", "nexus_t11_n20_translate.trees", "nexus_t3_n10_comments.trees"] {
+        let path = Path::new("tests").join("fixtures").join(fixture);
+        let (trees, leaf_map) = parse_nexus_file(path.to_str().unwrap()).unwrap();
+
+        let mut reader = TreeReader::for_file(File::open(&path).unwrap()).unwrap();
+        let streamed: Vec<_> = reader.by_ref().map(|tree| tree.unwrap()).collect();
+        let streamed_map = reader.into_leaf_label_map();
+
+        assert_eq!(streamed.len(), trees.len(), "{}", fixture);
+        assert_eq!(streamed_map.labels(), leaf_map.labels());
+        for (tree, streamed_tree) in trees.iter().zip(&streamed) {
+            assert_eq!(streamed_tree.name(), tree.name());
+            assert_eq!(streamed_tree.to_newick(&NewickStyle::ZeroIndexed, None),
+                tree.to_newick(&NewickStyle::ZeroIndexed, None));
+        }
+    }
+}
+
+#[test]
+fn test_tree_reader_quoted_labels_and_unknown_blocks() {
+    let nexus = "#NEXUS
+Begin taxa;
+\tDimensions ntax=3;
+\tTaxlabels 'Wilson''s storm-petrel' 'A;B' C;
+End;
+Begin notes;
+\tThat's a weird block; isn't it?
+End;
+Begin trees;
+\tTranslate 1 'Wilson''s storm-petrel', 2 'A;B', 3 C;
+\ttree first = ((1:1,2:1):1,3:2);
+\ttree 'second tree' = [&R] ((1:1,3:1):1,2:2);
+End;
+";
+    let mut reader = TreeReader::new(Cursor::new(nexus)).unwrap();
+    assert_eq!(reader.leaf_label_map().num_labels(), 3);
+    assert!(reader.leaf_label_map().contains_label("Wilson's storm-petrel"));
+
+    let first = reader.next().unwrap().unwrap();
+    assert_eq!(first.name().unwrap(), "first");
+    assert_eq!(first.to_newick(&NewickStyle::ZeroIndexed, None), "((0:1,1:1):1,2:2);");
+    let second = reader.next().unwrap().unwrap();
+    assert_eq!(second.name().unwrap(), "second tree");
+    assert!(reader.next().is_none());
+
+    // Same for in-memory parser
+    let (trees, _) = NexusParserBuilder::for_bytes(nexus.as_bytes().to_vec())
+        .build().unwrap()
+        .into_results().unwrap();
+    assert_eq!(trees.len(), 2);
+}
+
+fn tree_names<'a>(trees: impl IntoIterator<Item = &'a Tree>) -> Vec<String> {
+    trees.into_iter().map(|tree| tree.name().unwrap().clone()).collect()
+}
+
+#[cfg(feature = "fs")]
+#[test]
+fn test_load_options() {
+    let path = Path::new("tests").join("fixtures").join("nexus_t11_n20_translate.trees");
+    let options = LoadOptions { skip_first: 1, skip_fraction: 0.25, thin_every: 3 };
+
+    // 10 trees after skipping first, 2 discarded as burnin, then every third of the remaining 8
+    let (trees, _) = parse_nexus_sample(path.to_str().unwrap(), options).unwrap();
+    assert_eq!(tree_names(&trees), vec!["STATE_3000", "STATE_6000", "STATE_9000"]);
+
+    // Lazy mode selects the same trees, also after reset
+    let mut parser = NexusParserBuilder::for_file(File::open(&path).unwrap()).unwrap()
+        .with_load_options(options)
+        .lazy().build().unwrap();
+    assert_eq!(parser.num_trees(), 3);
+    assert_eq!(parser.num_total_trees(), 11);
+    assert_eq!(parser.next_tree().unwrap().unwrap().name().unwrap(), "STATE_3000");
+    parser.reset();
+    let (trees, _) = parser.into_results().unwrap();
+    assert_eq!(tree_names(&trees), vec!["STATE_3000", "STATE_6000", "STATE_9000"]);
+
+    // Remaining trees of eager parser
+    let mut parser = NexusParserBuilder::for_file(File::open(&path).unwrap()).unwrap()
+        .with_thinning(5)
+        .build().unwrap();
+    assert_eq!(parser.num_trees(), 3);
+    assert_eq!(parser.next_tree().unwrap().unwrap().name().unwrap(), "STATE_0");
+    let (trees, _) = parser.into_results().unwrap();
+    assert_eq!(tree_names(&trees), vec!["STATE_5000", "STATE_10000"]);
+
+    // Defaults load everything
+    let (trees, _) = parse_nexus_sample(path.to_str().unwrap(), LoadOptions::default()).unwrap();
+    assert_eq!(trees.len(), 11);
+}
+
+#[test]
+fn test_tree_reader_load_options() {
+    let path = Path::new("tests").join("fixtures").join("nexus_t11_n20_translate.trees");
+    let reader = TreeReader::new(std::io::BufReader::new(File::open(&path).unwrap())).unwrap()
+        .with_load_options(LoadOptions { skip_first: 2, skip_fraction: 0.0, thin_every: 4 });
+
+    let trees: Vec<_> = reader.map(|tree| tree.unwrap()).collect();
+    assert_eq!(tree_names(&trees), vec!["STATE_2000", "STATE_6000", "STATE_10000"]);
+}
+
+#[test]
+#[should_panic(expected = "Cannot discard a fraction")]
+fn test_tree_reader_burnin_fraction_panics() {
+    let nexus = "#NEXUS\nBegin trees;\n tree t = (A,B);\nEnd;";
+    let _ = TreeReader::new(Cursor::new(nexus)).unwrap()
+        .with_load_options(LoadOptions { skip_fraction: 0.1, ..LoadOptions::default() });
+}
+
+#[cfg(feature = "fs")]
+#[test]
+#[should_panic(expected = "Thinning interval must be positive")]
+fn test_zero_thinning_panics() {
+    let path = Path::new("tests").join("fixtures").join("nexus_t11_n20_translate.trees");
+    let _ = NexusParserBuilder::for_file(File::open(&path).unwrap()).unwrap().with_thinning(0);
+}
+
+#[test]
+fn test_tree_reader_errors() {
+    assert!(TreeReader::new(Cursor::new("Begin trees; tree t = (A,B); End;")).is_err());
+
+    let mut reader = TreeReader::new(Cursor::new("#NEXUS\nBegin trees;\n tree t = (A,B;\n tree u = (A,B);\nEnd;")).unwrap();
+    assert!(reader.next().unwrap().is_err());
+    assert!(reader.next().unwrap().is_ok());
+    assert!(reader.next().is_none());
+}
+
+#[test]
+fn test_underscores_to_spaces() {
+    let nexus = "#NEXUS
+Begin taxa;
+\tDimensions ntax=3;
+\tTaxlabels Homo_sapiens 'Pan_troglodytes' Gorilla_gorilla;
+End;
+Begin trees;
+\ttree first = ((Homo_sapiens:1,'Pan_troglodytes':1):1,'Gorilla gorilla':2);
+End;
+";
+    let (trees, labels) = NexusParserBuilder::for_bytes(nexus.as_bytes().to_vec())
+        .with_underscores_to_spaces()
+        .build().unwrap()
+        .into_results().unwrap();
+    assert_eq!(trees.len(), 1);
+    assert_eq!(labels.labels(), &["Homo sapiens", "Pan_troglodytes", "Gorilla gorilla"]);
+    assert_eq!(trees[0].to_newick(&NewickStyle::ZeroIndexed, None), "((0:1,1:1):1,2:2);");
+
+    // Same for streaming reader
+    let mut reader = TreeReader::new_with_underscores_to_spaces(Cursor::new(nexus), true).unwrap();
+    assert_eq!(reader.leaf_label_map().labels(), labels.labels());
+    assert!(reader.next().unwrap().is_ok());
+
+    // Without conversion, the quoted 'Gorilla gorilla' does not match TAXLABELS
+    let result = NexusParserBuilder::for_bytes(nexus.as_bytes().to_vec()).build();
+    assert!(result.is_err());
+}
+
+/// Nexus string with the given TRANSLATE command and a tree over keys 1, 2, 3
+fn nexus_with_translate(translate: &str) -> String {
+    format!("#NEXUS\nBegin trees;\n\tTranslate {}\n\ttree t = ((1:1,2:1):1,3:2);\nEnd;\n", translate)
+}
+
+#[test]
+fn test_translate_quotes_and_comments() {
+    let nexus = nexus_with_translate(
+        "1 'Homo sapiens [isolate 7]', [comment] 2 [before] 'Wilson''s storm-petrel' [after],\n\t\t3 Pan[x]_troglodytes[;];"
+    );
+
+    let mut reader = TreeReader::new(Cursor::new(nexus.clone())).unwrap();
+    let labels = reader.leaf_label_map();
+    assert!(labels.contains_label("Homo sapiens [isolate 7]"));
+    assert!(labels.contains_label("Wilson's storm-petrel"));
+    assert!(labels.contains_label("Pan_troglodytes"));
+    assert!(reader.next().unwrap().is_ok());
+
+    let (trees, labels) = NexusParserBuilder::for_bytes(nexus.into_bytes())
+        .build().unwrap()
+        .into_results().unwrap();
+    assert_eq!(trees.len(), 1);
+    assert_eq!(labels.num_labels(), 3);
+}
+
+#[test]
+fn test_translate_without_commas_and_mixed_keys() {
+    // Entries on separate lines without commas, trailing comma, quoted label with comma
+    let nexus = nexus_with_translate("\n\t\t1 'Pan, troglodytes'\n\t\t2 Gorilla [x]\n\t\t3 Homo,\n\t;");
+    let mut reader = TreeReader::new(Cursor::new(nexus)).unwrap();
+    let table = reader.translation_table().unwrap();
+    assert_eq!(table.iter().collect::<Vec<_>>(), vec![("1", "Pan, troglodytes"), ("2", "Gorilla"), ("3", "Homo")]);
+    assert!(table.is_integer_keyed());
+    assert!(reader.next().unwrap().is_ok());
+
+    // Mixed numeric and string keys
+    let nexus = "#NEXUS\nBegin trees;\n\tTranslate 1 A, b B, '3' C;\n\ttree t = ((1,b),'3');\nEnd;\n";
+    let parser = NexusParserBuilder::for_bytes(nexus.as_bytes().to_vec()).build().unwrap();
+    let table = parser.translation_table().unwrap();
+    assert_eq!((table.get("b"), table.get("3"), table.key_of("A")), (Some("B"), Some("C"), Some("1")));
+    assert!(!table.is_integer_keyed());
+    let (trees, labels) = parser.into_results().unwrap();
+    assert_eq!(trees[0].to_newick(&NewickStyle::Label, Some(&labels)), "((A,B),C);");
+
+    // No TRANSLATE command
+    let parser = NexusParserBuilder::for_bytes(nexus_with_taxa("\ttree t = ((A,B),C);").into_bytes()).build().unwrap();
+    assert!(parser.translation_table().is_none());
+}
+
+#[test]
+fn test_translate_entry_errors_report_key() {
+    let cases = [
+        ("1 A, 2, 3 C;", "2", "Missing label"),
+        ("1 A, 2 B 3 C;", "2", "Expected"),
+        ("1 A, 1 B, 3 C;", "1", "Duplicate key"),
+        ("1 A, 'two' [no label];", "two", "Missing label"),
+    ];
+
+    for (translate, expected_key, expected_msg) in cases {
+        let result = NexusParserBuilder::for_bytes(nexus_with_translate(translate).into_bytes()).build();
+        let error = result.err().unwrap_or_else(|| panic!("Expected error for '{}'", translate));
+        match error.kind() {
+            ParsingErrorType::InvalidTranslateEntry { key, msg } => {
+                assert_eq!(key, expected_key);
+                assert!(msg.starts_with(expected_msg), "Unexpected message '{}'", msg);
+            }
+            kind => panic!("Unexpected error {:?} for '{}'", kind, translate),
+        }
+    }
+
+    let error = TreeReader::new(Cursor::new(nexus_with_translate("1 A, 2, 3 C;"))).err().unwrap();
+    assert!(error.to_string().contains("key '2'"));
+}
+
+#[cfg(feature = "fs")]
+#[test]
+fn test_parser_over_borrowed_buffer() {
+    let path = Path::new("tests").join("fixtures").join("nexus_t11_n20_translate.trees");
+    let bytes = std::fs::read(&path).unwrap();
+
+    let (borrowed_trees, borrowed_labels) = NexusParserBuilder::for_buffer(bytes.as_slice())
+        .build().unwrap()
+        .into_results().unwrap();
+    let (trees, labels) = parse_nexus_file(path.to_str().unwrap()).unwrap();
+
+    assert_eq!(borrowed_trees.len(), trees.len());
+    assert_eq!(borrowed_labels.labels(), labels.labels());
+    for (borrowed, tree) in borrowed_trees.iter().zip(&trees) {
+        assert_eq!(borrowed.to_newick(&NewickStyle::ZeroIndexed, None), tree.to_newick(&NewickStyle::ZeroIndexed, None));
+    }
+}
+
+#[cfg(feature = "fs")]
+#[test]
+fn test_parse_str_borrowed() {
+    // TAXA block and TRANSLATE command
+    let path = Path::new("tests").join("fixtures").join("nexus_t11_n20_translate.trees");
+    let nexus = std::fs::read_to_string(&path).unwrap();
+    let (borrowed_trees, borrowed_labels) = nexus::parse_str_borrowed(&nexus).unwrap();
+    let (trees, labels) = parse_nexus_file(path.to_str().unwrap()).unwrap();
+    assert_eq!(borrowed_labels.num_borrowed(), labels.num_labels());
+    assert_eq!(borrowed_trees.len(), trees.len());
+    for (borrowed, tree) in borrowed_trees.iter().zip(&trees) {
+        assert_eq!(borrowed.to_newick(&NewickStyle::ZeroIndexed, None), tree.to_newick(&NewickStyle::ZeroIndexed, None));
+    }
+    assert_eq!(borrowed_labels.into_owned().labels(), labels.labels());
+
+    // Only TRANSLATE labels with escaped quotes or comments inside are copied
+    let nexus = nexus_with_translate("1 'Homo sapiens', 2 'Wilson''s storm-petrel', 3 Pan[x]_troglodytes;");
+    let (trees, labels) = nexus::parse_str_borrowed(&nexus).unwrap();
+    assert_eq!(labels.num_borrowed(), 1);
+    assert_eq!(trees[0].to_newick(&NewickStyle::Label, Some(&labels.into_owned())),
+               "(('Homo sapiens':1,'Wilson''s storm-petrel':1):1,Pan_troglodytes:2);");
+
+    // Verbatim labels without TAXA block and TRANSLATE command
+    let nexus = "#NEXUS\nBegin trees;\n\ttree t = ((A,B),C);\n\ttree u = ((A,C),B);\nEnd;\n";
+    let (trees, labels) = nexus::parse_str_borrowed(nexus).unwrap();
+    assert_eq!((trees.len(), labels.num_labels(), labels.num_borrowed()), (2, 3, 3));
+}
+
+#[cfg(feature = "mmap")]
+#[test]
+fn test_parse_nexus_mmap() {
+    use nexus_parser::parse_nexus_mmap;
+
+    let path = Path::new("tests").join("fixtures").join("nexus_t11_n20_translate.trees");
+    let (mapped_trees, mapped_labels) = parse_nexus_mmap(path.to_str().unwrap()).unwrap();
+    let (trees, labels) = parse_nexus_file(path.to_str().unwrap()).unwrap();
+
+    assert_eq!(mapped_trees.len(), trees.len());
+    assert_eq!(mapped_labels.labels(), labels.labels());
+    assert_eq!(mapped_trees[3].to_newick(&NewickStyle::ZeroIndexed, None), trees[3].to_newick(&NewickStyle::ZeroIndexed, None));
+
+    // Lazy parsing works on the mapped file as well
+    let mut parser = NexusParserBuilder::for_mmap(&File::open(&path).unwrap()).unwrap()
+        .lazy().build().unwrap();
+    assert_eq!(parser.num_trees(), trees.len());
+    assert!(parser.next_tree().unwrap().is_some());
+}
+
+#[test]
+fn test_rooting_comments() {
+    let nexus = b"#NEXUS\nBegin trees;\n\
+        tree rooted = [&R] ((A,B),C);\n\
+        tree unrooted = [&lnP=-12.5] [&u] (A,B,C);\n\
+        tree unknown = ((A,B),C);\n\
+        End;\n";
+
+    let (trees, _) = NexusParserBuilder::for_buffer(nexus.as_slice())
+        .build().unwrap()
+        .into_results().unwrap();
+
+    assert_eq!(trees.len(), 3);
+    assert!(trees[0].is_rooted());
+    assert!(!trees[1].is_rooted());
+    assert_eq!(trees[1].num_leaves(), 3);
+    // Rooted unless marked otherwise
+    assert!(trees[2].is_rooted());
+}
+
+/// Nexus string with TAXA block of A, B, C and the given TREES block content
+fn nexus_with_taxa(trees: &str) -> String {
+    format!("#NEXUS\nBegin taxa;\n\tDimensions ntax=3;\n\tTaxlabels A B C;\nEnd;\nBegin trees;\n{}\nEnd;\n", trees)
+}
+
+fn strict_error(nexus: &str) -> ParsingErrorType {
+    let options = ParseOptions { strict: true, ..ParseOptions::default() };
+    let error = NexusParserBuilder::for_bytes(nexus.as_bytes().to_vec())
+        .with_options(options)
+        .build().err().expect("Strict parsing should fail");
+
+    // Streaming reader fails the same way, when creating it or on the first tree
+    let reader_error = match TreeReader::new_with_options(Cursor::new(nexus.to_string()), options) {
+        Ok(mut reader) => reader.next().unwrap().expect_err("Strict reading should fail"),
+        Err(e) => e,
+    };
+    assert_eq!(reader_error.kind(), error.kind());
+    error.kind().clone()
+}
+
+#[cfg(feature = "fs")]
+#[test]
+fn test_strict_accepts_declared_taxa() {
+    let nexus = nexus_with_taxa("\tTranslate 1 A, 2 B, 3 C;\n\ttree t1 = ((1,2),3);\n\ttree t2 = ((1,3),2);");
+    let (trees, labels) = NexusParserBuilder::for_bytes(nexus.into_bytes())
+        .with_strict()
+        .build().unwrap()
+        .into_results().unwrap();
+    assert_eq!(trees.len(), 2);
+    assert_eq!(labels.num_labels(), 3);
+
+    for fixture in ["nexus_t1_n10.trees", "nexus_t11_n20_translate.trees", "nexus_t3_n10_comments.trees"] {
+        let path = Path::new("tests").join("fixtures").join(fixture);
+        let options = ParseOptions { strict: true, ..ParseOptions::default() };
+        assert!(parse_nexus_file_with_options(path.to_str().unwrap(), options).is_ok(), "{}", fixture);
+    }
+}
+
+#[test]
+fn test_strict_rejects_missing_and_duplicate_taxa() {
+    let kind = strict_error(&nexus_with_taxa("\ttree t = (A,B);"));
+    assert_eq!(kind, ParsingErrorType::TaxonCountMismatch { expected: 3, found: 2 });
+
+    let kind = strict_error(&nexus_with_taxa("\ttree t = ((A,B),(A,C));"));
+    assert_eq!(kind, ParsingErrorType::DuplicateTaxon("A".to_string()));
+
+    // Even if duplicates are allowed otherwise
+    let nexus = nexus_with_taxa("\ttree t = ((A,B),(A,C));");
+    let options = ParseOptions { strict: true, duplicate_taxa: DuplicateTaxonPolicy::AllowDuplicates, ..ParseOptions::default() };
+    let error = NexusParserBuilder::for_bytes(nexus.into_bytes()).with_options(options).build().err().unwrap();
+    assert_eq!(error.kind(), &ParsingErrorType::DuplicateTaxon("A".to_string()));
+
+    // Lenient parsing accepts both
+    let nexus = nexus_with_taxa("\ttree t1 = (A,B);\n\ttree t2 = ((A,B),(A,C));");
+    let (trees, _) = NexusParserBuilder::for_bytes(nexus.into_bytes())
+        .with_duplicate_taxa(DuplicateTaxonPolicy::AllowDuplicates)
+        .build().unwrap()
+        .into_results().unwrap();
+    assert_eq!(trees.len(), 2);
+}
+
+#[test]
+fn test_duplicate_taxa_in_nexus() {
+    // Key 1 twice in translated tree, reported by its label
+    let nexus = nexus_with_taxa("\tTranslate 1 A, 2 B, 3 C;\n\ttree t = ((1,2),(1,3));");
+    let error = NexusParserBuilder::for_bytes(nexus.clone().into_bytes()).build().err().unwrap();
+    assert_eq!(error.kind(), &ParsingErrorType::DuplicateTaxon("A".to_string()));
+    let error = TreeReader::new(Cursor::new(nexus.clone())).unwrap().next().unwrap().unwrap_err();
+    assert_eq!(error.kind(), &ParsingErrorType::DuplicateTaxon("A".to_string()));
+
+    // Renamed taxa need to be declared, since the number of taxa is fixed by the TAXA block
+    let options = ParseOptions { duplicate_taxa: DuplicateTaxonPolicy::AutoRename, ..ParseOptions::default() };
+    let mut reader = TreeReader::new_with_options(Cursor::new(nexus), options).unwrap();
+    let error = reader.next().unwrap().unwrap_err();
+    assert_eq!(error.kind(), &ParsingErrorType::DuplicateTaxon("A".to_string()));
+
+    let nexus = "#NEXUS\nBegin taxa;\n\tTaxlabels A A_2 B;\nEnd;\nBegin trees;\n\ttree t = ((A,A),B);\nEnd;\n";
+    let mut reader = TreeReader::new_with_options(Cursor::new(nexus), options).unwrap();
+    let tree = reader.next().unwrap().unwrap();
+    assert!(tree.is_valid());
+    assert_eq!(tree.to_newick(&NewickStyle::Label, Some(reader.leaf_label_map())), "((A,A_2),B);");
+}
+
+#[test]
+fn test_negative_branch_lengths_in_nexus() {
+    let nexus = "#NEXUS\nBEGIN TREES;\n\ttree t = ((A:1,B:1):-2.5e-16,C:1);\nEND;\n";
+    let trees = parse_nexus_bytes(nexus.as_bytes()).unwrap().0;
+    assert!(trees[0].is_valid() && trees[0].validate().is_empty());
+
+    let options = ParseOptions { negative_branch_lengths: NegativeBranchLengthPolicy::Error, ..ParseOptions::default() };
+    let builder = NexusParserBuilder::for_bytes(nexus.as_bytes().to_vec()).with_options(options);
+    assert!(builder.build().is_err());
+    let mut reader = TreeReader::new_with_options(Cursor::new(nexus), options).unwrap();
+    assert!(reader.next().unwrap().is_err());
+
+    let (trees, _) = NexusParserBuilder::for_bytes(nexus.as_bytes().to_vec())
+        .with_negative_branch_lengths(NegativeBranchLengthPolicy::Keep)
+        .build().unwrap()
+        .into_results().unwrap();
+    assert!(!trees[0].validate().is_empty());
+}
+
+#[test]
+fn test_strict_rejects_undeclared_taxa() {
+    // Translate label not in TAXA block
+    let kind = strict_error(&nexus_with_taxa("\tTranslate 1 A, 2 B, 3 D;\n\ttree t = ((1,2),3);"));
+    assert_eq!(kind, ParsingErrorType::UndeclaredTaxon("D".to_string()));
+
+    // Translate with fewer taxa than declared
+    let nexus = nexus_with_taxa("\tTranslate 1 A, 2 B;\n\ttree t = (1,2);");
+    let options = ParseOptions { strict: true, ..ParseOptions::default() };
+    let error = NexusParserBuilder::for_bytes(nexus.into_bytes()).with_options(options).build().err().unwrap();
+    assert_eq!(error.kind(), &ParsingErrorType::TaxonCountMismatch { expected: 3, found: 2 });
+
+    // No TAXA block at all
+    let kind = strict_error("#NEXUS\nBegin trees;\n\ttree t = ((A,B),C);\nEnd;\n");
+    assert_eq!(kind, ParsingErrorType::UndeclaredTaxon("A".to_string()));
+    let kind = strict_error(&nexus_with_translate("1 A, 2 B, 3 C;"));
+    assert_eq!(kind, ParsingErrorType::UndeclaredTaxon("A".to_string()));
+}
+
+#[test]
+fn test_tree_names_and_metadata() {
+    let nexus = b"#NEXUS\nBegin trees;\n\
+        tree STATE_0 [&lnP=-12.5,posterior=-20.25] = [&R] ((A,B),C);\n\
+        tree gen.1000 = [&U] [&lnP=-10] [sampled] (A,B,C);\n\
+        tree plain = ((A,B),C);\n\
+        End;\n";
+
+    let (trees, _) = NexusParserBuilder::for_buffer(nexus.as_slice())
+        .build().unwrap()
+        .into_results().unwrap();
+
+    let names: Vec<&str> = trees.iter().map(|tree| tree.name().unwrap().as_str()).collect();
+    assert_eq!(names, vec!["STATE_0", "gen.1000", "plain"]);
+
+    // Kept raw without annotations configured
+    assert_eq!(trees[0].metadata().unwrap().raw(), "lnP=-12.5,posterior=-20.25");
+    assert!(!trees[0].metadata().unwrap().is_parsed());
+    assert_eq!(trees[1].metadata().unwrap().raw(), "lnP=-10,sampled");
+    assert!(!trees[1].is_rooted());
+    assert!(trees[2].metadata().is_none());
+
+    // Parsed with parsed annotations
+    let mut reader = TreeReader::new(Cursor::new(nexus.to_vec())).unwrap()
+        .with_annotations(AnnotationMode::Parsed);
+    let tree = reader.next().unwrap().unwrap();
+    let metadata = tree.metadata().unwrap();
+    assert_eq!(metadata.get("lnP").and_then(|value| value.as_number()), Some(-12.5));
+    assert_eq!(metadata.get("posterior").and_then(|value| value.as_number()), Some(-20.25));
+}
+
+#[cfg(feature = "fs")]
+#[test]
+fn test_lenient_skips_malformed_trees() {
+    let path = Path::new("tests").join("fixtures").join("nexus_t6_n4_malformed.trees");
+    assert!(parse_nexus_file(path.to_str().unwrap()).is_err());
+
+    let (trees, skipped, labels) = parse_nexus_file_lenient(path.to_str().unwrap()).unwrap();
+    let names: Vec<_> = trees.iter().map(|tree| tree.name().unwrap().as_str()).collect();
+    assert_eq!(names, vec!["TREE_0", "TREE_2", "TREE_4"]);
+    assert_eq!(labels.num_labels(), 4);
+    assert!(trees.iter().all(|tree| tree.is_valid()));
+
+    let skipped_indices: Vec<_> = skipped.iter().map(|skipped| skipped.index).collect();
+    assert_eq!(skipped_indices, vec![1, 3, 5]);
+    assert_eq!(skipped[0].name.as_deref(), Some("TREE_1"));
+    assert!(matches!(skipped[1].error.kind(), ParsingErrorType::UnresolvedLabel(_)));
+}
+
+#[cfg(feature = "fs")]
+#[test]
+fn test_lenient_lazy_mode() {
+    let path = Path::new("tests").join("fixtures").join("nexus_t6_n4_malformed.trees");
+    let mut parser = NexusParserBuilder::for_file(File::open(path).unwrap()).unwrap()
+        .with_lenient()
+        .lazy()
+        .build().unwrap();
+    assert_eq!(parser.num_total_trees(), 6);
+
+    assert_eq!(parser.next_tree().unwrap().unwrap().name().unwrap(), "TREE_0");
+    assert_eq!(parser.next_tree().unwrap().unwrap().name().unwrap(), "TREE_2");
+    assert_eq!(parser.skipped_trees().len(), 1);
+
+    let (trees, skipped, _) = parser.into_results_with_skipped().unwrap();
+    assert_eq!(trees.len(), 1);
+    assert_eq!(skipped.len(), 3);
+}
+
+#[cfg(feature = "fs")]
+#[test]
+fn test_errors_report_line_column_and_tree_name() {
+    let path = Path::new("tests").join("fixtures").join("nexus_t6_n4_malformed.trees");
+    let (_, skipped, _) = parse_nexus_file_lenient(path.to_str().unwrap()).unwrap();
+    let error = &skipped[1].error;
+    assert_eq!(error.line(), Some(18));
+    assert!(error.column().unwrap() > 40);
+    assert_eq!(error.tree_name(), Some("TREE_3"));
+    let message = error.to_string();
+    assert!(message.contains("at line 18, column"), "{}", message);
+    assert!(message.contains("in tree 'TREE_3'"), "{}", message);
+
+    // Streamed commands report locations in the whole input, not in the command
+    let mut reader = TreeReader::new(Cursor::new("#NEXUS\nBegin trees;\n tree t = (A,B);\n tree u = (A,B;\nEnd;")).unwrap();
+    assert!(reader.next().unwrap().is_ok());
+    let error = reader.next().unwrap().unwrap_err();
+    assert_eq!(error.line(), Some(4));
+    assert_eq!(error.tree_name(), Some("u"));
+
+    let error = TreeReader::new(Cursor::new("#NEXUS\n\nBegin taxa;\n dimensions ntax=x;\nEnd;")).err().unwrap();
+    assert_eq!(error.line(), Some(4));
+    assert_eq!(error.tree_name(), None);
+}
+
+#[cfg(feature = "fs")]
+#[test]
+fn test_progress_hook() {
+    let path = Path::new("tests").join("fixtures").join("nexus_t11_n20_translate.trees");
+    let file_size = std::fs::metadata(&path).unwrap().len() as usize;
+    let calls = Arc::new(Mutex::new(Vec::new()));
+    let recorder = Arc::clone(&calls);
+    let hook = move |bytes_read, trees_parsed| recorder.lock().unwrap().push((bytes_read, trees_parsed));
+
+    // Small file: reported once after the last tree
+    let (trees, _) = NexusParserBuilder::for_file(File::open(&path).unwrap()).unwrap()
+        .with_progress(hook.clone())
+        .build().unwrap()
+        .into_results().unwrap();
+    let (bytes_read, trees_parsed) = *calls.lock().unwrap().last().unwrap();
+    assert_eq!(calls.lock().unwrap().len(), 1);
+    assert_eq!(trees_parsed, trees.len());
+    assert!(bytes_read > 0 && bytes_read <= file_size);
+
+    calls.lock().unwrap().clear();
+    let reader = TreeReader::new(File::open(&path).map(std::io::BufReader::new).unwrap()).unwrap().with_progress(hook);
+    assert_eq!(reader.count(), 11);
+    assert_eq!(calls.lock().unwrap().as_slice(), &[(file_size, 11)]);
+}
+
+#[test]
+fn test_progress_hook_reports_periodically() {
+    let tree = "tree t = ((A:1.0,B:1.0):1.0,(C:1.0,D:1.0):1.0);\n";
+    let num_trees = 2 * (1 << 20) / tree.len() + 10;
+    let nexus = format!("#NEXUS\nBegin trees;\n{}End;\n", tree.repeat(num_trees));
+    let calls = Arc::new(Mutex::new(Vec::new()));
+    let recorder = Arc::clone(&calls);
+    NexusParserBuilder::for_bytes(nexus.into_bytes())
+        .with_progress(move |bytes_read, trees_parsed| recorder.lock().unwrap().push((bytes_read, trees_parsed)))
+        .build().unwrap();
+    let calls = calls.lock().unwrap();
+    assert_eq!(calls.len(), 3);
+    assert!(calls.windows(2).all(|pair| pair[0].0 < pair[1].0 && pair[0].1 < pair[1].1));
+    assert_eq!(calls.last().unwrap().1, num_trees);
+}
+
+#[cfg(feature = "fs")]
+#[test]
+fn test_parse_nexus_reader_and_bytes() {
+    let path = Path::new("tests").join("fixtures").join("nexus_t11_n20_translate.trees");
+    let (trees, labels) = parse_nexus_file(path.to_str().unwrap()).unwrap();
+    let bytes = std::fs::read(&path).unwrap();
+
+    let (read_trees, read_labels) = parse_nexus_reader(Cursor::new(bytes.clone())).unwrap();
+    assert_eq!(read_labels.labels(), labels.labels());
+    assert!(trees.iter().zip(&read_trees).all(|(tree, read)| read.exact_eq(tree, 0.0)));
+
+    let (byte_trees, byte_labels) = parse_nexus_bytes(&bytes).unwrap();
+    assert_eq!(byte_labels.labels(), labels.labels());
+    assert!(trees.iter().zip(&byte_trees).all(|(tree, parsed)| parsed.exact_eq(tree, 0.0)));
+
+    // Byte order mark is skipped, invalid content fails
+    let (bom_trees, _) = parse_nexus_bytes(b"\xEF\xBB\xBF#NEXUS\nBegin trees;\n\ttree t = ((A,B),C);\nEnd;\n").unwrap();
+    assert_eq!(bom_trees.len(), 1);
+    assert!(parse_nexus_bytes(b"((A,B),C);").is_err());
+    assert!(parse_nexus_reader(Cursor::new("Begin trees;")).is_err());
+}
+
+#[cfg(feature = "fs")]
+#[test]
+fn test_output_styles_of_common_programs() {
+    // MrBayes, BEAST, PAUP*, RevBayes, and keywords in mixed case with comments in between
+    for (name, rooted) in [("mrbayes", false), ("beast", true), ("paup", false), ("revbayes", true), ("mixed_case", true)] {
+        let path = Path::new("tests").join("fixtures").join(format!("nexus_t2_n4_{}.trees", name));
+        let (trees, labels) = parse_nexus_file(path.to_str().unwrap())
+            .unwrap_or_else(|e| panic!("Failed to parse {} output: {}", name, e));
+        assert_eq!(trees.len(), 2, "{}", name);
+        assert_eq!(labels.num_labels(), 4, "{}", name);
+        assert!(trees.iter().all(|tree| tree.is_valid() && tree.is_rooted() == rooted), "{}", name);
+
+        let mut reader = TreeReader::new(Cursor::new(std::fs::read(&path).unwrap())).unwrap();
+        let read_trees: Vec<Tree> = reader.by_ref().map(Result::unwrap).collect();
+        assert_eq!(reader.leaf_label_map().labels(), labels.labels(), "{}", name);
+        assert!(trees.iter().zip(&read_trees).all(|(tree, read)| read.exact_eq(tree, 0.0)), "{}", name);
+    }
+}
+
+#[test]
+fn test_keywords_match_whole_words_only() {
+    // Commands starting like keywords are skipped, not misread
+    let nexus = "#NEXUS\nBEGIN TREES;\n\tTRANSLATEX 1 A;\n\tTREES = 1;\nTREE t = ((A,B),C);\nEND;\n";
+    let parser = NexusParserBuilder::for_bytes(nexus.as_bytes().to_vec()).build().unwrap();
+    assert!(parser.translation_table().is_none());
+    assert_eq!(parser.num_trees(), 1);
+
+    assert!(parse_nexus_bytes(b"#NEXUSFILE\nBEGIN TREES;\nTREE t = ((A,B),C);\nEND;\n").is_err());
+}
+
+#[test]
+fn test_raw_blocks() {
+    let data = "BEGIN DATA;\n\tDIMENSIONS NTAX=3 NCHAR=4;\n\tMATRIX\n\t\tA ACGT\n\t\tB ACGA\n\t\tC 'ACG[T]'\n\t;\nEND;";
+    let figtree = "begin figtree;\n\tset appearance.branchLineWidth=2.0;\nend;";
+    let nexus = format!("#NEXUS\n{}\nBEGIN TREES;\n\ttree t1 = ((A,B),C);\n\ttree t2 = ((A,C),B);\nEND;\n{}\n", data, figtree);
+
+    // Not retained by default
+    let parser = NexusParserBuilder::for_bytes(nexus.clone().into_bytes()).build().unwrap();
+    assert!(parser.raw_blocks().is_empty());
+
+    for lazy in [false, true] {
+        let builder = NexusParserBuilder::for_bytes(nexus.clone().into_bytes()).with_raw_blocks();
+        let parser = if lazy { builder.lazy() } else { builder }.build().unwrap();
+        let document = parser.into_document().unwrap();
+        assert_eq!(document.trees.len(), 2);
+        assert_eq!(document.taxa.num_labels(), 3);
+        assert_eq!(document.blocks.len(), 2);
+
+        let block = document.block("data").unwrap();
+        assert_eq!(block.name, "DATA");
+        assert_eq!(block.text(), data);
+        assert_eq!(&nexus.as_bytes()[block.range.clone()], data.as_bytes());
+        assert_eq!(document.block("FigTree").unwrap().text(), figtree);
+        assert!(document.block("trees").is_none());
+    }
+}
+
+#[cfg(feature = "fs")]
+#[test]
+fn test_parse_nexus_document() {
+    let path = Path::new("tests").join("fixtures").join("nexus_t3_n10_comments.trees");
+    let document = parse_nexus_document(path.to_str().unwrap()).unwrap();
+    assert_eq!(document.trees.len(), 3);
+    assert_eq!(document.blocks.len(), 1);
+    assert!(document.blocks[0].is_named("whatisthis"));
+    assert!(document.blocks[0].text().contains("unknown block"));
+}
+
+#[cfg(feature = "fs")]
+#[test]
+fn test_interleaved_alignment() {
+    let path = Path::new("tests").join("fixtures").join("nexus_t2_n4_alignment.trees");
+    let document = parse_nexus_document(path.to_str().unwrap()).unwrap();
+    assert_eq!(document.trees.len(), 2);
+    assert_eq!(document.taxa.num_labels(), 4);
+
+    let alignment = document.alignment.unwrap();
+    assert_eq!(alignment.data_type(), DataType::Dna);
+    assert_eq!(alignment.num_sites(), 12);
+    assert_eq!(alignment.num_sequences(), 4);
+    let sequence = |label: &str| alignment.sequence(document.taxa.get_index(label).unwrap()).unwrap();
+    assert_eq!(sequence("Homo_sapiens"), b"ACGTACGTTTGA");
+    // Match characters resolved against the first sequence
+    assert_eq!(sequence("Pan_troglodytes"), b"ACGT-CGTTTAA");
+    // Set of states as IUPAC code
+    assert_eq!(sequence("Gorilla_gorilla"), b"ACGAACRTTT-A");
+    // Upper case
+    assert_eq!(sequence("Pongo_abelii"), b"ACGTAC?TTTGA");
+}
+
+#[test]
+fn test_alignment() {
+    let nexus = "#NEXUS\nBEGIN CHARACTERS;\n\tDIMENSIONS NCHAR=6;\n\tFORMAT DATATYPE=Standard SYMBOLS=\"0 1 2\" MISSING=N GAP=-;\n\
+                 \tMATRIX\n\t\tA 012\n\t\t  (01)10\n\t\t'C c' 0 1 2 [x] 2 1 -\n\t\tD NNNNNN\n\t;\nEND;\n\
+                 BEGIN TREES;\n\ttree t = ((A,B),'C c');\nEND;\n";
+
+    // Not parsed by default
+    let document = NexusParserBuilder::for_bytes(nexus.as_bytes().to_vec()).build().unwrap().into_document().unwrap();
+    assert!(document.alignment.is_none());
+
+    for lazy in [false, true] {
+        let builder = NexusParserBuilder::for_bytes(nexus.as_bytes().to_vec()).with_alignment();
+        let parser = if lazy { builder.lazy() } else { builder }.build().unwrap();
+        let document = parser.into_document().unwrap();
+        let alignment = document.alignment.unwrap();
+        assert_eq!(alignment.data_type(), DataType::Standard);
+        assert_eq!(alignment.missing(), b'N');
+        assert_eq!(alignment.sequence(document.taxa.get_index("A").unwrap()), Some(&b"012N10"[..]));
+        assert_eq!(alignment.sequence(document.taxa.get_index("C c").unwrap()), Some(&b"01221-"[..]));
+        // Sequences of taxa not in the trees are added to the labels after those of the trees
+        assert_eq!(document.taxa.get_index("D"), Some(3));
+        assert!(!alignment.contains(document.taxa.get_index("B").unwrap()));
+        assert_eq!(alignment.site(2).collect::<Vec<_>>(), vec![(0, b'2'), (2, b'2'), (3, b'N')]);
+    }
+
+    let invalid = [
+        ("BEGIN DATA;\nMATRIX A ACGT;\nEND;", "nchar"),
+        ("BEGIN DATA;\nDIMENSIONS NCHAR=4;\nMATRIX A ACGT B ACG;\nEND;", "'B' has 3 characters"),
+        ("BEGIN DATA;\nDIMENSIONS NCHAR=4;\nFORMAT INTERLEAVE;\nMATRIX A ACGT\nB ACGTA;\nEND;", "more than 4"),
+        ("BEGIN DATA;\nDIMENSIONS NCHAR=2;\nMATRIX A AC A GT;\nEND;", "Duplicate sequence 'A'"),
+        ("BEGIN DATA;\nDIMENSIONS NCHAR=2;\nFORMAT DATATYPE=continuous;\nMATRIX A 0.1 0.2;\nEND;", "continuous"),
+    ];
+    for (block, msg) in invalid {
+        let nexus = format!("#NEXUS\n{}\nBEGIN TREES;\ntree t = (A,B);\nEND;\n", block);
+        let error = NexusParserBuilder::for_bytes(nexus.into_bytes()).with_alignment().build().err().unwrap();
+        assert!(matches!(error.kind(), ParsingErrorType::InvalidCharactersBlock(_)), "{}", error);
+        assert!(error.to_string().contains(msg), "{}", error);
+    }
+}
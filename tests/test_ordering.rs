@@ -0,0 +1,57 @@
+use nexus_parser::io::writer::newick::NewickStyle;
+use nexus_parser::parse_newick_str;
+
+#[test]
+fn test_ladderize() {
+    let (mut trees, labels) = parse_newick_str("((E:1,(D:1,(C:1,B:1):2):3):1,A:4,(F:2,G:2):1);").unwrap();
+    let tree = &mut trees[0];
+
+    tree.ladderize(true);
+    assert!(tree.is_valid());
+    assert_eq!(tree.to_newick(&NewickStyle::Label, Some(&labels)), "(A:4,(F:2,G:2):1,(E:1,(D:1,(C:1,B:1):2):3):1);");
+
+    tree.ladderize(false);
+    assert!(tree.is_valid());
+    assert_eq!(tree.to_newick(&NewickStyle::Label, Some(&labels)), "((((C:1,B:1):2,D:1):3,E:1):1,(F:2,G:2):1,A:4);");
+}
+
+#[test]
+fn test_canonicalize() {
+    let newicks = "((C,(A,B)),(D,E));\n((E,D),((B,A),C));\n(((A,B),C),(D,E));\n";
+    let (mut trees, labels) = parse_newick_str(newicks).unwrap();
+    let written: Vec<String> = trees.iter_mut()
+        .map(|tree| {
+            tree.canonicalize();
+            tree.to_newick(&NewickStyle::Label, Some(&labels))
+        })
+        .collect();
+    // Label indices by first occurrence: C, A, B, D, E
+    assert_eq!(written, vec!["((C,(A,B)),(D,E));"; 3]);
+}
+
+#[test]
+fn test_ordering_keeps_annotations_and_labels() {
+    let (mut trees, labels) = parse_newick_str("(C:3,(B:1,A:2)x:0.5)root;").unwrap();
+    let tree = &mut trees[0];
+    tree.canonicalize();
+    assert_eq!(tree.to_newick(&NewickStyle::Label, Some(&labels)), "(C:3,(B:1,A:2)x:0.5)root;");
+    tree.ladderize(false);
+    assert_eq!(tree.to_newick(&NewickStyle::Label, Some(&labels)), "((B:1,A:2)x:0.5,C:3)root;");
+    assert_eq!(tree.root_height(), None);
+}
+
+#[test]
+fn test_order_leaves() {
+    let (mut trees, labels) = parse_newick_str("((A,B),(C,(D,E)));\n(((D,E),C),(B,A));\n").unwrap();
+    let order = trees[1].leaf_order();
+
+    // Realizable order of another tree with the same topology is followed exactly
+    trees[0].order_leaves(&order);
+    assert!(trees[0].is_valid());
+    assert_eq!(trees[0].leaf_order(), order);
+    assert_eq!(trees[0].to_newick(&NewickStyle::Label, Some(&labels)), "(((D,E),C),(B,A));");
+
+    // Leaves not in the order come last
+    trees[0].order_leaves(&[labels.get_index("C").unwrap()]);
+    assert_eq!(trees[0].to_newick(&NewickStyle::Label, Some(&labels)), "((C,(D,E)),(A,B));");
+}
@@ -0,0 +1,61 @@
+use nexus_parser::algorithms::fitch_score;
+use nexus_parser::io::parser::fasta;
+use nexus_parser::model::alignment::DataType;
+use nexus_parser::parse_newick_str;
+
+/// Parses the trees and a FASTA alignment over their labels, and returns the score of each tree.
+fn scores(newicks: &str, fasta: &str, data_type: DataType) -> Vec<usize> {
+    let (trees, mut labels) = parse_newick_str(newicks).unwrap();
+    let alignment = fasta::parse_str(fasta, data_type, &mut labels).unwrap();
+    trees.iter().map(|tree| fitch_score(tree, &alignment)).collect()
+}
+
+#[test]
+fn test_fitch_score_dna() {
+    let fasta = ">A\nAACG\n>B\nAACT\n>C\nGTCA\n>D\nGTTA\n";
+    // Sites: AAGG, AATT, CCCT, GTAA
+    assert_eq!(scores("((A,B),(C,D));\n((A,C),(B,D));\n(A,B,(C,D));\n", fasta, DataType::Dna), vec![5, 7, 5]);
+
+    // Ambiguity codes, gaps and missing data fit any of their states
+    let fasta = ">A\nA\n>B\nR\n>C\nG\n>D\n-\n";
+    assert_eq!(scores("((A,B),(C,D));\n((A,C),(B,D));\n", fasta, DataType::Dna), vec![1, 1]);
+    let fasta = ">A\nA\n>B\nN\n>C\nG\n>D\n?\n";
+    assert_eq!(scores("((A,B),(C,D));\n", fasta, DataType::Dna), vec![1]);
+
+    // RNA
+    assert_eq!(scores("((A,B),C);\n", ">A\nU\n>B\nU\n>C\nA\n", DataType::Rna), vec![1]);
+}
+
+#[test]
+fn test_fitch_score_many_sites() {
+    // 150 copies of the same pattern span three words of sites
+    let (a, b, c, d) = ("ACGT".repeat(150), "ACGA".repeat(150), "TCCA".repeat(150), "TCCT".repeat(150));
+    let fasta = format!(">A\n{}\n>B\n{}\n>C\n{}\n>D\n{}\n", a, b, c, d);
+    let single = scores("((A,B),(C,D));\n", ">A\nACGT\n>B\nACGA\n>C\nTCCA\n>D\nTCCT\n", DataType::Dna)[0];
+    assert_eq!(single, 4);
+    assert_eq!(scores("((A,B),(C,D));\n", &fasta, DataType::Dna), vec![150 * single]);
+}
+
+#[test]
+fn test_fitch_score_polytomies_and_missing_taxa() {
+    // Polytomy of four states needs three changes, three of one state none
+    let fasta = ">A\nAA\n>B\nCA\n>C\nGA\n>D\nTA\n";
+    assert_eq!(scores("(A,B,C,D);\n", fasta, DataType::Dna), vec![3]);
+    // Majority state at the polytomy, then resolved with the other subtree
+    let fasta = ">A\nA\n>B\nA\n>C\nC\n>D\nC\n>E\nC\n";
+    assert_eq!(scores("((A,B,C),(D,E));\n(A,B,C,D,E);\n((A,B),(C,D),E);\n", fasta, DataType::Dna), vec![2, 2, 1]);
+
+    // Leaves without sequence are treated as missing data
+    let fasta = ">A\nA\n>B\nA\n>C\nG\n";
+    assert_eq!(scores("((A,D),(B,C));\n((A,B),(C,D));\n", fasta, DataType::Dna), vec![1, 1]);
+}
+
+#[test]
+fn test_fitch_score_protein_and_standard() {
+    let fasta = ">A\nMKV\n>B\nMKV\n>C\nMRL\n>D\nMBX\n";
+    // Site 2: K,K,R,B (B = N or D) needs two changes, site 3: V,V,L,X one
+    assert_eq!(scores("((A,B),(C,D));\n", fasta, DataType::Protein), vec![3]);
+
+    let fasta = ">A\n0120\n>B\n0121\n>C\n1?2-\n>D\n1020\n";
+    assert_eq!(scores("((A,B),(C,D));\n((A,C),(B,D));\n", fasta, DataType::Standard), vec![3, 4]);
+}
@@ -0,0 +1,64 @@
+mod common;
+#[cfg(feature = "fs")]
+use common::approx_eq;
+
+use nexus_parser::algorithms::patristic::DistanceMatrix;
+#[cfg(feature = "fs")]
+use nexus_parser::parse_nexus_file;
+#[cfg(feature = "fs")]
+use std::path::Path;
+
+#[test]
+fn test_patristic_distances() {
+    let (trees, labels) = nexus_parser::parse_newick_str("(((A:1,B:1):2,C:3):1,(D:2,E:2):2);").unwrap();
+    let distances = trees[0].patristic_distances(&labels);
+
+    assert_eq!(distances.len(), 5);
+    assert_eq!(distances.labels(), &["A", "B", "C", "D", "E"]);
+    assert_eq!(distances.get(0, 1), 2.0);
+    assert_eq!(distances.get(1, 0), 2.0);
+    assert_eq!(distances.get(2, 2), 0.0);
+    assert_eq!(distances.get(0, 2), 6.0);
+    assert_eq!(distances.get(0, 3), 8.0);
+    assert_eq!(distances.get_by_label("D", "E"), Some(4.0));
+    assert_eq!(distances.get_by_label("D", "X"), None);
+
+    let rows = distances.to_rows();
+    for (i, row) in rows.iter().enumerate() {
+        for (j, &distance) in row.iter().enumerate() {
+            assert_eq!(distance, rows[j][i]);
+        }
+    }
+}
+
+#[cfg(feature = "fs")]
+#[test]
+fn test_patristic_distances_ultrametric() {
+    // In an ultrametric tree, leaves are twice their MRCA's height apart
+    let path = Path::new("tests").join("fixtures").join("nexus_t1_n10.trees");
+    let (trees, labels) = parse_nexus_file(path.to_str().unwrap()).unwrap();
+    let tree = &trees[0];
+    let distances = tree.patristic_distances(&labels);
+
+    for i in 0..10 {
+        for j in i + 1..10 {
+            let mrca = tree.mrca(&[i, j]);
+            assert!(approx_eq(distances.get(i, j), 2.0 * tree.node_height(mrca).unwrap(), 1e-12));
+        }
+    }
+}
+
+#[test]
+fn test_write_phylip() {
+    let matrix = DistanceMatrix::new(vec!["A".to_string(), "B".to_string(), "C".to_string()], vec![1.0, 2.0, 3.0]);
+    let mut phylip = Vec::new();
+    matrix.write_phylip(&mut phylip).unwrap();
+
+    assert_eq!(String::from_utf8(phylip).unwrap(), "3\nA          0 1 2\nB          1 0 3\nC          2 3 0\n");
+}
+
+#[test]
+#[should_panic]
+fn test_distance_matrix_wrong_size() {
+    DistanceMatrix::new(vec!["A".to_string(), "B".to_string()], vec![1.0, 2.0]);
+}
@@ -0,0 +1,77 @@
+#![cfg(feature = "rand")]
+
+mod common;
+use common::parse_tree;
+
+use rand::distr::Uniform;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+#[test]
+fn test_perturb_branch_lengths() {
+    let original = parse_tree("((A:1,B:2):0.5,(C:1,D):4);", 4);
+    let mut tree = original.clone();
+    let mut rng = StdRng::seed_from_u64(42);
+
+    tree.perturb_branch_lengths(&Uniform::new(0.9, 1.1).unwrap(), &mut rng);
+
+    assert!(tree.is_valid());
+    for index in 0..tree.num_vertices() {
+        match (original[index].branch_length(), tree[index].branch_length()) {
+            (Some(before), Some(after)) => {
+                assert!(*after >= 0.9 * *before && *after <= 1.1 * *before);
+            }
+            (None, None) => {}
+            _ => panic!("Perturbation must not add or remove branch lengths"),
+        }
+    }
+}
+
+#[test]
+fn test_perturb_branch_lengths_truncates_at_zero() {
+    let mut tree = parse_tree("((A:1,B:2):0.5,C:1);", 3);
+    let mut rng = StdRng::seed_from_u64(7);
+
+    tree.perturb_branch_lengths(&Uniform::new(-2.0, -1.0).unwrap(), &mut rng);
+
+    assert_eq!(tree.total_branch_length(), 0.0);
+}
+
+#[test]
+fn test_random_nni_moves_keep_tree_valid() {
+    let original = parse_tree("(((A:1,B:1):1,(C:1,D:1):1):1,((E:1,F:1):1,G:2):1);", 7);
+    let mut tree = original.clone();
+    let mut rng = StdRng::seed_from_u64(1);
+
+    assert_eq!(tree.random_nni_moves(20, &mut rng), 20);
+    assert!(tree.is_valid());
+    assert_eq!(tree.num_leaves(), original.num_leaves());
+    assert!((tree.total_branch_length() - original.total_branch_length()).abs() < 1e-9);
+}
+
+#[test]
+fn test_random_nni_moves_change_topology() {
+    let original = parse_tree("(((A,B),(C,D)),((E,F),G));", 7);
+    let mut tree = original.clone();
+    let mut rng = StdRng::seed_from_u64(3);
+
+    tree.random_nni_moves(1, &mut rng);
+
+    assert_ne!(tree.clades(7), original.clades(7));
+}
+
+#[test]
+fn test_random_nni_moves_without_internal_edge() {
+    let mut tree = parse_tree("(A,B,C);", 3);
+    let mut rng = StdRng::seed_from_u64(0);
+    assert_eq!(tree.random_nni_moves(5, &mut rng), 0);
+}
+
+#[test]
+#[should_panic]
+fn test_nni_move_invalid_child() {
+    let mut tree = parse_tree("((A,B),C);", 3);
+    let internal = (0..tree.num_vertices()).find(|&i| tree[i].is_internal()).unwrap();
+    let root = tree.root().index();
+    tree.nni_move(internal, root, root);
+}
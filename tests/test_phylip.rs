@@ -0,0 +1,68 @@
+use nexus_parser::io::parser::parsing_error::ParsingErrorType;
+use nexus_parser::io::parser::phylip::{self, PhylipOptions};
+use nexus_parser::model::alignment::DataType;
+use nexus_parser::model::leaf_label_map::LeafLabelMap;
+#[cfg(feature = "fs")]
+use nexus_parser::{parse_nexus_file, parse_phylip_file};
+#[cfg(feature = "fs")]
+use std::path::Path;
+
+fn phylip_error(phylip: &str, options: PhylipOptions) -> String {
+    let error = phylip::parse_str(phylip, DataType::Dna, &mut LeafLabelMap::new(0), options)
+        .expect_err("Invalid PHYLIP is rejected");
+    assert!(matches!(error.kind(), ParsingErrorType::InvalidPhylip(_)), "{}", error);
+    error.to_string()
+}
+
+#[cfg(feature = "fs")]
+#[test]
+fn test_interleaved_phylip_file_joined_with_trees() {
+    let trees_path = Path::new("tests").join("fixtures").join("nexus_t2_n4_mrbayes.trees");
+    let (_, mut labels) = parse_nexus_file(trees_path.to_str().unwrap()).unwrap();
+
+    let path = Path::new("tests").join("fixtures").join("phylip_n4_interleaved.phy");
+    let alignment = parse_phylip_file(path.to_str().unwrap(), DataType::Dna, &mut labels, PhylipOptions::default()).unwrap();
+
+    assert_eq!(labels.num_labels(), 4);
+    assert_eq!(alignment.num_sequences(), 4);
+    let sequence = |label: &str| alignment.sequence(labels.get_index(label).unwrap()).unwrap();
+    assert_eq!(sequence("Homo_sapiens"), b"ACGTACGTTTGA");
+    assert_eq!(sequence("Gorilla_gorilla"), b"ACGAACRTTT-A");
+    assert_eq!(sequence("Pongo_abelii"), b"ACGTAC?TTTGA");
+}
+
+#[test]
+fn test_phylip_variants() {
+    let sequential = PhylipOptions { sequential: true, ..Default::default() };
+    let strict = PhylipOptions { strict_names: true, ..Default::default() };
+
+    // Sequential sequences spanning several lines
+    let mut labels = LeafLabelMap::new(0);
+    let alignment = phylip::parse_str("2 6 \nA ACG\nTAC\nB\nACGTAA\n", DataType::Dna, &mut labels, sequential).unwrap();
+    assert_eq!(alignment.sequence(0), Some(&b"ACGTAC"[..]));
+    assert_eq!(alignment.sequence(1), Some(&b"ACGTAA"[..]));
+
+    // Strict names with spaces or directly followed by the sequence, interleaved
+    let mut labels = LeafLabelMap::new(0);
+    let alignment = phylip::parse_str("2 6\nHomo sap. ACG\nPan_trogloACG\n  TAC\n  TAA\n", DataType::Dna, &mut labels, strict).unwrap();
+    assert_eq!(labels.labels(), &["Homo sap.", "Pan_troglo"]);
+    assert_eq!(alignment.sequence(0), Some(&b"ACGTAC"[..]));
+    assert_eq!(alignment.sequence(1), Some(&b"ACGTAA"[..]));
+
+    // One line per sequence is read alike in both layouts
+    for options in [PhylipOptions::default(), sequential] {
+        let alignment = phylip::parse_str("2 4\nA 0101\nB 0011\n", DataType::Standard, &mut LeafLabelMap::new(0), options).unwrap();
+        assert_eq!(alignment.sequence(1), Some(&b"0011"[..]));
+    }
+}
+
+#[test]
+fn test_invalid_phylip() {
+    let sequential = PhylipOptions { sequential: true, ..Default::default() };
+    assert!(phylip_error("A ACGT\n", PhylipOptions::default()).contains("number of taxa and characters"));
+    assert!(phylip_error("2 4\nA ACGT\n", PhylipOptions::default()).contains("Expected more sequences"));
+    assert!(phylip_error("2 4\nA ACGT\nA ACGT\n", PhylipOptions::default()).contains("Duplicate sequence 'A'"));
+    assert!(phylip_error("2 4\nA ACGTA\nB ACGT\n", PhylipOptions::default()).contains("'A' has 5 characters, expected 4"));
+    assert!(phylip_error("2 4\nA AC\nB AC\nGT\n", PhylipOptions::default()).contains("'B' has 2 characters"));
+    assert!(phylip_error("2 4\nA ACGT\nB AC", sequential).contains("'B' has 2 characters"));
+}
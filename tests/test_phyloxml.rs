@@ -0,0 +1,107 @@
+mod common;
+#[cfg(feature = "fs")]
+use common::approx_eq;
+
+use nexus_parser::io::parser::parsing_error::ParsingErrorType;
+use nexus_parser::io::parser::phyloxml;
+#[cfg(feature = "fs")]
+use nexus_parser::io::writer::newick::NewickStyle;
+#[cfg(feature = "fs")]
+use nexus_parser::model::taxon_set::TaxonSet;
+#[cfg(feature = "fs")]
+use nexus_parser::{parse_phyloxml_file, parse_trees_file};
+#[cfg(feature = "fs")]
+use std::path::Path;
+
+fn phyloxml_error(xml: &str) -> ParsingErrorType {
+    phyloxml::parse_str(xml).expect_err("Invalid phyloXML is rejected").kind().clone()
+}
+
+#[cfg(feature = "fs")]
+#[test]
+fn test_phyloxml_file() {
+    let path = Path::new("tests").join("fixtures").join("phyloxml_t2_n5.xml");
+    let (trees, labels) = parse_phyloxml_file(path.to_str().unwrap()).unwrap();
+
+    assert_eq!(trees.len(), 2);
+    assert_eq!(labels.num_labels(), 5);
+    assert_eq!(labels.get_label(0), Some("Homo sapiens"));
+    for tree in &trees {
+        assert!(tree.is_valid());
+        assert_eq!(tree.num_leaves(), 5);
+        assert_eq!(tree.num_leaves_init(), 5);
+    }
+
+    let first = &trees[0];
+    assert_eq!(first.name().unwrap(), "primates");
+    assert!(first.is_rooted());
+    assert_eq!(first.to_newick(&NewickStyle::Label, Some(&labels)),
+               "(('Homo sapiens':0.1,'Pan troglodytes':0.1)91:0.2,('Gorilla gorilla':0.3,'Pongo abelii':0.3,'Hylobates lar':0.3)'Hominidae & others':0.4);");
+    assert!(approx_eq(first.total_branch_length(), 1.7, 1e-9));
+
+    let second = &trees[1];
+    assert_eq!(second.name(), None);
+    assert!(!second.is_rooted());
+    assert!(!second.vertices_have_branch_lengths());
+
+    // Both trees share the clade of humans and chimpanzees
+    let hominini = TaxonSet::from_indices(5, [0, 1]);
+    assert!(first.is_monophyletic(&hominini));
+    assert!(second.is_monophyletic(&hominini));
+
+    // Format is detected
+    let (detected, _) = parse_trees_file(path.to_str().unwrap()).unwrap();
+    assert_eq!(detected.len(), 2);
+}
+
+#[test]
+fn test_phyloxml_confidence_and_names() {
+    let xml = "<phy:phyloxml xmlns:phy=\"http://www.phyloxml.org\"><phy:phylogeny rooted=\"true\"><phy:clade>
+        <phy:name>root</phy:name>
+        <phy:clade><phy:name>AB</phy:name><phy:confidence type=\"probability\">0.98</phy:confidence><phy:confidence type=\"bootstrap\">75</phy:confidence>
+            <phy:clade><phy:name>A</phy:name></phy:clade><phy:clade><phy:name><![CDATA[B<1>]]></phy:name></phy:clade>
+        </phy:clade>
+        <phy:clade><phy:name>C&#x41;</phy:name><phy:confidence type=\"bootstrap\">10</phy:confidence></phy:clade>
+    </phy:clade></phy:phylogeny></phy:phyloxml>";
+
+    let (trees, labels) = phyloxml::parse_str(xml).unwrap();
+    let tree = &trees[0];
+    assert_eq!(labels.labels(), &vec!["A".to_string(), "B<1>".to_string(), "CA".to_string()]);
+    assert_eq!(tree.root().node_label(), Some("root"));
+    let ab = tree.mrca(&[0, 1]);
+    assert_eq!(tree[ab].support(), Some(0.98));
+}
+
+#[test]
+fn test_phyloxml_deep_tree() {
+    let num_leaves = 50_000;
+    let mut xml = String::from("<phyloxml><phylogeny rooted=\"true\">");
+    for k in 0..num_leaves - 1 {
+        xml.push_str(&format!("<clade><clade branch_length=\"1\"><name>t{}</name></clade>", k));
+    }
+    xml.push_str(&format!("<clade branch_length=\"1\"><name>t{}</name></clade>", num_leaves - 1));
+    xml.push_str(&"</clade>".repeat(num_leaves - 1));
+    xml.push_str("</phylogeny></phyloxml>");
+
+    let (trees, _) = phyloxml::parse_str(&xml).unwrap();
+    assert!(trees[0].is_valid());
+    assert_eq!(trees[0].num_leaves(), num_leaves);
+}
+
+#[test]
+fn test_phyloxml_errors() {
+    assert!(matches!(phyloxml_error("<nexml></nexml>"), ParsingErrorType::InvalidPhyloXml(_)));
+    assert!(matches!(phyloxml_error("<phyloxml><phylogeny><clade><clade><name>A</name></clade><clade/></clade></phylogeny></phyloxml>"),
+                     ParsingErrorType::InvalidPhyloXml(_)));
+    assert!(matches!(phyloxml_error("<phyloxml><phylogeny><clade><clade branch_length=\"-1\"><name>A</name></clade></clade></phylogeny></phyloxml>"),
+                     ParsingErrorType::InvalidPhyloXml(_)));
+    assert_eq!(phyloxml_error("<phyloxml><phylogeny><clade><clade><name>A</name><branch_length>inf</branch_length></clade></clade></phylogeny></phyloxml>"),
+               ParsingErrorType::InvalidPhyloXml("invalid branch length 'inf': Branch length must be finite, got inf".to_string()));
+    assert!(matches!(phyloxml_error("<phyloxml><phylogeny><clade><clade><name>A</name></clade><clade><name>A</name></clade></clade></phylogeny></phyloxml>"),
+                     ParsingErrorType::DuplicateTaxon(_)));
+    assert!(matches!(phyloxml_error("<phyloxml><phylogeny><clade><clade><name>A</name></clade></phylogeny></phyloxml>"),
+                     ParsingErrorType::InvalidXml(_)));
+    assert!(matches!(phyloxml_error("<phyloxml><phylogeny><clade><clade><name>A</name></clade>"),
+                     ParsingErrorType::InvalidXml(_)));
+    assert!(matches!(phyloxml_error("<phyloxml><!-- unclosed </phyloxml>"), ParsingErrorType::UnclosedComment));
+}
@@ -0,0 +1,94 @@
+mod common;
+use common::{newick, parse_tree};
+
+use nexus_parser::model::taxon_set::TaxonSet;
+use nexus_parser::model::tree::Tree;
+use std::collections::HashSet;
+
+#[test]
+fn test_nni_neighborhood() {
+    let tree = parse_tree("((((A,B),C),D),(E,F));", 6);
+    let neighbors = tree.nni_neighborhood();
+
+    assert_eq!(neighbors.len(), 2 * (6 - 2));
+    let hashes: HashSet<u64> = neighbors.iter().map(Tree::topology_hash).collect();
+    assert_eq!(hashes.len(), neighbors.len());
+    for neighbor in &neighbors {
+        assert!(neighbor.is_valid());
+        assert!(!neighbor.topology_eq(&tree));
+    }
+}
+
+#[test]
+#[should_panic]
+fn test_nni_on_leaf() {
+    let tree = parse_tree("((A,B),C);", 3);
+    tree.nni(0);
+}
+
+#[test]
+fn test_spr_suppresses_attachment_vertex() {
+    let mut tree = parse_tree("(((A:1,B:1):1,C:2):1,D:3);", 4);
+    let total = tree.total_branch_length();
+    let num_vertices = tree.num_vertices();
+
+    // Regrafting onto the branch above its former parent gives the same topology
+    let original = tree.clone();
+    tree.spr(tree.mrca(&[0]), tree.mrca(&[0, 1]));
+    assert!(tree.topology_eq(&original));
+
+    tree.spr(tree.mrca(&[1]), tree.mrca(&[3]));
+    assert!(tree.is_valid());
+    assert_eq!(tree.num_vertices(), num_vertices);
+    assert!((tree.total_branch_length() - total).abs() < 1e-12);
+    assert!(tree.is_monophyletic(&TaxonSet::from_indices(4, [1, 3])));
+    assert!(tree.is_monophyletic(&TaxonSet::from_indices(4, [0, 2])));
+}
+
+#[test]
+fn test_spr_at_root() {
+    // Pruning a child of a binary root makes the other child the root
+    let mut tree = parse_tree("((A,B)ab,(C,D)cd);", 4);
+    tree.spr(tree.mrca(&[0, 1]), tree.mrca(&[2]));
+    assert!(tree.is_valid());
+    assert_eq!(newick(&tree), "((2,(0,1)ab),3)cd;");
+
+    // Regrafting above the root adds a new root
+    tree.spr(tree.mrca(&[0, 1]), tree.root().index());
+    assert!(tree.is_valid());
+    assert_eq!(newick(&tree), "((2,3)cd,(0,1)ab);");
+}
+
+#[test]
+fn test_spr_in_polytomy() {
+    let mut tree = parse_tree("(A,B,C,(D,E));", 5);
+    let num_vertices = tree.num_vertices();
+
+    tree.spr(tree.mrca(&[0]), tree.mrca(&[3]));
+    assert!(tree.is_valid());
+    assert_eq!(tree.num_vertices(), num_vertices + 1);
+    assert_eq!(newick(&tree), "(1,2,((3,0),4));");
+}
+
+#[test]
+#[should_panic]
+fn test_spr_into_pruned_subtree() {
+    let mut tree = parse_tree("(((A,B),C),D);", 4);
+    tree.spr(tree.mrca(&[0, 1, 2]), tree.mrca(&[0]));
+}
+
+#[test]
+fn test_spr_neighborhood() {
+    let tree = parse_tree("((((A,B),C),D),(E,F));", 6);
+    let neighbors = tree.spr_neighborhood();
+
+    let hashes: HashSet<u64> = neighbors.iter().map(Tree::topology_hash).collect();
+    assert_eq!(hashes.len(), neighbors.len());
+    assert!(!hashes.contains(&tree.topology_hash()));
+    assert!(neighbors.iter().all(Tree::is_valid));
+
+    // NNI moves are SPR moves
+    for neighbor in tree.nni_neighborhood() {
+        assert!(hashes.contains(&neighbor.topology_hash()));
+    }
+}
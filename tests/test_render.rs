@@ -0,0 +1,105 @@
+use nexus_parser::io::parser::byte_parser::ByteParser;
+use nexus_parser::io::parser::newick::{AnnotationMode, NewickParser};
+use nexus_parser::io::writer::render::RenderOptions;
+use nexus_parser::model::tree::Tree;
+use nexus_parser::parse_newick_str;
+
+#[test]
+fn test_to_ascii_polytomies() {
+    let (trees, labels) = parse_newick_str("((A,B,C),(D,(E,F)),Gorilla);").unwrap();
+    let expected = concat!(
+        "      /--A\n",
+        "      |\n",
+        "/-----+--B\n",
+        "|     |\n",
+        "|     \\--C\n",
+        "|\n",
+        "|  /-----D\n",
+        "+--+\n",
+        "|  |  /--E\n",
+        "|  \\--+\n",
+        "|     \\--F\n",
+        "|\n",
+        "\\--------Gorilla\n",
+    );
+    assert_eq!(trees[0].to_ascii(&labels), expected);
+}
+
+#[test]
+fn test_to_ascii_unary_and_empty() {
+    let (trees, labels) = parse_newick_str("(((A,B)),C);").unwrap();
+    let expected = concat!(
+        "      /--A\n",
+        "/-----+\n",
+        "+     \\--B\n",
+        "|\n",
+        "\\--------C\n",
+    );
+    assert_eq!(trees[0].to_ascii(&labels), expected);
+    assert_eq!(Tree::new(2).to_ascii(&labels), "");
+}
+
+#[test]
+fn test_to_dot() {
+    let (trees, labels) = parse_newick_str("((A:1,'Say \"hi\"':2.5):0.5,C)x;").unwrap();
+    let expected = concat!(
+        "digraph tree {\n",
+        "  rankdir=LR;\n",
+        "  node [shape=point];\n",
+        "  v4 [label=\"x\"];\n",
+        "  v2;\n",
+        "  v0 [label=\"A\", shape=plaintext];\n",
+        "  v1 [label=\"Say \\\"hi\\\"\", shape=plaintext];\n",
+        "  v3 [label=\"C\", shape=plaintext];\n",
+        "  v4 -> v2 [label=\"0.5\"];\n",
+        "  v4 -> v3;\n",
+        "  v2 -> v0 [label=\"1\"];\n",
+        "  v2 -> v1 [label=\"2.5\"];\n",
+        "}\n",
+    );
+    assert_eq!(trees[0].to_dot(&labels), expected);
+}
+
+#[test]
+fn test_to_svg_phylogram() {
+    let (trees, labels) = parse_newick_str("((A:1,'B & C':1)0.9:2,D:4);").unwrap();
+    let svg = trees[0].to_svg(&labels, &RenderOptions::default());
+
+    assert!(svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\""));
+    assert!(svg.ends_with("</svg>\n"));
+    assert_eq!(svg.matches("<line ").count(), 6 + 1);
+    assert!(svg.contains(">B &amp; C</text>"));
+    assert!(svg.contains(">0.9</text>"));
+    // Leaves to scale: A at 3 of 4 units, D at 4 of 4 units of the 600 pixels (after the margin)
+    assert!(svg.contains("<line x1=\"310.00\" y1=\"20.00\" x2=\"460.00\" y2=\"20.00\"/>"));
+    assert!(svg.contains("<line x1=\"10.00\" y1=\"60.00\" x2=\"610.00\" y2=\"60.00\"/>"));
+    // Scale bar of 0.5 units
+    assert!(svg.contains("<line x1=\"10.00\" y1=\"76.00\" x2=\"85.00\" y2=\"76.00\"/>"));
+    assert!(svg.contains(">0.5</text>"));
+}
+
+#[test]
+fn test_to_svg_options() {
+    let mut newick_parser = NewickParser::new().with_num_leaves(3).with_annotations(AnnotationMode::Parsed);
+    let tree = newick_parser.parse(&mut ByteParser::from_str("((A[&posterior=0.5],B)95[&posterior=0.75],C);")).unwrap();
+    let labels = newick_parser.into_leaf_label_map();
+    let options = RenderOptions {
+        width: 300.0,
+        font_family: "Times".to_string(),
+        italic_labels: true,
+        support_key: Some("posterior".to_string()),
+        ..RenderOptions::default()
+    };
+    let svg = tree.to_svg(&labels, &options);
+
+    // Cladogram without branch lengths: leaves aligned, no scale bar
+    assert_eq!(svg.matches("<line ").count(), 6);
+    assert_eq!(svg.matches("x2=\"310.00\"").count(), 3);
+    assert_eq!(svg.matches("font-style=\"italic\"").count(), 3);
+    assert!(svg.contains("font-family=\"Times\""));
+    assert!(svg.contains(">0.75</text>"));
+    assert!(!svg.contains(">95</text>") && !svg.contains(">0.5</text>"));
+
+    let options = RenderOptions { show_support: false, ..options };
+    assert!(!tree.to_svg(&labels, &options).contains(">0.75</text>"));
+}
@@ -0,0 +1,113 @@
+#[cfg(feature = "fs")]
+use nexus_parser::io::parser::nexus::Burnin;
+use nexus_parser::parse_newick_str;
+use nexus_parser::stats::report::SampleReport;
+#[cfg(feature = "fs")]
+use nexus_parser::write_report_file;
+#[cfg(feature = "fs")]
+use std::path::Path;
+#[cfg(feature = "cli")]
+use std::process::Command;
+
+fn report_html(report: &SampleReport) -> String {
+    let mut html = Vec::new();
+    report.write_html(&mut html).unwrap();
+    String::from_utf8(html).unwrap()
+}
+
+#[test]
+fn test_report_summaries() {
+    let (trees, labels) = parse_newick_str("((A:1,B:1):1,C:2);\n((A:1,C:1):2,B:3);\n((A:2,B:2):1,C:3);").unwrap();
+    let report = SampleReport::new(&trees, &labels);
+
+    assert_eq!(report.num_trees(), 3);
+    assert_eq!(report.num_taxa(), 3);
+
+    // Tree lengths 5, 7, 8 and heights 2, 3, 3
+    let traces = report.traces();
+    assert_eq!(traces.len(), 2);
+    assert!((traces[0].mean() - 20.0 / 3.0).abs() < 1e-12);
+    assert!((traces[1].mean() - 8.0 / 3.0).abs() < 1e-12);
+    assert!(!report.is_converged());
+
+    // AB in two trees, AC in one
+    let clades = report.clades();
+    assert_eq!(clades.len(), 2);
+    assert_eq!(clades[0].0, "A+B");
+    assert_eq!(clades[1].2, 1.0 / 3.0);
+
+    // Three lineages at present; shortly before the oldest root (at 3), only the two trees of height 3 have two left
+    let ltt = report.lineages_through_time();
+    assert_eq!(ltt[0], (0.0, 3.0));
+    assert_eq!(ltt.last().unwrap().1, 4.0 / 3.0);
+    assert!(ltt.windows(2).all(|pair| pair[0].1 >= pair[1].1));
+
+    // MCC tree is the first tree with clade AB, labelled with its frequency
+    let mcc = report.mcc();
+    let ab = mcc.mrca(&[labels.get_index("A").unwrap(), labels.get_index("B").unwrap()]);
+    assert_eq!(mcc[ab].node_label(), Some((2.0 / 3.0f64).to_string().as_str()));
+    assert_eq!(mcc.total_branch_length(), trees[0].total_branch_length());
+
+    let html = report_html(&report);
+    assert!(html.contains("<h2>Maximum clade credibility tree</h2>"));
+    // LTT plot starts with all three lineages at the present (top right)
+    assert!(html.contains("<polyline points=\"440.0,40.0 "));
+}
+
+#[test]
+fn test_report_without_branch_lengths() {
+    let (trees, labels) = parse_newick_str("((A,B),C);\n((A,B),C);").unwrap();
+    let report = SampleReport::new(&trees, &labels);
+
+    assert!(report.traces().is_empty());
+    assert!(report.lineages_through_time().is_empty());
+    assert!(!report.is_converged());
+
+    let html = report_html(&report);
+    assert!(html.contains("not all trees have branch lengths"));
+    assert!(html.contains("<code>((A,B)1,C);</code>"));
+}
+
+#[test]
+fn test_report_html_escapes_labels() {
+    let (trees, labels) = parse_newick_str("(('A<B>':1,'C&D':1):1,E:2);").unwrap();
+    let html = report_html(&SampleReport::new(&trees, &labels));
+
+    assert!(html.starts_with("<!DOCTYPE html>"));
+    assert!(html.contains("A&lt;B&gt;"));
+    assert!(html.contains("C&amp;D"));
+    assert!(!html.contains("A<B>"));
+}
+
+#[cfg(feature = "fs")]
+#[test]
+fn test_write_report_file_with_burnin() {
+    let input = Path::new("tests").join("fixtures").join("nexus_t11_n20_translate.trees");
+    let output = std::env::temp_dir().join(format!("nexus_parser_report_{}.html", std::process::id()));
+
+    let num_trees = write_report_file(input.to_str().unwrap(), output.to_str().unwrap(), Burnin::Percentage(0.1)).unwrap();
+    assert_eq!(num_trees, 10);
+    let html = std::fs::read_to_string(&output).unwrap();
+    assert!(html.contains("<p>10 trees on 20 taxa</p>"));
+    std::fs::remove_file(&output).unwrap();
+
+    assert!(write_report_file(input.to_str().unwrap(), output.to_str().unwrap(), Burnin::Count(11)).is_err());
+}
+
+#[test]
+#[cfg(feature = "cli")]
+fn test_report_cli() {
+    let input = Path::new("tests").join("fixtures").join("nexus_t11_n20_translate.trees");
+    let output = std::env::temp_dir().join(format!("nexus_parser_report_cli_{}.html", std::process::id()));
+
+    let status = Command::new(env!("CARGO_BIN_EXE_phylo"))
+        .args(["report", input.to_str().unwrap(), "-o", output.to_str().unwrap(), "--burnin", "0.1"])
+        .status()
+        .unwrap();
+    assert!(status.success());
+    assert!(std::fs::read_to_string(&output).unwrap().contains("<p>10 trees on 20 taxa</p>"));
+    std::fs::remove_file(&output).unwrap();
+
+    let status = Command::new(env!("CARGO_BIN_EXE_phylo")).arg("unknown").status().unwrap();
+    assert!(!status.success());
+}
@@ -0,0 +1,60 @@
+use nexus_parser::model::rescaling::rescale_trees;
+use nexus_parser::model::vertex::BranchLengthError;
+use nexus_parser::parse_newick_str;
+
+#[test]
+fn test_scale_branch_lengths() {
+    let (mut trees, _) = parse_newick_str("((A:1,B:1):2,(C:2.5,D):0.5);").unwrap();
+    let tree = &mut trees[0];
+    tree.scale_branch_lengths(2.0);
+    assert_eq!(tree.total_branch_length(), 14.0);
+    // Vertices without branch length stay without
+    assert!(!tree.vertices_have_branch_lengths());
+
+    tree.scale_branch_lengths(0.0);
+    assert_eq!(tree.total_branch_length(), 0.0);
+}
+
+#[test]
+#[should_panic(expected = "Scaling factor must be non-negative")]
+fn test_scale_branch_lengths_negative() {
+    let (mut trees, _) = parse_newick_str("(A:1,B:1);").unwrap();
+    trees[0].scale_branch_lengths(-1.0);
+}
+
+#[test]
+fn test_set_all_branch_lengths() {
+    let (mut trees, _) = parse_newick_str("((A:1,B):2,C);").unwrap();
+    let tree = &mut trees[0];
+    tree.set_all_branch_lengths(0.5);
+    assert!(tree.vertices_have_branch_lengths());
+    assert_eq!(tree.total_branch_length(), 2.0);
+    assert_eq!(tree.root().branch_length(), None);
+    // Cached heights are recomputed
+    assert_eq!(tree.root_height(), None);
+    assert_eq!(tree.node_height(tree[0].parent_index().unwrap()), None);
+}
+
+#[test]
+fn test_transform_branch_lengths() {
+    let (mut trees, _) = parse_newick_str("((A:1,B:1):3,C:4);").unwrap();
+    let tree = &mut trees[0];
+    assert_eq!(tree.root_height(), Some(4.0));
+
+    tree.transform_branch_lengths(|length| length * length).unwrap();
+    assert_eq!(tree.total_branch_length(), 27.0);
+    assert_eq!(tree.root_height(), None);
+
+    // Invalid results leave the tree untouched
+    assert_eq!(tree.transform_branch_lengths(|length| 4.0 - length), Err(BranchLengthError::Negative(-5.0)));
+    assert!(matches!(tree.transform_branch_lengths(|length| length / 0.0), Err(BranchLengthError::NotFinite(_))));
+    assert_eq!(tree.total_branch_length(), 27.0);
+}
+
+#[test]
+fn test_rescale_trees() {
+    let (mut trees, _) = parse_newick_str("((A:1,B:1):1,C:2);\n((A:2,C:2):2,B:4);\n").unwrap();
+    rescale_trees(&mut trees, 10.0);
+    let heights: Vec<Option<f64>> = trees.iter().map(|tree| tree.root_height()).collect();
+    assert_eq!(heights, vec![Some(20.0), Some(40.0)]);
+}
@@ -0,0 +1,103 @@
+mod common;
+use common::parse_trees;
+
+use nexus_parser::algorithms::robinson_foulds::{branch_score, normalized_robinson_foulds, path_distance, robinson_foulds};
+use nexus_parser::model::taxon_set::TaxonSet;
+#[cfg(feature = "fs")]
+use nexus_parser::parse_nexus_file;
+
+#[test]
+fn test_bipartitions() {
+    let trees = parse_trees(&["(((A,B),C),(D,E));", "((A,B),C,(D,E));", "(A,B,C,D,E);"], 5);
+
+    let expected = [TaxonSet::from_indices(5, [2, 3, 4]), TaxonSet::from_indices(5, [3, 4])];
+    for tree in &trees[..2] {
+        let splits = tree.bipartitions();
+        assert_eq!(splits.len(), 2);
+        assert!(expected.iter().all(|split| splits.contains(split)));
+    }
+    assert!(trees[2].bipartitions().is_empty());
+}
+
+#[test]
+fn test_robinson_foulds_ignores_root_and_order() {
+    // Same unrooted tree, rooted differently and children permuted
+    let trees = parse_trees(&["(((A,B),C),(D,E));", "((D,E),(C,(B,A)));", "((A,B),(C,(D,E)));"], 5);
+    assert_eq!(robinson_foulds(&trees[0], &trees[1]), 0);
+    assert_eq!(robinson_foulds(&trees[0], &trees[2]), 0);
+}
+
+#[test]
+fn test_robinson_foulds_distances() {
+    let trees = parse_trees(&["((((A,B),C),D),(E,F));", "((((A,C),B),D),(E,F));", "((((A,F),E),D),(B,C));", "(A,B,C,D,E,F);"], 6);
+
+    assert_eq!(robinson_foulds(&trees[0], &trees[1]), 2);
+    assert_eq!(robinson_foulds(&trees[1], &trees[0]), 2);
+    // No split shared
+    assert_eq!(robinson_foulds(&trees[0], &trees[2]), 6);
+    // Star tree
+    assert_eq!(robinson_foulds(&trees[0], &trees[3]), 3);
+
+    assert_eq!(normalized_robinson_foulds(&trees[0], &trees[0]), 0.0);
+    assert_eq!(normalized_robinson_foulds(&trees[0], &trees[2]), 1.0);
+    assert!((normalized_robinson_foulds(&trees[0], &trees[1]) - 2.0 / 6.0).abs() < 1e-12);
+}
+
+#[cfg(feature = "fs")]
+#[test]
+fn test_robinson_foulds_posterior_sample() {
+    let (trees, _) = parse_nexus_file("tests/fixtures/nexus_t11_n20_translate.trees").unwrap();
+    for tree in &trees {
+        assert_eq!(tree.bipartitions().len(), 20 - 3);
+        assert_eq!(robinson_foulds(tree, tree), 0);
+        assert!(robinson_foulds(&trees[0], tree) <= 2 * (20 - 3));
+    }
+}
+
+#[test]
+#[should_panic]
+fn test_robinson_foulds_different_sizes() {
+    let small = parse_trees(&["((A,B),(C,D));"], 4);
+    let large = parse_trees(&["((A,B),(C,(D,E)));"], 5);
+    robinson_foulds(&small[0], &large[0]);
+}
+
+#[test]
+fn test_branch_score() {
+    let trees = parse_trees(&["((A:1,B:1):1,(C:1,D:1):1);", "(A:1,B:1,(C:1,D:1):2);",
+                              "((A:1,B:2):1,(C:1,D:1):1);", "((A:1,C:1):1,(B:1,D:1):1);"], 4);
+
+    // Same unrooted tree with the same branch lengths
+    assert_eq!(branch_score(&trees[0], &trees[1]), 0.0);
+    // Only a trivial split differs in length
+    assert_eq!(branch_score(&trees[0], &trees[2]), 1.0);
+    // AB|CD and AC|BD of length 2 each
+    assert_eq!(branch_score(&trees[0], &trees[3]), 8.0_f64.sqrt());
+    assert_eq!(branch_score(&trees[3], &trees[0]), branch_score(&trees[0], &trees[3]));
+
+    let lengths = trees[1].split_lengths();
+    assert_eq!(lengths.len(), 5);
+    assert_eq!(lengths[&TaxonSet::from_indices(4, [2, 3])], 2.0);
+}
+
+#[test]
+fn test_path_distance() {
+    let trees = parse_trees(&["(((A,B),C),(D,E));", "((D,E),(C,(B,A)));", "((A,B),(C,(D,E)));",
+                              "(((A,C),B),(D,E));", "(((A,B),D),(C,E));"], 5);
+
+    // Same unrooted tree, rooted differently
+    assert_eq!(path_distance(&trees[0], &trees[1]), 0.0);
+    assert_eq!(path_distance(&trees[0], &trees[2]), 0.0);
+
+    // Swapping taxa of neighboring cherries changes six path lengths by one
+    assert_eq!(path_distance(&trees[0], &trees[3]), 6.0_f64.sqrt());
+    assert_eq!(path_distance(&trees[4], &trees[0]), 6.0_f64.sqrt());
+    assert!(path_distance(&trees[3], &trees[4]) > path_distance(&trees[0], &trees[4]));
+}
+
+#[test]
+#[should_panic]
+fn test_path_distance_different_taxa() {
+    let trees = parse_trees(&["((A,B),(C,D));", "((A,B),(C,A));"], 4);
+    path_distance(&trees[0], &trees[1]);
+}
@@ -0,0 +1,254 @@
+mod common;
+use common::{leaf, newick, parse_tree};
+
+use nexus_parser::io::parser::byte_parser::ByteParser;
+use nexus_parser::io::parser::newick::{AnnotationMode, NewickParser};
+use nexus_parser::io::writer::newick::NewickStyle;
+use nexus_parser::model::rooting::OutgroupError;
+
+#[test]
+fn test_unroot_suppresses_binary_root() {
+    let mut tree = parse_tree("((A:1,B:1):1,(C:1,D:1):2);", 4);
+    let splits = tree.bipartitions();
+    assert!(tree.is_rooted());
+
+    tree.unroot();
+    assert!(!tree.is_rooted());
+    assert!(tree.is_valid());
+    assert_eq!(tree.num_vertices(), 6);
+    assert_eq!(tree.total_branch_length(), 7.0);
+    assert_eq!(tree.bipartitions(), splits);
+    assert_eq!(newick(&tree), "(0:1,1:1,(2:1,3:1):3);");
+
+    // Already unrooted, nothing changes
+    tree.unroot();
+    assert_eq!(newick(&tree), "(0:1,1:1,(2:1,3:1):3);");
+}
+
+#[test]
+fn test_unroot_with_leaf_child_of_root() {
+    let mut tree = parse_tree("(A:1,(B:1,(C:1,D:1):1):1);", 4);
+
+    tree.unroot();
+    assert!(tree.is_valid());
+    assert_eq!(newick(&tree), "(1:1,(2:1,3:1):1,0:2);");
+}
+
+#[test]
+fn test_unroot_two_leaves_only_marks_tree() {
+    let mut tree = parse_tree("(A:1,B:1);", 2);
+
+    tree.unroot();
+    assert!(!tree.is_rooted());
+    assert_eq!(newick(&tree), "(0:1,1:1);");
+}
+
+#[test]
+fn test_root_at_binary_root() {
+    let mut tree = parse_tree("((A:1,B:1):1,(C:1,D:1):2);", 4);
+    let num_vertices = tree.num_vertices();
+    let splits = tree.bipartitions();
+
+    // Root on branch of A
+    tree.root_at(leaf(&tree, 0));
+    assert!(tree.is_valid());
+    assert!(tree.is_rooted());
+    assert_eq!(tree.num_vertices(), num_vertices);
+    assert_eq!(tree.bipartitions(), splits);
+    assert_eq!(newick(&tree), "(0:0.5,(1:1,(2:1,3:1):3):0.5);");
+
+    // Root on the merged branch of the old root again
+    let cd = (0..tree.num_vertices())
+        .find(|&index| tree.clade_taxa(index).len() == 2 && tree.clade_taxa(index).contains(2))
+        .unwrap();
+    tree.root_at(cd);
+    assert!(tree.is_valid());
+    assert_eq!(newick(&tree), "((2:1,3:1):1.5,(1:1,0:1):1.5);");
+}
+
+#[test]
+fn test_root_at_unrooted_tree() {
+    let mut tree = parse_tree("(A:2,B:1,(C:1,D:1):1);", 4);
+    tree.unroot();
+
+    tree.root_at(leaf(&tree, 0));
+    assert!(tree.is_rooted());
+    assert!(tree.is_valid());
+    assert_eq!(tree.num_vertices(), 7);
+    assert_eq!(newick(&tree), "(0:1,(1:1,(2:1,3:1):1):1);");
+
+    // Unrooting again gives the original unrooted tree
+    tree.unroot();
+    assert_eq!(tree.num_vertices(), 6);
+    assert_eq!(tree.bipartitions(), parse_tree("(A,B,(C,D));", 4).bipartitions());
+}
+
+#[test]
+fn test_root_at_keeps_node_labels_with_vertices() {
+    let mut tree = parse_tree("((A,B)x,(C,D)y);", 4);
+    tree.root_at(leaf(&tree, 2));
+
+    assert!(tree.is_valid());
+    assert_eq!(newick(&tree), "(2,(3,(0,1)x)y);");
+}
+
+#[test]
+#[should_panic(expected = "Cannot place root above the root")]
+fn test_root_at_root_panics() {
+    let mut tree = parse_tree("((A,B),C);", 3);
+    let root = tree.root().index();
+    tree.root_at(root);
+}
+
+#[test]
+fn test_reroot_at_edge_splits_branch() {
+    let mut tree = parse_tree("((A:1,B:1):1,(C:1,D:1):3);", 4);
+    let splits = tree.bipartitions();
+    let length = tree.total_branch_length();
+
+    tree.reroot_at_edge(leaf(&tree, 2), 0.25);
+    assert!(tree.is_valid());
+    assert_eq!(tree.bipartitions(), splits);
+    assert_eq!(tree.total_branch_length(), length);
+    assert_eq!(newick(&tree), "(2:0.25,(3:1,(0:1,1:1):4):0.75);");
+
+    // Ends of the branch
+    tree.reroot_at_edge(leaf(&tree, 0), 0.0);
+    assert!(tree.is_valid());
+    assert_eq!(newick(&tree), "(0:0,(1:1,(3:1,2:1):4):1);");
+    tree.reroot_at_edge(leaf(&tree, 0), 1.0);
+    assert_eq!(newick(&tree), "(0:1,(1:1,(3:1,2:1):4):0);");
+}
+
+#[test]
+fn test_reroot_at_edge_on_child_of_binary_root() {
+    // Either child of the root gives the same merged branch, measured from the given child
+    let mut left = parse_tree("((A:1,B:1):1,(C:1,D:1):3);", 4);
+    let mut right = left.clone();
+    let ab = left.mrca(&[0, 1]);
+    let cd = right.mrca(&[2, 3]);
+
+    left.reroot_at_edge(ab, 0.75);
+    right.reroot_at_edge(cd, 0.25);
+    assert_eq!(newick(&left), "((0:1,1:1):3,(2:1,3:1):1);");
+    assert_eq!(newick(&right), "((2:1,3:1):1,(0:1,1:1):3);");
+}
+
+#[test]
+#[should_panic(expected = "not within [0, 1]")]
+fn test_reroot_at_edge_with_invalid_fraction_panics() {
+    let mut tree = parse_tree("((A,B),C);", 3);
+    tree.reroot_at_edge(leaf(&tree, 2), 1.5);
+}
+
+#[test]
+fn test_reroot_at_vertex() {
+    let mut tree = parse_tree("((A:1,B:1)x:1,((C:1,D:1)y:1,E:2):2);", 5);
+    let splits = tree.bipartitions();
+
+    // Binary root is suppressed
+    let y = tree.mrca(&[2, 3]);
+    tree.reroot_at_vertex(y);
+    assert!(tree.is_valid());
+    assert_eq!(tree.num_vertices(), 8);
+    assert_eq!(tree.bipartitions(), splits);
+    assert_eq!(newick(&tree), "(2:1,3:1,(4:2,(0:1,1:1)x:3):1)y;");
+
+    // Old root with three children becomes an internal vertex
+    let x = tree.mrca(&[0, 1]);
+    tree.reroot_at_vertex(x);
+    assert!(tree.is_valid());
+    assert_eq!(tree.num_vertices(), 8);
+    assert_eq!(newick(&tree), "(0:1,1:1,(4:2,(2:1,3:1)y:1):3)x;");
+
+    // Root stays the root
+    tree.unroot();
+    let root = tree.root().index();
+    tree.reroot_at_vertex(root);
+    assert!(tree.is_rooted());
+    assert_eq!(newick(&tree), "(0:1,1:1,(4:2,(2:1,3:1)y:1):3)x;");
+}
+
+#[test]
+#[should_panic(expected = "Cannot make leaf")]
+fn test_reroot_at_leaf_panics() {
+    let mut tree = parse_tree("((A,B),C);", 3);
+    tree.reroot_at_vertex(leaf(&tree, 0));
+}
+
+#[test]
+fn test_root_with_outgroup() {
+    // Outgroup {3, 4} contains the current root side of leaf 0
+    let mut tree = parse_tree("((0:1,1:1):1,(2:1,(3:1,4:1):2):1);", 5);
+    tree.set_rooted(false);
+    tree.root_with_outgroup(&[3, 4]).unwrap();
+    assert!(tree.is_valid());
+    assert!(tree.is_rooted());
+    assert_eq!(newick(&tree), "((3:1,4:1):1,(2:1,(0:1,1:1):2):1);");
+    assert!((tree.total_branch_length() - 9.0).abs() < 1e-12);
+
+    // Already separated by the binary root: only marked rooted
+    let mut tree = parse_tree("((0:1,1:1):1,(2:1,3:1):3);", 4);
+    tree.set_rooted(false);
+    tree.root_with_outgroup(&[3, 2]).unwrap();
+    assert!(tree.is_rooted());
+    assert_eq!(newick(&tree), "((0:1,1:1):1,(2:1,3:1):3);");
+
+    // Single taxon
+    let mut tree = parse_tree("(0:2,1:1,(2:1,3:1):1);", 4);
+    tree.root_with_outgroup(&[0]).unwrap();
+    assert_eq!(newick(&tree), "(0:1,(1:1,(2:1,3:1):1):1);");
+}
+
+#[test]
+fn test_root_with_outgroup_errors() {
+    let original = parse_tree("(0,1,(2,(3,4)));", 5);
+    let mut tree = original.clone();
+    assert_eq!(tree.root_with_outgroup(&[]), Err(OutgroupError::EmptyOutgroup));
+    assert_eq!(tree.root_with_outgroup(&[0, 7]), Err(OutgroupError::MissingTaxon(7)));
+    assert_eq!(tree.root_with_outgroup(&[0, 1, 2, 3, 4]), Err(OutgroupError::NoIngroup));
+    assert_eq!(tree.root_with_outgroup(&[0, 3]), Err(OutgroupError::NotMonophyletic));
+    assert_eq!(OutgroupError::NotMonophyletic.to_string(), "Outgroup is not monophyletic");
+    assert_eq!(newick(&tree), newick(&original));
+}
+
+#[test]
+fn test_root_with_outgroup_clade() {
+    // Smallest clade containing {2, 4} is {2, 3, 4}
+    let mut tree = parse_tree("(0,1,(2,(3,4)));", 5);
+    tree.root_with_outgroup_clade(&[2, 4]).unwrap();
+    assert!(tree.is_valid());
+    assert_eq!(newick(&tree), "((2,(3,4)),(0,1));");
+
+    // Monophyletic outgroup as with the strict variant
+    let mut tree = parse_tree("(0,1,(2,(3,4)));", 5);
+    tree.root_with_outgroup_clade(&[3, 4]).unwrap();
+    assert_eq!(newick(&tree), "((3,4),(2,(0,1)));");
+}
+
+#[test]
+fn test_suppressed_root_metadata_is_recorded() {
+    let mut newick_parser = NewickParser::new().with_num_leaves(4).with_annotations(AnnotationMode::Raw);
+    let tree = newick_parser.parse(&mut ByteParser::from_str("((A:1,B:1):[&r=1]1,(C:1,D:1):[&r=2,s=3]2)root[&n=0];")).unwrap();
+
+    // Merged branch keeps the entries of the first branch, completed by the other
+    let mut unrooted = tree.clone();
+    unrooted.unroot();
+    assert_eq!(unrooted.to_newick(&NewickStyle::ZeroIndexed, None), "(0:1,1:1,(2:1,3:1):[&r=1,s=3]3);");
+    let dropped = unrooted.dropped_metadata();
+    assert_eq!(dropped.len(), 1);
+    assert_eq!(dropped[0].taxa, vec![0, 1, 2, 3]);
+    assert_eq!(dropped[0].node_label.as_deref(), Some("root"));
+    assert_eq!(dropped[0].annotation.as_ref().unwrap().raw(), "n=0");
+
+    // New root reusing the index of the old one does not inherit its label and annotation
+    let mut rerooted = tree.clone();
+    rerooted.root_at(leaf(&rerooted, 0));
+    assert!(rerooted.root().node_label().is_none());
+    assert!(rerooted.annotation(rerooted.root().index()).is_none());
+    assert_eq!(rerooted.dropped_metadata().len(), 1);
+
+    let mut rerooted = tree.clone();
+    rerooted.reroot_at_vertex(rerooted.mrca(&[2, 3]));
+    assert_eq!(rerooted.dropped_metadata(), dropped);
+}
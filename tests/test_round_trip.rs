@@ -0,0 +1,73 @@
+#![cfg(feature = "test-util")]
+
+use nexus_parser::io::parser::newick;
+use nexus_parser::testing::{check_round_trip, NewickGenerator};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+/// Generates trees and checks that they parse and survive the round trip
+fn check_generated(generator: &NewickGenerator, seed: u64, num_trees: usize) {
+    let mut rng = StdRng::seed_from_u64(seed);
+    for _ in 0..num_trees {
+        let newick = generator.generate(&mut rng);
+        if let Err(e) = newick::parse_str(&newick) {
+            panic!("Generated Newick cannot be parsed: {}\n{}", e, newick);
+        }
+        if let Err(e) = check_round_trip(&newick) {
+            panic!("{}\nGenerated: {}", e, newick);
+        }
+    }
+}
+
+#[test]
+fn test_round_trip_of_default_trees() {
+    check_generated(&NewickGenerator::new(), 1, 500);
+}
+
+#[test]
+fn test_round_trip_of_edge_cases() {
+    let generator = NewickGenerator::new()
+        .with_max_leaves(50)
+        .with_max_children(6)
+        .with_quoted_labels(0.8)
+        .with_node_labels(0.8)
+        .with_scientific_notation(0.8)
+        .with_comments(0.5);
+    check_generated(&generator, 2, 200);
+}
+
+#[test]
+fn test_round_trip_of_binary_topologies() {
+    let generator = NewickGenerator::new().with_max_children(2).with_branch_lengths(0.0).with_node_labels(0.0);
+    check_generated(&generator, 3, 200);
+}
+
+#[test]
+fn test_generated_trees_are_valid() {
+    let generator = NewickGenerator::new().with_max_leaves(10);
+    let mut rng = StdRng::seed_from_u64(4);
+    let newicks: Vec<String> = (0..50).map(|_| generator.generate(&mut rng)).collect();
+    for newick in &newicks {
+        let (trees, labels) = newick::parse_str(newick).unwrap();
+        assert!(trees[0].is_valid());
+        assert!((2..=10).contains(&labels.num_labels()));
+    }
+    // Several trees per string pass as well
+    check_round_trip(&newicks.join("\n")).unwrap();
+}
+
+#[test]
+fn test_round_trip_of_handwritten_edge_cases() {
+    for newick in [
+        "('Homo ''sapiens''':1e-3,'a,b':2.5E+2)'node (x)':0.5;",
+        "(A [comment] : [another] 1.0, (B,C,D)95:0)[root];",
+        "((A:0,B:.5):1.,C:7);",
+        "(A_b,B-c,'C d');",
+        // Not valid Newick: passes without panic
+        "((A,B);",
+        "",
+        ";",
+    ] {
+        check_round_trip(newick).unwrap();
+    }
+}
@@ -0,0 +1,77 @@
+#![cfg(feature = "serde")]
+
+use nexus_parser::io::parser::byte_parser::ByteParser;
+use nexus_parser::io::parser::newick::{AnnotationMode, NewickParser};
+use nexus_parser::io::writer::newick::NewickStyle;
+use nexus_parser::model::leaf_label_map::LeafLabelMap;
+use nexus_parser::model::tree::Tree;
+use nexus_parser::model::vertex::BranchLength;
+use nexus_parser::parse_nexus_file;
+use std::path::Path;
+
+#[test]
+fn test_serde_roundtrip_sample() {
+    let path = Path::new("tests").join("fixtures").join("nexus_t11_n20_translate.trees");
+    let (trees, labels) = parse_nexus_file(path.to_str().unwrap()).unwrap();
+
+    let json = serde_json::to_string(&(&trees, &labels)).unwrap();
+    let (restored_trees, restored_labels): (Vec<Tree>, LeafLabelMap) = serde_json::from_str(&json).unwrap();
+
+    assert_eq!(restored_labels.labels(), labels.labels());
+    assert_eq!(restored_labels.get_index(&labels[7]), Some(7));
+    assert_eq!(restored_trees.len(), trees.len());
+    for (tree, restored) in trees.iter().zip(&restored_trees) {
+        assert!(restored.is_valid());
+        assert_eq!(restored.name(), tree.name());
+        assert!(restored.exact_eq(tree, 0.0));
+        assert_eq!(restored.to_newick(&NewickStyle::Label, Some(&restored_labels)),
+                   tree.to_newick(&NewickStyle::Label, Some(&labels)));
+        // Caches are rebuilt on demand
+        assert_eq!(restored.cached_clades(), tree.cached_clades());
+    }
+}
+
+#[test]
+fn test_serde_roundtrip_annotations() {
+    let mut newick_parser = NewickParser::new().with_num_leaves(3).with_annotations(AnnotationMode::Parsed);
+    let tree = newick_parser.parse(&mut ByteParser::from_str("((A[&rate=0.1]:1,B:2)[&support=0.9]:0.5,C:3);")).unwrap();
+
+    let json = serde_json::to_string(&tree).unwrap();
+    let restored: Tree = serde_json::from_str(&json).unwrap();
+
+    let a = tree.mrca(&[0]);
+    assert_eq!(restored.annotation(a), tree.annotation(a));
+    assert_eq!(restored.annotation(a).unwrap().get("rate").unwrap().as_number(), Some(0.1));
+    assert!(restored.is_rooted());
+}
+
+#[test]
+fn test_serde_branch_length() {
+    assert_eq!(serde_json::to_string(&BranchLength::new(0.5)).unwrap(), "0.5");
+    assert_eq!(serde_json::from_str::<BranchLength>("1.5").unwrap(), BranchLength::new(1.5));
+    assert!(serde_json::from_str::<BranchLength>("-1.0").is_err());
+}
+
+#[test]
+fn test_serde_leaf_label_map_schema() {
+    let mut labels = LeafLabelMap::new(2);
+    labels.get_or_insert("A");
+    labels.get_or_insert("B");
+
+    assert_eq!(serde_json::to_string(&labels).unwrap(), r#"{"num_leaves":2,"labels":["A","B"]}"#);
+}
+
+#[test]
+fn test_serde_rejects_invalid_tree() {
+    let tree = NewickParser::new().parse(&mut ByteParser::from_str("((A,B),C);")).unwrap();
+    let json = serde_json::to_string(&tree).unwrap();
+    assert!(serde_json::from_str::<Tree>(&json).is_ok());
+
+    // Root index out of bounds, or more leaves than declared
+    let bad_root = json.replacen(&format!("\"root_index\":{}", tree.root().index()), "\"root_index\":99", 1);
+    assert_ne!(bad_root, json);
+    assert!(serde_json::from_str::<Tree>(&bad_root).is_err());
+    let too_many_leaves = json.replacen("\"num_leaves_init\":3", "\"num_leaves_init\":2", 1);
+    assert_ne!(too_many_leaves, json);
+    assert!(serde_json::from_str::<Tree>(&too_many_leaves).is_err());
+}
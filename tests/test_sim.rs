@@ -0,0 +1,55 @@
+#![cfg(feature = "rand")]
+
+use nexus_parser::io::writer::newick::to_newick;
+use nexus_parser::parse_newick_str;
+use nexus_parser::sim::{coalescent_tree, yule_tree};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+const NUM_REPLICATES: usize = 2000;
+
+#[test]
+fn test_yule_trees_are_valid_and_reproducible() {
+    let (tree, labels) = yule_tree(50, 2.0, &mut StdRng::seed_from_u64(1));
+    assert!(tree.is_valid());
+    assert!(tree.is_binary());
+    assert!(tree.is_ultrametric());
+    assert_eq!(labels.labels()[49], "t50");
+    assert!(tree.validate().is_empty());
+
+    let (again, _) = yule_tree(50, 2.0, &mut StdRng::seed_from_u64(1));
+    assert_eq!(to_newick(&again, &labels), to_newick(&tree, &labels));
+
+    // Written trees can be parsed back
+    let (parsed, _) = parse_newick_str(&to_newick(&tree, &labels)).unwrap();
+    assert_eq!(parsed[0].num_leaves(), 50);
+}
+
+#[test]
+fn test_yule_mean_height() {
+    let mut rng = StdRng::seed_from_u64(7);
+    let mean_height = (0..NUM_REPLICATES).map(|_| yule_tree(10, 1.0, &mut rng).0.height()).sum::<f64>() / NUM_REPLICATES as f64;
+    let expected: f64 = (2..=10).map(|k| 1.0 / k as f64).sum();
+    assert!((mean_height - expected).abs() < 0.08, "{} vs {}", mean_height, expected);
+}
+
+#[test]
+fn test_coalescent_mean_height() {
+    let mut rng = StdRng::seed_from_u64(7);
+    let mean_height = (0..NUM_REPLICATES).map(|_| coalescent_tree(10, 100.0, &mut rng).0.height()).sum::<f64>() / NUM_REPLICATES as f64;
+    let expected = 2.0 * 100.0 * (1.0 - 1.0 / 10.0);
+    assert!((mean_height - expected).abs() < 10.0, "{} vs {}", mean_height, expected);
+}
+
+#[test]
+fn test_two_leaves() {
+    let (tree, _) = coalescent_tree(2, 1.0, &mut StdRng::seed_from_u64(3));
+    assert_eq!(tree.num_vertices(), 3);
+    assert!(tree.is_valid());
+}
+
+#[test]
+#[should_panic(expected = "at least 2 leaves")]
+fn test_single_leaf_panics() {
+    yule_tree(1, 1.0, &mut StdRng::seed_from_u64(3));
+}
@@ -0,0 +1,199 @@
+mod common;
+use common::parse_tree_with_labels;
+
+use nexus_parser::io::parser::byte_parser::ByteParser;
+use nexus_parser::io::parser::newick::{AnnotationMode, NewickParser};
+use nexus_parser::io::writer::newick::NewickStyle;
+use nexus_parser::model::subtree::restrict_sample;
+use nexus_parser::model::taxon_set::TaxonSet;
+use nexus_parser::model::tree::Tree;
+use nexus_parser::parse_newick_str;
+
+fn taxa_under(tree: &Tree, index: usize) -> Vec<usize> {
+    tree.leaves_under(index).filter_map(|leaf| leaf.label_index()).collect()
+}
+
+#[test]
+fn test_mrca() {
+    // Taxa A-F are 0-5
+    let (tree, _) = parse_tree_with_labels("(((A,B),C),((D,E),F));", 6);
+
+    let ab = tree.mrca(&[0, 1]);
+    assert_eq!(taxa_under(&tree, ab), vec![0, 1]);
+    assert_eq!(taxa_under(&tree, tree.mrca(&[1, 2])), vec![0, 1, 2]);
+    assert_eq!(tree.mrca(&[0, 5]), tree.root().index());
+
+    // Duplicates and order do not matter
+    assert_eq!(tree.mrca(&[1, 0, 1]), ab);
+
+    // Single taxon is its leaf
+    let leaf = tree.mrca(&[4]);
+    assert!(tree[leaf].is_leaf());
+    assert_eq!(tree[leaf].label_index(), Some(4));
+}
+
+#[test]
+fn test_mrca_matches_clades() {
+    let (tree, _) = parse_tree_with_labels("((A,(B,C)),(D,(E,(F,G))));", 7);
+
+    for (index, clade) in tree.clades(7).iter().enumerate() {
+        let taxa: Vec<usize> = clade.iter().collect();
+        assert_eq!(tree.clade_taxa(tree.mrca(&taxa)), clade);
+        assert_eq!(TaxonSet::from_indices(7, taxa_under(&tree, index)), *clade);
+    }
+}
+
+#[test]
+#[should_panic(expected = "Not all taxa")]
+fn test_mrca_of_missing_taxon_panics() {
+    let (tree, _) = parse_tree_with_labels("((A,B),C);", 3);
+    tree.mrca(&[0, 7]);
+}
+
+#[test]
+#[should_panic(expected = "at least one taxon")]
+fn test_mrca_of_no_taxa_panics() {
+    let (tree, _) = parse_tree_with_labels("((A,B),C);", 3);
+    tree.mrca(&[]);
+}
+
+#[test]
+fn test_extract_subtree_shares_labels() {
+    let (tree, labels) = parse_tree_with_labels("((A:1,B:2)x:1,(C:1,(D:1,E:1):2)y:1);", 5);
+
+    let subtree = tree.extract_subtree(tree.mrca(&[2, 4]));
+    assert_eq!(subtree.to_newick(&NewickStyle::Label, Some(&labels)), "(C:1,(D:1,E:1):2)y;");
+    assert_eq!(subtree.num_leaves(), 3);
+    assert_eq!(subtree.num_leaves_init(), 5);
+
+    // Clades range over all taxa of the original tree
+    let de = subtree.mrca(&[3, 4]);
+    assert_eq!(subtree.clade_taxa(de), tree.clade_taxa(tree.mrca(&[3, 4])));
+}
+
+#[test]
+fn test_extract_subtree_remapped() {
+    let (tree, labels) = parse_tree_with_labels("((A:1,B:2):1,(C:1,(D:1,E:1):2):1);", 5);
+
+    let (subtree, sublabels) = tree.extract_subtree_remapped(tree.mrca(&[2, 3]), &labels);
+    assert!(subtree.is_valid());
+    assert_eq!(sublabels.labels(), &vec!["C".to_string(), "D".to_string(), "E".to_string()]);
+    assert_eq!(subtree.to_newick(&NewickStyle::Label, Some(&sublabels)), "(C:1,(D:1,E:1):2);");
+    assert_eq!(subtree.to_newick(&NewickStyle::ZeroIndexed, None), "(0:1,(1:1,2:1):2);");
+}
+
+#[test]
+fn test_extract_subtree_copies_annotations() {
+    let mut newick_parser = NewickParser::new().with_num_leaves(3).with_annotations(AnnotationMode::Raw);
+    let tree = newick_parser.parse(&mut ByteParser::from_str("((A[&a=1]:[&r=2]1,B:1)[&c=3]:[&r=4]1,C:1);")).unwrap();
+
+    let subtree = tree.extract_subtree(tree.mrca(&[0, 1]));
+    assert_eq!(subtree.to_newick(&NewickStyle::ZeroIndexed, None), "(0[&a=1]:[&r=2]1,1:1)[&c=3];");
+}
+
+#[test]
+#[should_panic(expected = "Cannot extract subtree below leaf")]
+fn test_extract_subtree_of_leaf_panics() {
+    let (tree, _) = parse_tree_with_labels("((A,B),C);", 3);
+    tree.extract_subtree(tree.mrca(&[2]));
+}
+
+#[test]
+fn test_prune_taxa_merges_branches() {
+    let (tree, labels) = parse_tree_with_labels("((A:1,B:1)x:2,(C:1,(D:1,E:1)y:1)z:1);", 5);
+
+    let pruned = tree.prune_taxa(&[2]);
+    assert_eq!(pruned.to_newick(&NewickStyle::Label, Some(&labels)), "((A:1,B:1)x:2,(D:1,E:1)y:2);");
+    assert_eq!(pruned.num_leaves(), 4);
+    assert_eq!(pruned.num_leaves_init(), 5);
+    assert_eq!(pruned.total_branch_length(), tree.total_branch_length() - 1.0);
+
+    // Emptied clades disappear, unknown taxa are ignored
+    let pruned = tree.prune_taxa(&[3, 4, 9]);
+    assert_eq!(pruned.to_newick(&NewickStyle::Label, Some(&labels)), "((A:1,B:1)x:2,C:2);");
+}
+
+#[test]
+fn test_prune_taxa_replaces_root() {
+    let (tree, labels) = parse_tree_with_labels("(A:3,(B:2,(C:1,D:1):1):1);", 4);
+
+    let pruned = tree.prune_taxa(&[0]);
+    assert_eq!(pruned.to_newick(&NewickStyle::Label, Some(&labels)), "(B:2,(C:1,D:1):1);");
+    assert!(pruned.root().is_root());
+    assert_eq!(pruned.root_height(), Some(2.0));
+
+    let pruned = tree.prune_taxa(&[0, 1]);
+    assert_eq!(pruned.to_newick(&NewickStyle::Label, Some(&labels)), "(C:1,D:1);");
+}
+
+#[test]
+fn test_prune_taxa_merges_branch_annotations() {
+    let mut newick_parser = NewickParser::new().with_num_leaves(4).with_annotations(AnnotationMode::Raw);
+    let tree = newick_parser.parse(&mut ByteParser::from_str("((A:[&r=1]1,B:1)x[&n=1]:[&r=2,s=3]1,(C:1,D:1):1);")).unwrap();
+    assert!(tree.dropped_metadata().is_empty());
+
+    // Branch of A keeps its entries, completed by those of the branch above the suppressed vertex
+    let pruned = tree.prune_taxa(&[1]);
+    assert_eq!(pruned.to_newick(&NewickStyle::ZeroIndexed, None), "(0:[&r=1,s=3]2,(2:1,3:1):1);");
+    let dropped = pruned.dropped_metadata();
+    assert_eq!(dropped.len(), 1);
+    assert_eq!(dropped[0].taxa, vec![0, 1]);
+    assert_eq!(dropped[0].node_label.as_deref(), Some("x"));
+    assert_eq!(dropped[0].annotation.as_ref().unwrap().raw(), "n=1");
+    assert!(dropped[0].branch_annotation.is_none());
+}
+
+#[test]
+fn test_prune_taxa_records_dropped_root_metadata() {
+    let mut newick_parser = NewickParser::new().with_num_leaves(4).with_annotations(AnnotationMode::Raw);
+    let tree = newick_parser.parse(&mut ByteParser::from_str("(A:3,(B:2,(C:1,D:1)y[&n=2]:[&r=5]1)x:1)[&n=0];")).unwrap();
+
+    let mut pruned = tree.prune_taxa(&[0, 1]);
+    assert_eq!(pruned.to_newick(&NewickStyle::ZeroIndexed, None), "(2:1,3:1)y[&n=2];");
+    let dropped = pruned.take_dropped_metadata();
+    assert_eq!(dropped.len(), 3);
+    assert_eq!((dropped[0].taxa.len(), dropped[0].annotation.as_ref().unwrap().raw()), (4, "n=0"));
+    assert_eq!((dropped[1].taxa.len(), dropped[1].node_label.as_deref()), (3, Some("x")));
+    assert_eq!(dropped[2].taxa, vec![2, 3]);
+    assert!(dropped[2].annotation.is_none());
+    assert_eq!(dropped[2].branch_annotation.as_ref().unwrap().raw(), "r=5");
+    assert!(pruned.dropped_metadata().is_empty());
+}
+
+#[test]
+fn test_prune_taxa_without_branch_lengths() {
+    let (tree, labels) = parse_tree_with_labels("((A,B),(C,D));", 4);
+
+    let pruned = tree.prune_taxa(&[1]);
+    assert_eq!(pruned.to_newick(&NewickStyle::Label, Some(&labels)), "(A,(C,D));");
+    assert_eq!(pruned.clade_taxa(pruned.mrca(&[2, 3])), tree.clade_taxa(tree.mrca(&[2, 3])));
+}
+
+#[test]
+#[should_panic(expected = "fewer than two taxa")]
+fn test_prune_to_single_taxon_panics() {
+    let (tree, _) = parse_tree_with_labels("((A,B),C);", 3);
+    tree.prune_taxa(&[0, 1]);
+}
+
+#[test]
+fn test_restrict_sample() {
+    let (trees, labels) = parse_newick_str("((A:1,B:1):1,(C:1,D:1):1);\n((A:1,C:1):1,(B:1,D:1):1);").unwrap();
+
+    let (restricted, sublabels) = restrict_sample(&trees, &labels, &["C", "A", "D", "A"]);
+    assert_eq!(sublabels.labels(), &vec!["C".to_string(), "A".to_string(), "D".to_string()]);
+    assert_eq!(restricted.len(), 2);
+    for tree in &restricted {
+        assert!(tree.is_valid());
+        assert_eq!(tree.num_leaves(), 3);
+    }
+    assert_eq!(restricted[0].to_newick(&NewickStyle::Label, Some(&sublabels)), "(A:2,(C:1,D:1):1);");
+    assert_eq!(restricted[1].to_newick(&NewickStyle::ZeroIndexed, None), "((1:1,0:1):1,2:2);");
+}
+
+#[test]
+#[should_panic(expected = "Taxon 'X' to keep")]
+fn test_restrict_sample_to_unknown_taxon_panics() {
+    let (trees, labels) = parse_newick_str("((A,B),C);").unwrap();
+    restrict_sample(&trees, &labels, &["A", "X"]);
+}
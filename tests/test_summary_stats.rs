@@ -0,0 +1,122 @@
+use nexus_parser::stats::summary::{effective_sample_size, hpd_interval, mean, median, std_dev, variance};
+#[cfg(feature = "fs")]
+use nexus_parser::stats::tree_summary::SampleSummary;
+#[cfg(feature = "fs")]
+use nexus_parser::parse_nexus_file;
+use nexus_parser::parse_newick_str;
+#[cfg(feature = "fs")]
+use std::path::Path;
+
+const EPSILON: f64 = 1e-9;
+
+#[test]
+fn test_mean_and_median() {
+    let values = [3.0, 1.0, 4.0, 1.0, 5.0];
+    assert!((mean(&values).unwrap() - 2.8).abs() < EPSILON);
+    assert_eq!(median(&values), Some(3.0));
+    assert_eq!(median(&[4.0, 1.0, 3.0, 2.0]), Some(2.5));
+
+    assert_eq!(mean(&[]), None);
+    assert_eq!(median(&[]), None);
+}
+
+#[test]
+fn test_variance_and_std_dev() {
+    let values = [2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0];
+    assert!((variance(&values).unwrap() - 32.0 / 7.0).abs() < EPSILON);
+    assert!((std_dev(&values).unwrap() - (32.0f64 / 7.0).sqrt()).abs() < EPSILON);
+    assert_eq!(variance(&[1.0]), None);
+}
+
+#[test]
+fn test_hpd_interval_uniform() {
+    let values: Vec<f64> = (0..100).map(|i| i as f64).collect();
+    let (lower, upper) = hpd_interval(&values, 0.95).unwrap();
+    assert_eq!(upper - lower, 94.0);
+
+    assert_eq!(hpd_interval(&values, 1.0), Some((0.0, 99.0)));
+    assert_eq!(hpd_interval(&[], 0.95), None);
+}
+
+#[test]
+fn test_hpd_interval_skewed() {
+    // Mass concentrated near zero with a long right tail: HPD hugs the left end
+    let mut values: Vec<f64> = (0..90).map(|i| i as f64 * 0.01).collect();
+    values.extend((0..10).map(|i| 10.0 + i as f64 * 10.0));
+    let (lower, upper) = hpd_interval(&values, 0.9).unwrap();
+    assert_eq!(lower, 0.0);
+    assert!((upper - 0.89).abs() < EPSILON);
+}
+
+#[test]
+#[should_panic]
+fn test_hpd_interval_invalid_mass() {
+    hpd_interval(&[1.0, 2.0], 1.5);
+}
+
+#[test]
+fn test_effective_sample_size() {
+    // Alternating series is negatively autocorrelated: no reduction
+    let alternating: Vec<f64> = (0..100).map(|i| (i % 2) as f64).collect();
+    assert_eq!(effective_sample_size(&alternating), Some(100.0));
+
+    // Slowly drifting series is strongly autocorrelated
+    let drifting: Vec<f64> = (0..1000).map(|i| (i / 100) as f64).collect();
+    let ess = effective_sample_size(&drifting).unwrap();
+    assert!(ess < 100.0);
+
+    // AR(1) chain x_t = φ x_(t-1) + ε_t with φ = 0.5 has τ = (1 + φ) / (1 - φ) = 3
+    let mut state: u64 = 42;
+    let mut noise = || {
+        // xorshift64, uniform in [-0.5, 0.5)
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        (state >> 11) as f64 / (1u64 << 53) as f64 - 0.5
+    };
+    let mut chain = vec![0.0; 200_000];
+    for t in 1..chain.len() {
+        chain[t] = 0.5 * chain[t - 1] + noise();
+    }
+    let ess = effective_sample_size(&chain).unwrap();
+    assert!((ess / (200_000.0 / 3.0) - 1.0).abs() < 0.05, "ESS {ess}");
+
+    // Constant series
+    assert_eq!(effective_sample_size(&[1.0; 10]), Some(10.0));
+    assert_eq!(effective_sample_size(&[1.0]), None);
+}
+
+#[test]
+fn test_branch_length_stats() {
+    let (trees, _) = parse_newick_str("((A:1,B:2):0.5,(C:1,D:4):0.5);\n((A,B),(C,D));\n").unwrap();
+    let stats = trees[0].branch_length_stats().unwrap();
+    assert_eq!(stats.count, 6);
+    assert_eq!((stats.min, stats.max), (0.5, 4.0));
+    assert!((stats.mean - 9.0 / 6.0).abs() < EPSILON);
+    assert_eq!(stats.median, 1.0);
+    assert_eq!(trees[0].max_root_distance(), Some(4.5));
+
+    assert_eq!(trees[1].branch_length_stats(), None);
+    assert_eq!(trees[1].max_root_distance(), None);
+}
+
+#[cfg(feature = "fs")]
+#[test]
+fn test_sample_summary_of_tree_lengths_and_root_heights() {
+    let path = Path::new("tests").join("fixtures").join("nexus_t11_n20_translate.trees");
+    let (trees, _) = parse_nexus_file(path.to_str().unwrap()).unwrap();
+    let summary = SampleSummary::new(&trees);
+    assert_eq!(summary.num_trees(), 11);
+    assert_eq!(summary.num_without_branch_lengths(), 0);
+    assert_eq!(summary.tree_lengths().len(), 11);
+    assert!((summary.tree_lengths()[3] - trees[3].total_branch_length()).abs() < EPSILON);
+
+    let heights = summary.root_height_stats().unwrap();
+    assert!(heights.min <= heights.median && heights.median <= heights.max);
+    let (lower, upper) = summary.root_height_hpd().unwrap();
+    assert!(heights.min <= lower && upper <= heights.max);
+
+    let empty = SampleSummary::new(&[]);
+    assert_eq!(empty.tree_length_stats(), None);
+    assert_eq!(empty.root_height_hpd(), None);
+}
@@ -0,0 +1,41 @@
+use nexus_parser::algorithms::tanglegram::count_crossings;
+use nexus_parser::algorithms::untangle;
+use nexus_parser::parse_newick_str;
+
+#[test]
+fn test_count_crossings() {
+    assert_eq!(count_crossings(&[], &[]), 0);
+    assert_eq!(count_crossings(&[0, 1, 2, 3, 4], &[0, 1, 2, 3, 4]), 0);
+    assert_eq!(count_crossings(&[0, 1, 2, 3, 4], &[4, 3, 2, 1, 0]), 10);
+    assert_eq!(count_crossings(&[2, 0, 3, 1], &[0, 1, 2, 3]), 3);
+    // Taxa in one order only are ignored
+    assert_eq!(count_crossings(&[5, 0, 1], &[1, 0, 7]), 1);
+}
+
+#[test]
+fn test_untangle_same_topology() {
+    let (trees, _) = parse_newick_str("((A,(B,C)),((D,E),(F,G)));\n(((G,F),(E,D)),((C,B),A));\n").unwrap();
+    let untangled = untangle(&trees[0], &trees[1]);
+    assert_eq!(untangled.crossings(), 0);
+    assert_eq!(untangled.left(), untangled.right());
+
+    // Inputs are not changed
+    assert_eq!(count_crossings(&trees[0].leaf_order(), &trees[1].leaf_order()), 21);
+}
+
+#[test]
+fn test_untangle_different_topologies() {
+    let (mut trees, labels) = parse_newick_str("(((A,B),(C,D)),(E,F));\n((F,(D,(B,E))),(C,A));\n").unwrap();
+    let before = count_crossings(&trees[0].leaf_order(), &trees[1].leaf_order());
+    let untangled = untangle(&trees[0], &trees[1]);
+    assert!(untangled.crossings() < before);
+    assert_eq!(untangled.crossings(), count_crossings(untangled.left(), untangled.right()));
+
+    // Orders are realized by reordering the children, e.g. before drawing
+    trees[0].order_leaves(untangled.left());
+    trees[1].order_leaves(untangled.right());
+    assert_eq!(trees[0].leaf_order(), untangled.left());
+    assert_eq!(trees[1].leaf_order(), untangled.right());
+    assert!(trees.iter().all(|tree| tree.is_valid()));
+    assert_eq!(trees[0].to_ascii(&labels).lines().filter(|line| line.ends_with(char::is_alphabetic)).count(), 6);
+}
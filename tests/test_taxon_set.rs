@@ -0,0 +1,61 @@
+use nexus_parser::model::taxon_set::TaxonSet;
+use std::collections::HashSet;
+
+#[test]
+fn test_insert_contains_remove() {
+    let mut set = TaxonSet::new(100);
+    assert!(set.is_empty());
+
+    set.insert(3);
+    set.insert(64);
+    set.insert(99);
+    assert_eq!(set.len(), 3);
+    assert!(set.contains(64));
+    assert!(!set.contains(65));
+    assert!(!set.contains(1000));
+
+    set.remove(64);
+    assert!(!set.contains(64));
+    assert_eq!(set.iter().collect::<Vec<_>>(), vec![3, 99]);
+    assert_eq!(set.first(), Some(3));
+}
+
+#[test]
+#[should_panic]
+fn test_insert_out_of_bounds() {
+    let mut set = TaxonSet::new(10);
+    set.insert(10);
+}
+
+#[test]
+fn test_set_operations() {
+    let a = TaxonSet::from_indices(70, [0, 1, 2, 65]);
+    let b = TaxonSet::from_indices(70, [2, 3, 65]);
+
+    assert_eq!(a.union(&b), TaxonSet::from_indices(70, [0, 1, 2, 3, 65]));
+    assert_eq!(a.intersection(&b), TaxonSet::from_indices(70, [2, 65]));
+    assert_eq!(a.difference(&b), TaxonSet::from_indices(70, [0, 1]));
+    assert!(TaxonSet::from_indices(70, [1, 65]).is_subset_of(&a));
+    assert!(!b.is_subset_of(&a));
+    assert!(TaxonSet::from_indices(70, [4, 5]).is_disjoint(&a));
+}
+
+#[test]
+fn test_complement_and_full() {
+    let set = TaxonSet::from_indices(67, [0, 66]);
+    let complement = set.complement();
+    assert_eq!(complement.len(), 65);
+    assert!(!complement.contains(0));
+    assert_eq!(set.union(&complement), TaxonSet::full(67));
+    assert_eq!(complement.complement(), set);
+}
+
+#[test]
+fn test_hash_and_display() {
+    let a = TaxonSet::from_indices(4, [1, 2]);
+    let b = TaxonSet::from_indices(4, [2, 1]);
+    let mut seen = HashSet::new();
+    seen.insert(a.clone());
+    assert!(seen.contains(&b));
+    assert_eq!(a.to_string(), "0110");
+}
@@ -0,0 +1,81 @@
+mod common;
+use common::approx_eq;
+
+use nexus_parser::parse_newick_str;
+use nexus_parser::stats::tip_dating::{parse_date, root_to_tip_distances, RootToTipRegression, TipDates};
+
+const EPSILON: f64 = 1e-9;
+
+#[test]
+fn test_parse_date() {
+    assert_eq!(parse_date("2021"), Some(2021.5));
+    assert_eq!(parse_date(" 2019.9 "), Some(2019.9));
+    assert_eq!(parse_date("2021-12-31"), Some(2021.0 + 364.5 / 365.0));
+    assert_eq!(parse_date("2020-12"), Some(2020.0 + (335.0 + 15.5) / 366.0));
+    assert!(parse_date("1900-02-29").is_none());
+    assert!(parse_date("2000-02-29").is_some());
+
+    for invalid in ["", "abc", "2021-13", "2021-00-10", "2021-01-32", "2021-1-1-1", "2021--01", "NaN.", "-2021"] {
+        assert_eq!(parse_date(invalid), None, "{}", invalid);
+    }
+}
+
+#[test]
+fn test_tip_dates_from_labels() {
+    let (_, labels) = parse_newick_str("((A_2001,B_2002.5),(C,D_unknown));").unwrap();
+    let mut dates = TipDates::from_delimited(&labels, '_');
+    assert_eq!(dates.num_labels(), 4);
+    assert_eq!(dates.get(0), Some(2001.5));
+    assert_eq!(dates.get(1), Some(2002.5));
+    assert_eq!(dates.undated(), vec![2, 3]);
+    assert_eq!(dates.get(17), None);
+
+    dates.set(2, Some(2003.0));
+    dates.set(0, None);
+    assert_eq!(dates.num_dated(), 2);
+
+    // Custom extraction, e.g. the first four characters
+    let (_, labels) = parse_newick_str("(1999abc,2005xyz);").unwrap();
+    let dates = TipDates::from_labels(&labels, |label| label.get(..4));
+    assert_eq!(dates.num_dated(), 2);
+}
+
+#[cfg(feature = "regex")]
+#[test]
+fn test_tip_dates_from_regex() {
+    let (_, labels) = parse_newick_str("(virus|2021-03-04|x,virus|2020|y,virus);").unwrap();
+    let dates = TipDates::from_regex(&labels, &regex::Regex::new(r"\|(\d{4}(-\d{2})*)\|").unwrap());
+    assert_eq!(dates.get(1), Some(2020.5));
+    assert!((dates.get(0).unwrap() - 2021.17).abs() < 0.01);
+    assert_eq!(dates.undated(), vec![2]);
+}
+
+#[test]
+fn test_root_to_tip_regression() {
+    // Root-to-tip distances increase with the dates, except for D
+    let newick = "((A/2000:0.01,B/2010:0.03):0.03,(C/2020:0.04,D/2015:0.04):0.04);";
+    let (trees, labels) = parse_newick_str(newick).unwrap();
+    let dates = TipDates::from_delimited(&labels, '/');
+
+    let distances = root_to_tip_distances(&trees[0]).unwrap();
+    assert_eq!(distances.len(), 4);
+    assert!(approx_eq(distances[3].1, 0.08, EPSILON));
+
+    let regression = RootToTipRegression::fit(&trees[0], &dates).unwrap();
+    assert_eq!(regression.num_tips, 4);
+    assert!(regression.slope > 0.0);
+    assert!(regression.r_squared > 0.5 && regression.r_squared < 1.0);
+    assert!((regression.intercept + regression.slope * regression.root_date().unwrap()).abs() < EPSILON);
+
+    // Too few or equal dates
+    let mut one_date = TipDates::new(4);
+    one_date.set(0, Some(2000.0));
+    assert!(RootToTipRegression::fit(&trees[0], &one_date).is_none());
+    one_date.set(1, Some(2000.0));
+    assert!(RootToTipRegression::fit(&trees[0], &one_date).is_none());
+
+    // Missing branch lengths
+    let (trees, labels) = parse_newick_str("((A/2000,B/2010:1):1,C/2020:1);").unwrap();
+    assert!(root_to_tip_distances(&trees[0]).is_none());
+    assert!(RootToTipRegression::fit(&trees[0], &TipDates::from_delimited(&labels, '/')).is_none());
+}
@@ -0,0 +1,53 @@
+mod common;
+use common::parse_trees;
+
+use nexus_parser::algorithms::topology_hash::dedup_topologies;
+use nexus_parser::model::tree::Tree;
+#[cfg(feature = "fs")]
+use nexus_parser::parse_nexus_file;
+#[cfg(feature = "fs")]
+use std::path::Path;
+
+#[test]
+fn test_topology_hash() {
+    let trees = parse_trees(&["(((A:1,B:1):1,C:2):1,(D:1,E:1):2);", "((E,D),(C,(B,A)));",
+                              "((A,B),(C,(D,E)));", "((A,B),C,(D,E));"], 5);
+
+    assert_eq!(trees[0].topology_hash(), trees[1].topology_hash());
+    assert_ne!(trees[0].topology_hash(), trees[2].topology_hash());
+    assert_ne!(trees[0].topology_hash(), trees[3].topology_hash());
+
+    // Unrooted, all but the resolution is the same
+    let unrooted: Vec<Tree> = trees.into_iter().map(|tree| tree.with_rooted(false)).collect();
+    assert_eq!(unrooted[0].topology_hash(), unrooted[2].topology_hash());
+    assert_eq!(unrooted[2].topology_hash(), unrooted[3].topology_hash());
+}
+
+#[test]
+fn test_dedup_topologies() {
+    let trees = parse_trees(&["((A,B),(C,D));", "((A,C),(B,D));", "((C,A),(D,B));", "((B,D),(A,C));", "((D,C),(B,A));"], 4);
+    let topologies = dedup_topologies(&trees);
+
+    assert_eq!(topologies.len(), 2);
+    assert!(std::ptr::eq(topologies[0].0, &trees[1]));
+    assert_eq!(topologies[0].1, 3);
+    assert!(std::ptr::eq(topologies[1].0, &trees[0]));
+    assert_eq!(topologies[1].1, 2);
+
+    assert!(dedup_topologies(&[]).is_empty());
+}
+
+#[cfg(feature = "fs")]
+#[test]
+fn test_dedup_topologies_of_sample() {
+    let path = Path::new("tests").join("fixtures").join("nexus_t11_n20_translate.trees");
+    let (trees, _) = parse_nexus_file(path.to_str().unwrap()).unwrap();
+    let topologies = dedup_topologies(&trees);
+
+    assert_eq!(topologies.iter().map(|(_, count)| count).sum::<usize>(), trees.len());
+    for (i, (tree, _)) in topologies.iter().enumerate() {
+        for (other, _) in &topologies[i + 1..] {
+            assert!(!tree.topology_eq(other));
+        }
+    }
+}
@@ -0,0 +1,87 @@
+#[cfg(feature = "fs")]
+use nexus_parser::io::parser::nexus::Burnin;
+use nexus_parser::io::parser::parsing_error::ParsingErrorType;
+use nexus_parser::io::parser::trace;
+#[cfg(feature = "fs")]
+use nexus_parser::parse_trace_file;
+#[cfg(feature = "fs")]
+use std::path::Path;
+
+fn trace_error(trace: &str) -> String {
+    let error = trace::parse_str(trace).expect_err("Invalid trace is rejected");
+    assert!(matches!(error.kind(), ParsingErrorType::InvalidTrace(_)), "{}", error);
+    error.to_string()
+}
+
+#[cfg(feature = "fs")]
+#[test]
+fn test_mrbayes_trace_file() {
+    let path = Path::new("tests").join("fixtures").join("trace_s5_mrbayes.p");
+    let mut trace = parse_trace_file(path.to_str().unwrap()).unwrap();
+
+    assert_eq!(trace.column_names(), ["Gen", "LnL", "LnPr", "TL", "r(A<->C)", "pi(A)"]);
+    assert_eq!(trace.num_columns(), 6);
+    assert_eq!(trace.num_samples(), 5);
+    assert_eq!(trace.states(), Some(&[1.0, 1000.0, 2000.0, 3000.0, 4000.0][..]));
+    assert_eq!(trace.column("TL").unwrap()[1], 1.0234);
+    assert_eq!(trace.column_at(5), trace.column("pi(A)"));
+    assert_eq!(trace.column("kappa"), None);
+
+    assert_eq!(trace.discard_burnin(Burnin::Count(1)), 1);
+    assert_eq!(trace.num_samples(), 4);
+    let summary = trace.summary("LnL").unwrap();
+    assert!((summary.mean() - (-4321.876 - 4310.455 - 4312.913 - 4309.210) / 4.0).abs() < 1e-9);
+    assert_eq!(summary.hpd(), Some((-4321.876, -4309.210)));
+    assert!(summary.ess().is_some());
+}
+
+#[cfg(feature = "fs")]
+#[test]
+fn test_beast_trace_file() {
+    let path = Path::new("tests").join("fixtures").join("trace_s4_beast.log");
+    let mut trace = parse_trace_file(path.to_str().unwrap()).unwrap();
+
+    assert_eq!(trace.num_samples(), 4);
+    assert_eq!(trace.column("clockRate"), Some(&[1.0e-3, 1.2e-3, 1.1e-3, 9.5e-4][..]));
+    assert_eq!(trace.discard_burnin(Burnin::Percentage(0.5)), 2);
+    assert_eq!(trace.states(), Some(&[20000.0, 30000.0][..]));
+
+    // All parameters but the state are summarized
+    let names: Vec<String> = trace.summaries().iter().map(|summary| summary.name().to_string()).collect();
+    assert_eq!(names, ["posterior", "likelihood", "prior", "treeLength", "clockRate"]);
+}
+
+#[cfg(feature = "fs")]
+#[test]
+fn test_beast2_trace_file_with_trailing_tabs() {
+    let path = Path::new("tests").join("fixtures").join("trace_s3_beast2_trailing_tabs.log");
+    let trace = parse_trace_file(path.to_str().unwrap()).unwrap();
+
+    assert_eq!(trace.num_columns(), 7);
+    assert_eq!(trace.column_names().last().unwrap(), "freqParameter.2");
+    assert_eq!(trace.states(), Some(&[0.0, 1000.0, 2000.0][..]));
+    assert_eq!(trace.column("freqParameter.2"), Some(&[0.25, 0.23, 0.24][..]));
+
+    let trace = trace::parse_str("Sample\tposterior\t\n0\t-1.0\t\n").unwrap();
+    assert_eq!(trace.column("posterior"), Some(&[-1.0][..]));
+}
+
+#[test]
+fn test_trace_without_state_column() {
+    let trace = trace::parse_str("a\tb\r\n1\t2\r\n\r\n3\tNaN\r\n").unwrap();
+    assert_eq!(trace.states(), None);
+    assert_eq!(trace.num_samples(), 2);
+    assert!(trace.column("b").unwrap()[1].is_nan());
+    assert_eq!(trace.summaries().len(), 2);
+}
+
+#[test]
+fn test_invalid_traces() {
+    assert!(trace_error("# only comments\n").contains("Missing header"));
+    assert!(trace_error("state\tx\tx\n0\t1\t2\n").contains("Duplicate column 'x'"));
+    assert!(trace_error("state\tx\n0\t1\n1000\n").contains("1 values, expected 2"));
+
+    let error = trace_error("state\tx\n0\t1\n1000\tabc\n");
+    assert!(error.contains("Invalid value 'abc' in column 'x'"), "{}", error);
+    assert!(error.contains("line 3"), "{}", error);
+}
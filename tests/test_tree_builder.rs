@@ -0,0 +1,68 @@
+use nexus_parser::io::parser::byte_parser::ByteParser;
+use nexus_parser::io::parser::newick::NewickParser;
+use nexus_parser::io::writer::newick::NewickStyle;
+use nexus_parser::model::tree::Tree;
+use nexus_parser::model::tree_builder::TreeBuilder;
+
+#[test]
+fn test_with_capacity() {
+    // Unary vertex above a leaf needs more than 2n - 1 vertices
+    let mut tree = Tree::with_capacity(2, 4);
+    let a = tree.add_leaf(None, 0);
+    let unary = tree.add_internal_vertex(vec![a], None);
+    let b = tree.add_leaf(None, 1);
+    tree.add_root(vec![unary, b]);
+    assert_eq!(tree.num_vertices(), 4);
+    assert_eq!(tree.num_leaves_init(), 2);
+}
+
+#[test]
+fn test_builder_reuses_recycled_trees() {
+    let mut builder = TreeBuilder::new();
+    let mut tree = builder.tree(3);
+    assert_eq!(builder.num_spare_arenas(), 0);
+    let leaves: Vec<_> = (0..3).map(|label_index| tree.add_leaf(None, label_index)).collect();
+    let mut children = builder.children();
+    children.extend(leaves);
+    tree.add_root(children);
+
+    builder.recycle(tree);
+    assert_eq!(builder.num_spare_arenas(), 1);
+    assert!(builder.children().is_empty());
+
+    // Reused tree is empty again, with the number of leaves asked for
+    let tree = builder.tree(4);
+    assert_eq!(builder.num_spare_arenas(), 0);
+    assert_eq!(tree.num_vertices(), 0);
+    assert_eq!(tree.num_leaves_init(), 4);
+    assert!(!tree.is_root_set());
+}
+
+#[test]
+fn test_newick_parser_recycle() {
+    let input = "((A:1,B:1):1,C:2);((A:1,C:1):1,B:2);(A,(B,C)95);";
+    let mut byte_parser = ByteParser::from_str(input);
+    let mut newick_parser = NewickParser::new();
+
+    let mut written = Vec::new();
+    for _ in 0..3 {
+        byte_parser.skip_whitespace();
+        let tree = newick_parser.parse(&mut byte_parser).unwrap();
+        assert!(tree.is_valid());
+        written.push(tree.to_newick(&NewickStyle::Label, Some(newick_parser.leaf_label_map())));
+        newick_parser.recycle(tree);
+    }
+
+    assert_eq!(written, vec!["((A:1,B:1):1,C:2);", "((A:1,C:1):1,B:2);", "(A,(B,C)95);"]);
+}
+
+#[test]
+fn test_newick_parser_after_error() {
+    let mut newick_parser = NewickParser::new();
+    assert!(newick_parser.parse(&mut ByteParser::from_str("((A,B),C")).is_err());
+
+    // Buffers of the failed tree are reused for the next one
+    let tree = newick_parser.parse(&mut ByteParser::from_str("((A,B),C);")).unwrap();
+    assert!(tree.is_valid());
+    assert_eq!(tree.num_leaves(), 3);
+}
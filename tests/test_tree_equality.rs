@@ -0,0 +1,69 @@
+mod common;
+use common::{length_tolerance, parse_trees};
+
+use nexus_parser::model::tree::Tree;
+#[cfg(feature = "fs")]
+use nexus_parser::parse_nexus_file;
+#[cfg(feature = "fs")]
+use std::path::Path;
+
+#[test]
+fn test_topology_eq() {
+    let trees = parse_trees(&["(((A,B),C),(D,E));", "((E,D),(C,(B,A)));", "((A,B),(C,(D,E)));", "((A,B),C,(D,E));"], 5);
+
+    assert!(trees[0].topology_eq(&trees[0]));
+    assert!(trees[0].topology_eq(&trees[1]));
+    assert!(!trees[0].topology_eq(&trees[2]));
+    assert!(!trees[0].topology_eq(&trees[3]));
+
+    // Unrooted, the root position does not matter, but resolution does
+    let unrooted: Vec<Tree> = trees.into_iter().map(|tree| tree.with_rooted(false)).collect();
+    assert!(unrooted[0].topology_eq(&unrooted[2]));
+    assert!(unrooted[0].topology_eq(&unrooted[3]));
+    assert!(unrooted[3].topology_eq(&unrooted[2]));
+}
+
+#[test]
+fn test_topology_eq_different_taxa() {
+    let trees = parse_trees(&["((A,B),(C,D));", "((A,B),(C,E));"], 5);
+    assert!(!trees[0].topology_eq(&trees[1]));
+    assert!(!trees[0].exact_eq(&trees[1], 1.0));
+}
+
+#[test]
+fn test_exact_eq() {
+    let trees = parse_trees(&["((A:0.1,B:0.2):0.3,(C:0.4,D:0.5):0.6);",
+                              "((D:0.5,C:0.4):0.6,(B:0.2,A:0.1):0.3);",
+                              "((A:0.1,B:0.2):0.6,(C:0.4,D:0.5):0.3);",
+                              "((A,B),(C,D));",
+                              "((A:0.1,B:0.2):0.3,(C:0.4,D:0.5):0.6000001);"], 4);
+
+    assert!(trees[0].exact_eq(&trees[1], 0.0));
+    assert!(trees[0].topology_eq(&trees[2]));
+    assert!(!trees[0].exact_eq(&trees[2], 0.1));
+    assert!(trees[0].exact_eq(&trees[2], 0.3 + length_tolerance(0.6)));
+    assert!(!trees[0].exact_eq(&trees[3], 1.0));
+    assert!(trees[3].exact_eq(&trees[3], 0.0));
+    assert!(!trees[0].exact_eq(&trees[4], 1e-9));
+    assert!(trees[0].exact_eq(&trees[4], 1e-6));
+}
+
+#[cfg(feature = "fs")]
+#[test]
+fn test_deduplicate_sample() {
+    let path = Path::new("tests").join("fixtures").join("nexus_t11_n20_translate.trees");
+    let (trees, _) = parse_nexus_file(path.to_str().unwrap()).unwrap();
+
+    let mut distinct: Vec<&Tree> = Vec::new();
+    for tree in &trees {
+        assert!(tree.topology_eq(tree));
+        assert!(tree.exact_eq(tree, 0.0));
+        if !distinct.iter().any(|other| other.topology_eq(tree)) {
+            distinct.push(tree);
+        }
+    }
+    // Ten samples share one topology, the second one differs
+    assert_eq!(trees.len(), 11);
+    assert_eq!(distinct.len(), 2);
+    assert!(std::ptr::eq(distinct[1], &trees[1]));
+}
@@ -0,0 +1,116 @@
+use nexus_parser::io::writer::newick::to_newick;
+use nexus_parser::model::leaf_label_map::{LabelRenaming, LeafLabelMap, RenameError};
+use nexus_parser::model::tree::Tree;
+use nexus_parser::model::tree_sample::{remap_labels, LabelMapping, TreeSample, TreeSampleError};
+use nexus_parser::parse_newick_str;
+
+fn sample(newick: &str) -> TreeSample {
+    TreeSample::from(parse_newick_str(newick).unwrap())
+}
+
+#[test]
+fn test_new_checks_label_indices() {
+    let (trees, labels) = parse_newick_str("((A,B),C);(A,(B,C));").unwrap();
+    assert!(TreeSample::new(trees.clone(), labels).is_ok());
+
+    let mut too_few = LeafLabelMap::new(2);
+    too_few.get_or_insert("A");
+    too_few.get_or_insert("B");
+    let result = TreeSample::new(trees, too_few);
+    assert_eq!(result.unwrap_err(), TreeSampleError::UnknownLabelIndex { tree_index: 0, label_index: 2 });
+}
+
+#[test]
+fn test_iteration_and_indexing() {
+    let sample = sample("((A,B),C);(A,(B,C));((A,C),B);");
+    assert_eq!(sample.len(), 3);
+    assert!(!sample.is_empty());
+    assert_eq!(sample.iter().count(), 3);
+    assert_eq!((&sample).into_iter().filter(|tree| tree.is_binary()).count(), 3);
+    assert!(sample.get(3).is_none());
+    assert_eq!(sample.names().collect::<Vec<_>>(), vec![None, None, None]);
+    assert!(sample.get_by_name("t1").is_none());
+}
+
+#[test]
+fn test_push_and_retain() {
+    let mut sample = sample("((A,B),C);");
+    let mut tree = Tree::new(4);
+    let leaves = (0..4).map(|label_index| tree.add_leaf(None, label_index)).collect();
+    tree.add_root(leaves);
+    assert_eq!(sample.push(tree), Err(TreeSampleError::UnknownLabelIndex { tree_index: 1, label_index: 3 }));
+
+    let (trees, _) = parse_newick_str("(A,B,C);").unwrap();
+    sample.push(trees.into_iter().next().unwrap()).unwrap();
+    assert_eq!(sample.len(), 2);
+    sample.retain(|tree| tree.is_binary());
+    assert_eq!(sample.len(), 1);
+}
+
+#[test]
+fn test_concat() {
+    let mut first = sample("((A,B),C);");
+    let second = sample("(A,(B,C));((A,C),B);");
+    first.concat(second).unwrap();
+    assert_eq!(first.len(), 3);
+
+    // Same taxa in another order have different label indices
+    let other_order = sample("((C,B),A);");
+    assert_eq!(first.concat(other_order), Err(TreeSampleError::DifferentLabels(0)));
+    let more_taxa = sample("((A,B),(C,D));");
+    assert_eq!(first.concat(more_taxa), Err(TreeSampleError::DifferentLabels(3)));
+    assert_eq!(first.len(), 3);
+
+    let (trees, labels) = first.into_parts();
+    assert_eq!(trees.len(), 3);
+    assert_eq!(labels.num_labels(), 3);
+}
+
+#[test]
+fn test_merge_reconciles_labels_by_name() {
+    let mut chain_1 = TreeSample::from(parse_newick_str("((A,B),C);").unwrap());
+    let chain_2 = TreeSample::from(parse_newick_str("((D,C),A);((A,D),C);").unwrap());
+
+    let mapping = chain_1.merge(chain_2);
+    assert_eq!(chain_1.len(), 3);
+    assert_eq!(chain_1.labels().labels(), &vec!["A".to_string(), "B".to_string(), "C".to_string(), "D".to_string()]);
+    assert_eq!(mapping.get(0), Some(3));
+    assert_eq!(mapping.get(1), Some(2));
+    assert_eq!(mapping.get(2), Some(0));
+    assert_eq!(mapping.get(3), None);
+    assert_eq!(mapping.num_labels(), 4);
+
+    assert_eq!(to_newick(&chain_1[1], chain_1.labels()), "((D,C),A);");
+    assert_eq!(to_newick(&chain_1[2], chain_1.labels()), "((A,D),C);");
+    assert!(chain_1.iter().all(|tree| tree.num_leaves_init() == 4));
+    assert!(TreeSample::new(chain_1.trees().to_vec(), chain_1.labels().clone()).is_ok());
+}
+
+#[test]
+fn test_remap_labels_between_maps() {
+    let (_, mut labels) = parse_newick_str("(A,B);").unwrap();
+    let (mut trees, other_labels) = parse_newick_str("(B,A);").unwrap();
+
+    let mapping = LabelMapping::new(&other_labels, &mut labels);
+    assert_eq!(labels.num_labels(), 2);
+    remap_labels(&mut trees[0], &mapping);
+    assert_eq!(to_newick(&trees[0], &labels), "(B,A);");
+    assert_eq!(trees[0].num_leaves_init(), 2);
+}
+
+#[test]
+fn test_rename_labels_of_all_trees() {
+    let mut sample = sample("((AB1:1,AB2:1):1,AB3:2);\n((AB1:1,AB3:1):1,AB2:2);\n");
+    let mut renaming = LabelRenaming::new();
+    renaming.insert("AB1", "Kea");
+    renaming.insert("AB2", "Kaka");
+    renaming.insert("ZZ9", "Kakapo");
+
+    assert_eq!(sample.rename_labels(&renaming), Ok(2));
+    assert_eq!(to_newick(&sample[0], sample.labels()), "((Kea:1,Kaka:1):1,AB3:2);");
+    assert_eq!(to_newick(&sample[1], sample.labels()), "((Kea:1,AB3:1):1,Kaka:2);");
+
+    renaming.insert("AB3", "Kea");
+    assert_eq!(sample.rename_labels(&renaming), Err(RenameError::DuplicateLabel("Kea".to_string())));
+    assert_eq!(sample.labels().get_label(2), Some("AB3"));
+}
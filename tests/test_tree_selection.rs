@@ -0,0 +1,88 @@
+use nexus_parser::io::parser::nexus::NexusParserBuilder;
+use nexus_parser::model::tree::Tree;
+use nexus_parser::model::tree_sample::TreeSample;
+use nexus_parser::model::tree_selection::{find_tree, select_trees};
+use nexus_parser::parse_newick_str;
+
+fn parse_sample() -> Vec<Tree> {
+    let nexus = b"#NEXUS\nBegin trees;\n\
+        tree STATE_0 = ((A,B),C);\n\
+        tree STATE_500 = ((A,C),B);\n\
+        tree STATE_1000 = ((B,C),A);\n\
+        tree MAP = ((A,B),C);\n\
+        End;\n";
+    NexusParserBuilder::for_buffer(nexus.as_slice())
+        .build().unwrap()
+        .into_results().unwrap().0
+}
+
+fn names(trees: &[&Tree]) -> Vec<String> {
+    trees.iter().map(|tree| tree.name().unwrap().clone()).collect()
+}
+
+#[test]
+fn test_find_tree() {
+    let trees = parse_sample();
+
+    assert_eq!(find_tree(&trees, "MAP").unwrap().name().unwrap(), "MAP");
+    assert!(std::ptr::eq(find_tree(&trees, "STATE_500").unwrap(), &trees[1]));
+    assert!(find_tree(&trees, "STATE_5").is_none());
+    assert!(find_tree(&[Tree::new(2)], "").is_none());
+}
+
+#[test]
+fn test_select_trees() {
+    let trees = parse_sample();
+
+    let states = select_trees(&trees, |name| name.starts_with("STATE_"));
+    assert_eq!(names(&states), vec!["STATE_0", "STATE_500", "STATE_1000"]);
+    assert!(select_trees(&trees, |_| false).is_empty());
+}
+
+#[cfg(feature = "regex")]
+#[test]
+fn test_select_trees_matching() {
+    use nexus_parser::model::tree_selection::select_trees_matching;
+    use regex::Regex;
+
+    let trees = parse_sample();
+
+    let selected = select_trees_matching(&trees, &Regex::new(r"^STATE_\d*[1-9]\d*$").unwrap());
+    assert_eq!(names(&selected), vec!["STATE_500", "STATE_1000"]);
+    let selected = select_trees_matching(&trees, &Regex::new("A").unwrap());
+    assert_eq!(names(&selected), vec!["STATE_0", "STATE_500", "STATE_1000", "MAP"]);
+}
+
+#[test]
+fn test_leaves_where_and_select_taxa() {
+    // Taxon D only occurs in the second tree
+    let sample = TreeSample::from(parse_newick_str("((A|NZL,B|AUS),C|NZL);\n((A|NZL,D|NZL),(C|NZL,B|AUS));\n").unwrap());
+    let is_nz = |label: &str| label.ends_with("|NZL");
+
+    assert_eq!(sample[0].leaves_where(sample.labels(), is_nz), vec![0, 2]);
+    assert_eq!(sample[1].leaves_where(sample.labels(), is_nz), vec![0, 2, 3]);
+    assert_eq!(sample.select_taxa(is_nz), vec![0, 2, 3]);
+    assert!(sample.select_taxa(|_| false).is_empty());
+}
+
+#[cfg(feature = "regex")]
+#[test]
+fn test_taxa_matching_feed_clade_queries() {
+    use nexus_parser::io::writer::newick::to_newick;
+    use nexus_parser::model::taxon_set::TaxonSet;
+    use regex::Regex;
+
+    let newick = "(((EPI_1|B.1.1.7|GBR,EPI_2|B.1.1.7|USA),EPI_3|B.1.351|ZAF),(EPI_4|P.1|BRA,EPI_5|B.1.1.7|BRA));\n";
+    let sample = TreeSample::from(parse_newick_str(newick).unwrap());
+    let tree = &sample[0];
+
+    let alpha = tree.leaves_matching(sample.labels(), &Regex::new(r"\|B\.1\.1\.7\|").unwrap());
+    assert_eq!(alpha, vec![0, 1, 4]);
+    assert!(!tree.is_monophyletic(&TaxonSet::from_indices(sample.labels().num_labels(), alpha.iter().copied())));
+    assert_eq!(tree.mrca(&alpha), tree.root().index());
+
+    let brazil = sample.select_taxa_matching(&Regex::new(r"\|BRA$").unwrap());
+    assert_eq!(brazil, vec![3, 4]);
+    let pruned = tree.prune_taxa(&brazil);
+    assert_eq!(to_newick(&pruned, sample.labels()), "((EPI_1|B.1.1.7|GBR,EPI_2|B.1.1.7|USA),EPI_3|B.1.351|ZAF);");
+}
@@ -0,0 +1,66 @@
+use nexus_parser::parse_newick_str;
+use nexus_parser::stats::tree_shape::ShapeSummary;
+
+#[test]
+fn test_gamma_statistic() {
+    // Balanced tree with branchings at 2 and 1: g_2 = 1, g_3 = 0, g_4 = 1
+    let (trees, _) = parse_newick_str("((A:1,B:1):1,(C:1,D:1):1);").unwrap();
+    let gamma = trees[0].gamma_statistic().unwrap();
+    assert!((gamma + 1.0 / 1.5f64.sqrt()).abs() < 1e-12);
+
+    // Branchings close to the present give a positive gamma
+    let (trees, _) = parse_newick_str("(((A:0.1,B:0.1):0.1,C:0.2):2.8,D:3);").unwrap();
+    assert!(trees[0].gamma_statistic().unwrap() > 0.0);
+
+    // Branchings close to the root give a negative gamma
+    let (trees, _) = parse_newick_str("(((A:2.8,B:2.8):0.1,C:2.9):0.1,D:3);").unwrap();
+    assert!(trees[0].gamma_statistic().unwrap() < 0.0);
+}
+
+#[test]
+fn test_gamma_statistic_undefined() {
+    // Not ultrametric, missing branch lengths, not binary, too small
+    let (trees, _) = parse_newick_str("((A:1,B:2):1,C:2);\n((A,B),C);\n((A:1,B:1,C:1):1,D:2);\n(A:1,B:1);\n").unwrap();
+    assert!(trees.iter().all(|tree| tree.gamma_statistic().is_none()));
+}
+
+#[test]
+fn test_imbalance_indices() {
+    let (trees, _) = parse_newick_str("((((A,B),C),D),E);\n(((A,B),C),(D,E));\n((A,B),(C,D),E);\n").unwrap();
+
+    // Caterpillar on 5 leaves: (n - 1)(n - 2) / 2
+    assert_eq!(trees[0].colless_index(), Some(6));
+    assert_eq!(trees[0].sackin_index(), 4 + 4 + 3 + 2 + 1);
+    assert_eq!(trees[0].num_cherries(), 1);
+
+    // Imbalance 1 at the root and at the parent of the cherry (A,B)
+    assert_eq!(trees[1].colless_index(), Some(2));
+    assert_eq!(trees[1].sackin_index(), 3 + 3 + 2 + 2 + 2);
+    assert_eq!(trees[1].num_cherries(), 2);
+
+    // Colless is only defined for binary trees
+    assert_eq!(trees[2].colless_index(), None);
+    assert_eq!(trees[2].sackin_index(), 2 + 2 + 2 + 2 + 1);
+    assert_eq!(trees[2].num_cherries(), 2);
+}
+
+#[test]
+fn test_shape_summary() {
+    let (trees, _) = parse_newick_str("((A:1,B:1):1,(C:1,D:1):1);\n(((A,B),C),D);\n((A,B,C),D);\n").unwrap();
+    let summary = ShapeSummary::new(&trees);
+
+    assert_eq!(summary.num_trees(), 3);
+    assert_eq!(summary.gammas().len(), 1);
+    assert_eq!(summary.colless(), &[0, 3]);
+    assert_eq!(summary.sackin(), &[8, 9, 7]);
+    assert_eq!(summary.cherries(), &[2, 1, 0]);
+
+    let colless = summary.colless_stats().unwrap();
+    assert_eq!((colless.count, colless.min, colless.max, colless.mean), (2, 0.0, 3.0, 1.5));
+    assert_eq!(summary.cherry_stats().unwrap().median, 1.0);
+    assert_eq!(summary.gamma_stats().unwrap().count, 1);
+
+    let empty = ShapeSummary::new(&[]);
+    assert_eq!(empty.sackin_stats(), None);
+    assert_eq!(empty.gamma_stats(), None);
+}
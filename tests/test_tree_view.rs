@@ -0,0 +1,100 @@
+mod common;
+use common::parse_trees;
+
+use nexus_parser::algorithms::consensus::majority_rule;
+use nexus_parser::algorithms::robinson_foulds::robinson_foulds;
+use nexus_parser::algorithms::tree_view::{TreeView, TreeViewError};
+use nexus_parser::io::writer::newick::NewickStyle;
+
+#[test]
+fn test_robinson_foulds_by_rooting() {
+    let trees = parse_trees(&["((A,B),(C,D));", "(A,(B,(C,D)));"], 4);
+
+    let unrooted = TreeView::Unrooted(&trees[0]).robinson_foulds(&TreeView::Unrooted(&trees[1])).unwrap();
+    assert_eq!(unrooted, robinson_foulds(&trees[0], &trees[1]));
+    assert_eq!(unrooted, 0);
+
+    // Clades AB, CD vs. BCD, CD
+    let rooted = TreeView::Rooted(&trees[0]).robinson_foulds(&TreeView::Rooted(&trees[1])).unwrap();
+    assert_eq!(rooted, 2);
+    let normalized = TreeView::Rooted(&trees[0]).normalized_robinson_foulds(&TreeView::Rooted(&trees[1])).unwrap();
+    assert_eq!(normalized, 0.5);
+}
+
+#[test]
+fn test_mixed_rooting_is_error() {
+    let trees = parse_trees(&["((A,B),(C,D));", "(A,(B,(C,D)));"], 4);
+    let views = [TreeView::Rooted(&trees[0]), TreeView::Unrooted(&trees[1])];
+
+    assert_eq!(views[0].robinson_foulds(&views[1]), Err(TreeViewError::MixedRooting));
+    assert_eq!(views[1].is_compatible_with(&views[0]), Err(TreeViewError::MixedRooting));
+    assert_eq!(TreeView::strict(&views).unwrap_err(), TreeViewError::MixedRooting);
+    assert!(TreeView::filter_compatible(&views[..1], &views[1]).is_err());
+}
+
+#[test]
+fn test_different_num_leaves_is_error() {
+    let small = parse_trees(&["((A,B),C);"], 3);
+    let large = parse_trees(&["((A,B),(C,D));"], 4);
+
+    let result = TreeView::Unrooted(&small[0]).robinson_foulds(&TreeView::Unrooted(&large[0]));
+    assert_eq!(result, Err(TreeViewError::DifferentNumLeaves { expected: 3, found: 4 }));
+}
+
+#[test]
+fn test_empty_sample_is_error() {
+    assert_eq!(TreeView::majority_rule(&[], 0.5).unwrap_err(), TreeViewError::EmptySample);
+}
+
+#[test]
+fn test_rooted_consensus_matches_plain_consensus() {
+    let trees = parse_trees(&["(((A,B),C),D);", "(((A,B),D),C);", "((A,(B,C)),D);"], 4);
+
+    let view_consensus = TreeView::majority_rule(&TreeView::rooted_all(&trees), 0.5).unwrap();
+    assert_eq!(
+        view_consensus.to_newick(&NewickStyle::ZeroIndexed, None),
+        majority_rule(&trees, 0.5).to_newick(&NewickStyle::ZeroIndexed, None)
+    );
+}
+
+#[test]
+fn test_unrooted_consensus_ignores_rooting() {
+    // Same unrooted tree AB|CDE, DE|ABC with three different roots
+    let trees = parse_trees(&["((A,B),(C,(D,E)));", "(A,(B,(C,(D,E))));", "(((A,B),C),(D,E));"], 5);
+
+    // Rooted, only clade DE is in all trees
+    let rooted = TreeView::strict(&TreeView::rooted_all(&trees)).unwrap();
+    assert_eq!(rooted.to_newick(&NewickStyle::ZeroIndexed, None), "(0,1,2,(3,4)1);");
+
+    let unrooted = TreeView::strict(&TreeView::unrooted_all(&trees)).unwrap();
+    assert_eq!(unrooted.to_newick(&NewickStyle::ZeroIndexed, None), "(0,1,(2,(3,4)1)1);");
+}
+
+#[test]
+fn test_rooted_compatibility() {
+    let trees = parse_trees(&["((A,B),(C,D));", "(A,(B,(C,D)));", "((A,C),(B,D));"], 4);
+    let backbone = parse_trees(&["((A,B),C,D);"], 4);
+
+    let (compatible, mass) = TreeView::filter_compatible(&TreeView::rooted_all(&trees), &TreeView::Rooted(&backbone[0])).unwrap();
+    assert_eq!(compatible.len(), 1);
+    assert!((mass - 1.0 / 3.0).abs() < 1e-12);
+
+    // Unrooted, the second tree also contains split AB|CD
+    let (compatible, _) = TreeView::filter_compatible(&TreeView::unrooted_all(&trees), &TreeView::Unrooted(&backbone[0])).unwrap();
+    assert_eq!(compatible.len(), 2);
+}
+
+#[test]
+fn test_view_of_marked_trees() {
+    let mut trees = parse_trees(&["((A,B),(C,D));", "(A,(B,(C,D)));"], 4);
+    assert!(TreeView::of(&trees[0]).is_rooted());
+
+    trees[1].unroot();
+    let views = TreeView::of_all(&trees);
+    assert!(!views[1].is_rooted());
+    assert_eq!(views[0].robinson_foulds(&views[1]), Err(TreeViewError::MixedRooting));
+
+    trees[0].unroot();
+    let views = TreeView::of_all(&trees);
+    assert_eq!(views[0].robinson_foulds(&views[1]), Ok(0));
+}
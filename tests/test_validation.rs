@@ -0,0 +1,74 @@
+mod common;
+use common::parse_tree;
+
+use nexus_parser::model::tree::Tree;
+use nexus_parser::model::validation::ValidationIssue;
+use nexus_parser::model::vertex::BranchLength;
+
+#[test]
+fn test_valid_trees_have_no_issues() {
+    assert!(parse_tree("((A:1,B:1):1,(C:0.5,D:0.5):1.5);", 4).validate().is_empty());
+    assert!(parse_tree("((A,B),(C,D));", 4).validate().is_empty());
+}
+
+#[test]
+fn test_validate_reports_non_ultrametric_leaves() {
+    let tree = parse_tree("((A:1,B:1):1,(C:0.5,D:0.25):1.5);", 4);
+    let issues = tree.validate();
+    assert_eq!(issues.len(), 1);
+    match &issues[0] {
+        ValidationIssue::NonUltrametric { leaves, max_deviation } => {
+            assert_eq!(leaves.len(), 1);
+            assert_eq!(tree[leaves[0]].label_index(), Some(3));
+            assert_eq!(*max_deviation, 0.25);
+        }
+        issue => panic!("Unexpected issue {}", issue),
+    }
+    assert!(!issues[0].is_error());
+    assert!(tree.is_valid());
+}
+
+#[test]
+fn test_validate_reports_missing_and_negative_branch_lengths() {
+    let mut tree = parse_tree("((A:1,B):1,C:2);", 3);
+    let issues = tree.validate();
+    assert_eq!(issues.len(), 1);
+    assert!(matches!(issues[0], ValidationIssue::MissingBranchLength { vertex } if tree[vertex].label_index() == Some(1)));
+
+    // Negative zero, e.g. from scaling a zero length by a negative factor
+    let c = tree.post_order_iter().find(|v| v.label_index() == Some(2)).unwrap().index();
+    tree.vertex_mut(c).set_branch_length(Some(BranchLength::new(-0.0)));
+    let issues = tree.validate();
+    assert!(issues.contains(&ValidationIssue::NegativeBranchLength { vertex: c, length: -0.0 }));
+    assert!(issues.iter().any(|issue| issue.is_error()));
+}
+
+#[test]
+fn test_validate_reports_duplicate_labels() {
+    let mut tree = Tree::new(3);
+    let a = tree.add_leaf(None, 0);
+    let b = tree.add_leaf(None, 2);
+    let c = tree.add_leaf(None, 2);
+    let bc = tree.add_internal_vertex(vec![b, c], None);
+    tree.add_root(vec![a, bc]);
+
+    assert_eq!(tree.validate(), vec![ValidationIssue::DuplicateLeafLabel { label_index: 2, leaves: vec![b, c] }]);
+}
+
+#[test]
+fn test_validate_reports_dangling_references_and_cycles() {
+    let mut tree = parse_tree("((A:1,B:1):1,C:2);", 3);
+    let a = tree.post_order_iter().find(|v| v.label_index() == Some(0)).unwrap().index();
+    let ab = tree[a].parent_index().unwrap();
+
+    // Parent of the internal vertex set to one of its children
+    tree.vertex_mut(ab).set_parent(a);
+    let issues = tree.validate();
+    assert!(!tree.is_valid());
+    assert!(issues.contains(&ValidationIssue::DanglingParent { vertex: ab, parent: a }));
+    assert!(issues.contains(&ValidationIssue::DanglingChild { vertex: tree.root().index(), child: ab }));
+    assert!(issues.iter().any(|issue| matches!(issue, ValidationIssue::Cycle { vertex } if *vertex == a || *vertex == ab)));
+    assert_eq!(issues.iter().filter(|issue| matches!(issue, ValidationIssue::Cycle { .. })).count(), 1);
+
+    assert!(Tree::new(2).validate().contains(&ValidationIssue::MissingRoot));
+}
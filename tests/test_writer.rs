@@ -0,0 +1,279 @@
+mod common;
+use common::{parse_annotated, parse_newick};
+
+use nexus_parser::io::parser::newick::AnnotationMode;
+use nexus_parser::io::parser::nexus::{NexusParserBuilder, TreeReader};
+use nexus_parser::io::writer::newick::{to_newick, BranchLengthFormat, LabelQuoting, NewickStreamWriter, NewickStyle, NewickWriteOptions, SupportFormat};
+use nexus_parser::io::writer::nexus::NexusWriter;
+#[cfg(feature = "fs")]
+use nexus_parser::{convert_nexus_to_newick, parse_newick_file, parse_nexus_file, write_nexus_file};
+use std::io::Cursor;
+#[cfg(feature = "fs")]
+use std::path::Path;
+
+// --- TESTS NEWICK WRITER ---
+
+#[test]
+fn test_newick_round_trip() {
+    let newick = "((A:1,B:2.5):0.125,(C:0.009529961339106089,D:1e-7):3,E:4);";
+    let (tree, labels) = parse_newick(newick);
+
+    let written = to_newick(&tree, &labels);
+    let (reparsed, reparsed_labels) = parse_newick(&written);
+
+    assert_eq!(reparsed.num_vertices(), tree.num_vertices());
+    assert_eq!(to_newick(&reparsed, &reparsed_labels), written);
+}
+
+#[test]
+fn test_newick_quotes_labels() {
+    let newick = "(('Great Spotted Kiwi':1,'Wilson''s storm petrel':1)'Clade (A)':1,Kea:2);";
+    let (tree, labels) = parse_newick(newick);
+
+    let written = to_newick(&tree, &labels);
+    assert_eq!(written, newick);
+
+    let (_, reparsed_labels) = parse_newick(&written);
+    assert!(reparsed_labels.contains_label("Great Spotted Kiwi"));
+    assert!(reparsed_labels.contains_label("Wilson's storm petrel"));
+}
+
+#[test]
+fn test_newick_indexed_styles() {
+    let (tree, labels) = parse_newick("((A:1,B:2):1,C:3);");
+
+    assert_eq!(tree.to_newick(&NewickStyle::ZeroIndexed, None), "((0:1,1:2):1,2:3);");
+    assert_eq!(tree.to_newick(&NewickStyle::OneIndexed, None), "((1:1,2:2):1,3:3);");
+    assert_eq!(tree.to_newick(&NewickStyle::Label, None), "");
+    assert_eq!(tree.to_newick(&NewickStyle::Label, Some(&labels)), "((A:1,B:2):1,C:3);");
+}
+
+#[test]
+fn test_newick_write_options_default_matches_to_newick() {
+    let newick = "(('Great Spotted Kiwi':[&rate=0.5]1,Kea:2)'Clade A'[&height=2]:1,(C:0.1,D:0.2)0.95:3);";
+    let (tree, labels) = parse_annotated(newick, AnnotationMode::Raw);
+
+    let written = tree.to_newick_with_options(&NewickWriteOptions::default(), Some(&labels));
+    assert_eq!(written, to_newick(&tree, &labels));
+    assert_eq!(written, newick);
+}
+
+#[test]
+fn test_newick_write_options_branch_lengths() {
+    let (tree, labels) = parse_newick("((A:1,B:0.123456789):0.5,C:1e-7);");
+    let write = |branch_lengths| {
+        let options = NewickWriteOptions { branch_lengths, ..NewickWriteOptions::default() };
+        tree.to_newick_with_options(&options, Some(&labels))
+    };
+
+    assert_eq!(write(BranchLengthFormat::Decimals(3)), "((A:1.000,B:0.123):0.500,C:0.000);");
+    assert_eq!(write(BranchLengthFormat::Scientific(2)), "((A:1.00e0,B:1.23e-1):5.00e-1,C:1.00e-7);");
+    assert_eq!(write(BranchLengthFormat::Omit), "((A,B),C);");
+
+    // Scientific notation parses back
+    let (reparsed, _) = parse_newick(&write(BranchLengthFormat::Scientific(2)));
+    assert_eq!(reparsed.num_vertices(), tree.num_vertices());
+}
+
+#[test]
+fn test_newick_write_options_labels_and_support() {
+    let (tree, labels) = parse_annotated("((A:1,B:1)95[&height=1]:1,(C:1,D:1)clade:1);", AnnotationMode::Raw);
+
+    // Support values kept as labels, other internal labels and annotations dropped
+    let options = NewickWriteOptions { internal_labels: false, annotations: false, ..NewickWriteOptions::default() };
+    assert_eq!(tree.to_newick_with_options(&options, Some(&labels)), "((A:1,B:1)95:1,(C:1,D:1):1);");
+
+    // Support values as comment entry, merged with annotations
+    let options = NewickWriteOptions { support: SupportFormat::Comment("posterior".to_string()), ..NewickWriteOptions::default() };
+    assert_eq!(
+        tree.to_newick_with_options(&options, Some(&labels)),
+        "((A:1,B:1)[&height=1,posterior=95]:1,(C:1,D:1)clade:1);"
+    );
+    let options = NewickWriteOptions { annotations: false, ..options };
+    assert_eq!(
+        tree.to_newick_with_options(&options, Some(&labels)),
+        "((A:1,B:1)[&posterior=95]:1,(C:1,D:1)clade:1);"
+    );
+}
+
+#[test]
+fn test_newick_write_options_quoting() {
+    let (tree, labels) = parse_newick("(('Homo sapiens','Pan (chimp)'),Gorilla);");
+    let write = |quoting| {
+        let options = NewickWriteOptions { quoting, ..NewickWriteOptions::default() };
+        tree.to_newick_with_options(&options, Some(&labels))
+    };
+
+    assert_eq!(write(LabelQuoting::Needed), "(('Homo sapiens','Pan (chimp)'),Gorilla);");
+    assert_eq!(write(LabelQuoting::Always), "(('Homo sapiens','Pan (chimp)'),'Gorilla');");
+    assert_eq!(write(LabelQuoting::Underscores), "((Homo_sapiens,'Pan (chimp)'),Gorilla);");
+}
+
+// --- TESTS NEXUS WRITER ---
+
+#[test]
+fn test_nexus_writer_with_translate() {
+    let (tree, labels) = parse_newick("((A:1,'B b':2):1,C:3);");
+
+    let mut writer = NexusWriter::new(Vec::new());
+    writer.write_nexus(&[tree], &labels).unwrap();
+    let output = String::from_utf8(writer.into_inner().unwrap()).unwrap();
+
+    assert!(output.starts_with("#NEXUS\n"));
+    assert!(output.contains("Begin taxa;\n\tDimensions ntax=3;\n\tTaxlabels\n\t\tA\n\t\t'B b'\n\t\tC\n\t\t;\nEnd;\n"));
+    assert!(output.contains("Begin trees;\n\tTranslate\n\t\t1 A,\n\t\t2 'B b',\n\t\t3 C\n\t\t;\n"));
+    assert!(output.contains("\ttree tree_0 = ((1:1,2:2):1,3:3);\nEnd;\n"));
+}
+
+#[test]
+fn test_nexus_writer_without_translate() {
+    let (tree, labels) = parse_newick("((A:1,'B b':2):1,C:3);");
+
+    let mut writer = NexusWriter::new(Vec::new()).with_translate(false);
+    writer.write_nexus(&[tree.clone(), tree], &labels).unwrap();
+    let output = String::from_utf8(writer.into_inner().unwrap()).unwrap();
+
+    assert!(!output.contains("Translate"));
+    assert!(output.contains("\ttree tree_0 = ((A:1,'B b':2):1,C:3);\n"));
+    assert!(output.contains("\ttree tree_1 = ((A:1,'B b':2):1,C:3);\n"));
+}
+
+#[test]
+fn test_nexus_writer_with_newick_options() {
+    let (tree, labels) = parse_newick("((A:1,'B b':2.25)90:1,C:3);");
+    let options = NewickWriteOptions {
+        style: NewickStyle::ZeroIndexed,
+        branch_lengths: BranchLengthFormat::Decimals(1),
+        support: SupportFormat::Comment("support".to_string()),
+        ..NewickWriteOptions::default()
+    };
+
+    // Style follows the translate table
+    let mut writer = NexusWriter::new(Vec::new()).with_newick_options(options);
+    writer.write_nexus(&[tree], &labels).unwrap();
+    let output = String::from_utf8(writer.into_inner().unwrap()).unwrap();
+    assert!(output.contains("\ttree tree_0 = ((1:1.0,2:2.2)[&support=90]:1.0,3:3.0);\n"));
+}
+
+#[test]
+fn test_nexus_writer_keeps_figtree_block() {
+    let nexus = b"#NEXUS\nBegin trees;\n\ttree t = ((A:1,B:2):1,C:3);\nEnd;\n\
+                  begin figtree;\n\tset appearance.branchLineWidth=2.0;\n\tset tipLabels.fontSize=14;\nend;\n";
+    let document = NexusParserBuilder::for_buffer(nexus.as_slice())
+        .with_raw_blocks()
+        .build().unwrap()
+        .into_document().unwrap();
+    let figtree = document.block("FIGTREE").unwrap().clone();
+
+    let mut writer = NexusWriter::new(Vec::new()).with_raw_block(figtree.clone());
+    writer.write_nexus(&document.trees, &document.taxa).unwrap();
+    let output = writer.into_inner().unwrap();
+
+    // Written after the trees, and retained again when parsing the output
+    let reparsed = NexusParserBuilder::for_buffer(output.as_slice())
+        .with_raw_blocks()
+        .build().unwrap()
+        .into_document().unwrap();
+    assert_eq!(reparsed.trees.len(), 1);
+    assert_eq!(reparsed.blocks.len(), 1);
+    assert_eq!(reparsed.block("figtree").unwrap().content, figtree.content);
+}
+
+#[cfg(feature = "fs")]
+#[test]
+fn test_nexus_file_round_trip() {
+    let path = std::env::temp_dir().join("nexus_parser_test_writer_round_trip.trees");
+    let path = path.to_str().unwrap();
+
+    let (trees, labels) = parse_nexus_file("tests/fixtures/nexus_t11_n20_translate.trees").unwrap();
+    write_nexus_file(path, &trees, &labels).unwrap();
+    let (reparsed_trees, reparsed_labels) = parse_nexus_file(path).unwrap();
+    std::fs::remove_file(path).unwrap();
+
+    assert_eq!(reparsed_labels.labels(), labels.labels());
+    assert_eq!(reparsed_trees.len(), trees.len());
+    for (tree, reparsed) in trees.iter().zip(&reparsed_trees) {
+        assert_eq!(to_newick(reparsed, &reparsed_labels), to_newick(tree, &labels));
+    }
+}
+
+#[test]
+fn test_newick_stream_writer_from_reader() {
+    let nexus = "#NEXUS
+Begin trees;
+\ttree t1 = ((A:1,B:1):1,C:2);
+\ttree t2 = ((A:1,C:1):1,B:2);
+\ttree t3 = ((B:1,C:1):1,'D d':2);
+End;
+";
+    let mut reader = TreeReader::new(Cursor::new(nexus)).unwrap();
+    let mut writer = NewickStreamWriter::new(Vec::new());
+
+    // Labels of the reader grow while reading (no TAXA block), so D is only known for the third tree
+    assert_eq!(writer.write_from_reader(&mut reader, 1).unwrap(), 3);
+    assert_eq!(writer.num_written(), 3);
+    let output = String::from_utf8(writer.finish().unwrap()).unwrap();
+    assert_eq!(output, "((A:1,B:1):1,C:2);\n((A:1,C:1):1,B:2);\n((B:1,C:1):1,'D d':2);\n");
+}
+
+#[test]
+fn test_newick_stream_writer_thinning_and_style() {
+    let nexus = "#NEXUS\nBegin trees;\n\ttree t1 = ((A,B),C);\n\ttree t2 = ((A,C),B);\n\ttree t3 = ((B,C),A);\nEnd;\n";
+    let mut reader = TreeReader::new(Cursor::new(nexus)).unwrap();
+    let mut writer = NewickStreamWriter::new(Vec::new()).with_style(NewickStyle::ZeroIndexed);
+
+    assert_eq!(writer.write_from_reader(&mut reader, 2).unwrap(), 2);
+    let output = String::from_utf8(writer.finish().unwrap()).unwrap();
+    assert_eq!(output, "((0,1),2);\n((1,2),0);\n");
+}
+
+#[cfg(feature = "fs")]
+#[test]
+fn test_convert_nexus_to_newick() {
+    let input = Path::new("tests").join("fixtures").join("nexus_t11_n20_translate.trees");
+    let output = std::env::temp_dir().join(format!("nexus_parser_convert_{}.nwk", std::process::id()));
+
+    let num_written = convert_nexus_to_newick(input.to_str().unwrap(), output.to_str().unwrap(), 1).unwrap();
+    let (trees, labels) = parse_newick_file(output.to_str().unwrap()).unwrap();
+    let (expected_trees, expected_labels) = parse_nexus_file(input.to_str().unwrap()).unwrap();
+
+    assert_eq!(num_written, expected_trees.len());
+    assert_eq!(trees.len(), expected_trees.len());
+    assert_eq!(
+        to_newick(&trees[5], &labels),
+        to_newick(&expected_trees[5], &expected_labels)
+    );
+    std::fs::remove_file(output).unwrap();
+}
+
+#[test]
+fn test_nexus_writer_marks_unrooted_trees() {
+    let (tree, labels) = parse_newick("((A:1,B:2):1,C:3);");
+    let unrooted = tree.clone().with_rooted(false);
+
+    let mut writer = NexusWriter::new(Vec::new()).with_translate(false);
+    writer.write_nexus(&[tree, unrooted], &labels).unwrap();
+    let output = String::from_utf8(writer.into_inner().unwrap()).unwrap();
+
+    assert!(output.contains("\ttree tree_0 = ((A:1,B:2):1,C:3);\n"));
+    assert!(output.contains("\ttree tree_1 = [&U] ((A:1,B:2):1,C:3);\n"));
+
+    let (trees, _) = NexusParserBuilder::for_buffer(output.as_bytes())
+        .build().unwrap()
+        .into_results().unwrap();
+    assert!(trees[0].is_rooted());
+    assert!(!trees[1].is_rooted());
+}
+
+#[test]
+fn test_nexus_writer_keeps_tree_metadata() {
+    let nexus = b"#NEXUS\nBegin trees;\n\ttree STATE_1000 [&lnP=-12.5,posterior=-20] = [&U] ((A:1,B:2):1,C:3);\nEnd;\n";
+    let (trees, labels) = NexusParserBuilder::for_buffer(nexus.as_slice())
+        .build().unwrap()
+        .into_results().unwrap();
+
+    let mut writer = NexusWriter::new(Vec::new()).with_translate(false);
+    writer.write_nexus(&trees, &labels).unwrap();
+    let output = String::from_utf8(writer.into_inner().unwrap()).unwrap();
+    assert!(output.contains("\ttree STATE_1000 [&lnP=-12.5,posterior=-20] = [&U] ((A:1,B:2):1,C:3);\n"));
+}
@@ -1,7 +1,9 @@
 use nexus_parser::io::writer::newick::NewickStyle;
+use nexus_parser::model::annotation::Annotation;
 use nexus_parser::model::leaf_label_map::LeafLabelMap;
+use nexus_parser::model::taxon_set::TaxonSet;
 use nexus_parser::model::tree::Tree;
-use nexus_parser::model::vertex::BranchLength;
+use nexus_parser::model::vertex::{BranchLength, Vertex};
 
 #[test]
 fn test_building_tree() {
@@ -9,8 +11,8 @@ fn test_building_tree() {
     let index_l1 = tree.add_leaf(Some(BranchLength::new(1.0)), 0);
     let index_l2 = tree.add_leaf(Some(BranchLength::new(1.0)), 1);
     let index_l3 = tree.add_leaf(Some(BranchLength::new(0.5)), 2);
-    let index_i1 = tree.add_internal_vertex((index_l1, index_l2), Some(BranchLength::new(1.5)));
-    let index_root = tree.add_root((index_l3, index_i1));
+    let index_i1 = tree.add_internal_vertex(vec![index_l1, index_l2], Some(BranchLength::new(1.5)));
+    let index_root = tree.add_root(vec![index_l3, index_i1]);
 
     // Counts
     assert_eq!(tree.num_leaves(), 3);
@@ -36,6 +38,23 @@ fn test_building_tree() {
     assert_eq!(inti.branch_length().unwrap(), BranchLength::new(1.5));
 }
 
+#[test]
+fn test_building_multifurcating_tree() {
+    let mut tree = Tree::new(4);
+    let a = tree.add_leaf(Some(BranchLength::new(1.0)), 0);
+    let b = tree.add_leaf(Some(BranchLength::new(1.0)), 1);
+    let c = tree.add_leaf(Some(BranchLength::new(1.0)), 2);
+    let d = tree.add_leaf(Some(BranchLength::new(2.0)), 3);
+    let internal = tree.add_internal_vertex(vec![a, b, c], Some(BranchLength::new(1.0)));
+    tree.add_root(vec![internal, d]);
+
+    assert!(tree.is_valid());
+    assert!(!tree.is_binary());
+    assert_eq!(tree[internal].children(), Some(&[a, b, c][..]));
+    assert_eq!(tree.to_newick(&NewickStyle::ZeroIndexed, None), "((0:1,1:1,2:1):1,3:2);");
+    assert_eq!(tree.post_order_iter().map(|v| v.index()).collect::<Vec<_>>(), vec![a, b, c, internal, d, 5]);
+}
+
 #[test]
 #[should_panic]
 fn test_get_root_panics_on_empty_tree() {
@@ -59,8 +78,8 @@ fn test_post_order_iter_visits_children_before_parents() {
     let a = tree.add_leaf(Some(BranchLength::new(1.0)), 0);
     let b = tree.add_leaf(Some(BranchLength::new(1.0)), 1);
     let c = tree.add_leaf(Some(BranchLength::new(1.5)), 2);
-    let internal = tree.add_internal_vertex((a, b), Some(BranchLength::new(0.5)));
-    let root = tree.add_root((internal, c));
+    let internal = tree.add_internal_vertex(vec![a, b], Some(BranchLength::new(0.5)));
+    let root = tree.add_root(vec![internal, c]);
 
     let visited: Vec<_> = tree.post_order_iter()
         .map(|v| v.index())
@@ -92,8 +111,8 @@ fn test_pre_order_iter_visits_parents_before_children() {
     let a = tree.add_leaf(Some(BranchLength::new(1.0)), 0);
     let b = tree.add_leaf(Some(BranchLength::new(1.0)), 1);
     let c = tree.add_leaf(Some(BranchLength::new(1.5)), 2);
-    let internal = tree.add_internal_vertex((a, b), Some(BranchLength::new(0.5)));
-    let root = tree.add_root((internal, c));
+    let internal = tree.add_internal_vertex(vec![a, b], Some(BranchLength::new(0.5)));
+    let root = tree.add_root(vec![internal, c]);
 
     let visited: Vec<_> = tree.pre_order_iter()
         .map(|v| v.index())
@@ -140,8 +159,8 @@ fn test_to_newick_with_labels() {
     let a = tree.add_leaf(Some(BranchLength::new(1.0)), labels.get_or_insert("A"));
     let b = tree.add_leaf(Some(BranchLength::new(1.0)), labels.get_or_insert("B"));
     let c = tree.add_leaf(Some(BranchLength::new(2.0)), labels.get_or_insert("C"));
-    let internal = tree.add_internal_vertex((a, b), Some(BranchLength::new(0.5)));
-    tree.add_root((internal, c));
+    let internal = tree.add_internal_vertex(vec![a, b], Some(BranchLength::new(0.5)));
+    tree.add_root(vec![internal, c]);
 
     let newick = tree.to_newick(&NewickStyle::Label, Some(&labels));
     assert_eq!(newick, "((A:1,B:1):0.5,C:2);");
@@ -152,7 +171,7 @@ fn test_to_newick_zero_indexed() {
     let mut tree = Tree::new(2);
     let a = tree.add_leaf(Some(BranchLength::new(1.0)), 0);
     let b = tree.add_leaf(Some(BranchLength::new(2.0)), 1);
-    tree.add_root((a, b));
+    tree.add_root(vec![a, b]);
 
     let newick = tree.to_newick(&NewickStyle::ZeroIndexed, None);
     assert_eq!(newick, "(0:1,1:2);");
@@ -163,8 +182,175 @@ fn test_to_newick_one_indexed() {
     let mut tree = Tree::new(2);
     let a = tree.add_leaf(Some(BranchLength::new(1.5)), 0);
     let b = tree.add_leaf(Some(BranchLength::new(2.5)), 1);
-    tree.add_root((a, b));
+    tree.add_root(vec![a, b]);
 
     let newick = tree.to_newick(&NewickStyle::OneIndexed, None);
     assert_eq!(newick, "(1:1.5,2:2.5);");
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_clades_and_monophyly() {
+    // ((0,1),2,(3,4))
+    let mut tree = Tree::new(5);
+    let l0 = tree.add_leaf(None, 0);
+    let l1 = tree.add_leaf(None, 1);
+    let l2 = tree.add_leaf(None, 2);
+    let l3 = tree.add_leaf(None, 3);
+    let l4 = tree.add_leaf(None, 4);
+    let i01 = tree.add_internal_vertex(vec![l0, l1], None);
+    let i34 = tree.add_internal_vertex(vec![l3, l4], None);
+    let root = tree.add_root(vec![i01, l2, i34]);
+
+    let clades = tree.clades(5);
+    assert_eq!(clades.len(), tree.num_vertices());
+    assert_eq!(clades[l2], TaxonSet::from_indices(5, [2]));
+    assert_eq!(clades[i01], TaxonSet::from_indices(5, [0, 1]));
+    assert_eq!(clades[i34], TaxonSet::from_indices(5, [3, 4]));
+    assert_eq!(clades[root], TaxonSet::full(5));
+
+    assert!(tree.is_monophyletic(&TaxonSet::from_indices(5, [3, 4])));
+    assert!(tree.is_monophyletic(&TaxonSet::from_indices(5, [2])));
+    assert!(!tree.is_monophyletic(&TaxonSet::from_indices(5, [1, 2])));
+    assert!(!tree.is_monophyletic(&TaxonSet::from_indices(5, [0, 1, 2])));
+    assert!(!tree.is_monophyletic(&TaxonSet::new(5)));
+}
+
+#[test]
+fn test_clade_taxa_matches_clades() {
+    let mut tree = Tree::new(4);
+    let a = tree.add_leaf(None, 0);
+    let b = tree.add_leaf(None, 1);
+    let c = tree.add_leaf(None, 2);
+    let d = tree.add_leaf(None, 3);
+    let ab = tree.add_internal_vertex(vec![a, b], None);
+    let abc = tree.add_internal_vertex(vec![ab, c], None);
+    let root = tree.add_root(vec![abc, d]);
+
+    let clades = tree.clades(4);
+    for index in [a, b, c, d, ab, abc, root] {
+        assert_eq!(tree.clade_taxa(index), &clades[index]);
+    }
+    assert_eq!(tree.cached_clades(), clades.as_slice());
+    assert_eq!(tree.clade_taxa(abc), &TaxonSet::from_indices(4, [0, 1, 2]));
+    assert!(tree.is_monophyletic(&TaxonSet::from_indices(4, [0, 1])));
+    assert!(!tree.is_monophyletic(&TaxonSet::from_indices(4, [1, 2])));
+    assert_eq!(tree.leaf_taxa(), clades[root]);
+}
+
+#[cfg(feature = "rand")]
+#[test]
+fn test_clade_cache_invalidated_by_edits() {
+    let mut tree = Tree::new(3);
+    let a = tree.add_leaf(None, 0);
+    let b = tree.add_leaf(None, 1);
+    let c = tree.add_leaf(None, 2);
+    let ab = tree.add_internal_vertex(vec![a, b], None);
+    tree.add_root(vec![ab, c]);
+    assert_eq!(tree.clade_taxa(ab), &TaxonSet::from_indices(3, [0, 1]));
+
+    // ((A,B),C) -> ((C,B),A)
+    tree.nni_move(ab, a, c);
+    assert_eq!(tree.clade_taxa(ab), &TaxonSet::from_indices(3, [1, 2]));
+    assert!(tree.is_monophyletic(&TaxonSet::from_indices(3, [1, 2])));
+
+    // Clones keep a valid cache
+    let clone = tree.clone();
+    assert_eq!(clone.clade_taxa(ab), tree.clade_taxa(ab));
+}
+
+#[test]
+fn test_node_heights() {
+    let mut tree = Tree::new(3);
+    let a = tree.add_leaf(Some(BranchLength::new(1.0)), 0);
+    let b = tree.add_leaf(Some(BranchLength::new(1.0)), 1);
+    let c = tree.add_leaf(Some(BranchLength::new(2.5)), 2);
+    let ab = tree.add_internal_vertex(vec![a, b], Some(BranchLength::new(1.5)));
+    let root = tree.add_root(vec![ab, c]);
+
+    let heights = tree.node_heights().unwrap();
+    assert_eq!(heights, &[0.0, 0.0, 0.0, 1.0, 2.5]);
+    assert_eq!(tree.root_height(), Some(2.5));
+    assert_eq!(tree.node_height(ab), Some(1.0));
+    assert_eq!(tree.root_height(), Some(tree.height()));
+
+    // Rounding noise is tolerated
+    tree.vertex_mut(c).set_branch_length(Some(BranchLength::new(2.5 + 1e-9)));
+    assert!((tree.root_height().unwrap() - 2.5).abs() < 1e-7);
+
+    // Cache is cleared by edits
+    tree.vertex_mut(c).set_branch_length(Some(BranchLength::new(3.0)));
+    assert_eq!(tree.node_heights(), None);
+    assert_eq!(tree.root_height(), None);
+    assert_eq!(tree.node_height(root), None);
+}
+
+#[test]
+fn test_node_heights_without_branch_lengths() {
+    let mut tree = Tree::new(2);
+    let a = tree.add_leaf(Some(BranchLength::new(1.0)), 0);
+    let b = tree.add_leaf(None, 1);
+    tree.add_root(vec![a, b]);
+
+    assert_eq!(tree.node_heights(), None);
+}
+
+// ============= Compaction Tests =============
+
+#[test]
+fn test_compact_removes_detached_subtree() {
+    // ((A,B),(C,D),E) with annotations, then detaching (C,D) from the root
+    let mut tree = Tree::new(5);
+    let a = tree.add_leaf(Some(BranchLength::new(1.0)), 0);
+    let b = tree.add_leaf(Some(BranchLength::new(1.0)), 1);
+    let ab = tree.add_internal_vertex(vec![a, b], Some(BranchLength::new(1.0)));
+    let c = tree.add_leaf(Some(BranchLength::new(1.0)), 2);
+    let d = tree.add_leaf(Some(BranchLength::new(1.0)), 3);
+    let cd = tree.add_internal_vertex(vec![c, d], Some(BranchLength::new(1.0)));
+    let e = tree.add_leaf(Some(BranchLength::new(2.0)), 4);
+    let root = tree.add_root(vec![ab, cd, e]);
+    tree.set_annotation(ab, Some(Annotation::parse("rate=0.5")));
+    tree.set_annotation(cd, Some(Annotation::parse("rate=2")));
+    tree.set_branch_annotation(e, Some(Annotation::parse("color=red")));
+
+    if let Vertex::Root { children, .. } = tree.vertex_mut(root) {
+        children.retain(|&child| child != cd);
+    }
+    assert_eq!(tree.num_leaves(), 5);
+
+    let mapping = tree.compact();
+    assert_eq!(mapping, vec![Some(0), Some(1), Some(2), None, None, None, Some(3), Some(4)]);
+    assert_eq!(tree.num_vertices(), 5);
+    assert_eq!(tree.num_leaves(), 3);
+    assert_eq!(tree.root().index(), 4);
+    assert_eq!(tree[3].parent_index(), Some(4));
+
+    // Annotations moved with their vertices, those of removed vertices dropped
+    assert_eq!(tree.to_newick(&NewickStyle::ZeroIndexed, None), "((0:1,1:1)[&rate=0.5]:1,4:[&color=red]2);");
+    assert_eq!(tree.annotation(2).unwrap().raw(), "rate=0.5");
+    assert_eq!(tree.branch_annotation(3).unwrap().raw(), "color=red");
+}
+
+#[test]
+fn test_compact_without_dead_vertices() {
+    let mut tree = Tree::new(3);
+    let a = tree.add_leaf(None, 0);
+    let b = tree.add_leaf(None, 1);
+    let ab = tree.add_internal_vertex(vec![a, b], None);
+    let c = tree.add_leaf(None, 2);
+    tree.add_root(vec![ab, c]);
+    let newick = tree.to_newick(&NewickStyle::ZeroIndexed, None);
+
+    assert_eq!(tree.compact(), vec![Some(0), Some(1), Some(2), Some(3), Some(4)]);
+    assert_eq!(tree.to_newick(&NewickStyle::ZeroIndexed, None), newick);
+}
+
+#[cfg(debug_assertions)]
+#[test]
+#[should_panic(expected = "stale index")]
+fn test_annotation_of_vertex_outside_arena_panics() {
+    let mut tree = Tree::new(2);
+    let a = tree.add_leaf(None, 0);
+    let b = tree.add_leaf(None, 1);
+    tree.add_root(vec![a, b]);
+    tree.set_annotation(3, Some(Annotation::parse("rate=1")));
+}
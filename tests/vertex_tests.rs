@@ -1,11 +1,26 @@
 #![allow(unused)]
-use nexus_parser::model::vertex::{BranchLength, Vertex};
+// Casts to and from `StoredLength` are only necessary with the `f32-lengths` feature
+#![allow(clippy::unnecessary_cast)]
+mod common;
+use common::approx_eq;
+
+use nexus_parser::model::tree::StoredIndex;
+use nexus_parser::model::vertex::{BranchLength, BranchLengthError, StoredLength, Vertex};
 
 #[test]
 fn test_branch_lengths() {
     let test_length = 1.234;
-    let vertex = Vertex::new_internal(5, (1, 2), Some(BranchLength::new(test_length)));
-    assert_eq!(*vertex.branch_length().unwrap(), test_length);
+    let vertex = Vertex::new_internal(5, vec![1, 2], Some(BranchLength::new(test_length)));
+    assert!(approx_eq(*vertex.branch_length().unwrap(), test_length, 0.0));
+}
+
+#[test]
+fn test_stored_length_saturates() {
+    // Stored as f32 with the `f32-lengths` feature, lengths beyond f32::MAX do not become infinite
+    let mut leaf = Vertex::new_leaf(0, Some(BranchLength::new(1e300)), 0);
+    assert_eq!(*leaf.branch_length().unwrap(), 1e300_f64.min(StoredLength::MAX as f64));
+    leaf.set_branch_length(None);
+    assert!(!leaf.has_branch_length());
 }
 
 #[test]
@@ -14,27 +29,36 @@ fn test_negative_branch_length() {
     let negative_length = BranchLength::new(-1.0);
 }
 
+#[test]
+fn test_try_new_branch_length() {
+    assert_eq!(BranchLength::try_new(0.0).map(|length| *length), Ok(0.0));
+    assert_eq!(BranchLength::try_new(-1e-17), Err(BranchLengthError::Negative(-1e-17)));
+    assert_eq!(BranchLength::try_from(f64::INFINITY), Err(BranchLengthError::NotFinite(f64::INFINITY)));
+    assert!(matches!(BranchLength::try_from(f64::NAN), Err(BranchLengthError::NotFinite(_))));
+    assert_eq!(BranchLengthError::Negative(-2.0).to_string(), "Branch length must be non-negative, got -2");
+}
+
 #[test]
 fn test_is_x() {
     let leaf = Vertex::new_leaf(0, Some(BranchLength::new(0.5)), 10);
     assert!(leaf.is_leaf());
 
-    let vertex = Vertex::new_internal(0, (1, 2), Some(BranchLength::new(0.5)));
+    let vertex = Vertex::new_internal(0, vec![1, 2], Some(BranchLength::new(0.5)));
     assert!(vertex.is_internal());
 
-    let root = Vertex::new_root(2, (42, 42));
+    let root = Vertex::new_root(2, vec![42, 42]);
     assert!(root.is_root());
 }
 
 #[test]
 fn test_internal_vertex_has_no_label() {
-    let vertex = Vertex::new_internal(0, (1, 2), Some(BranchLength::new(0.5)));
+    let vertex = Vertex::new_internal(0, vec![1, 2], Some(BranchLength::new(0.5)));
     assert_eq!(vertex.label_index(), None);
 }
 
 #[test]
 fn test_parent_unset() {
-    let vertex = Vertex::new_internal(0, (1, 2), Some(BranchLength::new(0.5)));
+    let vertex = Vertex::new_internal(0, vec![1, 2], Some(BranchLength::new(0.5)));
     assert_eq!(vertex.parent_index(), None);
     assert!(!vertex.has_parent());
 
@@ -42,7 +66,7 @@ fn test_parent_unset() {
     assert_eq!(leaf.parent_index(), None);
     assert!(!leaf.has_parent());
 
-    let root = Vertex::new_root(2, (42, 42));
+    let root = Vertex::new_root(2, vec![42, 42]);
     assert_eq!(root.parent_index(), None);
 }
 
@@ -53,3 +77,51 @@ fn test_leaf_has_no_children() {
 }
 
 
+
+#[test]
+fn test_node_label_and_support() {
+    let mut vertex = Vertex::new_internal(0, vec![1, 2], None);
+    assert_eq!(vertex.node_label(), None);
+
+    vertex.set_node_label(Some("0.97".to_string()));
+    assert_eq!(vertex.node_label(), Some("0.97"));
+    assert_eq!(vertex.support(), Some(0.97));
+
+    vertex.set_node_label(Some("Ratites".to_string()));
+    assert_eq!(vertex.support(), None);
+}
+
+#[test]
+#[should_panic]
+fn test_set_node_label_on_leaf() {
+    let mut leaf = Vertex::new_leaf(0, None, 0);
+    leaf.set_node_label(Some("Kiwi".to_string()));
+}
+
+/// Layout of [Vertex] with all indices stored as `usize` and branch lengths as `f64`.
+enum WideVertex {
+    Root { index: usize, children: Vec<usize>, node_label: Option<String> },
+    Internal { index: usize, parent: usize, children: Vec<usize>, branch_length: Option<BranchLength>, node_label: Option<String> },
+    Leaf { index: usize, label_index: usize, parent: usize, branch_length: Option<BranchLength> },
+}
+
+#[test]
+fn test_vertex_size() {
+    use std::mem::size_of;
+
+    // Index and parent of internal vertices stored as `u32` save 8 bytes per vertex on 64-bit machines,
+    // branch lengths stored as `f32` (`f32-lengths` feature) another 8 bytes
+    let index_savings = 2 * (size_of::<usize>() - size_of::<StoredIndex>());
+    let length_savings = size_of::<Option<f64>>() - size_of::<Option<StoredLength>>();
+    assert_eq!(size_of::<Vertex>(), size_of::<WideVertex>() - index_savings - length_savings);
+    if size_of::<StoredIndex>() < size_of::<usize>() {
+        assert!(size_of::<Vertex>() < size_of::<WideVertex>());
+    }
+}
+
+#[test]
+fn test_stored_index_round_trip() {
+    let mut leaf = Vertex::new_leaf(7, None, 3);
+    leaf.set_parent(9);
+    assert_eq!((leaf.index(), leaf.label_index(), leaf.parent_index()), (7, Some(3), Some(9)));
+}